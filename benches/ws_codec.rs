@@ -0,0 +1,168 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use serde::Serialize;
+use std::collections::HashMap;
+
+// Kodlama modülü doğrudan derlenir (crate yalnızca binary olarak derlenmektedir)
+#[path = "../src/handlers/ws_codec.rs"]
+#[allow(dead_code)]
+mod ws_codec;
+
+use ws_codec::{EncodedMessage, Encoding, Frame};
+
+// Yayınlanan mesajlar sunucudaki WebSocketMessage ile aynı tip adları ve alanlarla serileştirilir
+#[derive(Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Message {
+    LobbyUpdate {
+        game_code: String,
+        players: Vec<PlayerInfo>,
+    },
+    QuestionStart {
+        question_id: i32,
+        question_text: String,
+        options: HashMap<String, String>,
+        time_limit: Option<i32>,
+        question_number: i64,
+        total_questions: i64,
+        started_at: i64,
+        deadline: i64,
+        server_time: i64,
+        seq: Option<i32>,
+        audio_url: Option<String>,
+        option_labels: Option<String>,
+    },
+    QuestionEnd {
+        question_id: i32,
+        correct_option: String,
+        leaderboard: Vec<LeaderboardEntry>,
+        seq: Option<i32>,
+    },
+}
+
+#[derive(Clone, Serialize)]
+struct PlayerInfo {
+    player_id: i32,
+    nickname: String,
+    is_guest: bool,
+    avatar: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+struct LeaderboardEntry {
+    player_id: i32,
+    nickname: String,
+    score: i32,
+    is_guest: bool,
+    avatar: Option<String>,
+}
+
+fn lobby_update(players: usize) -> Message {
+    let players = (0..players as i32)
+        .map(|i| PlayerInfo {
+            player_id: i,
+            nickname: format!("oyuncu-{}", i),
+            is_guest: i % 3 == 0,
+            avatar: None,
+        })
+        .collect();
+
+    Message::LobbyUpdate { game_code: "AB12CD".to_string(), players }
+}
+
+fn question_end(players: usize) -> Message {
+    let leaderboard = (0..players as i32)
+        .map(|i| LeaderboardEntry {
+            player_id: i,
+            nickname: format!("oyuncu-{}", i),
+            score: 10_000 - i * 37,
+            is_guest: i % 3 == 0,
+            avatar: None,
+        })
+        .collect();
+
+    Message::QuestionEnd { question_id: 4217, correct_option: "B".to_string(), leaderboard, seq: Some(12) }
+}
+
+fn question_start() -> Message {
+    let options = [("A", "İstanbul"), ("B", "Ankara"), ("C", "İzmir"), ("D", "Bursa")]
+        .into_iter()
+        .map(|(label, text)| (label.to_string(), text.to_string()))
+        .collect();
+
+    Message::QuestionStart {
+        question_id: 4217,
+        question_text: "Türkiye'nin başkenti neresidir?".to_string(),
+        options,
+        time_limit: Some(20),
+        question_number: 3,
+        total_questions: 15,
+        started_at: 1_760_000_000_000,
+        deadline: 1_760_000_020_000,
+        server_time: 1_760_000_000_000,
+        seq: Some(11),
+        audio_url: None,
+        option_labels: None,
+    }
+}
+
+fn frame_len(frame: &Frame) -> usize {
+    match frame {
+        Frame::Text(text) => text.len(),
+        Frame::Binary(bytes) => bytes.len(),
+    }
+}
+
+fn bench_encoding(c: &mut Criterion) {
+    let messages = [
+        ("question_start", question_start()),
+        ("lobby_update/40_players", lobby_update(40)),
+        ("question_end/40_players", question_end(40)),
+        ("question_end/200_players", question_end(200)),
+    ];
+
+    // Bir yayının oyuncu başına boyutu
+    for (name, message) in &messages {
+        let encoded = EncodedMessage::new(message.clone());
+        let json = frame_len(&encoded.frame(Encoding::Json));
+        let msgpack = frame_len(&encoded.frame(Encoding::MessagePack));
+        println!(
+            "{}: json={} B, msgpack={} B ({:.0}%)",
+            name,
+            json,
+            msgpack,
+            msgpack as f64 / json as f64 * 100.0
+        );
+    }
+
+    // Yayın başına kodlama: tipli mesaj yalnızca ihtiyaç duyulan kodlamaya, o kodlamayı isteyen ilk
+    // bağlantıda bir kez çevrilir
+    for (name, message) in &messages {
+        c.bench_function(&format!("encode_broadcast/msgpack/{}", name), |b| {
+            b.iter(|| EncodedMessage::new(black_box(message.clone())).frame(Encoding::MessagePack))
+        });
+        c.bench_function(&format!("encode_broadcast/json/{}", name), |b| {
+            b.iter(|| EncodedMessage::new(black_box(message.clone())).frame(Encoding::Json))
+        });
+    }
+
+    // 40 MessagePack bağlantısına aynı yayın: kodlama paylaşılır, bağlantı başına yalnızca kopyalanır
+    let broadcast = EncodedMessage::new(question_end(40));
+    c.bench_function("frame_msgpack/question_end/40_connections", |b| {
+        b.iter(|| {
+            for _ in 0..40 {
+                black_box(broadcast.frame(Encoding::MessagePack));
+            }
+        })
+    });
+
+    let encoded = match broadcast.frame(Encoding::MessagePack) {
+        Frame::Binary(bytes) => bytes,
+        Frame::Text(_) => unreachable!("MessagePack kodlaması ikili çerçeve üretir"),
+    };
+    c.bench_function("decode_msgpack/question_end/40_players", |b| {
+        b.iter(|| Encoding::MessagePack.decode(black_box(&encoded)))
+    });
+}
+
+criterion_group!(benches, bench_encoding);
+criterion_main!(benches);
@@ -0,0 +1,89 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use serde::Serialize;
+use std::sync::Arc;
+
+// Giden kuyruk modülü doğrudan derlenir (crate yalnızca binary olarak derlenmektedir)
+#[path = "../src/handlers/ws_codec.rs"]
+#[allow(dead_code)]
+mod ws_codec;
+
+#[path = "../src/handlers/ws_outbox.rs"]
+#[allow(dead_code)]
+mod ws_outbox;
+
+use ws_codec::EncodedMessage;
+use ws_outbox::Outbox;
+
+// Lobi güncellemesi birleştirilebilir bir mesajdır (bkz. WebSocketMessage::coalesce_kind)
+const LOBBY_UPDATE: Option<&str> = Some("lobby_update");
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Message {
+    LobbyUpdate {
+        game_code: String,
+        players: Vec<PlayerInfo>,
+    },
+    QuestionStart {
+        question_text: String,
+        options: [String; 4],
+        time_limit: i32,
+    },
+}
+
+#[derive(Clone, Serialize)]
+struct PlayerInfo {
+    player_id: i32,
+    nickname: String,
+    is_guest: bool,
+    avatar: Option<String>,
+}
+
+// 40 oyunculu bir sınıfın lobi güncellemesi
+fn lobby_message(players: usize) -> Message {
+    let players = (0..players as i32)
+        .map(|i| PlayerInfo {
+            player_id: i,
+            nickname: format!("oyuncu-{}", i),
+            is_guest: i % 3 == 0,
+            avatar: None,
+        })
+        .collect();
+
+    Message::LobbyUpdate { game_code: "AB12CD".to_string(), players }
+}
+
+fn bench_broadcast(c: &mut Criterion) {
+    let lobby = lobby_message(40);
+    let question = Message::QuestionStart {
+        question_text: "Türkiye'nin başkenti neresidir?".to_string(),
+        options: ["İstanbul", "Ankara", "İzmir", "Bursa"].map(String::from),
+        time_limit: 20,
+    };
+
+    // Yayın döngüsü: tek mesajın 40 oyuncunun kuyruğuna eklenmesi
+    c.bench_function("broadcast/40_players", |b| {
+        b.iter_batched(
+            || (0..40).map(|_| Outbox::default()).collect::<Vec<_>>(),
+            |outboxes| {
+                let message = Arc::new(EncodedMessage::new(question.clone()));
+                for outbox in &outboxes {
+                    let _ = outbox.push(None, black_box(&message));
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    // Yavaş istemci: gönderilmemiş lobi güncellemeleri tek mesajda birleştirilir
+    c.bench_function("push/coalesced_lobby_update", |b| {
+        let outbox = Outbox::default();
+        let message = Arc::new(EncodedMessage::new(lobby.clone()));
+        b.iter(|| {
+            let _ = outbox.push(LOBBY_UPDATE, black_box(&message));
+        })
+    });
+}
+
+criterion_group!(benches, bench_broadcast);
+criterion_main!(benches);
\ No newline at end of file
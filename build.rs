@@ -0,0 +1,32 @@
+// Dahili gRPC servisinin sunucu kodunu üret (sözleşme: proto/internal.proto)
+// protoc gerektirmemek için servis elle tanımlanır; mesaj tipleri src/grpc.rs içindedir.
+fn main() {
+    let method = |name: &str, route: &str, input: &str, output: &str| {
+        tonic_build::manual::Method::builder()
+            .name(name)
+            .route_name(route)
+            .input_type(format!("crate::grpc::{}", input))
+            .output_type(format!("crate::grpc::{}", output))
+            .codec_path("tonic_prost::ProstCodec")
+    };
+
+    let service = tonic_build::manual::Service::builder()
+        .name("InternalApi")
+        .package("sorukayisi.internal.v1")
+        .method(method("get_game", "GetGame", "GetGameRequest", "Game").build())
+        .method(method("list_games", "ListGames", "ListGamesRequest", "ListGamesResponse").build())
+        .method(method("get_game_results", "GetGameResults", "GetGameResultsRequest", "GameResults").build())
+        .method(
+            method("subscribe_game_events", "SubscribeGameEvents", "SubscribeGameEventsRequest", "GameEvent")
+                .server_streaming()
+                .build(),
+        )
+        .build();
+
+    tonic_build::manual::Builder::new()
+        .build_client(false)
+        .compile(&[service]);
+
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=proto/internal.proto");
+}
\ No newline at end of file
@@ -55,15 +55,7 @@ CREATE TABLE IF NOT EXISTS users (
     last_login TIMESTAMP WITH TIME ZONE
 );
 
--- Admin kullanıcısı kontrolü ve oluşturma
-DO $$
-BEGIN
-    IF NOT EXISTS (SELECT 1 FROM users WHERE username = 'cancebi') THEN
-        INSERT INTO users (username, email, password_hash, role, is_approved, is_email_verified)
-        VALUES ('cancebi', 'can@edu.tr', '$2a$12$k8Y6JHT9vFx5aHrbjlU9heJGiVjZxrBPHOYWEbXoumtJ3Q3PRxJkW', 'admin', TRUE, TRUE);
-    END IF;
-END
-$$;
+-- Admin kullanıcısı sunucunun ilk çalıştırılmasında ADMIN_EMAIL/ADMIN_PASSWORD ile oluşturulur
 
 -- Soru setleri tablosu
 CREATE TABLE IF NOT EXISTS question_sets (
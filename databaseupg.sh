@@ -96,6 +96,726 @@ FROM
     LEFT JOIN players p ON g.id = p.game_id AND p.is_active = true
 GROUP BY 
     g.id, g.code, qs.title, u.username, g.started_at, g.ended_at;
+
+-- Oyun olay kaydı (tekrar oynatma / zaman çizelgesi için)
+CREATE TABLE IF NOT EXISTS game_events (
+    id BIGSERIAL PRIMARY KEY,
+    game_id INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    sequence INTEGER NOT NULL,
+    event_type VARCHAR(50) NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (game_id, sequence)
+);
+
+CREATE INDEX IF NOT EXISTS idx_game_events_game ON game_events(game_id, sequence);
+
+-- Oyunun son olay sıra numarası; numaralar bu sayaç artırılarak atomik olarak atanır
+ALTER TABLE games ADD COLUMN IF NOT EXISTS event_seq INTEGER NOT NULL DEFAULT 0;
+
+UPDATE games g SET event_seq = e.max_sequence
+FROM (SELECT game_id, MAX(sequence) as max_sequence FROM game_events GROUP BY game_id) e
+WHERE e.game_id = g.id AND g.event_seq < e.max_sequence;
+
+-- İçerik şikayetleri (moderasyon kuyruğu)
+CREATE TABLE IF NOT EXISTS content_reports (
+    id SERIAL PRIMARY KEY,
+    target_type VARCHAR(20) NOT NULL CHECK (target_type IN ('question_set', 'nickname')),
+    target_id INTEGER NOT NULL,
+    reporter_user_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    reporter_player_id INTEGER REFERENCES players(id) ON DELETE SET NULL,
+    reason TEXT NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'dismissed', 'hidden', 'deleted')),
+    resolved_by INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    resolution_note TEXT,
+    resolved_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_content_reports_status ON content_reports(status);
+CREATE INDEX IF NOT EXISTS idx_content_reports_target ON content_reports(target_type, target_id);
+
+-- Moderasyon ile gizlenen soru setleri
+ALTER TABLE question_sets ADD COLUMN IF NOT EXISTS is_hidden BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Öğretmen kullanım kotaları için sayaçlar
+CREATE TABLE IF NOT EXISTS usage_counters (
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    metric VARCHAR(50) NOT NULL,
+    period_start DATE NOT NULL,
+    amount BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, metric, period_start)
+);
+
+-- Kurumlar (okul / üniversite)
+CREATE TABLE IF NOT EXISTS organizations (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    slug VARCHAR(100) UNIQUE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS organization_id INTEGER REFERENCES organizations(id) ON DELETE SET NULL;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS is_org_admin BOOLEAN NOT NULL DEFAULT FALSE;
+CREATE INDEX IF NOT EXISTS idx_users_organization ON users(organization_id);
+
+-- Soru setlerinin kurum içinde paylaşımı
+ALTER TABLE question_sets ADD COLUMN IF NOT EXISTS visibility VARCHAR(20) NOT NULL DEFAULT 'private';
+ALTER TABLE question_sets DROP CONSTRAINT IF EXISTS question_sets_visibility_check;
+ALTER TABLE question_sets ADD CONSTRAINT question_sets_visibility_check CHECK (visibility IN ('private', 'organization'));
+
+-- Sınıflar ve üyeleri
+CREATE TABLE IF NOT EXISTS classes (
+    id SERIAL PRIMARY KEY,
+    teacher_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    organization_id INTEGER REFERENCES organizations(id) ON DELETE SET NULL,
+    name VARCHAR(255) NOT NULL,
+    shared_with_org BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS class_members (
+    class_id INTEGER NOT NULL REFERENCES classes(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    joined_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (class_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_classes_teacher ON classes(teacher_id);
+CREATE INDEX IF NOT EXISTS idx_classes_organization ON classes(organization_id);
+
+-- Kurumların kayıt için izin verdiği e-posta alan adları
+CREATE TABLE IF NOT EXISTS organization_email_domains (
+    id SERIAL PRIMARY KEY,
+    organization_id INTEGER NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    domain VARCHAR(255) UNIQUE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_org_email_domains_org ON organization_email_domains(organization_id);
+
+-- Admin panelinden değiştirilebilen sistem ayarları
+CREATE TABLE IF NOT EXISTS system_settings (
+    key VARCHAR(100) PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_by INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Öğretmenlerin oluşturduğu öğrenci davet kodları
+CREATE TABLE IF NOT EXISTS invitations (
+    id SERIAL PRIMARY KEY,
+    code VARCHAR(16) UNIQUE NOT NULL,
+    teacher_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    class_id INTEGER REFERENCES classes(id) ON DELETE CASCADE,
+    max_uses INTEGER,
+    use_count INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMP WITH TIME ZONE,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_invitations_teacher ON invitations(teacher_id);
+
+-- Davetle kayıt olan kullanıcıları takip et
+ALTER TABLE users ADD COLUMN IF NOT EXISTS invited_by INTEGER REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS invitation_id INTEGER REFERENCES invitations(id) ON DELETE SET NULL;
+
+-- Misafir oyuncu sonuçlarının kayıtlı hesaba aktarılma zamanı
+ALTER TABLE players ADD COLUMN IF NOT EXISTS claimed_at TIMESTAMP WITH TIME ZONE;
+CREATE INDEX IF NOT EXISTS idx_players_session ON players(session_id);
+
+-- Avatarlar (kayıtlı kullanıcı profili ve oyun bazında oyuncu/misafir)
+ALTER TABLE users ADD COLUMN IF NOT EXISTS avatar VARCHAR(50);
+ALTER TABLE players ADD COLUMN IF NOT EXISTS avatar VARCHAR(50);
+
+-- Başarımlarla kazanılan rozetler
+CREATE TABLE IF NOT EXISTS user_badges (
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    badge VARCHAR(50) NOT NULL,
+    game_id INTEGER REFERENCES games(id) ON DELETE SET NULL,
+    earned_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, badge)
+);
+
+-- Deneyim puanı (XP) sistemi
+ALTER TABLE users ADD COLUMN IF NOT EXISTS xp BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE players ADD COLUMN IF NOT EXISTS xp_earned INTEGER;
+
+-- Aylık rekabet sezonları
+CREATE TABLE IF NOT EXISTS seasons (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(50) UNIQUE NOT NULL,
+    starts_on DATE NOT NULL,
+    ends_on DATE NOT NULL,
+    archived_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Sezon içindeki ELO benzeri puanlar
+CREATE TABLE IF NOT EXISTS season_ratings (
+    season_id INTEGER NOT NULL REFERENCES seasons(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    rating INTEGER NOT NULL DEFAULT 1000,
+    games_played INTEGER NOT NULL DEFAULT 0,
+    wins INTEGER NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (season_id, user_id)
+);
+
+-- Puanlaması yapılmış oyunlar (aynı oyunun iki kez puanlanmasını engeller)
+CREATE TABLE IF NOT EXISTS season_rated_games (
+    game_id INTEGER PRIMARY KEY REFERENCES games(id) ON DELETE CASCADE,
+    season_id INTEGER NOT NULL REFERENCES seasons(id) ON DELETE CASCADE,
+    rated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Biten sezonların arşivlenmiş sıralamaları
+CREATE TABLE IF NOT EXISTS season_standings (
+    season_id INTEGER NOT NULL REFERENCES seasons(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    organization_id INTEGER REFERENCES organizations(id) ON DELETE SET NULL,
+    rank INTEGER NOT NULL,
+    organization_rank INTEGER,
+    rating INTEGER NOT NULL,
+    games_played INTEGER NOT NULL,
+    wins INTEGER NOT NULL,
+    PRIMARY KEY (season_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_season_ratings_rating ON season_ratings(season_id, rating DESC);
+
+-- Push bildirim abonelikleri (FCM cihaz tokenları ve Web Push uç noktaları)
+CREATE TABLE IF NOT EXISTS push_subscriptions (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    platform VARCHAR(20) NOT NULL CHECK (platform IN ('fcm', 'webpush')),
+    token TEXT UNIQUE NOT NULL,
+    p256dh TEXT,
+    auth TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    last_used_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_push_subscriptions_user ON push_subscriptions(user_id);
+
+-- Uygulama içi bildirim kutusu
+CREATE TABLE IF NOT EXISTS notifications (
+    id BIGSERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(50) NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    body TEXT NOT NULL,
+    data JSONB NOT NULL DEFAULT '{}'::jsonb,
+    read_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_user ON notifications(user_id, created_at DESC);
+
+-- Soru seti ortak editörleri
+CREATE TABLE IF NOT EXISTS question_set_editors (
+    question_set_id INTEGER NOT NULL REFERENCES question_sets(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    permission VARCHAR(10) NOT NULL CHECK (permission IN ('read', 'write')),
+    added_by INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (question_set_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_question_set_editors_user ON question_set_editors(user_id);
+
+-- Soru düzenleme geçmişi (silinen sorular için question_id korunur, FK yok)
+CREATE TABLE IF NOT EXISTS question_edit_log (
+    id BIGSERIAL PRIMARY KEY,
+    question_set_id INTEGER NOT NULL REFERENCES question_sets(id) ON DELETE CASCADE,
+    question_id INTEGER,
+    user_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    action VARCHAR(20) NOT NULL CHECK (action IN ('create', 'update', 'delete')),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_question_edit_log_set ON question_edit_log(question_set_id, created_at DESC);
+
+-- Herkese açık soru setleri
+ALTER TABLE question_sets DROP CONSTRAINT IF EXISTS question_sets_visibility_check;
+ALTER TABLE question_sets ADD CONSTRAINT question_sets_visibility_check CHECK (visibility IN ('private', 'organization', 'public'));
+
+-- Soru seti puanları (kullanıcı başına tek puan)
+CREATE TABLE IF NOT EXISTS question_set_ratings (
+    question_set_id INTEGER NOT NULL REFERENCES question_sets(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    rating SMALLINT NOT NULL CHECK (rating BETWEEN 1 AND 5),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (question_set_id, user_id)
+);
+
+-- Soru seti yorumları
+CREATE TABLE IF NOT EXISTS question_set_comments (
+    id SERIAL PRIMARY KEY,
+    question_set_id INTEGER NOT NULL REFERENCES question_sets(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    body TEXT NOT NULL,
+    is_hidden BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_question_set_comments_set ON question_set_comments(question_set_id, created_at DESC);
+
+-- Arşivlenen soru setleri ve oyunlar varsayılan listelerde gösterilmez
+ALTER TABLE question_sets ADD COLUMN IF NOT EXISTS archived_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS archived_at TIMESTAMP WITH TIME ZONE;
+
+
+-- Silinen soru setleri çöp kutusunda bekler, saklama süresi dolunca kalıcı olarak silinir
+ALTER TABLE question_sets ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX IF NOT EXISTS idx_question_sets_deleted ON question_sets(deleted_at) WHERE deleted_at IS NOT NULL;
+
+-- Tam metin arama sütunları ve GIN indeksleri
+ALTER TABLE question_sets ADD COLUMN IF NOT EXISTS search_vector tsvector
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('turkish', coalesce(title, '')), 'A') ||
+        setweight(to_tsvector('turkish', coalesce(description, '')), 'B')
+    ) STORED;
+ALTER TABLE questions ADD COLUMN IF NOT EXISTS search_vector tsvector
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('turkish', coalesce(question_text, '')), 'A') ||
+        setweight(to_tsvector('turkish', coalesce(option_a, '') || ' ' || coalesce(option_b, '') || ' ' ||
+                                         coalesce(option_c, '') || ' ' || coalesce(option_d, '')), 'C')
+    ) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_question_sets_search ON question_sets USING GIN (search_vector);
+CREATE INDEX IF NOT EXISTS idx_questions_search ON questions USING GIN (search_vector);
+
+-- Hile şüphesi işaretleri (imkansız cevap süresi, sekme değiştirme vb.)
+-- Aynı oyuncu, tür ve soru için tek kayıt tutulur, tekrarlar sayaçta birikir
+CREATE TABLE IF NOT EXISTS cheat_flags (
+    id BIGSERIAL PRIMARY KEY,
+    game_id INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    player_id INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    question_id INTEGER REFERENCES questions(id) ON DELETE CASCADE,
+    kind VARCHAR(50) NOT NULL,
+    details JSONB NOT NULL DEFAULT '{}'::jsonb,
+    occurrences INTEGER NOT NULL DEFAULT 1,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_cheat_flags_unique ON cheat_flags(player_id, kind, (COALESCE(question_id, 0)));
+CREATE INDEX IF NOT EXISTS idx_cheat_flags_game ON cheat_flags(game_id);
+
+-- Misafir oyuncuların hashlenmiş IP adresi ve oyun bazında IP sınırı
+ALTER TABLE players ADD COLUMN IF NOT EXISTS ip_hash VARCHAR(64);
+ALTER TABLE games ADD COLUMN IF NOT EXISTS is_competitive BOOLEAN NOT NULL DEFAULT true;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS max_guests_per_ip INTEGER CHECK (max_guests_per_ip > 0);
+
+CREATE INDEX IF NOT EXISTS idx_players_game_ip ON players(game_id, ip_hash) WHERE ip_hash IS NOT NULL;
+
+-- JWT sürümü: şifre/rol değişikliği veya tüm oturumlardan çıkışta artırılır, eski tokenlar geçersiz olur
+ALTER TABLE users ADD COLUMN IF NOT EXISTS token_version INTEGER NOT NULL DEFAULT 0;
+
+-- Şifre sıfırlama istekleri (e-posta bazında hız sınırı için)
+CREATE TABLE IF NOT EXISTS password_reset_requests (
+    id SERIAL PRIMARY KEY,
+    email VARCHAR(255) NOT NULL,
+    requested_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_password_reset_requests_email ON password_reset_requests(email, requested_at);
+
+-- E-posta doğrulama tokenlarının geçerlilik süresi (önceden süresiz olan tokenlara 2 gün tanınır)
+ALTER TABLE users ADD COLUMN IF NOT EXISTS verification_token_expires_at TIMESTAMP WITH TIME ZONE;
+UPDATE users SET verification_token_expires_at = NOW() + INTERVAL '2 days'
+WHERE verification_token IS NOT NULL AND verification_token_expires_at IS NULL;
+
+-- Güvenlikle ilgili hesap olaylarının denetim kaydı
+CREATE TABLE IF NOT EXISTS audit_logs (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    action VARCHAR(50) NOT NULL,
+    details JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_logs_user ON audit_logs(user_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_audit_logs_action ON audit_logs(action, created_at);
+
+-- Takma adların oyun içinde büyük/küçük harf ve Türkçe karakter farkı gözetmeksizin benzersizliği.
+-- Boşluklar kırpılıp teke indirilir, I/İ/ı harfleri i'ye katlanır ("Ali", "ali", "ALİ", "Alı" aynı sayılır).
+-- Uygulamadaki services::nickname::normalize_nickname ile aynı kuralları izler.
+CREATE OR REPLACE FUNCTION normalize_nickname(nickname TEXT) RETURNS TEXT AS $$
+    SELECT LOWER(TRANSLATE(BTRIM(REGEXP_REPLACE(nickname, '\s+', ' ', 'g')), 'İIıÇĞÖŞÜ', 'iiiçğöşü'))
+$$ LANGUAGE SQL IMMUTABLE STRICT;
+
+-- İndeks oluşturulmadan önce mevcut çakışan takma adlara oyuncu numarası eklenir
+UPDATE players p SET nickname = LEFT(p.nickname, 90) || '_' || p.id
+WHERE EXISTS (
+    SELECT 1 FROM players o
+    WHERE o.game_id = p.game_id AND o.id < p.id
+      AND normalize_nickname(o.nickname) = normalize_nickname(p.nickname)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_players_game_nickname ON players(game_id, normalize_nickname(nickname));
+
+-- Müfredat konuları: üst düzey kayıtlar üniteler, parent_id ile bağlananlar ünitenin konularıdır.
+-- Taksonomiyi adminler yönetir; sorular bir veya daha fazla konuyla etiketlenir.
+CREATE TABLE IF NOT EXISTS curriculum_topics (
+    id SERIAL PRIMARY KEY,
+    parent_id INTEGER REFERENCES curriculum_topics(id) ON DELETE CASCADE,
+    code VARCHAR(50) UNIQUE,
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    position INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_curriculum_topics_parent ON curriculum_topics(parent_id);
+
+CREATE TABLE IF NOT EXISTS question_topics (
+    question_id INTEGER NOT NULL REFERENCES questions(id) ON DELETE CASCADE,
+    topic_id INTEGER NOT NULL REFERENCES curriculum_topics(id) ON DELETE CASCADE,
+    PRIMARY KEY (question_id, topic_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_question_topics_topic ON question_topics(topic_id);
+
+-- Çok dilli soru setleri: setin asıl dili ve soruların diğer dillere çevirileri.
+-- Oyuncu katılırken tercih ettiği dili bildirir; çevirisi olan sorular ona o dilde gösterilir.
+ALTER TABLE question_sets ADD COLUMN IF NOT EXISTS language VARCHAR(10) NOT NULL DEFAULT 'tr';
+ALTER TABLE players ADD COLUMN IF NOT EXISTS locale VARCHAR(10);
+
+CREATE TABLE IF NOT EXISTS question_translations (
+    question_id INTEGER NOT NULL REFERENCES questions(id) ON DELETE CASCADE,
+    locale VARCHAR(10) NOT NULL,
+    question_text TEXT NOT NULL,
+    option_a TEXT NOT NULL,
+    option_b TEXT NOT NULL,
+    option_c TEXT NOT NULL,
+    option_d TEXT NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (question_id, locale)
+);
+
+-- Sınıf listesindeki öğrenciler için erişilebilirlik düzenlemeleri: ek süre çarpanı ve ekran okuyucu desteği.
+-- Öğretmen bunları kendi sınıflarında ayarlar; düzenlemeler öğretmenin yönettiği oyunlarda uygulanır.
+ALTER TABLE class_members ADD COLUMN IF NOT EXISTS time_multiplier DOUBLE PRECISION NOT NULL DEFAULT 1.0
+    CHECK (time_multiplier BETWEEN 1.0 AND 3.0);
+ALTER TABLE class_members ADD COLUMN IF NOT EXISTS screen_reader BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Şık etiketleri (harf, şekil veya renk; renk körlüğüne uygun gösterim için)
+ALTER TABLE games ADD COLUMN IF NOT EXISTS option_labels VARCHAR(20) NOT NULL DEFAULT 'letters'
+    CHECK (option_labels IN ('letters', 'shapes', 'colors'));
+
+-- Sahne işaretleri (sahne -> müzik/animasyon işareti)
+ALTER TABLE games ADD COLUMN IF NOT EXISTS scene_cues JSONB NOT NULL DEFAULT '{}';
+
+-- Host notları (yalnızca oyunu yöneten öğretmene gösterilir)
+ALTER TABLE questions ADD COLUMN IF NOT EXISTS host_notes TEXT;
+-- Sınıf listesi: öğretmenin CSV ile içe aktardığı öğrenciler (kayıt olana kadar bekleyen davetle)
+CREATE TABLE IF NOT EXISTS class_roster (
+    id SERIAL PRIMARY KEY,
+    class_id INTEGER NOT NULL REFERENCES classes(id) ON DELETE CASCADE,
+    display_name VARCHAR(50) NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    invitation_id INTEGER REFERENCES invitations(id) ON DELETE SET NULL,
+    user_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (class_id, email)
+);
+
+CREATE INDEX IF NOT EXISTS idx_class_roster_user ON class_roster(user_id);
+CREATE INDEX IF NOT EXISTS idx_class_roster_invitation ON class_roster(invitation_id);
+
+-- Yalnızca sınıf listesindeki öğrencilerin katılabildiği oyunlar
+ALTER TABLE games ADD COLUMN IF NOT EXISTS roster_class_id INTEGER REFERENCES classes(id) ON DELETE SET NULL;
+
+-- Oyunun not ölçeği (yüzde veya not bantları)
+ALTER TABLE games ADD COLUMN IF NOT EXISTS grading JSONB NOT NULL DEFAULT '{"scale": "percent"}';
+
+-- Günlük platform istatistikleri (gece toplama işiyle kurum bazında doldurulur)
+CREATE TABLE IF NOT EXISTS daily_stats (
+    id SERIAL PRIMARY KEY,
+    day DATE NOT NULL,
+    organization_id INTEGER REFERENCES organizations(id) ON DELETE SET NULL,
+    games_played INTEGER NOT NULL DEFAULT 0,
+    new_users INTEGER NOT NULL DEFAULT 0,
+    answers_submitted INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_daily_stats_day ON daily_stats(day);
+
+-- Veri ambarı dışa aktarımları (her çalıştırma bir önceki başarılı aktarımın bittiği yerden devam eder)
+CREATE TABLE IF NOT EXISTS warehouse_exports (
+    id SERIAL PRIMARY KEY,
+    window_start TIMESTAMP WITH TIME ZONE,
+    window_end TIMESTAMP WITH TIME ZONE NOT NULL,
+    location TEXT NOT NULL,
+    game_count INTEGER NOT NULL DEFAULT 0,
+    answer_count INTEGER NOT NULL DEFAULT 0,
+    status VARCHAR(20) NOT NULL DEFAULT 'running' CHECK (status IN ('running', 'completed', 'failed')),
+    error TEXT,
+    started_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    finished_at TIMESTAMP WITH TIME ZONE
+);
+
+-- Dijital pano gibi dış sayfalara gömülen canlı liderlik tablosu için oyun bazında erişim tokenı (SHA-256 özeti)
+ALTER TABLE games ADD COLUMN IF NOT EXISTS widget_token_hash VARCHAR(64) UNIQUE;
+
+-- Birbirine benzeyen kullanıcı adlarını (ör. Latin/Kiril harf karışımı) yakalamak için karşılaştırma anahtarı;
+-- sunucu açılışında boş kalan kayıtlar doldurulur
+ALTER TABLE users ADD COLUMN IF NOT EXISTS username_skeleton VARCHAR(255);
+CREATE INDEX IF NOT EXISTS idx_users_username_skeleton ON users(username_skeleton);
+
+-- Sunucudan sunucuya entegrasyonlar için admin tarafından verilen API anahtarları (yalnızca SHA-256 özeti saklanır)
+CREATE TABLE IF NOT EXISTS api_keys (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL,
+    key_hash VARCHAR(64) NOT NULL UNIQUE,
+    key_prefix VARCHAR(20) NOT NULL,
+    scopes TEXT[] NOT NULL,
+    created_by INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    last_used_at TIMESTAMP WITH TIME ZONE,
+    revoked_at TIMESTAMP WITH TIME ZONE
+);
+
+-- Soru seti pazaryeri: herkese açık setler admin incelemesinden geçerek kategorilere göre listelenir
+CREATE TABLE IF NOT EXISTS marketplace_categories (
+    id SERIAL PRIMARY KEY,
+    slug VARCHAR(100) NOT NULL UNIQUE,
+    name VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO marketplace_categories (slug, name) VALUES
+    ('matematik', 'Matematik'),
+    ('fen-bilimleri', 'Fen Bilimleri'),
+    ('turkce', 'Türkçe'),
+    ('sosyal-bilgiler', 'Sosyal Bilgiler'),
+    ('yabanci-dil', 'Yabancı Dil'),
+    ('genel-kultur', 'Genel Kültür')
+ON CONFLICT (slug) DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS marketplace_listings (
+    id SERIAL PRIMARY KEY,
+    question_set_id INTEGER NOT NULL UNIQUE REFERENCES question_sets(id) ON DELETE CASCADE,
+    category_id INTEGER NOT NULL REFERENCES marketplace_categories(id),
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected', 'withdrawn')),
+    submitted_by INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    submitted_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    reviewed_by INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    reviewed_at TIMESTAMP WITH TIME ZONE,
+    review_note TEXT,
+    is_featured BOOLEAN NOT NULL DEFAULT FALSE,
+    download_count INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_marketplace_listings_status ON marketplace_listings(status, category_id);
+
+-- Gönderimde yayındaki setlerle benzerlik taraması: en yüksek örtüşme oranı, örtüşen setler ve admin incelemesi işareti
+ALTER TABLE marketplace_listings ADD COLUMN IF NOT EXISTS similarity_score REAL;
+ALTER TABLE marketplace_listings ADD COLUMN IF NOT EXISTS similarity_matches JSONB NOT NULL DEFAULT '[]'::jsonb;
+ALTER TABLE marketplace_listings ADD COLUMN IF NOT EXISTS similarity_flagged BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Kötüye kullanım engel listesi: IP/CIDR blokları, e-posta alan adları ve kullanıcılar (süreli veya süresiz)
+CREATE TABLE IF NOT EXISTS blocklist_entries (
+    id SERIAL PRIMARY KEY,
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('ip', 'email_domain', 'user')),
+    value VARCHAR(255) NOT NULL,
+    reason TEXT,
+    expires_at TIMESTAMP WITH TIME ZONE,
+    created_by INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (kind, value)
+);
+
+-- Oyunların son değişiklik zamanı (GET /api/game/{code} ETag'i için). Oyun satırını güncelleyen her yazma
+-- (başlatma, soru geçişi, ayarlar, bitirme) tetikleyiciyle zamanı kendiliğinden ilerletir.
+ALTER TABLE games ADD COLUMN IF NOT EXISTS updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP;
+
+CREATE OR REPLACE FUNCTION touch_updated_at() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at = CURRENT_TIMESTAMP;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS games_touch_updated_at ON games;
+CREATE TRIGGER games_touch_updated_at BEFORE UPDATE ON games
+    FOR EACH ROW EXECUTE FUNCTION touch_updated_at();
+
+-- Soruların son değişiklik zamanı; ortak editörlerin birbirinin değişikliğini fark etmeden ezmemesi için
+-- güncellemelerde If-Unmodified-Since ön koşuluyla karşılaştırılır
+ALTER TABLE questions ADD COLUMN IF NOT EXISTS updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP;
+
+DROP TRIGGER IF EXISTS questions_touch_updated_at ON questions;
+CREATE TRIGGER questions_touch_updated_at BEFORE UPDATE ON questions
+    FOR EACH ROW EXECUTE FUNCTION touch_updated_at();
+
+-- Silinen sorular kısa bir geri alma süresi boyunca saklanır, süre dolunca kalıcı olarak silinir
+ALTER TABLE questions ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX IF NOT EXISTS idx_questions_deleted ON questions(deleted_at) WHERE deleted_at IS NOT NULL;
+
+ALTER TABLE question_edit_log DROP CONSTRAINT IF EXISTS question_edit_log_action_check;
+ALTER TABLE question_edit_log ADD CONSTRAINT question_edit_log_action_check
+    CHECK (action IN ('create', 'update', 'delete', 'restore'));
+
+-- Oyun kodu takma adları; bitmemiş oyunlar arasında kurum bazında benzersizdir (kurumsuz hostlar tek kapsam)
+ALTER TABLE games ADD COLUMN IF NOT EXISTS alias VARCHAR(32);
+ALTER TABLE games ADD COLUMN IF NOT EXISTS alias_organization_id INTEGER REFERENCES organizations(id) ON DELETE SET NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_games_alias_active ON games(COALESCE(alias_organization_id, 0), alias)
+    WHERE alias IS NOT NULL AND status <> 'completed';
+
+-- Turnuvalar: birden fazla oyun (tur) birikimli puanla oynanır, her turun sonunda ilk N katılımcı bir
+-- sonraki tura yükselir
+CREATE TABLE IF NOT EXISTS tournaments (
+    id SERIAL PRIMARY KEY,
+    host_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'completed')),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    completed_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_tournaments_host ON tournaments(host_id, created_at DESC);
+
+CREATE TABLE IF NOT EXISTS tournament_rounds (
+    tournament_id INTEGER NOT NULL REFERENCES tournaments(id) ON DELETE CASCADE,
+    round_number INTEGER NOT NULL,
+    game_id INTEGER NOT NULL UNIQUE REFERENCES games(id) ON DELETE CASCADE,
+    advance_count INTEGER CHECK (advance_count > 0), -- NULL: herkes bir sonraki tura geçer
+    completed_at TIMESTAMP WITH TIME ZONE,
+    PRIMARY KEY (tournament_id, round_number)
+);
+
+-- Katılımcılar turlar arasında kullanıcı hesabıyla, misafirler normalize edilmiş takma adla eşleştirilir
+CREATE TABLE IF NOT EXISTS tournament_participants (
+    id SERIAL PRIMARY KEY,
+    tournament_id INTEGER NOT NULL REFERENCES tournaments(id) ON DELETE CASCADE,
+    participant_key VARCHAR(120) NOT NULL,
+    user_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    nickname VARCHAR(50) NOT NULL,
+    total_score INTEGER NOT NULL DEFAULT 0,
+    rounds_played INTEGER NOT NULL DEFAULT 0,
+    eliminated_after_round INTEGER,
+    UNIQUE (tournament_id, participant_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_tournament_participants_score ON tournament_participants(tournament_id, total_score DESC);
+
+-- Oyunda setin tamamı yerine rastgele seçilen N soru sorulabilir: {"count": 10, "stratify_by": "topic"}
+ALTER TABLE games ADD COLUMN IF NOT EXISTS question_sample JSONB;
+
+-- Oyun başlarken seçilen sorular; istatistikler oyunda gerçekten sorulan sorulara göre hesaplanır
+CREATE TABLE IF NOT EXISTS game_questions (
+    game_id INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    question_id INTEGER NOT NULL REFERENCES questions(id) ON DELETE CASCADE,
+    PRIMARY KEY (game_id, question_id)
+);
+
+-- Oyunda sorulan sorular: soru seçimi yapıldıysa seçilen sorular, yapılmadıysa setin tüm soruları
+CREATE OR REPLACE FUNCTION game_question_ids(p_game_id INTEGER) RETURNS SETOF INTEGER AS $$
+    SELECT q.id
+    FROM games g
+    JOIN questions q ON q.question_set_id = g.question_set_id AND q.deleted_at IS NULL
+    WHERE g.id = p_game_id
+      AND (NOT EXISTS (SELECT 1 FROM game_questions gq WHERE gq.game_id = g.id)
+           OR q.id IN (SELECT gq.question_id FROM game_questions gq WHERE gq.game_id = g.id))
+$$ LANGUAGE SQL STABLE;
+
+-- Oyuna ana setin (games.question_set_id) ardından eklenen diğer soru setleri, eklenme sırasıyla
+CREATE TABLE IF NOT EXISTS game_question_sets (
+    game_id INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    question_set_id INTEGER NOT NULL REFERENCES question_sets(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    PRIMARY KEY (game_id, question_set_id)
+);
+
+-- Kaydedilen soru listesinde sorunun oyundaki sırası; birden fazla setin soruları tek sırada birleşir
+ALTER TABLE game_questions ADD COLUMN IF NOT EXISTS position INTEGER;
+UPDATE game_questions gq SET position = q.position FROM questions q WHERE q.id = gq.question_id AND gq.position IS NULL;
+ALTER TABLE game_questions ALTER COLUMN position SET NOT NULL;
+
+-- Oyunun soru listesi ve soruların oyundaki sırası (games.current_question bu sırayı gösterir): liste
+-- kaydedildiyse kaydedilen sorular, kaydedilmediyse ana setin tüm soruları setteki sırasıyla
+CREATE OR REPLACE FUNCTION game_playlist(p_game_id INTEGER) RETURNS TABLE(question_id INTEGER, "position" INTEGER) AS $$
+    SELECT gq.question_id, gq.position
+    FROM game_questions gq
+    JOIN questions q ON q.id = gq.question_id AND q.deleted_at IS NULL
+    WHERE gq.game_id = p_game_id
+    UNION ALL
+    SELECT q.id, q.position
+    FROM games g
+    JOIN questions q ON q.question_set_id = g.question_set_id AND q.deleted_at IS NULL
+    WHERE g.id = p_game_id
+      AND NOT EXISTS (SELECT 1 FROM game_questions gq WHERE gq.game_id = g.id)
+$$ LANGUAGE SQL STABLE;
+
+CREATE OR REPLACE FUNCTION game_question_ids(p_game_id INTEGER) RETURNS SETOF INTEGER AS $$
+    SELECT gp.question_id FROM game_playlist(p_game_id) gp
+$$ LANGUAGE SQL STABLE;
+
+-- Oyunu oyuncu veya host olmadan izleyen bağlantılar; izleyici istatistikleri için bağlanma ve ayrılma zamanları
+CREATE TABLE IF NOT EXISTS game_viewers (
+    id SERIAL PRIMARY KEY,
+    game_id INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    session_id VARCHAR(255) NOT NULL,
+    joined_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    left_at TIMESTAMP WITH TIME ZONE
+);
+CREATE INDEX IF NOT EXISTS idx_game_viewers_game ON game_viewers(game_id);
+CREATE INDEX IF NOT EXISTS idx_game_viewers_open ON game_viewers(session_id) WHERE left_at IS NULL;
+
+-- Misafir oyuncu oturumları: tek oyuncu ve oyunla sınırlı, süresi dolan veya yeniden bağlanınca iptal edilen
+-- oturumlar geçersizdir (kayıt yoksa oturum geçersiz sayılır)
+CREATE TABLE IF NOT EXISTS guest_sessions (
+    id SERIAL PRIMARY KEY,
+    player_id INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    game_id INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    session_id VARCHAR(255) UNIQUE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    revoked_at TIMESTAMP WITH TIME ZONE
+);
+CREATE INDEX IF NOT EXISTS idx_guest_sessions_player ON guest_sessions(player_id);
+
+-- Devam eden oyunlardaki misafirler için oturum aç; bitmiş oyunların misafir oturumları geçersiz kalır
+INSERT INTO guest_sessions (player_id, game_id, session_id, expires_at)
+SELECT p.id, p.game_id, p.session_id, CURRENT_TIMESTAMP + INTERVAL '6 hours'
+FROM players p
+JOIN games g ON g.id = p.game_id
+WHERE p.user_id IS NULL AND g.status <> 'completed'
+ON CONFLICT (session_id) DO NOTHING;
+
+-- Ani ölüm turunda finalistlerin sırası (1: kazanan); oyun sonu sıralaması ve ödüller önce buna, sonra puana bakar
+ALTER TABLE players ADD COLUMN IF NOT EXISTS sudden_death_place INTEGER;
+
+-- Soru listesi artık her oyun başlarken kaydedilir. Kaydedilen liste, sonradan silinen sorular dahil oyunda sorulan
+-- soruları gösterir; böylece silinen bir soru geçmiş oyunların soru sayısını ve istatistiklerini değiştirmez.
+-- Listesi kaydedilmemiş eski oyunlarda oyun başladıktan sonra silinen sorular da listede kalır.
+CREATE OR REPLACE FUNCTION game_playlist(p_game_id INTEGER) RETURNS TABLE(question_id INTEGER, "position" INTEGER) AS $$
+    SELECT gq.question_id, gq.position
+    FROM game_questions gq
+    WHERE gq.game_id = p_game_id
+    UNION ALL
+    SELECT q.id, q.position
+    FROM games g
+    JOIN questions q ON q.question_set_id = g.question_set_id
+    WHERE g.id = p_game_id
+      AND (q.deleted_at IS NULL OR q.deleted_at > g.started_at)
+      AND NOT EXISTS (SELECT 1 FROM game_questions gq WHERE gq.game_id = g.id)
+$$ LANGUAGE SQL STABLE;
+
+-- Planlanan oyunlar: başlangıçtan önce hatırlatma bildirimi gönderilir
+ALTER TABLE games ADD COLUMN IF NOT EXISTS scheduled_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS reminder_sent_at TIMESTAMP WITH TIME ZONE;
+CREATE INDEX IF NOT EXISTS idx_games_reminder_due ON games(scheduled_at) WHERE scheduled_at IS NOT NULL AND reminder_sent_at IS NULL;
 EOL
 
 # Şemayı veritabanına uygulama
@@ -0,0 +1,212 @@
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHasher, SaltString},
+    Argon2,
+};
+use chrono::{Duration, Utc};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
+use std::env;
+
+// Operasyonel yönetim komutları: veritabanını elle düzenlemeden admin oluşturma,
+// öğretmen onaylama, şifre sıfırlama ve takılı kalmış oyunları temizleme.
+//
+// Kullanım:
+//   cargo run --bin admin -- create-admin --username <ad> --email <e-posta> --password <şifre>
+//   cargo run --bin admin -- approve-teacher --email <e-posta>
+//   cargo run --bin admin -- reset-password --email <e-posta> --password <yeni şifre>
+//   cargo run --bin admin -- purge-stale-games [--hours 24]
+
+const USAGE: &str = "Kullanım: admin <create-admin|approve-teacher|reset-password|purge-stale-games> [--parametre değer]...";
+
+// Sunucudaki şifre kurallarıyla aynı alt sınır
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+type CommandResult = Result<String, Box<dyn std::error::Error>>;
+
+fn parse_flags(mut args: impl Iterator<Item = String>) -> Result<HashMap<String, String>, String> {
+    let mut flags = HashMap::new();
+
+    while let Some(arg) = args.next() {
+        let name = arg
+            .strip_prefix("--")
+            .ok_or_else(|| format!("Beklenmeyen parametre: {}", arg))?;
+        let value = args.next().ok_or_else(|| format!("--{} için değer eksik", name))?;
+        flags.insert(name.to_string(), value);
+    }
+
+    Ok(flags)
+}
+
+fn required<'a>(flags: &'a HashMap<String, String>, name: &str) -> Result<&'a str, String> {
+    flags
+        .get(name)
+        .map(String::as_str)
+        .ok_or_else(|| format!("--{} gerekli", name))
+}
+
+// Sunucunun kullandığı Argon2 biçiminde şifre hashle
+fn hash_password(password: &str) -> Result<String, String> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(format!("Şifre en az {} karakter olmalıdır", MIN_PASSWORD_LENGTH));
+    }
+
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|h| h.to_string())
+        .map_err(|e| e.to_string())
+}
+
+// Yeni admin oluştur; kullanıcı zaten varsa admin yetkisi verilir
+async fn create_admin(pool: &Pool<Postgres>, flags: &HashMap<String, String>) -> CommandResult {
+    let username = required(flags, "username")?;
+    let email = required(flags, "email")?;
+    let password_hash = hash_password(required(flags, "password")?)?;
+
+    let record = sqlx::query!(
+        r#"
+        INSERT INTO users (username, email, password_hash, role, is_approved, is_email_verified, created_at)
+        VALUES ($1, $2, $3, 'admin', true, true, $4)
+        ON CONFLICT (email) DO UPDATE
+        SET role = 'admin', password_hash = EXCLUDED.password_hash, is_approved = true, is_email_verified = true,
+            token_version = users.token_version + 1
+        RETURNING id, (xmax = 0) as "created!"
+        "#,
+        username,
+        email,
+        password_hash,
+        Utc::now()
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(if record.created {
+        format!("Admin oluşturuldu: {} (id={})", username, record.id)
+    } else {
+        format!("Mevcut kullanıcıya admin yetkisi verildi: {} (id={})", email, record.id)
+    })
+}
+
+async fn approve_teacher(pool: &Pool<Postgres>, flags: &HashMap<String, String>) -> CommandResult {
+    let email = required(flags, "email")?;
+
+    let user = sqlx::query!(
+        "UPDATE users SET is_approved = true WHERE email = $1 AND role = 'teacher' RETURNING id, username",
+        email
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| format!("Bu e-posta ile kayıtlı öğretmen bulunamadı: {}", email))?;
+
+    Ok(format!("Öğretmen onaylandı: {} (id={})", user.username, user.id))
+}
+
+async fn reset_password(pool: &Pool<Postgres>, flags: &HashMap<String, String>) -> CommandResult {
+    let email = required(flags, "email")?;
+    let password_hash = hash_password(required(flags, "password")?)?;
+
+    // Bekleyen sıfırlama bağlantıları ve açık oturumlar da geçersiz kılınır
+    let user = sqlx::query!(
+        r#"
+        UPDATE users SET password_hash = $1, reset_token = NULL, reset_token_expires_at = NULL,
+                         token_version = token_version + 1
+        WHERE email = $2
+        RETURNING id, username
+        "#,
+        password_hash,
+        email
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| format!("Kullanıcı bulunamadı: {}", email))?;
+
+    Ok(format!("Şifre güncellendi: {} (id={})", user.username, user.id))
+}
+
+// Belirtilen süreden uzun süredir lobide veya aktif kalan oyunları temizle.
+// Hiç başlamamış lobiler silinir, yarıda kalmış oyunlar sonuçları korunarak tamamlanmış sayılır.
+async fn purge_stale_games(pool: &Pool<Postgres>, flags: &HashMap<String, String>) -> CommandResult {
+    let hours: i64 = match flags.get("hours") {
+        Some(value) => value.parse().map_err(|_| "--hours bir sayı olmalıdır")?,
+        None => 24,
+    };
+    let cutoff = Utc::now() - Duration::hours(hours);
+
+    let mut tx = pool.begin().await?;
+
+    let deleted = sqlx::query!(
+        "DELETE FROM games WHERE status = 'lobby' AND created_at < $1",
+        cutoff
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    let completed = sqlx::query!(
+        r#"
+        UPDATE games SET status = 'completed', ended_at = NOW()
+        WHERE status = 'active' AND COALESCE(started_at, created_at) < $1
+        "#,
+        cutoff
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    tx.commit().await?;
+
+    Ok(format!(
+        "{} saatten eski {} lobi silindi, {} aktif oyun tamamlandı olarak işaretlendi",
+        hours, deleted, completed
+    ))
+}
+
+#[tokio::main]
+async fn main() {
+    dotenv::dotenv().ok();
+
+    let mut args = env::args().skip(1);
+    let command = args.next().unwrap_or_default();
+    let flags = match parse_flags(args) {
+        Ok(flags) => flags,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let database_url = match env::var("DATABASE_URL") {
+        Ok(url) => url,
+        Err(_) => {
+            eprintln!("DATABASE_URL tanımlı değil");
+            std::process::exit(2);
+        }
+    };
+    let pool = match PgPoolOptions::new().max_connections(1).connect(&database_url).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            eprintln!("Veritabanına bağlanılamadı: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let result = match command.as_str() {
+        "create-admin" => create_admin(&pool, &flags).await,
+        "approve-teacher" => approve_teacher(&pool, &flags).await,
+        "reset-password" => reset_password(&pool, &flags).await,
+        "purge-stale-games" => purge_stale_games(&pool, &flags).await,
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    match result {
+        Ok(message) => println!("{}", message),
+        Err(e) => {
+            eprintln!("Hata: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
\ No newline at end of file
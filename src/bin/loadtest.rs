@@ -0,0 +1,296 @@
+use futures_util::{SinkExt, StreamExt};
+use rand::Rng;
+use serde_json::{json, Value};
+use std::env;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Barrier;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+// Yük testi: N sanal oyuncu bir oyuna WebSocket üzerinden katılır ve soruları cevaplar.
+// Katılım süresi, lobi yayınının oyunculara ulaşma gecikmesi ve cevap gidiş-dönüş süreleri ölçülür.
+//
+// Kullanım:
+//   cargo run --release --bin loadtest -- --token <öğretmen JWT> --question-set <id> [--players 40] [--base-url http://127.0.0.1:8080]
+
+// Tek bir mesaj için beklenecek en uzun süre
+const MESSAGE_TIMEOUT: Duration = Duration::from_secs(30);
+
+type WsStream = tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+struct Options {
+    base_url: String,
+    token: String,
+    question_set_id: i32,
+    players: usize,
+}
+
+// Tek bir sanal oyuncunun ölçümleri
+#[derive(Default)]
+struct PlayerMetrics {
+    join_ms: Option<f64>,
+    joined_at: Option<Instant>,
+    full_lobby_at: Option<Instant>,
+    answer_rtts_ms: Vec<f64>,
+    errors: Vec<String>,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut base_url = "http://127.0.0.1:8080".to_string();
+    let mut token = None;
+    let mut question_set_id = None;
+    let mut players = 40;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or_else(|| format!("{} için değer eksik", arg))?;
+        match arg.as_str() {
+            "--base-url" => base_url = value.trim_end_matches('/').to_string(),
+            "--token" => token = Some(value),
+            "--question-set" => {
+                question_set_id = Some(value.parse().map_err(|_| "--question-set bir sayı olmalıdır")?)
+            }
+            "--players" => players = value.parse().map_err(|_| "--players bir sayı olmalıdır")?,
+            _ => return Err(format!("Bilinmeyen parametre: {}", arg)),
+        }
+    }
+
+    Ok(Options {
+        base_url,
+        token: token.ok_or("--token gerekli")?,
+        question_set_id: question_set_id.ok_or("--question-set gerekli")?,
+        players,
+    })
+}
+
+// Belirtilen tipte mesaj gelene kadar bekle, aradaki diğer mesajları atla
+async fn wait_for<F>(ws: &mut WsStream, mut matches: F) -> Result<Value, String>
+where
+    F: FnMut(&Value) -> bool,
+{
+    let deadline = Instant::now() + MESSAGE_TIMEOUT;
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let msg = tokio::time::timeout(remaining, ws.next())
+            .await
+            .map_err(|_| "Mesaj beklenirken zaman aşımı".to_string())?
+            .ok_or("Bağlantı kapandı")?
+            .map_err(|e| e.to_string())?;
+
+        if let Message::Text(text) = msg {
+            let value: Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+            if value["type"] == "error" {
+                return Err(format!("Sunucu hatası: {}", value["code"]));
+            }
+            if matches(&value) {
+                return Ok(value);
+            }
+        }
+    }
+}
+
+async fn run_player(
+    index: usize,
+    ws_url: String,
+    game_code: String,
+    question_ids: Arc<Vec<i64>>,
+    total_players: usize,
+    barrier: Arc<Barrier>,
+) -> PlayerMetrics {
+    let mut metrics = PlayerMetrics::default();
+    let mut rounds_waited = 0;
+
+    let result: Result<(), String> = async {
+        let (mut ws, _) = connect_async(&ws_url).await.map_err(|e| e.to_string())?;
+        wait_for(&mut ws, |m| m["type"] == "welcome").await?;
+
+        // Lobiye katıl
+        let started = Instant::now();
+        ws.send(Message::Text(
+            json!({
+                "type": "join_lobby",
+                "game_code": game_code,
+                "nickname": format!("bot-{}", index)
+            })
+            .to_string(),
+        ))
+        .await
+        .map_err(|e| e.to_string())?;
+        wait_for(&mut ws, |m| m["type"] == "join_success").await?;
+        metrics.join_ms = Some(started.elapsed().as_secs_f64() * 1000.0);
+        metrics.joined_at = Some(Instant::now());
+
+        // Tüm oyuncuları içeren lobi yayınını bekle
+        wait_for(&mut ws, |m| {
+            m["type"] == "lobby_update"
+                && m["players"].as_array().map(|p| p.len() >= total_players).unwrap_or(false)
+        })
+        .await?;
+        metrics.full_lobby_at = Some(Instant::now());
+
+        // Her soruyu tüm oyuncular aynı anda cevaplar (sınıf ortamındaki gibi)
+        for question_id in question_ids.iter() {
+            barrier.wait().await;
+            rounds_waited += 1;
+
+            let (answer, response_time_ms) = {
+                let mut rng = rand::thread_rng();
+                (["A", "B", "C", "D"][rng.gen_range(0..4)], rng.gen_range(800..8000))
+            };
+
+            let sent = Instant::now();
+            ws.send(Message::Text(
+                json!({
+                    "type": "submit_answer",
+                    "question_id": question_id,
+                    "answer": answer,
+                    "response_time_ms": response_time_ms
+                })
+                .to_string(),
+            ))
+            .await
+            .map_err(|e| e.to_string())?;
+            wait_for(&mut ws, |m| m["type"] == "answer_received" && m["question_id"] == *question_id).await?;
+            metrics.answer_rtts_ms.push(sent.elapsed().as_secs_f64() * 1000.0);
+        }
+
+        let _ = ws.close(None).await;
+        Ok(())
+    }
+    .await;
+
+    if let Err(e) = result {
+        metrics.errors.push(format!("bot-{}: {}", index, e));
+        // Diğer oyuncuların engellenmemesi için kalan bariyerlere katıl
+        for _ in rounds_waited..question_ids.len() {
+            barrier.wait().await;
+        }
+    }
+
+    metrics
+}
+
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((p / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+fn print_distribution(label: &str, mut values: Vec<f64>) {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    println!(
+        "{:<28} n={:<6} p50={:>8.1}ms p95={:>8.1}ms p99={:>8.1}ms max={:>8.1}ms",
+        label,
+        values.len(),
+        percentile(&values, 50.0),
+        percentile(&values, 95.0),
+        percentile(&values, 99.0),
+        values.last().copied().unwrap_or(0.0)
+    );
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let options = parse_options().inspect_err(|_| {
+        eprintln!("Kullanım: loadtest --token <JWT> --question-set <id> [--players N] [--base-url URL]");
+    })?;
+    let client = reqwest::Client::new();
+
+    // Soru setindeki soruları getir
+    let set: Value = client
+        .get(format!("{}/api/question-sets/{}", options.base_url, options.question_set_id))
+        .bearer_auth(&options.token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let question_ids: Vec<i64> = set["questions"]
+        .as_array()
+        .map(|qs| qs.iter().filter_map(|q| q["id"].as_i64()).collect())
+        .unwrap_or_default();
+
+    // Oyunu oluştur
+    let game: Value = client
+        .post(format!("{}/api/game", options.base_url))
+        .bearer_auth(&options.token)
+        .json(&json!({ "question_set_id": options.question_set_id }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let game_code = game["code"].as_str().ok_or("Oyun kodu alınamadı")?.to_string();
+
+    println!(
+        "Oyun {} oluşturuldu: {} oyuncu, {} soru",
+        game_code,
+        options.players,
+        question_ids.len()
+    );
+
+    let ws_url = format!("{}/ws", options.base_url.replacen("http", "ws", 1));
+    let question_ids = Arc::new(question_ids);
+    let barrier = Arc::new(Barrier::new(options.players));
+    let started = Instant::now();
+
+    let tasks: Vec<_> = (0..options.players)
+        .map(|i| {
+            tokio::spawn(run_player(
+                i,
+                ws_url.clone(),
+                game_code.clone(),
+                question_ids.clone(),
+                options.players,
+                barrier.clone(),
+            ))
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        results.push(task.await?);
+    }
+    let elapsed = started.elapsed();
+
+    // Son katılımdan itibaren tam lobi yayınının her oyuncuya ulaşma süresi
+    let last_join = results.iter().filter_map(|m| m.joined_at).max();
+    let broadcast_ms: Vec<f64> = match last_join {
+        Some(last_join) => results
+            .iter()
+            .filter_map(|m| m.full_lobby_at)
+            .map(|t| t.saturating_duration_since(last_join).as_secs_f64() * 1000.0)
+            .collect(),
+        None => Vec::new(),
+    };
+    let total_answers: usize = results.iter().map(|m| m.answer_rtts_ms.len()).sum();
+
+    println!();
+    print_distribution("Katılım (join_success)", results.iter().filter_map(|m| m.join_ms).collect());
+    print_distribution("Lobi yayını gecikmesi", broadcast_ms);
+    print_distribution(
+        "Cevap gidiş-dönüş",
+        results.iter().flat_map(|m| m.answer_rtts_ms.iter().copied()).collect(),
+    );
+    println!(
+        "{:<28} {} cevap, {:.1} cevap/sn, toplam {:.2}s",
+        "Veritabanı verimi",
+        total_answers,
+        total_answers as f64 / elapsed.as_secs_f64(),
+        elapsed.as_secs_f64()
+    );
+
+    let errors: Vec<&String> = results.iter().flat_map(|m| m.errors.iter()).collect();
+    if !errors.is_empty() {
+        println!("\n{} oyuncu hata aldı:", errors.len());
+        for e in errors.iter().take(10) {
+            println!("  {}", e);
+        }
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -1,9 +1,15 @@
 use lazy_static::lazy_static;
+use std::collections::HashMap;
 use std::env;
+use std::net::IpAddr;
+
+use crate::services::tts::TtsProvider;
+use crate::utils::validation::{EmailPolicy, NameCharset};
 
 // Uygulamanın tüm konfigürasyon ayarları
 pub struct Config {
     pub database_url: String,
+    pub read_database_url: String,
     pub server_addr: String,
     pub jwt_secret: String,
     pub jwt_expiration: i64,
@@ -12,13 +18,69 @@ pub struct Config {
     pub email_username: String,
     pub email_password: String,
     pub recaptcha_secret_key: String,
+    pub recaptcha_verify_url: String,
+    pub recaptcha_bypass: bool,
+    pub recaptcha_min_score: f64,
+    pub recaptcha_actions: HashMap<String, Option<f64>>,
     pub frontend_url: String,
+    pub ws_allowed_origins: Vec<String>,
+    pub trusted_proxies: Vec<IpAddr>,
+    pub quota_games_per_day: i64,
+    pub quota_ai_generations_per_month: i64,
+    pub quota_media_storage_mb: i64,
+    pub email_policy: EmailPolicy,
+    pub username_charset: NameCharset,
+    pub guest_claim_window_hours: i64,
+    pub trash_retention_days: i64,
+    pub question_undo_window_minutes: i64,
+    pub reconnect_grace_seconds: u64,
+    pub max_viewers_per_game: usize,
+    pub player_token_ttl_minutes: i64,
+    pub guest_session_ttl_hours: i64,
+    pub fcm_service_account_path: String,
+    pub vapid_private_key_path: String,
+    pub vapid_public_key: String,
+    pub vapid_subject: String,
+    pub admin_username: String,
+    pub admin_email: String,
+    pub admin_password: String,
+    pub password_min_length: usize,
+    pub password_require_uppercase: bool,
+    pub password_require_lowercase: bool,
+    pub password_require_digit: bool,
+    pub password_require_symbol: bool,
+    pub password_min_score: u8,
+    pub password_breach_check: bool,
+    pub password_breach_api_url: String,
+    pub password_reset_max_per_hour: i64,
+    pub email_verification_expiry_hours: i64,
+    pub json_payload_limit_kb: usize,
+    pub payload_limit_kb: usize,
+    pub db_max_connections: u32,
+    pub db_min_connections: u32,
+    pub db_acquire_timeout_secs: u64,
+    pub db_statement_timeout_ms: u64,
+    pub slow_query_threshold_ms: u64,
+    pub media_dir: String,
+    pub media_base_url: String,
+    pub tts_provider: TtsProvider,
+    pub tts_api_url: String,
+    pub tts_api_key: String,
+    pub tts_voice: String,
+    pub export_dir: String,
+    pub export_s3_access_key_id: String,
+    pub export_s3_secret_access_key: String,
+    pub internal_grpc_addr: String,
+    pub internal_api_token: String,
+    pub metrics_addr: String,
 }
 
 impl Config {
     pub fn from_env() -> Self {
         Config {
             database_url: env::var("DATABASE_URL").expect("DATABASE_URL must be set"),
+            // Okuma replikası; sıralama, istatistik ve geçmiş sorguları buraya gider. Boş bırakılırsa ana veritabanı kullanılır
+            read_database_url: env::var("READ_DATABASE_URL").unwrap_or_default(),
             server_addr: env::var("SERVER_ADDR").unwrap_or_else(|_| "0.0.0.0:8080".to_string()),
             jwt_secret: env::var("JWT_SECRET").expect("JWT_SECRET must be set"),
             jwt_expiration: env::var("JWT_EXPIRATION")
@@ -29,12 +91,230 @@ impl Config {
             email_server: env::var("EMAIL_SERVER").expect("EMAIL_SERVER must be set"),
             email_username: env::var("EMAIL_USERNAME").expect("EMAIL_USERNAME must be set"),
             email_password: env::var("EMAIL_PASSWORD").expect("EMAIL_PASSWORD must be set"),
-            recaptcha_secret_key: env::var("RECAPTCHA_SECRET_KEY").expect("RECAPTCHA_SECRET_KEY must be set"),
+            // Atlama modunda anahtar gerekmez, aksi halde load_config içinde kontrol edilir
+            recaptcha_secret_key: env::var("RECAPTCHA_SECRET_KEY").unwrap_or_default(),
+            // Testlerde yerel bir doğrulama sunucusuna yönlendirilebilir
+            recaptcha_verify_url: env::var("RECAPTCHA_VERIFY_URL")
+                .unwrap_or_else(|_| "https://www.google.com/recaptcha/api/siteverify".to_string()),
+            // Yerel geliştirme ve testler için doğrulamayı tamamen atlar
+            recaptcha_bypass: env::var("RECAPTCHA_BYPASS")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse::<bool>()
+                .expect("RECAPTCHA_BYPASS must be true or false"),
+            recaptcha_min_score: env::var("RECAPTCHA_MIN_SCORE")
+                .unwrap_or_else(|_| "0.5".to_string())
+                .parse::<f64>()
+                .expect("RECAPTCHA_MIN_SCORE must be a number"),
+            // Doğrulanacak işlemler ve isteğe bağlı işlem bazlı eşikler, örn. "login,register,password_reset=0.7,guest_join"
+            recaptcha_actions: parse_recaptcha_actions(
+                &env::var("RECAPTCHA_ACTIONS").unwrap_or_else(|_| "register,login,password_reset=0.7,verification_resend,guest_join".to_string()),
+            )
+            .expect("RECAPTCHA_ACTIONS must be a comma separated list of action or action=score"),
             frontend_url: env::var("FRONTEND_URL").expect("FRONTEND_URL must be set"),
+            // WebSocket bağlantısı açabilecek sayfaların kaynakları (virgülle ayrılmış); varsayılan FRONTEND_URL
+            ws_allowed_origins: parse_origins(
+                &env::var("WS_ALLOWED_ORIGINS").unwrap_or_else(|_| env::var("FRONTEND_URL").unwrap_or_default()),
+            ),
+            // İstemci IP'sini Forwarded / X-Forwarded-For başlıklarıyla bildirebilecek ters proxy adresleri
+            // (virgülle ayrılmış); boşsa başlıklar yok sayılır ve bağlantının adresi kullanılır
+            trusted_proxies: parse_trusted_proxies(&env::var("TRUSTED_PROXIES").unwrap_or_default())
+                .expect("TRUSTED_PROXIES must be a comma separated list of IP addresses"),
+            quota_games_per_day: env::var("QUOTA_GAMES_PER_DAY")
+                .unwrap_or_else(|_| "50".to_string())
+                .parse::<i64>()
+                .expect("QUOTA_GAMES_PER_DAY must be a number"),
+            // Sesli okuma seslendirmeleri de yapay zeka üretimi sayılır (her yeni ses dosyası bir üretim)
+            quota_ai_generations_per_month: env::var("QUOTA_AI_GENERATIONS_PER_MONTH")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse::<i64>()
+                .expect("QUOTA_AI_GENERATIONS_PER_MONTH must be a number"),
+            quota_media_storage_mb: env::var("QUOTA_MEDIA_STORAGE_MB")
+                .unwrap_or_else(|_| "500".to_string())
+                .parse::<i64>()
+                .expect("QUOTA_MEDIA_STORAGE_MB must be a number"),
+            email_policy: env::var("EMAIL_POLICY")
+                .unwrap_or_else(|_| "edu_or_allowlist".to_string())
+                .parse::<EmailPolicy>()
+                .expect("EMAIL_POLICY must be one of edu_only, edu_or_allowlist, allowlist, open"),
+            // Kullanıcı adları ve misafir takma adlarında izin verilen karakterler
+            username_charset: env::var("USERNAME_CHARSET")
+                .unwrap_or_else(|_| "turkish".to_string())
+                .parse::<NameCharset>()
+                .expect("USERNAME_CHARSET must be one of ascii, turkish, unicode"),
+            guest_claim_window_hours: env::var("GUEST_CLAIM_WINDOW_HOURS")
+                .unwrap_or_else(|_| "24".to_string())
+                .parse::<i64>()
+                .expect("GUEST_CLAIM_WINDOW_HOURS must be a number"),
+            trash_retention_days: env::var("TRASH_RETENTION_DAYS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse::<i64>()
+                .expect("TRASH_RETENTION_DAYS must be a number"),
+            // Silinen bir sorunun geri alınabileceği süre (dakika)
+            question_undo_window_minutes: env::var("QUESTION_UNDO_WINDOW_MINUTES")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse::<i64>()
+                .expect("QUESTION_UNDO_WINDOW_MINUTES must be a number"),
+            reconnect_grace_seconds: env::var("RECONNECT_GRACE_SECONDS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse::<u64>()
+                .expect("RECONNECT_GRACE_SECONDS must be a number"),
+            // Bir oyuna aynı anda bağlanabilecek en fazla izleyici; 0 izlemeyi kapatır
+            max_viewers_per_game: env::var("MAX_VIEWERS_PER_GAME")
+                .unwrap_or_else(|_| "50".to_string())
+                .parse::<usize>()
+                .expect("MAX_VIEWERS_PER_GAME must be a number"),
+            // REST cevap uç noktası için oyunculara verilen tokenların geçerlilik süresi (dakika)
+            player_token_ttl_minutes: env::var("PLAYER_TOKEN_TTL_MINUTES")
+                .unwrap_or_else(|_| "120".to_string())
+                .parse::<i64>()
+                .expect("PLAYER_TOKEN_TTL_MINUTES must be a number"),
+            // Misafir oturumlarının geçerlilik süresi (saat); süresi dolan misafir yeniden bağlanamaz ve cevap gönderemez
+            guest_session_ttl_hours: env::var("GUEST_SESSION_TTL_HOURS")
+                .unwrap_or_else(|_| "6".to_string())
+                .parse::<i64>()
+                .expect("GUEST_SESSION_TTL_HOURS must be a number"),
+            // Push bildirimleri: boş bırakılan sağlayıcı devre dışı kalır
+            fcm_service_account_path: env::var("FCM_SERVICE_ACCOUNT_PATH").unwrap_or_default(),
+            vapid_private_key_path: env::var("VAPID_PRIVATE_KEY_PATH").unwrap_or_default(),
+            vapid_public_key: env::var("VAPID_PUBLIC_KEY").unwrap_or_default(),
+            vapid_subject: env::var("VAPID_SUBJECT").unwrap_or_else(|_| "mailto:noreply@sorukayisi.com".to_string()),
+            // İlk kurulum: hiç admin yoksa bu bilgilerle bir admin oluşturulur
+            admin_username: env::var("ADMIN_USERNAME").unwrap_or_else(|_| "admin".to_string()),
+            admin_email: env::var("ADMIN_EMAIL").unwrap_or_default(),
+            admin_password: env::var("ADMIN_PASSWORD").unwrap_or_default(),
+            // Şifre politikası: karmaşıklık kuralları varsayılan olarak kapalı, güç eşiği 0-4 arası
+            password_min_length: env::var("PASSWORD_MIN_LENGTH")
+                .unwrap_or_else(|_| "8".to_string())
+                .parse::<usize>()
+                .expect("PASSWORD_MIN_LENGTH must be a number"),
+            password_require_uppercase: env::var("PASSWORD_REQUIRE_UPPERCASE")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse::<bool>()
+                .expect("PASSWORD_REQUIRE_UPPERCASE must be true or false"),
+            password_require_lowercase: env::var("PASSWORD_REQUIRE_LOWERCASE")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse::<bool>()
+                .expect("PASSWORD_REQUIRE_LOWERCASE must be true or false"),
+            password_require_digit: env::var("PASSWORD_REQUIRE_DIGIT")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse::<bool>()
+                .expect("PASSWORD_REQUIRE_DIGIT must be true or false"),
+            password_require_symbol: env::var("PASSWORD_REQUIRE_SYMBOL")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse::<bool>()
+                .expect("PASSWORD_REQUIRE_SYMBOL must be true or false"),
+            password_min_score: env::var("PASSWORD_MIN_SCORE")
+                .unwrap_or_else(|_| "1".to_string())
+                .parse::<u8>()
+                .expect("PASSWORD_MIN_SCORE must be a number"),
+            // Etkinleştirildiğinde yeni şifreler haveibeenpwned üzerinden kontrol edilir
+            password_breach_check: env::var("PASSWORD_BREACH_CHECK")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse::<bool>()
+                .expect("PASSWORD_BREACH_CHECK must be true or false"),
+            password_breach_api_url: env::var("PASSWORD_BREACH_API_URL")
+                .unwrap_or_else(|_| "https://api.pwnedpasswords.com/range/".to_string()),
+            password_reset_max_per_hour: env::var("PASSWORD_RESET_MAX_PER_HOUR")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse::<i64>()
+                .expect("PASSWORD_RESET_MAX_PER_HOUR must be a number"),
+            email_verification_expiry_hours: env::var("EMAIL_VERIFICATION_EXPIRY_HOURS")
+                .unwrap_or_else(|_| "48".to_string())
+                .parse::<i64>()
+                .expect("EMAIL_VERIFICATION_EXPIRY_HOURS must be a number"),
+            // İstek gövdesi sınırları (JSON ve ham gövde); daha büyük gövde gereken rotalar kendi sınırını belirler
+            json_payload_limit_kb: env::var("JSON_PAYLOAD_LIMIT_KB")
+                .unwrap_or_else(|_| "256".to_string())
+                .parse::<usize>()
+                .expect("JSON_PAYLOAD_LIMIT_KB must be a number"),
+            payload_limit_kb: env::var("PAYLOAD_LIMIT_KB")
+                .unwrap_or_else(|_| "1024".to_string())
+                .parse::<usize>()
+                .expect("PAYLOAD_LIMIT_KB must be a number"),
+            // Veritabanı bağlantı havuzu; canlı oyunlarda her cevap birkaç sorgu çalıştırır
+            db_max_connections: env::var("DB_MAX_CONNECTIONS")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse::<u32>()
+                .expect("DB_MAX_CONNECTIONS must be a number"),
+            db_min_connections: env::var("DB_MIN_CONNECTIONS")
+                .unwrap_or_else(|_| "2".to_string())
+                .parse::<u32>()
+                .expect("DB_MIN_CONNECTIONS must be a number"),
+            // Havuzdan bağlantı almak için en fazla beklenecek süre
+            db_acquire_timeout_secs: env::var("DB_ACQUIRE_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse::<u64>()
+                .expect("DB_ACQUIRE_TIMEOUT_SECS must be a number"),
+            // Tek bir sorgunun en uzun çalışma süresi (0: sınırsız)
+            db_statement_timeout_ms: env::var("DB_STATEMENT_TIMEOUT_MS")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse::<u64>()
+                .expect("DB_STATEMENT_TIMEOUT_MS must be a number"),
+            // Bu süreyi aşan sorgular rota ve oyun koduyla birlikte uyarı olarak loglanır (bkz. db::repositories::observe)
+            slow_query_threshold_ms: env::var("SLOW_QUERY_THRESHOLD_MS")
+                .unwrap_or_else(|_| "250".to_string())
+                .parse::<u64>()
+                .expect("SLOW_QUERY_THRESHOLD_MS must be a number"),
+            // Üretilen medya dosyaları (ör. sesli okuma) bu dizinde saklanır ve media_base_url altından sunulur
+            media_dir: env::var("MEDIA_DIR").unwrap_or_else(|_| "media".to_string()),
+            media_base_url: env::var("MEDIA_BASE_URL").unwrap_or_else(|_| "/media".to_string()),
+            // Sesli okuma sağlayıcısı: none (kapalı), google veya http (TTS_API_URL'e metin gönderilir, ses döner)
+            tts_provider: env::var("TTS_PROVIDER")
+                .unwrap_or_else(|_| "none".to_string())
+                .parse::<TtsProvider>()
+                .expect("TTS_PROVIDER must be one of none, google, http"),
+            tts_api_url: env::var("TTS_API_URL")
+                .unwrap_or_else(|_| "https://texttospeech.googleapis.com/v1/text:synthesize".to_string()),
+            tts_api_key: env::var("TTS_API_KEY").unwrap_or_default(),
+            // Boş bırakılırsa sağlayıcı dile göre varsayılan sesi seçer
+            tts_voice: env::var("TTS_VOICE").unwrap_or_default(),
+            // Veri ambarı dışa aktarımı: yerel hedef bu dizine yazar, S3 hedefi bu kimlik bilgileriyle yükler
+            export_dir: env::var("EXPORT_DIR").unwrap_or_else(|_| "exports".to_string()),
+            export_s3_access_key_id: env::var("EXPORT_S3_ACCESS_KEY_ID").unwrap_or_default(),
+            export_s3_secret_access_key: env::var("EXPORT_S3_SECRET_ACCESS_KEY").unwrap_or_default(),
+            // Dahili gRPC API (örn. "127.0.0.1:50051"); boş bırakılırsa sunucu açılmaz
+            internal_grpc_addr: env::var("INTERNAL_GRPC_ADDR").unwrap_or_default(),
+            internal_api_token: env::var("INTERNAL_API_TOKEN").unwrap_or_default(),
+            // Prometheus metrikleri için dahili HTTP adresi (örn. "127.0.0.1:9464"); boş bırakılırsa açılmaz
+            metrics_addr: env::var("METRICS_ADDR").unwrap_or_default(),
         }
     }
 }
 
+// "login,password_reset=0.7" biçimindeki işlem listesini ayrıştır; eşik verilmeyen işlemler için
+// RECAPTCHA_MIN_SCORE kullanılır
+fn parse_recaptcha_actions(spec: &str) -> Result<HashMap<String, Option<f64>>, String> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match entry.split_once('=') {
+            Some((action, score)) => score
+                .trim()
+                .parse::<f64>()
+                .map(|score| (action.trim().to_string(), Some(score)))
+                .map_err(|_| format!("Geçersiz reCAPTCHA eşiği: {}", entry)),
+            None => Ok((entry.to_string(), None)),
+        })
+        .collect()
+}
+
+// Güvenilen proxy adreslerini ayrıştır, örn. "10.0.0.2, 10.0.0.3"
+fn parse_trusted_proxies(spec: &str) -> Result<Vec<IpAddr>, std::net::AddrParseError> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|addr| !addr.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+// Kaynak listesini karşılaştırmaya uygun biçime getir: "https://ornek.com/" -> "https://ornek.com"
+fn parse_origins(spec: &str) -> Vec<String> {
+    spec.split(',')
+        .map(|origin| origin.trim().trim_end_matches('/').to_ascii_lowercase())
+        .filter(|origin| !origin.is_empty())
+        .collect()
+}
+
 lazy_static! {
     pub static ref CONFIG: Config = Config::from_env();
 }
@@ -48,4 +328,13 @@ pub fn load_config() {
     // Kritik değişkenleri kontrol et
     let _ = &CONFIG.database_url;
     let _ = &CONFIG.jwt_secret;
+    if !CONFIG.recaptcha_bypass && CONFIG.recaptcha_secret_key.is_empty() {
+        panic!("RECAPTCHA_SECRET_KEY must be set (or RECAPTCHA_BYPASS=true for local development)");
+    }
+    if !CONFIG.internal_grpc_addr.is_empty() && CONFIG.internal_api_token.is_empty() {
+        panic!("INTERNAL_API_TOKEN must be set when INTERNAL_GRPC_ADDR is set");
+    }
+    if !CONFIG.metrics_addr.is_empty() && CONFIG.internal_api_token.is_empty() {
+        panic!("INTERNAL_API_TOKEN must be set when METRICS_ADDR is set");
+    }
 }
\ No newline at end of file
@@ -1,8 +1,4 @@
 pub mod models;
 pub mod pool;
-pub mod schema;
-
-// Modül dışında kullandığımız özellikleri burada export ediyoruz
-// Ancak create_pool ve DbPool şu anda kullanılmadığı için yorum satırına alıyoruz
-// pub use pool::create_pool;
-// pub use pool::DbPool;
\ No newline at end of file
+pub mod repositories;
+pub mod schema;
\ No newline at end of file
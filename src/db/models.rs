@@ -4,6 +4,13 @@ use sqlx::FromRow;
 use std::fmt;
 use std::collections::HashMap;
 
+use crate::services::accommodations::Accommodations;
+use crate::services::cosmetics::EarnedBadge;
+use crate::services::guest_ip::SharedIpGroup;
+use crate::services::progression::XpAward;
+use crate::services::seasons::RatingChange;
+use crate::services::tournament::Standing;
+
 // Kullanıcı rolleri
 #[derive(Debug, Serialize, Deserialize, sqlx::Type, Clone, PartialEq)]
 #[sqlx(type_name = "VARCHAR", rename_all = "lowercase")]
@@ -52,6 +59,7 @@ pub struct CreateUserDto {
     pub email: String,
     pub password: String,
     pub role: UserRole,
+    pub invite_code: Option<String>, // Öğretmen davet kodu (varsa rol öğrenci olarak atanır)
 }
 
 // Kullanıcı giriş DTO
@@ -59,14 +67,28 @@ pub struct CreateUserDto {
 pub struct LoginDto {
     pub email: String,
     pub password: String,
+    // Doğrulama X-Recaptcha-Token başlığıyla yapılır; API anahtarıyla gelen istemciler bu alanı göndermez
+    #[serde(default)]
     pub recaptcha_token: String,
 }
 
+// Şifre gücü kontrolü DTO (kayıt formundaki güç göstergesi için)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PasswordStrengthDto {
+    pub password: String,
+    pub username: Option<String>,
+    pub email: Option<String>,
+}
+
 // JWT Claims
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
     pub sub: String, // Kullanıcı ID
     pub role: String, // Kullanıcı rolü
+    pub username: String, // Görünen ad (kullanıcı tablosunu tekrar sorgulamamak için)
+    pub organization_id: Option<i32>, // Bağlı olduğu kurum
+    pub is_org_admin: bool, // Kurum admini mi
+    pub ver: i32, // Token sürümü (users.token_version ile eşleşmeli)
     pub exp: usize, // Son kullanma tarihi
 }
 
@@ -201,6 +223,7 @@ pub struct ApproveUserDto {
 pub struct CreateQuestionSetDto {
     pub title: String,
     pub description: Option<String>,
+    pub language: Option<String>, // Soruların asıl dili (varsayılan: "tr")
 }
 
 // Soru Oluşturma DTO
@@ -216,19 +239,70 @@ pub struct CreateQuestionDto {
     pub points: Option<i32>,     // Varsayılan: 100
     pub time_limit: Option<i32>, // Varsayılan: 30 saniye
     pub position: i32,
+    pub host_notes: Option<String>, // Yalnızca host'a gösterilen konuşma notları ve açıklamalar
+}
+
+// Soru kısmi güncelleme DTO (PATCH); gönderilmeyen alanlar değişmez
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PatchQuestionDto {
+    pub question_text: Option<String>,
+    pub option_a: Option<String>,
+    pub option_b: Option<String>,
+    pub option_c: Option<String>,
+    pub option_d: Option<String>,
+    pub correct_option: Option<String>,
+    pub points: Option<i32>,
+    pub time_limit: Option<i32>,
+    pub position: Option<i32>,
+    pub host_notes: Option<String>, // Boş metin notları siler
+}
+
+// Toplu soru işlemi; "op" alanı işlemi belirler. Oluşturmada alanlar PATCH ile aynıdır (metin, şıklar,
+// doğru cevap ve sıra zorunlu), güncellemede yalnızca gönderilen alanlar değişir.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BulkQuestionOperation {
+    Create(PatchQuestionDto),
+    Update {
+        id: i32,
+        #[serde(flatten)]
+        fields: PatchQuestionDto,
+    },
+    Delete {
+        id: i32,
+    },
+}
+
+// Toplu soru işlemleri DTO
+#[derive(Debug, Deserialize, Clone)]
+pub struct BulkQuestionsDto {
+    pub operations: Vec<BulkQuestionOperation>,
 }
 
 // Oyun Oluşturma DTO
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CreateGameDto {
     pub question_set_id: i32,
+    #[serde(default)]
+    pub question_set_ids: Vec<i32>,      // Ana setin ardından oyunda birleştirilecek diğer setler
+    pub competitive: Option<bool>,       // Varsayılan: true
+    pub max_guests_per_ip: Option<i32>,  // Aynı IP'den en fazla misafir sayısı (boş: sınırsız)
+    pub option_labels: Option<String>,   // letters, shapes veya colors (varsayılan: letters)
+    pub scene_cues: Option<serde_json::Value>, // Sahne -> müzik/animasyon işareti
+    pub roster_class_id: Option<i32>,    // Yalnızca bu sınıfın listesindeki öğrenciler katılabilir
+    pub grading: Option<serde_json::Value>, // Not ölçeği (varsayılan: {"scale": "percent"})
+    pub question_sample: Option<serde_json::Value>, // Rastgele soru seçimi: {"count": 10, "stratify_by": "topic"}
 }
 
 // Oyun Katılım DTO
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct JoinGameDto {
-    pub game_code: String,
+    #[serde(default)]
+    pub game_code: String, // Oyun kodu veya takma adı; katılım bağlantısıyla gelindiyse boş olabilir
+    pub join_token: Option<String>, // Kişiye özel katılım bağlantısındaki token
     pub nickname: Option<String>, // Misafir oyuncular için
+    pub avatar: Option<String>,
+    pub locale: Option<String>, // Soruların gösterileceği tercih edilen dil (ör. "en", "en-US")
 }
 
 // Cevap Gönderme DTO
@@ -239,9 +313,19 @@ pub struct SubmitAnswerDto {
     pub response_time_ms: i32,
 }
 
-// WebSocket Mesaj DTO
+// Soru seti önizlemesinde cevap DTO (cevap süresi sunucuda ölçülür)
 #[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(tag = "type")]
+pub struct PreviewAnswerDto {
+    pub question_id: i32,
+    pub answer: String,
+}
+
+// WebSocket Mesaj DTO ("type" etiketi istemciye giden mesaj tipidir, örn. "lobby_update").
+// Kuyruktan giden yayınlar bu tipten doğrudan JSON'a veya MessagePack'e kodlanır (bkz. ws_codec).
+// "seq" alanı olay kaydındaki sıra numarasıdır (bkz. game_events); istemci en son aldığı numarayı yeniden
+// bağlanırken göndererek yalnızca kaçırdığı olayları alır. Kayıt yazılamadıysa gönderilmez.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum WebSocketMessage {
     // Lobby mesajları
     JoinLobby {
@@ -259,44 +343,129 @@ pub enum WebSocketMessage {
         game_code: String,
         players: Vec<PlayerInfo>,
     },
+    PlayerCount {
+        game_code: String,
+        count: usize,
+    },
     GameStarted {
         game_code: String,
         message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        seq: Option<i32>,
     },
     
-    // Soru mesajları
-    QuestionStart {
-        question_id: i32,
-        question_text: String,
-        options: HashMap<String, String>,
-        time_limit: i32,
-        question_number: i64,
-        total_questions: i64,
-        correct_option: Option<String>, // Sadece öğretmen için
+    // Host'a giden oyuncu durumları
+    PlayerPresence {
+        game_code: String,
+        player_id: i32,
+        nickname: String,
+        status: String,
+    },
+    PlayerAnswered {
+        player_id: i32,
+        nickname: String,
+        #[serde(flatten)]
+        progress: AnswerProgress,
     },
+    PlayerAnswerRetracted {
+        player_id: i32,
+        nickname: String,
+        #[serde(flatten)]
+        progress: AnswerProgress,
+    },
+    GuestIpWarning {
+        game_code: String,
+        groups: Vec<SharedIpGroup>,
+    },
+    
+    // Soru mesajları
+    QuestionStart(QuestionMessage),
     SubmitAnswer {
         question_id: i32,
         answer: String,
         response_time_ms: i32,
     },
+    // Cevap soru süresi dolana kadar geri alınabildiğinden doğruluk ve puan burada gönderilmez
     AnswerReceived {
         question_id: i32,
         your_answer: String,
-        is_correct: bool,
-        points_earned: i32,
         message: String,
     },
+    // Süre uzatıldığında oyuncuya (kendi ek süresiyle) ve host'a gönderilen yeni bitiş zamanı
+    TimerExtended {
+        question_id: Option<i32>,
+        added_seconds: u64,
+        time_limit: u64,
+        deadline: i64,
+        server_time: i64,
+    },
     QuestionEnd {
         question_id: i32,
         correct_option: String,
         leaderboard: Vec<LeaderboardEntry>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        seq: Option<i32>,
     },
     
-    // Oyun sonu
+    // Host'un medya ve sahne komutları
+    ShowMedia {
+        game_code: String,
+        position_ms: i64,
+        server_time: i64,
+        start_at: i64,
+    },
+    PauseMedia {
+        game_code: String,
+        position_ms: i64,
+        server_time: i64,
+    },
+    Scene {
+        game_code: String,
+        scene: String,
+        cue: serde_json::Value,
+        start_at: i64,
+        server_time: i64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        seq: Option<i32>,
+    },
+    
+    // Ani ölüm turu
+    SuddenDeathStart {
+        finalists: Vec<LeaderboardEntry>,
+        message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        seq: Option<i32>,
+    },
+    SuddenDeathElimination {
+        question_id: i32,
+        eliminated: Vec<PlayerRef>,
+        remaining: Vec<PlayerRef>,
+        winner: Option<PlayerRef>,
+        message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        seq: Option<i32>,
+    },
+    
+    // Oyun sonu; oyun tamamlandıysa sonuçlarla, yarıda kesildiyse nedeniyle gönderilir
     GameEnd {
-        final_leaderboard: Vec<LeaderboardEntry>,
-        player_stats: Vec<PlayerStatistics>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+        #[serde(flatten)]
+        results: Option<GameResults>,
         message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        seq: Option<i32>,
+    },
+    LevelUp {
+        player_id: i32,
+        level: i32,
+        total_xp: i64,
+    },
+    TournamentStandings {
+        tournament_id: i32,
+        round: i32,
+        advance_count: Option<i32>,
+        standings: Vec<Standing>,
     },
     
     // Yeniden bağlanma
@@ -329,12 +498,94 @@ pub enum WebSocketMessage {
     },
 }
 
+impl WebSocketMessage {
+    // Yalnızca en güncel hali önemli olan anlık durum mesajlarının tipi; gönderilmemiş eski hali kuyrukta
+    // yenisiyle değiştirilir (bkz. ws_outbox). İkisi de lobinin tam halini taşır.
+    pub fn coalesce_kind(&self) -> Option<&'static str> {
+        match self {
+            WebSocketMessage::LobbyUpdate { .. } => Some("lobby_update"),
+            WebSocketMessage::PlayerCount { .. } => Some("player_count"),
+            _ => None,
+        }
+    }
+}
+
+// Oyunculara gösterilen soru. Zamanlar milisaniye cinsinden Unix zamanıdır; istemciler geri sayımı kendi
+// saatlerine göre değil deadline ile server_time arasındaki farka göre başlatır
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuestionMessage {
+    pub question_id: i32,
+    pub question_text: String,
+    pub options: HashMap<String, String>,
+    pub time_limit: Option<i32>,
+    pub question_number: i64,
+    pub total_questions: i64,
+    pub started_at: i64,
+    pub deadline: i64,
+    #[serde(default)]
+    pub server_time: i64,
+    pub seq: Option<i32>,
+    pub audio_url: Option<String>,
+    #[serde(default)]
+    pub option_labels: serde_json::Value,
+    // Çeviri gönderildiyse dili
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    // Oyuncunun ek süre ve ekran okuyucu düzenlemeleri
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accommodations: Option<Accommodations>,
+    // Sadece öğretmen için
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correct_option: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host_notes: Option<String>,
+}
+
+impl QuestionMessage {
+    // Şıklar istemciye harf etiketleriyle gönderilir
+    pub fn option_map(options: &[String; 4]) -> HashMap<String, String> {
+        ["A", "B", "C", "D"]
+            .into_iter()
+            .zip(options.iter())
+            .map(|(label, text)| (label.to_string(), text.clone()))
+            .collect()
+    }
+}
+
+// Host'a gönderilen cevap durumu: cevaplaması beklenen oyunculardan kaç kişinin cevapladığı ve bekleyenler
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AnswerProgress {
+    pub question_id: i32,
+    pub answered_count: usize,
+    pub expected_count: usize,
+    pub waiting: Vec<PlayerRef>,
+}
+
+// Tamamlanan oyunun oyunculara gönderilen sonuçları
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GameResults {
+    pub final_leaderboard: Vec<LeaderboardEntry>,
+    pub player_stats: Vec<serde_json::Value>,
+    pub xp_awards: Vec<XpAward>,
+    pub badges_earned: Vec<EarnedBadge>,
+    pub rating_changes: Vec<RatingChange>,
+    pub sudden_death_winner: Option<PlayerRef>,
+}
+
+// Mesajlarda adıyla anılan oyuncu
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlayerRef {
+    pub player_id: i32,
+    pub nickname: Option<String>,
+}
+
 // WebSocket için basitleştirilmiş oyuncu bilgisi
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PlayerInfo {
     pub player_id: i32,
     pub nickname: String,
     pub is_guest: bool,
+    pub avatar: Option<String>,
 }
 
 // Liderlik tablosu girişi
@@ -344,6 +595,7 @@ pub struct LeaderboardEntry {
     pub nickname: String,
     pub score: i32,
     pub is_guest: bool,
+    pub avatar: Option<String>,
 }
 
 // Oyuncu istatistikleri
@@ -356,6 +608,7 @@ pub struct PlayerStatistics {
     pub correct: i64,
     pub accuracy: f64,
     pub avg_response_time_ms: Option<i64>,
+    pub percent_of_max: f64, // Puanın en yüksek olası puana oranı (%)
 }
 
 // Soru istatistiği
@@ -369,6 +622,18 @@ pub struct QuestionStatistics {
     pub accuracy: f64,
     pub avg_response_time_ms: Option<f64>,
     pub difficulty_score: f64, // 0-10 arası, 10 en zor
+    pub median_response_time_ms: Option<i64>,
+    pub time_to_first_answer_ms: Option<i64>, // Soru gösterildikten ilk cevaba kadar (sunucu saatiyle)
+    pub timed_out_count: i64,                 // Süre dolduğu için boş geçilen cevaplar
+    pub response_time_histogram: Vec<ResponseTimeBucket>,
+}
+
+// Cevap süresi dağılımında bir aralık [from_ms, to_ms)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResponseTimeBucket {
+    pub from_ms: i64,
+    pub to_ms: i64,
+    pub count: i64,
 }
 
 // Oyun istatistikleri
@@ -383,4 +648,371 @@ pub struct GameStatistics {
     pub avg_score: f64,
     pub top_players: Vec<LeaderboardEntry>,
     pub question_stats: Vec<QuestionStatistics>,
+}
+
+// İçerik şikayeti oluşturma DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateReportDto {
+    pub target_type: String, // "question_set" veya "nickname"
+    pub target_id: i32,      // Soru seti ID'si veya oyuncu ID'si
+    pub reason: String,
+}
+
+// Şikayet sonuçlandırma DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResolveReportDto {
+    pub action: String, // "dismiss", "hide" veya "delete"
+    pub note: Option<String>,
+}
+
+// Şikayet listeleme filtresi
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReportQuery {
+    pub status: Option<String>,
+}
+
+// Denetim kaydı listeleme filtresi
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuditLogQuery {
+    pub user_id: Option<i32>,
+    pub action: Option<String>,
+    pub limit: Option<i64>,
+}
+
+// Kurum oluşturma DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateOrganizationDto {
+    pub name: String,
+    pub slug: String,
+}
+
+// Kullanıcıyı kuruma atama DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AssignOrganizationMemberDto {
+    pub user_id: i32,
+    pub is_org_admin: Option<bool>,
+}
+
+// Soru seti görünürlük güncelleme DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpdateVisibilityDto {
+    pub visibility: String, // "private", "organization" veya "public"
+}
+
+// Sınıf oluşturma DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateClassDto {
+    pub name: String,
+    pub shared_with_org: Option<bool>,
+}
+
+// Sınıfa üye ekleme DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AddClassMemberDto {
+    pub user_id: i32,
+}
+
+// Sınıf listesi içe aktarma sorgu parametreleri
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RosterImportQuery {
+    pub send_emails: Option<bool>, // Davet bağlantılarını öğrencilere e-postayla gönder
+}
+
+// Yoklama raporu sorgu parametreleri (tarihler YYYY-MM-DD, bitiş günü dahil)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AttendanceQuery {
+    pub from: Option<chrono::NaiveDate>,
+    pub to: Option<chrono::NaiveDate>,
+    pub format: Option<String>, // "json" (varsayılan) veya "csv"
+}
+
+// Platform istatistikleri zaman serisi sorgu parametreleri (tarihler YYYY-MM-DD, bitiş günü dahil)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StatsTimeseriesQuery {
+    pub from: Option<chrono::NaiveDate>,
+    pub to: Option<chrono::NaiveDate>,
+}
+
+// Gömülebilir liderlik tablosu sorgu parametreleri
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WidgetLeaderboardQuery {
+    pub limit: Option<i64>, // Gösterilecek oyuncu sayısı (varsayılan 10, en fazla 50)
+}
+
+// Dışa aktarma biçimi sorgu parametresi
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportFormatQuery {
+    pub format: Option<String>, // "json" (varsayılan) veya "csv"
+}
+
+// Kurum e-posta alan adı ekleme DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AddEmailDomainDto {
+    pub domain: String,
+}
+
+// Kayıt e-posta politikası güncelleme DTO (None: konfigürasyondaki varsayılana dön)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpdateEmailPolicyDto {
+    pub policy: Option<String>,
+}
+
+// Güvenilir istemci API anahtarı oluşturma DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateApiKeyDto {
+    pub name: String,
+    pub scopes: Vec<String>,
+}
+
+// Engel listesine kayıt ekleme DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateBlocklistEntryDto {
+    pub kind: String, // "ip", "email_domain" veya "user"
+    pub value: String,
+    pub reason: Option<String>,
+    pub expires_in_hours: Option<i64>, // Boşsa süresiz
+}
+
+// Davet kodu oluşturma DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateInvitationDto {
+    pub class_id: Option<i32>,
+    pub max_uses: Option<i32>,
+    pub expires_in_hours: Option<i64>,
+}
+
+// Misafir oyuncu sonuçlarını hesaba aktarma DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClaimGuestDto {
+    pub session_id: String,
+}
+
+// Profil avatarı güncelleme DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpdateAvatarDto {
+    pub avatar: String,
+}
+
+// Sezon sıralaması sorgu parametreleri
+#[derive(Debug, Deserialize)]
+pub struct SeasonLeaderboardQuery {
+    pub scope: Option<String>, // "organization" (varsayılan, kurumu olanlar için) veya "global"
+}
+
+// Turnuva oluşturma DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateTournamentDto {
+    pub name: String,
+}
+
+// Turnuvaya tur ekleme DTO; oyun lobide beklemeli
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AddTournamentRoundDto {
+    pub game_code: String,
+    pub advance_count: Option<i32>, // Tur sonunda yükselecek katılımcı sayısı; boşsa kimse elenmez
+}
+
+// Push bildirim aboneliği kaydetme DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RegisterPushSubscriptionDto {
+    pub platform: String, // "fcm" veya "webpush"
+    pub token: String,    // FCM cihaz tokenı veya Web Push uç noktası
+    pub p256dh: Option<String>,
+    pub auth: Option<String>,
+}
+
+// Push bildirim aboneliği silme DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RemovePushSubscriptionDto {
+    pub token: String,
+}
+
+// Bildirim listesi sorgu parametreleri
+#[derive(Debug, Deserialize)]
+pub struct NotificationListQuery {
+    pub unread_only: Option<bool>,
+    pub limit: Option<i64>,
+    pub before_id: Option<i64>, // sayfalama için: bu id'den eski bildirimler
+}
+
+// Soru setine ortak editör ekleme DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AddEditorDto {
+    pub user_id: i32,
+    pub permission: Option<String>, // "read" veya "write" (varsayılan: write)
+}
+
+// Herkese açık soru seti arama parametreleri
+#[derive(Debug, Deserialize)]
+pub struct PublicQuestionSetQuery {
+    pub search: Option<String>,
+    pub sort: Option<String>, // "recent" (varsayılan) veya "top_rated"
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+// Soru seti getirme parametreleri
+#[derive(Debug, Deserialize)]
+pub struct QuestionSetQuery {
+    pub summary: Option<bool>, // true ise sorular dahil edilmez, yalnızca soru sayısı döner
+}
+
+// Soru setindeki soruları listeleme parametreleri
+#[derive(Debug, Deserialize)]
+pub struct QuestionListQuery {
+    pub sort: Option<String>,  // "position" (varsayılan) veya "difficulty"
+    pub order: Option<String>, // "asc" veya "desc" (varsayılan: sırada asc, zorlukta desc)
+    pub limit: Option<i64>,
+    pub cursor: Option<String>, // önceki sayfanın next_cursor değeri
+}
+
+// Soru seti puanlama DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RateQuestionSetDto {
+    pub rating: i16, // 1-5
+}
+
+// Soru seti yorumu DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateCommentDto {
+    pub body: String,
+}
+
+// Yorum gizleme DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HideCommentDto {
+    pub hidden: bool,
+}
+
+// Pazaryeri listeleme sorgu parametreleri
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MarketplaceQuery {
+    pub category: Option<String>, // Kategori kısa adı
+    pub featured: Option<bool>,   // Yalnızca öne çıkan setler
+    pub search: Option<String>,
+    pub sort: Option<String>, // "popular" (varsayılan), "recent" veya "top_rated"
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+// Pazaryeri inceleme kuyruğu sorgu parametreleri
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MarketplaceSubmissionsQuery {
+    pub status: Option<String>, // Varsayılan "pending"
+    pub flagged: Option<bool>,  // Yalnızca benzerlik taramasında işaretlenenler
+}
+
+// Soru setini pazaryerine gönderme DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SubmitToMarketplaceDto {
+    pub question_set_id: i32,
+    pub category_id: i32,
+}
+
+// Pazaryeri kaydı inceleme DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReviewListingDto {
+    pub approve: bool,
+    pub note: Option<String>, // Reddedilen setlerde yazara gösterilecek gerekçe
+}
+
+// Pazaryeri kaydını öne çıkarma DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FeatureListingDto {
+    pub featured: bool,
+}
+
+// Pazaryeri kategorisi oluşturma DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateCategoryDto {
+    pub slug: String,
+    pub name: String,
+}
+
+// Arşivlenmiş kayıtları listeye dahil etme parametresi
+#[derive(Debug, Deserialize)]
+pub struct IncludeArchivedQuery {
+    pub include_archived: Option<bool>,
+}
+
+// Arama sorgu parametreleri
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    pub limit: Option<i64>, // Tür başına en fazla sonuç (varsayılan: 10)
+}
+
+// Oyun ayarlarını güncelleme DTO (yalnızca lobideyken)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpdateGameSettingsDto {
+    pub competitive: Option<bool>,
+    pub max_guests_per_ip: Option<i32>, // 0: sınırı kaldır
+    pub option_labels: Option<String>,  // letters, shapes veya colors
+    pub scene_cues: Option<serde_json::Value>, // Gönderilirse tüm sahne işaretlerinin yerini alır
+    pub roster_class_id: Option<i32>,   // 0: sınıf listesi sınırını kaldır
+    pub grading: Option<serde_json::Value>, // Not ölçeği (yüzde veya not bantları)
+    pub question_sample: Option<serde_json::Value>, // {"count": 0} gönderilirse tüm sorular sorulur
+}
+
+// Oyun kodu takma adı DTO (ör. "BIO101-QUIZ3")
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GameAliasDto {
+    pub alias: String,
+}
+
+// Oyun planlama DTO; null planı kaldırır
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScheduleGameDto {
+    pub scheduled_at: Option<DateTime<Utc>>,
+}
+
+// Meydan okuma daveti DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChallengeDto {
+    pub username: String,
+}
+
+// Ani ölüm turu başlatma DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SuddenDeathDto {
+    pub top_n: i32, // Finale kalacak oyuncu sayısı (güncel puana göre)
+}
+
+// Müfredat konusu oluşturma/güncelleme DTO (parent_id boşsa ünite)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TopicDto {
+    pub parent_id: Option<i32>,
+    pub code: Option<String>,
+    pub name: String,
+    pub description: Option<String>,
+    pub position: Option<i32>,
+}
+
+// Sorunun konu etiketlerini belirleme DTO (mevcut etiketlerin yerine geçer)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuestionTopicsDto {
+    pub topic_ids: Vec<i32>,
+}
+
+// Soru seti dili güncelleme DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpdateLanguageDto {
+    pub language: String,
+}
+
+// Soru çevirisi DTO (dil kodu yoldan alınır)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuestionTranslationDto {
+    pub question_text: String,
+    pub option_a: String,
+    pub option_b: String,
+    pub option_c: String,
+    pub option_d: String,
+}
+
+// Sınıf üyesinin erişilebilirlik düzenlemeleri DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpdateAccommodationsDto {
+    pub time_multiplier: f64, // 1.0 (ek süre yok) - 3.0
+    pub screen_reader: bool,
 }
\ No newline at end of file
@@ -1,23 +1,113 @@
-use sqlx::postgres::{PgPool, PgPoolOptions};
-use std::sync::Arc;
+use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions};
+use serde::Serialize;
+use std::str::FromStr;
+use std::time::Duration;
 use crate::config::CONFIG;
-use log::info;
+use log::{error, info, warn};
 
-pub type DbPool = Arc<PgPool>;
+// Bağlantı havuzunun anlık kullanımı
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolStats {
+    pub size: u32,
+    pub idle: usize,
+    pub in_use: u32,
+    pub max: u32,
+}
+
+impl PoolStats {
+    // Havuzdaki tüm bağlantılar kullanımda; yeni sorgular bağlantı beklemek zorunda
+    pub fn is_saturated(&self) -> bool {
+        self.in_use >= self.max
+    }
+}
 
-pub async fn create_pool() -> DbPool {
-    let pool = PgPoolOptions::new()
-        .max_connections(10)
-        .connect(&CONFIG.database_url)
+// Ana veritabanı ve okuma replikası için ortak bağlantı ayarları
+fn connect_options(url: &str) -> Result<PgConnectOptions, sqlx::Error> {
+    let mut options = PgConnectOptions::from_str(url)?;
+    
+    // Uzun süren sorgular sunucu tarafında iptal edilir, bağlantı havuza geri döner
+    if CONFIG.db_statement_timeout_ms > 0 {
+        options = options.options([("statement_timeout", CONFIG.db_statement_timeout_ms.to_string())]);
+    }
+    
+    Ok(options)
+}
+
+fn pool_options() -> PgPoolOptions {
+    PgPoolOptions::new()
+        .max_connections(CONFIG.db_max_connections)
+        .min_connections(CONFIG.db_min_connections)
+        .acquire_timeout(Duration::from_secs(CONFIG.db_acquire_timeout_secs))
+}
+
+pub async fn create_pool() -> PgPool {
+    let options = connect_options(&CONFIG.database_url)
+        .expect("DATABASE_URL geçerli bir bağlantı adresi değil");
+    
+    let pool = pool_options()
+        .connect_with(options)
         .await
         .expect("Veritabanına bağlanılamadı");
     
-    info!("Veritabanı bağlantısı başarıyla kuruldu");
+    info!(
+        "Veritabanı bağlantısı başarıyla kuruldu (en fazla {} bağlantı)",
+        CONFIG.db_max_connections
+    );
     
     // Veritabanı şemasını kontrol et
     check_database_schema(&pool).await;
     
-    Arc::new(pool)
+    pool
+}
+
+// Okuma replikası havuzu (READ_DATABASE_URL). Tanımlı değilse veya bağlanılamazsa None döner ve okuma
+// sorguları ana veritabanında çalışmaya devam eder; replika sorunu sunucunun açılmasını engellemez.
+pub async fn create_read_pool() -> Option<PgPool> {
+    if CONFIG.read_database_url.is_empty() {
+        return None;
+    }
+    
+    let options = match connect_options(&CONFIG.read_database_url) {
+        Ok(options) => options,
+        Err(e) => {
+            error!("READ_DATABASE_URL geçerli bir bağlantı adresi değil: {}", e);
+            return None;
+        }
+    };
+    
+    match pool_options().connect_with(options).await {
+        Ok(pool) => {
+            info!("Okuma replikası bağlantısı kuruldu");
+            Some(pool)
+        }
+        Err(e) => {
+            error!("Okuma replikasına bağlanılamadı, okumalar ana veritabanından yapılacak: {}", e);
+            None
+        }
+    }
+}
+
+pub fn pool_stats(pool: &PgPool) -> PoolStats {
+    let size = pool.size();
+    let idle = pool.num_idle();
+    
+    PoolStats {
+        size,
+        idle,
+        in_use: size.saturating_sub(idle as u32),
+        max: CONFIG.db_max_connections,
+    }
+}
+
+// Havuz doluysa uyar; düzenli olarak çağrılır
+pub fn warn_if_saturated(pool: &PgPool) {
+    let stats = pool_stats(pool);
+    if stats.is_saturated() {
+        warn!(
+            "Veritabanı bağlantı havuzu dolu: {}/{} bağlantı kullanımda, DB_MAX_CONNECTIONS artırılabilir",
+            stats.in_use, stats.max
+        );
+    }
 }
 
 async fn check_database_schema(pool: &PgPool) {
@@ -0,0 +1,107 @@
+use chrono::Utc;
+use sqlx::{Pool, Postgres};
+use std::future::Future;
+
+use super::observe;
+use crate::db::models::GameStatus;
+
+// Oyun akışının ihtiyaç duyduğu oyun alanları
+#[derive(Debug, Clone)]
+pub struct GameRecord {
+    pub id: i32,
+    pub host_id: i32,
+    pub status: String,
+    pub current_question: Option<i32>,
+    pub question_set_id: i32,
+    pub option_labels: String,
+}
+
+pub trait GameRepo {
+    fn find_by_code(&self, code: &str) -> impl Future<Output = Result<Option<GameRecord>, sqlx::Error>> + Send;
+
+    // Lobideki oyunu başlat; oyun lobide değilse false döner
+    fn start(&self, game_id: i32) -> impl Future<Output = Result<bool, sqlx::Error>> + Send;
+
+    fn set_current_question(&self, game_id: i32, position: i32) -> impl Future<Output = Result<(), sqlx::Error>> + Send;
+
+    fn complete(&self, game_id: i32) -> impl Future<Output = Result<(), sqlx::Error>> + Send;
+}
+
+pub struct PgGameRepo<'a> {
+    pool: &'a Pool<Postgres>,
+}
+
+impl<'a> PgGameRepo<'a> {
+    pub fn new(pool: &'a Pool<Postgres>) -> Self {
+        PgGameRepo { pool }
+    }
+}
+
+impl GameRepo for PgGameRepo<'_> {
+    async fn find_by_code(&self, code: &str) -> Result<Option<GameRecord>, sqlx::Error> {
+        let game = observe(
+            "games.find_by_code",
+            sqlx::query!(
+                "SELECT id, host_id, status, current_question, question_set_id, option_labels FROM games WHERE code = $1",
+                code
+            )
+            .fetch_optional(self.pool),
+        )
+        .await?;
+
+        Ok(game.map(|g| GameRecord {
+            id: g.id,
+            host_id: g.host_id,
+            status: g.status,
+            current_question: g.current_question,
+            question_set_id: g.question_set_id,
+            option_labels: g.option_labels,
+        }))
+    }
+
+    async fn start(&self, game_id: i32) -> Result<bool, sqlx::Error> {
+        // Durum koşulu sorguda da kontrol edilir; aynı anda gelen iki başlatma isteğinden yalnızca biri geçer
+        let started = observe(
+            "games.start",
+            sqlx::query!(
+                r#"
+                UPDATE games SET status = $1, started_at = $2, current_question = NULL
+                WHERE id = $3 AND status = 'lobby'
+                "#,
+                GameStatus::Active.to_string().to_lowercase(),
+                Utc::now(),
+                game_id
+            )
+            .execute(self.pool),
+        )
+        .await?;
+
+        Ok(started.rows_affected() > 0)
+    }
+
+    async fn set_current_question(&self, game_id: i32, position: i32) -> Result<(), sqlx::Error> {
+        observe(
+            "games.set_current_question",
+            sqlx::query!("UPDATE games SET current_question = $1 WHERE id = $2", position, game_id)
+                .execute(self.pool),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn complete(&self, game_id: i32) -> Result<(), sqlx::Error> {
+        observe(
+            "games.complete",
+            sqlx::query!(
+                "UPDATE games SET status = 'completed', ended_at = $1 WHERE id = $2",
+                Utc::now(),
+                game_id
+            )
+            .execute(self.pool),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
\ No newline at end of file
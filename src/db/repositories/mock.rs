@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+
+use super::game::{GameRecord, GameRepo};
+use super::player::{LobbyPlayer, NewAnswer, PlayerRepo, SessionPlayer};
+use super::question::{AnswerKey, QuestionRecord, QuestionRepo};
+use crate::db::models::LeaderboardEntry;
+
+// Testlerde kullanılan bellek içi depolar; veritabanı olmadan servis mantığını çalıştırır
+
+type RecordHook = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+pub struct MockPlayerRepo {
+    pub players: Mutex<Vec<SessionPlayer>>,
+    pub answers: Mutex<Vec<NewAnswer>>,
+    // Sıradaki bu kadar cevap yazma denemesi hata döndürür
+    pub failing_writes: AtomicU32,
+    // Cevaplar yazılırken (kayıt tamamlanmadan) bir kez çalışır
+    pub on_record: Mutex<Option<RecordHook>>,
+    pub sudden_death_places: Mutex<Vec<i32>>,
+}
+
+impl MockPlayerRepo {
+    pub fn fail_next_writes(&self, count: u32) {
+        self.failing_writes.store(count, Ordering::SeqCst);
+    }
+
+    pub fn while_recording(&self, hook: impl FnOnce() + Send + 'static) {
+        *self.on_record.lock().unwrap() = Some(Box::new(hook));
+    }
+
+    pub fn recorded(&self) -> Vec<(i32, i32)> {
+        self.answers.lock().unwrap().iter().map(|a| (a.player_id, a.question_id)).collect()
+    }
+}
+
+impl PlayerRepo for MockPlayerRepo {
+    async fn find_active_by_session(&self, session_id: &str) -> Result<Option<SessionPlayer>, sqlx::Error> {
+        // Oturum kimliği testlerde oyuncu kimliğidir
+        let players = self.players.lock().unwrap();
+        Ok(players.iter().find(|p| p.id.to_string() == session_id).cloned())
+    }
+
+    async fn list_active(&self, _game_id: i32) -> Result<Vec<LobbyPlayer>, sqlx::Error> {
+        Ok(Vec::new())
+    }
+
+    async fn leaderboard(&self, _game_id: i32) -> Result<Vec<LeaderboardEntry>, sqlx::Error> {
+        Ok(Vec::new())
+    }
+
+    async fn has_answered(&self, player_id: i32, question_id: i32) -> Result<bool, sqlx::Error> {
+        Ok(self.recorded().contains(&(player_id, question_id)))
+    }
+
+    async fn record_answers(&self, answers: &[NewAnswer]) -> Result<(), sqlx::Error> {
+        if let Some(hook) = self.on_record.lock().unwrap().take() {
+            hook();
+        }
+
+        let failing = self.failing_writes.load(Ordering::SeqCst);
+        if failing > 0 {
+            self.failing_writes.store(failing - 1, Ordering::SeqCst);
+            return Err(sqlx::Error::PoolTimedOut);
+        }
+
+        // Gerçek depo gibi zaten kayıtlı cevaplar atlanır
+        let mut recorded = self.answers.lock().unwrap();
+        for answer in answers {
+            let key = (answer.player_id, answer.question_id);
+            if !recorded.iter().any(|a| (a.player_id, a.question_id) == key) {
+                recorded.push(answer.clone());
+            }
+        }
+        Ok(())
+    }
+
+    async fn retract_answer(&self, player_id: i32, question_id: i32) -> Result<bool, sqlx::Error> {
+        let mut recorded = self.answers.lock().unwrap();
+        let before = recorded.len();
+        recorded.retain(|a| (a.player_id, a.question_id) != (player_id, question_id));
+        Ok(recorded.len() < before)
+    }
+
+    async fn deactivate(&self, player_id: i32) -> Result<(), sqlx::Error> {
+        self.players.lock().unwrap().retain(|p| p.id != player_id);
+        Ok(())
+    }
+
+    async fn set_sudden_death_places(&self, player_ids: &[i32]) -> Result<(), sqlx::Error> {
+        *self.sudden_death_places.lock().unwrap() = player_ids.to_vec();
+        Ok(())
+    }
+}
+
+// Oyunlar koda göre tutulur
+#[derive(Default)]
+pub struct MockGameRepo {
+    pub games: Mutex<HashMap<String, GameRecord>>,
+}
+
+impl MockGameRepo {
+    pub fn insert(&self, code: &str, game: GameRecord) {
+        self.games.lock().unwrap().insert(code.to_string(), game);
+    }
+
+    pub fn status(&self, game_id: i32) -> Option<String> {
+        let games = self.games.lock().unwrap();
+        games.values().find(|g| g.id == game_id).map(|g| g.status.clone())
+    }
+
+    fn update(&self, game_id: i32, update: impl FnOnce(&mut GameRecord)) {
+        if let Some(game) = self.games.lock().unwrap().values_mut().find(|g| g.id == game_id) {
+            update(game);
+        }
+    }
+}
+
+impl GameRepo for MockGameRepo {
+    async fn find_by_code(&self, code: &str) -> Result<Option<GameRecord>, sqlx::Error> {
+        Ok(self.games.lock().unwrap().get(code).cloned())
+    }
+
+    async fn start(&self, game_id: i32) -> Result<bool, sqlx::Error> {
+        let mut started = false;
+        self.update(game_id, |game| {
+            if game.status == "lobby" {
+                game.status = "active".to_string();
+                game.current_question = None;
+                started = true;
+            }
+        });
+        Ok(started)
+    }
+
+    async fn set_current_question(&self, game_id: i32, position: i32) -> Result<(), sqlx::Error> {
+        self.update(game_id, |game| game.current_question = Some(position));
+        Ok(())
+    }
+
+    async fn complete(&self, game_id: i32) -> Result<(), sqlx::Error> {
+        self.update(game_id, |game| game.status = "completed".to_string());
+        Ok(())
+    }
+}
+
+// Oyunların soru listeleri (game_id -> sıralı sorular)
+#[derive(Default)]
+pub struct MockQuestionRepo {
+    pub playlists: Mutex<HashMap<i32, Vec<QuestionRecord>>>,
+}
+
+impl MockQuestionRepo {
+    pub fn add(&self, game_id: i32, question: QuestionRecord) {
+        let mut playlists = self.playlists.lock().unwrap();
+        let playlist = playlists.entry(game_id).or_default();
+        playlist.push(question);
+        playlist.sort_by_key(|q| q.position);
+    }
+}
+
+impl QuestionRepo for MockQuestionRepo {
+    async fn next_after(&self, _question_set_id: i32, _position: Option<i32>) -> Result<Option<QuestionRecord>, sqlx::Error> {
+        Ok(None)
+    }
+
+    async fn next_in_game(&self, game_id: i32, position: Option<i32>) -> Result<Option<QuestionRecord>, sqlx::Error> {
+        let playlists = self.playlists.lock().unwrap();
+        Ok(playlists
+            .get(&game_id)
+            .and_then(|playlist| playlist.iter().find(|q| position.is_none_or(|p| q.position > p)))
+            .cloned())
+    }
+
+    async fn answer_key(&self, question_id: i32, game_id: i32) -> Result<Option<AnswerKey>, sqlx::Error> {
+        let playlists = self.playlists.lock().unwrap();
+        Ok(playlists
+            .get(&game_id)
+            .and_then(|playlist| playlist.iter().find(|q| q.id == question_id))
+            .map(|q| AnswerKey {
+                correct_option: q.correct_option.clone(),
+                position: q.position,
+            }))
+    }
+}
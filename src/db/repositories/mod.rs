@@ -0,0 +1,67 @@
+use log::{debug, warn};
+use sqlx::{Pool, Postgres};
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use crate::config::CONFIG;
+use crate::services::metrics;
+
+// Veri erişim katmanı
+// Tablolara yapılan sorgular handler ve servislere dağılmak yerine buradaki depolarda toplanır.
+// Her depo bir trait ve Postgres uygulamasından oluşur; trait'e bağlı yazılan kod testlerde
+// sahte bir depoyla çalıştırılabilir. Tüm sorgular `observe` üzerinden geçer, böylece loglama
+// ve süre ölçümü gibi ortak davranışlar tek yerden eklenir; süreler Prometheus metriklerine de yansır.
+
+pub mod game;
+#[cfg(test)]
+pub mod mock;
+pub mod player;
+pub mod question;
+pub mod user;
+
+pub use game::{GameRepo, PgGameRepo};
+pub use player::{LobbyPlayer, NewAnswer, PgPlayerRepo, PlayerRepo, SessionPlayer};
+pub use question::{PgQuestionRepo, QuestionRecord, QuestionRepo};
+pub use user::{PgUserRepo, UserRepo};
+
+// Okuma replikası (bkz. db::pool::create_read_pool); açılışta bir kez ayarlanır
+static READ_POOL: OnceLock<Pool<Postgres>> = OnceLock::new();
+
+pub fn set_read_pool(pool: Pool<Postgres>) {
+    let _ = READ_POOL.set(pool);
+}
+
+// Salt okunur ağır sorgular (sezon sıralamaları, istatistikler, oyun geçmişi) için havuz. Replika varsa
+// onu, yoksa verilen ana havuzu döndürür; böylece canlı oyunlar sürerken analiz sorguları ana veritabanını
+// meşgul etmez. Replika birkaç saniye geride kalabilir: yazmadan hemen sonra okunması gereken veriler
+// (ör. devam eden oyunun sıralaması) için ana havuz kullanılmalıdır.
+// Handler'lar replikaya doğrudan değil, depoların `reader` kurucuları ve replikayı kendisi seçen rapor
+// servisleri üzerinden erişir.
+pub(crate) fn read_pool(primary: &Pool<Postgres>) -> &Pool<Postgres> {
+    READ_POOL.get().unwrap_or(primary)
+}
+
+// Sorguyu çalıştır ve süresini kaydet. SLOW_QUERY_THRESHOLD_MS süresini aşan sorgular, çalıştıkları istek
+// veya WebSocket mesajının rotası ve oyun koduyla uyarı olarak loglanır.
+pub async fn observe<T>(
+    name: &'static str,
+    query: impl Future<Output = Result<T, sqlx::Error>>,
+) -> Result<T, sqlx::Error> {
+    let started = Instant::now();
+    let result = query.await;
+    let elapsed = started.elapsed();
+
+    if elapsed >= Duration::from_millis(CONFIG.slow_query_threshold_ms) {
+        let context = metrics::current_context();
+        let route = context.as_ref().map_or("-", |c| c.route.as_str());
+        let game_code = context.as_ref().and_then(|c| c.game_code.as_deref()).unwrap_or("-");
+        warn!("Yavaş sorgu: {} ({} ms, rota: {}, oyun: {})", name, elapsed.as_millis(), route, game_code);
+        metrics::record_query(name, elapsed, Some(route));
+    } else {
+        debug!("Sorgu: {} ({} ms)", name, elapsed.as_millis());
+        metrics::record_query(name, elapsed, None);
+    }
+
+    result
+}
\ No newline at end of file
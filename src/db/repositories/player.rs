@@ -0,0 +1,450 @@
+use chrono::{DateTime, Utc};
+use sqlx::types::BigDecimal;
+use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
+use std::future::Future;
+
+use super::{observe, read_pool};
+use crate::db::models::LeaderboardEntry;
+
+// Oturumu aktif olan oyuncu ve bulunduğu oyunun durumu
+#[derive(Debug, Clone)]
+pub struct SessionPlayer {
+    pub id: i32,
+    pub game_id: i32,
+    pub game_code: String,
+    pub status: String,
+    pub current_question: Option<i32>,
+}
+
+// Lobide gösterilen oyuncu
+#[derive(Debug, Clone)]
+pub struct LobbyPlayer {
+    pub id: i32,
+    pub nickname: String,
+    pub is_guest: bool,
+    pub avatar: Option<String>,
+}
+
+// Veritabanına yazılacak oyuncu cevabı
+#[derive(Debug, Clone)]
+pub struct NewAnswer {
+    pub player_id: i32,
+    pub question_id: i32,
+    pub answer: String,
+    pub is_correct: bool,
+    pub response_time_ms: i32,
+    pub points: i32,
+    pub answered_at: DateTime<Utc>,
+}
+
+// Oyun istatistiklerinde oyuncu başına cevap özeti
+#[derive(Debug, Clone)]
+pub struct PlayerGameStats {
+    pub player_id: i32,
+    pub nickname: String,
+    pub score: Option<i32>,
+    pub answer_count: Option<i64>,
+    pub correct_count: Option<i64>,
+    pub avg_response_time: Option<BigDecimal>,
+}
+
+// Oyuncunun tüm cevaplarının özeti
+#[derive(Debug, Clone)]
+pub struct AnswerSummary {
+    pub correct_count: i64,
+    pub incorrect_count: i64,
+    pub avg_response_time: Option<BigDecimal>,
+    pub total_points: Option<i64>,
+    pub max_points: Option<i32>,
+}
+
+// Oyuncunun bir soruya verdiği cevap, soru metni ve doğru şıkla birlikte
+#[derive(Debug, Clone)]
+pub struct AnswerDetail {
+    pub question_id: i32,
+    pub question_text: String,
+    pub answer: Option<String>,
+    pub is_correct: bool,
+    pub response_time_ms: Option<i32>,
+    pub points_earned: Option<i32>,
+    pub correct_option: String,
+}
+
+// Kullanıcının oyun geçmişindeki bir oyun
+#[derive(Debug, Clone)]
+pub struct PlayedGame {
+    pub player_id: i32,
+    pub game_id: i32,
+    pub nickname: String,
+    pub score: Option<i32>,
+    pub joined_at: Option<DateTime<Utc>>,
+    pub game_code: String,
+    pub game_status: String,
+    pub started_at: Option<DateTime<Utc>>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub question_set_title: String,
+    pub host_username: String,
+    pub answer_count: Option<i64>,
+    pub correct_count: Option<i64>,
+}
+
+pub trait PlayerRepo {
+    fn find_active_by_session(&self, session_id: &str) -> impl Future<Output = Result<Option<SessionPlayer>, sqlx::Error>> + Send;
+
+    fn list_active(&self, game_id: i32) -> impl Future<Output = Result<Vec<LobbyPlayer>, sqlx::Error>> + Send;
+
+    // Aktif oyuncular puana göre (en fazla 100)
+    fn leaderboard(&self, game_id: i32) -> impl Future<Output = Result<Vec<LeaderboardEntry>, sqlx::Error>> + Send;
+
+    fn has_answered(&self, player_id: i32, question_id: i32) -> impl Future<Output = Result<bool, sqlx::Error>> + Send;
+
+    // Cevapları tek işlemde kaydet ve puanları güncelle; zaten kayıtlı cevaplar atlanır
+    fn record_answers(&self, answers: &[NewAnswer]) -> impl Future<Output = Result<(), sqlx::Error>> + Send;
+
+    // Kaydedilmiş cevabı sil ve puanını geri al; cevap yoksa false döner
+    fn retract_answer(&self, player_id: i32, question_id: i32) -> impl Future<Output = Result<bool, sqlx::Error>> + Send;
+
+    fn deactivate(&self, player_id: i32) -> impl Future<Output = Result<(), sqlx::Error>> + Send;
+
+    // Ani ölüm turu finalistlerinin sırasını kaydet; player_ids en iyiden başlayarak sıralıdır
+    fn set_sudden_death_places(&self, player_ids: &[i32]) -> impl Future<Output = Result<(), sqlx::Error>> + Send;
+}
+
+pub struct PgPlayerRepo<'a> {
+    pool: &'a Pool<Postgres>,
+}
+
+impl<'a> PgPlayerRepo<'a> {
+    pub fn new(pool: &'a Pool<Postgres>) -> Self {
+        PgPlayerRepo { pool }
+    }
+
+    // Salt okunur sorgular için okuma replikasını kullanan depo (bkz. read_pool); yalnızca birkaç saniyelik
+    // gecikmenin sorun olmadığı sıralama, istatistik ve geçmiş okumalarında kullanılır
+    pub fn reader(primary: &'a Pool<Postgres>) -> Self {
+        PgPlayerRepo { pool: read_pool(primary) }
+    }
+}
+
+impl PlayerRepo for PgPlayerRepo<'_> {
+    async fn find_active_by_session(&self, session_id: &str) -> Result<Option<SessionPlayer>, sqlx::Error> {
+        let player = observe(
+            "players.find_active_by_session",
+            sqlx::query!(
+                r#"
+                SELECT p.id, p.game_id, g.code as game_code, g.status, g.current_question
+                FROM players p
+                JOIN games g ON p.game_id = g.id
+                WHERE p.session_id = $1 AND p.is_active = true
+                "#,
+                session_id
+            )
+            .fetch_optional(self.pool),
+        )
+        .await?;
+
+        Ok(player.map(|p| SessionPlayer {
+            id: p.id,
+            game_id: p.game_id,
+            game_code: p.game_code,
+            status: p.status,
+            current_question: p.current_question,
+        }))
+    }
+
+    async fn list_active(&self, game_id: i32) -> Result<Vec<LobbyPlayer>, sqlx::Error> {
+        let players = observe(
+            "players.list_active",
+            sqlx::query!(
+                r#"
+                SELECT p.id, p.nickname, p.user_id IS NULL as is_guest, p.avatar
+                FROM players p
+                WHERE p.game_id = $1 AND p.is_active = true
+                "#,
+                game_id
+            )
+            .fetch_all(self.pool),
+        )
+        .await?;
+
+        Ok(players
+            .into_iter()
+            .map(|p| LobbyPlayer {
+                id: p.id,
+                nickname: p.nickname,
+                is_guest: p.is_guest.unwrap_or(false),
+                avatar: p.avatar,
+            })
+            .collect())
+    }
+
+    async fn leaderboard(&self, game_id: i32) -> Result<Vec<LeaderboardEntry>, sqlx::Error> {
+        let players = observe(
+            "players.leaderboard",
+            sqlx::query!(
+                r#"
+                SELECT id, nickname, score, user_id IS NULL as is_guest, avatar
+                FROM players
+                WHERE game_id = $1 AND is_active = true
+                ORDER BY sudden_death_place ASC NULLS LAST, score DESC
+                LIMIT 100
+                "#,
+                game_id
+            )
+            .fetch_all(self.pool),
+        )
+        .await?;
+
+        Ok(players
+            .into_iter()
+            .map(|p| LeaderboardEntry {
+                player_id: p.id,
+                nickname: p.nickname,
+                score: p.score.unwrap_or(0),
+                is_guest: p.is_guest.unwrap_or(false),
+                avatar: p.avatar,
+            })
+            .collect())
+    }
+
+    async fn has_answered(&self, player_id: i32, question_id: i32) -> Result<bool, sqlx::Error> {
+        observe(
+            "player_answers.has_answered",
+            sqlx::query!(
+                r#"SELECT EXISTS(SELECT 1 FROM player_answers WHERE player_id = $1 AND question_id = $2) as "answered!""#,
+                player_id,
+                question_id
+            )
+            .fetch_one(self.pool),
+        )
+        .await
+        .map(|r| r.answered)
+    }
+
+    async fn record_answers(&self, answers: &[NewAnswer]) -> Result<(), sqlx::Error> {
+        let player_ids: Vec<i32> = answers.iter().map(|a| a.player_id).collect();
+        let question_ids: Vec<i32> = answers.iter().map(|a| a.question_id).collect();
+        let options: Vec<String> = answers.iter().map(|a| a.answer.clone()).collect();
+        let correct: Vec<bool> = answers.iter().map(|a| a.is_correct).collect();
+        let response_times: Vec<i32> = answers.iter().map(|a| a.response_time_ms).collect();
+        let points: Vec<i32> = answers.iter().map(|a| a.points).collect();
+        let answered_at: Vec<DateTime<Utc>> = answers.iter().map(|a| a.answered_at).collect();
+
+        let mut tx = self.pool.begin().await?;
+
+        let inserted = observe(
+            "player_answers.record_batch",
+            sqlx::query!(
+                r#"
+                INSERT INTO player_answers (player_id, question_id, answer, is_correct, response_time_ms, points_earned, answered_at)
+                SELECT u.player_id, u.question_id, u.answer, u.is_correct, u.response_time_ms, u.points, u.answered_at
+                FROM UNNEST($1::int[], $2::int[], $3::text[], $4::bool[], $5::int[], $6::int[], $7::timestamptz[])
+                    AS u(player_id, question_id, answer, is_correct, response_time_ms, points, answered_at)
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM player_answers pa WHERE pa.player_id = u.player_id AND pa.question_id = u.question_id
+                )
+                RETURNING player_id, points_earned
+                "#,
+                &player_ids,
+                &question_ids,
+                &options,
+                &correct,
+                &response_times,
+                &points,
+                &answered_at
+            )
+            .fetch_all(&mut *tx),
+        )
+        .await?;
+
+        // Yalnızca gerçekten kaydedilen cevapların puanı eklenir
+        let mut totals: HashMap<i32, i32> = HashMap::new();
+        for row in &inserted {
+            *totals.entry(row.player_id).or_default() += row.points_earned.unwrap_or(0);
+        }
+        let (score_ids, score_points): (Vec<i32>, Vec<i32>) = totals.into_iter().unzip();
+
+        observe(
+            "players.add_scores",
+            sqlx::query!(
+                r#"
+                UPDATE players p SET score = p.score + s.points
+                FROM UNNEST($1::int[], $2::int[]) AS s(id, points)
+                WHERE p.id = s.id
+                "#,
+                &score_ids,
+                &score_points
+            )
+            .execute(&mut *tx),
+        )
+        .await?;
+
+        tx.commit().await
+    }
+
+    async fn retract_answer(&self, player_id: i32, question_id: i32) -> Result<bool, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let deleted = observe(
+            "player_answers.retract",
+            sqlx::query!(
+                "DELETE FROM player_answers WHERE player_id = $1 AND question_id = $2 RETURNING points_earned",
+                player_id,
+                question_id
+            )
+            .fetch_optional(&mut *tx),
+        )
+        .await?;
+
+        let Some(deleted) = deleted else {
+            return Ok(false);
+        };
+
+        observe(
+            "players.subtract_score",
+            sqlx::query!(
+                "UPDATE players SET score = score - $1 WHERE id = $2",
+                deleted.points_earned.unwrap_or(0),
+                player_id
+            )
+            .execute(&mut *tx),
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn deactivate(&self, player_id: i32) -> Result<(), sqlx::Error> {
+        observe(
+            "players.deactivate",
+            sqlx::query!("UPDATE players SET is_active = false WHERE id = $1", player_id)
+                .execute(self.pool),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn set_sudden_death_places(&self, player_ids: &[i32]) -> Result<(), sqlx::Error> {
+        observe(
+            "players.set_sudden_death_places",
+            sqlx::query!(
+                r#"
+                UPDATE players p SET sudden_death_place = f.place
+                FROM UNNEST($1::int[]) WITH ORDINALITY AS f(id, place)
+                WHERE p.id = f.id
+                "#,
+                player_ids
+            )
+            .execute(self.pool),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+// İstatistik ve geçmiş sorguları; trait'te değil, çünkü oyun akışı bunlara ihtiyaç duymaz
+impl PgPlayerRepo<'_> {
+    // Oyundaki aktif oyuncuların cevap sayıları ve ortalama süreleri, final sıralamasına göre
+    pub async fn game_stats(&self, game_id: i32) -> Result<Vec<PlayerGameStats>, sqlx::Error> {
+        observe(
+            "players.game_stats",
+            sqlx::query_as!(
+                PlayerGameStats,
+                r#"
+                SELECT 
+                    p.id as player_id,
+                    p.nickname,
+                    p.score,
+                    COUNT(pa.id) as answer_count,
+                    COUNT(pa.id) FILTER (WHERE pa.is_correct) as correct_count,
+                    ROUND(AVG(pa.response_time_ms)) as avg_response_time
+                FROM players p
+                LEFT JOIN player_answers pa ON p.id = pa.player_id
+                WHERE p.game_id = $1 AND p.is_active = true
+                GROUP BY p.id, p.nickname, p.score
+                ORDER BY p.sudden_death_place ASC NULLS LAST, p.score DESC
+                "#,
+                game_id
+            )
+            .fetch_all(self.pool),
+        )
+        .await
+    }
+
+    pub async fn answer_summary(&self, player_id: i32) -> Result<AnswerSummary, sqlx::Error> {
+        observe(
+            "player_answers.summary",
+            sqlx::query_as!(
+                AnswerSummary,
+                r#"
+                SELECT 
+                    COUNT(*) FILTER (WHERE is_correct = true) as "correct_count!",
+                    COUNT(*) FILTER (WHERE is_correct = false) as "incorrect_count!",
+                    ROUND(AVG(response_time_ms)) as "avg_response_time",
+                    SUM(points_earned) as "total_points",
+                    MAX(points_earned) as "max_points"
+                FROM player_answers
+                WHERE player_id = $1
+                "#,
+                player_id
+            )
+            .fetch_one(self.pool),
+        )
+        .await
+    }
+
+    // Oyuncunun cevapları, verildikleri sırayla
+    pub async fn answer_details(&self, player_id: i32) -> Result<Vec<AnswerDetail>, sqlx::Error> {
+        observe(
+            "player_answers.details",
+            sqlx::query_as!(
+                AnswerDetail,
+                r#"
+                SELECT 
+                    pa.question_id, q.question_text, pa.answer, pa.is_correct, 
+                    pa.response_time_ms, pa.points_earned,
+                    q.correct_option
+                FROM player_answers pa
+                JOIN questions q ON pa.question_id = q.id
+                WHERE pa.player_id = $1
+                ORDER BY pa.answered_at
+                "#,
+                player_id
+            )
+            .fetch_all(self.pool),
+        )
+        .await
+    }
+
+    // Kullanıcının katıldığı oyunlar, en yeniden eskiye
+    pub async fn game_history(&self, user_id: i32) -> Result<Vec<PlayedGame>, sqlx::Error> {
+        observe(
+            "players.game_history",
+            sqlx::query_as!(
+                PlayedGame,
+                r#"
+                SELECT 
+                    p.id as player_id, p.game_id, p.nickname, p.score, p.joined_at,
+                    g.code as game_code, g.status as game_status, g.started_at, g.ended_at,
+                    qs.title as question_set_title,
+                    u.username as host_username,
+                    (SELECT COUNT(*) FROM player_answers WHERE player_id = p.id) as answer_count,
+                    (SELECT COUNT(*) FROM player_answers WHERE player_id = p.id AND is_correct = true) as correct_count
+                FROM players p
+                JOIN games g ON p.game_id = g.id
+                JOIN question_sets qs ON g.question_set_id = qs.id
+                JOIN users u ON g.host_id = u.id
+                WHERE p.user_id = $1
+                ORDER BY p.joined_at DESC
+                "#,
+                user_id
+            )
+            .fetch_all(self.pool),
+        )
+        .await
+    }
+}
@@ -0,0 +1,206 @@
+use sqlx::types::BigDecimal;
+use sqlx::{Pool, Postgres};
+use std::future::Future;
+
+use super::{observe, read_pool};
+
+// Oyunda gösterilecek soru, sıra numarası ve setteki toplam soru sayısıyla birlikte
+#[derive(Debug, Clone)]
+pub struct QuestionRecord {
+    pub id: i32,
+    pub question_text: String,
+    pub options: [String; 4],
+    pub correct_option: String,
+    pub time_limit: Option<i32>,
+    pub position: i32,
+    pub question_number: i64,
+    pub total_questions: i64,
+    pub host_notes: Option<String>,
+}
+
+// Cevap kontrolü için sorunun doğru şıkkı ve sırası
+#[derive(Debug, Clone)]
+pub struct AnswerKey {
+    pub correct_option: String,
+    pub position: i32,
+}
+
+// Oyun istatistiklerinde soru başına cevap özeti
+#[derive(Debug, Clone)]
+pub struct QuestionGameStats {
+    pub question_id: i32,
+    pub question_text: String,
+    pub answer_count: Option<i64>,
+    pub correct_count: Option<i64>,
+    pub avg_response_time: Option<BigDecimal>,
+}
+
+pub trait QuestionRepo {
+    // Verilen pozisyondan sonraki soru; pozisyon boşsa setin ilk sorusu
+    fn next_after(
+        &self,
+        question_set_id: i32,
+        position: Option<i32>,
+    ) -> impl Future<Output = Result<Option<QuestionRecord>, sqlx::Error>> + Send;
+
+    // Oyunun soru listesinde (bkz. game_playlist) verilen sıradan sonraki soru; sıra numarası ve toplam soru
+    // sayısı da bu listeye göredir. Dönen position sorunun oyundaki sırasıdır
+    fn next_in_game(
+        &self,
+        game_id: i32,
+        position: Option<i32>,
+    ) -> impl Future<Output = Result<Option<QuestionRecord>, sqlx::Error>> + Send;
+
+    // Soru oyunun soru listesindeyse cevap anahtarı ve oyundaki sırası
+    fn answer_key(
+        &self,
+        question_id: i32,
+        game_id: i32,
+    ) -> impl Future<Output = Result<Option<AnswerKey>, sqlx::Error>> + Send;
+}
+
+pub struct PgQuestionRepo<'a> {
+    pool: &'a Pool<Postgres>,
+}
+
+impl<'a> PgQuestionRepo<'a> {
+    pub fn new(pool: &'a Pool<Postgres>) -> Self {
+        PgQuestionRepo { pool }
+    }
+
+    // Salt okunur istatistik sorguları için okuma replikasını kullanan depo (bkz. read_pool)
+    pub fn reader(primary: &'a Pool<Postgres>) -> Self {
+        PgQuestionRepo { pool: read_pool(primary) }
+    }
+}
+
+impl QuestionRepo for PgQuestionRepo<'_> {
+    async fn next_after(&self, question_set_id: i32, position: Option<i32>) -> Result<Option<QuestionRecord>, sqlx::Error> {
+        let question = observe(
+            "questions.next_after",
+            sqlx::query!(
+                r#"
+                SELECT q.id, q.question_text, q.option_a, q.option_b, q.option_c, q.option_d,
+                       q.correct_option, q.time_limit, q.position, q.host_notes,
+                       (SELECT COUNT(*) FROM questions q2
+                        WHERE q2.question_set_id = q.question_set_id AND q2.position <= q.position
+                          AND q2.deleted_at IS NULL) as "question_number!",
+                       (SELECT COUNT(*) FROM questions q3
+                        WHERE q3.question_set_id = q.question_set_id AND q3.deleted_at IS NULL) as "total_questions!"
+                FROM questions q
+                WHERE q.question_set_id = $1 AND q.deleted_at IS NULL AND ($2::int IS NULL OR q.position > $2)
+                ORDER BY q.position
+                LIMIT 1
+                "#,
+                question_set_id,
+                position
+            )
+            .fetch_optional(self.pool),
+        )
+        .await?;
+
+        Ok(question.map(|q| QuestionRecord {
+            id: q.id,
+            question_text: q.question_text,
+            options: [q.option_a, q.option_b, q.option_c, q.option_d],
+            correct_option: q.correct_option,
+            time_limit: q.time_limit,
+            position: q.position,
+            question_number: q.question_number,
+            total_questions: q.total_questions,
+            host_notes: q.host_notes,
+        }))
+    }
+
+    async fn next_in_game(&self, game_id: i32, position: Option<i32>) -> Result<Option<QuestionRecord>, sqlx::Error> {
+        let question = observe(
+            "questions.next_in_game",
+            sqlx::query!(
+                r#"
+                WITH playlist AS (
+                    SELECT gp.question_id, gp.position FROM game_playlist($1) gp
+                )
+                SELECT q.id, q.question_text, q.option_a, q.option_b, q.option_c, q.option_d,
+                       q.correct_option, q.time_limit, p.position as "position!", q.host_notes,
+                       (SELECT COUNT(*) FROM playlist s WHERE s.position <= p.position) as "question_number!",
+                       (SELECT COUNT(*) FROM playlist) as "total_questions!"
+                FROM playlist p
+                JOIN questions q ON q.id = p.question_id
+                WHERE $2::int IS NULL OR p.position > $2
+                ORDER BY p.position
+                LIMIT 1
+                "#,
+                game_id,
+                position
+            )
+            .fetch_optional(self.pool),
+        )
+        .await?;
+
+        Ok(question.map(|q| QuestionRecord {
+            id: q.id,
+            question_text: q.question_text,
+            options: [q.option_a, q.option_b, q.option_c, q.option_d],
+            correct_option: q.correct_option,
+            time_limit: q.time_limit,
+            position: q.position,
+            question_number: q.question_number,
+            total_questions: q.total_questions,
+            host_notes: q.host_notes,
+        }))
+    }
+
+    async fn answer_key(&self, question_id: i32, game_id: i32) -> Result<Option<AnswerKey>, sqlx::Error> {
+        let question = observe(
+            "questions.answer_key",
+            sqlx::query!(
+                r#"
+                SELECT q.correct_option, p.position as "position!"
+                FROM game_playlist($2) p
+                JOIN questions q ON q.id = p.question_id
+                WHERE p.question_id = $1
+                "#,
+                question_id,
+                game_id
+            )
+            .fetch_optional(self.pool),
+        )
+        .await?;
+
+        Ok(question.map(|q| AnswerKey {
+            correct_option: q.correct_option,
+            position: q.position,
+        }))
+    }
+}
+
+impl PgQuestionRepo<'_> {
+    // Oyunun sorularına verilen cevapların sayısı ve ortalama süresi, oyundaki soru sırasıyla
+    pub async fn game_stats(&self, game_id: i32) -> Result<Vec<QuestionGameStats>, sqlx::Error> {
+        observe(
+            "questions.game_stats",
+            sqlx::query_as!(
+                QuestionGameStats,
+                r#"
+                SELECT 
+                    q.id as question_id,
+                    q.question_text,
+                    COUNT(pa.id) as answer_count,
+                    COUNT(pa.id) FILTER (WHERE pa.is_correct) as correct_count,
+                    ROUND(AVG(pa.response_time_ms)) as avg_response_time
+                FROM game_playlist($1) gp
+                JOIN questions q ON q.id = gp.question_id
+                LEFT JOIN player_answers pa ON q.id = pa.question_id
+                WHERE pa.player_id IN (
+                    SELECT id FROM players WHERE game_id = $1
+                )
+                GROUP BY q.id, q.question_text, gp.position
+                ORDER BY gp.position
+                "#,
+                game_id
+            )
+            .fetch_all(self.pool),
+        )
+        .await
+    }
+}
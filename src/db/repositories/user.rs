@@ -0,0 +1,83 @@
+use sqlx::{Pool, Postgres};
+use std::future::Future;
+
+use super::observe;
+
+// Bildirim ve e-posta işlemleri için kullanıcı özeti
+#[derive(Debug, Clone)]
+pub struct UserSummary {
+    pub id: i32,
+    pub username: String,
+    pub email: String,
+}
+
+pub trait UserRepo {
+    fn find_by_email(&self, email: &str) -> impl Future<Output = Result<Option<UserSummary>, sqlx::Error>> + Send;
+
+    fn email_taken(&self, email: &str) -> impl Future<Output = Result<bool, sqlx::Error>> + Send;
+
+    fn username_taken(&self, username: &str) -> impl Future<Output = Result<bool, sqlx::Error>> + Send;
+
+    // Aynı karşılaştırma anahtarına (validation::name_skeleton) sahip bir kullanıcı adı var mı
+    fn username_lookalike_taken(&self, skeleton: &str) -> impl Future<Output = Result<bool, sqlx::Error>> + Send;
+}
+
+pub struct PgUserRepo<'a> {
+    pool: &'a Pool<Postgres>,
+}
+
+impl<'a> PgUserRepo<'a> {
+    pub fn new(pool: &'a Pool<Postgres>) -> Self {
+        PgUserRepo { pool }
+    }
+}
+
+impl UserRepo for PgUserRepo<'_> {
+    async fn find_by_email(&self, email: &str) -> Result<Option<UserSummary>, sqlx::Error> {
+        let user = observe(
+            "users.find_by_email",
+            sqlx::query!("SELECT id, username, email FROM users WHERE email = $1", email)
+                .fetch_optional(self.pool),
+        )
+        .await?;
+
+        Ok(user.map(|u| UserSummary {
+            id: u.id,
+            username: u.username,
+            email: u.email,
+        }))
+    }
+
+    async fn email_taken(&self, email: &str) -> Result<bool, sqlx::Error> {
+        observe(
+            "users.email_taken",
+            sqlx::query!(r#"SELECT EXISTS(SELECT 1 FROM users WHERE email = $1) as "taken!""#, email)
+                .fetch_one(self.pool),
+        )
+        .await
+        .map(|r| r.taken)
+    }
+
+    async fn username_taken(&self, username: &str) -> Result<bool, sqlx::Error> {
+        observe(
+            "users.username_taken",
+            sqlx::query!(r#"SELECT EXISTS(SELECT 1 FROM users WHERE username = $1) as "taken!""#, username)
+                .fetch_one(self.pool),
+        )
+        .await
+        .map(|r| r.taken)
+    }
+
+    async fn username_lookalike_taken(&self, skeleton: &str) -> Result<bool, sqlx::Error> {
+        observe(
+            "users.username_lookalike_taken",
+            sqlx::query!(
+                r#"SELECT EXISTS(SELECT 1 FROM users WHERE username_skeleton = $1) as "taken!""#,
+                skeleton
+            )
+            .fetch_one(self.pool),
+        )
+        .await
+        .map(|r| r.taken)
+    }
+}
\ No newline at end of file
@@ -1,8 +1,13 @@
 // Veritabanı şema yapılandırması
 // Bu dosya veritabanı şemasının program içerisindeki tanımlamalarını içerir
 
+use chrono::Utc;
 use sqlx::postgres::PgPool;
-use log::info;
+use log::{error, info, warn};
+
+use crate::config::CONFIG;
+use crate::utils::security::hash_password;
+use crate::utils::validation::{name_skeleton, validate_password};
 
 // Veritabanı şemasının doğruluğunu kontrol eden yardımcı fonksiyon
 pub async fn check_schema(pool: &PgPool) -> bool {
@@ -40,26 +45,105 @@ pub async fn check_schema(pool: &PgPool) -> bool {
     true
 }
 
-// Admin kullanıcısının varlığını kontrol et
-pub async fn check_admin_user(pool: &PgPool) -> bool {
+// Admin kullanıcısının varlığını kontrol et; hiç admin yoksa ADMIN_EMAIL/ADMIN_PASSWORD ile ilk admini oluştur
+pub async fn ensure_admin_user(pool: &PgPool) -> bool {
     let result = sqlx::query!(
-        r#"SELECT COUNT(*) as "count!" FROM users WHERE username = 'cancebi' AND role = 'admin'"#
+        r#"SELECT COUNT(*) as "count!" FROM users WHERE role = 'admin'"#
     )
     .fetch_one(pool)
     .await;
     
     match result {
-        Ok(record) => {
-            if record.count == 0 {
-                info!("Admin kullanıcısı bulunamadı");
-                return false;
-            }
+        Ok(record) if record.count > 0 => {
             info!("Admin kullanıcısı kontrol edildi");
             true
         },
+        Ok(_) => bootstrap_admin(pool).await,
         Err(e) => {
             info!("Admin kullanıcısı kontrolü başarısız: {}", e);
             false
         }
     }
+}
+
+// İlk çalıştırmada ortam değişkenlerinden admin oluştur
+async fn bootstrap_admin(pool: &PgPool) -> bool {
+    if CONFIG.admin_email.is_empty() || CONFIG.admin_password.is_empty() {
+        warn!(
+            "Admin kullanıcısı bulunamadı. ADMIN_EMAIL ve ADMIN_PASSWORD ile yeniden başlatın \
+             veya `cargo run --bin admin -- create-admin` komutunu kullanın"
+        );
+        return false;
+    }
+    
+    if !validate_password(&CONFIG.admin_password) {
+        warn!("ADMIN_PASSWORD en az 8 karakter olmalıdır, admin oluşturulmadı");
+        return false;
+    }
+    
+    let password_hash = match hash_password(&CONFIG.admin_password) {
+        Ok(hash) => hash,
+        Err(e) => {
+            error!("Admin şifresi hashlenemedi: {}", e);
+            return false;
+        }
+    };
+    
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO users (username, username_skeleton, email, password_hash, role, is_approved, is_email_verified, created_at)
+        VALUES ($1, $2, $3, $4, 'admin', true, true, $5)
+        RETURNING id
+        "#,
+        CONFIG.admin_username,
+        name_skeleton(&CONFIG.admin_username),
+        CONFIG.admin_email,
+        password_hash,
+        Utc::now()
+    )
+    .fetch_one(pool)
+    .await;
+    
+    match result {
+        Ok(record) => {
+            info!("İlk admin kullanıcısı oluşturuldu: {} (id={})", CONFIG.admin_username, record.id);
+            true
+        },
+        Err(e) => {
+            error!("İlk admin kullanıcısı oluşturulamadı: {}", e);
+            false
+        }
+    }
+}
+
+// Karşılaştırma anahtarı olmayan kullanıcıları (sütun eklenmeden önce veya admin aracıyla oluşturulanlar) doldur
+pub async fn backfill_username_skeletons(pool: &PgPool) {
+    let users = match sqlx::query!("SELECT id, username FROM users WHERE username_skeleton IS NULL")
+        .fetch_all(pool)
+        .await
+    {
+        Ok(users) => users,
+        Err(e) => {
+            error!("Kullanıcı adı anahtarları alınamadı: {}", e);
+            return;
+        }
+    };
+    
+    for user in &users {
+        if let Err(e) = sqlx::query!(
+            "UPDATE users SET username_skeleton = $1 WHERE id = $2",
+            name_skeleton(&user.username),
+            user.id
+        )
+        .execute(pool)
+        .await
+        {
+            error!("Kullanıcı adı anahtarı güncellenemedi (id={}): {}", user.id, e);
+            return;
+        }
+    }
+    
+    if !users.is_empty() {
+        info!("{} kullanıcının kullanıcı adı anahtarı oluşturuldu", users.len());
+    }
 }
\ No newline at end of file
@@ -1,4 +1,8 @@
-use actix_web::{error::ResponseError, http::StatusCode, HttpResponse};
+use actix_web::{
+    error::{JsonPayloadError, ResponseError},
+    http::StatusCode,
+    HttpRequest, HttpResponse,
+};
 use derive_more::Display;
 use serde::{Deserialize, Serialize};
 use sqlx::error::Error as SqlxError;
@@ -23,6 +27,9 @@ pub enum AppError {
     
     #[display(fmt = "Veritabanı hatası: {}", _0)]
     DatabaseError(String),
+    
+    #[display(fmt = "İstek gövdesi çok büyük: {}", _0)]
+    PayloadTooLarge(String),
 }
 
 impl ResponseError for AppError {
@@ -45,6 +52,7 @@ impl ResponseError for AppError {
             AppError::BadRequestError(_) => StatusCode::BAD_REQUEST,
             AppError::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
         }
     }
 }
@@ -58,6 +66,27 @@ impl From<SqlxError> for AppError {
     }
 }
 
+// JSON gövdesi okunamadığında dönen hatayı AppError biçimine çevir (web::JsonConfig::error_handler).
+// Boyut sınırını aşan gövdeler 413, bozuk veya çok derin iç içe JSON 400 ile reddedilir.
+pub fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    let app_error = match &err {
+        JsonPayloadError::OverflowKnownLength { limit, .. } | JsonPayloadError::Overflow { limit } => {
+            AppError::PayloadTooLarge(format!("en fazla {} bayt gönderilebilir", limit))
+        }
+        JsonPayloadError::ContentType => {
+            AppError::BadRequestError("İçerik tipi application/json olmalıdır".to_string())
+        }
+        // serde_json iç içe geçme derinliğini sınırlar, aşıldığında bu hata döner
+        JsonPayloadError::Deserialize(e) if e.to_string().contains("recursion limit exceeded") => {
+            AppError::BadRequestError("JSON çok derin iç içe geçmiş".to_string())
+        }
+        JsonPayloadError::Deserialize(e) => AppError::BadRequestError(format!("Geçersiz JSON: {}", e)),
+        _ => AppError::BadRequestError("İstek gövdesi okunamadı".to_string()),
+    };
+    
+    app_error.into()
+}
+
 #[derive(Serialize, Deserialize)]
 struct ErrorResponse {
     error: String,
@@ -0,0 +1,472 @@
+use chrono::{DateTime, Utc};
+use log::{error, info};
+use sqlx::{Pool, Postgres};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+
+use crate::config::CONFIG;
+use crate::services::game as game_service;
+use crate::services::game_events::EVENT_GAME_END;
+use crate::utils::security;
+
+// Dahili gRPC API (sözleşme: proto/internal.proto)
+// Raporlama servisi ve yapay zeka soru üreticisi gibi iç servisler oyunları, sonuçları ve oyun olaylarını
+// buradan okur. Sunucu yalnızca INTERNAL_GRPC_ADDR ayarlandığında açılır ve public ağa açılmamalıdır;
+// kimlik doğrulama kullanıcı JWT'si yerine paylaşılan INTERNAL_API_TOKEN ile yapılır. Olay aboneliği
+// game_events tablosunu yoklar, böylece sunucu yeniden başlasa da abone son aldığı olay kimliğinden devam eder.
+
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/sorukayisi.internal.v1.InternalApi.rs"));
+}
+
+use generated::internal_api_server::{InternalApi, InternalApiServer};
+
+// ListGames varsayılan ve en büyük sayfa boyutu
+const DEFAULT_LIST_LIMIT: i64 = 50;
+const MAX_LIST_LIMIT: i64 = 500;
+
+// Olay aboneliğinde yeni olayların yoklanma aralığı
+const EVENT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+// Tek yoklamada okunacak en fazla olay
+const EVENT_BATCH_SIZE: i64 = 500;
+// Aboneye gönderilmeyi bekleyen en fazla olay (yavaş abone yoklamayı bekletir)
+const EVENT_BUFFER_SIZE: usize = 1000;
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetGameRequest {
+    #[prost(string, tag = "1")]
+    pub code: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Game {
+    #[prost(int32, tag = "1")]
+    pub id: i32,
+    #[prost(string, tag = "2")]
+    pub code: String,
+    #[prost(string, tag = "3")]
+    pub status: String,
+    #[prost(int32, tag = "4")]
+    pub host_id: i32,
+    #[prost(int32, tag = "5")]
+    pub question_set_id: i32,
+    #[prost(string, tag = "6")]
+    pub question_set_title: String,
+    #[prost(int64, tag = "7")]
+    pub question_count: i64,
+    #[prost(int64, tag = "8")]
+    pub player_count: i64,
+    #[prost(int64, tag = "9")]
+    pub created_at_ms: i64,
+    #[prost(int64, optional, tag = "10")]
+    pub started_at_ms: Option<i64>,
+    #[prost(int64, optional, tag = "11")]
+    pub ended_at_ms: Option<i64>,
+    #[prost(int32, optional, tag = "12")]
+    pub organization_id: Option<i32>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ListGamesRequest {
+    #[prost(string, optional, tag = "1")]
+    pub status: Option<String>,
+    #[prost(int32, optional, tag = "2")]
+    pub host_id: Option<i32>,
+    #[prost(int32, optional, tag = "3")]
+    pub organization_id: Option<i32>,
+    #[prost(int32, tag = "4")]
+    pub after_id: i32,
+    #[prost(int32, tag = "5")]
+    pub limit: i32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ListGamesResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub games: Vec<Game>,
+    #[prost(int32, optional, tag = "2")]
+    pub next_after_id: Option<i32>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetGameResultsRequest {
+    #[prost(string, tag = "1")]
+    pub code: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PlayerResult {
+    #[prost(int32, tag = "1")]
+    pub player_id: i32,
+    #[prost(int32, optional, tag = "2")]
+    pub user_id: Option<i32>,
+    #[prost(string, tag = "3")]
+    pub nickname: String,
+    #[prost(int32, tag = "4")]
+    pub rank: i32,
+    #[prost(int64, tag = "5")]
+    pub score: i64,
+    #[prost(int64, tag = "6")]
+    pub answers: i64,
+    #[prost(int64, tag = "7")]
+    pub correct: i64,
+    #[prost(int64, optional, tag = "8")]
+    pub avg_response_time_ms: Option<i64>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QuestionResult {
+    #[prost(int32, tag = "1")]
+    pub question_id: i32,
+    #[prost(int32, tag = "2")]
+    pub position: i32,
+    #[prost(string, tag = "3")]
+    pub correct_option: String,
+    #[prost(int64, tag = "4")]
+    pub answer_count: i64,
+    #[prost(int64, tag = "5")]
+    pub correct_count: i64,
+    #[prost(int64, optional, tag = "6")]
+    pub avg_response_time_ms: Option<i64>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GameResults {
+    #[prost(message, optional, tag = "1")]
+    pub game: Option<Game>,
+    #[prost(message, repeated, tag = "2")]
+    pub players: Vec<PlayerResult>,
+    #[prost(message, repeated, tag = "3")]
+    pub questions: Vec<QuestionResult>,
+    #[prost(int64, tag = "4")]
+    pub max_possible_score: i64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeGameEventsRequest {
+    #[prost(int32, optional, tag = "1")]
+    pub game_id: Option<i32>,
+    #[prost(int64, tag = "2")]
+    pub after_event_id: i64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GameEvent {
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+    #[prost(int32, tag = "2")]
+    pub game_id: i32,
+    #[prost(string, tag = "3")]
+    pub game_code: String,
+    #[prost(int32, tag = "4")]
+    pub sequence: i32,
+    #[prost(string, tag = "5")]
+    pub event_type: String,
+    #[prost(string, tag = "6")]
+    pub payload_json: String,
+    #[prost(int64, tag = "7")]
+    pub created_at_ms: i64,
+}
+
+// Oyun sorgusu filtreleri (kod verilirse diğerleri yok sayılır)
+#[derive(Default)]
+struct GameFilter {
+    code: Option<String>,
+    status: Option<String>,
+    host_id: Option<i32>,
+    organization_id: Option<i32>,
+    after_id: i32,
+    limit: i64,
+}
+
+fn db_error(e: sqlx::Error) -> Status {
+    error!("Veritabanı sorgu hatası: {}", e);
+    Status::internal("Veritabanı hatası")
+}
+
+fn millis(time: DateTime<Utc>) -> i64 {
+    time.timestamp_millis()
+}
+
+async fn fetch_games(pool: &Pool<Postgres>, filter: GameFilter) -> Result<Vec<Game>, sqlx::Error> {
+    let games = sqlx::query!(
+        r#"
+        SELECT g.id, g.code, g.status, g.host_id, g.question_set_id, qs.title as question_set_title,
+               g.created_at, g.started_at, g.ended_at, u.organization_id,
+               (SELECT COUNT(*) FROM game_question_ids(g.id)) as "question_count!",
+               (SELECT COUNT(*) FROM players p WHERE p.game_id = g.id AND p.is_active = true) as "player_count!"
+        FROM games g
+        JOIN question_sets qs ON qs.id = g.question_set_id
+        JOIN users u ON u.id = g.host_id
+        WHERE ($1::varchar IS NULL OR g.code = $1)
+          AND ($2::varchar IS NULL OR g.status = $2)
+          AND ($3::int IS NULL OR g.host_id = $3)
+          AND ($4::int IS NULL OR u.organization_id = $4)
+          AND g.id > $5
+        ORDER BY g.id
+        LIMIT $6
+        "#,
+        filter.code,
+        filter.status,
+        filter.host_id,
+        filter.organization_id,
+        filter.after_id,
+        filter.limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(games
+        .into_iter()
+        .map(|g| Game {
+            id: g.id,
+            code: g.code,
+            status: g.status,
+            host_id: g.host_id,
+            question_set_id: g.question_set_id,
+            question_set_title: g.question_set_title,
+            question_count: g.question_count,
+            player_count: g.player_count,
+            created_at_ms: g.created_at.map(millis).unwrap_or_default(),
+            started_at_ms: g.started_at.map(millis),
+            ended_at_ms: g.ended_at.map(millis),
+            organization_id: g.organization_id,
+        })
+        .collect())
+}
+
+async fn find_game(pool: &Pool<Postgres>, code: &str) -> Result<Game, Status> {
+    let filter = GameFilter {
+        code: Some(code.trim().to_uppercase()),
+        limit: 1,
+        ..Default::default()
+    };
+    fetch_games(pool, filter)
+        .await
+        .map_err(db_error)?
+        .pop()
+        .ok_or_else(|| Status::not_found("Oyun bulunamadı"))
+}
+
+pub struct InternalService {
+    pool: Pool<Postgres>,
+}
+
+#[tonic::async_trait]
+impl InternalApi for InternalService {
+    async fn get_game(&self, request: Request<GetGameRequest>) -> Result<Response<Game>, Status> {
+        let game = find_game(&self.pool, &request.into_inner().code).await?;
+        Ok(Response::new(game))
+    }
+
+    async fn list_games(&self, request: Request<ListGamesRequest>) -> Result<Response<ListGamesResponse>, Status> {
+        let request = request.into_inner();
+        let limit = if request.limit > 0 { (request.limit as i64).min(MAX_LIST_LIMIT) } else { DEFAULT_LIST_LIMIT };
+
+        let filter = GameFilter {
+            code: None,
+            status: request.status,
+            host_id: request.host_id,
+            organization_id: request.organization_id,
+            after_id: request.after_id,
+            limit,
+        };
+        let games = fetch_games(&self.pool, filter).await.map_err(db_error)?;
+
+        // Sayfa dolduysa devamı olabilir
+        let next_after_id = if games.len() as i64 == limit { games.last().map(|g| g.id) } else { None };
+        Ok(Response::new(ListGamesResponse { games, next_after_id }))
+    }
+
+    async fn get_game_results(&self, request: Request<GetGameResultsRequest>) -> Result<Response<GameResults>, Status> {
+        let game = find_game(&self.pool, &request.into_inner().code).await?;
+
+        // Devam eden oyunda henüz yazılmamış cevaplar da sonuçlara dahil edilsin
+        if game.status != "completed" {
+            game_service::flush_answers(&self.pool, game.id).await.map_err(db_error)?;
+        }
+
+        let players = sqlx::query!(
+            r#"
+            SELECT p.id, p.user_id, p.nickname, COALESCE(p.score, 0)::bigint as "score!",
+                   RANK() OVER (ORDER BY p.sudden_death_place ASC NULLS LAST, COALESCE(p.score, 0) DESC)::int as "rank!",
+                   COUNT(pa.id) as "answers!",
+                   COUNT(pa.id) FILTER (WHERE pa.is_correct) as "correct!",
+                   AVG(pa.response_time_ms) FILTER (WHERE pa.answer <> 'X')::float8 as avg_response_time
+            FROM players p
+            LEFT JOIN player_answers pa ON pa.player_id = p.id
+            WHERE p.game_id = $1 AND p.is_active = true
+            GROUP BY p.id
+            ORDER BY p.sudden_death_place ASC NULLS LAST, COALESCE(p.score, 0) DESC, p.nickname
+            "#,
+            game.id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        let questions = sqlx::query!(
+            r#"
+            SELECT q.id, gp.position as "position!", q.correct_option,
+                   COUNT(pa.id) as "answer_count!",
+                   COUNT(pa.id) FILTER (WHERE pa.is_correct) as "correct_count!",
+                   AVG(pa.response_time_ms) FILTER (WHERE pa.answer <> 'X')::float8 as avg_response_time
+            FROM game_playlist($1) gp
+            JOIN questions q ON q.id = gp.question_id
+            LEFT JOIN player_answers pa ON pa.question_id = q.id
+                AND pa.player_id IN (SELECT id FROM players WHERE game_id = $1)
+            GROUP BY q.id, gp.position
+            ORDER BY gp.position
+            "#,
+            game.id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(Response::new(GameResults {
+            max_possible_score: game_service::max_possible_score(game.question_count),
+            game: Some(game),
+            players: players
+                .into_iter()
+                .map(|p| PlayerResult {
+                    player_id: p.id,
+                    user_id: p.user_id,
+                    nickname: p.nickname,
+                    rank: p.rank,
+                    score: p.score,
+                    answers: p.answers,
+                    correct: p.correct,
+                    avg_response_time_ms: p.avg_response_time.map(|t| t.round() as i64),
+                })
+                .collect(),
+            questions: questions
+                .into_iter()
+                .map(|q| QuestionResult {
+                    question_id: q.id,
+                    position: q.position,
+                    correct_option: q.correct_option,
+                    answer_count: q.answer_count,
+                    correct_count: q.correct_count,
+                    avg_response_time_ms: q.avg_response_time.map(|t| t.round() as i64),
+                })
+                .collect(),
+        }))
+    }
+
+    type SubscribeGameEventsStream = ReceiverStream<Result<GameEvent, Status>>;
+
+    async fn subscribe_game_events(
+        &self,
+        request: Request<SubscribeGameEventsRequest>,
+    ) -> Result<Response<Self::SubscribeGameEventsStream>, Status> {
+        let request = request.into_inner();
+        if let Some(game_id) = request.game_id {
+            let exists = sqlx::query_scalar!(r#"SELECT EXISTS(SELECT 1 FROM games WHERE id = $1) as "exists!""#, game_id)
+                .fetch_one(&self.pool)
+                .await
+                .map_err(db_error)?;
+            if !exists {
+                return Err(Status::not_found("Oyun bulunamadı"));
+            }
+        }
+
+        let (sender, receiver) = mpsc::channel(EVENT_BUFFER_SIZE);
+        tokio::spawn(stream_events(self.pool.clone(), request.game_id, request.after_event_id, sender));
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+}
+
+// Yeni olayları yoklayıp aboneye gönder; abone ayrılınca veya tek oyunun aboneliğinde oyun bitince sona erer
+async fn stream_events(
+    pool: Pool<Postgres>,
+    game_id: Option<i32>,
+    mut last_id: i64,
+    sender: mpsc::Sender<Result<GameEvent, Status>>,
+) {
+    let mut interval = tokio::time::interval(EVENT_POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        if sender.is_closed() {
+            return;
+        }
+
+        let events = sqlx::query!(
+            r#"
+            SELECT e.id, e.game_id, g.code, e.sequence, e.event_type, e.payload, e.created_at
+            FROM game_events e
+            JOIN games g ON g.id = e.game_id
+            WHERE e.id > $1 AND ($2::int IS NULL OR e.game_id = $2)
+            ORDER BY e.id
+            LIMIT $3
+            "#,
+            last_id,
+            game_id,
+            EVENT_BATCH_SIZE
+        )
+        .fetch_all(&pool)
+        .await;
+
+        let events = match events {
+            Ok(events) => events,
+            Err(e) => {
+                let _ = sender.send(Err(db_error(e))).await;
+                return;
+            }
+        };
+
+        for event in events {
+            last_id = event.id;
+            let finished = game_id.is_some() && event.event_type == EVENT_GAME_END;
+            let message = GameEvent {
+                id: event.id,
+                game_id: event.game_id,
+                game_code: event.code,
+                sequence: event.sequence,
+                event_type: event.event_type,
+                payload_json: event.payload.to_string(),
+                created_at_ms: event.created_at.map(millis).unwrap_or_default(),
+            };
+            if sender.send(Ok(message)).await.is_err() || finished {
+                return;
+            }
+        }
+    }
+}
+
+// İstek metadata'sındaki paylaşılan anahtarı doğrula
+fn check_token(request: Request<()>) -> Result<Request<()>, Status> {
+    let token = request
+        .metadata()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match token {
+        Some(token) if security::internal_token_matches(token) => Ok(request),
+        _ => Err(Status::unauthenticated("Geçersiz dahili API anahtarı")),
+    }
+}
+
+// Dahili gRPC sunucusunu başlat (INTERNAL_GRPC_ADDR boşsa hiçbir şey yapmaz)
+pub async fn serve(pool: Pool<Postgres>) {
+    if CONFIG.internal_grpc_addr.is_empty() {
+        return;
+    }
+
+    let addr = match CONFIG.internal_grpc_addr.parse() {
+        Ok(addr) => addr,
+        Err(e) => {
+            error!("INTERNAL_GRPC_ADDR geçersiz ({}): {}", CONFIG.internal_grpc_addr, e);
+            return;
+        }
+    };
+
+    info!("Dahili gRPC sunucusu başlatılıyor: {}", addr);
+    let service = InternalApiServer::with_interceptor(InternalService { pool }, check_token);
+    if let Err(e) = tonic::transport::Server::builder().add_service(service).serve(addr).await {
+        error!("Dahili gRPC sunucusu durdu: {}", e);
+    }
+}
\ No newline at end of file
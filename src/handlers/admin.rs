@@ -1,21 +1,41 @@
 use actix_web::{web, HttpResponse, Responder};
+use chrono::{Duration, Utc};
 use log::{error, info};
 use sqlx::{Pool, Postgres};
 
-use crate::db::models::{ApproveUserDto, Claims};
+use crate::config::CONFIG;
+use crate::db::models::{ApproveUserDto, AuditLogQuery, Claims, CreateApiKeyDto, CreateBlocklistEntryDto, ReportQuery, ResolveReportDto, StatsTimeseriesQuery, UpdateEmailPolicyDto};
+use crate::db::pool::pool_stats;
+use crate::handlers::websocket::AppState;
+use crate::services::api_keys;
+use crate::services::audit;
+use crate::services::blocklist;
 use crate::services::email::EmailService;
+use crate::services::email_domains;
+use crate::services::game_events;
+use crate::services::notifications;
+use crate::services::organization::{self, AdminScope};
+use crate::services::platform_stats;
+use crate::services::push::PushService;
+use crate::services::settings;
+use crate::services::warehouse_export::{self, ExportSettings};
+use crate::utils::security::generate_api_key;
+use crate::utils::validation::EmailPolicy;
 
 // Onay bekleyen öğretmenleri listele
 pub async fn list_pending_teachers(
     pool: web::Data<Pool<Postgres>>,
-    claims: web::ReqData<Claims>,
+    claims: Claims,
 ) -> impl Responder {
-    // Sadece adminler erişebilir
-    if claims.role != "admin" {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "error": "Bu işlem için admin yetkisi gerekiyor"
-        }));
-    }
+    // Sistem adminleri tüm kullanıcılara, kurum adminleri kendi kurumlarına erişebilir
+    let scope = match organization::admin_scope(&claims) {
+        Some(scope) => scope,
+        None => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu işlem için admin yetkisi gerekiyor"
+            }));
+        }
+    };
     
     // Onay bekleyen öğretmenleri getir
     let teachers = sqlx::query!(
@@ -23,8 +43,10 @@ pub async fn list_pending_teachers(
         SELECT id, username, email, created_at
         FROM users
         WHERE role = 'teacher' AND is_approved = false AND is_email_verified = true
+          AND ($1::int IS NULL OR organization_id = $1)
         ORDER BY created_at
-        "#
+        "#,
+        scope.organization_id()
     )
     .fetch_all(&**pool)
     .await;
@@ -55,19 +77,22 @@ pub async fn list_pending_teachers(
 pub async fn approve_teacher(
     pool: web::Data<Pool<Postgres>>,
     approval: web::Json<ApproveUserDto>,
-    claims: web::ReqData<Claims>,
+    claims: Claims,
 ) -> impl Responder {
-    // Sadece adminler erişebilir
-    if claims.role != "admin" {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "error": "Bu işlem için admin yetkisi gerekiyor"
-        }));
-    }
+    // Sistem adminleri tüm kullanıcılara, kurum adminleri kendi kurumlarına erişebilir
+    let scope = match organization::admin_scope(&claims) {
+        Some(scope) => scope,
+        None => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu işlem için admin yetkisi gerekiyor"
+            }));
+        }
+    };
     
     // Kullanıcının öğretmen olup olmadığını kontrol et
     let user = sqlx::query!(
         r#"
-        SELECT id, username, email, role
+        SELECT id, username, email, role, organization_id
         FROM users
         WHERE id = $1
         "#,
@@ -77,6 +102,11 @@ pub async fn approve_teacher(
     .await;
     
     match user {
+        Ok(Some(user)) if !scope.covers(user.organization_id) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Kullanıcı bulunamadı"
+            }))
+        }
         Ok(Some(user)) => {
             if user.role != "teacher" {
                 return HttpResponse::BadRequest().json(serde_json::json!({
@@ -105,6 +135,35 @@ pub async fn approve_teacher(
                         )
                         .await;
                     
+                    notifications::notify(
+                        &pool,
+                        user.id,
+                        notifications::KIND_TEACHER_APPROVAL,
+                        "Öğretmen hesabı",
+                        if approval.approve {
+                            "Öğretmen hesabınız onaylandı"
+                        } else {
+                            "Öğretmen hesabı başvurunuz reddedildi"
+                        },
+                        serde_json::json!({"approved": approval.approve}),
+                    )
+                    .await;
+                    
+                    // Kayıtlı cihazlarına push bildirimi gönder
+                    PushService::new()
+                        .send_to_user(
+                            &pool,
+                            user.id,
+                            "Öğretmen hesabı",
+                            if approval.approve {
+                                "Öğretmen hesabınız onaylandı"
+                            } else {
+                                "Öğretmen hesabı başvurunuz reddedildi"
+                            },
+                            serde_json::json!({"type": "teacher_approval", "approved": approval.approve}),
+                        )
+                        .await;
+                    
                     info!(
                         "Öğretmen {} {}",
                         user.username,
@@ -144,22 +203,28 @@ pub async fn approve_teacher(
 // Tüm kullanıcıları listele (admin için)
 pub async fn list_all_users(
     pool: web::Data<Pool<Postgres>>,
-    claims: web::ReqData<Claims>,
+    claims: Claims,
 ) -> impl Responder {
-    // Sadece adminler erişebilir
-    if claims.role != "admin" {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "error": "Bu işlem için admin yetkisi gerekiyor"
-        }));
-    }
+    // Sistem adminleri tüm kullanıcılara, kurum adminleri kendi kurumlarına erişebilir
+    let scope = match organization::admin_scope(&claims) {
+        Some(scope) => scope,
+        None => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu işlem için admin yetkisi gerekiyor"
+            }));
+        }
+    };
     
     // Tüm kullanıcıları getir
     let users = sqlx::query!(
         r#"
-        SELECT id, username, email, role, is_approved, is_email_verified, created_at, last_login
+        SELECT id, username, email, role, is_approved, is_email_verified, created_at, last_login,
+               organization_id, is_org_admin
         FROM users
+        WHERE $1::int IS NULL OR organization_id = $1
         ORDER BY created_at DESC
-        "#
+        "#,
+        scope.organization_id()
     )
     .fetch_all(&**pool)
     .await;
@@ -176,7 +241,9 @@ pub async fn list_all_users(
                         "is_approved": u.is_approved,
                         "is_email_verified": u.is_email_verified,
                         "created_at": u.created_at,
-                        "last_login": u.last_login
+                        "last_login": u.last_login,
+                        "organization_id": u.organization_id,
+                        "is_org_admin": u.is_org_admin
                     })
                 }).collect::<Vec<_>>()
             }))
@@ -194,35 +261,65 @@ pub async fn list_all_users(
 pub async fn delete_user(
     pool: web::Data<Pool<Postgres>>,
     user_id: web::Path<i32>,
-    claims: web::ReqData<Claims>,
+    claims: Claims,
 ) -> impl Responder {
-    // Sadece adminler erişebilir
-    if claims.role != "admin" {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "error": "Bu işlem için admin yetkisi gerekiyor"
-        }));
-    }
+    // Sistem adminleri tüm kullanıcılara, kurum adminleri kendi kurumlarına erişebilir
+    let scope = match organization::admin_scope(&claims) {
+        Some(scope) => scope,
+        None => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu işlem için admin yetkisi gerekiyor"
+            }));
+        }
+    };
     
     // into_inner'ı bir kez kullanıp saklayalım
     let user_id_inner = user_id.into_inner();
     
-    // Admin kullanıcıyı silemez
-    if user_id_inner == 1 {
+    // Adminler kendi hesaplarını bu uç noktadan silemez
+    if user_id_inner == claims.sub.parse::<i32>().unwrap_or_default() {
         return HttpResponse::BadRequest().json(serde_json::json!({
-            "error": "Ana admin kullanıcı silinemez"
+            "error": "Kendi hesabınızı silemezsiniz"
         }));
     }
     
     // Kullanıcıyı getir
     let user = sqlx::query!(
-        "SELECT username FROM users WHERE id = $1",
+        "SELECT username, role, organization_id FROM users WHERE id = $1",
         user_id_inner
     )
     .fetch_optional(&**pool)
     .await;
     
     match user {
+        Ok(Some(user)) if !scope.covers(user.organization_id) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Kullanıcı bulunamadı"
+            }))
+        }
+        Ok(Some(user)) if user.role == "admin" && scope != AdminScope::Global => {
+            HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Sistem adminleri kurum adminleri tarafından silinemez"
+            }))
+        }
         Ok(Some(user)) => {
+            // Sistemde en az bir admin kalmalı
+            if user.role == "admin" {
+                let admin_count = sqlx::query!(
+                    r#"SELECT COUNT(*) as "count!" FROM users WHERE role = 'admin'"#
+                )
+                .fetch_one(&**pool)
+                .await
+                .map(|r| r.count)
+                .unwrap_or(0);
+                
+                if admin_count <= 1 {
+                    return HttpResponse::BadRequest().json(serde_json::json!({
+                        "error": "Son admin kullanıcı silinemez"
+                    }));
+                }
+            }
+            
             // Kullanıcıyı sil (cascade ile ilişkili tüm veriler silinecek)
             let result = sqlx::query!(
                 "DELETE FROM users WHERE id = $1",
@@ -263,7 +360,104 @@ pub async fn delete_user(
 // Sistem istatistiklerini getir
 pub async fn get_system_stats(
     pool: web::Data<Pool<Postgres>>,
-    claims: web::ReqData<Claims>,
+    claims: Claims,
+) -> impl Responder {
+    // Sistem adminleri tüm kullanıcılara, kurum adminleri kendi kurumlarına erişebilir
+    let scope = match organization::admin_scope(&claims) {
+        Some(scope) => scope,
+        None => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu işlem için admin yetkisi gerekiyor"
+            }));
+        }
+    };
+    
+    match platform_stats::current_counts(&pool, scope.organization_id()).await {
+        Ok(counts) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "users": {
+                    "total": (counts.students + counts.teachers
+                        + if scope == AdminScope::Global { 1 } else { 0 }), // +1 for admin
+                    "students": counts.students,
+                    "teachers": counts.teachers,
+                    "pending_teachers": counts.pending_teachers,
+                    "unverified": counts.unverified
+                },
+                "content": {
+                    "question_sets": counts.question_sets,
+                    "questions": counts.questions,
+                    "games": {
+                        "total": counts.games,
+                        "active": counts.active_games
+                    },
+                    "players": counts.players
+                },
+                "system": {
+                    "active_connections": counts.active_connections,
+                    // Altyapı bilgisi yalnızca sistem adminlerine gösterilir
+                    "database_pool": (scope == AdminScope::Global).then(|| pool_stats(&pool))
+                }
+            }))
+        }
+        Err(e) => {
+            error!("İstatistikler alınırken hata oluştu: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Sistem istatistikleri alınamadı"
+            }))
+        }
+    }
+}
+
+// Günlük platform istatistikleri (grafikler için zaman serisi)
+pub async fn get_stats_timeseries(
+    pool: web::Data<Pool<Postgres>>,
+    query: web::Query<StatsTimeseriesQuery>,
+    claims: Claims,
+) -> impl Responder {
+    let scope = match organization::admin_scope(&claims) {
+        Some(scope) => scope,
+        None => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu işlem için admin yetkisi gerekiyor"
+            }));
+        }
+    };
+    
+    let (from, to) = match platform_stats::resolve_range(query.from, query.to) {
+        Ok(range) => range,
+        Err(message) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": message
+            }));
+        }
+    };
+    
+    let series = platform_stats::timeseries(&pool, scope.organization_id(), from, to).await;
+    let last_day = platform_stats::last_aggregated_day(&pool).await;
+    
+    match (series, last_day) {
+        (Ok(series), Ok(last_day)) => HttpResponse::Ok().json(serde_json::json!({
+            "from": from,
+            "to": to,
+            // İstatistikler her gün gece toplanır; bu tarihten sonraki günler henüz boş döner
+            "aggregated_through": last_day,
+            "series": series
+        })),
+        (Err(e), _) | (_, Err(e)) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "İstatistik zaman serisi alınamadı"
+            }))
+        }
+    }
+}
+
+// Takılı kalmış bir oyunu zorla sonlandır
+pub async fn force_end_game(
+    pool: web::Data<Pool<Postgres>>,
+    app_state: web::Data<AppState>,
+    game_code: web::Path<String>,
+    claims: Claims,
 ) -> impl Responder {
     // Sadece adminler erişebilir
     if claims.role != "admin" {
@@ -272,72 +466,912 @@ pub async fn get_system_stats(
         }));
     }
     
-    // Kullanıcı sayıları
-    let user_counts = sqlx::query!(
-        r#"
-        SELECT
-            COUNT(*) FILTER (WHERE role = 'student') as student_count,
-            COUNT(*) FILTER (WHERE role = 'teacher') as teacher_count,
-            COUNT(*) FILTER (WHERE role = 'teacher' AND is_approved = false) as pending_teacher_count,
-            COUNT(*) FILTER (WHERE is_email_verified = false) as unverified_count
-        FROM users
-        "#
+    let game_code_inner = game_code.into_inner();
+    
+    let game = sqlx::query!(
+        "SELECT id, status FROM games WHERE code = $1",
+        game_code_inner
     )
-    .fetch_one(&**pool)
+    .fetch_optional(&**pool)
     .await;
     
-    // Oyun ve soru seti sayıları
-    let content_counts = sqlx::query!(
+    match game {
+        Ok(Some(game)) => {
+            if game.status == "completed" {
+                return HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "Bu oyun zaten tamamlanmış"
+                }));
+            }
+            
+            let result = sqlx::query!(
+                "UPDATE games SET status = 'completed', ended_at = $1 WHERE id = $2",
+                Utc::now(),
+                game.id
+            )
+            .execute(&**pool)
+            .await;
+            
+            match result {
+                Ok(_) => {
+                    game_events::record_event(&pool, game.id, game_events::EVENT_GAME_END, serde_json::json!({
+                        "reason": "admin_ended"
+                    })).await;
+                    
+                    // Bağlı istemcilere oyunun sonlandırıldığını bildir
+                    app_state
+                        .end_game(&game_code_inner, "admin_ended", "Oyun yönetici tarafından sonlandırıldı")
+                        .await;
+                    
+                    info!("Oyun admin tarafından sonlandırıldı: {}", game_code_inner);
+                    
+                    HttpResponse::Ok().json(serde_json::json!({
+                        "message": format!("Oyun sonlandırıldı: {}", game_code_inner),
+                        "game_id": game.id,
+                        "previous_status": game.status,
+                        "status": "completed"
+                    }))
+                }
+                Err(e) => {
+                    error!("Oyun sonlandırma hatası: {}", e);
+                    HttpResponse::InternalServerError().json(serde_json::json!({
+                        "error": "Oyun sonlandırılamadı"
+                    }))
+                }
+            }
+        }
+        Ok(None) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Oyun bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Oyun sonlandırılamadı"
+            }))
+        }
+    }
+}
+
+// Oyunu ve ilişkili tüm verileri sil
+pub async fn force_delete_game(
+    pool: web::Data<Pool<Postgres>>,
+    app_state: web::Data<AppState>,
+    game_code: web::Path<String>,
+    claims: Claims,
+) -> impl Responder {
+    // Sadece adminler erişebilir
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    let game_code_inner = game_code.into_inner();
+    
+    let game = sqlx::query!(
+        "SELECT id FROM games WHERE code = $1",
+        game_code_inner
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match game {
+        Ok(Some(game)) => {
+            // Silmeden önce bağlı istemcileri bilgilendir
+            app_state
+                .end_game(&game_code_inner, "admin_deleted", "Oyun yönetici tarafından kaldırıldı")
+                .await;
+            
+            // Oyuncular, cevaplar, bağlantılar ve olaylar cascade ile silinir
+            let result = sqlx::query!(
+                "DELETE FROM games WHERE id = $1",
+                game.id
+            )
+            .execute(&**pool)
+            .await;
+            
+            match result {
+                Ok(_) => {
+                    app_state.remove_game(&game_code_inner).await;
+                    
+                    info!("Oyun admin tarafından silindi: {}", game_code_inner);
+                    
+                    HttpResponse::Ok().json(serde_json::json!({
+                        "message": format!("Oyun silindi: {}", game_code_inner)
+                    }))
+                }
+                Err(e) => {
+                    error!("Oyun silme hatası: {}", e);
+                    HttpResponse::InternalServerError().json(serde_json::json!({
+                        "error": "Oyun silinemedi"
+                    }))
+                }
+            }
+        }
+        Ok(None) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Oyun bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Oyun silinemedi"
+            }))
+        }
+    }
+}
+
+// Denetim kaydını listele (isteğe bağlı kullanıcı ve olay tipi filtresiyle)
+pub async fn list_audit_log(
+    pool: web::Data<Pool<Postgres>>,
+    query: web::Query<AuditLogQuery>,
+    claims: Claims,
+) -> impl Responder {
+    // Sadece adminler erişebilir
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+
+    match audit::list(&pool, query.user_id, query.action.as_deref(), limit).await {
+        Ok(entries) => HttpResponse::Ok().json(serde_json::json!({ "entries": entries })),
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Denetim kaydı alınamadı"
+            }))
+        }
+    }
+}
+
+// Moderasyon kuyruğundaki şikayetleri listele
+pub async fn list_reports(
+    pool: web::Data<Pool<Postgres>>,
+    query: web::Query<ReportQuery>,
+    claims: Claims,
+) -> impl Responder {
+    // Sadece adminler erişebilir
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    // Varsayılan olarak bekleyen şikayetler, "all" ile hepsi
+    let status_filter = match query.status.as_deref() {
+        Some("all") => None,
+        Some(status) => Some(status.to_string()),
+        None => Some("pending".to_string()),
+    };
+    
+    let reports = sqlx::query!(
         r#"
-        SELECT
-            (SELECT COUNT(*) FROM question_sets) as question_set_count,
-            (SELECT COUNT(*) FROM questions) as question_count,
-            (SELECT COUNT(*) FROM games) as game_count,
-            (SELECT COUNT(*) FROM games WHERE status = 'active') as active_game_count,
-            (SELECT COUNT(*) FROM players) as player_count
-        "#
+        SELECT r.id, r.target_type, r.target_id, r.reason, r.status, r.resolution_note,
+               r.created_at, r.resolved_at,
+               ru.username as "reporter_username?",
+               rp.nickname as "reporter_nickname?",
+               qs.title as "set_title?",
+               qsu.username as "set_owner?",
+               p.nickname as "target_nickname?",
+               g.code as "game_code?",
+               (SELECT COUNT(*) FROM content_reports r2
+                WHERE r2.target_type = r.target_type AND r2.target_id = r.target_id
+                  AND r2.status = 'pending') as "pending_count!"
+        FROM content_reports r
+        LEFT JOIN users ru ON r.reporter_user_id = ru.id
+        LEFT JOIN players rp ON r.reporter_player_id = rp.id
+        LEFT JOIN question_sets qs ON r.target_type = 'question_set' AND qs.id = r.target_id
+        LEFT JOIN users qsu ON qs.creator_id = qsu.id
+        LEFT JOIN players p ON r.target_type = 'nickname' AND p.id = r.target_id
+        LEFT JOIN games g ON p.game_id = g.id
+        WHERE ($1::text IS NULL OR r.status = $1)
+        ORDER BY r.created_at
+        "#,
+        status_filter
     )
-    .fetch_one(&**pool)
+    .fetch_all(&**pool)
     .await;
     
-    // Aktif bağlantı sayısı
-    let active_connections = sqlx::query!(
+    match reports {
+        Ok(reports) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "reports": reports.iter().map(|r| {
+                    serde_json::json!({
+                        "id": r.id,
+                        "target_type": r.target_type,
+                        "target_id": r.target_id,
+                        "target": if r.target_type == "question_set" {
+                            serde_json::json!({
+                                "title": r.set_title,
+                                "owner": r.set_owner
+                            })
+                        } else {
+                            serde_json::json!({
+                                "nickname": r.target_nickname,
+                                "game_code": r.game_code
+                            })
+                        },
+                        "reason": r.reason,
+                        "reporter": r.reporter_username.clone().or_else(|| r.reporter_nickname.clone()),
+                        "pending_reports_for_target": r.pending_count,
+                        "status": r.status,
+                        "resolution_note": r.resolution_note,
+                        "created_at": r.created_at,
+                        "resolved_at": r.resolved_at
+                    })
+                }).collect::<Vec<_>>()
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Şikayet listesi alınamadı"
+            }))
+        }
+    }
+}
+
+// Şikayeti sonuçlandır (yoksay, gizle veya sil)
+pub async fn resolve_report(
+    pool: web::Data<Pool<Postgres>>,
+    report_id: web::Path<i32>,
+    resolve_dto: web::Json<ResolveReportDto>,
+    claims: Claims,
+) -> impl Responder {
+    // Sadece adminler erişebilir
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    let admin_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let report_id_inner = report_id.into_inner();
+    
+    let new_status = match resolve_dto.action.as_str() {
+        "dismiss" => "dismissed",
+        "hide" => "hidden",
+        "delete" => "deleted",
+        _ => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "İşlem 'dismiss', 'hide' veya 'delete' olmalıdır"
+            }));
+        }
+    };
+    
+    let report = sqlx::query!(
+        "SELECT id, target_type, target_id, status FROM content_reports WHERE id = $1",
+        report_id_inner
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    let report = match report {
+        Ok(Some(report)) => report,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Şikayet bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Şikayet sonuçlandırılamadı"
+            }));
+        }
+    };
+    
+    if report.status != "pending" {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Bu şikayet zaten sonuçlandırılmış"
+        }));
+    }
+    
+    // İçerik sahibini bul (bildirim e-postası için)
+    let (owner, content_label) = if report.target_type == "question_set" {
+        let owner = sqlx::query!(
+            r#"
+            SELECT qs.title, u.id as user_id, u.email, u.username
+            FROM question_sets qs
+            JOIN users u ON qs.creator_id = u.id
+            WHERE qs.id = $1
+            "#,
+            report.target_id
+        )
+        .fetch_optional(&**pool)
+        .await
+        .ok()
+        .flatten();
+        
+        let label = owner.as_ref().map(|o| format!("\"{}\" soru seti", o.title)).unwrap_or_default();
+        (owner.map(|o| (o.user_id, o.email, o.username)), label)
+    } else {
+        let owner = sqlx::query!(
+            r#"
+            SELECT p.nickname, u.id as "user_id?", u.email as "email?", u.username as "username?"
+            FROM players p
+            LEFT JOIN users u ON p.user_id = u.id
+            WHERE p.id = $1
+            "#,
+            report.target_id
+        )
+        .fetch_optional(&**pool)
+        .await
+        .ok()
+        .flatten();
+        
+        let label = owner.as_ref().map(|o| format!("\"{}\" takma adı", o.nickname)).unwrap_or_default();
+        let owner = owner.and_then(|o| match (o.user_id, o.email, o.username) {
+            (Some(user_id), Some(email), Some(username)) => Some((user_id, email, username)),
+            _ => None,
+        });
+        (owner, label)
+    };
+    
+    // İşlemi uygula
+    let action_result = match (report.target_type.as_str(), resolve_dto.action.as_str()) {
+        (_, "dismiss") => Ok(()),
+        ("question_set", "hide") => sqlx::query!(
+            "UPDATE question_sets SET is_hidden = true WHERE id = $1",
+            report.target_id
+        )
+        .execute(&**pool)
+        .await
+        .map(|_| ()),
+        ("question_set", _) => {
+            // Soru setini kullanan oyunlar da silinir (games tablosunda cascade yok)
+            let tx = pool.begin().await;
+            match tx {
+                Ok(mut tx) => {
+                    let result = async {
+                        sqlx::query!("DELETE FROM games WHERE question_set_id = $1", report.target_id)
+                            .execute(&mut *tx)
+                            .await?;
+                        sqlx::query!("DELETE FROM question_sets WHERE id = $1", report.target_id)
+                            .execute(&mut *tx)
+                            .await?;
+                        tx.commit().await
+                    }
+                    .await;
+                    result
+                }
+                Err(e) => Err(e),
+            }
+        }
+        (_, "hide") => sqlx::query!(
+            r#"
+            UPDATE players
+            SET nickname = CASE WHEN user_id IS NULL THEN '**Oyuncu' ELSE 'Oyuncu' END || id
+            WHERE id = $1
+            "#,
+            report.target_id
+        )
+        .execute(&**pool)
+        .await
+        .map(|_| ()),
+        _ => {
+            // Takma adı gizle ve oyuncuyu oyundan çıkar
+            let result = sqlx::query!(
+                r#"
+                UPDATE players
+                SET nickname = CASE WHEN user_id IS NULL THEN '**Oyuncu' ELSE 'Oyuncu' END || id,
+                    is_active = false
+                WHERE id = $1
+                "#,
+                report.target_id
+            )
+            .execute(&**pool)
+            .await;
+            
+            if result.is_ok() {
+                let _ = sqlx::query!(
+                    "DELETE FROM active_connections WHERE player_id = $1",
+                    report.target_id
+                )
+                .execute(&**pool)
+                .await;
+            }
+            result.map(|_| ())
+        }
+    };
+    
+    if let Err(e) = action_result {
+        error!("Moderasyon işlemi uygulanamadı: {}", e);
+        return HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Moderasyon işlemi uygulanamadı"
+        }));
+    }
+    
+    // Aynı içerik için bekleyen tüm şikayetleri kapat
+    let result = sqlx::query!(
         r#"
-        SELECT COUNT(*) as count FROM active_connections
-        WHERE last_seen > CURRENT_TIMESTAMP - INTERVAL '1 minute'
-        "#
+        UPDATE content_reports
+        SET status = $1, resolved_by = $2, resolution_note = $3, resolved_at = $4
+        WHERE target_type = $5 AND target_id = $6 AND status = 'pending'
+        "#,
+        new_status,
+        admin_id,
+        resolve_dto.note,
+        Utc::now(),
+        report.target_type,
+        report.target_id
     )
-    .fetch_one(&**pool)
+    .execute(&**pool)
     .await;
     
-    match (user_counts, content_counts, active_connections) {
-        (Ok(users), Ok(content), Ok(connections)) => {
+    match result {
+        Ok(updated) => {
+            // İçerik sahibine bildirim gönder
+            if resolve_dto.action != "dismiss" {
+                if let Some((user_id, email, username)) = owner {
+                    let email_service = EmailService::new();
+                    let _ = email_service
+                        .send_moderation_email(
+                            &email,
+                            &username,
+                            &content_label,
+                            &resolve_dto.action,
+                            resolve_dto.note.as_deref(),
+                        )
+                        .await;
+                    
+                    notifications::notify(
+                        &pool,
+                        user_id,
+                        notifications::KIND_MODERATION,
+                        "İçeriğiniz moderasyona takıldı",
+                        &format!(
+                            "{} {}",
+                            content_label,
+                            if resolve_dto.action == "hide" { "gizlendi" } else { "silindi" }
+                        ),
+                        serde_json::json!({
+                            "target_type": report.target_type,
+                            "target_id": report.target_id,
+                            "action": resolve_dto.action,
+                            "note": resolve_dto.note
+                        }),
+                    )
+                    .await;
+                }
+            }
+            
+            info!(
+                "Şikayet sonuçlandırıldı: id={}, işlem={}",
+                report.id, resolve_dto.action
+            );
+            
             HttpResponse::Ok().json(serde_json::json!({
-                "users": {
-                    "total": (users.student_count.unwrap_or(0) + users.teacher_count.unwrap_or(0) + 1), // +1 for admin
-                    "students": users.student_count.unwrap_or(0),
-                    "teachers": users.teacher_count.unwrap_or(0),
-                    "pending_teachers": users.pending_teacher_count.unwrap_or(0),
-                    "unverified": users.unverified_count.unwrap_or(0)
-                },
-                "content": {
-                    "question_sets": content.question_set_count.unwrap_or(0),
-                    "questions": content.question_count.unwrap_or(0),
-                    "games": {
-                        "total": content.game_count.unwrap_or(0),
-                        "active": content.active_game_count.unwrap_or(0)
-                    },
-                    "players": content.player_count.unwrap_or(0)
-                },
-                "system": {
-                    "active_connections": connections.count.unwrap_or(0)
+                "message": "Şikayet sonuçlandırıldı",
+                "status": new_status,
+                "resolved_reports": updated.rows_affected()
+            }))
+        }
+        Err(e) => {
+            error!("Şikayet güncelleme hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Şikayet sonuçlandırılamadı"
+            }))
+        }
+    }
+}
+
+// Kayıt e-posta politikasını getir
+pub async fn get_email_policy(
+    pool: web::Data<Pool<Postgres>>,
+    claims: Claims,
+) -> impl Responder {
+    // Sadece adminler erişebilir
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    let policy = email_domains::current_policy(&pool).await;
+    
+    HttpResponse::Ok().json(serde_json::json!({
+        "policy": policy.as_str(),
+        "default_policy": CONFIG.email_policy.as_str(),
+        "is_default": policy == CONFIG.email_policy
+    }))
+}
+
+// Kayıt e-posta politikasını güncelle
+pub async fn update_email_policy(
+    pool: web::Data<Pool<Postgres>>,
+    policy_dto: web::Json<UpdateEmailPolicyDto>,
+    claims: Claims,
+) -> impl Responder {
+    // Sadece adminler erişebilir
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    let admin_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let result = match &policy_dto.policy {
+        Some(value) => {
+            let policy = match value.parse::<EmailPolicy>() {
+                Ok(policy) => policy,
+                Err(_) => {
+                    return HttpResponse::BadRequest().json(serde_json::json!({
+                        "error": "Politika 'edu_only', 'edu_or_allowlist', 'allowlist' veya 'open' olmalıdır"
+                    }));
                 }
+            };
+            settings::set_setting(&pool, settings::SETTING_EMAIL_POLICY, policy.as_str(), admin_id).await
+        }
+        None => settings::clear_setting(&pool, settings::SETTING_EMAIL_POLICY).await,
+    };
+    
+    match result {
+        Ok(_) => {
+            let policy = email_domains::current_policy(&pool).await;
+            info!("Kayıt e-posta politikası güncellendi: {} (admin_id: {})", policy.as_str(), admin_id);
+            HttpResponse::Ok().json(serde_json::json!({
+                "policy": policy.as_str(),
+                "is_default": policy_dto.policy.is_none()
             }))
         }
-        _ => {
-            error!("İstatistikler alınırken hata oluştu");
+        Err(e) => {
+            error!("E-posta politikası güncellenirken hata: {}", e);
             HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Sistem istatistikleri alınamadı"
+                "error": "E-posta politikası güncellenemedi"
+            }))
+        }
+    }
+}
+
+// Veri ambarı aktarım ayarlarını ve son aktarımları getir
+pub async fn get_warehouse_export(
+    pool: web::Data<Pool<Postgres>>,
+    claims: Claims,
+) -> impl Responder {
+    // Sadece adminler erişebilir
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    match warehouse_export::recent_exports(&pool).await {
+        Ok(exports) => HttpResponse::Ok().json(serde_json::json!({
+            "settings": warehouse_export::current_settings(&pool).await,
+            "s3_credentials_configured": warehouse_export::s3_credentials_configured(),
+            "exports": exports
+        })),
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Veri ambarı aktarımları alınamadı"
+            }))
+        }
+    }
+}
+
+// Veri ambarı aktarımının zamanlamasını ve hedefini güncelle
+pub async fn update_warehouse_export(
+    pool: web::Data<Pool<Postgres>>,
+    settings_dto: web::Json<ExportSettings>,
+    claims: Claims,
+) -> impl Responder {
+    // Sadece adminler erişebilir
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    let admin_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let export_settings = match settings_dto.into_inner().validate() {
+        Ok(export_settings) => export_settings,
+        Err(message) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": message
+            }));
+        }
+    };
+    
+    let value = serde_json::to_string(&export_settings).unwrap_or_default();
+    match settings::set_setting(&pool, settings::SETTING_WAREHOUSE_EXPORT, &value, admin_id).await {
+        Ok(_) => {
+            info!("Veri ambarı aktarım ayarları güncellendi (admin_id: {})", admin_id);
+            HttpResponse::Ok().json(serde_json::json!({
+                "settings": export_settings
+            }))
+        }
+        Err(e) => {
+            error!("Veri ambarı aktarım ayarları güncellenirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Veri ambarı aktarım ayarları güncellenemedi"
+            }))
+        }
+    }
+}
+
+// Veri ambarı aktarımını zamanlamayı beklemeden hemen başlat
+pub async fn run_warehouse_export(
+    pool: web::Data<Pool<Postgres>>,
+    claims: Claims,
+) -> impl Responder {
+    // Sadece adminler erişebilir
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    let export_settings = warehouse_export::current_settings(&pool).await;
+    
+    match warehouse_export::run_export(&pool, &export_settings).await {
+        Ok(Some(run)) => HttpResponse::Ok().json(run),
+        Ok(None) => HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Bir veri ambarı aktarımı zaten sürüyor"
+        })),
+        Err(e) => {
+            error!("Veri ambarı aktarımı başlatılırken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Veri ambarı aktarımı başlatılamadı"
+            }))
+        }
+    }
+}
+
+// Güvenilir istemci API anahtarlarını listele (anahtarların kendisi değil, yalnızca başlangıçları gösterilir)
+pub async fn list_api_keys(
+    pool: web::Data<Pool<Postgres>>,
+    claims: Claims,
+) -> impl Responder {
+    // Sadece adminler erişebilir
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    match api_keys::list(&pool).await {
+        Ok(keys) => HttpResponse::Ok().json(serde_json::json!({
+            "api_keys": keys,
+            "available_scopes": api_keys::SCOPES
+        })),
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "API anahtarları alınamadı"
+            }))
+        }
+    }
+}
+
+// Sunucudan sunucuya entegrasyon için yeni API anahtarı oluştur; anahtar yalnızca bu yanıtta gösterilir
+pub async fn create_api_key(
+    pool: web::Data<Pool<Postgres>>,
+    key_dto: web::Json<CreateApiKeyDto>,
+    claims: Claims,
+) -> impl Responder {
+    // Sadece adminler erişebilir
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    let name = key_dto.name.trim();
+    if name.is_empty() || name.chars().count() > 100 {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Anahtar adı 1-100 karakter arasında olmalıdır"
+        }));
+    }
+    
+    let mut scopes = key_dto.scopes.clone();
+    scopes.sort();
+    scopes.dedup();
+    if scopes.is_empty() || scopes.iter().any(|scope| !api_keys::SCOPES.contains(&scope.as_str())) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("Kapsamlar boş olamaz ve yalnızca şunlar olabilir: {}", api_keys::SCOPES.join(", "))
+        }));
+    }
+    
+    let admin_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let key = generate_api_key();
+    
+    match api_keys::create(&pool, name, &scopes, admin_id, &key).await {
+        Ok(summary) => {
+            info!("API anahtarı oluşturuldu: {} (id: {}, admin_id: {})", name, summary.id, admin_id);
+            audit::record(&pool, Some(admin_id), audit::API_KEY_CREATED, serde_json::json!({
+                "api_key_id": summary.id,
+                "name": name,
+                "scopes": scopes
+            })).await;
+            HttpResponse::Created().json(serde_json::json!({
+                "key": key,
+                "api_key": summary
+            }))
+        }
+        Err(e) => {
+            error!("API anahtarı oluşturulurken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "API anahtarı oluşturulamadı"
+            }))
+        }
+    }
+}
+
+// API anahtarını iptal et; anahtarla gelen istekler hemen reddedilir
+pub async fn revoke_api_key(
+    pool: web::Data<Pool<Postgres>>,
+    key_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    // Sadece adminler erişebilir
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    let key_id = key_id.into_inner();
+    let admin_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    match api_keys::revoke(&pool, key_id).await {
+        Ok(Some(name)) => {
+            info!("API anahtarı iptal edildi: {} (id: {}, admin_id: {})", name, key_id, admin_id);
+            audit::record(&pool, Some(admin_id), audit::API_KEY_REVOKED, serde_json::json!({
+                "api_key_id": key_id,
+                "name": name
+            })).await;
+            HttpResponse::Ok().json(serde_json::json!({
+                "message": "API anahtarı iptal edildi"
+            }))
+        }
+        Ok(None) => HttpResponse::NotFound().json(serde_json::json!({
+            "error": "API anahtarı bulunamadı veya zaten iptal edilmiş"
+        })),
+        Err(e) => {
+            error!("API anahtarı iptal edilirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "API anahtarı iptal edilemedi"
+            }))
+        }
+    }
+}
+
+// Engel listesini listele (süresi dolan kayıtlar dahil)
+pub async fn list_blocklist(
+    pool: web::Data<Pool<Postgres>>,
+    claims: Claims,
+) -> impl Responder {
+    // Sadece adminler erişebilir
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    match blocklist::list(&pool).await {
+        Ok(entries) => HttpResponse::Ok().json(serde_json::json!({
+            "entries": entries,
+            "kinds": blocklist::KINDS
+        })),
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Engel listesi alınamadı"
+            }))
+        }
+    }
+}
+
+// Engel listesine IP/CIDR bloğu, e-posta alan adı veya kullanıcı ekle; aynı kayıt varsa süresi ve gerekçesi yenilenir
+pub async fn create_blocklist_entry(
+    pool: web::Data<Pool<Postgres>>,
+    entry_dto: web::Json<CreateBlocklistEntryDto>,
+    claims: Claims,
+) -> impl Responder {
+    // Sadece adminler erişebilir
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    let value = match blocklist::normalize_value(&entry_dto.kind, &entry_dto.value) {
+        Ok(value) => value,
+        Err(message) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": message
+            }));
+        }
+    };
+    
+    let admin_id = claims.sub.parse::<i32>().unwrap_or_default();
+    if entry_dto.kind == blocklist::KIND_USER && value == admin_id.to_string() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Kendinizi engelleyemezsiniz"
+        }));
+    }
+    
+    let expires_at = match entry_dto.expires_in_hours {
+        Some(hours) if hours <= 0 => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Engel süresi pozitif olmalıdır"
+            }));
+        }
+        Some(hours) => Some(Utc::now() + Duration::hours(hours)),
+        None => None,
+    };
+    let reason = entry_dto.reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+    
+    match blocklist::add(&pool, &entry_dto.kind, &value, reason, expires_at, admin_id).await {
+        Ok(entry_id) => {
+            info!("Engel listesine eklendi: {} {} (id: {}, admin_id: {})", entry_dto.kind, value, entry_id, admin_id);
+            audit::record(&pool, Some(admin_id), audit::BLOCKLIST_ENTRY_ADDED, serde_json::json!({
+                "entry_id": entry_id,
+                "kind": entry_dto.kind,
+                "value": value,
+                "reason": reason,
+                "expires_at": expires_at
+            })).await;
+            HttpResponse::Created().json(serde_json::json!({
+                "id": entry_id,
+                "kind": entry_dto.kind,
+                "value": value,
+                "expires_at": expires_at
+            }))
+        }
+        Err(e) => {
+            error!("Engel listesine eklenirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Engel listesine eklenemedi"
+            }))
+        }
+    }
+}
+
+// Engel listesinden kayıt kaldır
+pub async fn delete_blocklist_entry(
+    pool: web::Data<Pool<Postgres>>,
+    entry_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    // Sadece adminler erişebilir
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    let entry_id = entry_id.into_inner();
+    let admin_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    match blocklist::remove(&pool, entry_id).await {
+        Ok(Some((kind, value))) => {
+            info!("Engel listesinden kaldırıldı: {} {} (id: {}, admin_id: {})", kind, value, entry_id, admin_id);
+            audit::record(&pool, Some(admin_id), audit::BLOCKLIST_ENTRY_REMOVED, serde_json::json!({
+                "entry_id": entry_id,
+                "kind": kind,
+                "value": value
+            })).await;
+            HttpResponse::Ok().json(serde_json::json!({
+                "message": "Kayıt engel listesinden kaldırıldı"
+            }))
+        }
+        Ok(None) => HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Kayıt bulunamadı"
+        })),
+        Err(e) => {
+            error!("Engel listesinden kaldırılırken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Kayıt kaldırılamadı"
             }))
         }
     }
@@ -3,10 +3,14 @@ use chrono::{Duration, Utc};
 use log::{error, info};
 use sqlx::{Pool, Postgres};
 
-use crate::db::models::{Claims, CreateUserDto, LoginDto, UserRole};
+use crate::config::CONFIG;
+use crate::db::models::{Claims, CreateUserDto, LoginDto, PasswordStrengthDto, UserRole};
+use crate::db::repositories::{PgUserRepo, UserRepo};
 use crate::services::email::EmailService;
+use crate::services::{audit, email_domains, invitation, password_policy, password_reset, roster, token_version};
 use crate::utils::security::{
-    generate_jwt, generate_reset_token, generate_verification_token, hash_password, verify_password,
+    generate_jwt, generate_reset_token, generate_verification_token, hash_password, hash_reset_token, verify_password,
+    TokenUser,
 };
 use crate::utils::validation;
 
@@ -15,16 +19,58 @@ pub async fn register(
     pool: web::Data<Pool<Postgres>>,
     user_dto: web::Json<CreateUserDto>,
 ) -> impl Responder {
+    let mut user_dto = user_dto.into_inner();
+    // Aynı görünen adların aynı biçimde saklanması için kullanıcı adını normalize et
+    user_dto.username = validation::normalize_name(&user_dto.username);
+
     // Alan doğrulamalarını yap
-    if !validation::validate_email(&user_dto.email) {
+    if !validation::validate_email_format(&user_dto.email) {
         return HttpResponse::BadRequest().json(serde_json::json!({
-            "error": "E-posta adresi .edu.tr veya .edu ile bitmelidir"
+            "error": "Geçersiz e-posta adresi"
         }));
     }
 
-    if !validation::validate_username(&user_dto.username) {
+    // Davet kodu varsa geçerliliğini kontrol et
+    let invitation = match &user_dto.invite_code {
+        Some(code) => match invitation::find_valid(&pool, code).await {
+            Ok(Some(invitation)) => Some(invitation),
+            Ok(None) => {
+                return HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "Davet kodu geçersiz veya süresi dolmuş"
+                }));
+            }
+            Err(e) => {
+                error!("Davet kodu kontrol edilirken hata: {}", e);
+                return HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Kayıt işlemi başarısız oldu"
+                }));
+            }
+        },
+        None => None,
+    };
+
+    // Kurum izin listesindeki alan adları kullanıcıyı doğrudan kuruma bağlar
+    let domain_organization_id = match email_domains::find_organization_for_email(&pool, &user_dto.email).await {
+        Ok(organization_id) => organization_id,
+        Err(e) => {
+            error!("Kurum alan adları alınamadı: {}", e);
+            None
+        }
+    };
+
+    // Kayıt politikası (edu, izin listesi veya açık kayıt); davetli öğrenciler muaftır
+    let email_policy = email_domains::current_policy(&pool).await;
+    if invitation.is_none() && !email_policy.allows(&user_dto.email, domain_organization_id.is_some()) {
         return HttpResponse::BadRequest().json(serde_json::json!({
-            "error": "Kullanıcı adı geçersiz. 3-30 karakter arasında olmalı ve sadece harf, rakam ve alt çizgi içermelidir."
+            "error": email_policy.rejection_message()
+        }));
+    }
+
+    let organization_id = domain_organization_id.or(invitation.as_ref().and_then(|i| i.organization_id));
+
+    if !validation::validate_username(&user_dto.username, CONFIG.username_charset) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": CONFIG.username_charset.username_rule_message()
         }));
     }
 
@@ -34,34 +80,35 @@ pub async fn register(
         }));
     }
 
-    // E-posta adresinin zaten kayıtlı olup olmadığını kontrol et
-    let existing_user = sqlx::query!(
-        "SELECT id FROM users WHERE email = $1",
-        user_dto.email
-    )
-    .fetch_optional(&**pool)
-    .await;
+    if let Err(rejection) =
+        password_policy::check_new_password(&user_dto.password, &[&user_dto.username, &user_dto.email]).await
+    {
+        return HttpResponse::BadRequest().json(rejection.body());
+    }
 
-    if let Ok(Some(_)) = existing_user {
+    // E-posta adresinin zaten kayıtlı olup olmadığını kontrol et
+    let users = PgUserRepo::new(&pool);
+    if let Ok(true) = users.email_taken(&user_dto.email).await {
         return HttpResponse::Conflict().json(serde_json::json!({
             "error": "Bu e-posta adresi zaten kullanımda"
         }));
     }
 
     // Kullanıcı adının zaten kayıtlı olup olmadığını kontrol et
-    let existing_username = sqlx::query!(
-        "SELECT id FROM users WHERE username = $1",
-        user_dto.username
-    )
-    .fetch_optional(&**pool)
-    .await;
-
-    if let Ok(Some(_)) = existing_username {
+    if let Ok(true) = users.username_taken(&user_dto.username).await {
         return HttpResponse::Conflict().json(serde_json::json!({
             "error": "Bu kullanıcı adı zaten kullanımda"
         }));
     }
 
+    // Mevcut bir kullanıcı adını taklit eden adları (ör. büyük/küçük harf veya benzer görünen harf farkı) reddet
+    let username_skeleton = validation::name_skeleton(&user_dto.username);
+    if let Ok(true) = users.username_lookalike_taken(&username_skeleton).await {
+        return HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Bu kullanıcı adı mevcut bir kullanıcı adına çok benziyor, lütfen başka bir ad seçin"
+        }));
+    }
+
     // Misafirler için ** öneki kontrol et
     if user_dto.username.starts_with("**") {
         return HttpResponse::BadRequest().json(serde_json::json!({
@@ -82,34 +129,103 @@ pub async fn register(
 
     // Doğrulama tokeni oluştur
     let verification_token = generate_verification_token();
+    let verification_expires_at = Utc::now() + Duration::hours(CONFIG.email_verification_expiry_hours);
 
-    // Kullanıcıyı veritabanına ekle
-    let role = user_dto.role.clone();
+    // Kullanıcıyı veritabanına ekle (davetle gelenler her zaman öğrencidir)
+    let role = if invitation.is_some() { UserRole::Student } else { user_dto.role.clone() };
     let is_approved = match &role {
         UserRole::Student => true, // Öğrenci hesapları otomatik onaylanır
         UserRole::Teacher => false, // Öğretmen hesapları admin onayı gerektirir
         UserRole::Admin => false, // Admin hesapları oluşturulamaz (hardcoded)
     };
 
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("Veritabanı işlem hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Kayıt işlemi başarısız oldu"
+            }));
+        }
+    };
+
+    // Davet kullanım hakkını kullanıcı kaydıyla aynı işlemde düş
+    if let Some(invitation) = &invitation {
+        match invitation::consume(&mut tx, invitation.id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                return HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "Davet kodu geçersiz veya süresi dolmuş"
+                }));
+            }
+            Err(e) => {
+                error!("Davet kodu kullanılırken hata: {}", e);
+                return HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Kayıt işlemi başarısız oldu"
+                }));
+            }
+        }
+    }
+
     let result = sqlx::query!(
         r#"
-        INSERT INTO users (username, email, password_hash, role, is_approved, is_email_verified, verification_token, created_at)
-        VALUES ($1, $2, $3, $4, $5, false, $6, $7)
+        INSERT INTO users (username, username_skeleton, email, password_hash, role, is_approved, is_email_verified,
+                           verification_token, verification_token_expires_at, created_at, organization_id, invited_by,
+                           invitation_id)
+        VALUES ($1, $2, $3, $4, $5, $6, false, $7, $8, $9, $10, $11, $12)
         RETURNING id
         "#,
         user_dto.username,
+        username_skeleton,
         user_dto.email,
         password_hash,
         role.to_string().to_lowercase(),
         is_approved,
         verification_token,
-        Utc::now()
+        verification_expires_at,
+        Utc::now(),
+        organization_id,
+        invitation.as_ref().map(|i| i.teacher_id),
+        invitation.as_ref().map(|i| i.id)
     )
-    .fetch_one(&**pool)
+    .fetch_one(&mut *tx)
     .await;
 
+    // Davet bir sınıfa bağlıysa öğrenciyi sınıfa ekle
+    let result = match (result, invitation.as_ref().and_then(|i| i.class_id)) {
+        (Ok(record), Some(class_id)) => sqlx::query!(
+            "INSERT INTO class_members (class_id, user_id, joined_at) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+            class_id,
+            record.id,
+            Utc::now()
+        )
+        .execute(&mut *tx)
+        .await
+        .map(|_| record),
+        (result, _) => result,
+    };
+
+    // Sınıf listesinden gelen davetse listedeki satırı yeni hesapla eşleştir
+    let result = match (result, &invitation) {
+        (Ok(record), Some(invitation)) => roster::link_invited_user(&mut tx, invitation.id, record.id)
+            .await
+            .map(|_| record),
+        (result, _) => result,
+    };
+
+    let result = match result {
+        Ok(record) => tx.commit().await.map(|_| record),
+        Err(e) => Err(e),
+    };
+
     match result {
         Ok(record) => {
+            audit::record(&pool, Some(record.id), audit::EMAIL_VERIFICATION_SENT, serde_json::json!({
+                "reason": "register",
+                "expires_at": verification_expires_at
+            }))
+            .await;
+
             // E-posta doğrulama mesajı gönder
             let email_service = EmailService::new();
             match email_service
@@ -139,6 +255,8 @@ pub async fn register(
                 "role": role.to_string().to_lowercase(),
                 "is_approved": is_approved,
                 "is_email_verified": false,
+                "organization_id": organization_id,
+                "class_id": invitation.as_ref().and_then(|i| i.class_id),
                 "message": "Kullanıcı başarıyla kaydedildi. Lütfen e-posta adresinizi doğrulayın."
             }))
         }
@@ -159,7 +277,8 @@ pub async fn login(
     // Kullanıcıyı e-posta adresi ile bul
     let user = sqlx::query!(
         r#"
-        SELECT id, username, email, password_hash, role, is_approved, is_email_verified
+        SELECT id, username, email, password_hash, role, is_approved, is_email_verified,
+               organization_id, is_org_admin, token_version
         FROM users
         WHERE email = $1
         "#,
@@ -197,7 +316,14 @@ pub async fn login(
                     .await;
 
                     // JWT token oluştur
-                    match generate_jwt(user.id, &user.role) {
+                    match generate_jwt(&TokenUser {
+                        id: user.id,
+                        role: &user.role,
+                        username: &user.username,
+                        organization_id: user.organization_id,
+                        is_org_admin: user.is_org_admin,
+                        token_version: user.token_version,
+                    }) {
                         Ok(token) => {
                             info!("Kullanıcı giriş yaptı: {}", user.email);
                             HttpResponse::Ok().json(serde_json::json!({
@@ -253,7 +379,7 @@ pub async fn verify_email(
     // Tokeni kullanarak kullanıcıyı bul
     let token_inner = token.into_inner();
     let user = sqlx::query!(
-        "SELECT id, username, email FROM users WHERE verification_token = $1",
+        "SELECT id, username, email, verification_token_expires_at FROM users WHERE verification_token = $1",
         token_inner
     )
     .fetch_optional(&**pool)
@@ -261,10 +387,25 @@ pub async fn verify_email(
 
     match user {
         Ok(Some(user)) => {
+            // Süresi dolan token kullanılamaz, yeni bağlantı istenmelidir
+            if user.verification_token_expires_at.is_none_or(|expires_at| expires_at <= Utc::now()) {
+                audit::record(&pool, Some(user.id), audit::EMAIL_VERIFICATION_EXPIRED, serde_json::json!({
+                    "expires_at": user.verification_token_expires_at
+                }))
+                .await;
+                return HttpResponse::Gone().json(serde_json::json!({
+                    "error": "Doğrulama bağlantısının süresi dolmuş, lütfen yeni bir bağlantı isteyin"
+                }));
+            }
+
             // Kullanıcıyı doğrulanmış olarak işaretle
             let result = sqlx::query!(
-                "UPDATE users SET is_email_verified = true, verification_token = NULL WHERE id = $1",
-                user.id
+                r#"
+                UPDATE users SET is_email_verified = true, verification_token = NULL, verification_token_expires_at = NULL
+                WHERE id = $1 AND verification_token = $2
+                "#,
+                user.id,
+                token_inner
             )
             .execute(&**pool)
             .await;
@@ -272,6 +413,10 @@ pub async fn verify_email(
             match result {
                 Ok(_) => {
                     info!("E-posta doğrulandı: {}", user.email);
+                    audit::record(&pool, Some(user.id), audit::EMAIL_VERIFIED, serde_json::json!({
+                        "email": user.email
+                    }))
+                    .await;
                     HttpResponse::Ok().json(serde_json::json!({
                         "message": "E-posta adresiniz başarıyla doğrulandı. Şimdi giriş yapabilirsiniz."
                     }))
@@ -298,10 +443,63 @@ pub async fn verify_email(
     }
 }
 
+// Doğrulama e-postasını yeniden gönder; önceki bağlantı geçersiz olur
+pub async fn resend_verification_email(
+    pool: web::Data<Pool<Postgres>>,
+    email: web::Json<String>,
+) -> impl Responder {
+    let verification_token = generate_verification_token();
+    let expires_at = Utc::now() + Duration::hours(CONFIG.email_verification_expiry_hours);
+
+    // Yalnızca henüz doğrulanmamış hesaplar için yeni token üretilir
+    let user = sqlx::query!(
+        r#"
+        UPDATE users SET verification_token = $1, verification_token_expires_at = $2
+        WHERE email = $3 AND is_email_verified = false
+        RETURNING id, username, email
+        "#,
+        verification_token,
+        expires_at,
+        email.into_inner()
+    )
+    .fetch_optional(&**pool)
+    .await;
+
+    match user {
+        Ok(Some(user)) => {
+            audit::record(&pool, Some(user.id), audit::EMAIL_VERIFICATION_SENT, serde_json::json!({
+                "reason": "resend",
+                "expires_at": expires_at
+            }))
+            .await;
+
+            let email_service = EmailService::new();
+            if let Err(e) = email_service
+                .send_verification_email(&user.email, &user.username, &verification_token)
+                .await
+            {
+                error!("Doğrulama e-postası gönderilemedi ({}): {}", user.email, e);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Doğrulama e-postası gönderilemedi"
+            }));
+        }
+    }
+
+    // Güvenlik nedeniyle hesabın varlığından bağımsız olarak aynı mesaj gösterilir
+    HttpResponse::Ok().json(serde_json::json!({
+        "message": "Hesabınız doğrulanmamışsa yeni bir doğrulama bağlantısı e-posta adresinize gönderildi"
+    }))
+}
+
 // Mevcut kullanıcı bilgilerini getir
 pub async fn get_current_user(
     pool: web::Data<Pool<Postgres>>,
-    claims: web::ReqData<Claims>,
+    claims: Claims,
 ) -> impl Responder {
     let user_id = claims.sub.parse::<i32>().unwrap_or_default();
 
@@ -349,13 +547,26 @@ pub async fn request_password_reset(
     pool: web::Data<Pool<Postgres>>,
     email: web::Json<String>,
 ) -> impl Responder {
+    let email = email.into_inner();
+
+    // E-posta başına saatlik istek sınırı
+    match password_reset::record_request(&pool, &email).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return HttpResponse::TooManyRequests().json(serde_json::json!({
+                "error": "Çok fazla şifre sıfırlama isteği yapıldı, lütfen daha sonra tekrar deneyin"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Şifre sıfırlama isteği işlenemedi"
+            }));
+        }
+    }
+
     // Kullanıcıyı e-posta ile bul
-    let user = sqlx::query!(
-        "SELECT id, username, email FROM users WHERE email = $1",
-        email.into_inner()
-    )
-    .fetch_optional(&**pool)
-    .await;
+    let user = PgUserRepo::new(&pool).find_by_email(&email).await;
     
     match user {
         Ok(Some(user)) => {
@@ -363,10 +574,10 @@ pub async fn request_password_reset(
             let reset_token = generate_reset_token();
             let expires_at = Utc::now() + Duration::hours(24);
             
-            // Tokeni veritabanına kaydet
+            // Veritabanına yalnızca tokenın özeti kaydedilir; önceki istekten kalan token geçersiz olur
             let _ = sqlx::query!(
                 "UPDATE users SET reset_token = $1, reset_token_expires_at = $2 WHERE id = $3",
-                reset_token,
+                hash_reset_token(&reset_token),
                 expires_at,
                 user.id
             )
@@ -394,6 +605,22 @@ pub async fn request_password_reset(
     }
 }
 
+// Şifre gücü tahmini: istemcideki güç göstergesi için, sızıntı kontrolü yapılmaz
+pub async fn password_strength(dto: web::Json<PasswordStrengthDto>) -> impl Responder {
+    let user_inputs: Vec<&str> = [dto.username.as_deref(), dto.email.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect();
+    let strength = password_policy::estimate_strength(&dto.password, &user_inputs);
+    let violations = password_policy::policy_violations(&dto.password);
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "password_strength": strength,
+        "violations": violations,
+        "acceptable": violations.is_empty() && strength.score >= CONFIG.password_min_score
+    }))
+}
+
 // Şifre sıfırlama işleyicisi
 pub async fn reset_password(
     pool: web::Data<Pool<Postgres>>,
@@ -406,51 +633,69 @@ pub async fn reset_password(
         }));
     }
 
-    // Tokeni kullanarak kullanıcıyı bul
-    let token_inner = token.into_inner();
-    let user = sqlx::query!(
+    let token_hash = hash_reset_token(&token.into_inner());
+
+    // Geçersiz tokenlar için sızıntı sorgusu ve şifre hashleme maliyetine girmeden dön
+    match sqlx::query!(
         "SELECT id FROM users WHERE reset_token = $1 AND reset_token_expires_at > $2",
-        token_inner,
+        token_hash,
         Utc::now()
     )
     .fetch_optional(&**pool)
-    .await;
+    .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Geçersiz veya süresi dolmuş sıfırlama tokeni"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Şifre sıfırlama başarısız oldu"
+            }));
+        }
+    }
 
-    match user {
-        Ok(Some(user)) => {
-            // Yeni şifreyi hashle
-            let password_hash = match hash_password(&new_password) {
-                Ok(hash) => hash,
-                Err(e) => {
-                    error!("Şifre hashleme hatası: {}", e);
-                    return HttpResponse::InternalServerError().json(serde_json::json!({
-                        "error": "Şifre sıfırlama başarısız oldu"
-                    }));
-                }
-            };
+    if let Err(rejection) = password_policy::check_new_password(&new_password, &[]).await {
+        return HttpResponse::BadRequest().json(rejection.body());
+    }
 
-            // Kullanıcının şifresini güncelle
-            let result = sqlx::query!(
-                "UPDATE users SET password_hash = $1, reset_token = NULL, reset_token_expires_at = NULL WHERE id = $2",
-                password_hash,
-                user.id
-            )
-            .execute(&**pool)
-            .await;
+    // Yeni şifreyi hashle
+    let password_hash = match hash_password(&new_password) {
+        Ok(hash) => hash,
+        Err(e) => {
+            error!("Şifre hashleme hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Şifre sıfırlama başarısız oldu"
+            }));
+        }
+    };
 
-            match result {
-                Ok(_) => {
-                    HttpResponse::Ok().json(serde_json::json!({
-                        "message": "Şifreniz başarıyla sıfırlandı. Şimdi giriş yapabilirsiniz."
-                    }))
-                }
-                Err(e) => {
-                    error!("Şifre güncelleme hatası: {}", e);
-                    HttpResponse::InternalServerError().json(serde_json::json!({
-                        "error": "Şifre sıfırlama başarısız oldu"
-                    }))
-                }
-            }
+    // Token şifreyle aynı sorguda tüketilir; eşzamanlı isteklerde bile yalnızca biri başarılı olur.
+    // Şifre değişince mevcut oturumlar da geçersiz olur.
+    let result = sqlx::query!(
+        r#"
+        UPDATE users
+        SET password_hash = $1, reset_token = NULL, reset_token_expires_at = NULL,
+            token_version = token_version + 1
+        WHERE reset_token = $2 AND reset_token_expires_at > $3
+        RETURNING id
+        "#,
+        password_hash,
+        token_hash,
+        Utc::now()
+    )
+    .fetch_optional(&**pool)
+    .await;
+
+    match result {
+        Ok(Some(user)) => {
+            token_version::invalidate(user.id);
+            HttpResponse::Ok().json(serde_json::json!({
+                "message": "Şifreniz başarıyla sıfırlandı. Şimdi giriş yapabilirsiniz."
+            }))
         }
         Ok(None) => {
             HttpResponse::NotFound().json(serde_json::json!({
@@ -458,10 +703,33 @@ pub async fn reset_password(
             }))
         }
         Err(e) => {
-            error!("Veritabanı sorgu hatası: {}", e);
+            error!("Şifre güncelleme hatası: {}", e);
             HttpResponse::InternalServerError().json(serde_json::json!({
                 "error": "Şifre sıfırlama başarısız oldu"
             }))
         }
     }
+}
+
+// Tüm cihazlardaki oturumları sonlandır (mevcut token dahil)
+pub async fn logout_all(
+    pool: web::Data<Pool<Postgres>>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+
+    match token_version::bump(&pool, user_id).await {
+        Ok(_) => {
+            info!("Kullanıcının tüm oturumları sonlandırıldı: {}", claims.username);
+            HttpResponse::Ok().json(serde_json::json!({
+                "message": "Tüm oturumlarınız sonlandırıldı"
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Oturumlar sonlandırılamadı"
+            }))
+        }
+    }
 }
\ No newline at end of file
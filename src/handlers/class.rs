@@ -0,0 +1,715 @@
+use actix_web::{http::header, web, HttpResponse, Responder};
+use chrono::Utc;
+use log::{error, info};
+use sqlx::{Pool, Postgres};
+
+use crate::db::models::{AddClassMemberDto, AttendanceQuery, Claims, CreateClassDto, RosterImportQuery, UpdateAccommodationsDto};
+use crate::services::email::EmailService;
+use crate::services::{accommodations, attendance, organization, roster, student_report};
+
+// Yeni sınıf oluştur
+pub async fn create_class(
+    pool: web::Data<Pool<Postgres>>,
+    class_dto: web::Json<CreateClassDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    if claims.role != "teacher" && claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Sadece öğretmenler sınıf oluşturabilir"
+        }));
+    }
+    
+    let name = class_dto.name.trim();
+    if name.is_empty() || name.chars().count() > 255 {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Sınıf adı 1-255 karakter arasında olmalıdır"
+        }));
+    }
+    
+    // Sınıf öğretmenin kurumuna bağlanır; kurumu yoksa paylaşılamaz
+    let membership = organization::membership(&claims);
+    let shared_with_org = class_dto.shared_with_org.unwrap_or(false) && membership.organization_id.is_some();
+    
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO classes (teacher_id, organization_id, name, shared_with_org, created_at)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, created_at
+        "#,
+        user_id,
+        membership.organization_id,
+        name,
+        shared_with_org,
+        Utc::now()
+    )
+    .fetch_one(&**pool)
+    .await;
+    
+    match result {
+        Ok(class) => {
+            info!("Sınıf oluşturuldu: {} (user_id: {})", name, user_id);
+            HttpResponse::Created().json(serde_json::json!({
+                "id": class.id,
+                "name": name,
+                "organization_id": membership.organization_id,
+                "shared_with_org": shared_with_org,
+                "created_at": class.created_at
+            }))
+        }
+        Err(e) => {
+            error!("Sınıf oluşturulurken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Sınıf oluşturulamadı"
+            }))
+        }
+    }
+}
+
+// Kullanıcının sınıflarını ve kurumunda paylaşılan sınıfları listele
+pub async fn list_classes(
+    pool: web::Data<Pool<Postgres>>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let classes = sqlx::query!(
+        r#"
+        SELECT c.id, c.name, c.teacher_id, c.shared_with_org, c.created_at,
+               u.username as teacher_username,
+               (SELECT COUNT(*) FROM class_members cm WHERE cm.class_id = c.id) as "member_count!"
+        FROM classes c
+        JOIN users u ON c.teacher_id = u.id
+        LEFT JOIN users viewer ON viewer.id = $1
+        WHERE c.teacher_id = $1
+           OR (c.shared_with_org AND c.organization_id = viewer.organization_id)
+           OR EXISTS (SELECT 1 FROM class_members cm WHERE cm.class_id = c.id AND cm.user_id = $1)
+        ORDER BY c.created_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    match classes {
+        Ok(classes) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "classes": classes.iter().map(|c| {
+                    serde_json::json!({
+                        "id": c.id,
+                        "name": c.name,
+                        "teacher": c.teacher_username,
+                        "is_owner": c.teacher_id == user_id,
+                        "shared_with_org": c.shared_with_org,
+                        "member_count": c.member_count,
+                        "created_at": c.created_at
+                    })
+                }).collect::<Vec<_>>()
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Sınıflar alınamadı"
+            }))
+        }
+    }
+}
+
+// Sınıf detaylarını üyeleriyle birlikte getir
+pub async fn get_class(
+    pool: web::Data<Pool<Postgres>>,
+    class_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let class_id_inner = class_id.into_inner();
+    
+    let class = sqlx::query!(
+        r#"
+        SELECT c.id, c.name, c.teacher_id, c.organization_id, c.shared_with_org, c.created_at
+        FROM classes c
+        WHERE c.id = $1
+        "#,
+        class_id_inner
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match class {
+        Ok(Some(class)) => {
+            // Sınıf sahibi, admin veya sınıfın paylaşıldığı kurumun öğretmenleri görebilir
+            let can_view = if class.teacher_id == user_id || claims.role == "admin" {
+                true
+            } else if class.shared_with_org && claims.role == "teacher" {
+                class.organization_id.is_some() && organization::membership(&claims).organization_id == class.organization_id
+            } else {
+                false
+            };
+            
+            if !can_view {
+                return HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": "Bu sınıfa erişim izniniz yok"
+                }));
+            }
+            
+            let members = sqlx::query!(
+                r#"
+                SELECT u.id, u.username, u.email, cm.joined_at, cm.time_multiplier, cm.screen_reader
+                FROM class_members cm
+                JOIN users u ON cm.user_id = u.id
+                WHERE cm.class_id = $1
+                ORDER BY u.username
+                "#,
+                class.id
+            )
+            .fetch_all(&**pool)
+            .await;
+            
+            match members {
+                Ok(members) => {
+                    HttpResponse::Ok().json(serde_json::json!({
+                        "id": class.id,
+                        "name": class.name,
+                        "is_owner": class.teacher_id == user_id,
+                        "shared_with_org": class.shared_with_org,
+                        "created_at": class.created_at,
+                        "members": members.iter().map(|m| {
+                            serde_json::json!({
+                                "id": m.id,
+                                "username": m.username,
+                                "email": m.email,
+                                "joined_at": m.joined_at,
+                                "time_multiplier": m.time_multiplier,
+                                "screen_reader": m.screen_reader
+                            })
+                        }).collect::<Vec<_>>()
+                    }))
+                }
+                Err(e) => {
+                    error!("Veritabanı sorgu hatası: {}", e);
+                    HttpResponse::InternalServerError().json(serde_json::json!({
+                        "error": "Sınıf üyeleri alınamadı"
+                    }))
+                }
+            }
+        }
+        Ok(None) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Sınıf bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Sınıf alınamadı"
+            }))
+        }
+    }
+}
+
+// Sınıfa öğrenci ekle
+pub async fn add_class_member(
+    pool: web::Data<Pool<Postgres>>,
+    class_id: web::Path<i32>,
+    member_dto: web::Json<AddClassMemberDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let class_id_inner = class_id.into_inner();
+    
+    // Sadece sınıf sahibi veya admin üye ekleyebilir
+    let class = sqlx::query!(
+        "SELECT teacher_id FROM classes WHERE id = $1",
+        class_id_inner
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match class {
+        Ok(Some(class)) if class.teacher_id != user_id && claims.role != "admin" => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu sınıfı düzenleme izniniz yok"
+            }));
+        }
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Sınıf bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Öğrenci sınıfa eklenemedi"
+            }));
+        }
+    }
+    
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO class_members (class_id, user_id, joined_at)
+        SELECT $1, id, $3 FROM users WHERE id = $2 AND role = 'student'
+        ON CONFLICT (class_id, user_id) DO NOTHING
+        RETURNING user_id
+        "#,
+        class_id_inner,
+        member_dto.user_id,
+        Utc::now()
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match result {
+        Ok(Some(_)) => {
+            info!("Öğrenci sınıfa eklendi: class_id={}, user_id={}", class_id_inner, member_dto.user_id);
+            HttpResponse::Created().json(serde_json::json!({
+                "message": "Öğrenci sınıfa eklendi"
+            }))
+        }
+        Ok(None) => {
+            HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Öğrenci bulunamadı veya zaten sınıfta"
+            }))
+        }
+        Err(e) => {
+            error!("Öğrenci sınıfa eklenirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Öğrenci sınıfa eklenemedi"
+            }))
+        }
+    }
+}
+
+// Sınıftan öğrenci çıkar
+pub async fn remove_class_member(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<(i32, i32)>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let (class_id, member_id) = path.into_inner();
+    
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM class_members cm
+        USING classes c
+        WHERE cm.class_id = c.id AND cm.class_id = $1 AND cm.user_id = $2
+          AND (c.teacher_id = $3 OR $4)
+        "#,
+        class_id,
+        member_id,
+        user_id,
+        claims.role == "admin"
+    )
+    .execute(&**pool)
+    .await;
+    
+    match result {
+        Ok(result) if result.rows_affected() > 0 => {
+            info!("Öğrenci sınıftan çıkarıldı: class_id={}, user_id={}", class_id, member_id);
+            HttpResponse::Ok().json(serde_json::json!({
+                "message": "Öğrenci sınıftan çıkarıldı"
+            }))
+        }
+        Ok(_) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Sınıf üyesi bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Öğrenci sınıftan çıkarılırken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Öğrenci sınıftan çıkarılamadı"
+            }))
+        }
+    }
+}
+
+// Sınıftaki öğrencinin erişilebilirlik düzenlemelerini ayarla (ek süre çarpanı, ekran okuyucu)
+pub async fn update_member_accommodations(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<(i32, i32)>,
+    accommodations_dto: web::Json<UpdateAccommodationsDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let (class_id, member_id) = path.into_inner();
+    
+    if !accommodations::is_valid_multiplier(accommodations_dto.time_multiplier) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!(
+                "Süre çarpanı {} ile {} arasında olmalıdır",
+                accommodations::MIN_TIME_MULTIPLIER,
+                accommodations::MAX_TIME_MULTIPLIER
+            )
+        }));
+    }
+    
+    // Sadece sınıf sahibi veya admin düzenleyebilir
+    let result = sqlx::query!(
+        r#"
+        UPDATE class_members cm SET time_multiplier = $1, screen_reader = $2
+        FROM classes c
+        WHERE cm.class_id = c.id AND cm.class_id = $3 AND cm.user_id = $4
+          AND (c.teacher_id = $5 OR $6)
+        "#,
+        accommodations_dto.time_multiplier,
+        accommodations_dto.screen_reader,
+        class_id,
+        member_id,
+        user_id,
+        claims.role == "admin"
+    )
+    .execute(&**pool)
+    .await;
+    
+    match result {
+        Ok(result) if result.rows_affected() > 0 => {
+            info!(
+                "Erişilebilirlik düzenlemeleri güncellendi: class_id={}, user_id={}, time_multiplier={}",
+                class_id, member_id, accommodations_dto.time_multiplier
+            );
+            HttpResponse::Ok().json(serde_json::json!({
+                "class_id": class_id,
+                "user_id": member_id,
+                "time_multiplier": accommodations_dto.time_multiplier,
+                "screen_reader": accommodations_dto.screen_reader
+            }))
+        }
+        Ok(_) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Sınıf üyesi bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Erişilebilirlik düzenlemeleri güncellenirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Erişilebilirlik düzenlemeleri güncellenemedi"
+            }))
+        }
+    }
+}
+
+// Sınıf listesini CSV'den içe aktar (ad, e-posta); hesabı olmayan öğrenciler için davet oluşturulur
+pub async fn import_roster(
+    pool: web::Data<Pool<Postgres>>,
+    class_id: web::Path<i32>,
+    query: web::Query<RosterImportQuery>,
+    body: String,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let class_id_inner = class_id.into_inner();
+    
+    // Sadece sınıf sahibi veya admin listeyi içe aktarabilir
+    let class = sqlx::query!(
+        "SELECT c.teacher_id, c.name, u.username as teacher_username FROM classes c JOIN users u ON c.teacher_id = u.id WHERE c.id = $1",
+        class_id_inner
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    let class = match class {
+        Ok(Some(class)) if class.teacher_id != user_id && claims.role != "admin" => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu sınıfı düzenleme izniniz yok"
+            }));
+        }
+        Ok(Some(class)) => class,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Sınıf bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Sınıf listesi içe aktarılamadı"
+            }));
+        }
+    };
+    
+    let (rows, mut errors) = roster::parse_csv(&body);
+    if rows.is_empty() && errors.is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "CSV dosyasında öğrenci bulunamadı"
+        }));
+    }
+    
+    if rows.len() > roster::MAX_ROSTER_ROWS {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("Tek seferde en fazla {} öğrenci içe aktarılabilir", roster::MAX_ROSTER_ROWS)
+        }));
+    }
+    
+    let mut outcome = match roster::import(&pool, class_id_inner, class.teacher_id, rows).await {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            error!("Sınıf listesi içe aktarılırken hata: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Sınıf listesi içe aktarılamadı"
+            }));
+        }
+    };
+    
+    errors.append(&mut outcome.errors);
+    errors.sort_by_key(|e| e.line);
+    
+    let invited = outcome.entries.iter().filter(|e| e.status == "invited").count();
+    info!(
+        "Sınıf listesi içe aktarıldı: class_id={}, satır={}, davet={}, hatalı={}",
+        class_id_inner,
+        outcome.entries.len(),
+        invited,
+        errors.len()
+    );
+    
+    // Davet bağlantıları istenirse arka planda e-postayla gönderilir
+    if query.send_emails.unwrap_or(false) && invited > 0 {
+        let invitations: Vec<_> = outcome
+            .entries
+            .iter()
+            .filter_map(|e| e.invite_link.clone().map(|link| (e.email.clone(), e.name.clone(), link)))
+            .collect();
+        let class_name = class.name.clone();
+        let teacher_username = class.teacher_username.clone();
+        
+        actix_web::rt::spawn(async move {
+            let email_service = EmailService::new();
+            for (email, name, link) in invitations {
+                let _ = email_service
+                    .send_class_invitation(&email, &name, &class_name, &teacher_username, &link)
+                    .await;
+            }
+        });
+    }
+    
+    HttpResponse::Ok().json(serde_json::json!({
+        "class_id": class_id_inner,
+        "imported": outcome.entries,
+        "errors": errors
+    }))
+}
+
+// Sınıf listesini davet ve kayıt durumlarıyla getir
+pub async fn list_roster(
+    pool: web::Data<Pool<Postgres>>,
+    class_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let class_id_inner = class_id.into_inner();
+    
+    match roster::can_manage_class(&pool, class_id_inner, user_id, claims.role == "admin").await {
+        Ok(Some(true)) => {}
+        Ok(Some(false)) => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu sınıfa erişim izniniz yok"
+            }));
+        }
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Sınıf bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Sınıf listesi alınamadı"
+            }));
+        }
+    }
+    
+    let entries = sqlx::query!(
+        r#"
+        SELECT r.id, r.display_name, r.email, r.user_id, r.created_at,
+               i.code as "invite_code?", i.expires_at as "invite_expires_at?"
+        FROM class_roster r
+        LEFT JOIN invitations i ON r.invitation_id = i.id
+        WHERE r.class_id = $1
+        ORDER BY r.display_name
+        "#,
+        class_id_inner
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    match entries {
+        Ok(entries) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "class_id": class_id_inner,
+                "roster": entries.iter().map(|r| {
+                    serde_json::json!({
+                        "id": r.id,
+                        "name": r.display_name,
+                        "email": r.email,
+                        "user_id": r.user_id,
+                        "status": if r.user_id.is_some() { "registered" } else { "pending" },
+                        "invite_code": r.invite_code.as_ref().filter(|_| r.user_id.is_none()),
+                        "invite_expires_at": r.invite_expires_at.filter(|_| r.user_id.is_none()),
+                        "created_at": r.created_at
+                    })
+                }).collect::<Vec<_>>()
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Sınıf listesi alınamadı"
+            }))
+        }
+    }
+}
+
+// Sınıf için oynatılan oyunlara göre yoklama raporu (JSON veya CSV)
+pub async fn class_attendance(
+    pool: web::Data<Pool<Postgres>>,
+    class_id: web::Path<i32>,
+    query: web::Query<AttendanceQuery>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let class_id_inner = class_id.into_inner();
+    
+    let as_csv = match query.format.as_deref() {
+        None | Some("json") => false,
+        Some("csv") => true,
+        Some(_) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Rapor biçimi json veya csv olmalıdır"
+            }));
+        }
+    };
+    
+    let (from, to) = match attendance::resolve_range(query.from, query.to) {
+        Ok(range) => range,
+        Err(message) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": message
+            }));
+        }
+    };
+    
+    match roster::can_manage_class(&pool, class_id_inner, user_id, claims.role == "admin").await {
+        Ok(Some(true)) => {}
+        Ok(Some(false)) => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu sınıfa erişim izniniz yok"
+            }));
+        }
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Sınıf bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Yoklama raporu oluşturulamadı"
+            }));
+        }
+    }
+    
+    match attendance::report(&pool, class_id_inner, from, to).await {
+        // Excel'in Türkçe karakterleri doğru göstermesi için UTF-8 BOM eklenir
+        Ok(report) if as_csv => HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .insert_header((
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"yoklama-{}-{}-{}.csv\"", class_id_inner, from, to),
+            ))
+            .body(format!("\u{feff}{}", attendance::to_csv(&report))),
+        Ok(report) => HttpResponse::Ok().json(report),
+        Err(e) => {
+            error!("Yoklama raporu oluşturulurken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Yoklama raporu oluşturulamadı"
+            }))
+        }
+    }
+}
+
+// Öğrencinin sınıf oyunlarındaki başarısını konu bazında sınıf ortalamasıyla karşılaştır (veli görüşmeleri için)
+pub async fn student_report(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<(i32, i32)>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let (class_id, student_id) = path.into_inner();
+    
+    match roster::can_manage_class(&pool, class_id, user_id, claims.role == "admin").await {
+        Ok(Some(true)) => {}
+        Ok(Some(false)) => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu sınıfa erişim izniniz yok"
+            }));
+        }
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Sınıf bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Öğrenci raporu oluşturulamadı"
+            }));
+        }
+    }
+    
+    let student = sqlx::query!(
+        r#"
+        SELECT u.id, u.username, r.display_name as "display_name?"
+        FROM class_members cm
+        JOIN users u ON cm.user_id = u.id
+        LEFT JOIN class_roster r ON r.class_id = cm.class_id AND r.user_id = cm.user_id
+        WHERE cm.class_id = $1 AND cm.user_id = $2
+        "#,
+        class_id,
+        student_id
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    let student = match student {
+        Ok(Some(student)) => student,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Öğrenci bu sınıfta değil"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Öğrenci raporu oluşturulamadı"
+            }));
+        }
+    };
+    
+    match student_report::student_report(&pool, class_id, student_id).await {
+        Ok(report) => HttpResponse::Ok().json(serde_json::json!({
+            "class_id": class_id,
+            "student": {
+                "user_id": student.id,
+                "username": student.username,
+                "display_name": student.display_name
+            },
+            "class_game_count": report.class_game_count,
+            "games_played": report.games_played,
+            "overall": {
+                "student": report.overall_student,
+                "class": report.overall_class
+            },
+            "topics": report.topics
+        })),
+        Err(e) => {
+            error!("Öğrenci raporu oluşturulurken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Öğrenci raporu oluşturulamadı"
+            }))
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,342 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use log::{error, info};
+use sqlx::{Pool, Postgres};
+
+use crate::db::models::{AddEditorDto, Claims};
+use crate::services::collaboration::{self, SetAccess};
+use crate::services::notifications;
+
+// Soru setinin ortak editörlerini listele
+pub async fn list_editors(
+    pool: web::Data<Pool<Postgres>>,
+    set_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let set_id_inner = set_id.into_inner();
+    
+    let set = sqlx::query!(
+        "SELECT creator_id, visibility FROM question_sets WHERE id = $1 AND deleted_at IS NULL",
+        set_id_inner
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match set {
+        Ok(Some(set)) => {
+            let access = collaboration::access_level(&pool, user_id, set_id_inner, set.creator_id, &set.visibility).await;
+            if access == SetAccess::None && claims.role != "admin" {
+                return HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": "Bu soru setine erişim izniniz yok"
+                }));
+            }
+        }
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Soru seti bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Editörler alınamadı"
+            }));
+        }
+    }
+    
+    let editors = sqlx::query!(
+        r#"
+        SELECT u.id, u.username, e.permission, e.created_at
+        FROM question_set_editors e
+        JOIN users u ON e.user_id = u.id
+        WHERE e.question_set_id = $1
+        ORDER BY u.username
+        "#,
+        set_id_inner
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    match editors {
+        Ok(editors) => {
+            let editors_json: Vec<serde_json::Value> = editors
+                .into_iter()
+                .map(|e| {
+                    serde_json::json!({
+                        "user_id": e.id,
+                        "username": e.username,
+                        "permission": e.permission,
+                        "added_at": e.created_at
+                    })
+                })
+                .collect();
+            
+            HttpResponse::Ok().json(serde_json::json!({
+                "editors": editors_json
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Editörler alınamadı"
+            }))
+        }
+    }
+}
+
+// Soru setine ortak editör ekle veya mevcut editörün iznini güncelle
+pub async fn add_editor(
+    pool: web::Data<Pool<Postgres>>,
+    set_id: web::Path<i32>,
+    editor_dto: web::Json<AddEditorDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let set_id_inner = set_id.into_inner();
+    
+    let permission = editor_dto.permission.clone().unwrap_or_else(|| "write".to_string());
+    if !collaboration::is_valid_permission(&permission) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "İzin 'read' veya 'write' olmalıdır"
+        }));
+    }
+    
+    // Sadece set sahibi veya admin editör ekleyebilir
+    let set = sqlx::query!(
+        "SELECT creator_id, title FROM question_sets WHERE id = $1 AND deleted_at IS NULL",
+        set_id_inner
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    let set = match set {
+        Ok(Some(set)) if set.creator_id != user_id && claims.role != "admin" => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Sadece soru setinin sahibi editör ekleyebilir"
+            }));
+        }
+        Ok(Some(set)) => set,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Soru seti bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Editör eklenemedi"
+            }));
+        }
+    };
+    
+    if editor_dto.user_id == set.creator_id {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Soru setinin sahibi editör olarak eklenemez"
+        }));
+    }
+    
+    // Editörler yalnızca onaylı öğretmenler olabilir
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO question_set_editors (question_set_id, user_id, permission, added_by, created_at)
+        SELECT $1, id, $3, $4, $5 FROM users
+        WHERE id = $2 AND role = 'teacher' AND is_approved = true
+        ON CONFLICT (question_set_id, user_id) DO UPDATE SET permission = EXCLUDED.permission
+        RETURNING user_id
+        "#,
+        set_id_inner,
+        editor_dto.user_id,
+        permission,
+        user_id,
+        Utc::now()
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match result {
+        Ok(Some(_)) => {
+            info!(
+                "Ortak editör eklendi: set_id={}, user_id={}, permission={}",
+                set_id_inner, editor_dto.user_id, permission
+            );
+            
+            notifications::notify(
+                &pool,
+                editor_dto.user_id,
+                notifications::KIND_SET_SHARED,
+                "Ortak düzenleme daveti",
+                &format!("\"{}\" soru setine editör olarak eklendiniz", set.title),
+                serde_json::json!({"question_set_id": set_id_inner, "permission": permission}),
+            )
+            .await;
+            
+            HttpResponse::Created().json(serde_json::json!({
+                "question_set_id": set_id_inner,
+                "user_id": editor_dto.user_id,
+                "permission": permission
+            }))
+        }
+        Ok(None) => {
+            HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Kullanıcı bulunamadı veya onaylı bir öğretmen değil"
+            }))
+        }
+        Err(e) => {
+            error!("Editör eklenirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Editör eklenemedi"
+            }))
+        }
+    }
+}
+
+// Soru setinden ortak editör çıkar (sahip, admin veya editörün kendisi)
+pub async fn remove_editor(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<(i32, i32)>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let (set_id, editor_id) = path.into_inner();
+    
+    let set = sqlx::query!(
+        "SELECT creator_id FROM question_sets WHERE id = $1 AND deleted_at IS NULL",
+        set_id
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match set {
+        Ok(Some(set)) if set.creator_id != user_id && editor_id != user_id && claims.role != "admin" => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu soru setinin editörlerini düzenleme izniniz yok"
+            }));
+        }
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Soru seti bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Editör çıkarılamadı"
+            }));
+        }
+    }
+    
+    let result = sqlx::query!(
+        "DELETE FROM question_set_editors WHERE question_set_id = $1 AND user_id = $2",
+        set_id,
+        editor_id
+    )
+    .execute(&**pool)
+    .await;
+    
+    match result {
+        Ok(done) if done.rows_affected() > 0 => {
+            info!("Ortak editör çıkarıldı: set_id={}, user_id={}", set_id, editor_id);
+            HttpResponse::Ok().json(serde_json::json!({
+                "message": "Editör çıkarıldı"
+            }))
+        }
+        Ok(_) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Editör bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Editör çıkarılırken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Editör çıkarılamadı"
+            }))
+        }
+    }
+}
+
+// Soru setinin düzenleme geçmişi (kim hangi soruyu ne zaman düzenledi)
+pub async fn get_activity(
+    pool: web::Data<Pool<Postgres>>,
+    set_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let set_id_inner = set_id.into_inner();
+    
+    let set = sqlx::query!(
+        "SELECT creator_id, visibility FROM question_sets WHERE id = $1 AND deleted_at IS NULL",
+        set_id_inner
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match set {
+        Ok(Some(set)) => {
+            let access = collaboration::access_level(&pool, user_id, set_id_inner, set.creator_id, &set.visibility).await;
+            if access < SetAccess::Write && claims.role != "admin" {
+                return HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": "Düzenleme geçmişini görme izniniz yok"
+                }));
+            }
+        }
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Soru seti bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Düzenleme geçmişi alınamadı"
+            }));
+        }
+    }
+    
+    // Silinen sorular için metin bulunmaz, yalnızca id döner
+    let activity = sqlx::query!(
+        r#"
+        SELECT l.id, l.question_id, l.action, l.created_at, l.user_id,
+               u.username as "username?", q.question_text as "question_text?"
+        FROM question_edit_log l
+        LEFT JOIN users u ON l.user_id = u.id
+        LEFT JOIN questions q ON l.question_id = q.id
+        WHERE l.question_set_id = $1
+        ORDER BY l.created_at DESC, l.id DESC
+        LIMIT 200
+        "#,
+        set_id_inner
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    match activity {
+        Ok(activity) => {
+            let activity_json: Vec<serde_json::Value> = activity
+                .into_iter()
+                .map(|a| {
+                    serde_json::json!({
+                        "id": a.id,
+                        "question_id": a.question_id,
+                        "question_text": a.question_text,
+                        "action": a.action,
+                        "user_id": a.user_id,
+                        "username": a.username,
+                        "created_at": a.created_at
+                    })
+                })
+                .collect();
+            
+            HttpResponse::Ok().json(serde_json::json!({
+                "activity": activity_json
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Düzenleme geçmişi alınamadı"
+            }))
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,544 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use log::{error, info};
+use sqlx::{Pool, Postgres};
+
+use crate::db::models::{Claims, QuestionTopicsDto, TopicDto};
+use crate::services::collaboration::{self, SetAccess};
+use crate::services::curriculum::{self, CoverageReport};
+use crate::services::organization;
+
+// Konu kaydının reddedilme nedeni
+enum TopicRejection {
+    Invalid(&'static str),
+    Database,
+}
+
+impl From<sqlx::Error> for TopicRejection {
+    fn from(e: sqlx::Error) -> Self {
+        error!("Veritabanı sorgu hatası: {}", e);
+        TopicRejection::Database
+    }
+}
+
+impl TopicRejection {
+    fn into_response(self) -> HttpResponse {
+        match self {
+            TopicRejection::Invalid(message) => HttpResponse::BadRequest().json(serde_json::json!({
+                "error": message
+            })),
+            TopicRejection::Database => HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Konu kaydedilemedi"
+            })),
+        }
+    }
+}
+
+// Doğrulanmış ve sadeleştirilmiş konu alanları
+struct TopicFields {
+    name: String,
+    code: Option<String>,
+    description: Option<String>,
+    position: i32,
+}
+
+// Konu alanlarını doğrula. Üst konu bir ünite olmalı; alt konuları olan bir ünite başka bir ünitenin altına taşınamaz
+async fn checked_topic(
+    pool: &Pool<Postgres>,
+    topic_id: Option<i32>,
+    topic_dto: &TopicDto,
+) -> Result<TopicFields, TopicRejection> {
+    let name = topic_dto.name.trim();
+    if name.is_empty() || name.chars().count() > 255 {
+        return Err(TopicRejection::Invalid("Konu adı 1-255 karakter arasında olmalıdır"));
+    }
+    
+    let code = topic_dto.code.as_deref().map(str::trim).filter(|c| !c.is_empty());
+    if code.is_some_and(|c| c.chars().count() > 50) {
+        return Err(TopicRejection::Invalid("Konu kodu en fazla 50 karakter olabilir"));
+    }
+    
+    if let Some(parent_id) = topic_dto.parent_id {
+        if topic_id == Some(parent_id) {
+            return Err(TopicRejection::Invalid("Konu kendi altına taşınamaz"));
+        }
+        
+        let parent = sqlx::query!(
+            r#"
+            SELECT parent_id,
+                   EXISTS (SELECT 1 FROM curriculum_topics WHERE parent_id = $2) as "topic_has_children!"
+            FROM curriculum_topics
+            WHERE id = $1
+            "#,
+            parent_id,
+            topic_id
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or(TopicRejection::Invalid("Üst ünite bulunamadı"))?;
+        
+        if parent.parent_id.is_some() {
+            return Err(TopicRejection::Invalid("Konular yalnızca bir ünitenin altına eklenebilir"));
+        }
+        if parent.topic_has_children {
+            return Err(TopicRejection::Invalid("Alt konuları olan bir ünite başka bir ünitenin altına taşınamaz"));
+        }
+    }
+    
+    Ok(TopicFields {
+        name: name.to_string(),
+        code: code.map(String::from),
+        description: topic_dto.description.as_deref().map(str::trim).filter(|d| !d.is_empty()).map(String::from),
+        position: topic_dto.position.unwrap_or(0),
+    })
+}
+
+// Müfredat konularını ünite ağacı olarak listele - tüm oturum açmış kullanıcılar
+pub async fn list_topics(pool: web::Data<Pool<Postgres>>) -> impl Responder {
+    let topics = sqlx::query!(
+        r#"
+        SELECT t.id, t.parent_id, t.code, t.name, t.description, t.position,
+               (SELECT COUNT(*) FROM question_topics qt WHERE qt.topic_id = t.id) as "question_count!"
+        FROM curriculum_topics t
+        ORDER BY t.position, t.name, t.id
+        "#
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    match topics {
+        Ok(topics) => {
+            let units: Vec<serde_json::Value> = topics
+                .iter()
+                .filter(|u| u.parent_id.is_none())
+                .map(|u| {
+                    serde_json::json!({
+                        "id": u.id,
+                        "code": u.code,
+                        "name": u.name,
+                        "description": u.description,
+                        "position": u.position,
+                        "question_count": u.question_count,
+                        "topics": topics.iter().filter(|t| t.parent_id == Some(u.id)).map(|t| {
+                            serde_json::json!({
+                                "id": t.id,
+                                "code": t.code,
+                                "name": t.name,
+                                "description": t.description,
+                                "position": t.position,
+                                "question_count": t.question_count
+                            })
+                        }).collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            
+            HttpResponse::Ok().json(serde_json::json!({ "units": units }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Müfredat konuları alınamadı"
+            }))
+        }
+    }
+}
+
+// Yeni ünite veya konu oluştur - sadece sistem adminleri
+pub async fn create_topic(
+    pool: web::Data<Pool<Postgres>>,
+    topic_dto: web::Json<TopicDto>,
+    claims: Claims,
+) -> impl Responder {
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    let topic = match checked_topic(&pool, None, &topic_dto).await {
+        Ok(topic) => topic,
+        Err(rejection) => return rejection.into_response(),
+    };
+    
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO curriculum_topics (parent_id, code, name, description, position, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, created_at
+        "#,
+        topic_dto.parent_id,
+        topic.code,
+        topic.name,
+        topic.description,
+        topic.position,
+        Utc::now()
+    )
+    .fetch_one(&**pool)
+    .await;
+    
+    match result {
+        Ok(created) => {
+            info!("Müfredat konusu oluşturuldu: {} (id: {})", topic.name, created.id);
+            HttpResponse::Created().json(serde_json::json!({
+                "id": created.id,
+                "parent_id": topic_dto.parent_id,
+                "code": topic.code,
+                "name": topic.name,
+                "description": topic.description,
+                "position": topic.position,
+                "created_at": created.created_at
+            }))
+        }
+        Err(e) if curriculum::is_code_conflict(&e) => {
+            HttpResponse::Conflict().json(serde_json::json!({
+                "error": "Bu kod ile bir konu zaten var"
+            }))
+        }
+        Err(e) => {
+            error!("Müfredat konusu oluşturulurken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Konu oluşturulamadı"
+            }))
+        }
+    }
+}
+
+// Ünite veya konuyu güncelle - sadece sistem adminleri
+pub async fn update_topic(
+    pool: web::Data<Pool<Postgres>>,
+    topic_id: web::Path<i32>,
+    topic_dto: web::Json<TopicDto>,
+    claims: Claims,
+) -> impl Responder {
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    let topic_id = topic_id.into_inner();
+    
+    let topic = match checked_topic(&pool, Some(topic_id), &topic_dto).await {
+        Ok(topic) => topic,
+        Err(rejection) => return rejection.into_response(),
+    };
+    
+    let result = sqlx::query!(
+        r#"
+        UPDATE curriculum_topics
+        SET parent_id = $1, code = $2, name = $3, description = $4, position = $5
+        WHERE id = $6
+        RETURNING id
+        "#,
+        topic_dto.parent_id,
+        topic.code,
+        topic.name,
+        topic.description,
+        topic.position,
+        topic_id
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match result {
+        Ok(Some(_)) => {
+            info!("Müfredat konusu güncellendi: {} (id: {})", topic.name, topic_id);
+            HttpResponse::Ok().json(serde_json::json!({
+                "id": topic_id,
+                "parent_id": topic_dto.parent_id,
+                "code": topic.code,
+                "name": topic.name,
+                "description": topic.description,
+                "position": topic.position
+            }))
+        }
+        Ok(None) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Konu bulunamadı"
+            }))
+        }
+        Err(e) if curriculum::is_code_conflict(&e) => {
+            HttpResponse::Conflict().json(serde_json::json!({
+                "error": "Bu kod ile bir konu zaten var"
+            }))
+        }
+        Err(e) => {
+            error!("Müfredat konusu güncellenirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Konu güncellenemedi"
+            }))
+        }
+    }
+}
+
+// Ünite veya konuyu sil; ünitenin alt konuları ve sorulardaki etiketler de silinir - sadece sistem adminleri
+pub async fn delete_topic(
+    pool: web::Data<Pool<Postgres>>,
+    topic_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    let topic_id = topic_id.into_inner();
+    
+    let result = sqlx::query!("DELETE FROM curriculum_topics WHERE id = $1", topic_id)
+        .execute(&**pool)
+        .await;
+    
+    match result {
+        Ok(result) if result.rows_affected() > 0 => {
+            info!("Müfredat konusu silindi: id={}", topic_id);
+            HttpResponse::Ok().json(serde_json::json!({
+                "message": "Konu silindi"
+            }))
+        }
+        Ok(_) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Konu bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Müfredat konusu silinirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Konu silinemedi"
+            }))
+        }
+    }
+}
+
+// Sorunun konu etiketlerini belirle (soru setini düzenleyebilenler)
+pub async fn set_question_topics(
+    pool: web::Data<Pool<Postgres>>,
+    question_id: web::Path<i32>,
+    topics_dto: web::Json<QuestionTopicsDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let mut topic_ids = topics_dto.topic_ids.clone();
+    topic_ids.sort_unstable();
+    topic_ids.dedup();
+    
+    if topic_ids.len() > curriculum::MAX_TOPICS_PER_QUESTION {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("Bir soru en fazla {} konuyla etiketlenebilir", curriculum::MAX_TOPICS_PER_QUESTION)
+        }));
+    }
+    
+    let question = sqlx::query!(
+        r#"
+        SELECT q.id, q.question_set_id, qs.creator_id, qs.visibility
+        FROM questions q
+        JOIN question_sets qs ON q.question_set_id = qs.id
+        WHERE q.id = $1 AND q.deleted_at IS NULL AND qs.deleted_at IS NULL
+        "#,
+        question_id.into_inner()
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    let question = match question {
+        Ok(Some(question)) => question,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Soru bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Soru konuları güncellenemedi"
+            }));
+        }
+    };
+    
+    let access = collaboration::access_level(
+        &pool, user_id, question.question_set_id, question.creator_id, &question.visibility,
+    )
+    .await;
+    
+    if access < SetAccess::Write && claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu soruyu güncelleme izniniz yok"
+        }));
+    }
+    
+    let result: Result<Vec<_>, sqlx::Error> = async {
+        let mut tx = pool.begin().await?;
+        
+        sqlx::query!("DELETE FROM question_topics WHERE question_id = $1", question.id)
+            .execute(&mut *tx)
+            .await?;
+        
+        let topics = sqlx::query!(
+            r#"
+            INSERT INTO question_topics (question_id, topic_id)
+            SELECT $1, t.id FROM curriculum_topics t WHERE t.id = ANY($2)
+            RETURNING topic_id
+            "#,
+            question.id,
+            &topic_ids
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        
+        // Bilinmeyen konu varsa hiçbir değişiklik kaydedilmez
+        if topics.len() == topic_ids.len() {
+            tx.commit().await?;
+        }
+        
+        Ok(topics)
+    }
+    .await;
+    
+    match result {
+        Ok(topics) if topics.len() == topic_ids.len() => {
+            // Soru seti güncelleme zamanını güncelle (ETag'ler bu zamandan üretilir)
+            let _ = sqlx::query!(
+                "UPDATE question_sets SET updated_at = $1 WHERE id = $2",
+                Utc::now(),
+                question.question_set_id
+            )
+            .execute(&**pool)
+            .await;
+            
+            collaboration::record_edit(
+                &pool, question.question_set_id, question.id, user_id, collaboration::EDIT_UPDATE,
+            )
+            .await;
+            
+            HttpResponse::Ok().json(serde_json::json!({
+                "question_id": question.id,
+                "topic_ids": topic_ids
+            }))
+        }
+        Ok(_) => {
+            HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Konulardan biri bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Soru konuları güncellenirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Soru konuları güncellenemedi"
+            }))
+        }
+    }
+}
+
+fn coverage_response(report: Result<CoverageReport, sqlx::Error>, owner: serde_json::Value) -> HttpResponse {
+    match report {
+        Ok(report) => {
+            let mut body = serde_json::json!(report);
+            if let (Some(body), Some(owner)) = (body.as_object_mut(), owner.as_object()) {
+                body.extend(owner.clone());
+            }
+            HttpResponse::Ok().json(body)
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Müfredat kapsamı hesaplanamadı"
+            }))
+        }
+    }
+}
+
+// Soru setinin ünite bazında müfredat kapsamı
+pub async fn question_set_coverage(
+    pool: web::Data<Pool<Postgres>>,
+    set_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let set = sqlx::query!(
+        "SELECT id, title, creator_id, visibility FROM question_sets WHERE id = $1 AND deleted_at IS NULL",
+        set_id.into_inner()
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match set {
+        Ok(Some(set)) => {
+            let access = collaboration::access_level(&pool, user_id, set.id, set.creator_id, &set.visibility).await;
+            if claims.role != "admin" && access == SetAccess::None {
+                return HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": "Bu soru setine erişim izniniz yok"
+                }));
+            }
+            
+            coverage_response(
+                curriculum::question_set_coverage(&pool, set.id).await,
+                serde_json::json!({ "question_set_id": set.id, "title": set.title }),
+            )
+        }
+        Ok(None) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Soru seti bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Müfredat kapsamı hesaplanamadı"
+            }))
+        }
+    }
+}
+
+// Sınıfın oyunlarda işlediği konulara göre müfredat kapsamı
+pub async fn class_coverage(
+    pool: web::Data<Pool<Postgres>>,
+    class_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let class = sqlx::query!(
+        "SELECT id, name, teacher_id, organization_id, shared_with_org FROM classes WHERE id = $1",
+        class_id.into_inner()
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match class {
+        Ok(Some(class)) => {
+            // Sınıf sahibi, admin veya sınıfın paylaşıldığı kurumun öğretmenleri görebilir
+            let can_view = if class.teacher_id == user_id || claims.role == "admin" {
+                true
+            } else if class.shared_with_org && claims.role == "teacher" {
+                class.organization_id.is_some() && organization::membership(&claims).organization_id == class.organization_id
+            } else {
+                false
+            };
+            
+            if !can_view {
+                return HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": "Bu sınıfa erişim izniniz yok"
+                }));
+            }
+            
+            coverage_response(
+                curriculum::class_coverage(&pool, class.id).await,
+                serde_json::json!({ "class_id": class.id, "name": class.name }),
+            )
+        }
+        Ok(None) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Sınıf bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Müfredat kapsamı hesaplanamadı"
+            }))
+        }
+    }
+}
\ No newline at end of file
@@ -1,13 +1,42 @@
-use actix_web::{web, HttpRequest, HttpResponse, Responder};
-use chrono::Utc;
+use actix_web::{http::header, web, HttpRequest, HttpResponse, Responder};
+use chrono::{Duration, Utc};
 use log::{debug, error, info};
 use sqlx::{Pool, Postgres};
 use sqlx::types::BigDecimal;
 use uuid::Uuid;
 
-use crate::db::models::{Claims, CreateGameDto, GameStatus, JoinGameDto, LeaderboardEntry, SubmitAnswerDto, PlayerStatistics, QuestionStatistics};
+use crate::config::CONFIG;
+use crate::db::models::{ChallengeDto, Claims, CreateGameDto, ExportFormatQuery, GameAliasDto, GameStatus, IncludeArchivedQuery, JoinGameDto, ScheduleGameDto, UpdateGameSettingsDto, SubmitAnswerDto, SuddenDeathDto, PlayerStatistics, QuestionStatistics};
+use crate::db::repositories::{LobbyPlayer, PgPlayerRepo, PgQuestionRepo, PlayerRepo};
 use crate::services::email::EmailService;
-use crate::utils::security::generate_game_code;
+use crate::services::answer_timing;
+use crate::services::anti_cheat;
+use crate::services::collaboration::{self, SetAccess};
+use crate::services::cosmetics;
+use crate::handlers::websocket::AppState;
+use crate::services::game::{self as game_service, GameError, GameService, NextStep};
+use crate::services::game_alias::{self, Resolution};
+use crate::services::game_events;
+use crate::services::game_notifications::{self, ChallengeError};
+use crate::services::game_playlist;
+use crate::services::grading::{self, GradingScale};
+use crate::services::guest_ip;
+use crate::services::guest_sessions;
+use crate::services::localization;
+use crate::services::misconceptions;
+use crate::services::nickname::{guest_nickname_base, is_nickname_conflict, resolve_guest_nickname};
+use crate::services::option_labels::OptionLabelStyle;
+use crate::services::question_sample::QuestionSample;
+use crate::services::quota::{self, QuotaError, QuotaMetric};
+use crate::services::roster;
+use crate::services::scenes;
+use crate::services::tournament;
+use crate::services::viewers;
+use crate::utils::etag;
+use crate::utils::security::{
+    client_ip, decode_join_link_token, generate_game_code, generate_join_link_token, generate_player_token, hash_client_ip,
+    parse_player_token,
+};
 
 // BigDecimal değerlerini f64'e dönüştürmek için yardımcı fonksiyon
 fn bigdecimal_to_f64(value: Option<BigDecimal>) -> f64 {
@@ -21,7 +50,7 @@ fn bigdecimal_to_f64(value: Option<BigDecimal>) -> f64 {
 pub async fn create_game(
     pool: web::Data<Pool<Postgres>>,
     game_dto: web::Json<CreateGameDto>,
-    claims: web::ReqData<Claims>,
+    claims: Claims,
 ) -> impl Responder {
     let user_id = claims.sub.parse::<i32>().unwrap_or_default();
     
@@ -32,9 +61,86 @@ pub async fn create_game(
         }));
     }
     
+    if matches!(game_dto.max_guests_per_ip, Some(limit) if limit < 1) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "IP başına misafir sınırı en az 1 olmalıdır"
+        }));
+    }
+    
+    let option_labels = match game_dto.option_labels.as_deref().map(str::parse::<OptionLabelStyle>) {
+        Some(Ok(style)) => style,
+        Some(Err(_)) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Şık etiketi stili letters, shapes veya colors olmalıdır"
+            }));
+        }
+        None => OptionLabelStyle::default(),
+    };
+    
+    let scene_cues = match game_dto.scene_cues.as_ref().map(scenes::validate_cues) {
+        Some(Ok(cues)) => cues,
+        Some(Err(message)) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": message
+            }));
+        }
+        None => serde_json::json!({}),
+    };
+    
+    let grading = match game_dto.grading.as_ref().map(GradingScale::from_settings) {
+        Some(Ok(scale)) => scale,
+        Some(Err(message)) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": message
+            }));
+        }
+        None => GradingScale::default(),
+    };
+    
+    let question_sample = match game_dto.question_sample.as_ref().map(QuestionSample::from_settings) {
+        Some(Ok(sample)) => sample,
+        Some(Err(message)) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": message
+            }));
+        }
+        None => None,
+    };
+    
+    // Oyun bir sınıf listesiyle sınırlandırılacaksa sınıf öğretmene ait olmalı
+    if let Some(class_id) = game_dto.roster_class_id {
+        match roster::can_manage_class(&pool, class_id, user_id, claims.role == "admin").await {
+            Ok(Some(true)) => {}
+            Ok(_) => {
+                return HttpResponse::NotFound().json(serde_json::json!({
+                    "error": "Sınıf bulunamadı"
+                }));
+            }
+            Err(e) => {
+                error!("Veritabanı sorgu hatası: {}", e);
+                return HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Oyun oluşturulamadı"
+                }));
+            }
+        }
+    }
+    
+    // Ana setin ardından eklenecek setler; tekrar edenler ve ana set çıkarılır
+    let mut pooled_set_ids: Vec<i32> = Vec::new();
+    for &set_id in &game_dto.question_set_ids {
+        if set_id != game_dto.question_set_id && !pooled_set_ids.contains(&set_id) {
+            pooled_set_ids.push(set_id);
+        }
+    }
+    if pooled_set_ids.len() + 1 > game_playlist::MAX_POOLED_SETS {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("Bir oyunda en fazla {} soru seti birleştirilebilir", game_playlist::MAX_POOLED_SETS)
+        }));
+    }
+    
     // Soru setinin varlığını kontrol et
     let question_set = sqlx::query!(
-        "SELECT id, title, creator_id FROM question_sets WHERE id = $1",
+        "SELECT id, title, creator_id, is_hidden, visibility FROM question_sets WHERE id = $1 AND deleted_at IS NULL",
         game_dto.question_set_id
     )
     .fetch_optional(&**pool)
@@ -42,17 +148,32 @@ pub async fn create_game(
     
     match question_set {
         Ok(Some(set)) => {
-            // Soru setinin bu kullanıcıya ait olup olmadığını kontrol et
-            if set.creator_id != user_id && claims.role != "admin" {
+            // Soru seti kullanıcıya ait, ortak editörü olduğu veya kurumunda paylaşılmış olmalı
+            if claims.role != "admin"
+                && collaboration::access_level(&pool, user_id, set.id, set.creator_id, &set.visibility).await
+                    == SetAccess::None
+            {
                 return HttpResponse::Forbidden().json(serde_json::json!({
                     "error": "Bu soru seti size ait değil"
                 }));
             }
 
-            // Soru setinde soru var mı kontrol et
+            // Moderasyon ile gizlenen setlerle oyun başlatılamaz
+            if set.is_hidden && claims.role != "admin" {
+                return HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": "Bu soru seti moderasyon nedeniyle gizlendi"
+                }));
+            }
+
+            if let Err(response) = check_pooled_sets(&pool, &pooled_set_ids, user_id, claims.role == "admin").await {
+                return response;
+            }
+
+            // Soru setlerinde soru var mı kontrol et
+            let all_set_ids: Vec<i32> = std::iter::once(set.id).chain(pooled_set_ids.iter().copied()).collect();
             let question_count = sqlx::query!(
-                "SELECT COUNT(*) as count FROM questions WHERE question_set_id = $1",
-                set.id
+                "SELECT COUNT(*) as count FROM questions WHERE question_set_id = ANY($1) AND deleted_at IS NULL",
+                &all_set_ids
             )
             .fetch_one(&**pool)
             .await;
@@ -65,27 +186,61 @@ pub async fn create_game(
                 }
             }
             
+            // Günlük oyun kotasını kontrol et (adminler muaf)
+            if claims.role != "admin" {
+                match quota::consume(&pool, user_id, QuotaMetric::GamesCreated, 1).await {
+                    Ok(_) => {}
+                    Err(QuotaError::Exceeded { metric, used, limit }) => {
+                        return HttpResponse::TooManyRequests().json(quota::exceeded_body(metric, used, limit));
+                    }
+                    Err(QuotaError::Database(e)) => {
+                        error!("Kota kontrolü başarısız: {}", e);
+                        return HttpResponse::InternalServerError().json(serde_json::json!({
+                            "error": "Oyun oluşturulamadı"
+                        }));
+                    }
+                }
+            }
+            
             // Benzersiz oyun kodu oluştur
             let game_code = generate_game_code();
+            let competitive = game_dto.competitive.unwrap_or(true);
             
             // Oyunu veritabanına ekle
             let game_result = sqlx::query!(
                 r#"
-                INSERT INTO games (code, question_set_id, host_id, status, created_at)
-                VALUES ($1, $2, $3, $4, $5)
+                INSERT INTO games (code, question_set_id, host_id, status, created_at, is_competitive, max_guests_per_ip, option_labels, scene_cues, roster_class_id, grading, question_sample)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
                 RETURNING id, code, created_at
                 "#,
                 game_code,
                 game_dto.question_set_id,
                 user_id,
                 GameStatus::Lobby.to_string().to_lowercase(),
-                Utc::now()
+                Utc::now(),
+                competitive,
+                game_dto.max_guests_per_ip,
+                option_labels.as_str(),
+                scene_cues,
+                game_dto.roster_class_id,
+                grading.to_value(),
+                question_sample.as_ref().map(QuestionSample::to_value)
             )
             .fetch_one(&**pool)
             .await;
             
             match game_result {
                 Ok(game) => {
+                    if !pooled_set_ids.is_empty() {
+                        if let Err(e) = game_playlist::add_pooled_sets(&pool, game.id, &pooled_set_ids).await {
+                            error!("Oyuna soru setleri eklenemedi: {}", e);
+                            refund_game_quota(&pool, user_id, &claims.role).await;
+                            return HttpResponse::InternalServerError().json(serde_json::json!({
+                                "error": "Oyun oluşturulamadı"
+                            }));
+                        }
+                    }
+                    
                     // Kullanıcıya oyun bağlantısını e-posta ile gönder
                     let user = sqlx::query!(
                         "SELECT email, username FROM users WHERE id = $1",
@@ -108,12 +263,20 @@ pub async fn create_game(
                         "id": game.id,
                         "code": game.code,
                         "question_set_id": game_dto.question_set_id,
+                        "question_set_ids": all_set_ids,
                         "status": "lobby",
+                        "competitive": competitive,
+                        "max_guests_per_ip": game_dto.max_guests_per_ip,
+                        "option_labels": option_labels.as_str(),
+                        "scene_cues": scene_cues,
+                        "roster_class_id": game_dto.roster_class_id,
+                        "grading": grading,
                         "created_at": game.created_at
                     }))
                 }
                 Err(e) => {
                     error!("Oyun oluşturulurken hata: {}", e);
+                    refund_game_quota(&pool, user_id, &claims.role).await;
                     HttpResponse::InternalServerError().json(serde_json::json!({
                         "error": "Oyun oluşturulamadı"
                     }))
@@ -134,16 +297,149 @@ pub async fn create_game(
     }
 }
 
+// Oluşturulamayan oyun için tüketilen günlük oyun kotasını geri ver (adminlerden kota düşülmez)
+async fn refund_game_quota(pool: &Pool<Postgres>, user_id: i32, role: &str) {
+    if role == "admin" {
+        return;
+    }
+    if let Err(e) = quota::refund(pool, user_id, QuotaMetric::GamesCreated, 1).await {
+        error!("Oyun kotası geri verilemedi (user_id={}): {}", user_id, e);
+    }
+}
+
+// Oyunda ana setle birleştirilecek setleri doğrula: her set mevcut, kullanıcının erişebildiği ve gizlenmemiş olmalı
+async fn check_pooled_sets(
+    pool: &Pool<Postgres>,
+    set_ids: &[i32],
+    user_id: i32,
+    is_admin: bool,
+) -> Result<(), HttpResponse> {
+    if set_ids.is_empty() {
+        return Ok(());
+    }
+    
+    let sets = sqlx::query!(
+        "SELECT id, creator_id, is_hidden, visibility FROM question_sets WHERE id = ANY($1) AND deleted_at IS NULL",
+        set_ids
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| {
+        error!("Veritabanı sorgu hatası: {}", e);
+        HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Oyun oluşturulamadı"
+        }))
+    })?;
+    
+    if sets.len() != set_ids.len() {
+        return Err(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Soru seti bulunamadı"
+        })));
+    }
+    
+    for set in sets {
+        if !is_admin
+            && collaboration::access_level(pool, user_id, set.id, set.creator_id, &set.visibility).await == SetAccess::None
+        {
+            return Err(HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu soru seti size ait değil",
+                "question_set_id": set.id
+            })));
+        }
+        if set.is_hidden && !is_admin {
+            return Err(HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu soru seti moderasyon nedeniyle gizlendi",
+                "question_set_id": set.id
+            })));
+        }
+    }
+    
+    Ok(())
+}
+
+// Kişiye özel katılım bağlantısını çöz: oyun kodu ve sınıf listesindeki öğrenci. Listedeki satır bir hesaba
+// bağlıysa ve istek başka bir kullanıcıdan geliyorsa bağlantı kullanılamaz.
+async fn join_link_identity(
+    pool: &Pool<Postgres>,
+    token: &str,
+    user_id: Option<i32>,
+) -> Result<(String, roster::RosterEntry), HttpResponse> {
+    let Ok(link) = decode_join_link_token(token) else {
+        return Err(HttpResponse::Unauthorized().json(serde_json::json!({
+            "error": "Katılım bağlantısı geçersiz veya süresi dolmuş"
+        })));
+    };
+    
+    let game_code = sqlx::query_scalar!("SELECT code FROM games WHERE id = $1", link.game_id)
+        .fetch_optional(pool)
+        .await;
+    let entry = roster::entry(pool, link.roster_id).await;
+    
+    match (game_code, entry) {
+        (Ok(Some(game_code)), Ok(Some(entry))) => {
+            if matches!((user_id, entry.user_id), (Some(user_id), Some(owner)) if user_id != owner) {
+                return Err(HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": "Bu katılım bağlantısı başka bir öğrenciye ait"
+                })));
+            }
+            Ok((game_code, entry))
+        }
+        (Ok(_), Ok(_)) => Err(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Katılım bağlantısına ait oyun veya öğrenci bulunamadı"
+        }))),
+        (Err(e), _) | (_, Err(e)) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            Err(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Oyuna katılınamadı"
+            })))
+        }
+    }
+}
+
 // Oyuna katıl
 pub async fn join_game(
+    req: HttpRequest,
     pool: web::Data<Pool<Postgres>>,
+    app_state: web::Data<AppState>,
     join_dto: web::Json<JoinGameDto>,
-    claims: Option<web::ReqData<Claims>>,
+    claims: Option<Claims>,
 ) -> impl Responder {
+    let user_id = claims.as_ref().map(|c| c.sub.parse::<i32>().unwrap_or_default());
+    
+    // Kişiye özel katılım bağlantısıyla gelindiyse oyun ve öğrenci tokendan alınır; aksi halde
+    // oyun kodu yerine takma ad girilmiş olabilir
+    let (game_code, link_entry) = match join_dto.join_token.as_deref() {
+        Some(token) => match join_link_identity(&pool, token, user_id).await {
+            Ok((game_code, entry)) => (game_code, Some(entry)),
+            Err(response) => return response,
+        },
+        None => match game_alias::resolve(&pool, &join_dto.game_code, user_id).await {
+            Ok(Resolution::Code(code)) => (code, None),
+            Ok(Resolution::NotFound) => {
+                return HttpResponse::NotFound().json(serde_json::json!({
+                    "error": "Oyun bulunamadı"
+                }));
+            }
+            Ok(Resolution::Ambiguous) => {
+                return HttpResponse::Conflict().json(serde_json::json!({
+                    "error": "Bu takma ad birden fazla oyunda kullanılıyor, lütfen oyun kodunu girin"
+                }));
+            }
+            Err(e) => {
+                error!("Veritabanı sorgu hatası: {}", e);
+                return HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Oyuna katılınamadı"
+                }));
+            }
+        },
+    };
+    // Bağlantı hesaplı bir öğrenciye aitse sonuçlar o hesaba yazılır
+    let user_id = user_id.or(link_entry.as_ref().and_then(|entry| entry.user_id));
+    
     // Oyunun varlığını ve durumunu kontrol et
     let game = sqlx::query!(
-        "SELECT id, status FROM games WHERE code = $1",
-        join_dto.game_code
+        "SELECT id, status, roster_class_id FROM games WHERE code = $1",
+        game_code
     )
     .fetch_optional(&**pool)
     .await;
@@ -156,45 +452,89 @@ pub async fn join_game(
                 }));
             }
             
-            let user_id = claims.as_ref().map(|c| c.sub.parse::<i32>().unwrap_or_default());
             let session_id = Uuid::new_v4().to_string();
             
-            // Oyuncu bilgilerini hazırla
-            let nickname = match (user_id, &join_dto.nickname) {
-                (Some(id), _) => {
-                    // Kayıtlı kullanıcı - kullanıcı adını veritabanından al
-                    let user_result = sqlx::query!(
-                        "SELECT username FROM users WHERE id = $1",
-                        id
-                    )
-                    .fetch_one(&**pool)
-                    .await;
-                    
-                    match user_result {
-                        Ok(user) => user.username,
-                        Err(_) => return HttpResponse::InternalServerError().json(serde_json::json!({
-                            "error": "Kullanıcı bilgileri alınamadı"
-                        }))
-                    }
+            // Oyun bir sınıf listesiyle sınırlandırılmışsa yalnızca listedeki öğrenciler katılabilir ve
+            // takma ad listedeki addan atanır
+            let roster_nickname = match (game.roster_class_id, &link_entry, user_id) {
+                (Some(class_id), Some(entry), _) if entry.class_id == class_id => Some(entry.display_name.clone()),
+                (_, Some(_), _) => {
+                    return HttpResponse::Forbidden().json(serde_json::json!({
+                        "error": "Katılım bağlantısı bu oyun için geçerli değil"
+                    }));
                 }
-                (None, Some(nickname)) => {
+                (None, None, _) => None,
+                (Some(_), None, None) => {
+                    return HttpResponse::Forbidden().json(serde_json::json!({
+                        "error": "Bu oyuna yalnızca sınıf listesindeki öğrenciler katılabilir"
+                    }));
+                }
+                (Some(class_id), None, Some(user_id)) => match roster::roster_nickname(&pool, class_id, user_id).await {
+                    Ok(Some(nickname)) => Some(nickname),
+                    Ok(None) => {
+                        return HttpResponse::Forbidden().json(serde_json::json!({
+                            "error": "Bu oyuna yalnızca sınıf listesindeki öğrenciler katılabilir"
+                        }));
+                    }
+                    Err(e) => {
+                        error!("Sınıf listesi kontrol edilirken hata: {}", e);
+                        return HttpResponse::InternalServerError().json(serde_json::json!({
+                            "error": "Oyuna katılınamadı"
+                        }));
+                    }
+                },
+            };
+            
+            // Oyuncu bilgilerini hazırla
+            let nickname = match (roster_nickname, &claims, &join_dto.nickname) {
+                (Some(nickname), _, _) => nickname,
+                // Kayıtlı kullanıcı - kullanıcı adı tokendan alınır
+                (None, Some(claims), _) => claims.username.clone(),
+                (None, None, Some(nickname)) => {
                     // Misafir kullanıcı - verilen takma adı kullan, ** ekle
-                    if !nickname.starts_with("**") {
-                        format!("**{}", nickname)
-                    } else {
-                        nickname.clone()
+                    let Some(base) = guest_nickname_base(nickname) else {
+                        return HttpResponse::BadRequest().json(serde_json::json!({
+                            "error": CONFIG.username_charset.nickname_rule_message()
+                        }));
+                    };
+                    
+                    // Kayıtlı bir öğrencinin adıyla çakışıyorsa sayı eklenir
+                    match resolve_guest_nickname(&pool, game.id, &base).await {
+                        Ok(nickname) => nickname,
+                        Err(e) => {
+                            error!("Takma ad kontrol edilirken hata: {}", e);
+                            return HttpResponse::InternalServerError().json(serde_json::json!({
+                                "error": "Oyuna katılınamadı"
+                            }));
+                        }
                     }
                 }
-                (None, None) => {
+                (None, None, None) => {
                     return HttpResponse::BadRequest().json(serde_json::json!({
                         "error": "Misafir kullanıcılar için takma ad zorunludur"
                     }))
                 }
             };
             
-            // Takma adın oyunda benzersiz olup olmadığını kontrol et
+            // Turnuvanın sonraki turlarına yalnızca elenmemiş katılımcılar katılabilir
+            match tournament::may_join(&pool, game.id, user_id, &nickname).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    return HttpResponse::Forbidden().json(serde_json::json!({
+                        "error": "Bu tura yalnızca önceki turlardan yükselen katılımcılar katılabilir"
+                    }));
+                }
+                Err(e) => {
+                    error!("Turnuva katılımı kontrol edilirken hata: {}", e);
+                    return HttpResponse::InternalServerError().json(serde_json::json!({
+                        "error": "Oyuna katılınamadı"
+                    }));
+                }
+            }
+            
+            // Takma adın oyunda benzersiz olup olmadığını kontrol et (büyük/küçük harf ve Türkçe karakter farkı gözetilmez)
             let existing_player = sqlx::query!(
-                "SELECT id FROM players WHERE game_id = $1 AND nickname = $2",
+                "SELECT id FROM players WHERE game_id = $1 AND normalize_nickname(nickname) = normalize_nickname($2)",
                 game.id,
                 nickname
             )
@@ -207,18 +547,47 @@ pub async fn join_game(
                 }));
             }
             
+            // Misafirler için IP adresi (hashlenmiş) kaydedilir ve oyunun IP sınırı uygulanır
+            let ip_hash = match user_id {
+                None => client_ip(&req).map(|ip| hash_client_ip(&ip)),
+                Some(_) => None,
+            };
+            
+            if let Some(ip_hash) = &ip_hash {
+                match guest_ip::guest_limit_reached(&pool, game.id, ip_hash).await {
+                    Ok(Some(limit)) => {
+                        return HttpResponse::Forbidden().json(serde_json::json!({
+                            "error": format!("Bu ağdan en fazla {} misafir oyuncu katılabilir", limit)
+                        }));
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        error!("IP sınırı kontrol edilirken hata: {}", e);
+                        return HttpResponse::InternalServerError().json(serde_json::json!({
+                            "error": "Oyuna katılınamadı"
+                        }));
+                    }
+                }
+            }
+            
+            let avatar = cosmetics::resolve_player_avatar(&pool, user_id, join_dto.avatar.as_deref()).await;
+            let locale = join_dto.locale.as_deref().and_then(localization::normalize_locale);
+            
             // Oyuncuyu veritabanına ekle
             let player_result = sqlx::query!(
                 r#"
-                INSERT INTO players (game_id, user_id, nickname, session_id, joined_at)
-                VALUES ($1, $2, $3, $4, $5)
+                INSERT INTO players (game_id, user_id, nickname, session_id, joined_at, avatar, ip_hash, locale)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
                 RETURNING id
                 "#,
                 game.id,
                 user_id,
                 nickname,
                 session_id,
-                Utc::now()
+                Utc::now(),
+                avatar,
+                ip_hash,
+                locale
             )
             .fetch_one(&**pool)
             .await;
@@ -240,15 +609,51 @@ pub async fn join_game(
                     .execute(&**pool)
                     .await;
                     
+                    // Misafirin oturumu bu oyunla sınırlı ve süreli
+                    if user_id.is_none() {
+                        if let Err(e) = guest_sessions::open(&pool, player.id, game.id, &session_id).await {
+                            error!("Misafir oturumu açılamadı (player_id={}): {}", player.id, e);
+                        }
+                    }
+                    
+                    game_events::record_event(&pool, game.id, game_events::EVENT_PLAYER_JOINED, serde_json::json!({
+                        "player_id": player.id,
+                        "nickname": nickname,
+                        "is_guest": user_id.is_none()
+                    })).await;
+                    
+                    // WebSocket ile bağlı oyunculara ve host'a yeni oyuncuyu bildir
+                    app_state
+                        .register_player(game.id, &game_code, &session_id, user_id, LobbyPlayer {
+                            id: player.id,
+                            nickname: nickname.clone(),
+                            is_guest: user_id.is_none(),
+                            avatar: avatar.clone(),
+                        }, locale.clone())
+                        .await;
+                    app_state.broadcast_lobby(game.id, &game_code).await;
+                    
+                    // REST ile cevap göndermek için kısa ömürlü oyuncu tokenı
+                    let (player_token, player_token_expires_at) = generate_player_token(player.id, game.id, &session_id);
+                    
                     HttpResponse::Ok().json(serde_json::json!({
                         "player_id": player.id,
                         "game_id": game.id,
+                        "game_code": game_code,
                         "session_id": session_id,
+                        "player_token": player_token,
+                        "player_token_expires_at": player_token_expires_at,
                         "nickname": nickname,
                         "is_guest": user_id.is_none(),
+                        "avatar": avatar,
+                        "locale": locale,
                         "message": "Lobby'ye başarıyla katıldınız. Oyun başlayana kadar bekleyin."
                     }))
                 }
+                // Aynı takma ad kontrolden sonra başka bir oyuncu tarafından alındı
+                Err(e) if is_nickname_conflict(&e) => HttpResponse::Conflict().json(serde_json::json!({
+                    "error": "Bu takma ad zaten kullanılıyor"
+                })),
                 Err(e) => {
                     error!("Oyuna katılırken hata: {}", e);
                     HttpResponse::InternalServerError().json(serde_json::json!({
@@ -271,80 +676,125 @@ pub async fn join_game(
     }
 }
 
-// Oyunu başlat
-pub async fn start_game(
+// Sınıf listesiyle sınırlandırılmış oyun için listedeki her öğrenciye kişiye özel katılım bağlantısı oluştur;
+// öğrenci bağlantıya tıklayınca kod ve takma ad girmeden listedeki adıyla lobiye katılır
+pub async fn create_join_links(
     pool: web::Data<Pool<Postgres>>,
     game_code: web::Path<String>,
-    claims: web::ReqData<Claims>,
+    claims: Claims,
 ) -> impl Responder {
     let user_id = claims.sub.parse::<i32>().unwrap_or_default();
-    let game_code_inner = game_code.into_inner();
     
-    // Oyunu bul ve host'un bu kullanıcı olup olmadığını kontrol et
     let game = sqlx::query!(
-        "SELECT id, host_id, status FROM games WHERE code = $1",
-        game_code_inner
+        "SELECT id, host_id, status, roster_class_id FROM games WHERE code = $1",
+        game_code.into_inner()
     )
     .fetch_optional(&**pool)
     .await;
     
-    match game {
-        Ok(Some(game)) => {
-            if game.host_id != user_id {
-                return HttpResponse::Forbidden().json(serde_json::json!({
-                    "error": "Sadece oyun sahibi oyunu başlatabilir"
-                }));
-            }
-            
-            if game.status != "lobby" {
-                return HttpResponse::BadRequest().json(serde_json::json!({
-                    "error": "Bu oyun zaten başlatılmış veya tamamlanmış"
-                }));
-            }
-            
-            // Oyun durumunu güncelle
-            let update_result = sqlx::query!(
-                r#"
-                UPDATE games
-                SET status = $1, started_at = $2
-                WHERE id = $3
-                "#,
-                GameStatus::Active.to_string().to_lowercase(),
-                Utc::now(),
-                game.id
-            )
-            .execute(&**pool)
-            .await;
-            
-            match update_result {
-                Ok(_) => {
-                    HttpResponse::Ok().json(serde_json::json!({
-                        "message": "Oyun başlatıldı",
-                        "game_id": game.id,
-                        "status": "active",
-                        "started_at": Utc::now()
-                    }))
-                }
-                Err(e) => {
-                    error!("Oyun başlatılırken hata: {}", e);
-                    HttpResponse::InternalServerError().json(serde_json::json!({
-                        "error": "Oyun başlatılamadı"
-                    }))
-                }
-            }
+    let game = match game {
+        Ok(Some(game)) if game.host_id != user_id && claims.role != "admin" => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu işlemi yalnızca oyun sahibi yapabilir"
+            }));
         }
+        Ok(Some(game)) => game,
         Ok(None) => {
-            HttpResponse::NotFound().json(serde_json::json!({
+            return HttpResponse::NotFound().json(serde_json::json!({
                 "error": "Oyun bulunamadı"
-            }))
+            }));
         }
         Err(e) => {
             error!("Veritabanı sorgu hatası: {}", e);
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Oyun başlatılamadı"
-            }))
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Katılım bağlantıları oluşturulamadı"
+            }));
+        }
+    };
+    
+    if game.status != "lobby" {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Bu oyun artık katılıma açık değil"
+        }));
+    }
+    let Some(class_id) = game.roster_class_id else {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Katılım bağlantıları için oyunun bir sınıf listesiyle sınırlandırılması gerekir"
+        }));
+    };
+    
+    let entries = match roster::class_entries(&pool, class_id).await {
+        Ok(entries) => entries,
+        Err(e) => {
+            error!("Sınıf listesi alınırken hata: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Katılım bağlantıları oluşturulamadı"
+            }));
+        }
+    };
+    
+    let expires_at = Utc::now() + Duration::hours(roster::JOIN_LINK_VALID_HOURS);
+    let mut links = Vec::with_capacity(entries.len());
+    for entry in entries {
+        match generate_join_link_token(game.id, entry.id, expires_at) {
+            Ok(token) => links.push(serde_json::json!({
+                "roster_id": entry.id,
+                "display_name": entry.display_name,
+                "join_link": roster::join_link(&token),
+                "token": token
+            })),
+            Err(e) => {
+                error!("Katılım bağlantısı tokenı oluşturulamadı: {}", e);
+                return HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Katılım bağlantıları oluşturulamadı"
+                }));
+            }
         }
     }
+    
+    info!("Kişiye özel katılım bağlantıları oluşturuldu: game_id={}, {} öğrenci", game.id, links.len());
+    HttpResponse::Ok().json(serde_json::json!({
+        "expires_at": expires_at,
+        "links": links
+    }))
+}
+
+// Oyun servisinin hatalarını HTTP yanıtına çevir
+fn game_error_response(e: &GameError) -> HttpResponse {
+    let body = serde_json::json!({ "error": e.message() });
+    match e {
+        GameError::GameNotFound | GameError::QuestionNotFound | GameError::NotAnswered => HttpResponse::NotFound().json(body),
+        GameError::NotHost | GameError::NotFinalist => HttpResponse::Forbidden().json(body),
+        GameError::PlayerNotFound => HttpResponse::Unauthorized().json(body),
+        GameError::Database => HttpResponse::InternalServerError().json(body),
+        GameError::AlreadyStarted
+        | GameError::NotActive
+        | GameError::QuestionNotActive
+        | GameError::InvalidAnswer
+        | GameError::AlreadyAnswered
+        | GameError::SuddenDeathUnavailable
+        | GameError::InvalidFinalistCount => HttpResponse::BadRequest().json(body),
+    }
+}
+
+// Oyunu başlat
+pub async fn start_game(
+    pool: web::Data<Pool<Postgres>>,
+    app_state: web::Data<AppState>,
+    game_code: web::Path<String>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().ok();
+    
+    match GameService::new(&pool).start_game(&app_state, &game_code, user_id).await {
+        Ok(game_id) => HttpResponse::Ok().json(serde_json::json!({
+            "message": "Oyun başlatıldı",
+            "game_id": game_id,
+            "status": "active",
+            "started_at": Utc::now()
+        })),
+        Err(e) => game_error_response(&e),
+    }
 }
 
 // Liderlik tablosunu getir
@@ -364,40 +814,11 @@ pub async fn get_leaderboard(
     
     match game {
         Ok(Some(game)) => {
-            // Oyuncuları puanlarına göre sırala
-            let players = sqlx::query!(
-                r#"
-                SELECT 
-                    p.id, 
-                    p.nickname, 
-                    p.score, 
-                    p.user_id IS NULL as is_guest,
-                    COUNT(pa.id) as answer_count,
-                    COUNT(pa.id) FILTER (WHERE pa.is_correct) as correct_count
-                FROM players p
-                LEFT JOIN player_answers pa ON p.id = pa.player_id
-                WHERE p.game_id = $1 AND p.is_active = true
-                GROUP BY p.id, p.nickname, p.score
-                ORDER BY p.score DESC
-                LIMIT 100
-                "#,
-                game.id
-            )
-            .fetch_all(&**pool)
-            .await;
+            // Oyuncuları puanlarına göre sırala; canlı oyuncular sıralamayı WebSocket ile aldığından okuma replikası yeterli
+            let players = PgPlayerRepo::reader(&pool).leaderboard(game.id).await;
             
             match players {
-                Ok(players) => {
-                    let leaderboard: Vec<LeaderboardEntry> = players
-                        .iter()
-                        .map(|p| LeaderboardEntry {
-                            player_id: p.id,
-                            nickname: p.nickname.clone(),
-                            score: p.score.unwrap_or(0),
-                            is_guest: p.is_guest.unwrap_or(false),
-                        })
-                        .collect();
-                    
+                Ok(leaderboard) => {
                     HttpResponse::Ok().json(serde_json::json!({
                         "leaderboard": leaderboard
                     }))
@@ -424,430 +845,678 @@ pub async fn get_leaderboard(
     }
 }
 
-// Cevap gönderme işleyicisi
+// Cevap gönderme işleyicisi; oyuncu katılımda veya yeniden bağlanınca aldığı player-token header'ı ile doğrulanır
 pub async fn submit_answer_with_header(
     req: HttpRequest,
     pool: web::Data<Pool<Postgres>>,
+    app_state: web::Data<AppState>,
     answer_dto: web::Json<SubmitAnswerDto>,
 ) -> HttpResponse {
-    // Session ID'yi header'dan al
-    let session_id_str = match req.headers().get("session-id") {
-        Some(value) => match value.to_str() {
-            Ok(str_value) => str_value.to_string(),
-            Err(_) => return HttpResponse::BadRequest().json(serde_json::json!({
-                "error": "Geçersiz session-id header değeri"
-            })),
-        },
+    let token = match req.headers().get("player-token").map(|value| value.to_str()) {
+        Some(Ok(value)) => value,
+        Some(Err(_)) => return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Geçersiz player-token header değeri"
+        })),
         None => return HttpResponse::BadRequest().json(serde_json::json!({
-            "error": "session-id header eksik"
+            "error": "player-token header eksik"
+        })),
+    };
+    
+    let Some(token) = parse_player_token(token) else {
+        return HttpResponse::Unauthorized().json(serde_json::json!({
+            "error": "Oyuncu tokenı geçersiz veya süresi dolmuş"
+        }));
+    };
+    
+    // Token oyuncunun güncel oturumuna (misafirlerde geçerli bir misafir oturumuna) bağlı olmalıdır
+    let player = guest_sessions::player_session(&pool, &token).await;
+    
+    let session_id = match player {
+        Ok(Some(session_id)) => session_id,
+        Ok(None) => return HttpResponse::Unauthorized().json(serde_json::json!({
+            "error": "Oyuncu tokenı geçersiz veya süresi dolmuş"
         })),
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Cevap gönderilemedi"
+            }));
+        }
     };
     
-    // İç fonksiyonu çağır
-    submit_answer_internal(pool, answer_dto, session_id_str).await
+    match GameService::new(&pool).submit_answer(
+        &app_state,
+        &session_id,
+        answer_dto.question_id,
+        &answer_dto.answer,
+        answer_dto.response_time_ms,
+    )
+    .await
+    {
+        Ok(result) => HttpResponse::Ok().json(result.receipt()),
+        Err(e) => game_error_response(&e),
+    }
 }
 
-// Cevap gönderme işleminin iç fonksiyonu
-async fn submit_answer_internal(
+// Bir sonraki soruya geç
+pub async fn next_question(
     pool: web::Data<Pool<Postgres>>,
-    answer_dto: web::Json<SubmitAnswerDto>,
-    session_id: String,
-) -> HttpResponse {  
-    // Oyuncu ve oyun bilgilerini kontrol et
-    let player = sqlx::query!(
-        r#"
-        SELECT p.id, p.user_id, p.game_id, p.nickname, g.status, g.current_question
-        FROM players p
-        JOIN games g ON p.game_id = g.id
-        WHERE p.session_id = $1 AND p.is_active = true
-        "#,
-        session_id
-    )
-    .fetch_optional(&**pool)
-    .await;
+    app_state: web::Data<AppState>,
+    game_code: web::Path<String>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().ok();
     
-    match player {
-        Ok(Some(player)) => {
-            if player.status != "active" {
-                return HttpResponse::BadRequest().json(serde_json::json!({
-                    "error": "Oyun aktif değil"
-                }));
-            }
-            
-            // Mevcut soru kontrolü - doğru soru için cevap gönderiliyor mu?
-            let current_question_position = player.current_question.unwrap_or(0);
-            let question_position = sqlx::query!(
-                "SELECT position FROM questions WHERE id = $1",
-                answer_dto.question_id
-            )
-            .fetch_optional(&**pool)
-            .await;
-            
-            if let Ok(Some(pos)) = question_position {
-                if pos.position != current_question_position {
-                    return HttpResponse::BadRequest().json(serde_json::json!({
-                        "error": "Bu soru şu anda aktif değil"
-                    }));
-                }
-            } else {
-                return HttpResponse::NotFound().json(serde_json::json!({
-                    "error": "Soru bulunamadı"
-                }));
-            }
-            
-            // Oyuncunun bu soruya daha önce cevap verip vermediğini kontrol et
-            let existing_answer = sqlx::query!(
-                "SELECT id FROM player_answers WHERE player_id = $1 AND question_id = $2",
-                player.id,
-                answer_dto.question_id
-            )
-            .fetch_optional(&**pool)
-            .await;
-            
-            if let Ok(Some(_)) = existing_answer {
-                return HttpResponse::BadRequest().json(serde_json::json!({
-                    "error": "Bu soruya zaten cevap verdiniz"
-                }));
-            }
-            
-            // Sorunun doğru cevabını bul
-            let question = sqlx::query!(
-                r#"
-                SELECT correct_option, question_set_id FROM questions WHERE id = $1
-                "#,
-                answer_dto.question_id
+    match GameService::new(&pool).next_question(&app_state, &game_code, user_id).await {
+        // Oyunculara soru yayınlandı; host yanıtında doğru cevap da bulunur
+        Ok(NextStep::Question(question)) => HttpResponse::Ok().json(question.host_message()),
+        Ok(NextStep::Completed(summary)) => HttpResponse::Ok().json(serde_json::json!({
+            "message": "Oyun tamamlandı",
+            "game_id": summary.game_id,
+            "status": "completed",
+            "final_leaderboard": summary.final_leaderboard,
+            "player_stats": summary.player_stats,
+            "xp_awards": summary.rewards.xp_awards,
+            "badges_earned": summary.rewards.badges_earned,
+            "rating_changes": summary.rewards.rating_changes,
+            "sudden_death_winner": summary.sudden_death_winner,
+            "ended_at": Utc::now()
+        })),
+        Err(e) => game_error_response(&e),
+    }
+}
+
+// Ani ölüm turunu başlat
+pub async fn start_sudden_death(
+    pool: web::Data<Pool<Postgres>>,
+    app_state: web::Data<AppState>,
+    game_code: web::Path<String>,
+    sudden_death_dto: web::Json<SuddenDeathDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().ok();
+    
+    match GameService::new(&pool).start_sudden_death(&app_state, &game_code, user_id, sudden_death_dto.top_n).await {
+        Ok(finalists) => HttpResponse::Ok().json(serde_json::json!({
+            "message": "Ani ölüm turu başlatıldı",
+            "finalists": finalists
+        })),
+        Err(e) => game_error_response(&e),
+    }
+}
+
+// Oyun detaylarını getir
+pub async fn get_game(
+    req: HttpRequest,
+    pool: web::Data<Pool<Postgres>>,
+    game_code: web::Path<String>,
+) -> impl Responder {
+    let game_code_inner = game_code.into_inner();
+    
+    // Oyun bilgilerini getir
+    let game = sqlx::query!(
+        r#"
+        SELECT g.id, g.code, g.question_set_id, g.host_id, g.status, 
+               g.current_question, g.started_at, g.ended_at, g.created_at, g.option_labels, g.scene_cues,
+               g.roster_class_id, g.grading, g.question_sample, g.updated_at,
+               qs.title as question_set_title,
+               u.username as host_username
+        FROM games g
+        JOIN question_sets qs ON g.question_set_id = qs.id
+        JOIN users u ON g.host_id = u.id
+        WHERE g.code = $1
+        "#,
+        game_code_inner
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match game {
+        Ok(Some(game)) => {
+            // Oyuncu sayısını getir
+            let player_count = sqlx::query!(
+                "SELECT COUNT(*) as count FROM players WHERE game_id = $1 AND is_active = true",
+                game.id
             )
-            .fetch_optional(&**pool)
+            .fetch_one(&**pool)
             .await;
             
-            match question {
-                Ok(Some(question)) => {
-                    // Sorunun bu oyuna ait olup olmadığını kontrol et
-                    let question_set = sqlx::query!(
-                        r#"
-                        SELECT id FROM games WHERE id = $1 AND question_set_id = $2
-                        "#,
-                        player.game_id,
-                        question.question_set_id
-                    )
-                    .fetch_optional(&**pool)
-                    .await;
-                    
-                    if question_set.is_err() || question_set.unwrap().is_none() {
-                        return HttpResponse::BadRequest().json(serde_json::json!({
-                            "error": "Bu soru bu oyuna ait değil"
-                        }));
-                    }
-                    
-                    // Cevabın doğru olup olmadığını kontrol et
-                    let is_correct = answer_dto.answer.to_uppercase() == question.correct_option;
-                    
-                    // Puanı hesapla - hız temelli puanlama
-                    let points = if is_correct {
-                        // Daha hızlı cevaplar daha yüksek puan alır
-                        // En fazla 1000 puan, en az 100 puan (10 saniye için)
-                        let max_points = 1000;
-                        let min_points = 100;
-                        let max_time_ms = 10000; // 10 saniye
-                        
-                        let time_factor = (max_time_ms - answer_dto.response_time_ms).max(0) as f64 / max_time_ms as f64;
-                        (min_points as f64 + (max_points - min_points) as f64 * time_factor) as i32
-                    } else {
-                        0
-                    };
-                    
-                    // Cevabı veritabanına kaydet
-                    let answer_result = sqlx::query!(
-                        r#"
-                        INSERT INTO player_answers
-                        (player_id, question_id, answer, is_correct, response_time_ms, points_earned)
-                        VALUES ($1, $2, $3, $4, $5, $6)
-                        RETURNING id, points_earned
-                        "#,
-                        player.id,
-                        answer_dto.question_id,
-                        answer_dto.answer.to_uppercase(),
-                        is_correct,
-                        answer_dto.response_time_ms,
-                        points
-                    )
-                    .fetch_one(&**pool)
-                    .await;
-                    
-                    match answer_result {
-                        Ok(answer) => {
-                            // Oyuncu puanını güncelle
-                            let _ = sqlx::query!(
-                                r#"
-                                UPDATE players
-                                SET score = score + $1
-                                WHERE id = $2
-                                "#,
-                                answer.points_earned,
-                                player.id
-                            )
-                            .execute(&**pool)
-                            .await;
-                            
-                            HttpResponse::Ok().json(serde_json::json!({
-                                "answer_id": answer.id,
-                                "is_correct": is_correct,
-                                "points_earned": answer.points_earned,
-                                "correct_option": question.correct_option,
-                                "message": if is_correct {
-                                    format!("Doğru! {} puan kazandınız", answer.points_earned.unwrap_or(0))
-                                } else {
-                                    "Yanlış cevap".to_string()
-                                }
-                            }))
-                        }
-                        Err(e) => {
-                            error!("Cevap kaydedilirken hata: {}", e);
-                            HttpResponse::InternalServerError().json(serde_json::json!({
-                                "error": "Cevap gönderilemedi"
-                            }))
-                        }
-                    }
-                }
-                Ok(None) => {
-                    HttpResponse::NotFound().json(serde_json::json!({
-                        "error": "Soru bulunamadı"
-                    }))
-                }
-                Err(e) => {
-                    error!("Veritabanı sorgu hatası: {}", e);
-                    HttpResponse::InternalServerError().json(serde_json::json!({
-                        "error": "Cevap gönderilemedi"
-                    }))
-                }
+            let player_count = player_count.map(|c| c.count.unwrap_or(0)).unwrap_or(0);
+            
+            // Oyunda sorulacak soru sayısı (birleştirilen setler ve rastgele seçim dahil)
+            let question_count = game_playlist::question_count(&pool, game.id).await.unwrap_or(0);
+            
+            // Lobide sık yoklanır: oyun, oyuncu sayısı ve soru sayısı değişmediyse gövde gönderilmez
+            let etag = etag::weak_etag(&[
+                &game.updated_at.to_rfc3339(),
+                &player_count.to_string(),
+                &question_count.to_string(),
+            ]);
+            if etag::if_none_match(&req, &etag) {
+                return HttpResponse::NotModified()
+                    .insert_header((header::ETAG, etag))
+                    .finish();
             }
+            
+            HttpResponse::Ok()
+                .insert_header((header::ETAG, etag))
+                .insert_header((header::CACHE_CONTROL, "no-cache"))
+                .json(serde_json::json!({
+                    "id": game.id,
+                    "code": game.code,
+                    "question_set_id": game.question_set_id,
+                    "question_set_title": game.question_set_title,
+                    "host_id": game.host_id,
+                    "host_username": game.host_username,
+                    "status": game.status,
+                    "current_question": game.current_question,
+                    "started_at": game.started_at,
+                    "ended_at": game.ended_at,
+                    "created_at": game.created_at,
+                    "option_labels": game.option_labels,
+                    "scene_cues": game.scene_cues,
+                    "roster_class_id": game.roster_class_id,
+                    "grading": game.grading,
+                    "question_sample": game.question_sample,
+                    "player_count": player_count,
+                    "question_count": question_count,
+                    "max_possible_score": game_service::max_possible_score(question_count)
+                }))
         }
         Ok(None) => {
-            HttpResponse::Unauthorized().json(serde_json::json!({
-                "error": "Aktif oyuncu bulunamadı"
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Oyun bulunamadı"
             }))
         }
         Err(e) => {
             error!("Veritabanı sorgu hatası: {}", e);
             HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Cevap gönderilemedi"
+                "error": "Oyun bilgileri alınamadı"
             }))
         }
     }
 }
 
-// Bir sonraki soruya geç
-pub async fn next_question(
+// Oyun ayarlarını güncelle (rekabet modu, IP başına misafir sınırı, rastgele soru seçimi vb.)
+pub async fn update_game_settings(
     pool: web::Data<Pool<Postgres>>,
     game_code: web::Path<String>,
-    claims: web::ReqData<Claims>,
+    settings_dto: web::Json<UpdateGameSettingsDto>,
+    claims: Claims,
 ) -> impl Responder {
     let user_id = claims.sub.parse::<i32>().unwrap_or_default();
     let game_code_inner = game_code.into_inner();
     
-    // Oyun ve host kontrolü
-    let game = sqlx::query!(
+    if matches!(settings_dto.max_guests_per_ip, Some(limit) if limit < 0) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "IP başına misafir sınırı negatif olamaz"
+        }));
+    }
+    
+    let option_labels = match settings_dto.option_labels.as_deref().map(str::parse::<OptionLabelStyle>) {
+        Some(Ok(style)) => Some(style.as_str()),
+        Some(Err(_)) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Şık etiketi stili letters, shapes veya colors olmalıdır"
+            }));
+        }
+        None => None,
+    };
+    
+    let scene_cues = match settings_dto.scene_cues.as_ref().map(scenes::validate_cues) {
+        Some(Ok(cues)) => Some(cues),
+        Some(Err(message)) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": message
+            }));
+        }
+        None => None,
+    };
+    
+    let grading = match settings_dto.grading.as_ref().map(GradingScale::from_settings) {
+        Some(Ok(scale)) => Some(scale.to_value()),
+        Some(Err(message)) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": message
+            }));
+        }
+        None => None,
+    };
+    
+    // Alan gönderildiyse Some(seçim); count 0 ise seçim kaldırılır
+    let question_sample = match settings_dto.question_sample.as_ref().map(QuestionSample::from_settings) {
+        Some(Ok(sample)) => Some(sample.as_ref().map(QuestionSample::to_value)),
+        Some(Err(message)) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": message
+            }));
+        }
+        None => None,
+    };
+    
+    // 0 gönderilirse sınıf listesi sınırı kaldırılır; aksi halde sınıf öğretmene ait olmalı
+    if let Some(class_id) = settings_dto.roster_class_id.filter(|&id| id != 0) {
+        match roster::can_manage_class(&pool, class_id, user_id, claims.role == "admin").await {
+            Ok(Some(true)) => {}
+            Ok(_) => {
+                return HttpResponse::NotFound().json(serde_json::json!({
+                    "error": "Sınıf bulunamadı"
+                }));
+            }
+            Err(e) => {
+                error!("Veritabanı sorgu hatası: {}", e);
+                return HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Oyun ayarları güncellenemedi"
+                }));
+            }
+        }
+    }
+    
+    // 0 gönderilirse sınır kaldırılır, alan gönderilmezse mevcut değer korunur
+    let result = sqlx::query!(
         r#"
-        SELECT g.id, g.host_id, g.status, g.current_question, g.question_set_id
-        FROM games g
-        WHERE g.code = $1
+        UPDATE games SET
+            is_competitive = COALESCE($1, is_competitive),
+            max_guests_per_ip = CASE WHEN $2::int IS NULL THEN max_guests_per_ip ELSE NULLIF($2, 0) END,
+            option_labels = COALESCE($3, option_labels),
+            scene_cues = COALESCE($4, scene_cues),
+            roster_class_id = CASE WHEN $5::int IS NULL THEN roster_class_id ELSE NULLIF($5, 0) END,
+            grading = COALESCE($6, grading),
+            question_sample = CASE WHEN $7 THEN $8 ELSE question_sample END
+        WHERE code = $9 AND (host_id = $10 OR $11) AND status = 'lobby'
+        RETURNING is_competitive, max_guests_per_ip, option_labels, scene_cues, roster_class_id, grading, question_sample
         "#,
-        game_code_inner
+        settings_dto.competitive,
+        settings_dto.max_guests_per_ip,
+        option_labels,
+        scene_cues,
+        settings_dto.roster_class_id,
+        grading,
+        question_sample.is_some(),
+        question_sample.flatten(),
+        game_code_inner,
+        user_id,
+        claims.role == "admin"
     )
     .fetch_optional(&**pool)
     .await;
+    
+    match result {
+        Ok(Some(game)) => {
+            info!("Oyun ayarları güncellendi: code={}", game_code_inner);
+            HttpResponse::Ok().json(serde_json::json!({
+                "code": game_code_inner,
+                "competitive": game.is_competitive,
+                "max_guests_per_ip": game.max_guests_per_ip,
+                "option_labels": game.option_labels,
+                "scene_cues": game.scene_cues,
+                "roster_class_id": game.roster_class_id,
+                "grading": game.grading,
+                "question_sample": game.question_sample
+            }))
+        }
+        Ok(None) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Lobide bekleyen ve size ait bir oyun bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Oyun ayarları güncellenirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Oyun ayarları güncellenemedi"
+            }))
+        }
+    }
+}
 
-    match game {
-        Ok(Some(g)) => {
-            // Sadece host soruyu ilerletebilir
-            if g.host_id != user_id {
-                return HttpResponse::Forbidden().json(serde_json::json!({
-                    "error": "Sadece oyun sahibi soruları ilerletebilir"
-                }));
-            }
-
-            if g.status != "active" {
-                return HttpResponse::BadRequest().json(serde_json::json!({
-                    "error": "Oyun aktif değil"
-                }));
-            }
-
-            // Bir sonraki soruyu getir
-            let next_question = g.current_question.unwrap_or(0) + 1;
-            
-            // Soru bilgilerini getir
-            let question = sqlx::query!(
-                r#"
-                SELECT id, question_text, option_a, option_b, option_c, option_d, 
-                       correct_option, time_limit, position
-                FROM questions
-                WHERE question_set_id = $1 AND position = $2
-                "#,
-                g.question_set_id,
-                next_question
-            )
-            .fetch_optional(&**pool)
-            .await;
-
-            // Toplam soru sayısını al
-            let total_questions = sqlx::query!(
-                "SELECT COUNT(*) as count FROM questions WHERE question_set_id = $1",
-                g.question_set_id
-            )
-            .fetch_one(&**pool)
-            .await
-            .map(|r| r.count.unwrap_or(0))
-            .unwrap_or(0);
-
-            match question {
-                Ok(Some(q)) => {
-                    // Oyun durumunu güncelle
-                    let _ = sqlx::query!(
-                        "UPDATE games SET current_question = $1 WHERE id = $2",
-                        next_question,
-                        g.id
-                    )
-                    .execute(&**pool)
-                    .await;
-
-                    HttpResponse::Ok().json(serde_json::json!({
-                        "question_id": q.id,
-                        "question_text": q.question_text,
-                        "options": {
-                            "A": q.option_a,
-                            "B": q.option_b,
-                            "C": q.option_c, 
-                            "D": q.option_d
-                        },
-                        "correct_option": q.correct_option,
-                        "time_limit": q.time_limit,
-                        "question_number": next_question + 1,
-                        "total_questions": total_questions
-                    }))
-                }
-                Ok(None) => {
-                    // Soru kalmadı, oyunu bitir
-                    let _ = sqlx::query!(
-                        r#"
-                        UPDATE games SET status = 'completed', ended_at = $1
-                        WHERE id = $2
-                        "#,
-                        Utc::now(),
-                        g.id
-                    )
-                    .execute(&**pool)
-                    .await;
+// Öğretmenin yönettiği oyunları listele
+pub async fn list_host_games(
+    pool: web::Data<Pool<Postgres>>,
+    query: web::Query<IncludeArchivedQuery>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let include_archived = query.include_archived.unwrap_or(false);
+    
+    let games = sqlx::query!(
+        r#"
+        SELECT g.id, g.code, g.alias, g.question_set_id, g.status, g.started_at, g.ended_at,
+               g.created_at, g.archived_at, qs.title as question_set_title,
+               (SELECT COUNT(*) FROM players p WHERE p.game_id = g.id) as "player_count!"
+        FROM games g
+        JOIN question_sets qs ON g.question_set_id = qs.id
+        WHERE g.host_id = $1 AND ($2 OR g.archived_at IS NULL)
+        ORDER BY g.created_at DESC
+        "#,
+        user_id,
+        include_archived
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    match games {
+        Ok(games) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "games": games.iter().map(|g| {
+                    serde_json::json!({
+                        "id": g.id,
+                        "code": g.code,
+                        "alias": g.alias,
+                        "question_set_id": g.question_set_id,
+                        "question_set_title": g.question_set_title,
+                        "status": g.status,
+                        "player_count": g.player_count,
+                        "started_at": g.started_at,
+                        "ended_at": g.ended_at,
+                        "created_at": g.created_at,
+                        "archived_at": g.archived_at
+                    })
+                }).collect::<Vec<_>>()
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Oyunlar alınamadı"
+            }))
+        }
+    }
+}
 
-                    HttpResponse::Ok().json(serde_json::json!({
-                        "message": "Oyun tamamlandı",
-                        "game_id": g.id,
-                        "status": "completed",
-                        "ended_at": Utc::now()
-                    }))
-                }
-                Err(e) => {
-                    error!("Veritabanı sorgu hatası: {}", e);
-                    HttpResponse::InternalServerError().json(serde_json::json!({
-                        "error": "Bir sonraki soru alınamadı"
-                    }))
-                }
-            }
+// Oyunu arşivle veya arşivden çıkar (oyunu yöneten öğretmen veya admin)
+// Devam eden oyunlar arşivlenemez
+async fn set_game_archived(
+    pool: &Pool<Postgres>,
+    game_code: &str,
+    claims: &Claims,
+    archived: bool,
+) -> HttpResponse {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let game = sqlx::query!(
+        "SELECT id, host_id, status FROM games WHERE code = $1",
+        game_code
+    )
+    .fetch_optional(pool)
+    .await;
+    
+    let game = match game {
+        Ok(Some(game)) if game.host_id != user_id && claims.role != "admin" => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu oyunu düzenleme izniniz yok"
+            }));
         }
+        Ok(Some(game)) => game,
         Ok(None) => {
-            HttpResponse::NotFound().json(serde_json::json!({
+            return HttpResponse::NotFound().json(serde_json::json!({
                 "error": "Oyun bulunamadı"
-            }))
+            }));
         }
         Err(e) => {
             error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Oyun arşiv durumu güncellenemedi"
+            }));
+        }
+    };
+    
+    if archived && game.status == "active" {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Devam eden bir oyun arşivlenemez"
+        }));
+    }
+    
+    let result = sqlx::query!(
+        r#"
+        UPDATE games SET archived_at = CASE WHEN $1 THEN COALESCE(archived_at, $2) END
+        WHERE id = $3
+        RETURNING archived_at
+        "#,
+        archived,
+        Utc::now(),
+        game.id
+    )
+    .fetch_one(pool)
+    .await;
+    
+    match result {
+        Ok(record) => {
+            info!("Oyun arşiv durumu güncellendi: code={}, archived={}", game_code, archived);
+            HttpResponse::Ok().json(serde_json::json!({
+                "code": game_code,
+                "archived": archived,
+                "archived_at": record.archived_at
+            }))
+        }
+        Err(e) => {
+            error!("Oyun arşivlenirken hata: {}", e);
             HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Oyun bilgileri alınamadı"
+                "error": "Oyun arşiv durumu güncellenemedi"
             }))
         }
     }
 }
 
-// Oyun detaylarını getir
-pub async fn get_game(
+// Oyunu arşivle
+pub async fn archive_game(
     pool: web::Data<Pool<Postgres>>,
     game_code: web::Path<String>,
+    claims: Claims,
 ) -> impl Responder {
-    let game_code_inner = game_code.into_inner();
+    set_game_archived(&pool, &game_code.into_inner(), &claims, true).await
+}
+
+// Oyunu arşivden çıkar
+pub async fn unarchive_game(
+    pool: web::Data<Pool<Postgres>>,
+    game_code: web::Path<String>,
+    claims: Claims,
+) -> impl Responder {
+    set_game_archived(&pool, &game_code.into_inner(), &claims, false).await
+}
+
+// Oyunun takma adını ayarla veya kaldır (oyunu yöneten öğretmen veya admin); bitmiş oyunlara takma ad verilemez
+async fn update_game_alias(
+    pool: &Pool<Postgres>,
+    game_code: &str,
+    claims: &Claims,
+    alias: Option<String>,
+) -> HttpResponse {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
     
-    // Oyun bilgilerini getir
     let game = sqlx::query!(
         r#"
-        SELECT g.id, g.code, g.question_set_id, g.host_id, g.status, 
-               g.current_question, g.started_at, g.ended_at, g.created_at,
-               qs.title as question_set_title,
-               u.username as host_username
+        SELECT g.id, g.host_id, g.status, u.organization_id
         FROM games g
-        JOIN question_sets qs ON g.question_set_id = qs.id
         JOIN users u ON g.host_id = u.id
         WHERE g.code = $1
         "#,
-        game_code_inner
+        game_code
     )
-    .fetch_optional(&**pool)
+    .fetch_optional(pool)
     .await;
     
-    match game {
-        Ok(Some(game)) => {
-            // Oyuncu sayısını getir
-            let player_count = sqlx::query!(
-                "SELECT COUNT(*) as count FROM players WHERE game_id = $1 AND is_active = true",
-                game.id
-            )
-            .fetch_one(&**pool)
-            .await;
-            
-            let player_count = player_count.map(|c| c.count.unwrap_or(0)).unwrap_or(0);
-            
-            // Soru sayısını getir
-            let question_count = sqlx::query!(
-                "SELECT COUNT(*) as count FROM questions WHERE question_set_id = $1",
-                game.question_set_id
-            )
-            .fetch_one(&**pool)
-            .await;
-            
-            let question_count = question_count.map(|c| c.count.unwrap_or(0)).unwrap_or(0);
-            
+    let game = match game {
+        Ok(Some(game)) if game.host_id != user_id && claims.role != "admin" => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu oyunu düzenleme izniniz yok"
+            }));
+        }
+        Ok(Some(game)) => game,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Oyun bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Oyun takma adı güncellenemedi"
+            }));
+        }
+    };
+    
+    if alias.is_some() && game.status == "completed" {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Bitmiş bir oyuna takma ad verilemez"
+        }));
+    }
+    
+    // Takma ad hostun kurumunda benzersiz olmalı
+    match game_alias::set_alias(pool, game.id, game.organization_id, alias.as_deref()).await {
+        Ok(()) => {
+            info!("Oyun takma adı güncellendi: code={}, alias={:?}", game_code, alias);
             HttpResponse::Ok().json(serde_json::json!({
-                "id": game.id,
-                "code": game.code,
-                "question_set_id": game.question_set_id,
-                "question_set_title": game.question_set_title,
-                "host_id": game.host_id,
-                "host_username": game.host_username,
-                "status": game.status,
-                "current_question": game.current_question,
-                "started_at": game.started_at,
-                "ended_at": game.ended_at,
-                "created_at": game.created_at,
-                "player_count": player_count,
-                "question_count": question_count
+                "code": game_code,
+                "alias": alias
+            }))
+        }
+        Err(e) if game_alias::is_alias_conflict(&e) => HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Bu takma ad kurumunuzda devam eden başka bir oyunda kullanılıyor"
+        })),
+        Err(e) => {
+            error!("Oyun takma adı güncellenirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Oyun takma adı güncellenemedi"
             }))
         }
+    }
+}
+
+// Oyuna takma ad ver; öğrenciler oyun kodu yerine bu adla katılabilir
+pub async fn set_game_alias(
+    pool: web::Data<Pool<Postgres>>,
+    game_code: web::Path<String>,
+    alias_dto: web::Json<GameAliasDto>,
+    claims: Claims,
+) -> impl Responder {
+    let alias = match game_alias::normalize_alias(&alias_dto.alias) {
+        Ok(alias) => alias,
+        Err(message) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": message
+            }));
+        }
+    };
+    
+    update_game_alias(&pool, &game_code.into_inner(), &claims, Some(alias)).await
+}
+
+// Oyunun takma adını kaldır
+pub async fn remove_game_alias(
+    pool: web::Data<Pool<Postgres>>,
+    game_code: web::Path<String>,
+    claims: Claims,
+) -> impl Responder {
+    update_game_alias(&pool, &game_code.into_inner(), &claims, None).await
+}
+
+// Lobideki oyunu ileri bir tarihe planla; başlangıçtan önce hatırlatma gönderilir
+pub async fn schedule_game(
+    pool: web::Data<Pool<Postgres>>,
+    game_code: web::Path<String>,
+    schedule_dto: web::Json<ScheduleGameDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let game_code = game_code.into_inner();
+    
+    if schedule_dto.scheduled_at.is_some_and(|at| at <= Utc::now()) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Planlanan başlangıç zamanı gelecekte olmalı"
+        }));
+    }
+    
+    let game = sqlx::query!(
+        "SELECT id, host_id, status FROM games WHERE code = $1",
+        game_code
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    let game = match game {
+        Ok(Some(game)) if game.host_id != user_id && claims.role != "admin" => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu oyunu düzenleme izniniz yok"
+            }));
+        }
+        Ok(Some(game)) => game,
         Ok(None) => {
-            HttpResponse::NotFound().json(serde_json::json!({
+            return HttpResponse::NotFound().json(serde_json::json!({
                 "error": "Oyun bulunamadı"
-            }))
+            }));
         }
         Err(e) => {
             error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Oyun planlanamadı"
+            }));
+        }
+    };
+    
+    if game.status != "lobby" {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Yalnızca başlamamış oyunlar planlanabilir"
+        }));
+    }
+    
+    // Plan değişince hatırlatma yeni zamana göre tekrar gönderilir
+    let result = sqlx::query!(
+        "UPDATE games SET scheduled_at = $1, reminder_sent_at = NULL WHERE id = $2",
+        schedule_dto.scheduled_at,
+        game.id
+    )
+    .execute(&**pool)
+    .await;
+    
+    match result {
+        Ok(_) => {
+            info!("Oyun planlandı: code={}, scheduled_at={:?}", game_code, schedule_dto.scheduled_at);
+            HttpResponse::Ok().json(serde_json::json!({
+                "code": game_code,
+                "scheduled_at": schedule_dto.scheduled_at
+            }))
+        }
+        Err(e) => {
+            error!("Oyun planlanırken hata: {}", e);
             HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Oyun bilgileri alınamadı"
+                "error": "Oyun planlanamadı"
             }))
         }
     }
 }
 
+// Kayıtlı bir kullanıcıyı lobideki oyuna davet et (bildirim ve push gönderilir)
+pub async fn challenge_player(
+    pool: web::Data<Pool<Postgres>>,
+    game_code: web::Path<String>,
+    challenge_dto: web::Json<ChallengeDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    match game_notifications::send_challenge(&pool, &game_code.into_inner(), user_id, challenge_dto.username.trim()).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({
+            "message": "Davet gönderildi"
+        })),
+        Err(e) => {
+            let body = serde_json::json!({"error": e.message()});
+            match e {
+                ChallengeError::GameNotFound | ChallengeError::UserNotFound => HttpResponse::NotFound().json(body),
+                ChallengeError::NotParticipant => HttpResponse::Forbidden().json(body),
+                ChallengeError::AlreadyChallenged => HttpResponse::Conflict().json(body),
+                ChallengeError::Database => HttpResponse::InternalServerError().json(body),
+                ChallengeError::NotInLobby | ChallengeError::SelfChallenge => HttpResponse::BadRequest().json(body),
+            }
+        }
+    }
+}
+
 // Oyun İstatistiklerini Getir
 pub async fn get_game_statistics(
     pool: web::Data<Pool<Postgres>>,
     game_code: web::Path<String>,
-    claims: web::ReqData<Claims>,
+    claims: Claims,
 ) -> impl Responder {
     let user_id = claims.sub.parse::<i32>().unwrap_or_default();
     let game_code_inner = game_code.into_inner();
@@ -856,7 +1525,8 @@ pub async fn get_game_statistics(
     let game = sqlx::query!(
         r#"
         SELECT g.id, g.host_id, g.status, g.question_set_id, 
-               qs.title as question_set_title, u.username as host_username
+               qs.title as question_set_title, u.username as host_username,
+               (SELECT COUNT(*) FROM game_question_ids(g.id)) as "question_count!"
         FROM games g
         JOIN question_sets qs ON g.question_set_id = qs.id
         JOIN users u ON g.host_id = u.id
@@ -876,53 +1546,35 @@ pub async fn get_game_statistics(
                 }));
             }
             
-            // Oyuncu istatistikleri
-            let player_stats = sqlx::query!(
-                r#"
-                SELECT 
-                    p.id as player_id,
-                    p.nickname,
-                    p.score,
-                    COUNT(pa.id) as answer_count,
-                    COUNT(pa.id) FILTER (WHERE pa.is_correct) as correct_count,
-                    ROUND(AVG(pa.response_time_ms)) as avg_response_time
-                FROM players p
-                LEFT JOIN player_answers pa ON p.id = pa.player_id
-                WHERE p.game_id = $1 AND p.is_active = true
-                GROUP BY p.id, p.nickname, p.score
-                ORDER BY p.score DESC
-                "#,
-                game.id
-            )
-            .fetch_all(&**pool)
-            .await;
+            // Ağır istatistik sorguları okuma replikasında çalışır
+            let player_stats = PgPlayerRepo::reader(&pool).game_stats(game.id).await;
+            let question_stats = PgQuestionRepo::reader(&pool).game_stats(game.id).await;
             
-            // Soru istatistikleri
-            let question_stats = sqlx::query!(
-                r#"
-                SELECT 
-                    q.id as question_id,
-                    q.question_text,
-                    q.correct_option,
-                    COUNT(pa.id) as answer_count,
-                    COUNT(pa.id) FILTER (WHERE pa.is_correct) as correct_count,
-                    ROUND(AVG(pa.response_time_ms)) as avg_response_time
-                FROM questions q
-                LEFT JOIN player_answers pa ON q.id = pa.question_id
-                WHERE q.question_set_id = $1 AND pa.player_id IN (
-                    SELECT id FROM players WHERE game_id = $2
-                )
-                GROUP BY q.id, q.question_text, q.correct_option
-                ORDER BY q.position
-                "#,
-                game.question_set_id,
-                game.id
-            )
-            .fetch_all(&**pool)
-            .await;
+            // Hile şüphesi raporu ve aynı IP'yi paylaşan misafirler
+            let cheat_report = anti_cheat::game_report(&pool, game.id).await;
+            let shared_ip_warnings = guest_ip::shared_ip_warnings(&pool, game.id).await;
             
-            match (player_stats, question_stats) {
-                (Ok(players), Ok(questions)) => {
+            // Soru başına cevap süresi dağılımları ve ilk cevap süreleri
+            let timings = answer_timing::game_question_timings(&pool, game.id).await;
+            
+            // Yanlış şıkların kümelenmesi: ortak kavram yanılgıları ve oyuncu örüntüleri
+            let misconception_report = misconceptions::game_report(&pool, game.id).await;
+            
+            // İzleyici sayısı, en yüksek eş zamanlı izleyici ve izleme süreleri
+            let viewer_report = viewers::game_report(&pool, game.id).await;
+            
+            let max_possible_score = game_service::max_possible_score(game.question_count);
+            
+            match (player_stats, question_stats, cheat_report, shared_ip_warnings, timings, misconception_report, viewer_report) {
+                (
+                    Ok(players),
+                    Ok(questions),
+                    Ok(cheat_report),
+                    Ok(shared_ip_warnings),
+                    Ok(mut timings),
+                    Ok(misconception_report),
+                    Ok(viewer_report),
+                ) => {
                     let player_statistics: Vec<PlayerStatistics> = players
                         .iter()
                         .map(|p| {
@@ -940,6 +1592,7 @@ pub async fn get_game_statistics(
                                 correct: p.correct_count.unwrap_or(0),
                                 accuracy,
                                 avg_response_time_ms: p.avg_response_time.as_ref().map(|bd| bigdecimal_to_f64(Some(bd.clone())) as i64),
+                                percent_of_max: game_service::percent_of_max(p.score.unwrap_or(0) as i64, max_possible_score),
                             }
                         })
                         .collect();
@@ -947,6 +1600,7 @@ pub async fn get_game_statistics(
                     let question_statistics: Vec<QuestionStatistics> = questions
                         .iter()
                         .map(|q| {
+                            let timing = timings.remove(&q.question_id).unwrap_or_default();
                             let total_answers = q.answer_count.unwrap_or(0);
                             let correct_count = q.correct_count.unwrap_or(0);
                             let incorrect_count = total_answers - correct_count;
@@ -981,6 +1635,10 @@ pub async fn get_game_statistics(
                                 accuracy,
                                 avg_response_time_ms: q.avg_response_time.as_ref().map(|t| bigdecimal_to_f64(Some(t.clone()))),
                                 difficulty_score,
+                                median_response_time_ms: timing.median_response_time_ms,
+                                time_to_first_answer_ms: timing.time_to_first_answer_ms,
+                                timed_out_count: timing.timed_out,
+                                response_time_histogram: timing.histogram,
                             }
                         })
                         .collect();
@@ -1001,8 +1659,14 @@ pub async fn get_game_statistics(
                         "status": game.status,
                         "player_count": total_players,
                         "avg_score": avg_score,
+                        "max_possible_score": max_possible_score,
+                        "avg_percent_of_max": game_service::percent_of_max(avg_score.round() as i64, max_possible_score),
                         "player_statistics": player_statistics,
                         "question_statistics": question_statistics,
+                        "cheat_flags": cheat_report,
+                        "shared_ip_warnings": shared_ip_warnings,
+                        "misconceptions": misconception_report,
+                        "viewers": viewer_report,
                     }))
                 }
                 _ => {
@@ -1025,4 +1689,180 @@ pub async fn get_game_statistics(
             }))
         }
     }
+}
+
+// Oyunun not ölçeğine göre öğrenci notlarını getir (JSON veya LMS'e aktarılabilir CSV)
+pub async fn get_game_grades(
+    pool: web::Data<Pool<Postgres>>,
+    game_code: web::Path<String>,
+    query: web::Query<ExportFormatQuery>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let game_code_inner = game_code.into_inner();
+    
+    let as_csv = match query.format.as_deref() {
+        None | Some("json") => false,
+        Some("csv") => true,
+        Some(_) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Rapor biçimi json veya csv olmalıdır"
+            }));
+        }
+    };
+    
+    let game = sqlx::query!(
+        r#"
+        SELECT g.id, g.host_id, g.status, g.grading, g.ended_at, qs.title as question_set_title,
+               (SELECT COUNT(*) FROM game_question_ids(g.id)) as "question_count!"
+        FROM games g
+        JOIN question_sets qs ON g.question_set_id = qs.id
+        WHERE g.code = $1
+        "#,
+        game_code_inner
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    let game = match game {
+        Ok(Some(game)) => game,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Oyun bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Notlar alınamadı"
+            }));
+        }
+    };
+    
+    // Sadece oyun sahibi veya admin notları görebilir
+    if game.host_id != user_id && claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu oyunun notlarını görüntüleme izniniz yok"
+        }));
+    }
+    
+    if game.status != "completed" {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Notlar oyun bittikten sonra hesaplanır"
+        }));
+    }
+    
+    let scale = GradingScale::from_stored(&game.grading);
+    match grading::game_grades(&pool, game.id, game.question_count, &scale).await {
+        Ok(grades) if as_csv => HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .insert_header((
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"notlar-{}.csv\"", game_code_inner),
+            ))
+            .body(format!("\u{feff}{}", grading::to_csv(&grades))),
+        Ok(grades) => HttpResponse::Ok().json(serde_json::json!({
+            "code": game_code_inner,
+            "title": game.question_set_title,
+            "ended_at": game.ended_at,
+            "grading": scale,
+            "max_possible_score": game_service::max_possible_score(game.question_count),
+            "grades": grades
+        })),
+        Err(e) => {
+            error!("Notlar hesaplanırken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Notlar alınamadı"
+            }))
+        }
+    }
+}
+
+// Oyunun tekrar oynatma zaman çizelgesini getir
+pub async fn get_game_replay(
+    pool: web::Data<Pool<Postgres>>,
+    game_code: web::Path<String>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let game_code_inner = game_code.into_inner();
+    
+    // Oyun bilgilerini getir
+    let game = sqlx::query!(
+        r#"
+        SELECT g.id, g.host_id, g.status, g.started_at, g.ended_at,
+               qs.title as question_set_title
+        FROM games g
+        JOIN question_sets qs ON g.question_set_id = qs.id
+        WHERE g.code = $1
+        "#,
+        game_code_inner
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match game {
+        Ok(Some(game)) => {
+            // Sadece oyun sahibi veya admin oyunu tekrar izleyebilir
+            if game.host_id != user_id && claims.role != "admin" {
+                return HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": "Bu oyunun kaydını görüntüleme izniniz yok"
+                }));
+            }
+            
+            match game_events::get_events(&pool, game.id).await {
+                Ok(events) => {
+                    // Her olayın oyun başlangıcına göre zamanını hesapla
+                    let origin = game
+                        .started_at
+                        .or_else(|| events.first().map(|e| e.created_at));
+                    
+                    let timeline: Vec<serde_json::Value> = events
+                        .iter()
+                        .map(|e| {
+                            let offset_ms = origin
+                                .map(|o| (e.created_at - o).num_milliseconds())
+                                .unwrap_or(0);
+                            
+                            serde_json::json!({
+                                "sequence": e.sequence,
+                                "type": e.event_type,
+                                "offset_ms": offset_ms,
+                                "created_at": e.created_at,
+                                "payload": e.payload
+                            })
+                        })
+                        .collect();
+                    
+                    HttpResponse::Ok().json(serde_json::json!({
+                        "game_id": game.id,
+                        "game_code": game_code_inner,
+                        "question_set_title": game.question_set_title,
+                        "status": game.status,
+                        "started_at": game.started_at,
+                        "ended_at": game.ended_at,
+                        "event_count": timeline.len(),
+                        "events": timeline
+                    }))
+                }
+                Err(e) => {
+                    error!("Oyun olayları alınamadı: {}", e);
+                    HttpResponse::InternalServerError().json(serde_json::json!({
+                        "error": "Oyun kaydı alınamadı"
+                    }))
+                }
+            }
+        }
+        Ok(None) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Oyun bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Oyun bilgileri alınamadı"
+            }))
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,492 @@
+use actix_web::{web, HttpResponse, Responder};
+use async_graphql::{ComplexObject, Context, EmptyMutation, EmptySubscription, Json, Object, Schema, SimpleObject};
+use chrono::{DateTime, NaiveDate, Utc};
+use lazy_static::lazy_static;
+use log::error;
+use sqlx::{Pool, Postgres};
+
+use crate::db::models::{Claims, ResponseTimeBucket};
+use crate::services::answer_timing;
+use crate::services::collaboration::{self, SetAccess};
+use crate::services::game as game_service;
+use crate::services::misconceptions::{self, MisconceptionReport};
+use crate::services::organization;
+use crate::services::platform_stats::{self, DailyStats};
+
+// GraphQL API
+// Panel istemcileri soru setlerini, oyunları, oyuncuları ve istatistikleri tek istekte, yalnızca ihtiyaç
+// duydukları alanları seçerek alabilir. İç içe alanlar (ör. oyunun oyuncuları, soru süre dağılımları,
+// kavram yanılgıları) yalnızca sorguda istendiğinde hesaplanır; böylece get_game_statistics gibi ağır
+// uçların tüm raporu her seferinde üretmesi gerekmez. Yetki kuralları REST uçlarıyla aynıdır.
+
+// Sorgu derinliği ve karmaşıklık sınırları (iç içe listelerle veritabanının yorulmasını önler)
+const MAX_QUERY_DEPTH: usize = 8;
+const MAX_QUERY_COMPLEXITY: usize = 500;
+
+// Tek sorguda listelenebilecek en fazla oyun
+const MAX_GAMES_LIMIT: i64 = 100;
+
+pub type ApiSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+lazy_static! {
+    static ref SCHEMA: ApiSchema = Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .limit_complexity(MAX_QUERY_COMPLEXITY)
+        .finish();
+}
+
+fn db_error(e: sqlx::Error) -> async_graphql::Error {
+    error!("Veritabanı sorgu hatası: {}", e);
+    async_graphql::Error::new("Veritabanı hatası")
+}
+
+fn current_user<'a>(ctx: &Context<'a>) -> (&'a Pool<Postgres>, &'a Claims, i32) {
+    let claims = ctx.data_unchecked::<Claims>();
+    (ctx.data_unchecked::<Pool<Postgres>>(), claims, claims.sub.parse::<i32>().unwrap_or_default())
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "User")]
+pub struct UserNode {
+    pub id: i32,
+    pub username: String,
+    pub role: String,
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex, name = "QuestionSet")]
+pub struct QuestionSetNode {
+    pub id: i32,
+    pub title: String,
+    pub description: Option<String>,
+    pub visibility: String,
+    pub language: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[ComplexObject]
+impl QuestionSetNode {
+    async fn question_count(&self, ctx: &Context<'_>) -> async_graphql::Result<i64> {
+        let (pool, _, _) = current_user(ctx);
+        sqlx::query_scalar!(r#"SELECT COUNT(*) as "count!" FROM questions WHERE question_set_id = $1 AND deleted_at IS NULL"#, self.id)
+            .fetch_one(pool)
+            .await
+            .map_err(db_error)
+    }
+
+    async fn questions(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<QuestionNode>> {
+        let (pool, _, _) = current_user(ctx);
+        let questions = sqlx::query!(
+            r#"
+            SELECT id, question_text, option_a, option_b, option_c, option_d, correct_option, points, time_limit, position
+            FROM questions
+            WHERE question_set_id = $1 AND deleted_at IS NULL
+            ORDER BY position
+            "#,
+            self.id
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(questions
+            .into_iter()
+            .map(|q| QuestionNode {
+                id: q.id,
+                question_text: q.question_text,
+                options: vec![q.option_a, q.option_b, q.option_c, q.option_d],
+                correct_option: q.correct_option,
+                points: q.points,
+                time_limit: q.time_limit,
+                position: q.position,
+            })
+            .collect())
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "Question")]
+pub struct QuestionNode {
+    pub id: i32,
+    pub question_text: String,
+    // A, B, C, D sırasıyla
+    pub options: Vec<String>,
+    pub correct_option: String,
+    pub points: Option<i32>,
+    pub time_limit: Option<i32>,
+    pub position: i32,
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex, name = "Game")]
+pub struct GameNode {
+    pub id: i32,
+    pub code: String,
+    pub status: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub question_count: i64,
+    pub max_possible_score: i64,
+    #[graphql(skip)]
+    pub question_set_id: i32,
+}
+
+#[ComplexObject]
+impl GameNode {
+    // Oyunda oynanan soru seti (oyunu görebilen, setin kendisine erişimi olmasa da başlığını görebilir)
+    async fn question_set(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<QuestionSetNode>> {
+        let (pool, _, _) = current_user(ctx);
+        sqlx::query_as!(
+            QuestionSetNode,
+            r#"
+            SELECT id, title, description, visibility, language, created_at, updated_at
+            FROM question_sets
+            WHERE id = $1
+            "#,
+            self.question_set_id
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(db_error)
+    }
+
+    // Oyuncular (ani ölüm sırasına, sonra puana göre)
+    async fn players(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<PlayerNode>> {
+        let (pool, _, _) = current_user(ctx);
+        let players = sqlx::query!(
+            r#"
+            SELECT p.id, p.nickname, p.score, p.user_id,
+                   COUNT(pa.id) as "answers!",
+                   COUNT(pa.id) FILTER (WHERE pa.is_correct) as "correct!",
+                   AVG(pa.response_time_ms)::float8 as avg_response_time
+            FROM players p
+            LEFT JOIN player_answers pa ON pa.player_id = p.id
+            WHERE p.game_id = $1 AND p.is_active = true
+            GROUP BY p.id
+            ORDER BY p.sudden_death_place ASC NULLS LAST, p.score DESC NULLS LAST, p.nickname
+            "#,
+            self.id
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(players
+            .into_iter()
+            .map(|p| {
+                let score = p.score.unwrap_or(0);
+                PlayerNode {
+                    id: p.id,
+                    nickname: p.nickname,
+                    is_guest: p.user_id.is_none(),
+                    score,
+                    answers: p.answers,
+                    correct: p.correct,
+                    accuracy: if p.answers > 0 { (p.correct as f64 / p.answers as f64 * 100.0).round() } else { 0.0 },
+                    avg_response_time_ms: p.avg_response_time.map(|t| t.round() as i64),
+                    percent_of_max: game_service::percent_of_max(score as i64, self.max_possible_score),
+                }
+            })
+            .collect())
+    }
+
+    // Soru bazında cevap istatistikleri ve süre dağılımları
+    async fn questions(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<QuestionStatsNode>> {
+        let (pool, _, _) = current_user(ctx);
+        let questions = sqlx::query!(
+            r#"
+            SELECT q.id, gp.position as "position!",
+                   COUNT(pa.id) as "answer_count!",
+                   COUNT(pa.id) FILTER (WHERE pa.is_correct) as "correct_count!",
+                   AVG(pa.response_time_ms)::float8 as avg_response_time
+            FROM game_playlist($1) gp
+            JOIN questions q ON q.id = gp.question_id
+            LEFT JOIN player_answers pa ON pa.question_id = q.id
+                AND pa.player_id IN (SELECT id FROM players WHERE game_id = $1)
+            GROUP BY q.id, gp.position
+            ORDER BY gp.position
+            "#,
+            self.id
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(db_error)?;
+
+        let mut timings = answer_timing::game_question_timings(pool, self.id).await.map_err(db_error)?;
+
+        Ok(questions
+            .into_iter()
+            .map(|q| {
+                let timing = timings.remove(&q.id).unwrap_or_default();
+                QuestionStatsNode {
+                    question_id: q.id,
+                    position: q.position,
+                    answer_count: q.answer_count,
+                    correct_count: q.correct_count,
+                    accuracy: if q.answer_count > 0 {
+                        (q.correct_count as f64 / q.answer_count as f64 * 100.0).round()
+                    } else {
+                        0.0
+                    },
+                    avg_response_time_ms: q.avg_response_time.map(|t| t.round() as i64),
+                    median_response_time_ms: timing.median_response_time_ms,
+                    time_to_first_answer_ms: timing.time_to_first_answer_ms,
+                    timed_out_count: timing.timed_out,
+                    response_time_histogram: timing.histogram.into_iter().map(ResponseTimeBucketNode::from).collect(),
+                }
+            })
+            .collect())
+    }
+
+    // Ortak yanlış şıklar ve oyuncu örüntüleri (REST istatistik yanıtıyla aynı yapı)
+    async fn misconceptions(&self, ctx: &Context<'_>) -> async_graphql::Result<Json<MisconceptionReport>> {
+        let (pool, _, _) = current_user(ctx);
+        misconceptions::game_report(pool, self.id).await.map(Json).map_err(db_error)
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "Player")]
+pub struct PlayerNode {
+    pub id: i32,
+    pub nickname: String,
+    pub is_guest: bool,
+    pub score: i32,
+    pub answers: i64,
+    pub correct: i64,
+    pub accuracy: f64,
+    pub avg_response_time_ms: Option<i64>,
+    pub percent_of_max: f64,
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "QuestionStats")]
+pub struct QuestionStatsNode {
+    pub question_id: i32,
+    pub position: i32,
+    pub answer_count: i64,
+    pub correct_count: i64,
+    pub accuracy: f64,
+    pub avg_response_time_ms: Option<i64>,
+    pub median_response_time_ms: Option<i64>,
+    pub time_to_first_answer_ms: Option<i64>,
+    pub timed_out_count: i64,
+    pub response_time_histogram: Vec<ResponseTimeBucketNode>,
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "ResponseTimeBucket")]
+pub struct ResponseTimeBucketNode {
+    pub from_ms: i64,
+    pub to_ms: i64,
+    pub count: i64,
+}
+
+impl From<ResponseTimeBucket> for ResponseTimeBucketNode {
+    fn from(bucket: ResponseTimeBucket) -> Self {
+        ResponseTimeBucketNode {
+            from_ms: bucket.from_ms,
+            to_ms: bucket.to_ms,
+            count: bucket.count,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "DailyStats")]
+pub struct DailyStatsNode {
+    pub date: NaiveDate,
+    pub games_played: i64,
+    pub new_users: i64,
+    pub answers_submitted: i64,
+}
+
+impl From<DailyStats> for DailyStatsNode {
+    fn from(stats: DailyStats) -> Self {
+        DailyStatsNode {
+            date: stats.date,
+            games_played: stats.games_played,
+            new_users: stats.new_users,
+            answers_submitted: stats.answers_submitted,
+        }
+    }
+}
+
+pub struct QueryRoot;
+
+#[Object(name = "Query")]
+impl QueryRoot {
+    // Oturum açmış kullanıcı
+    async fn me(&self, ctx: &Context<'_>) -> UserNode {
+        let (_, claims, user_id) = current_user(ctx);
+        UserNode {
+            id: user_id,
+            username: claims.username.clone(),
+            role: claims.role.clone(),
+        }
+    }
+
+    // Kullanıcının kendi soru setleri (çöp kutusundakiler hariç)
+    async fn question_sets(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<QuestionSetNode>> {
+        let (pool, _, user_id) = current_user(ctx);
+        sqlx::query_as!(
+            QuestionSetNode,
+            r#"
+            SELECT id, title, description, visibility, language, created_at, updated_at
+            FROM question_sets
+            WHERE creator_id = $1 AND deleted_at IS NULL
+            ORDER BY updated_at DESC
+            "#,
+            user_id
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(db_error)
+    }
+
+    // Erişim izni olan bir soru seti (sahip, ortak editör veya paylaşım)
+    async fn question_set(&self, ctx: &Context<'_>, id: i32) -> async_graphql::Result<Option<QuestionSetNode>> {
+        let (pool, claims, user_id) = current_user(ctx);
+        let set = sqlx::query!(
+            r#"
+            SELECT id, creator_id, title, description, visibility, language, created_at, updated_at
+            FROM question_sets
+            WHERE id = $1 AND deleted_at IS NULL
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(db_error)?;
+
+        let Some(set) = set else {
+            return Ok(None);
+        };
+
+        let access = collaboration::access_level(pool, user_id, set.id, set.creator_id, &set.visibility).await;
+        if claims.role != "admin" && access == SetAccess::None {
+            return Err(async_graphql::Error::new("Bu soru setine erişim izniniz yok"));
+        }
+
+        Ok(Some(QuestionSetNode {
+            id: set.id,
+            title: set.title,
+            description: set.description,
+            visibility: set.visibility,
+            language: set.language,
+            created_at: set.created_at,
+            updated_at: set.updated_at,
+        }))
+    }
+
+    // Kullanıcının açtığı son oyunlar; karmaşıklık istenen oyun sayısıyla çarpılır
+    #[graphql(complexity = "limit.clamp(1, MAX_GAMES_LIMIT) as usize * child_complexity")]
+    async fn games(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 20)] limit: i64,
+    ) -> async_graphql::Result<Vec<GameNode>> {
+        let (pool, _, user_id) = current_user(ctx);
+        let games = sqlx::query!(
+            r#"
+            SELECT g.id, g.code, g.status, g.created_at, g.started_at, g.ended_at, g.question_set_id,
+                   (SELECT COUNT(*) FROM game_question_ids(g.id)) as "question_count!"
+            FROM games g
+            WHERE g.host_id = $1
+            ORDER BY g.created_at DESC
+            LIMIT $2
+            "#,
+            user_id,
+            limit.clamp(1, MAX_GAMES_LIMIT)
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(games
+            .into_iter()
+            .map(|g| GameNode {
+                id: g.id,
+                code: g.code,
+                status: g.status,
+                created_at: g.created_at,
+                started_at: g.started_at,
+                ended_at: g.ended_at,
+                max_possible_score: game_service::max_possible_score(g.question_count),
+                question_count: g.question_count,
+                question_set_id: g.question_set_id,
+            })
+            .collect())
+    }
+
+    // Oyun ve istatistikleri (yalnızca oyun sahibi veya admin)
+    async fn game(&self, ctx: &Context<'_>, code: String) -> async_graphql::Result<Option<GameNode>> {
+        let (pool, claims, user_id) = current_user(ctx);
+        let game = sqlx::query!(
+            r#"
+            SELECT g.id, g.code, g.host_id, g.status, g.created_at, g.started_at, g.ended_at, g.question_set_id,
+                   (SELECT COUNT(*) FROM game_question_ids(g.id)) as "question_count!"
+            FROM games g
+            WHERE g.code = $1
+            "#,
+            code.to_uppercase()
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(db_error)?;
+
+        let Some(game) = game else {
+            return Ok(None);
+        };
+
+        if game.host_id != user_id && claims.role != "admin" {
+            return Err(async_graphql::Error::new("Bu oyunun istatistiklerini görüntüleme izniniz yok"));
+        }
+
+        Ok(Some(GameNode {
+            id: game.id,
+            code: game.code,
+            status: game.status,
+            created_at: game.created_at,
+            started_at: game.started_at,
+            ended_at: game.ended_at,
+            max_possible_score: game_service::max_possible_score(game.question_count),
+            question_count: game.question_count,
+            question_set_id: game.question_set_id,
+        }))
+    }
+
+    // Günlük platform istatistikleri (adminler; kurum adminleri yalnızca kendi kurumu)
+    async fn daily_stats(
+        &self,
+        ctx: &Context<'_>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> async_graphql::Result<Vec<DailyStatsNode>> {
+        let (pool, claims, _) = current_user(ctx);
+        let scope = organization::admin_scope(claims)
+            .ok_or_else(|| async_graphql::Error::new("Bu işlem için admin yetkisi gerekiyor"))?;
+        let (from, to) = platform_stats::resolve_range(from, to).map_err(async_graphql::Error::new)?;
+
+        let series = platform_stats::timeseries(pool, scope.organization_id(), from, to)
+            .await
+            .map_err(db_error)?;
+        Ok(series.into_iter().map(DailyStatsNode::from).collect())
+    }
+}
+
+// GraphQL sorgusunu çalıştır (POST /api/graphql, gövde: {"query": "...", "variables": {...}})
+pub async fn graphql(
+    pool: web::Data<Pool<Postgres>>,
+    request: web::Json<async_graphql::Request>,
+    claims: Claims,
+) -> impl Responder {
+    let request = request
+        .into_inner()
+        .data(pool.get_ref().clone())
+        .data(claims);
+
+    HttpResponse::Ok().json(SCHEMA.execute(request).await)
+}
\ No newline at end of file
@@ -0,0 +1,228 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::{Duration, Utc};
+use log::{error, info};
+use sqlx::{Pool, Postgres};
+
+use crate::config::CONFIG;
+use crate::db::models::{Claims, CreateInvitationDto};
+use crate::services::invitation;
+use crate::utils::security::generate_invite_code;
+
+// Öğrenci davet kodu oluştur
+pub async fn create_invitation(
+    pool: web::Data<Pool<Postgres>>,
+    invitation_dto: web::Json<CreateInvitationDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    if claims.role != "teacher" && claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Sadece öğretmenler davet oluşturabilir"
+        }));
+    }
+    
+    if matches!(invitation_dto.max_uses, Some(n) if n < 1) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Kullanım sayısı en az 1 olmalıdır"
+        }));
+    }
+    
+    if matches!(invitation_dto.expires_in_hours, Some(h) if !(1..=24 * 90).contains(&h)) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Geçerlilik süresi 1 saat ile 90 gün arasında olmalıdır"
+        }));
+    }
+    
+    // Davet bir sınıfa bağlanacaksa sınıf öğretmene ait olmalı
+    if let Some(class_id) = invitation_dto.class_id {
+        let class = sqlx::query!(
+            "SELECT teacher_id FROM classes WHERE id = $1",
+            class_id
+        )
+        .fetch_optional(&**pool)
+        .await;
+        
+        match class {
+            Ok(Some(class)) if class.teacher_id == user_id || claims.role == "admin" => {}
+            Ok(_) => {
+                return HttpResponse::NotFound().json(serde_json::json!({
+                    "error": "Sınıf bulunamadı"
+                }));
+            }
+            Err(e) => {
+                error!("Veritabanı sorgu hatası: {}", e);
+                return HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Davet oluşturulamadı"
+                }));
+            }
+        }
+    }
+    
+    let code = generate_invite_code();
+    let expires_at = invitation_dto.expires_in_hours.map(|h| Utc::now() + Duration::hours(h));
+    
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO invitations (code, teacher_id, class_id, max_uses, expires_at, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, created_at
+        "#,
+        code,
+        user_id,
+        invitation_dto.class_id,
+        invitation_dto.max_uses,
+        expires_at,
+        Utc::now()
+    )
+    .fetch_one(&**pool)
+    .await;
+    
+    match result {
+        Ok(record) => {
+            info!("Davet kodu oluşturuldu: {} (teacher_id: {})", code, user_id);
+            HttpResponse::Created().json(serde_json::json!({
+                "id": record.id,
+                "code": code,
+                "link": format!("{}/register?invite={}", CONFIG.frontend_url, code),
+                "class_id": invitation_dto.class_id,
+                "max_uses": invitation_dto.max_uses,
+                "expires_at": expires_at,
+                "created_at": record.created_at
+            }))
+        }
+        Err(e) => {
+            error!("Davet oluşturulurken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Davet oluşturulamadı"
+            }))
+        }
+    }
+}
+
+// Öğretmenin davetlerini ve davetle kayıt olan öğrencileri listele
+pub async fn list_invitations(
+    pool: web::Data<Pool<Postgres>>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let invitations = sqlx::query!(
+        r#"
+        SELECT i.id, i.code, i.class_id, c.name as "class_name?", i.max_uses, i.use_count,
+               i.expires_at, i.revoked_at, i.created_at,
+               COALESCE(
+                   (SELECT json_agg(json_build_object('id', u.id, 'username', u.username, 'created_at', u.created_at))
+                    FROM users u WHERE u.invitation_id = i.id),
+                   '[]'::json
+               ) as "students!"
+        FROM invitations i
+        LEFT JOIN classes c ON i.class_id = c.id
+        WHERE i.teacher_id = $1
+        ORDER BY i.created_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    match invitations {
+        Ok(invitations) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "invitations": invitations.iter().map(|i| {
+                    serde_json::json!({
+                        "id": i.id,
+                        "code": i.code,
+                        "link": format!("{}/register?invite={}", CONFIG.frontend_url, i.code),
+                        "class_id": i.class_id,
+                        "class_name": i.class_name,
+                        "max_uses": i.max_uses,
+                        "use_count": i.use_count,
+                        "expires_at": i.expires_at,
+                        "revoked": i.revoked_at.is_some(),
+                        "created_at": i.created_at,
+                        "students": i.students
+                    })
+                }).collect::<Vec<_>>()
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Davetler alınamadı"
+            }))
+        }
+    }
+}
+
+// Daveti iptal et
+pub async fn revoke_invitation(
+    pool: web::Data<Pool<Postgres>>,
+    invitation_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let invitation_id_inner = invitation_id.into_inner();
+    
+    let result = sqlx::query!(
+        r#"
+        UPDATE invitations SET revoked_at = $1
+        WHERE id = $2 AND (teacher_id = $3 OR $4) AND revoked_at IS NULL
+        "#,
+        Utc::now(),
+        invitation_id_inner,
+        user_id,
+        claims.role == "admin"
+    )
+    .execute(&**pool)
+    .await;
+    
+    match result {
+        Ok(result) if result.rows_affected() > 0 => {
+            info!("Davet iptal edildi: id={}", invitation_id_inner);
+            HttpResponse::Ok().json(serde_json::json!({
+                "message": "Davet iptal edildi"
+            }))
+        }
+        Ok(_) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Davet bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Davet iptal edilirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Davet iptal edilemedi"
+            }))
+        }
+    }
+}
+
+// Kayıt sayfası için davet bilgisi (herkese açık)
+pub async fn get_invitation_info(
+    pool: web::Data<Pool<Postgres>>,
+    code: web::Path<String>,
+) -> impl Responder {
+    let code_inner = code.into_inner();
+    
+    match invitation::find_valid(&pool, &code_inner).await {
+        Ok(Some(invitation)) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "teacher": invitation.teacher_username,
+                "class_name": invitation.class_name,
+                "role": "student"
+            }))
+        }
+        Ok(None) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Davet kodu geçersiz veya süresi dolmuş"
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Davet bilgisi alınamadı"
+            }))
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,358 @@
+use actix_web::{web, HttpResponse, Responder};
+use log::{error, info, warn};
+use sqlx::{Pool, Postgres};
+
+use crate::db::models::{
+    Claims, CreateCategoryDto, FeatureListingDto, MarketplaceQuery, MarketplaceSubmissionsQuery, ReviewListingDto,
+    SubmitToMarketplaceDto,
+};
+use crate::services::marketplace::{self, ListingFilter, MarketplaceError};
+use crate::services::notifications;
+
+// Pazaryeri hatasını HTTP yanıtına çevir
+fn error_response(e: MarketplaceError) -> HttpResponse {
+    let body = serde_json::json!({ "error": e.message() });
+    match e {
+        MarketplaceError::SetNotFound | MarketplaceError::CategoryNotFound | MarketplaceError::ListingNotFound => {
+            HttpResponse::NotFound().json(body)
+        }
+        MarketplaceError::NotPublic | MarketplaceError::InvalidSet => HttpResponse::BadRequest().json(body),
+        MarketplaceError::AlreadySubmitted | MarketplaceError::NotPending | MarketplaceError::NotApproved => {
+            HttpResponse::Conflict().json(body)
+        }
+        MarketplaceError::Database => HttpResponse::InternalServerError().json(body),
+    }
+}
+
+// Pazaryeri kategorileri
+pub async fn list_categories(pool: web::Data<Pool<Postgres>>) -> impl Responder {
+    match marketplace::categories(&pool).await {
+        Ok(categories) => HttpResponse::Ok().json(serde_json::json!({
+            "categories": categories
+        })),
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Kategoriler alınamadı"
+            }))
+        }
+    }
+}
+
+// Pazaryerindeki onaylı setleri listele (kategori, öne çıkanlar, arama ve sıralama ile)
+pub async fn browse_marketplace(
+    pool: web::Data<Pool<Postgres>>,
+    query: web::Query<MarketplaceQuery>,
+) -> impl Responder {
+    let sort = query.sort.clone().unwrap_or_else(|| "popular".to_string());
+    if !["popular", "recent", "top_rated"].contains(&sort.as_str()) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Sıralama 'popular', 'recent' veya 'top_rated' olmalıdır"
+        }));
+    }
+    
+    let filter = ListingFilter {
+        status: Some(marketplace::STATUS_APPROVED.to_string()),
+        category: query.category.clone(),
+        featured_only: query.featured.unwrap_or(false),
+        search: query
+            .search
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| format!("%{}%", s.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"))),
+        sort,
+        limit: query.limit.unwrap_or(20).clamp(1, 100),
+        offset: query.offset.unwrap_or(0).max(0),
+        ..Default::default()
+    };
+    
+    match marketplace::listings(&pool, &filter).await {
+        Ok(listings) => HttpResponse::Ok().json(serde_json::json!({
+            "listings": listings
+        })),
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Pazaryeri alınamadı"
+            }))
+        }
+    }
+}
+
+// Kendi setini pazaryerine incelemeye gönder
+pub async fn submit_to_marketplace(
+    pool: web::Data<Pool<Postgres>>,
+    submit_dto: web::Json<SubmitToMarketplaceDto>,
+    claims: Claims,
+) -> impl Responder {
+    if claims.role != "teacher" && claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Sadece öğretmenler pazaryerine soru seti gönderebilir"
+        }));
+    }
+    
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    match marketplace::submit(&pool, user_id, submit_dto.question_set_id, submit_dto.category_id).await {
+        Ok(submission) => {
+            info!(
+                "Soru seti pazaryerine gönderildi: set_id={}, listing_id={} (user_id: {})",
+                submit_dto.question_set_id, submission.listing_id, user_id
+            );
+            if submission.similarity_flagged {
+                warn!(
+                    "Pazaryeri gönderimi yayındaki bir setle yoğun örtüşüyor, incelemede işaretlendi: listing_id={}",
+                    submission.listing_id
+                );
+            }
+            HttpResponse::Created().json(serde_json::json!({
+                "id": submission.listing_id,
+                "question_set_id": submit_dto.question_set_id,
+                "status": marketplace::STATUS_PENDING
+            }))
+        }
+        Err(e) => error_response(e),
+    }
+}
+
+// Kullanıcının pazaryerine gönderdiği setler ve inceleme durumları
+pub async fn list_my_submissions(
+    pool: web::Data<Pool<Postgres>>,
+    claims: Claims,
+) -> impl Responder {
+    let filter = ListingFilter {
+        creator_id: Some(claims.sub.parse::<i32>().unwrap_or_default()),
+        sort: "recent".to_string(),
+        limit: 100,
+        ..Default::default()
+    };
+    
+    match marketplace::listings(&pool, &filter).await {
+        Ok(listings) => HttpResponse::Ok().json(serde_json::json!({
+            "submissions": listings
+        })),
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Gönderimler alınamadı"
+            }))
+        }
+    }
+}
+
+// Seti incelemeden veya pazaryerinden geri çek
+pub async fn withdraw_submission(
+    pool: web::Data<Pool<Postgres>>,
+    listing_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    match marketplace::withdraw(&pool, user_id, listing_id.into_inner()).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({
+            "status": marketplace::STATUS_WITHDRAWN
+        })),
+        Err(e) => error_response(e),
+    }
+}
+
+// Onaylı seti kendi kütüphanene kopyala
+pub async fn download_listing(
+    pool: web::Data<Pool<Postgres>>,
+    listing_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    if claims.role != "teacher" && claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Sadece öğretmenler pazaryerinden soru seti indirebilir"
+        }));
+    }
+    
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let listing_id = listing_id.into_inner();
+    
+    match marketplace::download(&pool, user_id, listing_id).await {
+        Ok(question_set_id) => {
+            info!("Pazaryerinden soru seti indirildi: listing_id={}, yeni set={} (user_id: {})", listing_id, question_set_id, user_id);
+            HttpResponse::Created().json(serde_json::json!({
+                "question_set_id": question_set_id
+            }))
+        }
+        Err(e) => error_response(e),
+    }
+}
+
+// İnceleme kuyruğu (admin); varsayılan olarak bekleyen gönderimler. Benzerlik taraması sonuçları da döner,
+// flagged=true ile yalnızca yayındaki setlerle yoğun örtüşenler listelenir.
+pub async fn list_submissions(
+    pool: web::Data<Pool<Postgres>>,
+    query: web::Query<MarketplaceSubmissionsQuery>,
+    claims: Claims,
+) -> impl Responder {
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    let status = query.status.clone().unwrap_or_else(|| marketplace::STATUS_PENDING.to_string());
+    let statuses = [
+        marketplace::STATUS_PENDING,
+        marketplace::STATUS_APPROVED,
+        marketplace::STATUS_REJECTED,
+        marketplace::STATUS_WITHDRAWN,
+    ];
+    if !statuses.contains(&status.as_str()) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Durum 'pending', 'approved', 'rejected' veya 'withdrawn' olmalıdır"
+        }));
+    }
+    
+    let filter = ListingFilter {
+        status: Some(status),
+        flagged_only: query.flagged.unwrap_or(false),
+        include_similarity: true,
+        sort: "recent".to_string(),
+        limit: 200,
+        ..Default::default()
+    };
+    
+    match marketplace::listings(&pool, &filter).await {
+        Ok(listings) => HttpResponse::Ok().json(serde_json::json!({
+            "submissions": listings
+        })),
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Gönderimler alınamadı"
+            }))
+        }
+    }
+}
+
+// Gönderimi onayla veya reddet (admin); yazar bildirimle bilgilendirilir
+pub async fn review_submission(
+    pool: web::Data<Pool<Postgres>>,
+    listing_id: web::Path<i32>,
+    review_dto: web::Json<ReviewListingDto>,
+    claims: Claims,
+) -> impl Responder {
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    let note = review_dto.note.as_deref().map(str::trim).filter(|n| !n.is_empty());
+    if !review_dto.approve && note.is_none() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Reddedilen setler için yazara gösterilecek bir gerekçe girilmelidir"
+        }));
+    }
+    
+    let admin_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let listing_id = listing_id.into_inner();
+    
+    match marketplace::review(&pool, admin_id, listing_id, review_dto.approve, note).await {
+        Ok(listing) => {
+            info!(
+                "Pazaryeri gönderimi incelendi: listing_id={}, onay={} (admin_id: {})",
+                listing_id, review_dto.approve, admin_id
+            );
+            
+            let (title, body) = if review_dto.approve {
+                ("Soru setiniz pazaryerinde", format!("\"{}\" soru seti onaylandı ve pazaryerinde yayında", listing.title))
+            } else {
+                (
+                    "Soru setiniz onaylanmadı",
+                    format!("\"{}\" soru seti pazaryerine kabul edilmedi: {}", listing.title, note.unwrap_or_default()),
+                )
+            };
+            notifications::notify(
+                &pool,
+                listing.creator_id,
+                notifications::KIND_MARKETPLACE,
+                title,
+                &body,
+                serde_json::json!({"listing_id": listing_id, "question_set_id": listing.question_set_id}),
+            )
+            .await;
+            
+            HttpResponse::Ok().json(serde_json::json!({
+                "id": listing_id,
+                "status": if review_dto.approve { marketplace::STATUS_APPROVED } else { marketplace::STATUS_REJECTED }
+            }))
+        }
+        Err(e) => error_response(e),
+    }
+}
+
+// Onaylı seti öne çıkar veya öne çıkanlardan kaldır (admin)
+pub async fn feature_listing(
+    pool: web::Data<Pool<Postgres>>,
+    listing_id: web::Path<i32>,
+    feature_dto: web::Json<FeatureListingDto>,
+    claims: Claims,
+) -> impl Responder {
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    let listing_id = listing_id.into_inner();
+    
+    match marketplace::set_featured(&pool, listing_id, feature_dto.featured).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({
+            "id": listing_id,
+            "is_featured": feature_dto.featured
+        })),
+        Err(e) => error_response(e),
+    }
+}
+
+// Yeni pazaryeri kategorisi ekle (admin)
+pub async fn create_category(
+    pool: web::Data<Pool<Postgres>>,
+    category_dto: web::Json<CreateCategoryDto>,
+    claims: Claims,
+) -> impl Responder {
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    let name = category_dto.name.trim();
+    let slug = category_dto.slug.trim().to_lowercase();
+    
+    if name.is_empty() || name.chars().count() > 255 {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Kategori adı 1-255 karakter arasında olmalıdır"
+        }));
+    }
+    
+    // Kısa ad yalnızca küçük harf, rakam ve tire içerebilir
+    if slug.is_empty()
+        || slug.len() > 100
+        || !slug.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Kategori kısa adı yalnızca küçük harf, rakam ve tire içerebilir"
+        }));
+    }
+    
+    match marketplace::create_category(&pool, &slug, name).await {
+        Ok(Some(category)) => HttpResponse::Created().json(category),
+        Ok(None) => HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Bu kısa ad ile bir kategori zaten var"
+        })),
+        Err(e) => {
+            error!("Kategori oluşturulurken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Kategori oluşturulamadı"
+            }))
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,114 @@
+use actix_web::{http::header, web, HttpResponse, Responder};
+use log::{error, info};
+use sqlx::{Pool, Postgres};
+
+use crate::db::models::Claims;
+use crate::services::collaboration::{self, SetAccess};
+use crate::services::tts::PreparationSummary;
+use crate::services::{media, quota, tts};
+
+// Medya deposundaki dosyayı sun; dosya adları içerikten türetildiği için süresiz önbelleğe alınabilir
+pub async fn serve_media(name: web::Path<String>) -> impl Responder {
+    let name = name.into_inner();
+    let content_type = name
+        .rsplit_once('.')
+        .and_then(|(_, extension)| media::content_type(extension));
+    
+    let Some(content_type) = content_type.filter(|_| media::is_valid_name(&name)) else {
+        return HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Dosya bulunamadı"
+        }));
+    };
+    
+    match media::read(&name).await {
+        Ok(data) => HttpResponse::Ok()
+            .content_type(content_type)
+            .insert_header((header::CACHE_CONTROL, "public, max-age=31536000, immutable"))
+            .body(data),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Dosya bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Medya dosyası okunamadı ({}): {}", name, e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Dosya okunamadı"
+            }))
+        }
+    }
+}
+
+// Soru setinin sesli okuma kayıtlarını önceden üret (soru setini düzenleyebilenler)
+pub async fn prepare_question_set_audio(
+    pool: web::Data<Pool<Postgres>>,
+    set_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    if !tts::is_enabled() {
+        return HttpResponse::ServiceUnavailable().json(serde_json::json!({
+            "error": "Sesli okuma servisi yapılandırılmamış"
+        }));
+    }
+    
+    let set = sqlx::query!(
+        "SELECT id, creator_id, visibility FROM question_sets WHERE id = $1 AND deleted_at IS NULL",
+        set_id.into_inner()
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    let set = match set {
+        Ok(Some(set)) => set,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Soru seti bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Sesler hazırlanamadı"
+            }));
+        }
+    };
+    
+    let access = collaboration::access_level(&pool, user_id, set.id, set.creator_id, &set.visibility).await;
+    if claims.role != "admin" && access < SetAccess::Write {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu soru setini düzenleme izniniz yok"
+        }));
+    }
+    
+    // Seslendirme ve depolama isteyen öğretmenin kotalarından düşülür (adminler muaf)
+    let charged_to = (claims.role != "admin").then_some(user_id);
+    match tts::prepare_question_set(&pool, set.id, charged_to).await {
+        Ok(PreparationSummary { quota_exceeded: Some((metric, used, limit)), generated, cached, .. }) => {
+            let mut body = quota::exceeded_body(metric, used, limit);
+            body["question_set_id"] = serde_json::json!(set.id);
+            body["generated"] = serde_json::json!(generated);
+            body["cached"] = serde_json::json!(cached);
+            HttpResponse::TooManyRequests().json(body)
+        }
+        Ok(summary) => {
+            info!(
+                "Soru seti sesleri hazırlandı: set_id={}, üretilen={}, hazır={}, hatalı={}",
+                set.id, summary.generated, summary.cached, summary.failed
+            );
+            HttpResponse::Ok().json(serde_json::json!({
+                "question_set_id": set.id,
+                "generated": summary.generated,
+                "cached": summary.cached,
+                "failed": summary.failed
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Sesler hazırlanamadı"
+            }))
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,56 @@
+use actix_web::{http::header, web, App, HttpRequest, HttpResponse, HttpServer, Responder};
+use log::{error, info};
+use sqlx::{Pool, Postgres};
+
+use crate::config::CONFIG;
+use crate::db::pool::pool_stats;
+use crate::services::metrics;
+use crate::utils::security;
+
+// Prometheus metrikleri; dahili adreste sunulur ve dahili API anahtarıyla
+// (Authorization: Bearer <INTERNAL_API_TOKEN>) okunur
+pub async fn prometheus_metrics(
+    req: HttpRequest,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match token {
+        Some(token) if security::internal_token_matches(token) => HttpResponse::Ok()
+            .content_type("text/plain; version=0.0.4; charset=utf-8")
+            .body(metrics::render(&pool_stats(&pool))),
+        _ => HttpResponse::Unauthorized().json(serde_json::json!({
+            "error": "Geçersiz dahili API anahtarı"
+        })),
+    }
+}
+
+// Metrik sunucusunu başlat (METRICS_ADDR boşsa hiçbir şey yapmaz). Ana sunucudan ayrı çalışır; böylece
+// metrik uç noktası dışarıya açılmaz ve JWT doğrulamasından geçmez.
+pub async fn serve(pool: Pool<Postgres>) {
+    if CONFIG.metrics_addr.is_empty() {
+        return;
+    }
+
+    info!("Metrik sunucusu başlatılıyor: {}", CONFIG.metrics_addr);
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .route("/metrics", web::get().to(prometheus_metrics))
+    })
+    .workers(1)
+    .disable_signals()
+    .bind(&CONFIG.metrics_addr);
+
+    let result = match server {
+        Ok(server) => server.run().await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        error!("Metrik sunucusu durdu: {}", e);
+    }
+}
\ No newline at end of file
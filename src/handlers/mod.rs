@@ -1,76 +1,334 @@
 pub mod admin;
 pub mod auth;
+pub mod class;
+pub mod collaboration;
+pub mod curriculum;
 pub mod game;
+pub mod graphql;
+pub mod invitation;
+pub mod marketplace;
+pub mod media;
+pub mod metrics;
+pub mod notification;
+pub mod organization;
 pub mod player;
+pub mod profile;
+pub mod push;
 pub mod question;
+pub mod report;
+pub mod review;
+pub mod search;
+pub mod season;
+pub mod stats;
+pub mod tournament;
+pub mod translation;
+pub mod usage;
 pub mod websocket;
+pub mod widget;
+pub mod ws_codec;
+pub mod ws_outbox;
+pub mod ws_protocol;
 
 // İşleyicileri ve yolları kaydetme fonksiyonu
 use actix_web::web;
 
-// Tüm API rotalarını yapılandır
+use crate::errors;
+use crate::middleware::{BlocklistGuard, RecaptchaValidator, RequireAuth};
+
+// Kimlik doğrulama isteklerinin gövdesi küçüktür, sınır buna göre dar tutulur
+const AUTH_JSON_LIMIT: usize = 16 * 1024;
+
+// Verilen boyut sınırıyla JSON ayrıştırma ayarı; uygulama geneline main.rs'de, farklı sınır gereken
+// kapsam veya rotalara app_data ile eklenir
+pub fn json_config(limit_bytes: usize) -> web::JsonConfig {
+    web::JsonConfig::default()
+        .limit(limit_bytes)
+        .error_handler(errors::json_error_handler)
+}
+
+// Tüm API rotalarını yapılandır.
+// JwtAuth yalnızca gönderilen tokenı doğrular; token zorunluluğu burada kapsam veya rota bazında
+// RequireAuth ile belirtilir. Claims parametresi alan handler'lar RequireAuth olmasa da tokensız isteği 401
+// ile reddeder; misafirlere de açık rotalar Option<Claims> alır.
+// Bot korumalı rotalar RecaptchaValidator ile frontend'deki reCAPTCHA işlem adını belirtir; bu işlem adları
+// aynı zamanda güvenilir istemci API anahtarlarının kapsamlarıdır (bkz. services::api_keys).
+// Giriş/kayıt ve oyuna katılma rotaları BlocklistGuard ile admin engel listesine karşı korunur.
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
     // Auth rotaları
     cfg.service(
         web::scope("/api/auth")
-            .route("/register", web::post().to(auth::register))
-            .route("/login", web::post().to(auth::login))
+            .app_data(json_config(AUTH_JSON_LIMIT))
+            .wrap(BlocklistGuard)
+            .route("/register", web::post().to(auth::register).wrap(RecaptchaValidator::action("register")))
+            .route("/register/invitation/{code}", web::get().to(invitation::get_invitation_info))
+            .route("/login", web::post().to(auth::login).wrap(RecaptchaValidator::action("login")))
+            .route("/password-strength", web::post().to(auth::password_strength))
             .route("/verify/{token}", web::get().to(auth::verify_email))
-            .route("/me", web::get().to(auth::get_current_user))
-            .route("/reset-password/request", web::post().to(auth::request_password_reset))
+            .route(
+                "/verify/resend",
+                web::post().to(auth::resend_verification_email).wrap(RecaptchaValidator::action("verification_resend")),
+            )
+            .route("/me", web::get().to(auth::get_current_user).wrap(RequireAuth))
+            .route("/logout-all", web::post().to(auth::logout_all).wrap(RequireAuth))
+            .route("/reset-password/request", web::post().to(auth::request_password_reset).wrap(RecaptchaValidator::action("password_reset")))
             .route("/reset-password/{token}", web::post().to(auth::reset_password)),
     );
 
     // Admin rotaları
     cfg.service(
         web::scope("/api/admin")
+            .wrap(RequireAuth)
             .route("/teachers/pending", web::get().to(admin::list_pending_teachers))
             .route("/teachers/approve", web::post().to(admin::approve_teacher))
             .route("/users", web::get().to(admin::list_all_users))
             .route("/users/{id}", web::delete().to(admin::delete_user))
-            .route("/stats", web::get().to(admin::get_system_stats)),
+            .route("/stats", web::get().to(admin::get_system_stats))
+            .route("/stats/timeseries", web::get().to(admin::get_stats_timeseries))
+            .route("/games/{code}/end", web::post().to(admin::force_end_game))
+            .route("/games/{code}", web::delete().to(admin::force_delete_game))
+            .route("/reports", web::get().to(admin::list_reports))
+            .route("/audit-log", web::get().to(admin::list_audit_log))
+            .route("/reports/{id}/resolve", web::post().to(admin::resolve_report))
+            .route("/settings/email-policy", web::get().to(admin::get_email_policy))
+            .route("/settings/email-policy", web::put().to(admin::update_email_policy))
+            .route("/warehouse-export", web::get().to(admin::get_warehouse_export))
+            .route("/warehouse-export", web::put().to(admin::update_warehouse_export))
+            .route("/warehouse-export/run", web::post().to(admin::run_warehouse_export))
+            .route("/api-keys", web::get().to(admin::list_api_keys))
+            .route("/api-keys", web::post().to(admin::create_api_key))
+            .route("/api-keys/{id}", web::delete().to(admin::revoke_api_key))
+            .route("/blocklist", web::get().to(admin::list_blocklist))
+            .route("/blocklist", web::post().to(admin::create_blocklist_entry))
+            .route("/blocklist/{id}", web::delete().to(admin::delete_blocklist_entry))
+            .route("/marketplace/submissions", web::get().to(marketplace::list_submissions))
+            .route("/marketplace/submissions/{id}/review", web::post().to(marketplace::review_submission))
+            .route("/marketplace/listings/{id}/featured", web::put().to(marketplace::feature_listing))
+            .route("/marketplace/categories", web::post().to(marketplace::create_category))
+            .route("/organizations", web::post().to(organization::create_organization))
+            .route("/organizations", web::get().to(organization::list_organizations))
+            .route("/organizations/{id}/members", web::post().to(organization::add_organization_member))
+            .route("/organizations/{id}/members/{user_id}", web::delete().to(organization::remove_organization_member))
+            .route("/organizations/{id}/domains", web::get().to(organization::list_email_domains))
+            .route("/organizations/{id}/domains", web::post().to(organization::add_email_domain))
+            .route("/organizations/{id}/domains/{domain}", web::delete().to(organization::remove_email_domain))
+            .route("/topics", web::post().to(curriculum::create_topic))
+            .route("/topics/{id}", web::put().to(curriculum::update_topic))
+            .route("/topics/{id}", web::delete().to(curriculum::delete_topic)),
+    );
+
+    // GraphQL API (panel istemcilerinin alan seçerek sorgulaması için)
+    cfg.route("/api/graphql", web::post().to(graphql::graphql).wrap(RequireAuth));
+
+    // Kurum rotası
+    cfg.route("/api/organization", web::get().to(organization::get_my_organization).wrap(RequireAuth));
+
+    // Soru seti pazaryeri rotaları
+    cfg.service(
+        web::scope("/api/marketplace")
+            .wrap(RequireAuth)
+            .route("", web::get().to(marketplace::browse_marketplace))
+            .route("/categories", web::get().to(marketplace::list_categories))
+            .route("/submissions", web::post().to(marketplace::submit_to_marketplace))
+            .route("/submissions", web::get().to(marketplace::list_my_submissions))
+            .route("/submissions/{id}", web::delete().to(marketplace::withdraw_submission))
+            .route("/{id}/download", web::post().to(marketplace::download_listing)),
+    );
+
+    // Müfredat konuları (soru etiketlemek için tüm kullanıcılar görebilir)
+    cfg.route("/api/topics", web::get().to(curriculum::list_topics).wrap(RequireAuth));
+
+    // Davet rotaları
+    cfg.service(
+        web::scope("/api/invitations")
+            .wrap(RequireAuth)
+            .route("", web::post().to(invitation::create_invitation))
+            .route("", web::get().to(invitation::list_invitations))
+            .route("/{id}", web::delete().to(invitation::revoke_invitation)),
+    );
+
+    // Sınıf rotaları
+    cfg.service(
+        web::scope("/api/classes")
+            .wrap(RequireAuth)
+            .route("", web::post().to(class::create_class))
+            .route("", web::get().to(class::list_classes))
+            .route("/{id}", web::get().to(class::get_class))
+            .route("/{id}/members", web::post().to(class::add_class_member))
+            .route("/{id}/members/{user_id}", web::delete().to(class::remove_class_member))
+            .route("/{id}/members/{user_id}/accommodations", web::put().to(class::update_member_accommodations))
+            .route("/{id}/roster", web::get().to(class::list_roster))
+            .route("/{id}/roster/import", web::post().to(class::import_roster))
+            .route("/{id}/attendance", web::get().to(class::class_attendance))
+            .route("/{id}/students/{user_id}/report", web::get().to(class::student_report))
+            .route("/{id}/coverage", web::get().to(curriculum::class_coverage)),
     );
 
     // Soru seti ve soru rotaları
     cfg.service(
         web::scope("/api/question-sets")
+            .wrap(RequireAuth)
             .route("", web::post().to(question::create_question_set))
             .route("", web::get().to(question::get_question_sets))
+            .route("/shared", web::get().to(question::get_shared_question_sets))
+            .route("/public", web::get().to(question::get_public_question_sets))
+            .route("/trash", web::get().to(question::get_trashed_question_sets))
             .route("/{id}", web::get().to(question::get_question_set))
-            .route("/{id}", web::delete().to(question::delete_question_set)),
+            .route("/{id}", web::delete().to(question::delete_question_set))
+            .route("/{id}/questions", web::get().to(question::list_question_set_questions))
+            .route("/{id}/questions/bulk", web::post().to(question::bulk_question_operations))
+            .route("/{id}/visibility", web::put().to(question::update_question_set_visibility))
+            .route("/{id}/language", web::put().to(question::update_question_set_language))
+            .route("/{id}/archive", web::post().to(question::archive_question_set))
+            .route("/{id}/unarchive", web::post().to(question::unarchive_question_set))
+            .route("/{id}/restore", web::post().to(question::restore_question_set))
+            .route("/{id}/validate", web::get().to(question::validate_question_set))
+            .route("/{id}/coverage", web::get().to(curriculum::question_set_coverage))
+            .route("/{id}/audio", web::post().to(media::prepare_question_set_audio))
+            .route("/{id}/preview", web::post().to(question::start_preview))
+            .route("/{id}/preview/{preview_id}/answer", web::post().to(question::answer_preview))
+            .route("/{id}/editors", web::get().to(collaboration::list_editors))
+            .route("/{id}/editors", web::post().to(collaboration::add_editor))
+            .route("/{id}/editors/{user_id}", web::delete().to(collaboration::remove_editor))
+            .route("/{id}/activity", web::get().to(collaboration::get_activity))
+            .route("/{id}/rating", web::put().to(review::rate_question_set))
+            .route("/{id}/comments", web::get().to(review::list_comments))
+            .route("/{id}/comments", web::post().to(review::create_comment))
+            .route("/{id}/comments/{comment_id}", web::delete().to(review::delete_comment))
+            .route("/{id}/comments/{comment_id}/hidden", web::put().to(review::hide_comment)),
     );
 
     cfg.service(
         web::scope("/api/questions")
+            .wrap(RequireAuth)
             .route("", web::post().to(question::create_question))
             .route("/{id}", web::put().to(question::update_question))
-            .route("/{id}", web::delete().to(question::delete_question)),
+            .route("/{id}", web::patch().to(question::patch_question))
+            .route("/{id}", web::delete().to(question::delete_question))
+            .route("/{id}/restore", web::post().to(question::restore_question))
+            .route("/{id}/stats", web::get().to(question::get_question_stats))
+            .route("/{id}/topics", web::put().to(curriculum::set_question_topics))
+            .route("/{id}/translations", web::get().to(translation::list_translations))
+            .route("/{id}/translations/{locale}", web::put().to(translation::upsert_translation))
+            .route("/{id}/translations/{locale}", web::delete().to(translation::delete_translation)),
     );
 
     // Oyun rotaları
     cfg.service(
         web::scope("/api/game")
-            .route("", web::post().to(game::create_game))
-            .route("/join", web::post().to(game::join_game))
-            .route("/{code}", web::get().to(game::get_game))
-            .route("/{code}/start", web::post().to(game::start_game))
-            .route("/{code}/next", web::post().to(game::next_question))
-            .route("/{code}/leaderboard", web::get().to(game::get_leaderboard))
-            .route("/{code}/statistics", web::get().to(game::get_game_statistics))  // Yeni eklenen rota
-            .route("/answer", web::post().to(game::submit_answer_with_header)),
+            .route("", web::post().to(game::create_game).wrap(RequireAuth))
+            .route("", web::get().to(game::list_host_games).wrap(RequireAuth))
+            .route(
+                "/join",
+                // Misafirler de katılabilir, token varsa kullanıcıya bağlanır; bot koruması yalnızca misafirler için
+                web::post()
+                    .to(game::join_game)
+                    .wrap(RecaptchaValidator::action("guest_join").guests_only())
+                    .wrap(BlocklistGuard),
+            )
+            .route("/{code}", web::get().to(game::get_game).wrap(RequireAuth))
+            .route("/{code}/start", web::post().to(game::start_game).wrap(RequireAuth))
+            .route("/{code}/next", web::post().to(game::next_question).wrap(RequireAuth))
+            .route("/{code}/sudden-death", web::post().to(game::start_sudden_death).wrap(RequireAuth))
+            .route("/{code}/leaderboard", web::get().to(game::get_leaderboard).wrap(RequireAuth))
+            .route("/{code}/statistics", web::get().to(game::get_game_statistics).wrap(RequireAuth))  // Yeni eklenen rota
+            .route("/{code}/grades", web::get().to(game::get_game_grades).wrap(RequireAuth))
+            .route("/{code}/replay", web::get().to(game::get_game_replay).wrap(RequireAuth))
+            .route("/{code}/settings", web::put().to(game::update_game_settings).wrap(RequireAuth))
+            .route("/{code}/archive", web::post().to(game::archive_game).wrap(RequireAuth))
+            .route("/{code}/unarchive", web::post().to(game::unarchive_game).wrap(RequireAuth))
+            .route("/{code}/alias", web::put().to(game::set_game_alias).wrap(RequireAuth))
+            .route("/{code}/alias", web::delete().to(game::remove_game_alias).wrap(RequireAuth))
+            .route("/{code}/schedule", web::put().to(game::schedule_game).wrap(RequireAuth))
+            .route("/{code}/challenge", web::post().to(game::challenge_player).wrap(RequireAuth))
+            .route("/{code}/join-links", web::post().to(game::create_join_links).wrap(RequireAuth))
+            .route("/{code}/widget", web::post().to(widget::create_widget_token).wrap(RequireAuth))
+            .route("/{code}/widget", web::delete().to(widget::revoke_widget_token).wrap(RequireAuth))
+            .route("/answer", web::post().to(game::submit_answer_with_header)), // Misafirler player-token header ile cevaplar
     );
     
     // Oyuncu rotaları
     cfg.service(
         web::scope("/api/player")
+            .wrap(RequireAuth)
             .route("/{id}", web::get().to(player::get_player_info))
             .route("/{id}/stats", web::get().to(player::get_player_stats))
             .route("/history", web::get().to(player::get_user_game_history))
+            .route("/claim", web::post().to(player::claim_guest_results))
             .route("/{id}/leave", web::post().to(player::leave_game)),
     );
 
+    // Profil ve avatar rotaları
+    cfg.route("/api/avatars", web::get().to(profile::list_avatars));
+    cfg.service(
+        web::scope("/api/profile")
+            .wrap(RequireAuth)
+            .route("", web::get().to(profile::get_profile))
+            .route("/avatar", web::put().to(profile::update_avatar))
+            .route("/badges", web::get().to(profile::get_badges)),
+    );
+
+    // Arama rotası
+    cfg.route("/api/search", web::get().to(search::search).wrap(RequireAuth));
+
+    // Sezon rotaları
+    cfg.service(
+        web::scope("/api/seasons")
+            .wrap(RequireAuth)
+            .route("", web::get().to(season::list_seasons))
+            .route("/{season}/leaderboard", web::get().to(season::get_season_leaderboard)),
+    );
+
+    // Turnuva rotaları
+    cfg.service(
+        web::scope("/api/tournaments")
+            .wrap(RequireAuth)
+            .route("", web::post().to(tournament::create_tournament))
+            .route("", web::get().to(tournament::list_tournaments))
+            .route("/{id}", web::get().to(tournament::get_tournament))
+            .route("/{id}/rounds", web::post().to(tournament::add_tournament_round))
+            .route("/{id}/finish", web::post().to(tournament::finish_tournament))
+            .route("/{id}/leaderboard", web::get().to(tournament::get_tournament_leaderboard)),
+    );
+
+    // Uygulama içi bildirim rotaları
+    cfg.service(
+        web::scope("/api/notifications")
+            .wrap(RequireAuth)
+            .route("", web::get().to(notification::list_notifications))
+            .route("/read-all", web::put().to(notification::mark_all_notifications_read))
+            .route("/{id}/read", web::put().to(notification::mark_notification_read)),
+    );
+
+    // Push bildirim rotaları
+    cfg.service(
+        web::scope("/api/push")
+            .wrap(RequireAuth)
+            .route("/vapid-public-key", web::get().to(push::get_vapid_public_key))
+            .route("/subscriptions", web::post().to(push::register_subscription))
+            .route("/subscriptions", web::delete().to(push::remove_subscription)),
+    );
+
+    // Canlı site istatistikleri (herkese açık)
+    cfg.route("/api/stats/live", web::get().to(stats::get_live_stats));
+
+    // Gömülebilir canlı liderlik tablosu (herkese açık, oyuna özel token ile; her kaynaktan çağrılabilir)
+    cfg.service(
+        web::scope("/api/widget")
+            .route("/{token}/leaderboard", web::get().to(widget::get_widget_leaderboard))
+            .route("/{token}/leaderboard/stream", web::get().to(widget::stream_widget_leaderboard)),
+    );
+
+    // Kota kullanım rotası
+    cfg.route("/api/usage", web::get().to(usage::get_my_usage).wrap(RequireAuth));
+
+    // İçerik şikayeti rotası
+    cfg.route("/api/reports", web::post().to(report::create_report));
+
+    // Üretilen medya dosyaları (sesli okuma); adresler oyunculara gönderildiği için herkese açıktır
+    cfg.route("/media/{name}", web::get().to(media::serve_media));
+
     // WebSocket rotası
-    cfg.route("/ws", web::get().to(websocket::ws_handler));
+    cfg.route("/ws", web::get().to(websocket::ws_handler).wrap(BlocklistGuard));
     
     // Sağlık kontrolü
     cfg.route("/health", web::get().to(|| async { "Health check OK" }));
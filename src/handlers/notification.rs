@@ -0,0 +1,141 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use log::error;
+use sqlx::{Pool, Postgres};
+
+use crate::db::models::{Claims, NotificationListQuery};
+
+// Kullanıcının bildirimlerini listele (en yeniden eskiye)
+pub async fn list_notifications(
+    pool: web::Data<Pool<Postgres>>,
+    query: web::Query<NotificationListQuery>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let unread_only = query.unread_only.unwrap_or(false);
+    let limit = query.limit.unwrap_or(50).clamp(1, 100);
+    
+    let notifications = sqlx::query!(
+        r#"
+        SELECT id, kind, title, body, data, read_at, created_at
+        FROM notifications
+        WHERE user_id = $1
+          AND ($2 = false OR read_at IS NULL)
+          AND ($3::bigint IS NULL OR id < $3)
+        ORDER BY id DESC
+        LIMIT $4
+        "#,
+        user_id,
+        unread_only,
+        query.before_id,
+        limit
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    let unread_count = sqlx::query!(
+        r#"SELECT COUNT(*) as "count!" FROM notifications WHERE user_id = $1 AND read_at IS NULL"#,
+        user_id
+    )
+    .fetch_one(&**pool)
+    .await;
+    
+    match (notifications, unread_count) {
+        (Ok(notifications), Ok(unread)) => {
+            let items: Vec<serde_json::Value> = notifications
+                .into_iter()
+                .map(|n| {
+                    serde_json::json!({
+                        "id": n.id,
+                        "kind": n.kind,
+                        "title": n.title,
+                        "body": n.body,
+                        "data": n.data,
+                        "is_read": n.read_at.is_some(),
+                        "read_at": n.read_at,
+                        "created_at": n.created_at
+                    })
+                })
+                .collect();
+            
+            HttpResponse::Ok().json(serde_json::json!({
+                "notifications": items,
+                "unread_count": unread.count
+            }))
+        }
+        (Err(e), _) | (_, Err(e)) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Bildirimler getirilemedi"
+            }))
+        }
+    }
+}
+
+// Tek bir bildirimi okundu olarak işaretle
+pub async fn mark_notification_read(
+    pool: web::Data<Pool<Postgres>>,
+    notification_id: web::Path<i64>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let notification_id_inner = notification_id.into_inner();
+    
+    // Daha önce okunmuşsa ilk okunma zamanı korunur
+    let result = sqlx::query!(
+        r#"
+        UPDATE notifications SET read_at = COALESCE(read_at, $1)
+        WHERE id = $2 AND user_id = $3
+        RETURNING id, read_at
+        "#,
+        Utc::now(),
+        notification_id_inner,
+        user_id
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match result {
+        Ok(Some(notification)) => HttpResponse::Ok().json(serde_json::json!({
+            "id": notification.id,
+            "read_at": notification.read_at
+        })),
+        Ok(None) => HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Bildirim bulunamadı"
+        })),
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Bildirim güncellenemedi"
+            }))
+        }
+    }
+}
+
+// Kullanıcının tüm okunmamış bildirimlerini okundu olarak işaretle
+pub async fn mark_all_notifications_read(
+    pool: web::Data<Pool<Postgres>>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let result = sqlx::query!(
+        "UPDATE notifications SET read_at = $1 WHERE user_id = $2 AND read_at IS NULL",
+        Utc::now(),
+        user_id
+    )
+    .execute(&**pool)
+    .await;
+    
+    match result {
+        Ok(done) => HttpResponse::Ok().json(serde_json::json!({
+            "updated": done.rows_affected()
+        })),
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Bildirimler güncellenemedi"
+            }))
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,457 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use log::{error, info};
+use sqlx::{Pool, Postgres};
+
+use crate::db::models::{AddEmailDomainDto, AssignOrganizationMemberDto, Claims, CreateOrganizationDto};
+use crate::services::{email_domains, organization, token_version};
+use crate::utils::validation;
+
+// Yeni kurum (okul) oluştur - sadece sistem adminleri
+pub async fn create_organization(
+    pool: web::Data<Pool<Postgres>>,
+    org_dto: web::Json<CreateOrganizationDto>,
+    claims: Claims,
+) -> impl Responder {
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    let name = org_dto.name.trim();
+    let slug = org_dto.slug.trim().to_lowercase();
+    
+    if name.is_empty() || name.chars().count() > 255 {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Kurum adı 1-255 karakter arasında olmalıdır"
+        }));
+    }
+    
+    // Kısa ad yalnızca küçük harf, rakam ve tire içerebilir
+    if slug.is_empty()
+        || slug.len() > 100
+        || !slug.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Kurum kısa adı yalnızca küçük harf, rakam ve tire içerebilir"
+        }));
+    }
+    
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO organizations (name, slug, created_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (slug) DO NOTHING
+        RETURNING id, created_at
+        "#,
+        name,
+        slug,
+        Utc::now()
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match result {
+        Ok(Some(org)) => {
+            info!("Kurum oluşturuldu: {} ({})", name, slug);
+            HttpResponse::Created().json(serde_json::json!({
+                "id": org.id,
+                "name": name,
+                "slug": slug,
+                "created_at": org.created_at
+            }))
+        }
+        Ok(None) => {
+            HttpResponse::Conflict().json(serde_json::json!({
+                "error": "Bu kısa ad ile bir kurum zaten var"
+            }))
+        }
+        Err(e) => {
+            error!("Kurum oluşturulurken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Kurum oluşturulamadı"
+            }))
+        }
+    }
+}
+
+// Tüm kurumları listele - sadece sistem adminleri
+pub async fn list_organizations(
+    pool: web::Data<Pool<Postgres>>,
+    claims: Claims,
+) -> impl Responder {
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    let organizations = sqlx::query!(
+        r#"
+        SELECT o.id, o.name, o.slug, o.created_at,
+               COUNT(u.id) FILTER (WHERE u.role = 'teacher') as "teacher_count!",
+               COUNT(u.id) FILTER (WHERE u.role = 'student') as "student_count!",
+               COUNT(u.id) FILTER (WHERE u.is_org_admin) as "org_admin_count!"
+        FROM organizations o
+        LEFT JOIN users u ON u.organization_id = o.id
+        GROUP BY o.id
+        ORDER BY o.name
+        "#
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    match organizations {
+        Ok(organizations) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "organizations": organizations.iter().map(|o| {
+                    serde_json::json!({
+                        "id": o.id,
+                        "name": o.name,
+                        "slug": o.slug,
+                        "created_at": o.created_at,
+                        "teacher_count": o.teacher_count,
+                        "student_count": o.student_count,
+                        "org_admin_count": o.org_admin_count
+                    })
+                }).collect::<Vec<_>>()
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Kurum listesi alınamadı"
+            }))
+        }
+    }
+}
+
+// Kullanıcıyı kuruma ata (isteğe bağlı olarak kurum admini yap) - sadece sistem adminleri
+pub async fn add_organization_member(
+    pool: web::Data<Pool<Postgres>>,
+    org_id: web::Path<i32>,
+    member_dto: web::Json<AssignOrganizationMemberDto>,
+    claims: Claims,
+) -> impl Responder {
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlem için admin yetkisi gerekiyor"
+        }));
+    }
+    
+    let org_id_inner = org_id.into_inner();
+    let is_org_admin = member_dto.is_org_admin.unwrap_or(false);
+    
+    let organization = sqlx::query!(
+        "SELECT id, name FROM organizations WHERE id = $1",
+        org_id_inner
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    let organization = match organization {
+        Ok(Some(org)) => org,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Kurum bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Kullanıcı kuruma eklenemedi"
+            }));
+        }
+    };
+    
+    // Kurum adminliği yalnızca öğretmenlere verilebilir
+    let result = sqlx::query!(
+        r#"
+        UPDATE users SET organization_id = $1, is_org_admin = ($2 AND role = 'teacher'),
+                         token_version = token_version + 1
+        WHERE id = $3 AND role <> 'admin'
+        RETURNING username, role, is_org_admin
+        "#,
+        organization.id,
+        is_org_admin,
+        member_dto.user_id
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match result {
+        Ok(Some(user)) => {
+            // Kurum bilgisi tokenda taşındığı için kullanıcı yeniden giriş yapmalı
+            token_version::invalidate(member_dto.user_id);
+            info!(
+                "Kullanıcı kuruma eklendi: {} -> {} (org_admin: {})",
+                user.username, organization.name, user.is_org_admin
+            );
+            HttpResponse::Ok().json(serde_json::json!({
+                "message": format!("{} kullanıcısı {} kurumuna eklendi", user.username, organization.name),
+                "user_id": member_dto.user_id,
+                "role": user.role,
+                "is_org_admin": user.is_org_admin
+            }))
+        }
+        Ok(None) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Kullanıcı bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Kullanıcı kuruma eklenirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Kullanıcı kuruma eklenemedi"
+            }))
+        }
+    }
+}
+
+// Kullanıcıyı kurumdan çıkar - sistem adminleri veya ilgili kurumun adminleri
+pub async fn remove_organization_member(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<(i32, i32)>,
+    claims: Claims,
+) -> impl Responder {
+    let (org_id, member_id) = path.into_inner();
+    
+    let scope = match organization::admin_scope(&claims) {
+        Some(scope) if scope.covers(Some(org_id)) => scope,
+        _ => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu işlem için admin yetkisi gerekiyor"
+            }));
+        }
+    };
+    
+    let result = sqlx::query!(
+        r#"
+        UPDATE users SET organization_id = NULL, is_org_admin = false, token_version = token_version + 1
+        WHERE id = $1 AND organization_id = $2
+        RETURNING username
+        "#,
+        member_id,
+        org_id
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match result {
+        Ok(Some(user)) => {
+            token_version::invalidate(member_id);
+            info!("Kullanıcı kurumdan çıkarıldı: {} (org_id: {}, scope: {:?})", user.username, org_id, scope);
+            HttpResponse::Ok().json(serde_json::json!({
+                "message": format!("{} kullanıcısı kurumdan çıkarıldı", user.username)
+            }))
+        }
+        Ok(None) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Kullanıcı bu kurumda bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Kullanıcı kurumdan çıkarılırken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Kullanıcı kurumdan çıkarılamadı"
+            }))
+        }
+    }
+}
+
+// Oturum açmış kullanıcının kurum bilgisi
+pub async fn get_my_organization(
+    pool: web::Data<Pool<Postgres>>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let organization = sqlx::query!(
+        r#"
+        SELECT o.id, o.name, o.slug, u.is_org_admin
+        FROM users u
+        JOIN organizations o ON u.organization_id = o.id
+        WHERE u.id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match organization {
+        Ok(Some(org)) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "id": org.id,
+                "name": org.name,
+                "slug": org.slug,
+                "is_org_admin": org.is_org_admin
+            }))
+        }
+        Ok(None) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Herhangi bir kuruma bağlı değilsiniz"
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Kurum bilgisi alınamadı"
+            }))
+        }
+    }
+}
+
+// Kurumun e-posta alan adı izin listesini getir - sistem adminleri veya kurum adminleri
+pub async fn list_email_domains(
+    pool: web::Data<Pool<Postgres>>,
+    org_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    let org_id_inner = org_id.into_inner();
+    
+    match organization::admin_scope(&claims) {
+        Some(scope) if scope.covers(Some(org_id_inner)) => {}
+        _ => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu işlem için admin yetkisi gerekiyor"
+            }));
+        }
+    }
+    
+    let domains = sqlx::query!(
+        "SELECT id, domain, created_at FROM organization_email_domains WHERE organization_id = $1 ORDER BY domain",
+        org_id_inner
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    match domains {
+        Ok(domains) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "domains": domains.iter().map(|d| {
+                    serde_json::json!({
+                        "id": d.id,
+                        "domain": d.domain,
+                        "created_at": d.created_at
+                    })
+                }).collect::<Vec<_>>()
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Alan adı listesi alınamadı"
+            }))
+        }
+    }
+}
+
+// Kurumun izin listesine alan adı ekle - sistem adminleri veya kurum adminleri
+pub async fn add_email_domain(
+    pool: web::Data<Pool<Postgres>>,
+    org_id: web::Path<i32>,
+    domain_dto: web::Json<AddEmailDomainDto>,
+    claims: Claims,
+) -> impl Responder {
+    let org_id_inner = org_id.into_inner();
+    
+    match organization::admin_scope(&claims) {
+        Some(scope) if scope.covers(Some(org_id_inner)) => {}
+        _ => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu işlem için admin yetkisi gerekiyor"
+            }));
+        }
+    }
+    
+    let domain = domain_dto.domain.trim().trim_start_matches('@').to_lowercase();
+    if !validation::validate_domain(&domain) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Geçersiz alan adı"
+        }));
+    }
+    
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO organization_email_domains (organization_id, domain, created_at)
+        SELECT id, $2, $3 FROM organizations WHERE id = $1
+        ON CONFLICT (domain) DO NOTHING
+        RETURNING id
+        "#,
+        org_id_inner,
+        domain,
+        Utc::now()
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match result {
+        Ok(Some(record)) => {
+            email_domains::invalidate_cache();
+            info!("Kurum alan adı eklendi: {} (org_id: {})", domain, org_id_inner);
+            HttpResponse::Created().json(serde_json::json!({
+                "id": record.id,
+                "domain": domain
+            }))
+        }
+        Ok(None) => {
+            HttpResponse::Conflict().json(serde_json::json!({
+                "error": "Bu alan adı zaten kayıtlı veya kurum bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Alan adı eklenirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Alan adı eklenemedi"
+            }))
+        }
+    }
+}
+
+// Kurumun izin listesinden alan adı çıkar - sistem adminleri veya kurum adminleri
+pub async fn remove_email_domain(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<(i32, String)>,
+    claims: Claims,
+) -> impl Responder {
+    let (org_id, domain) = path.into_inner();
+    
+    match organization::admin_scope(&claims) {
+        Some(scope) if scope.covers(Some(org_id)) => {}
+        _ => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu işlem için admin yetkisi gerekiyor"
+            }));
+        }
+    }
+    
+    let result = sqlx::query!(
+        "DELETE FROM organization_email_domains WHERE organization_id = $1 AND domain = $2",
+        org_id,
+        domain.to_lowercase()
+    )
+    .execute(&**pool)
+    .await;
+    
+    match result {
+        Ok(result) if result.rows_affected() > 0 => {
+            email_domains::invalidate_cache();
+            info!("Kurum alan adı silindi: {} (org_id: {})", domain, org_id);
+            HttpResponse::Ok().json(serde_json::json!({
+                "message": format!("{} alan adı silindi", domain)
+            }))
+        }
+        Ok(_) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Alan adı bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Alan adı silinirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Alan adı silinemedi"
+            }))
+        }
+    }
+}
\ No newline at end of file
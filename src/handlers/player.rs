@@ -1,9 +1,13 @@
 use actix_web::{web, HttpResponse, Responder};
+use chrono::{Duration, Utc};
 use log::{error, info};
 use sqlx::{Pool, Postgres};
 use sqlx::types::BigDecimal;
 
-use crate::db::models::Claims;
+use crate::config::CONFIG;
+use crate::db::models::{ClaimGuestDto, Claims};
+use crate::db::repositories::{PgPlayerRepo, PlayerRepo};
+use crate::handlers::websocket::AppState;
 
 // BigDecimal değerlerini f64'e dönüştürmek için yardımcı fonksiyon
 fn bigdecimal_to_f64(value: Option<BigDecimal>) -> f64 {
@@ -17,7 +21,7 @@ fn bigdecimal_to_f64(value: Option<BigDecimal>) -> f64 {
 pub async fn get_player_info(
     pool: web::Data<Pool<Postgres>>,
     player_id: web::Path<i32>,
-    claims: web::ReqData<Claims>,
+    claims: Claims,
 ) -> impl Responder {
     let user_id = claims.sub.parse::<i32>().unwrap_or_default();
     
@@ -93,7 +97,7 @@ pub async fn get_player_info(
 pub async fn get_player_stats(
     pool: web::Data<Pool<Postgres>>,
     player_id: web::Path<i32>,
-    claims: web::ReqData<Claims>,
+    claims: Claims,
 ) -> impl Responder {
     let user_id = claims.sub.parse::<i32>().unwrap_or_default();
     
@@ -118,40 +122,13 @@ pub async fn get_player_stats(
             }
             
             // Oyuncu cevap istatistiklerini getir
-            let stats = sqlx::query!(
-                r#"
-                SELECT 
-                    COUNT(*) FILTER (WHERE is_correct = true) as "correct_count!",
-                    COUNT(*) FILTER (WHERE is_correct = false) as "incorrect_count!",
-                    ROUND(AVG(response_time_ms)) as "avg_response_time",
-                    SUM(points_earned) as "total_points",
-                    MAX(points_earned) as "max_points"
-                FROM player_answers
-                WHERE player_id = $1
-                "#,
-                player_id_inner
-            )
-            .fetch_one(&**pool)
-            .await;
+            let players = PgPlayerRepo::reader(&pool);
+            let stats = players.answer_summary(player_id_inner).await;
             
             match stats {
                 Ok(stats) => {
                     // Soru bazında istatistikler
-                    let questions = sqlx::query!(
-                        r#"
-                        SELECT 
-                            pa.question_id, q.question_text, pa.answer, pa.is_correct, 
-                            pa.response_time_ms, pa.points_earned,
-                            q.correct_option
-                        FROM player_answers pa
-                        JOIN questions q ON pa.question_id = q.id
-                        WHERE pa.player_id = $1
-                        ORDER BY pa.answered_at
-                        "#,
-                        player_id_inner
-                    )
-                    .fetch_all(&**pool)
-                    .await;
+                    let questions = players.answer_details(player_id_inner).await;
                     
                     let total_questions = stats.correct_count + stats.incorrect_count;
                     let accuracy = if total_questions > 0 {
@@ -286,31 +263,12 @@ pub async fn get_player_stats(
 // Kullanıcının oyun geçmişini getir
 pub async fn get_user_game_history(
     pool: web::Data<Pool<Postgres>>,
-    claims: web::ReqData<Claims>,
+    claims: Claims,
 ) -> impl Responder {
     let user_id = claims.sub.parse::<i32>().unwrap_or_default();
     
     // Kullanıcının oynadığı oyunların listesini getir
-    let games = sqlx::query!(
-        r#"
-        SELECT 
-            p.id as player_id, p.game_id, p.nickname, p.score, p.joined_at,
-            g.code as game_code, g.status as game_status, g.started_at, g.ended_at,
-            qs.title as question_set_title,
-            u.username as host_username,
-            (SELECT COUNT(*) FROM player_answers WHERE player_id = p.id) as answer_count,
-            (SELECT COUNT(*) FROM player_answers WHERE player_id = p.id AND is_correct = true) as correct_count
-        FROM players p
-        JOIN games g ON p.game_id = g.id
-        JOIN question_sets qs ON g.question_set_id = qs.id
-        JOIN users u ON g.host_id = u.id
-        WHERE p.user_id = $1
-        ORDER BY p.joined_at DESC
-        "#,
-        user_id
-    )
-    .fetch_all(&**pool)
-    .await;
+    let games = PgPlayerRepo::reader(&pool).game_history(user_id).await;
     
     match games {
         Ok(games) => {
@@ -388,8 +346,9 @@ pub async fn get_user_game_history(
 // Oyundan ayrıl
 pub async fn leave_game(
     pool: web::Data<Pool<Postgres>>,
+    app_state: web::Data<AppState>,
     player_id: web::Path<i32>,
-    claims: web::ReqData<Claims>,
+    claims: Claims,
 ) -> impl Responder {
     let user_id = claims.sub.parse::<i32>().unwrap_or_default();
     
@@ -398,7 +357,12 @@ pub async fn leave_game(
     
     // Oyuncu bilgilerini getir
     let player = sqlx::query!(
-        "SELECT user_id, game_id FROM players WHERE id = $1",
+        r#"
+        SELECT p.user_id, p.game_id, g.code as game_code
+        FROM players p
+        JOIN games g ON p.game_id = g.id
+        WHERE p.id = $1
+        "#,
         player_id_inner
     )
     .fetch_optional(&**pool)
@@ -414,12 +378,7 @@ pub async fn leave_game(
             }
             
             // Oyuncuyu pasif olarak işaretle
-            let result = sqlx::query!(
-                "UPDATE players SET is_active = false WHERE id = $1",
-                player_id_inner
-            )
-            .execute(&**pool)
-            .await;
+            let result = PgPlayerRepo::new(&pool).deactivate(player_id_inner).await;
             
             match result {
                 Ok(_) => {
@@ -431,6 +390,10 @@ pub async fn leave_game(
                     .execute(&**pool)
                     .await;
                     
+                    // WebSocket ile bağlı oyunculara ve host'a güncel lobiyi bildir
+                    app_state.remove_player(&player.game_code, player_id_inner).await;
+                    app_state.broadcast_lobby(player.game_id, &player.game_code).await;
+                    
                     HttpResponse::Ok().json(serde_json::json!({
                         "message": "Oyundan ayrıldınız"
                     }))
@@ -455,4 +418,101 @@ pub async fn leave_game(
             }))
         }
     }
+}
+
+// Misafir olarak oynanan oyunun sonuçlarını yeni kayıtlı hesaba aktar
+pub async fn claim_guest_results(
+    pool: web::Data<Pool<Postgres>>,
+    claim_dto: web::Json<ClaimGuestDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    // Misafir oyuncu kaydını bul
+    let player = sqlx::query!(
+        r#"
+        SELECT p.id, p.game_id, p.nickname, p.score, p.user_id, p.joined_at as "joined_at!",
+               g.code as game_code
+        FROM players p
+        JOIN games g ON p.game_id = g.id
+        WHERE p.session_id = $1
+        "#,
+        claim_dto.session_id
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    let player = match player {
+        Ok(Some(player)) => player,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Misafir oyuncu kaydı bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Sonuçlar hesaba aktarılamadı"
+            }));
+        }
+    };
+    
+    if player.user_id.is_some() {
+        return HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Bu oyuncu kaydı zaten bir hesaba bağlı"
+        }));
+    }
+    
+    // Sonuçlar yalnızca belirli bir süre içinde aktarılabilir
+    if player.joined_at < Utc::now() - Duration::hours(CONFIG.guest_claim_window_hours) {
+        return HttpResponse::Gone().json(serde_json::json!({
+            "error": format!(
+                "Misafir sonuçları yalnızca oyundan sonraki {} saat içinde aktarılabilir",
+                CONFIG.guest_claim_window_hours
+            )
+        }));
+    }
+    
+    // Kullanıcı aynı oyunda zaten kayıtlı olarak oynadıysa birleştirme yapılmaz
+    let result = sqlx::query!(
+        r#"
+        UPDATE players SET user_id = $1, claimed_at = $2
+        WHERE id = $3 AND user_id IS NULL
+          AND NOT EXISTS (SELECT 1 FROM players other WHERE other.game_id = $4 AND other.user_id = $1)
+        "#,
+        user_id,
+        Utc::now(),
+        player.id,
+        player.game_id
+    )
+    .execute(&**pool)
+    .await;
+    
+    match result {
+        Ok(result) if result.rows_affected() > 0 => {
+            info!(
+                "Misafir sonuçları hesaba aktarıldı: player_id={}, user_id={}",
+                player.id, user_id
+            );
+            HttpResponse::Ok().json(serde_json::json!({
+                "player_id": player.id,
+                "game_id": player.game_id,
+                "game_code": player.game_code,
+                "nickname": player.nickname,
+                "score": player.score,
+                "message": "Misafir oyun sonuçlarınız hesabınıza aktarıldı"
+            }))
+        }
+        Ok(_) => {
+            HttpResponse::Conflict().json(serde_json::json!({
+                "error": "Bu oyunda hesabınızla zaten bir kaydınız var"
+            }))
+        }
+        Err(e) => {
+            error!("Misafir sonuçları aktarılırken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Sonuçlar hesaba aktarılamadı"
+            }))
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,180 @@
+use actix_web::{web, HttpResponse, Responder};
+use log::{error, info};
+use sqlx::{Pool, Postgres};
+
+use crate::db::models::{Claims, UpdateAvatarDto};
+use crate::services::cosmetics::{self, AVATARS, BADGES};
+use crate::services::progression;
+
+// Oturum açmış kullanıcının profili (seviye, XP, avatar)
+pub async fn get_profile(
+    pool: web::Data<Pool<Postgres>>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let profile = sqlx::query!(
+        r#"
+        SELECT u.username, u.role, u.avatar, u.xp, u.created_at,
+               (SELECT COUNT(*) FROM players p JOIN games g ON p.game_id = g.id
+                WHERE p.user_id = u.id AND g.status = 'completed') as "games_played!",
+               (SELECT COUNT(*) FROM user_badges b WHERE b.user_id = u.id) as "badge_count!"
+        FROM users u
+        WHERE u.id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match profile {
+        Ok(Some(profile)) => {
+            let level = progression::level_for_xp(profile.xp);
+            let current_level_xp = progression::xp_for_level(level);
+            let next_level_xp = progression::xp_for_level(level + 1);
+            
+            HttpResponse::Ok().json(serde_json::json!({
+                "username": profile.username,
+                "role": profile.role,
+                "avatar": profile.avatar,
+                "created_at": profile.created_at,
+                "xp": profile.xp,
+                "level": level,
+                "level_progress": {
+                    "current_level_xp": current_level_xp,
+                    "next_level_xp": next_level_xp,
+                    "xp_into_level": profile.xp - current_level_xp,
+                    "xp_to_next_level": next_level_xp - profile.xp
+                },
+                "games_played": profile.games_played,
+                "badge_count": profile.badge_count
+            }))
+        }
+        Ok(None) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Kullanıcı bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Profil alınamadı"
+            }))
+        }
+    }
+}
+
+// Avatar kataloğu (misafirler için herkese açık, kayıtlı kullanıcılar için kilit durumuyla)
+pub async fn list_avatars(
+    pool: web::Data<Pool<Postgres>>,
+    claims: Option<Claims>,
+) -> impl Responder {
+    let earned = match claims.as_ref().map(|c| c.sub.parse::<i32>().unwrap_or_default()) {
+        Some(user_id) => match cosmetics::earned_badges(&pool, user_id).await {
+            Ok(badges) => badges,
+            Err(e) => {
+                error!("Veritabanı sorgu hatası: {}", e);
+                return HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Avatarlar alınamadı"
+                }));
+            }
+        },
+        None => Vec::new(),
+    };
+    
+    HttpResponse::Ok().json(serde_json::json!({
+        "avatars": AVATARS.iter().map(|a| {
+            serde_json::json!({
+                "id": a.id,
+                "required_badge": a.required_badge,
+                "unlocked": cosmetics::is_avatar_unlocked(a.id, &earned)
+            })
+        }).collect::<Vec<_>>()
+    }))
+}
+
+// Profil avatarını güncelle
+pub async fn update_avatar(
+    pool: web::Data<Pool<Postgres>>,
+    avatar_dto: web::Json<UpdateAvatarDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let earned = match cosmetics::earned_badges(&pool, user_id).await {
+        Ok(badges) => badges,
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Avatar güncellenemedi"
+            }));
+        }
+    };
+    
+    if !cosmetics::is_avatar_unlocked(&avatar_dto.avatar, &earned) {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu avatar henüz açılmadı"
+        }));
+    }
+    
+    let result = sqlx::query!(
+        "UPDATE users SET avatar = $1 WHERE id = $2",
+        avatar_dto.avatar,
+        user_id
+    )
+    .execute(&**pool)
+    .await;
+    
+    match result {
+        Ok(_) => {
+            info!("Avatar güncellendi: user_id={}, avatar={}", user_id, avatar_dto.avatar);
+            HttpResponse::Ok().json(serde_json::json!({
+                "avatar": avatar_dto.avatar
+            }))
+        }
+        Err(e) => {
+            error!("Avatar güncellenirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Avatar güncellenemedi"
+            }))
+        }
+    }
+}
+
+// Kullanıcının rozetleri (kazanılanlar ve kilitli olanlar)
+pub async fn get_badges(
+    pool: web::Data<Pool<Postgres>>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let earned = sqlx::query!(
+        "SELECT badge, earned_at FROM user_badges WHERE user_id = $1",
+        user_id
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    match earned {
+        Ok(earned) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "badges": BADGES.iter().map(|b| {
+                    let earned_at = earned.iter().find(|e| e.badge == b.id).map(|e| e.earned_at);
+                    serde_json::json!({
+                        "id": b.id,
+                        "name": b.name,
+                        "description": b.description,
+                        "earned": earned_at.is_some(),
+                        "earned_at": earned_at.flatten()
+                    })
+                }).collect::<Vec<_>>()
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Rozetler alınamadı"
+            }))
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,123 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use log::{error, info};
+use sqlx::{Pool, Postgres};
+
+use crate::config::CONFIG;
+use crate::db::models::{Claims, RegisterPushSubscriptionDto, RemovePushSubscriptionDto};
+use crate::utils::validation;
+
+// Web Push aboneliği için istemcinin ihtiyaç duyduğu VAPID açık anahtarı
+pub async fn get_vapid_public_key() -> impl Responder {
+    if CONFIG.vapid_public_key.is_empty() {
+        return HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Web Push bildirimleri etkin değil"
+        }));
+    }
+    
+    HttpResponse::Ok().json(serde_json::json!({
+        "public_key": CONFIG.vapid_public_key
+    }))
+}
+
+// Cihaz tokenı / Web Push aboneliği kaydet
+pub async fn register_subscription(
+    pool: web::Data<Pool<Postgres>>,
+    subscription_dto: web::Json<RegisterPushSubscriptionDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    if subscription_dto.platform != "fcm" && subscription_dto.platform != "webpush" {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Platform 'fcm' veya 'webpush' olmalıdır"
+        }));
+    }
+    
+    let token = subscription_dto.token.trim();
+    if token.is_empty() || token.len() > 2048 {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Geçersiz bildirim tokenı"
+        }));
+    }
+    
+    if subscription_dto.platform == "webpush" && !validation::validate_url(token) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Web Push uç noktası geçerli bir adres olmalıdır"
+        }));
+    }
+    
+    // Aynı token başka bir kullanıcıya kayıtlıysa yeni kullanıcıya aktarılır (cihaz el değiştirmiş olabilir)
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO push_subscriptions (user_id, platform, token, p256dh, auth, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (token) DO UPDATE SET
+            user_id = EXCLUDED.user_id,
+            platform = EXCLUDED.platform,
+            p256dh = EXCLUDED.p256dh,
+            auth = EXCLUDED.auth
+        RETURNING id
+        "#,
+        user_id,
+        subscription_dto.platform,
+        token,
+        subscription_dto.p256dh,
+        subscription_dto.auth,
+        Utc::now()
+    )
+    .fetch_one(&**pool)
+    .await;
+    
+    match result {
+        Ok(record) => {
+            info!("Push aboneliği kaydedildi: user_id={}, platform={}", user_id, subscription_dto.platform);
+            HttpResponse::Created().json(serde_json::json!({
+                "id": record.id,
+                "platform": subscription_dto.platform
+            }))
+        }
+        Err(e) => {
+            error!("Push aboneliği kaydedilirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Bildirim aboneliği kaydedilemedi"
+            }))
+        }
+    }
+}
+
+// Cihaz tokenı / Web Push aboneliğini sil (çıkış yaparken)
+pub async fn remove_subscription(
+    pool: web::Data<Pool<Postgres>>,
+    subscription_dto: web::Json<RemovePushSubscriptionDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let result = sqlx::query!(
+        "DELETE FROM push_subscriptions WHERE user_id = $1 AND token = $2",
+        user_id,
+        subscription_dto.token
+    )
+    .execute(&**pool)
+    .await;
+    
+    match result {
+        Ok(result) if result.rows_affected() > 0 => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "message": "Bildirim aboneliği silindi"
+            }))
+        }
+        Ok(_) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Bildirim aboneliği bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Push aboneliği silinirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Bildirim aboneliği silinemedi"
+            }))
+        }
+    }
+}
\ No newline at end of file
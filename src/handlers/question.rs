@@ -1,15 +1,48 @@
-use actix_web::{web, HttpResponse, Responder};
-use chrono::Utc;
+use actix_web::{http::header, web, HttpRequest, HttpResponse, Responder};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Duration, SubsecRound, Utc};
 use log::{error, info};
 use sqlx::{Pool, Postgres};
 
-use crate::db::models::{Claims, CreateQuestionDto, CreateQuestionSetDto};
+use crate::config::CONFIG;
+use crate::db::models::{
+    BulkQuestionsDto, Claims, CreateQuestionDto, CreateQuestionSetDto, IncludeArchivedQuery, PatchQuestionDto,
+    PreviewAnswerDto, PublicQuestionSetQuery, QuestionListQuery, QuestionSetQuery, UpdateLanguageDto,
+    UpdateVisibilityDto,
+};
+use crate::services::collaboration::{self, SetAccess};
+use crate::services::preview::{self, PreviewError};
+use crate::services::question_edit::{self, MAX_HOST_NOTES_LEN};
+use crate::services::set_validation::{self, QuestionInput};
+use crate::services::{localization, notifications, organization};
+use crate::utils::etag;
+
+// Kayıt, istemcinin bildiği zamandan (If-Unmodified-Since) sonra değişmiş mi; HTTP tarihi saniye hassasiyetindedir
+fn modified_after(updated_at: Option<DateTime<Utc>>, since: Option<DateTime<Utc>>) -> bool {
+    match (updated_at, since) {
+        (Some(updated_at), Some(since)) => updated_at.trunc_subsecs(0) > since,
+        _ => false,
+    }
+}
+
+// Ön koşul sağlanmadı: güncel değişiklik zamanı Last-Modified olarak döner, istemci yeniden yükleyip tekrar dener
+fn precondition_failed(message: &str, updated_at: Option<DateTime<Utc>>) -> HttpResponse {
+    let mut response = HttpResponse::PreconditionFailed();
+    if let Some(updated_at) = updated_at {
+        response.insert_header((header::LAST_MODIFIED, etag::http_date(updated_at)));
+    }
+    response.json(serde_json::json!({
+        "error": message,
+        "updated_at": updated_at
+    }))
+}
 
 // Yeni soru seti oluştur
 pub async fn create_question_set(
     pool: web::Data<Pool<Postgres>>,
     set_dto: web::Json<CreateQuestionSetDto>,
-    claims: web::ReqData<Claims>,
+    claims: Claims,
 ) -> impl Responder {
     let user_id = claims.sub.parse::<i32>().unwrap_or_default();
     
@@ -20,16 +53,30 @@ pub async fn create_question_set(
         }));
     }
     
+    // Dil belirtilmezse Türkçe
+    let language = match set_dto.language.as_deref() {
+        Some(language) => match localization::normalize_locale(language) {
+            Some(language) => language,
+            None => {
+                return HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "Geçersiz dil kodu"
+                }));
+            }
+        },
+        None => localization::DEFAULT_LANGUAGE.to_string(),
+    };
+    
     // Soru setini veritabanına ekle
     let result = sqlx::query!(
         r#"
-        INSERT INTO question_sets (creator_id, title, description, created_at, updated_at)
-        VALUES ($1, $2, $3, $4, $5)
+        INSERT INTO question_sets (creator_id, title, description, language, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
         RETURNING id, created_at
         "#,
         user_id,
         set_dto.title,
         set_dto.description,
+        language,
         Utc::now(),
         Utc::now()
     )
@@ -47,6 +94,7 @@ pub async fn create_question_set(
                 "id": record.id,
                 "title": set_dto.title,
                 "description": set_dto.description,
+                "language": language,
                 "created_at": record.created_at
             }))
         }
@@ -63,7 +111,7 @@ pub async fn create_question_set(
 pub async fn create_question(
     pool: web::Data<Pool<Postgres>>,
     question_dto: web::Json<CreateQuestionDto>,
-    claims: web::ReqData<Claims>,
+    claims: Claims,
 ) -> impl Responder {
     let user_id = claims.sub.parse::<i32>().unwrap_or_default();
     
@@ -74,9 +122,9 @@ pub async fn create_question(
         }));
     }
     
-    // Soru setinin bu kullanıcıya ait olup olmadığını kontrol et
+    // Soru setinin sahibi veya yazma yetkili ortak editörü olunmalı
     let question_set = sqlx::query!(
-        "SELECT creator_id FROM question_sets WHERE id = $1",
+        "SELECT creator_id, visibility FROM question_sets WHERE id = $1 AND deleted_at IS NULL",
         question_dto.question_set_id
     )
     .fetch_optional(&**pool)
@@ -84,9 +132,14 @@ pub async fn create_question(
     
     match question_set {
         Ok(Some(set)) => {
-            if set.creator_id != user_id {
+            let access = collaboration::access_level(
+                &pool, user_id, question_dto.question_set_id, set.creator_id, &set.visibility,
+            )
+            .await;
+            
+            if access < SetAccess::Write {
                 return HttpResponse::Forbidden().json(serde_json::json!({
-                    "error": "Bu soru seti size ait değil"
+                    "error": "Bu soru setini düzenleme izniniz yok"
                 }));
             }
             
@@ -98,6 +151,13 @@ pub async fn create_question(
                 }));
             }
             
+            let host_notes = question_dto.host_notes.as_deref().map(str::trim).filter(|n| !n.is_empty());
+            if host_notes.is_some_and(|n| n.chars().count() > MAX_HOST_NOTES_LEN) {
+                return HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "Host notları en fazla 2000 karakter olabilir"
+                }));
+            }
+            
             // Varsayılan değerleri belirle
             let points = question_dto.points.unwrap_or(100);
             let time_limit = question_dto.time_limit.unwrap_or(30);
@@ -107,8 +167,8 @@ pub async fn create_question(
                 r#"
                 INSERT INTO questions 
                 (question_set_id, question_text, option_a, option_b, option_c, option_d,
-                correct_option, points, time_limit, position)
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                correct_option, points, time_limit, position, host_notes)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
                 RETURNING id
                 "#,
                 question_dto.question_set_id,
@@ -120,7 +180,8 @@ pub async fn create_question(
                 correct_option,
                 points,
                 time_limit,
-                question_dto.position
+                question_dto.position,
+                host_notes
             )
             .fetch_one(&**pool)
             .await;
@@ -136,6 +197,11 @@ pub async fn create_question(
                     .execute(&**pool)
                     .await;
                     
+                    collaboration::record_edit(
+                        &pool, question_dto.question_set_id, record.id, user_id, collaboration::EDIT_CREATE,
+                    )
+                    .await;
+                    
                     info!(
                         "Soru eklendi: id={}, soru seti={}",
                         record.id, question_dto.question_set_id
@@ -152,7 +218,8 @@ pub async fn create_question(
                         "correct_option": correct_option,
                         "points": points,
                         "time_limit": time_limit,
-                        "position": question_dto.position
+                        "position": question_dto.position,
+                        "host_notes": host_notes
                     }))
                 }
                 Err(e) => {
@@ -180,19 +247,27 @@ pub async fn create_question(
 // Kullanıcının soru setlerini getir
 pub async fn get_question_sets(
     pool: web::Data<Pool<Postgres>>,
-    claims: web::ReqData<Claims>,
+    query: web::Query<IncludeArchivedQuery>,
+    claims: Claims,
 ) -> impl Responder {
     let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let include_archived = query.include_archived.unwrap_or(false);
     
-    // Kullanıcının tüm soru setlerini getir
+    // Kullanıcının kendi soru setlerini ve ortak editörü olduğu setleri getir
+    // Arşivlenmiş setler yalnızca istenirse listelenir
     let sets = sqlx::query!(
         r#"
-        SELECT id, title, description, created_at, updated_at
-        FROM question_sets
-        WHERE creator_id = $1
-        ORDER BY updated_at DESC
+        SELECT qs.id, qs.creator_id, qs.title, qs.description, qs.is_hidden, qs.visibility,
+               qs.archived_at, qs.created_at, qs.updated_at, e.permission as "permission?"
+        FROM question_sets qs
+        LEFT JOIN question_set_editors e ON e.question_set_id = qs.id AND e.user_id = $1
+        WHERE (qs.creator_id = $1 OR e.user_id IS NOT NULL)
+          AND qs.deleted_at IS NULL
+          AND ($2 OR qs.archived_at IS NULL)
+        ORDER BY qs.updated_at DESC
         "#,
-        user_id
+        user_id,
+        include_archived
     )
     .fetch_all(&**pool)
     .await;
@@ -204,7 +279,7 @@ pub async fn get_question_sets(
             
             for set in sets {
                 let question_count = sqlx::query!(
-                    "SELECT COUNT(*) as count FROM questions WHERE question_set_id = $1",
+                    "SELECT COUNT(*) as count FROM questions WHERE question_set_id = $1 AND deleted_at IS NULL",
                     set.id
                 )
                 .fetch_one(&**pool)
@@ -216,6 +291,11 @@ pub async fn get_question_sets(
                     "id": set.id,
                     "title": set.title,
                     "description": set.description,
+                    "is_hidden": set.is_hidden,
+                    "visibility": set.visibility,
+                    "archived_at": set.archived_at,
+                    "is_owner": set.creator_id == user_id,
+                    "permission": if set.creator_id == user_id { Some("owner".to_string()) } else { set.permission },
                     "created_at": set.created_at,
                     "updated_at": set.updated_at,
                     "question_count": count
@@ -235,11 +315,329 @@ pub async fn get_question_sets(
     }
 }
 
+// Kullanıcının kurumunda paylaşılan soru setlerini getir
+pub async fn get_shared_question_sets(
+    pool: web::Data<Pool<Postgres>>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let sets = sqlx::query!(
+        r#"
+        SELECT qs.id, qs.title, qs.description, qs.updated_at, u.username as creator_username,
+               (SELECT COUNT(*) FROM questions q WHERE q.question_set_id = qs.id AND q.deleted_at IS NULL) as "question_count!"
+        FROM question_sets qs
+        JOIN users u ON qs.creator_id = u.id
+        JOIN users viewer ON viewer.id = $1
+        WHERE qs.visibility = 'organization'
+          AND qs.is_hidden = false
+          AND qs.archived_at IS NULL
+          AND qs.deleted_at IS NULL
+          AND qs.creator_id <> $1
+          AND u.organization_id = viewer.organization_id
+        ORDER BY qs.updated_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    match sets {
+        Ok(sets) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "question_sets": sets.iter().map(|set| {
+                    serde_json::json!({
+                        "id": set.id,
+                        "title": set.title,
+                        "description": set.description,
+                        "creator": set.creator_username,
+                        "updated_at": set.updated_at,
+                        "question_count": set.question_count
+                    })
+                }).collect::<Vec<_>>()
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Paylaşılan soru setleri alınamadı"
+            }))
+        }
+    }
+}
+
+// Herkese açık soru setlerini listele (arama ve en yüksek puan sıralaması ile)
+pub async fn get_public_question_sets(
+    pool: web::Data<Pool<Postgres>>,
+    query: web::Query<PublicQuestionSetQuery>,
+) -> impl Responder {
+    let top_rated = match query.sort.as_deref() {
+        None | Some("recent") => false,
+        Some("top_rated") => true,
+        Some(_) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Sıralama 'recent' veya 'top_rated' olmalıdır"
+            }));
+        }
+    };
+    
+    let search = query
+        .search
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| format!("%{}%", s.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")));
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    let offset = query.offset.unwrap_or(0).max(0);
+    
+    // En yüksek puan sıralamasında puanlanmamış setler sona kalır
+    let sets = sqlx::query!(
+        r#"
+        SELECT qs.id, qs.title, qs.description, qs.updated_at, u.username as creator_username,
+               (SELECT COUNT(*) FROM questions q WHERE q.question_set_id = qs.id AND q.deleted_at IS NULL) as "question_count!",
+               r.average_rating::float8 as "average_rating?",
+               COALESCE(r.rating_count, 0) as "rating_count!"
+        FROM question_sets qs
+        JOIN users u ON qs.creator_id = u.id
+        LEFT JOIN (
+            SELECT question_set_id, AVG(rating) as average_rating, COUNT(*) as rating_count
+            FROM question_set_ratings
+            GROUP BY question_set_id
+        ) r ON r.question_set_id = qs.id
+        WHERE qs.visibility = 'public'
+          AND qs.is_hidden = false
+          AND qs.archived_at IS NULL
+          AND qs.deleted_at IS NULL
+          AND ($1::text IS NULL OR qs.title ILIKE $1 OR qs.description ILIKE $1)
+        ORDER BY
+            CASE WHEN $2 THEN r.average_rating END DESC NULLS LAST,
+            CASE WHEN $2 THEN r.rating_count END DESC NULLS LAST,
+            qs.updated_at DESC
+        LIMIT $3 OFFSET $4
+        "#,
+        search,
+        top_rated,
+        limit,
+        offset
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    match sets {
+        Ok(sets) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "question_sets": sets.iter().map(|set| {
+                    serde_json::json!({
+                        "id": set.id,
+                        "title": set.title,
+                        "description": set.description,
+                        "creator": set.creator_username,
+                        "updated_at": set.updated_at,
+                        "question_count": set.question_count,
+                        "average_rating": set.average_rating,
+                        "rating_count": set.rating_count
+                    })
+                }).collect::<Vec<_>>()
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Herkese açık soru setleri alınamadı"
+            }))
+        }
+    }
+}
+
+// Kullanıcının (silinmemiş) soru setinin son değişiklik zamanı; set yoksa veya kullanıcıya ait değilse None
+async fn owned_set_updated_at(pool: &Pool<Postgres>, set_id: i32, user_id: i32) -> Option<DateTime<Utc>> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT COALESCE(updated_at, created_at, to_timestamp(0)) as "updated_at!"
+        FROM question_sets
+        WHERE id = $1 AND creator_id = $2 AND deleted_at IS NULL
+        "#,
+        set_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+    .ok()
+    .flatten()
+}
+
+// Soru setinin görünürlüğünü güncelle (özel / kurum içi / herkese açık)
+pub async fn update_question_set_visibility(
+    req: HttpRequest,
+    pool: web::Data<Pool<Postgres>>,
+    set_id: web::Path<i32>,
+    visibility_dto: web::Json<UpdateVisibilityDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let set_id_inner = set_id.into_inner();
+    
+    if !["private", "organization", "public"].contains(&visibility_dto.visibility.as_str()) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Görünürlük 'private', 'organization' veya 'public' olmalıdır"
+        }));
+    }
+    
+    // Kurumla paylaşmak için kullanıcının bir kuruma bağlı olması gerekir
+    if visibility_dto.visibility == "organization" && organization::membership(&claims).organization_id.is_none() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Soru setini paylaşmak için bir kuruma bağlı olmalısınız"
+        }));
+    }
+    
+    // If-Unmodified-Since gönderildiyse set o zamandan sonra değişmemiş olmalı
+    let unmodified_since = etag::if_unmodified_since(&req);
+    let result = sqlx::query!(
+        r#"
+        UPDATE question_sets qs SET visibility = $1, updated_at = $2
+        FROM question_sets old
+        WHERE qs.id = old.id AND qs.id = $3 AND qs.creator_id = $4 AND qs.deleted_at IS NULL
+          AND ($5::timestamptz IS NULL OR COALESCE(date_trunc('second', old.updated_at) <= $5, true))
+        RETURNING qs.id, qs.title, old.visibility as previous_visibility, qs.updated_at
+        "#,
+        visibility_dto.visibility,
+        Utc::now(),
+        set_id_inner,
+        user_id,
+        unmodified_since
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match result {
+        Ok(Some(set)) => {
+            info!(
+                "Soru seti görünürlüğü güncellendi: id={}, visibility={}",
+                set_id_inner, visibility_dto.visibility
+            );
+            
+            // Kurumla yeni paylaşılan setler için kurumdaki öğretmenleri bilgilendir
+            if visibility_dto.visibility == "organization" && set.previous_visibility != "organization" {
+                if let Some(org_id) = organization::membership(&claims).organization_id {
+                    notifications::notify_organization_teachers(
+                        &pool,
+                        org_id,
+                        user_id,
+                        notifications::KIND_SET_SHARED,
+                        "Yeni paylaşılan soru seti",
+                        &format!("\"{}\" soru seti kurumunuzla paylaşıldı", set.title),
+                        serde_json::json!({"question_set_id": set.id}),
+                    )
+                    .await;
+                }
+            }
+            
+            HttpResponse::Ok().json(serde_json::json!({
+                "id": set_id_inner,
+                "visibility": visibility_dto.visibility,
+                "updated_at": set.updated_at
+            }))
+        }
+        Ok(None) => {
+            let updated_at = owned_set_updated_at(&pool, set_id_inner, user_id).await;
+            if modified_after(updated_at, unmodified_since) {
+                precondition_failed("Soru seti siz düzenlerken değiştirildi", updated_at)
+            } else {
+                HttpResponse::NotFound().json(serde_json::json!({
+                    "error": "Soru seti bulunamadı veya size ait değil"
+                }))
+            }
+        }
+        Err(e) => {
+            error!("Görünürlük güncellenirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Görünürlük güncellenemedi"
+            }))
+        }
+    }
+}
+
+// Soru setinin asıl dilini değiştir (sadece sahibi)
+pub async fn update_question_set_language(
+    req: HttpRequest,
+    pool: web::Data<Pool<Postgres>>,
+    set_id: web::Path<i32>,
+    language_dto: web::Json<UpdateLanguageDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let set_id_inner = set_id.into_inner();
+    
+    let Some(language) = localization::normalize_locale(&language_dto.language) else {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Geçersiz dil kodu"
+        }));
+    };
+    
+    // Yeni dilde çevirisi olan sorular varsa asıl metinle çeviri çakışır; önce çeviriler silinmeli.
+    // If-Unmodified-Since gönderildiyse set o zamandan sonra değişmemiş olmalı.
+    let unmodified_since = etag::if_unmodified_since(&req);
+    let result = sqlx::query!(
+        r#"
+        UPDATE question_sets SET language = $1, updated_at = $2
+        WHERE id = $3 AND creator_id = $4 AND deleted_at IS NULL
+          AND ($5::timestamptz IS NULL OR COALESCE(date_trunc('second', updated_at) <= $5, true))
+          AND NOT EXISTS (
+              SELECT 1 FROM question_translations t
+              JOIN questions q ON t.question_id = q.id
+              WHERE q.question_set_id = $3 AND q.deleted_at IS NULL AND t.locale = $1
+          )
+        RETURNING id, updated_at
+        "#,
+        language,
+        Utc::now(),
+        set_id_inner,
+        user_id,
+        unmodified_since
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match result {
+        Ok(Some(set)) => {
+            info!("Soru seti dili güncellendi: id={}, language={}", set_id_inner, language);
+            HttpResponse::Ok().json(serde_json::json!({
+                "id": set_id_inner,
+                "language": language,
+                "updated_at": set.updated_at
+            }))
+        }
+        Ok(None) => {
+            // Güncellenmemesinin nedenini ayırt et
+            let updated_at = owned_set_updated_at(&pool, set_id_inner, user_id).await;
+            if modified_after(updated_at, unmodified_since) {
+                precondition_failed("Soru seti siz düzenlerken değiştirildi", updated_at)
+            } else if updated_at.is_some() {
+                HttpResponse::Conflict().json(serde_json::json!({
+                    "error": "Bu dilde çevirisi olan sorular var, önce çevirileri silin"
+                }))
+            } else {
+                HttpResponse::NotFound().json(serde_json::json!({
+                    "error": "Soru seti bulunamadı veya size ait değil"
+                }))
+            }
+        }
+        Err(e) => {
+            error!("Soru seti dili güncellenirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Soru seti dili güncellenemedi"
+            }))
+        }
+    }
+}
+
 // Soru setini detayları ile getir
 pub async fn get_question_set(
+    req: HttpRequest,
     pool: web::Data<Pool<Postgres>>,
     set_id: web::Path<i32>,
-    claims: web::ReqData<Claims>,
+    query: web::Query<QuestionSetQuery>,
+    claims: Claims,
 ) -> impl Responder {
     let user_id = claims.sub.parse::<i32>().unwrap_or_default();
     
@@ -249,9 +647,9 @@ pub async fn get_question_set(
     // Soru setini getir
     let set = sqlx::query!(
         r#"
-        SELECT id, creator_id, title, description, created_at, updated_at
+        SELECT id, creator_id, title, description, visibility, language, created_at, updated_at
         FROM question_sets
-        WHERE id = $1
+        WHERE id = $1 AND deleted_at IS NULL
         "#,
         set_id_inner
     )
@@ -260,20 +658,82 @@ pub async fn get_question_set(
     
     match set {
         Ok(Some(set)) => {
-            // Soru setinin bu kullanıcıya ait olup olmadığını kontrol et
-            if set.creator_id != user_id && claims.role != "admin" {
+            // Soru seti kullanıcıya ait değilse ortak editörlük ve kurum paylaşımı kontrol edilir
+            let access = collaboration::access_level(&pool, user_id, set.id, set.creator_id, &set.visibility).await;
+            if claims.role != "admin" && access == SetAccess::None {
                 return HttpResponse::Forbidden().json(serde_json::json!({
                     "error": "Bu soru setine erişim izniniz yok"
                 }));
             }
             
+            let is_owner = set.creator_id == user_id;
+            let can_edit = access >= SetAccess::Write || claims.role == "admin";
+            let summary = query.summary.unwrap_or(false);
+            
+            // Soru, çeviri ve konu değişiklikleri setin updated_at değerini ilerletir; yanıt kullanıcıya göre
+            // yalnızca sahiplik ve düzenleme yetkisinde değiştiği için bunlar da ETag'e katılır
+            let etag = etag::weak_etag(&[
+                &set.updated_at.map(|t| t.to_rfc3339()).unwrap_or_default(),
+                &is_owner.to_string(),
+                &can_edit.to_string(),
+                &summary.to_string(),
+            ]);
+            if etag::if_none_match(&req, &etag) {
+                return HttpResponse::NotModified()
+                    .insert_header((header::ETAG, etag))
+                    .finish();
+            }
+            
+            let mut body = serde_json::json!({
+                "id": set.id,
+                "title": set.title,
+                "description": set.description,
+                "visibility": set.visibility,
+                "language": set.language,
+                "is_owner": is_owner,
+                "can_edit": can_edit,
+                "created_at": set.created_at,
+                "updated_at": set.updated_at
+            });
+            
+            // Özet modunda sorular yerine yalnızca sayıları döner; sorular sayfalı olarak
+            // /api/question-sets/{id}/questions üzerinden alınır
+            if summary {
+                let count = sqlx::query_scalar!(
+                    r#"SELECT COUNT(*) as "count!" FROM questions WHERE question_set_id = $1 AND deleted_at IS NULL"#,
+                    set.id
+                )
+                .fetch_one(&**pool)
+                .await;
+                
+                return match count {
+                    Ok(count) => {
+                        body["question_count"] = count.into();
+                        HttpResponse::Ok()
+                            .insert_header((header::ETAG, etag))
+                            .insert_header((header::CACHE_CONTROL, "private, no-cache"))
+                            .json(body)
+                    }
+                    Err(e) => {
+                        error!("Veritabanı sorgu hatası: {}", e);
+                        HttpResponse::InternalServerError().json(serde_json::json!({
+                            "error": "Sorular alınamadı"
+                        }))
+                    }
+                };
+            }
+            
             // Soruları getir
             let questions = sqlx::query!(
                 r#"
                 SELECT id, question_text, option_a, option_b, option_c, option_d,
-                       correct_option, points, time_limit, position
+                       correct_option, points, time_limit, position, host_notes, updated_at,
+                       ARRAY(SELECT topic_id FROM question_topics qt WHERE qt.question_id = questions.id ORDER BY topic_id)
+                           as "topic_ids!",
+                       ARRAY(SELECT locale FROM question_translations t WHERE t.question_id = questions.id ORDER BY locale)
+                           as "translation_locales!"
                 FROM questions
-                WHERE question_set_id = $1
+                WHERE question_set_id = $1 AND deleted_at IS NULL
                 ORDER BY position
                 "#,
                 set.id
@@ -297,20 +757,21 @@ pub async fn get_question_set(
                                 "correct_option": q.correct_option,
                                 "points": q.points,
                                 "time_limit": q.time_limit,
-                                "position": q.position
+                                "position": q.position,
+                                "host_notes": q.host_notes,
+                                "topic_ids": q.topic_ids,
+                                "translation_locales": q.translation_locales,
+                                "updated_at": q.updated_at
                             })
                         })
                         .collect();
                     
-                    HttpResponse::Ok().json(serde_json::json!({
-                        "id": set.id,
-                        "title": set.title,
-                        "description": set.description,
-                        "created_at": set.created_at,
-                        "updated_at": set.updated_at,
-                        "questions": questions_json,
-                        "question_count": questions.len()
-                    }))
+                    body["question_count"] = questions.len().into();
+                    body["questions"] = questions_json.into();
+                    HttpResponse::Ok()
+                        .insert_header((header::ETAG, etag))
+                        .insert_header((header::CACHE_CONTROL, "private, no-cache"))
+                        .json(body)
                 }
                 Err(e) => {
                     error!("Veritabanı sorgu hatası: {}", e);
@@ -334,75 +795,431 @@ pub async fn get_question_set(
     }
 }
 
-// Soru seti sil
-pub async fn delete_question_set(
+// Sayfa başına varsayılan ve en fazla soru sayısı
+const DEFAULT_QUESTION_PAGE_SIZE: i64 = 50;
+const MAX_QUESTION_PAGE_SIZE: i64 = 200;
+
+// Sayfalama imleci: son sorunun sıralama anahtarı ve sırası, URL'de taşınabilmesi için base64 ile kodlanır
+fn encode_question_cursor(sort_key: i32, position: i32) -> String {
+    URL_SAFE_NO_PAD.encode(format!("{}:{}", sort_key, position))
+}
+
+fn decode_question_cursor(cursor: &str) -> Option<(i32, i32)> {
+    let decoded = String::from_utf8(URL_SAFE_NO_PAD.decode(cursor).ok()?).ok()?;
+    let (sort_key, position) = decoded.split_once(':')?;
+    Some((sort_key.parse().ok()?, position.parse().ok()?))
+}
+
+// Soru setindeki soruları sayfalı listele
+// Sıralama sıra numarasına (position) veya oynanmış oyunlardaki cevaplardan hesaplanan zorluğa göre yapılır;
+// zorluk, oyun istatistiklerindeki difficulty_score ile aynı formülle 0-10 arası hesaplanır (hiç cevaplanmamış
+// sorular 5). Eşit zorluktaki sorular sıra numarasına göre dizilir, böylece imleç her zaman tek bir konumu gösterir.
+pub async fn list_question_set_questions(
     pool: web::Data<Pool<Postgres>>,
     set_id: web::Path<i32>,
-    claims: web::ReqData<Claims>,
+    query: web::Query<QuestionListQuery>,
+    claims: Claims,
 ) -> impl Responder {
     let user_id = claims.sub.parse::<i32>().unwrap_or_default();
-    
-    // Path parametresini bir kere kullan ve sakla
     let set_id_inner = set_id.into_inner();
     
-    // Soru setini getir
+    let by_difficulty = match query.sort.as_deref() {
+        None | Some("position") => false,
+        Some("difficulty") => true,
+        Some(_) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Sıralama 'position' veya 'difficulty' olmalıdır"
+            }));
+        }
+    };
+    
+    // Varsayılan: sıra numarasında baştan sona, zorlukta en zordan en kolaya
+    let descending = match query.order.as_deref() {
+        None => by_difficulty,
+        Some("asc") => false,
+        Some("desc") => true,
+        Some(_) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Sıralama yönü 'asc' veya 'desc' olmalıdır"
+            }));
+        }
+    };
+    
+    let cursor = match query.cursor.as_deref() {
+        None => None,
+        Some(cursor) => match decode_question_cursor(cursor) {
+            Some(cursor) => Some(cursor),
+            None => {
+                return HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "Geçersiz sayfalama imleci"
+                }));
+            }
+        },
+    };
+    
+    let limit = query.limit.unwrap_or(DEFAULT_QUESTION_PAGE_SIZE).clamp(1, MAX_QUESTION_PAGE_SIZE);
+    
     let set = sqlx::query!(
-        "SELECT creator_id FROM question_sets WHERE id = $1",
+        "SELECT id, creator_id, visibility FROM question_sets WHERE id = $1 AND deleted_at IS NULL",
         set_id_inner
     )
     .fetch_optional(&**pool)
     .await;
     
-    match set {
-        Ok(Some(set)) => {
-            // Soru setinin bu kullanıcıya ait olup olmadığını kontrol et
-            if set.creator_id != user_id && claims.role != "admin" {
-                return HttpResponse::Forbidden().json(serde_json::json!({
-                    "error": "Bu soru setini silme izniniz yok"
-                }));
-            }
-            
-            // Soru setini ve ilişkili soruları sil (cascade)
-            let result = sqlx::query!(
-                "DELETE FROM question_sets WHERE id = $1",
-                set_id_inner
-            )
-            .execute(&**pool)
-            .await;
-            
-            match result {
-                Ok(_) => {
-                    HttpResponse::Ok().json(serde_json::json!({
-                        "message": "Soru seti başarıyla silindi"
-                    }))
-                }
-                Err(e) => {
-                    error!("Soru seti silinirken hata: {}", e);
-                    HttpResponse::InternalServerError().json(serde_json::json!({
-                        "error": "Soru seti silinemedi"
-                    }))
-                }
-            }
-        }
+    let set = match set {
+        Ok(Some(set)) => set,
         Ok(None) => {
-            HttpResponse::NotFound().json(serde_json::json!({
+            return HttpResponse::NotFound().json(serde_json::json!({
                 "error": "Soru seti bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Soru seti alınamadı"
+            }));
+        }
+    };
+    
+    let access = collaboration::access_level(&pool, user_id, set.id, set.creator_id, &set.visibility).await;
+    if claims.role != "admin" && access == SetAccess::None {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu soru setine erişim izniniz yok"
+        }));
+    }
+    
+    // Sıralama anahtarı azalan sırada negatif alınır; böylece imleç karşılaştırması her iki yönde de
+    // (anahtar, sıra) > (imleç) biçiminde kalır. Zorluk anahtarı puanın onda biri cinsindendir (0-100).
+    let questions = sqlx::query!(
+        r#"
+        WITH scored AS (
+            SELECT q.id, q.question_text, q.option_a, q.option_b, q.option_c, q.option_d,
+                   q.correct_option, q.points, q.time_limit, q.position, q.host_notes, q.updated_at,
+                   COUNT(pa.id) as answer_count,
+                   CASE WHEN COUNT(pa.id) > 0 THEN ROUND((
+                       (1 - COUNT(pa.id) FILTER (WHERE pa.is_correct)::float8 / COUNT(pa.id)) * 0.7
+                       + LEAST(COALESCE(AVG(pa.response_time_ms)::float8, 5000) / 10000, 1) * 0.3
+                   ) * 100)::int ELSE 50 END as difficulty
+            FROM questions q
+            LEFT JOIN player_answers pa ON pa.question_id = q.id
+            WHERE q.question_set_id = $1 AND q.deleted_at IS NULL
+            GROUP BY q.id
+        ),
+        keyed AS (
+            SELECT *, (CASE WHEN $2 THEN difficulty ELSE position END) * (CASE WHEN $3 THEN -1 ELSE 1 END) as sort_key
+            FROM scored
+        )
+        SELECT id as "id!", question_text as "question_text!", option_a as "option_a!", option_b as "option_b!",
+               option_c as "option_c!", option_d as "option_d!", correct_option as "correct_option!", points,
+               time_limit, position as "position!", host_notes, updated_at as "updated_at!",
+               answer_count as "answer_count!",
+               difficulty as "difficulty!", sort_key as "sort_key!",
+               ARRAY(SELECT topic_id FROM question_topics qt WHERE qt.question_id = keyed.id ORDER BY topic_id)
+                   as "topic_ids!",
+               ARRAY(SELECT locale FROM question_translations t WHERE t.question_id = keyed.id ORDER BY locale)
+                   as "translation_locales!"
+        FROM keyed
+        WHERE $4::int IS NULL OR (sort_key, position) > ($4, $5)
+        ORDER BY sort_key, position
+        LIMIT $6
+        "#,
+        set.id,
+        by_difficulty,
+        descending,
+        cursor.map(|(sort_key, _)| sort_key),
+        cursor.map(|(_, position)| position),
+        limit + 1
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    match questions {
+        Ok(mut questions) => {
+            // Bir fazla satır istenir; varsa sonraki sayfa vardır
+            let has_more = questions.len() as i64 > limit;
+            questions.truncate(limit as usize);
+            let next_cursor = if has_more {
+                questions.last().map(|q| encode_question_cursor(q.sort_key, q.position))
+            } else {
+                None
+            };
+            
+            let items: Vec<serde_json::Value> = questions
+                .iter()
+                .map(|q| {
+                    serde_json::json!({
+                        "id": q.id,
+                        "question_text": q.question_text,
+                        "option_a": q.option_a,
+                        "option_b": q.option_b,
+                        "option_c": q.option_c,
+                        "option_d": q.option_d,
+                        "correct_option": q.correct_option,
+                        "points": q.points,
+                        "time_limit": q.time_limit,
+                        "position": q.position,
+                        "host_notes": q.host_notes,
+                        "topic_ids": q.topic_ids,
+                        "translation_locales": q.translation_locales,
+                        "updated_at": q.updated_at,
+                        "answer_count": q.answer_count,
+                        "difficulty_score": q.difficulty as f64 / 10.0
+                    })
+                })
+                .collect();
+            
+            HttpResponse::Ok().json(serde_json::json!({
+                "question_set_id": set.id,
+                "sort": if by_difficulty { "difficulty" } else { "position" },
+                "order": if descending { "desc" } else { "asc" },
+                "questions": items,
+                "next_cursor": next_cursor
             }))
         }
         Err(e) => {
             error!("Veritabanı sorgu hatası: {}", e);
             HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Soru seti silinemedi"
+                "error": "Sorular alınamadı"
             }))
         }
     }
 }
 
-// Soruyu sil
-pub async fn delete_question(
-    pool: web::Data<Pool<Postgres>>,
-    question_id: web::Path<i32>,
-    claims: web::ReqData<Claims>,
+// Soru setini arşivle veya arşivden çıkar (sahip veya admin)
+async fn set_question_set_archived(
+    pool: &Pool<Postgres>,
+    set_id: i32,
+    claims: &Claims,
+    archived: bool,
+) -> HttpResponse {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let result = sqlx::query!(
+        r#"
+        UPDATE question_sets
+        SET archived_at = CASE WHEN $1 THEN COALESCE(archived_at, $2) END
+        WHERE id = $3 AND (creator_id = $4 OR $5) AND deleted_at IS NULL
+        RETURNING id, archived_at
+        "#,
+        archived,
+        Utc::now(),
+        set_id,
+        user_id,
+        claims.role == "admin"
+    )
+    .fetch_optional(pool)
+    .await;
+    
+    match result {
+        Ok(Some(set)) => {
+            info!("Soru seti arşiv durumu güncellendi: id={}, archived={}", set.id, archived);
+            HttpResponse::Ok().json(serde_json::json!({
+                "id": set.id,
+                "archived": archived,
+                "archived_at": set.archived_at
+            }))
+        }
+        Ok(None) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Soru seti bulunamadı veya düzenleme izniniz yok"
+            }))
+        }
+        Err(e) => {
+            error!("Soru seti arşivlenirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Soru seti arşiv durumu güncellenemedi"
+            }))
+        }
+    }
+}
+
+// Soru setini arşivle
+pub async fn archive_question_set(
+    pool: web::Data<Pool<Postgres>>,
+    set_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    set_question_set_archived(&pool, set_id.into_inner(), &claims, true).await
+}
+
+// Soru setini arşivden çıkar
+pub async fn unarchive_question_set(
+    pool: web::Data<Pool<Postgres>>,
+    set_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    set_question_set_archived(&pool, set_id.into_inner(), &claims, false).await
+}
+
+// Soru seti sil
+pub async fn delete_question_set(
+    pool: web::Data<Pool<Postgres>>,
+    set_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    // Path parametresini bir kere kullan ve sakla
+    let set_id_inner = set_id.into_inner();
+    
+    // Soru setini getir
+    let set = sqlx::query!(
+        "SELECT creator_id FROM question_sets WHERE id = $1 AND deleted_at IS NULL",
+        set_id_inner
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match set {
+        Ok(Some(set)) => {
+            // Soru setinin bu kullanıcıya ait olup olmadığını kontrol et
+            if set.creator_id != user_id && claims.role != "admin" {
+                return HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": "Bu soru setini silme izniniz yok"
+                }));
+            }
+            
+            // Soru setini çöp kutusuna taşı; saklama süresi dolunca temizleme görevi kalıcı olarak siler
+            let result = sqlx::query!(
+                "UPDATE question_sets SET deleted_at = $1 WHERE id = $2",
+                Utc::now(),
+                set_id_inner
+            )
+            .execute(&**pool)
+            .await;
+            
+            match result {
+                Ok(_) => {
+                    info!("Soru seti çöp kutusuna taşındı: id={}", set_id_inner);
+                    HttpResponse::Ok().json(serde_json::json!({
+                        "message": format!(
+                            "Soru seti çöp kutusuna taşındı, {} gün içinde geri yüklenebilir",
+                            CONFIG.trash_retention_days
+                        ),
+                        "restorable_until": Utc::now() + Duration::days(CONFIG.trash_retention_days)
+                    }))
+                }
+                Err(e) => {
+                    error!("Soru seti silinirken hata: {}", e);
+                    HttpResponse::InternalServerError().json(serde_json::json!({
+                        "error": "Soru seti silinemedi"
+                    }))
+                }
+            }
+        }
+        Ok(None) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Soru seti bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Soru seti silinemedi"
+            }))
+        }
+    }
+}
+
+// Çöp kutusundaki soru setlerini listele
+pub async fn get_trashed_question_sets(
+    pool: web::Data<Pool<Postgres>>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let sets = sqlx::query!(
+        r#"
+        SELECT id, title, description, deleted_at as "deleted_at!",
+               (SELECT COUNT(*) FROM questions q WHERE q.question_set_id = question_sets.id AND q.deleted_at IS NULL) as "question_count!"
+        FROM question_sets
+        WHERE creator_id = $1 AND deleted_at IS NOT NULL
+        ORDER BY deleted_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    match sets {
+        Ok(sets) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "retention_days": CONFIG.trash_retention_days,
+                "question_sets": sets.iter().map(|set| {
+                    serde_json::json!({
+                        "id": set.id,
+                        "title": set.title,
+                        "description": set.description,
+                        "question_count": set.question_count,
+                        "deleted_at": set.deleted_at,
+                        "restorable_until": set.deleted_at + Duration::days(CONFIG.trash_retention_days)
+                    })
+                }).collect::<Vec<_>>()
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Çöp kutusu alınamadı"
+            }))
+        }
+    }
+}
+
+// Çöp kutusundaki soru setini geri yükle (saklama süresi içinde)
+pub async fn restore_question_set(
+    pool: web::Data<Pool<Postgres>>,
+    set_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let set_id_inner = set_id.into_inner();
+    let now = Utc::now();
+    
+    let result = sqlx::query!(
+        r#"
+        UPDATE question_sets SET deleted_at = NULL, updated_at = $1
+        WHERE id = $2 AND (creator_id = $3 OR $4)
+          AND deleted_at IS NOT NULL AND deleted_at > $5
+        RETURNING id, title
+        "#,
+        now,
+        set_id_inner,
+        user_id,
+        claims.role == "admin",
+        now - Duration::days(CONFIG.trash_retention_days)
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match result {
+        Ok(Some(set)) => {
+            info!("Soru seti geri yüklendi: id={}", set.id);
+            HttpResponse::Ok().json(serde_json::json!({
+                "id": set.id,
+                "title": set.title,
+                "message": "Soru seti geri yüklendi"
+            }))
+        }
+        Ok(None) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Çöp kutusunda geri yüklenebilir bir soru seti bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Soru seti geri yüklenirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Soru seti geri yüklenemedi"
+            }))
+        }
+    }
+}
+
+// Soruyu sil
+pub async fn delete_question(
+    pool: web::Data<Pool<Postgres>>,
+    question_id: web::Path<i32>,
+    claims: Claims,
 ) -> impl Responder {
     let user_id = claims.sub.parse::<i32>().unwrap_or_default();
     
@@ -412,10 +1229,10 @@ pub async fn delete_question(
     // Soruyu ve ilişkili soru setini getir
     let question = sqlx::query!(
         r#"
-        SELECT q.id, qs.creator_id, q.question_set_id
+        SELECT q.id, qs.creator_id, qs.visibility, q.question_set_id
         FROM questions q
         JOIN question_sets qs ON q.question_set_id = qs.id
-        WHERE q.id = $1
+        WHERE q.id = $1 AND q.deleted_at IS NULL AND qs.deleted_at IS NULL
         "#,
         question_id_inner
     )
@@ -424,16 +1241,23 @@ pub async fn delete_question(
     
     match question {
         Ok(Some(question)) => {
-            // Soru setinin bu kullanıcıya ait olup olmadığını kontrol et
-            if question.creator_id != user_id && claims.role != "admin" {
+            // Soru setinin sahibi veya yazma yetkili ortak editörü olunmalı
+            let access = collaboration::access_level(
+                &pool, user_id, question.question_set_id, question.creator_id, &question.visibility,
+            )
+            .await;
+            
+            if access < SetAccess::Write && claims.role != "admin" {
                 return HttpResponse::Forbidden().json(serde_json::json!({
                     "error": "Bu soruyu silme izniniz yok"
                 }));
             }
             
-            // Soruyu sil
+            // Soruyu sil; geri alma süresi boyunca saklanır, süre dolunca temizleme görevi kalıcı olarak siler
+            let deleted_at = Utc::now();
             let result = sqlx::query!(
-                "DELETE FROM questions WHERE id = $1",
+                "UPDATE questions SET deleted_at = $1 WHERE id = $2",
+                deleted_at,
                 question.id
             )
             .execute(&**pool)
@@ -450,8 +1274,14 @@ pub async fn delete_question(
                     .execute(&**pool)
                     .await;
                     
+                    collaboration::record_edit(
+                        &pool, question.question_set_id, question.id, user_id, collaboration::EDIT_DELETE,
+                    )
+                    .await;
+                    
                     HttpResponse::Ok().json(serde_json::json!({
-                        "message": "Soru başarıyla silindi"
+                        "message": "Soru başarıyla silindi",
+                        "restorable_until": deleted_at + Duration::minutes(CONFIG.question_undo_window_minutes)
                     }))
                 }
                 Err(e) => {
@@ -476,12 +1306,100 @@ pub async fn delete_question(
     }
 }
 
+// Silinen soruyu geri yükle (geri alma süresi içinde)
+pub async fn restore_question(
+    pool: web::Data<Pool<Postgres>>,
+    question_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let question_id_inner = question_id.into_inner();
+    let now = Utc::now();
+    
+    let question = sqlx::query!(
+        r#"
+        SELECT q.id, qs.creator_id, qs.visibility, q.question_set_id
+        FROM questions q
+        JOIN question_sets qs ON q.question_set_id = qs.id
+        WHERE q.id = $1 AND q.deleted_at > $2 AND qs.deleted_at IS NULL
+        "#,
+        question_id_inner,
+        now - Duration::minutes(CONFIG.question_undo_window_minutes)
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    let question = match question {
+        Ok(Some(question)) => question,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Geri yüklenebilir bir soru bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Soru geri yüklenemedi"
+            }));
+        }
+    };
+    
+    let access = collaboration::access_level(
+        &pool, user_id, question.question_set_id, question.creator_id, &question.visibility,
+    )
+    .await;
+    
+    if access < SetAccess::Write && claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu soruyu geri yükleme izniniz yok"
+        }));
+    }
+    
+    let result = sqlx::query!(
+        "UPDATE questions SET deleted_at = NULL WHERE id = $1 AND deleted_at IS NOT NULL",
+        question.id
+    )
+    .execute(&**pool)
+    .await;
+    
+    match result {
+        Ok(_) => {
+            let _ = sqlx::query!(
+                "UPDATE question_sets SET updated_at = $1 WHERE id = $2",
+                now,
+                question.question_set_id
+            )
+            .execute(&**pool)
+            .await;
+            
+            collaboration::record_edit(
+                &pool, question.question_set_id, question.id, user_id, collaboration::EDIT_RESTORE,
+            )
+            .await;
+            
+            info!("Soru geri yüklendi: id={}", question.id);
+            HttpResponse::Ok().json(serde_json::json!({
+                "id": question.id,
+                "question_set_id": question.question_set_id,
+                "message": "Soru geri yüklendi"
+            }))
+        }
+        Err(e) => {
+            error!("Soru geri yüklenirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Soru geri yüklenemedi"
+            }))
+        }
+    }
+}
+
 // Soruyu güncelle
 pub async fn update_question(
+    req: HttpRequest,
     pool: web::Data<Pool<Postgres>>,
     question_id: web::Path<i32>,
     question_dto: web::Json<CreateQuestionDto>,
-    claims: web::ReqData<Claims>,
+    claims: Claims,
 ) -> impl Responder {
     let user_id = claims.sub.parse::<i32>().unwrap_or_default();
     
@@ -491,10 +1409,10 @@ pub async fn update_question(
     // Soruyu ve ilişkili soru setini getir
     let question = sqlx::query!(
         r#"
-        SELECT q.id, qs.creator_id, q.question_set_id
+        SELECT q.id, qs.creator_id, qs.visibility, q.question_set_id
         FROM questions q
         JOIN question_sets qs ON q.question_set_id = qs.id
-        WHERE q.id = $1
+        WHERE q.id = $1 AND q.deleted_at IS NULL AND qs.deleted_at IS NULL
         "#,
         question_id_inner
     )
@@ -503,8 +1421,13 @@ pub async fn update_question(
     
     match question {
         Ok(Some(question)) => {
-            // Soru setinin bu kullanıcıya ait olup olmadığını kontrol et
-            if question.creator_id != user_id && claims.role != "admin" {
+            // Soru setinin sahibi veya yazma yetkili ortak editörü olunmalı
+            let access = collaboration::access_level(
+                &pool, user_id, question.question_set_id, question.creator_id, &question.visibility,
+            )
+            .await;
+            
+            if access < SetAccess::Write && claims.role != "admin" {
                 return HttpResponse::Forbidden().json(serde_json::json!({
                     "error": "Bu soruyu güncelleme izniniz yok"
                 }));
@@ -518,18 +1441,28 @@ pub async fn update_question(
                 }));
             }
             
+            let host_notes = question_dto.host_notes.as_deref().map(str::trim).filter(|n| !n.is_empty());
+            if host_notes.is_some_and(|n| n.chars().count() > MAX_HOST_NOTES_LEN) {
+                return HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "Host notları en fazla 2000 karakter olabilir"
+                }));
+            }
+            
             // Varsayılan değerleri belirle
             let points = question_dto.points.unwrap_or(100);
             let time_limit = question_dto.time_limit.unwrap_or(30);
             
-            // Soruyu güncelle
+            // Soruyu güncelle. If-Unmodified-Since gönderildiyse soru o zamandan sonra değişmemiş olmalı
+            // (ortak editörler birbirinin değişikliğini fark etmeden ezmesin); HTTP tarihi saniye hassasiyetinde
+            // olduğundan karşılaştırma saniyeye yuvarlanarak yapılır.
+            let unmodified_since = etag::if_unmodified_since(&req);
             let result = sqlx::query!(
                 r#"
                 UPDATE questions 
                 SET question_text = $1, option_a = $2, option_b = $3, option_c = $4, option_d = $5,
-                    correct_option = $6, points = $7, time_limit = $8, position = $9
-                WHERE id = $10
-                RETURNING id
+                    correct_option = $6, points = $7, time_limit = $8, position = $9, host_notes = $10
+                WHERE id = $11 AND ($12::timestamptz IS NULL OR date_trunc('second', updated_at) <= $12)
+                RETURNING id, updated_at
                 "#,
                 question_dto.question_text,
                 question_dto.option_a,
@@ -540,13 +1473,15 @@ pub async fn update_question(
                 points,
                 time_limit,
                 question_dto.position,
-                question.id
+                host_notes,
+                question.id,
+                unmodified_since
             )
-            .fetch_one(&**pool)
+            .fetch_optional(&**pool)
             .await;
             
             match result {
-                Ok(_) => {
+                Ok(Some(updated)) => {
                     // Soru setinin güncellenme zamanını güncelle
                     let _ = sqlx::query!(
                         "UPDATE question_sets SET updated_at = $1 WHERE id = $2",
@@ -556,19 +1491,45 @@ pub async fn update_question(
                     .execute(&**pool)
                     .await;
                     
-                    HttpResponse::Ok().json(serde_json::json!({
-                        "id": question.id,
-                        "question_set_id": question.question_set_id,
-                        "question_text": question_dto.question_text,
-                        "option_a": question_dto.option_a,
-                        "option_b": question_dto.option_b,
-                        "option_c": question_dto.option_c,
-                        "option_d": question_dto.option_d,
-                        "correct_option": correct_option,
-                        "points": points,
-                        "time_limit": time_limit,
-                        "position": question_dto.position
-                    }))
+                    collaboration::record_edit(
+                        &pool, question.question_set_id, question.id, user_id, collaboration::EDIT_UPDATE,
+                    )
+                    .await;
+                    
+                    HttpResponse::Ok()
+                        .insert_header((header::LAST_MODIFIED, etag::http_date(updated.updated_at)))
+                        .json(serde_json::json!({
+                            "id": question.id,
+                            "question_set_id": question.question_set_id,
+                            "question_text": question_dto.question_text,
+                            "option_a": question_dto.option_a,
+                            "option_b": question_dto.option_b,
+                            "option_c": question_dto.option_c,
+                            "option_d": question_dto.option_d,
+                            "correct_option": correct_option,
+                            "points": points,
+                            "time_limit": time_limit,
+                            "position": question_dto.position,
+                            "host_notes": host_notes,
+                            "updated_at": updated.updated_at
+                        }))
+                }
+                Ok(None) => {
+                    // Soru bu arada silinmiş olabilir
+                    let updated_at = sqlx::query_scalar!("SELECT updated_at FROM questions WHERE id = $1", question.id)
+                        .fetch_optional(&**pool)
+                        .await
+                        .ok()
+                        .flatten();
+                    match updated_at {
+                        Some(updated_at) => precondition_failed(
+                            "Soru siz düzenlerken başka bir editör tarafından değiştirildi",
+                            Some(updated_at),
+                        ),
+                        None => HttpResponse::NotFound().json(serde_json::json!({
+                            "error": "Soru bulunamadı"
+                        })),
+                    }
                 }
                 Err(e) => {
                     error!("Soru güncellenirken hata: {}", e);
@@ -590,4 +1551,515 @@ pub async fn update_question(
             }))
         }
     }
+}
+
+// Soruyu kısmen güncelle (PATCH); yalnızca gönderilen alanlar değişir. update_question gibi
+// If-Unmodified-Since ön koşulunu destekler.
+pub async fn patch_question(
+    req: HttpRequest,
+    pool: web::Data<Pool<Postgres>>,
+    question_id: web::Path<i32>,
+    patch_dto: web::Json<PatchQuestionDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let question_id_inner = question_id.into_inner();
+    let patch = patch_dto.into_inner();
+    
+    let errors = question_edit::validate_fields(&patch);
+    if !errors.is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Bazı alanlar geçersiz",
+            "fields": errors
+        }));
+    }
+    
+    let question = sqlx::query!(
+        r#"
+        SELECT q.id, qs.creator_id, qs.visibility, q.question_set_id
+        FROM questions q
+        JOIN question_sets qs ON q.question_set_id = qs.id
+        WHERE q.id = $1 AND q.deleted_at IS NULL AND qs.deleted_at IS NULL
+        "#,
+        question_id_inner
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    let question = match question {
+        Ok(Some(question)) => question,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Soru bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Soru güncellenemedi"
+            }));
+        }
+    };
+    
+    let access = collaboration::access_level(
+        &pool, user_id, question.question_set_id, question.creator_id, &question.visibility,
+    )
+    .await;
+    
+    if access < SetAccess::Write && claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu soruyu güncelleme izniniz yok"
+        }));
+    }
+    
+    let unmodified_since = etag::if_unmodified_since(&req);
+    
+    let result = question_edit::patch(&**pool, question.id, &patch, unmodified_since).await;
+    
+    match result {
+        Ok(Some(updated)) => {
+            // Soru setinin güncellenme zamanını güncelle
+            let _ = sqlx::query!(
+                "UPDATE question_sets SET updated_at = $1 WHERE id = $2",
+                Utc::now(),
+                question.question_set_id
+            )
+            .execute(&**pool)
+            .await;
+            
+            collaboration::record_edit(
+                &pool, question.question_set_id, question.id, user_id, collaboration::EDIT_UPDATE,
+            )
+            .await;
+            
+            HttpResponse::Ok()
+                .insert_header((header::LAST_MODIFIED, etag::http_date(updated.updated_at)))
+                .json(updated)
+        }
+        Ok(None) => {
+            let updated_at = sqlx::query_scalar!("SELECT updated_at FROM questions WHERE id = $1", question.id)
+                .fetch_optional(&**pool)
+                .await
+                .ok()
+                .flatten();
+            match updated_at {
+                Some(updated_at) => precondition_failed(
+                    "Soru siz düzenlerken başka bir editör tarafından değiştirildi",
+                    Some(updated_at),
+                ),
+                None => HttpResponse::NotFound().json(serde_json::json!({
+                    "error": "Soru bulunamadı"
+                })),
+            }
+        }
+        Err(e) => {
+            error!("Soru güncellenirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Soru güncellenemedi"
+            }))
+        }
+    }
+}
+
+// Setin sorularını tek istekte oluştur, güncelle veya sil. İşlemler tek bir veritabanı işleminde uygulanır;
+// herhangi bir öğe başarısız olursa hiçbir değişiklik kaydedilmez. Yanıtta her öğenin sonucu yer alır.
+pub async fn bulk_question_operations(
+    pool: web::Data<Pool<Postgres>>,
+    set_id: web::Path<i32>,
+    bulk_dto: web::Json<BulkQuestionsDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let set_id_inner = set_id.into_inner();
+    let operations = bulk_dto.into_inner().operations;
+    
+    if operations.is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "En az bir işlem gönderilmelidir"
+        }));
+    }
+    if operations.len() > question_edit::MAX_BULK_OPERATIONS {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("Tek istekte en fazla {} işlem gönderilebilir", question_edit::MAX_BULK_OPERATIONS)
+        }));
+    }
+    
+    let question_set = sqlx::query!(
+        "SELECT creator_id, visibility FROM question_sets WHERE id = $1 AND deleted_at IS NULL",
+        set_id_inner
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    let question_set = match question_set {
+        Ok(Some(question_set)) => question_set,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Soru seti bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Sorular güncellenemedi"
+            }));
+        }
+    };
+    
+    let access = collaboration::access_level(
+        &pool, user_id, set_id_inner, question_set.creator_id, &question_set.visibility,
+    )
+    .await;
+    
+    if access < SetAccess::Write && claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu soru setini düzenleme izniniz yok"
+        }));
+    }
+    
+    match question_edit::apply_bulk(&pool, set_id_inner, user_id, &operations).await {
+        Ok(outcome) if outcome.applied => {
+            info!("Soru setinde toplu işlem uygulandı: {} ({} işlem)", set_id_inner, operations.len());
+            HttpResponse::Ok().json(serde_json::json!({
+                "applied": true,
+                "results": outcome.results
+            }))
+        }
+        Ok(outcome) => HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Bazı işlemler uygulanamadı, hiçbir değişiklik kaydedilmedi",
+            "applied": false,
+            "results": outcome.results
+        })),
+        Err(e) => {
+            error!("Toplu soru işlemi sırasında hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Sorular güncellenemedi"
+            }))
+        }
+    }
+}
+
+// Sorunun oynandığı tüm oyunlardaki performansı: oyun bazında doğruluk, ortalama yanıt süresi ve şık dağılımı.
+// Öğretmenlerin yeniden yazılması gereken soruları fark etmesi için
+pub async fn get_question_stats(
+    pool: web::Data<Pool<Postgres>>,
+    question_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let question = sqlx::query!(
+        r#"
+        SELECT q.id, q.question_text, q.correct_option, q.question_set_id, qs.creator_id, qs.visibility
+        FROM questions q
+        JOIN question_sets qs ON q.question_set_id = qs.id
+        WHERE q.id = $1 AND q.deleted_at IS NULL AND qs.deleted_at IS NULL
+        "#,
+        question_id.into_inner()
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    let question = match question {
+        Ok(Some(question)) => question,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Soru bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Soru istatistikleri alınamadı"
+            }));
+        }
+    };
+    
+    // İstatistikleri soru setini düzenleyebilenler görür
+    let access = collaboration::access_level(
+        &pool, user_id, question.question_set_id, question.creator_id, &question.visibility,
+    )
+    .await;
+    
+    if access < SetAccess::Write && claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu sorunun istatistiklerini görme izniniz yok"
+        }));
+    }
+    
+    // Oyun bazında doğruluk (en son oynanan 50 oyun, eskiden yeniye)
+    let per_game = sqlx::query!(
+        r#"
+        SELECT * FROM (
+            SELECT g.id as game_id, g.code, COALESCE(g.started_at, g.created_at) as played_at,
+                   COUNT(pa.id) as "answers!",
+                   COUNT(pa.id) FILTER (WHERE pa.is_correct) as "correct!",
+                   AVG(pa.response_time_ms)::float8 as avg_response_time_ms
+            FROM player_answers pa
+            JOIN players p ON pa.player_id = p.id
+            JOIN games g ON p.game_id = g.id
+            WHERE pa.question_id = $1
+            GROUP BY g.id
+            ORDER BY played_at DESC
+            LIMIT 50
+        ) recent
+        ORDER BY played_at
+        "#,
+        question.id
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    let distribution = sqlx::query!(
+        r#"
+        SELECT COALESCE(answer, 'X') as "answer!", COUNT(*) as "count!"
+        FROM player_answers
+        WHERE question_id = $1
+        GROUP BY 1
+        "#,
+        question.id
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    let totals = sqlx::query!(
+        r#"
+        SELECT COUNT(*) as "answers!",
+               COUNT(*) FILTER (WHERE pa.is_correct) as "correct!",
+               COUNT(DISTINCT p.game_id) as "games!",
+               AVG(pa.response_time_ms)::float8 as avg_response_time_ms
+        FROM player_answers pa
+        JOIN players p ON pa.player_id = p.id
+        WHERE pa.question_id = $1
+        "#,
+        question.id
+    )
+    .fetch_one(&**pool)
+    .await;
+    
+    match (per_game, distribution, totals) {
+        (Ok(per_game), Ok(distribution), Ok(totals)) => {
+            let accuracy = |correct: i64, answers: i64| {
+                if answers > 0 {
+                    (correct as f64 / answers as f64 * 1000.0).round() / 10.0
+                } else {
+                    0.0
+                }
+            };
+            
+            // Hiç seçilmemiş şıklar da 0 ile listelenir
+            let option_distribution: serde_json::Map<String, serde_json::Value> = ["A", "B", "C", "D", "X"]
+                .iter()
+                .map(|option| {
+                    let count = distribution.iter().find(|d| d.answer.trim() == *option).map(|d| d.count).unwrap_or(0);
+                    (option.to_string(), serde_json::json!(count))
+                })
+                .collect();
+            
+            let history: Vec<serde_json::Value> = per_game
+                .iter()
+                .map(|g| {
+                    serde_json::json!({
+                        "game_id": g.game_id,
+                        "game_code": g.code,
+                        "played_at": g.played_at,
+                        "answers": g.answers,
+                        "accuracy": accuracy(g.correct, g.answers),
+                        "avg_response_time_ms": g.avg_response_time_ms.map(|t| t.round())
+                    })
+                })
+                .collect();
+            
+            HttpResponse::Ok().json(serde_json::json!({
+                "question_id": question.id,
+                "question_text": question.question_text,
+                "correct_option": question.correct_option,
+                "games_played": totals.games,
+                "total_answers": totals.answers,
+                "accuracy": accuracy(totals.correct, totals.answers),
+                "avg_response_time_ms": totals.avg_response_time_ms.map(|t| t.round()),
+                "option_distribution": option_distribution,
+                "accuracy_over_time": history
+            }))
+        }
+        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Soru istatistikleri alınamadı"
+            }))
+        }
+    }
+}
+
+// Soru setinin oyun öncesi kontrol listesi: hatalar ve uyarılar
+pub async fn validate_question_set(
+    pool: web::Data<Pool<Postgres>>,
+    set_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let set = sqlx::query!(
+        "SELECT id, creator_id, visibility FROM question_sets WHERE id = $1 AND deleted_at IS NULL",
+        set_id.into_inner()
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match set {
+        Ok(Some(set)) => {
+            let access = collaboration::access_level(&pool, user_id, set.id, set.creator_id, &set.visibility).await;
+            if claims.role != "admin" && access == SetAccess::None {
+                return HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": "Bu soru setine erişim izniniz yok"
+                }));
+            }
+            
+            let questions = sqlx::query!(
+                r#"
+                SELECT id, position, question_text, option_a, option_b, option_c, option_d, time_limit
+                FROM questions
+                WHERE question_set_id = $1 AND deleted_at IS NULL
+                ORDER BY position, id
+                "#,
+                set.id
+            )
+            .fetch_all(&**pool)
+            .await;
+            
+            match questions {
+                Ok(questions) => {
+                    let inputs: Vec<QuestionInput> = questions
+                        .into_iter()
+                        .map(|q| QuestionInput {
+                            id: q.id,
+                            position: q.position,
+                            question_text: q.question_text,
+                            options: [q.option_a, q.option_b, q.option_c, q.option_d],
+                            time_limit: q.time_limit,
+                        })
+                        .collect();
+                    
+                    let issues = set_validation::validate_questions(&inputs);
+                    let error_count = issues.iter().filter(|i| i.severity == set_validation::SEVERITY_ERROR).count();
+                    
+                    HttpResponse::Ok().json(serde_json::json!({
+                        "question_set_id": set.id,
+                        "question_count": inputs.len(),
+                        "is_valid": error_count == 0,
+                        "error_count": error_count,
+                        "warning_count": issues.len() - error_count,
+                        "issues": issues
+                    }))
+                }
+                Err(e) => {
+                    error!("Veritabanı sorgu hatası: {}", e);
+                    HttpResponse::InternalServerError().json(serde_json::json!({
+                        "error": "Soru seti doğrulanamadı"
+                    }))
+                }
+            }
+        }
+        Ok(None) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Soru seti bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Soru seti doğrulanamadı"
+            }))
+        }
+    }
+}
+
+// Önizleme hatasını HTTP yanıtına çevir
+fn preview_error_response(e: PreviewError) -> HttpResponse {
+    let body = serde_json::json!({ "error": e.message() });
+    match e {
+        PreviewError::NotFound => HttpResponse::NotFound().json(body),
+        PreviewError::Database => HttpResponse::InternalServerError().json(body),
+        _ => HttpResponse::BadRequest().json(body),
+    }
+}
+
+// Soru setini öğrenci gibi tek başına oynamak için önizleme başlat (istatistiklere kaydedilmez)
+pub async fn start_preview(
+    pool: web::Data<Pool<Postgres>>,
+    set_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    if claims.role != "teacher" && claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Sadece öğretmenler soru setlerini önizleyebilir"
+        }));
+    }
+    
+    let set = sqlx::query!(
+        "SELECT id, creator_id, title, visibility FROM question_sets WHERE id = $1 AND deleted_at IS NULL",
+        set_id.into_inner()
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match set {
+        Ok(Some(set)) => {
+            let access = collaboration::access_level(&pool, user_id, set.id, set.creator_id, &set.visibility).await;
+            if claims.role != "admin" && access == SetAccess::None {
+                return HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": "Bu soru setine erişim izniniz yok"
+                }));
+            }
+            
+            match preview::start_preview(&pool, user_id, set.id).await {
+                Ok((preview_id, question)) => HttpResponse::Ok().json(serde_json::json!({
+                    "preview_id": preview_id,
+                    "question_set_id": set.id,
+                    "title": set.title,
+                    "question": question.player_message()
+                })),
+                Err(e) => preview_error_response(e),
+            }
+        }
+        Ok(None) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Soru seti bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Önizleme başlatılamadı"
+            }))
+        }
+    }
+}
+
+// Önizlemedeki açık soruya cevap ver; sonuçla birlikte sıradaki soru ya da önizleme özeti döner
+pub async fn answer_preview(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<(i32, String)>,
+    answer_dto: web::Json<PreviewAnswerDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let (_, preview_id) = path.into_inner();
+    
+    match preview::submit_answer(&pool, user_id, &preview_id, answer_dto.question_id, &answer_dto.answer).await {
+        Ok(result) => HttpResponse::Ok().json(serde_json::json!({
+            "question_id": result.question_id,
+            "is_correct": result.is_correct,
+            "correct_option": result.correct_option,
+            "points_earned": result.points_earned,
+            "score": result.score,
+            "correct_count": result.correct_count,
+            "answered_count": result.answered_count,
+            "completed": result.next_question.is_none(),
+            "next_question": result.next_question.map(|q| q.player_message())
+        })),
+        Err(e) => preview_error_response(e),
+    }
 }
\ No newline at end of file
@@ -0,0 +1,157 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use chrono::Utc;
+use log::{error, info};
+use sqlx::{Pool, Postgres};
+
+use crate::db::models::{Claims, CreateReportDto};
+use crate::services::guest_sessions;
+use crate::utils::security::parse_player_token;
+
+// Uygunsuz içerik şikayeti oluştur (soru seti veya takma ad)
+pub async fn create_report(
+    req: HttpRequest,
+    pool: web::Data<Pool<Postgres>>,
+    report_dto: web::Json<CreateReportDto>,
+    claims: Option<Claims>,
+) -> impl Responder {
+    // Şikayet tipini kontrol et
+    if report_dto.target_type != "question_set" && report_dto.target_type != "nickname" {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Şikayet tipi 'question_set' veya 'nickname' olmalıdır"
+        }));
+    }
+
+    let reason = report_dto.reason.trim();
+    if reason.is_empty() || reason.chars().count() > 1000 {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Şikayet nedeni 1-1000 karakter arasında olmalıdır"
+        }));
+    }
+
+    // Şikayet edeni belirle: oturum açmış kullanıcı veya katılımda aldığı player-token ile oyuncu
+    let reporter_user_id = claims.as_ref().map(|c| c.sub.parse::<i32>().unwrap_or_default());
+    let mut reporter_player_id = None;
+
+    if reporter_user_id.is_none() {
+        let token = req
+            .headers()
+            .get("player-token")
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_player_token);
+
+        let player_id = match token {
+            Some(token) => match guest_sessions::player_session(&pool, &token).await {
+                Ok(session) => session.map(|_| token.player_id),
+                Err(e) => {
+                    error!("Veritabanı sorgu hatası: {}", e);
+                    return HttpResponse::InternalServerError().json(serde_json::json!({
+                        "error": "Şikayet kaydedilemedi"
+                    }));
+                }
+            },
+            None => None,
+        };
+
+        match player_id {
+            Some(player_id) => reporter_player_id = Some(player_id),
+            None => {
+                return HttpResponse::Unauthorized().json(serde_json::json!({
+                    "error": "Şikayet için giriş yapmalı veya aktif bir oyunda olmalısınız"
+                }));
+            }
+        }
+    }
+
+    // Şikayet edilen içeriğin varlığını kontrol et
+    let target_exists = if report_dto.target_type == "question_set" {
+        sqlx::query!(
+            r#"SELECT EXISTS (SELECT 1 FROM question_sets WHERE id = $1) as "exists!""#,
+            report_dto.target_id
+        )
+        .fetch_one(&**pool)
+        .await
+        .map(|r| r.exists)
+    } else {
+        sqlx::query!(
+            r#"SELECT EXISTS (SELECT 1 FROM players WHERE id = $1) as "exists!""#,
+            report_dto.target_id
+        )
+        .fetch_one(&**pool)
+        .await
+        .map(|r| r.exists)
+    };
+
+    match target_exists {
+        Ok(true) => {}
+        Ok(false) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Şikayet edilen içerik bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Şikayet kaydedilemedi"
+            }));
+        }
+    }
+
+    // Aynı kişinin aynı içerik için bekleyen şikayeti var mı kontrol et
+    let existing_report = sqlx::query!(
+        r#"
+        SELECT id FROM content_reports
+        WHERE target_type = $1 AND target_id = $2 AND status = 'pending'
+          AND (reporter_user_id = $3 OR reporter_player_id = $4)
+        "#,
+        report_dto.target_type,
+        report_dto.target_id,
+        reporter_user_id,
+        reporter_player_id
+    )
+    .fetch_optional(&**pool)
+    .await;
+
+    if let Ok(Some(_)) = existing_report {
+        return HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Bu içerik için zaten bekleyen bir şikayetiniz var"
+        }));
+    }
+
+    // Şikayeti kaydet
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO content_reports (target_type, target_id, reporter_user_id, reporter_player_id, reason, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, status
+        "#,
+        report_dto.target_type,
+        report_dto.target_id,
+        reporter_user_id,
+        reporter_player_id,
+        reason,
+        Utc::now()
+    )
+    .fetch_one(&**pool)
+    .await;
+
+    match result {
+        Ok(report) => {
+            info!(
+                "İçerik şikayeti oluşturuldu: id={}, {}={}",
+                report.id, report_dto.target_type, report_dto.target_id
+            );
+
+            HttpResponse::Created().json(serde_json::json!({
+                "id": report.id,
+                "status": report.status,
+                "message": "Şikayetiniz alındı, yöneticiler tarafından incelenecek"
+            }))
+        }
+        Err(e) => {
+            error!("Şikayet kaydedilirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Şikayet kaydedilemedi"
+            }))
+        }
+    }
+}
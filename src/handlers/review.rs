@@ -0,0 +1,327 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use log::{error, info};
+use sqlx::{Pool, Postgres};
+
+use crate::db::models::{Claims, CreateCommentDto, HideCommentDto, RateQuestionSetDto};
+use crate::services::collaboration::{self, SetAccess};
+
+// Puan ve yorumlar yalnızca paylaşılmış (kurum içi veya herkese açık) setlerde kullanılabilir
+// Erişim uygunsa set sahibinin id'sini, değilse istemciye dönecek yanıtı döndürür
+async fn reviewable_set(pool: &Pool<Postgres>, set_id: i32, user_id: i32) -> Result<i32, HttpResponse> {
+    let set = sqlx::query!(
+        "SELECT creator_id, visibility, is_hidden FROM question_sets WHERE id = $1 AND deleted_at IS NULL",
+        set_id
+    )
+    .fetch_optional(pool)
+    .await;
+    
+    match set {
+        Ok(Some(set)) => {
+            if set.visibility == "private" || set.is_hidden {
+                return Err(HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "Bu soru seti paylaşılmadığı için puanlanamaz veya yorumlanamaz"
+                })));
+            }
+            
+            let access = collaboration::access_level(pool, user_id, set_id, set.creator_id, &set.visibility).await;
+            if access == SetAccess::None {
+                return Err(HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": "Bu soru setine erişim izniniz yok"
+                })));
+            }
+            
+            Ok(set.creator_id)
+        }
+        Ok(None) => Err(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Soru seti bulunamadı"
+        }))),
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            Err(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Soru seti alınamadı"
+            })))
+        }
+    }
+}
+
+// Soru setini puanla (1-5); aynı kullanıcının önceki puanı güncellenir
+pub async fn rate_question_set(
+    pool: web::Data<Pool<Postgres>>,
+    set_id: web::Path<i32>,
+    rating_dto: web::Json<RateQuestionSetDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let set_id_inner = set_id.into_inner();
+    
+    if !(1..=5).contains(&rating_dto.rating) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Puan 1 ile 5 arasında olmalıdır"
+        }));
+    }
+    
+    let creator_id = match reviewable_set(&pool, set_id_inner, user_id).await {
+        Ok(creator_id) => creator_id,
+        Err(response) => return response,
+    };
+    
+    if creator_id == user_id {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Kendi soru setinizi puanlayamazsınız"
+        }));
+    }
+    
+    let now = Utc::now();
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO question_set_ratings (question_set_id, user_id, rating, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $4)
+        ON CONFLICT (question_set_id, user_id) DO UPDATE SET rating = EXCLUDED.rating, updated_at = EXCLUDED.updated_at
+        "#,
+        set_id_inner,
+        user_id,
+        rating_dto.rating,
+        now
+    )
+    .execute(&**pool)
+    .await;
+    
+    if let Err(e) = result {
+        error!("Puan kaydedilirken hata: {}", e);
+        return HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Puan kaydedilemedi"
+        }));
+    }
+    
+    let summary = sqlx::query!(
+        r#"
+        SELECT AVG(rating)::float8 as "average_rating!", COUNT(*) as "rating_count!"
+        FROM question_set_ratings WHERE question_set_id = $1
+        "#,
+        set_id_inner
+    )
+    .fetch_one(&**pool)
+    .await;
+    
+    match summary {
+        Ok(summary) => HttpResponse::Ok().json(serde_json::json!({
+            "question_set_id": set_id_inner,
+            "rating": rating_dto.rating,
+            "average_rating": summary.average_rating,
+            "rating_count": summary.rating_count
+        })),
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Puan özeti alınamadı"
+            }))
+        }
+    }
+}
+
+// Soru setinin yorumlarını listele
+// Gizlenen yorumları yalnızca set sahibi ve adminler görür
+pub async fn list_comments(
+    pool: web::Data<Pool<Postgres>>,
+    set_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let set_id_inner = set_id.into_inner();
+    
+    let creator_id = match reviewable_set(&pool, set_id_inner, user_id).await {
+        Ok(creator_id) => creator_id,
+        Err(response) => return response,
+    };
+    let is_moderator = creator_id == user_id || claims.role == "admin";
+    
+    let comments = sqlx::query!(
+        r#"
+        SELECT c.id, c.user_id, u.username, c.body, c.is_hidden, c.created_at
+        FROM question_set_comments c
+        JOIN users u ON c.user_id = u.id
+        WHERE c.question_set_id = $1 AND ($2 OR c.is_hidden = false)
+        ORDER BY c.created_at DESC
+        "#,
+        set_id_inner,
+        is_moderator
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    match comments {
+        Ok(comments) => {
+            let comments_json: Vec<serde_json::Value> = comments
+                .into_iter()
+                .map(|c| {
+                    serde_json::json!({
+                        "id": c.id,
+                        "user_id": c.user_id,
+                        "username": c.username,
+                        "body": c.body,
+                        "is_hidden": c.is_hidden,
+                        "created_at": c.created_at
+                    })
+                })
+                .collect();
+            
+            HttpResponse::Ok().json(serde_json::json!({
+                "comments": comments_json
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Yorumlar alınamadı"
+            }))
+        }
+    }
+}
+
+// Soru setine yorum yaz
+pub async fn create_comment(
+    pool: web::Data<Pool<Postgres>>,
+    set_id: web::Path<i32>,
+    comment_dto: web::Json<CreateCommentDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let set_id_inner = set_id.into_inner();
+    
+    let body = comment_dto.body.trim();
+    if body.is_empty() || body.chars().count() > 2000 {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Yorum 1-2000 karakter arasında olmalıdır"
+        }));
+    }
+    
+    if let Err(response) = reviewable_set(&pool, set_id_inner, user_id).await {
+        return response;
+    }
+    
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO question_set_comments (question_set_id, user_id, body, created_at)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, created_at
+        "#,
+        set_id_inner,
+        user_id,
+        body,
+        Utc::now()
+    )
+    .fetch_one(&**pool)
+    .await;
+    
+    match result {
+        Ok(comment) => {
+            info!("Yorum eklendi: id={}, set_id={}", comment.id, set_id_inner);
+            HttpResponse::Created().json(serde_json::json!({
+                "id": comment.id,
+                "question_set_id": set_id_inner,
+                "body": body,
+                "created_at": comment.created_at
+            }))
+        }
+        Err(e) => {
+            error!("Yorum eklenirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Yorum eklenemedi"
+            }))
+        }
+    }
+}
+
+// Yorumu sil (yorum sahibi, set sahibi veya admin)
+pub async fn delete_comment(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<(i32, i32)>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let (set_id, comment_id) = path.into_inner();
+    
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM question_set_comments c
+        USING question_sets qs
+        WHERE c.id = $1 AND c.question_set_id = $2 AND qs.id = c.question_set_id
+          AND (c.user_id = $3 OR qs.creator_id = $3 OR $4)
+        "#,
+        comment_id,
+        set_id,
+        user_id,
+        claims.role == "admin"
+    )
+    .execute(&**pool)
+    .await;
+    
+    match result {
+        Ok(done) if done.rows_affected() > 0 => {
+            info!("Yorum silindi: id={}, set_id={}", comment_id, set_id);
+            HttpResponse::Ok().json(serde_json::json!({
+                "message": "Yorum silindi"
+            }))
+        }
+        Ok(_) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Yorum bulunamadı veya silme izniniz yok"
+            }))
+        }
+        Err(e) => {
+            error!("Yorum silinirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Yorum silinemedi"
+            }))
+        }
+    }
+}
+
+// Yorumu gizle veya tekrar göster (set sahibi veya admin)
+pub async fn hide_comment(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<(i32, i32)>,
+    hide_dto: web::Json<HideCommentDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let (set_id, comment_id) = path.into_inner();
+    
+    let result = sqlx::query!(
+        r#"
+        UPDATE question_set_comments c SET is_hidden = $1
+        FROM question_sets qs
+        WHERE c.id = $2 AND c.question_set_id = $3 AND qs.id = c.question_set_id
+          AND (qs.creator_id = $4 OR $5)
+        "#,
+        hide_dto.hidden,
+        comment_id,
+        set_id,
+        user_id,
+        claims.role == "admin"
+    )
+    .execute(&**pool)
+    .await;
+    
+    match result {
+        Ok(done) if done.rows_affected() > 0 => {
+            info!("Yorum görünürlüğü güncellendi: id={}, hidden={}", comment_id, hide_dto.hidden);
+            HttpResponse::Ok().json(serde_json::json!({
+                "id": comment_id,
+                "is_hidden": hide_dto.hidden
+            }))
+        }
+        Ok(_) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Yorum bulunamadı veya düzenleme izniniz yok"
+            }))
+        }
+        Err(e) => {
+            error!("Yorum güncellenirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Yorum güncellenemedi"
+            }))
+        }
+    }
+}
\ No newline at end of file
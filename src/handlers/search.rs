@@ -0,0 +1,139 @@
+use actix_web::{web, HttpResponse, Responder};
+use log::error;
+use sqlx::{Pool, Postgres};
+
+use crate::db::models::{Claims, SearchQuery};
+
+// Kullanıcının kendi içeriğinde tam metin arama (soru setleri, sorular, geçmiş oyunlar)
+// Sonuçlar "type" alanıyla ayrıştırılır, her tür kendi içinde alaka düzeyine göre sıralanır
+pub async fn search(
+    pool: web::Data<Pool<Postgres>>,
+    query: web::Query<SearchQuery>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let q = query.q.trim();
+    if q.chars().count() < 2 || q.chars().count() > 200 {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Arama metni 2-200 karakter arasında olmalıdır"
+        }));
+    }
+    let limit = query.limit.unwrap_or(10).clamp(1, 50);
+    
+    // Sahibi veya ortak editörü olunan, çöp kutusunda olmayan setler
+    let sets = sqlx::query!(
+        r#"
+        SELECT qs.id, qs.title, qs.archived_at,
+               ts_rank(qs.search_vector, websearch_to_tsquery('turkish', $2))::float8 as "rank!",
+               ts_headline('turkish', coalesce(qs.description, qs.title), websearch_to_tsquery('turkish', $2)) as "snippet!"
+        FROM question_sets qs
+        WHERE qs.search_vector @@ websearch_to_tsquery('turkish', $2)
+          AND qs.deleted_at IS NULL
+          AND (qs.creator_id = $1 OR EXISTS (
+              SELECT 1 FROM question_set_editors e WHERE e.question_set_id = qs.id AND e.user_id = $1
+          ))
+        ORDER BY 4 DESC
+        LIMIT $3
+        "#,
+        user_id,
+        q,
+        limit
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    let questions = sqlx::query!(
+        r#"
+        SELECT q.id, q.question_set_id, qs.title as question_set_title,
+               ts_rank(q.search_vector, websearch_to_tsquery('turkish', $2))::float8 as "rank!",
+               ts_headline('turkish', q.question_text, websearch_to_tsquery('turkish', $2)) as "snippet!"
+        FROM questions q
+        JOIN question_sets qs ON q.question_set_id = qs.id
+        WHERE q.search_vector @@ websearch_to_tsquery('turkish', $2)
+          AND q.deleted_at IS NULL AND qs.deleted_at IS NULL
+          AND (qs.creator_id = $1 OR EXISTS (
+              SELECT 1 FROM question_set_editors e WHERE e.question_set_id = qs.id AND e.user_id = $1
+          ))
+        ORDER BY 4 DESC
+        LIMIT $3
+        "#,
+        user_id,
+        q,
+        limit
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    // Yönetilen oyunlar, soru seti başlığına veya oyun koduna göre bulunur
+    let games = sqlx::query!(
+        r#"
+        SELECT g.id, g.code, g.status, g.created_at, g.ended_at, qs.title as question_set_title,
+               (CASE WHEN upper(g.code) = upper($2) THEN 1.0
+                     ELSE ts_rank(qs.search_vector, websearch_to_tsquery('turkish', $2)) END)::float8 as "rank!"
+        FROM games g
+        JOIN question_sets qs ON g.question_set_id = qs.id
+        WHERE g.host_id = $1
+          AND (upper(g.code) = upper($2) OR qs.search_vector @@ websearch_to_tsquery('turkish', $2))
+        ORDER BY 7 DESC, g.created_at DESC
+        LIMIT $3
+        "#,
+        user_id,
+        q,
+        limit
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    match (sets, questions, games) {
+        (Ok(sets), Ok(questions), Ok(games)) => {
+            let mut results: Vec<serde_json::Value> = Vec::new();
+            
+            results.extend(sets.into_iter().map(|s| {
+                serde_json::json!({
+                    "type": "question_set",
+                    "id": s.id,
+                    "title": s.title,
+                    "snippet": s.snippet,
+                    "is_archived": s.archived_at.is_some(),
+                    "rank": s.rank
+                })
+            }));
+            
+            results.extend(questions.into_iter().map(|q| {
+                serde_json::json!({
+                    "type": "question",
+                    "id": q.id,
+                    "question_set_id": q.question_set_id,
+                    "title": q.question_set_title,
+                    "snippet": q.snippet,
+                    "rank": q.rank
+                })
+            }));
+            
+            results.extend(games.into_iter().map(|g| {
+                serde_json::json!({
+                    "type": "game",
+                    "id": g.id,
+                    "code": g.code,
+                    "title": g.question_set_title,
+                    "status": g.status,
+                    "created_at": g.created_at,
+                    "ended_at": g.ended_at,
+                    "rank": g.rank
+                })
+            }));
+            
+            HttpResponse::Ok().json(serde_json::json!({
+                "query": q,
+                "results": results
+            }))
+        }
+        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Arama yapılamadı"
+            }))
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,141 @@
+use actix_web::{web, HttpResponse, Responder};
+use log::error;
+use sqlx::{Pool, Postgres};
+
+use crate::db::models::{Claims, SeasonLeaderboardQuery};
+use crate::services::{organization, seasons};
+
+// Sezonları listele
+pub async fn list_seasons(pool: web::Data<Pool<Postgres>>) -> impl Responder {
+    if let Err(e) = seasons::ensure_current_season(&pool).await {
+        error!("Geçerli sezon oluşturulamadı: {}", e);
+    }
+    
+    let seasons = sqlx::query!(
+        r#"
+        SELECT id, name, starts_on, ends_on, archived_at IS NOT NULL as "archived!"
+        FROM seasons
+        ORDER BY starts_on DESC
+        "#
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    match seasons {
+        Ok(seasons) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "seasons": seasons.iter().map(|s| {
+                    serde_json::json!({
+                        "id": s.id,
+                        "name": s.name,
+                        "starts_on": s.starts_on,
+                        "ends_on": s.ends_on,
+                        "archived": s.archived
+                    })
+                }).collect::<Vec<_>>()
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Sezonlar alınamadı"
+            }))
+        }
+    }
+}
+
+// Sezon sıralaması ("current" veya sezon id); varsayılan olarak kullanıcının kurumuyla sınırlıdır
+pub async fn get_season_leaderboard(
+    pool: web::Data<Pool<Postgres>>,
+    season: web::Path<String>,
+    query: web::Query<SeasonLeaderboardQuery>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let season_inner = season.into_inner();
+    
+    let season_id = if season_inner == "current" {
+        match seasons::ensure_current_season(&pool).await {
+            Ok(id) => id,
+            Err(e) => {
+                error!("Geçerli sezon alınamadı: {}", e);
+                return HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Sezon sıralaması alınamadı"
+                }));
+            }
+        }
+    } else {
+        match season_inner.parse::<i32>() {
+            Ok(id) => id,
+            Err(_) => {
+                return HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "Geçersiz sezon"
+                }));
+            }
+        }
+    };
+    
+    // Kurum kapsamı: kurumu olan kullanıcılar varsayılan olarak kendi kurumunu görür
+    let organization_id = match query.scope.as_deref() {
+        Some("global") => None,
+        Some("organization") | None => organization::membership(&claims).organization_id,
+        Some(_) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Kapsam 'organization' veya 'global' olmalıdır"
+            }));
+        }
+    };
+    
+    let season = sqlx::query!(
+        "SELECT id, name, archived_at IS NOT NULL as \"archived!\" FROM seasons WHERE id = $1",
+        season_id
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    let season = match season {
+        Ok(Some(season)) => season,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Sezon bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Sezon sıralaması alınamadı"
+            }));
+        }
+    };
+    
+    let standings = seasons::standings(&pool, season.id, organization_id, season.archived).await;
+    
+    match standings {
+        Ok(standings) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "season_id": season.id,
+                "season": season.name,
+                "archived": season.archived,
+                "organization_id": organization_id,
+                "standings": standings.iter().enumerate().map(|(i, s)| {
+                    serde_json::json!({
+                        "rank": i + 1,
+                        "user_id": s.user_id,
+                        "username": s.username,
+                        "avatar": s.avatar,
+                        "rating": s.rating,
+                        "games_played": s.games_played,
+                        "wins": s.wins,
+                        "is_me": s.user_id == user_id
+                    })
+                }).collect::<Vec<_>>()
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Sezon sıralaması alınamadı"
+            }))
+        }
+    }
+}
\ No newline at end of file
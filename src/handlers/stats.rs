@@ -0,0 +1,13 @@
+use actix_web::{web, HttpResponse, Responder};
+
+use crate::handlers::websocket::AppState;
+
+// Site genelindeki canlı bağlantı ve oyun sayısı
+pub async fn get_live_stats(app_state: web::Data<AppState>) -> impl Responder {
+    let (active_connections, active_games) = app_state.live_counts().await;
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "active_connections": active_connections,
+        "active_games": active_games
+    }))
+}
\ No newline at end of file
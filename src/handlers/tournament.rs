@@ -0,0 +1,374 @@
+use actix_web::{web, HttpResponse, Responder};
+use log::{error, info};
+use sqlx::{Pool, Postgres};
+
+use crate::db::models::{AddTournamentRoundDto, Claims, CreateTournamentDto};
+use crate::services::tournament;
+
+// Turnuvayı bul ve kullanıcının turnuva sahibi (veya admin) olduğunu doğrula; turnuvanın durumunu döndürür
+async fn find_hosted_tournament(pool: &Pool<Postgres>, tournament_id: i32, claims: &Claims) -> Result<String, HttpResponse> {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let tournament = sqlx::query!("SELECT host_id, status FROM tournaments WHERE id = $1", tournament_id)
+        .fetch_optional(pool)
+        .await;
+    
+    match tournament {
+        Ok(Some(t)) if t.host_id == user_id || claims.role == "admin" => Ok(t.status),
+        Ok(Some(_)) => Err(HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlemi yalnızca turnuva sahibi yapabilir"
+        }))),
+        Ok(None) => Err(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Turnuva bulunamadı"
+        }))),
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            Err(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Turnuva bilgileri alınamadı"
+            })))
+        }
+    }
+}
+
+// Turnuva oluştur
+pub async fn create_tournament(
+    pool: web::Data<Pool<Postgres>>,
+    tournament_dto: web::Json<CreateTournamentDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let name = tournament_dto.name.trim();
+    
+    if name.is_empty() || name.chars().count() > tournament::MAX_NAME_LEN {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("Turnuva adı 1-{} karakter olmalıdır", tournament::MAX_NAME_LEN)
+        }));
+    }
+    
+    let result = sqlx::query!(
+        "INSERT INTO tournaments (host_id, name) VALUES ($1, $2) RETURNING id, status, created_at",
+        user_id,
+        name
+    )
+    .fetch_one(&**pool)
+    .await;
+    
+    match result {
+        Ok(t) => {
+            info!("Turnuva oluşturuldu: id={}", t.id);
+            HttpResponse::Created().json(serde_json::json!({
+                "id": t.id,
+                "name": name,
+                "status": t.status,
+                "created_at": t.created_at
+            }))
+        }
+        Err(e) => {
+            error!("Turnuva oluşturulurken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Turnuva oluşturulamadı"
+            }))
+        }
+    }
+}
+
+// Öğretmenin turnuvalarını listele
+pub async fn list_tournaments(
+    pool: web::Data<Pool<Postgres>>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let tournaments = sqlx::query!(
+        r#"
+        SELECT t.id, t.name, t.status, t.created_at, t.completed_at,
+               (SELECT COUNT(*) FROM tournament_rounds r WHERE r.tournament_id = t.id) as "round_count!",
+               (SELECT COUNT(*) FROM tournament_participants tp WHERE tp.tournament_id = t.id) as "participant_count!"
+        FROM tournaments t
+        WHERE t.host_id = $1
+        ORDER BY t.created_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    match tournaments {
+        Ok(tournaments) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "tournaments": tournaments.iter().map(|t| {
+                    serde_json::json!({
+                        "id": t.id,
+                        "name": t.name,
+                        "status": t.status,
+                        "round_count": t.round_count,
+                        "participant_count": t.participant_count,
+                        "created_at": t.created_at,
+                        "completed_at": t.completed_at
+                    })
+                }).collect::<Vec<_>>()
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Turnuvalar alınamadı"
+            }))
+        }
+    }
+}
+
+// Turnuva ayrıntıları ve turları
+pub async fn get_tournament(
+    pool: web::Data<Pool<Postgres>>,
+    tournament_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    let tournament_id = tournament_id.into_inner();
+    if let Err(response) = find_hosted_tournament(&pool, tournament_id, &claims).await {
+        return response;
+    }
+    
+    let tournament = sqlx::query!(
+        "SELECT id, name, status, created_at, completed_at FROM tournaments WHERE id = $1",
+        tournament_id
+    )
+    .fetch_one(&**pool)
+    .await;
+    
+    let rounds = sqlx::query!(
+        r#"
+        SELECT r.round_number, r.advance_count, r.completed_at, g.code, g.status,
+               (SELECT COUNT(*) FROM players p WHERE p.game_id = g.id AND p.is_active = true) as "player_count!"
+        FROM tournament_rounds r
+        JOIN games g ON r.game_id = g.id
+        WHERE r.tournament_id = $1
+        ORDER BY r.round_number
+        "#,
+        tournament_id
+    )
+    .fetch_all(&**pool)
+    .await;
+    
+    match (tournament, rounds) {
+        (Ok(t), Ok(rounds)) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "id": t.id,
+                "name": t.name,
+                "status": t.status,
+                "created_at": t.created_at,
+                "completed_at": t.completed_at,
+                "rounds": rounds.iter().map(|r| {
+                    serde_json::json!({
+                        "round": r.round_number,
+                        "game_code": r.code,
+                        "game_status": r.status,
+                        "advance_count": r.advance_count,
+                        "player_count": r.player_count,
+                        "completed_at": r.completed_at
+                    })
+                }).collect::<Vec<_>>()
+            }))
+        }
+        (Err(e), _) | (_, Err(e)) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Turnuva bilgileri alınamadı"
+            }))
+        }
+    }
+}
+
+// Lobide bekleyen bir oyunu turnuvanın sonraki turu olarak ekle; önceki turlar tamamlanmış olmalı
+pub async fn add_tournament_round(
+    pool: web::Data<Pool<Postgres>>,
+    tournament_id: web::Path<i32>,
+    round_dto: web::Json<AddTournamentRoundDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let tournament_id = tournament_id.into_inner();
+    
+    match find_hosted_tournament(&pool, tournament_id, &claims).await {
+        Ok(status) if status == "completed" => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Tamamlanmış bir turnuvaya tur eklenemez"
+            }));
+        }
+        Ok(_) => {}
+        Err(response) => return response,
+    }
+    
+    if round_dto.advance_count.is_some_and(|n| n <= 0) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Yükselecek katılımcı sayısı pozitif olmalıdır"
+        }));
+    }
+    
+    let game = sqlx::query!(
+        r#"
+        SELECT g.id, g.host_id, g.status,
+               EXISTS (SELECT 1 FROM tournament_rounds r WHERE r.game_id = g.id) as "in_tournament!"
+        FROM games g
+        WHERE g.code = $1
+        "#,
+        round_dto.game_code
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    let game = match game {
+        Ok(Some(game)) if game.host_id != user_id && claims.role != "admin" => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Yalnızca kendi oyunlarınızı tur olarak ekleyebilirsiniz"
+            }));
+        }
+        Ok(Some(game)) => game,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Oyun bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Tur eklenemedi"
+            }));
+        }
+    };
+    
+    if game.status != "lobby" {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Yalnızca lobide bekleyen oyunlar tur olarak eklenebilir"
+        }));
+    }
+    if game.in_tournament {
+        return HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Bu oyun zaten bir turnuvanın turu"
+        }));
+    }
+    
+    // Önceki tur tamamlanmadan yükselenler belli olmadığından yeni tur eklenemez
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO tournament_rounds (tournament_id, round_number, game_id, advance_count)
+        SELECT $1, COALESCE(MAX(round_number), 0) + 1, $2, $3
+        FROM tournament_rounds
+        WHERE tournament_id = $1
+        HAVING COUNT(*) FILTER (WHERE completed_at IS NULL) = 0
+        RETURNING round_number
+        "#,
+        tournament_id,
+        game.id,
+        round_dto.advance_count
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match result {
+        Ok(Some(round)) => {
+            info!("Turnuvaya tur eklendi: tournament_id={}, tur={}", tournament_id, round.round_number);
+            HttpResponse::Created().json(serde_json::json!({
+                "tournament_id": tournament_id,
+                "round": round.round_number,
+                "game_code": round_dto.game_code,
+                "advance_count": round_dto.advance_count
+            }))
+        }
+        Ok(None) => HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Önceki tur tamamlanmadan yeni tur eklenemez"
+        })),
+        Err(e) => {
+            error!("Turnuvaya tur eklenirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Tur eklenemedi"
+            }))
+        }
+    }
+}
+
+// Turnuvayı tamamla; yeni tur eklenemez
+pub async fn finish_tournament(
+    pool: web::Data<Pool<Postgres>>,
+    tournament_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    let tournament_id = tournament_id.into_inner();
+    if let Err(response) = find_hosted_tournament(&pool, tournament_id, &claims).await {
+        return response;
+    }
+    
+    let result = sqlx::query!(
+        r#"
+        UPDATE tournaments SET status = 'completed', completed_at = NOW()
+        WHERE id = $1 AND status = 'active'
+        RETURNING completed_at
+        "#,
+        tournament_id
+    )
+    .fetch_optional(&**pool)
+    .await;
+    
+    match result {
+        Ok(Some(t)) => {
+            info!("Turnuva tamamlandı: id={}", tournament_id);
+            HttpResponse::Ok().json(serde_json::json!({
+                "id": tournament_id,
+                "status": "completed",
+                "completed_at": t.completed_at
+            }))
+        }
+        Ok(None) => HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Turnuva zaten tamamlanmış"
+        })),
+        Err(e) => {
+            error!("Turnuva tamamlanırken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Turnuva tamamlanamadı"
+            }))
+        }
+    }
+}
+
+// Turnuva sıralaması: birikimli puan, oynanan tur sayısı ve elendiği tur
+pub async fn get_tournament_leaderboard(
+    pool: web::Data<Pool<Postgres>>,
+    tournament_id: web::Path<i32>,
+) -> impl Responder {
+    let tournament_id = tournament_id.into_inner();
+    
+    let tournament = sqlx::query!("SELECT name, status FROM tournaments WHERE id = $1", tournament_id)
+        .fetch_optional(&**pool)
+        .await;
+    
+    let tournament = match tournament {
+        Ok(Some(t)) => t,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Turnuva bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Turnuva sıralaması alınamadı"
+            }));
+        }
+    };
+    
+    // Sıralama her tur sonunda güncellenir ve hemen okunur; replika gecikmesi olmaması için ana havuzdan alınır
+    match tournament::standings(&pool, tournament_id, None).await {
+        Ok(standings) => HttpResponse::Ok().json(serde_json::json!({
+            "tournament_id": tournament_id,
+            "name": tournament.name,
+            "status": tournament.status,
+            "standings": standings
+        })),
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Turnuva sıralaması alınamadı"
+            }))
+        }
+    }
+}
@@ -0,0 +1,294 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use log::{error, info};
+use sqlx::{Pool, Postgres};
+
+use crate::db::models::{Claims, QuestionTranslationDto};
+use crate::services::collaboration::{self, SetAccess};
+use crate::services::localization;
+
+// Çevirisi yapılan soru ve kullanıcının soru setindeki yetkisi
+struct TranslatableQuestion {
+    id: i32,
+    question_set_id: i32,
+    language: String,
+    access: SetAccess,
+}
+
+async fn translatable_question(
+    pool: &Pool<Postgres>,
+    question_id: i32,
+    claims: &Claims,
+) -> Result<Option<TranslatableQuestion>, sqlx::Error> {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let question = sqlx::query!(
+        r#"
+        SELECT q.id, q.question_set_id, qs.creator_id, qs.visibility, qs.language
+        FROM questions q
+        JOIN question_sets qs ON q.question_set_id = qs.id
+        WHERE q.id = $1 AND q.deleted_at IS NULL AND qs.deleted_at IS NULL
+        "#,
+        question_id
+    )
+    .fetch_optional(pool)
+    .await?;
+    
+    let Some(question) = question else {
+        return Ok(None);
+    };
+    
+    // Adminler tüm setleri düzenleyebilir
+    let access = if claims.role == "admin" {
+        SetAccess::Owner
+    } else {
+        collaboration::access_level(pool, user_id, question.question_set_id, question.creator_id, &question.visibility).await
+    };
+    
+    Ok(Some(TranslatableQuestion {
+        id: question.id,
+        question_set_id: question.question_set_id,
+        language: question.language,
+        access,
+    }))
+}
+
+// Sorunun çevirilerini listele
+pub async fn list_translations(
+    pool: web::Data<Pool<Postgres>>,
+    question_id: web::Path<i32>,
+    claims: Claims,
+) -> impl Responder {
+    let question = match translatable_question(&pool, question_id.into_inner(), &claims).await {
+        Ok(Some(question)) if question.access > SetAccess::None => question,
+        Ok(Some(_)) => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu soru setine erişim izniniz yok"
+            }));
+        }
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Soru bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Çeviriler alınamadı"
+            }));
+        }
+    };
+    
+    match localization::question_translations(&pool, question.id).await {
+        Ok(translations) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "question_id": question.id,
+                "language": question.language,
+                "translations": translations.iter().map(|t| {
+                    serde_json::json!({
+                        "locale": t.locale,
+                        "question_text": t.question_text,
+                        "options": {
+                            "A": t.options[0],
+                            "B": t.options[1],
+                            "C": t.options[2],
+                            "D": t.options[3]
+                        }
+                    })
+                }).collect::<Vec<_>>()
+            }))
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Çeviriler alınamadı"
+            }))
+        }
+    }
+}
+
+// Sorunun bir dildeki çevirisini ekle veya güncelle (soru setini düzenleyebilenler)
+pub async fn upsert_translation(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<(i32, String)>,
+    translation_dto: web::Json<QuestionTranslationDto>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let (question_id, locale) = path.into_inner();
+    
+    let Some(locale) = localization::normalize_locale(&locale) else {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Geçersiz dil kodu"
+        }));
+    };
+    
+    let texts = [
+        &translation_dto.question_text,
+        &translation_dto.option_a,
+        &translation_dto.option_b,
+        &translation_dto.option_c,
+        &translation_dto.option_d,
+    ];
+    if texts.iter().any(|t| t.trim().is_empty()) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Çeviride soru metni ve tüm şıklar dolu olmalıdır"
+        }));
+    }
+    
+    let question = match translatable_question(&pool, question_id, &claims).await {
+        Ok(Some(question)) if question.access >= SetAccess::Write => question,
+        Ok(Some(_)) => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu soruyu güncelleme izniniz yok"
+            }));
+        }
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Soru bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Çeviri kaydedilemedi"
+            }));
+        }
+    };
+    
+    // Asıl metin zaten setin dilinde
+    if locale == question.language {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Soru setinin kendi dili için çeviri eklenemez"
+        }));
+    }
+    
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO question_translations (question_id, locale, question_text, option_a, option_b, option_c, option_d, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        ON CONFLICT (question_id, locale) DO UPDATE
+        SET question_text = EXCLUDED.question_text, option_a = EXCLUDED.option_a, option_b = EXCLUDED.option_b,
+            option_c = EXCLUDED.option_c, option_d = EXCLUDED.option_d, updated_at = EXCLUDED.updated_at
+        "#,
+        question.id,
+        locale,
+        translation_dto.question_text,
+        translation_dto.option_a,
+        translation_dto.option_b,
+        translation_dto.option_c,
+        translation_dto.option_d,
+        Utc::now()
+    )
+    .execute(&**pool)
+    .await;
+    
+    match result {
+        Ok(_) => {
+            // Soru seti güncelleme zamanını güncelle (ETag'ler bu zamandan üretilir)
+            let _ = sqlx::query!(
+                "UPDATE question_sets SET updated_at = $1 WHERE id = $2",
+                Utc::now(),
+                question.question_set_id
+            )
+            .execute(&**pool)
+            .await;
+            
+            collaboration::record_edit(
+                &pool, question.question_set_id, question.id, user_id, collaboration::EDIT_UPDATE,
+            )
+            .await;
+            
+            info!("Soru çevirisi kaydedildi: question_id={}, locale={}", question.id, locale);
+            HttpResponse::Ok().json(serde_json::json!({
+                "question_id": question.id,
+                "locale": locale,
+                "question_text": translation_dto.question_text,
+                "options": {
+                    "A": translation_dto.option_a,
+                    "B": translation_dto.option_b,
+                    "C": translation_dto.option_c,
+                    "D": translation_dto.option_d
+                }
+            }))
+        }
+        Err(e) => {
+            error!("Soru çevirisi kaydedilirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Çeviri kaydedilemedi"
+            }))
+        }
+    }
+}
+
+// Sorunun bir dildeki çevirisini sil (soru setini düzenleyebilenler)
+pub async fn delete_translation(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<(i32, String)>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    let (question_id, locale) = path.into_inner();
+    let locale = localization::normalize_locale(&locale).unwrap_or(locale);
+    
+    let question = match translatable_question(&pool, question_id, &claims).await {
+        Ok(Some(question)) if question.access >= SetAccess::Write => question,
+        Ok(Some(_)) => {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Bu soruyu güncelleme izniniz yok"
+            }));
+        }
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Soru bulunamadı"
+            }));
+        }
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Çeviri silinemedi"
+            }));
+        }
+    };
+    
+    let result = sqlx::query!(
+        "DELETE FROM question_translations WHERE question_id = $1 AND locale = $2",
+        question.id,
+        locale
+    )
+    .execute(&**pool)
+    .await;
+    
+    match result {
+        Ok(result) if result.rows_affected() > 0 => {
+            // Soru seti güncelleme zamanını güncelle (ETag'ler bu zamandan üretilir)
+            let _ = sqlx::query!(
+                "UPDATE question_sets SET updated_at = $1 WHERE id = $2",
+                Utc::now(),
+                question.question_set_id
+            )
+            .execute(&**pool)
+            .await;
+            
+            collaboration::record_edit(
+                &pool, question.question_set_id, question.id, user_id, collaboration::EDIT_UPDATE,
+            )
+            .await;
+            
+            HttpResponse::Ok().json(serde_json::json!({
+                "message": "Çeviri silindi"
+            }))
+        }
+        Ok(_) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Çeviri bulunamadı"
+            }))
+        }
+        Err(e) => {
+            error!("Soru çevirisi silinirken hata: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Çeviri silinemedi"
+            }))
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,37 @@
+use actix_web::{web, HttpResponse, Responder};
+use log::error;
+use sqlx::{Pool, Postgres};
+
+use crate::db::models::Claims;
+use crate::services::quota;
+
+// Öğretmenin kota kullanımını getir
+pub async fn get_my_usage(
+    pool: web::Data<Pool<Postgres>>,
+    claims: Claims,
+) -> impl Responder {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    // Kotalar öğretmen hesapları için geçerlidir
+    if claims.role != "teacher" && claims.role != "admin" {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Kullanım bilgileri sadece öğretmenler için mevcuttur"
+        }));
+    }
+    
+    match quota::usage_summary(&pool, user_id).await {
+        Ok(usage) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "user_id": user_id,
+                "quota_enforced": claims.role != "admin",
+                "usage": usage
+            }))
+        }
+        Err(e) => {
+            error!("Kullanım bilgileri alınamadı: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Kullanım bilgileri alınamadı"
+            }))
+        }
+    }
+}
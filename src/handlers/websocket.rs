@@ -1,11 +1,12 @@
-use actix_web::{web, Error, HttpRequest, HttpResponse};
+use actix_web::http::header::{HeaderValue, SEC_WEBSOCKET_PROTOCOL};
+use actix_web::{web, Error, HttpMessage, HttpRequest, HttpResponse};
 use actix_ws::{Message, MessageStream, Session};
 use chrono::Utc;
 use futures_util::StreamExt;
 use log::{debug, error, info, warn};
 use serde_json::{json, Value};
 use sqlx::{Pool, Postgres};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -13,7 +14,33 @@ use tokio::sync::Mutex;
 use tokio::time;
 use uuid::Uuid;
 
-use crate::db::models::{ConnectionType, GameStatus, LeaderboardEntry};
+use crate::config::CONFIG;
+use crate::db::models::{AnswerProgress, ConnectionType, LeaderboardEntry, PlayerInfo, PlayerRef, QuestionMessage, WebSocketMessage};
+use crate::db::repositories::{LobbyPlayer, PgPlayerRepo, PlayerRepo};
+use crate::handlers::ws_codec::{EncodedMessage, Encoding};
+use crate::handlers::ws_outbox::Outbox;
+use crate::handlers::ws_protocol::{
+    negotiate_subprotocol, origin_allowed, ws_error, Subprotocol, WsCloseReason, WsErrorCode,
+};
+use crate::middleware::recaptcha;
+use crate::services::accommodations::{self, Accommodations};
+use crate::services::anti_cheat;
+use crate::services::api_keys::ApiClient;
+use crate::services::audit;
+use crate::services::cosmetics;
+use crate::services::game::{self as game_service, AnswerResult, GameError, GameService, NextStep, QuestionStart};
+use crate::services::game_alias::{self, Resolution};
+use crate::services::game_events;
+use crate::services::guest_ip;
+use crate::services::guest_sessions;
+use crate::services::localization::{self, QuestionTranslation};
+use crate::services::metrics::{self, RequestContext};
+use crate::services::nickname::{clean_nickname, guest_nickname_base, is_nickname_conflict, resolve_guest_nickname};
+use crate::services::roster;
+use crate::services::scenes;
+use crate::services::tournament;
+use crate::services::viewers;
+use crate::utils::security::{client_ip, generate_player_token, hash_client_ip};
 
 // Bağlantı durumları
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -25,6 +52,31 @@ enum ConnectionState {
     Ended,
 }
 
+// Medyanın tüm cihazlarda aynı anda başlaması için oynatma komutuna eklenen bekleme süresi
+const MEDIA_START_DELAY: Duration = Duration::from_millis(500);
+
+// Host'un süre uzatma komutunda süre belirtilmezse eklenen ve tek seferde eklenebilecek en fazla süre (saniye)
+const DEFAULT_TIMER_EXTENSION_SECS: u64 = 15;
+const MAX_TIMER_EXTENSION_SECS: u64 = 300;
+
+// Oyuncunun host'a bildirilen bağlantı durumu
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Presence {
+    Connected,
+    Away,         // Heartbeat gecikti, bağlantı henüz kopmadı
+    Disconnected,
+}
+
+impl Presence {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Presence::Connected => "connected",
+            Presence::Away => "away",
+            Presence::Disconnected => "disconnected",
+        }
+    }
+}
+
 // Uygulama durumu
 pub struct AppState {
     active_connections: Arc<Mutex<HashMap<String, WebSocketConnection>>>, // session_id -> connection
@@ -42,6 +94,27 @@ struct WebSocketConnection {
     connection_type: ConnectionType,
     session: Option<Session>,
     last_seen: Instant,
+    ip_hash: Option<String>, // Misafir IP sınırı için hashlenmiş istemci IP'si
+    outbox: Arc<Outbox>,     // Yayın mesajları için sınırlı giden kuyruk
+    api_client: Option<ApiClient>, // Bağlantı isteği geçerli bir API anahtarıyla geldiyse istemci
+}
+
+impl WebSocketConnection {
+    // Mesajı bağlantının kuyruğuna ekle; kuyruk dolmuşsa yavaş istemcinin bağlantısını kapat
+    fn enqueue(&self, kind: Option<&'static str>, message: &Arc<EncodedMessage>) {
+        if self.outbox.push(kind, message).is_ok() {
+            return;
+        }
+        
+        warn!("İstemci mesajlara yetişemiyor, bağlantı kapatılıyor: player_id={:?}", self.player_id);
+        self.outbox.close();
+        
+        if let Some(session) = self.session.clone() {
+            actix_web::rt::spawn(async move {
+                let _ = session.close(Some(WsCloseReason::SlowClient.into())).await;
+            });
+        }
+    }
 }
 
 // Oyun durumu
@@ -50,15 +123,166 @@ struct GameState {
     code: String,
     host_session_id: String,
     host_id: i32,
-    question_set_id: i32,
     players: HashMap<String, PlayerState>, // session_id -> PlayerState
     current_question: i32,
+    current_question_id: Option<i32>,      // Gösterilen sorunun id'si
     state: ConnectionState,
     started_at: Option<Instant>,
     ended_at: Option<Instant>,
     question_timer: Option<Instant>,       // Mevcut sorunun başlangıç zamanı
     question_duration: Option<Duration>,   // Mevcut sorunun süresi
     total_questions: i32,                  // Toplam soru sayısı
+    announced_player_count: usize,         // Oyunculara en son bildirilen aktif oyuncu sayısı
+    sudden_death: Option<SuddenDeath>,     // Ani ölüm turu başladıysa finalistler
+    viewers: HashSet<String>,              // Oyunu izleyen bağlantıların session_id'leri
+}
+
+// Ani ölüm turu: yalnızca finalistler cevap verir, soruyu doğru cevaplayamayan finalist elenir.
+// Kalan finalistlerin hepsi aynı soruda elenecekse kimse elenmez; tek finalist kaldığında kazanan belli olur.
+struct SuddenDeath {
+    finalists: HashSet<i32>,     // Hâlâ yarışan oyuncuların player_id'leri
+    eliminated: Vec<Vec<i32>>,   // Her soruda elenen finalistler, eleme sırasıyla
+}
+
+// Ani ölüm turunun sonucu: kazanan (tek finalist kaldıysa) ve finalistlerin en iyiden başlayan sırası
+pub struct SuddenDeathStandings {
+    pub winner: Option<i32>,
+    pub places: Vec<i32>,
+}
+
+// Soru sonunda ani ölüm turunda elenenler
+struct Elimination {
+    eliminated: Vec<i32>,
+    remaining: Vec<i32>,
+}
+
+impl GameState {
+    // Bellekteki puanlardan liderlik tablosu; veritabanındaki sıralamayla aynı (yalnızca bağlı oyuncular, en fazla 100)
+    fn leaderboard(&self) -> Vec<LeaderboardEntry> {
+        let mut entries: Vec<LeaderboardEntry> = self
+            .players
+            .values()
+            .filter(|p| p.is_active)
+            .map(|p| LeaderboardEntry {
+                player_id: p.player_id,
+                nickname: p.nickname.clone(),
+                score: p.score,
+                is_guest: p.user_id.is_none(),
+                avatar: p.avatar.clone(),
+            })
+            .collect();
+        
+        entries.sort_by(|a, b| b.score.cmp(&a.score).then(a.player_id.cmp(&b.player_id)));
+        entries.truncate(100);
+        entries
+    }
+    
+    // Finalistler, elenmeyenler önce ve daha geç elenenler daha önde olacak şekilde; aynı turdakiler puana göre
+    fn sudden_death_standings(&self) -> Option<SuddenDeathStandings> {
+        let sudden_death = self.sudden_death.as_ref()?;
+        let score_of = |player_id: &i32| {
+            self.players
+                .values()
+                .find(|p| p.player_id == *player_id)
+                .map_or(0, |p| p.score)
+        };
+        let by_score = |mut group: Vec<i32>| {
+            group.sort_by(|a, b| score_of(b).cmp(&score_of(a)).then(a.cmp(b)));
+            group
+        };
+        
+        let mut places = by_score(sudden_death.finalists.iter().copied().collect());
+        for round in sudden_death.eliminated.iter().rev() {
+            places.extend(by_score(round.clone()));
+        }
+        
+        let winner = match sudden_death.finalists.len() {
+            1 => sudden_death.finalists.iter().next().copied(),
+            _ => None,
+        };
+        Some(SuddenDeathStandings { winner, places })
+    }
+    
+    fn nickname_of(&self, player_id: i32) -> Option<&str> {
+        self.players
+            .values()
+            .find(|p| p.player_id == player_id)
+            .map(|p| p.nickname.as_str())
+    }
+    
+    // Ani ölüm turunda soruyu doğru cevaplamayan finalistleri ele; tur yoksa None
+    fn eliminate_finalists(&mut self, question_id: i32) -> Option<Elimination> {
+        let sudden_death = self.sudden_death.as_ref()?;
+        
+        let (survivors, eliminated): (Vec<i32>, Vec<i32>) = sudden_death.finalists.iter().partition(|&&player_id| {
+            self.players
+                .values()
+                .find(|p| p.player_id == player_id)
+                .and_then(|p| p.answers.get(&question_id))
+                .is_some_and(|a| a.is_correct)
+        });
+        
+        // Herkes yanıldıysa soru kimseyi elemez
+        let (mut remaining, mut eliminated) = match survivors.is_empty() {
+            true => (eliminated, Vec::new()),
+            false => (survivors, eliminated),
+        };
+        remaining.sort_unstable();
+        eliminated.sort_unstable();
+        
+        let sudden_death = self.sudden_death.as_mut()?;
+        for player_id in &eliminated {
+            sudden_death.finalists.remove(player_id);
+        }
+        if !eliminated.is_empty() {
+            sudden_death.eliminated.push(eliminated.clone());
+        }
+        
+        Some(Elimination { eliminated, remaining })
+    }
+    
+    // Host'a gönderilen cevap durumu: soruyu cevaplaması beklenen oyunculardan kaç kişinin cevapladığı ve henüz
+    // cevaplamayanların adları (ani ölüm turunda yalnızca finalistler beklenir)
+    fn answer_progress(&self, question_id: i32) -> AnswerProgress {
+        let expected: Vec<&PlayerState> = self
+            .players
+            .values()
+            .filter(|p| p.is_active)
+            .filter(|p| {
+                self.sudden_death
+                    .as_ref()
+                    .is_none_or(|sudden_death| sudden_death.finalists.contains(&p.player_id))
+            })
+            .collect();
+        
+        let mut waiting: Vec<&PlayerState> = expected
+            .iter()
+            .copied()
+            .filter(|p| !p.answers.contains_key(&question_id))
+            .collect();
+        waiting.sort_by(|a, b| a.nickname.cmp(&b.nickname));
+        
+        AnswerProgress {
+            question_id,
+            answered_count: expected.len() - waiting.len(),
+            expected_count: expected.len(),
+            waiting: waiting
+                .iter()
+                .map(|p| PlayerRef { player_id: p.player_id, nickname: Some(p.nickname.clone()) })
+                .collect(),
+        }
+    }
+    
+    // Ani ölüm turunda tüm finalistler bu soruyu cevapladı mı
+    fn finalists_answered(&self, question_id: i32) -> bool {
+        self.sudden_death.as_ref().is_some_and(|sudden_death| {
+            sudden_death.finalists.iter().all(|&player_id| {
+                self.players
+                    .values()
+                    .any(|p| p.player_id == player_id && p.answers.contains_key(&question_id))
+            })
+        })
+    }
 }
 
 // Oyuncu durumu
@@ -67,12 +291,16 @@ struct PlayerState {
     user_id: Option<i32>,
     session_id: String,
     nickname: String,
+    avatar: Option<String>,
+    locale: Option<String>,                // Soruların gösterileceği tercih edilen dil
+    accommodations: Accommodations,        // Ek süre ve ekran okuyucu desteği
     score: i32,
     answers: HashMap<i32, PlayerAnswer>,   // question_id -> PlayerAnswer
     is_active: bool,
     joined_at: Instant,
     last_seen: Instant,
     last_answer_time: Option<Instant>,     // Son cevabın verildiği zaman
+    presence: Presence,
 }
 
 // Oyuncu cevabı
@@ -95,53 +323,407 @@ impl AppState {
     }
 
     // Oyundaki tüm oyunculara mesaj gönderme
-    pub async fn broadcast_to_game(&self, game_code: &str, message: &str) {
-        debug!("Broadcast to game: {}, message: {}", game_code, message);
+    pub async fn broadcast_to_game(&self, game_code: &str, message: WebSocketMessage) {
+        debug!("Broadcast to game: {}, message: {:?}", game_code, message);
         
+        let kind = message.coalesce_kind();
+        let message = Arc::new(EncodedMessage::new(message));
         let active_connections = self.active_connections.lock().await;
         let games = self.games.lock().await;
         
         if let Some(game) = games.get(game_code) {
             for session_id in game.players.keys() {
                 if let Some(conn) = active_connections.get(session_id) {
-                    if let Some(session) = &conn.session {
-                        // Here we need a mutable session
-                        let mut session_clone = session.clone();
-                        if let Err(e) = session_clone.text(message.to_string()).await {
-                            error!("Mesaj gönderme hatası: {}", e);
-                        }
-                    }
+                    conn.enqueue(kind, &message);
+                }
+            }
+            
+            // Oyun sahibine ve izleyicilere de mesaj gönder
+            if let Some(conn) = active_connections.get(&game.host_session_id) {
+                conn.enqueue(kind, &message);
+            }
+            for session_id in &game.viewers {
+                if let Some(conn) = active_connections.get(session_id) {
+                    conn.enqueue(kind, &message);
+                }
+            }
+        }
+    }
+    
+    // Soruyu oyunculara tercih ettikleri dilde ve ek süreleriyle gönder; çevirisi ya da düzenlemesi olmayan
+    // oyuncular, host ve izleyiciler aynı mesajı alır
+    pub async fn broadcast_question(&self, game_code: &str, question: &QuestionStart, translations: &[QuestionTranslation]) {
+        let message = Arc::new(EncodedMessage::new(question.player_message()));
+        // (çeviri dili, ek süre çarpanı, ekran okuyucu) -> mesaj
+        let mut personalized: HashMap<(Option<&str>, u64, bool), Arc<EncodedMessage>> = HashMap::new();
+        
+        let active_connections = self.active_connections.lock().await;
+        let games = self.games.lock().await;
+        
+        if let Some(game) = games.get(game_code) {
+            for (session_id, player) in &game.players {
+                let Some(conn) = active_connections.get(session_id) else {
+                    continue;
+                };
+                
+                let translation = player
+                    .locale
+                    .as_deref()
+                    .and_then(|locale| localization::pick_translation(translations, locale));
+                
+                if translation.is_none() && player.accommodations.is_default() {
+                    conn.enqueue(None, &message);
+                    continue;
                 }
+                
+                let accommodations = player.accommodations;
+                let key = (
+                    translation.map(|t| t.locale.as_str()),
+                    accommodations.time_multiplier.to_bits(),
+                    accommodations.screen_reader,
+                );
+                let personal = personalized.entry(key).or_insert_with(|| {
+                    let mut personal = question.player_payload();
+                    if let Some(translation) = translation {
+                        localization::apply_translation(&mut personal, translation);
+                    }
+                    accommodations.apply_to_question(&mut personal);
+                    Arc::new(EncodedMessage::new(WebSocketMessage::QuestionStart(personal)))
+                });
+                conn.enqueue(None, personal);
             }
             
-            // Oyun sahibine de mesaj gönder
+            let watchers = std::iter::once(&game.host_session_id).chain(&game.viewers);
+            for conn in watchers.filter_map(|session_id| active_connections.get(session_id)) {
+                conn.enqueue(None, &message);
+            }
+        }
+    }
+    
+    // Yalnızca oyun sahibine mesaj gönderme
+    pub async fn send_to_host(&self, game_code: &str, message: WebSocketMessage) {
+        let active_connections = self.active_connections.lock().await;
+        let games = self.games.lock().await;
+        
+        if let Some(game) = games.get(game_code) {
             if let Some(conn) = active_connections.get(&game.host_session_id) {
-                if let Some(session) = &conn.session {
-                    // Here we need a mutable session
-                    let mut session_clone = session.clone();
-                    if let Err(e) = session_clone.text(message.to_string()).await {
-                        error!("Host'a mesaj gönderme hatası: {}", e);
+                conn.enqueue(message.coalesce_kind(), &Arc::new(EncodedMessage::new(message)));
+            }
+        }
+    }
+    
+    // Oyundaki aktif oyuncu sayısı değiştiyse oyunculara ve host'a bildir
+    async fn refresh_player_count(&self, game_code: &str) {
+        let count = {
+            let mut games = self.games.lock().await;
+            match games.get_mut(game_code) {
+                Some(game) => {
+                    let count = game.players.values().filter(|p| p.is_active).count();
+                    if count == game.announced_player_count {
+                        return;
                     }
+                    game.announced_player_count = count;
+                    count
                 }
+                None => return,
+            }
+        };
+        
+        self.broadcast_to_game(game_code, WebSocketMessage::PlayerCount {
+            game_code: game_code.to_string(),
+            count
+        }).await;
+    }
+    
+    // Oyuncuyu bellekteki oyun durumuna ekle; oyun henüz bellekte yoksa oluştur
+    pub async fn register_player(
+        &self,
+        game_id: i32,
+        game_code: &str,
+        session_id: &str,
+        user_id: Option<i32>,
+        player: LobbyPlayer,
+        locale: Option<String>,
+    ) {
+        // Misafirlerin sınıf kaydı olmadığı için düzenlemeleri de yoktur
+        let accommodations = match user_id {
+            Some(user_id) => accommodations::for_player(&self.db_pool, game_id, user_id)
+                .await
+                .unwrap_or_else(|e| {
+                    error!("Erişilebilirlik düzenlemeleri alınamadı: {}", e);
+                    Accommodations::default()
+                }),
+            None => Accommodations::default(),
+        };
+        
+        let mut games = self.games.lock().await;
+        if !games.contains_key(game_code) {
+            // Oyun state'ini oluştur
+            let total_questions = sqlx::query!(
+                "SELECT COUNT(*) as count FROM game_question_ids($1)",
+                game_id
+            )
+            .fetch_one(&*self.db_pool)
+            .await
+            .map(|r| r.count.unwrap_or(0) as i32)
+            .unwrap_or(0);
+            
+            let host_info = sqlx::query!(
+                "SELECT host_id FROM games WHERE id = $1",
+                game_id
+            )
+            .fetch_one(&*self.db_pool)
+            .await;
+            
+            if let Ok(host) = host_info {
+                // Oyun host'unun session ID'sini bul
+                let host_session = sqlx::query!(
+                    "SELECT session_id FROM active_connections WHERE user_id = $1 AND game_id = $2",
+                    host.host_id,
+                    game_id
+                )
+                .fetch_optional(&*self.db_pool)
+                .await
+                .ok()
+                .flatten()
+                .map(|r| r.session_id)
+                .unwrap_or_else(|| "unknown".to_string());
+                
+                games.insert(game_code.to_string(), GameState {
+                    id: game_id,
+                    code: game_code.to_string(),
+                    host_session_id: host_session,
+                    host_id: host.host_id,
+                    players: HashMap::new(),
+                    current_question: -1, // Henüz başlamamış
+                    current_question_id: None,
+                    state: ConnectionState::Lobby,
+                    started_at: None,
+                    ended_at: None,
+                    question_timer: None,
+                    question_duration: None,
+                    total_questions,
+                    announced_player_count: 0,
+                    sudden_death: None,
+                    viewers: HashSet::new(),
+                });
             }
         }
+        
+        // Oyuna oyuncuyu ekle
+        if let Some(game_state) = games.get_mut(game_code) {
+            game_state.players.insert(session_id.to_string(), PlayerState {
+                player_id: player.id,
+                user_id,
+                session_id: session_id.to_string(),
+                nickname: player.nickname,
+                avatar: player.avatar,
+                locale,
+                accommodations,
+                score: 0,
+                answers: HashMap::new(),
+                is_active: true,
+                joined_at: Instant::now(),
+                last_seen: Instant::now(),
+                last_answer_time: None,
+                presence: Presence::Connected,
+            });
+        }
     }
     
-    // Belirli bir oyuncuya mesaj gönderme
-    pub async fn send_to_player(&self, session_id: &str, message: &str) {
+    // Yeniden bağlanma süresi dolduğu için bellekten çıkarılmış oyuncuyu, puanı ve cevapları veritabanından
+    // alınarak yeniden ekle; oyuncu böylece yayınları alır ve host'un listelerinde yeniden görünür
+    pub async fn restore_player(
+        &self,
+        game_id: i32,
+        game_code: &str,
+        session_id: &str,
+        user_id: Option<i32>,
+        player: LobbyPlayer,
+        locale: Option<String>,
+    ) -> Result<(i32, Accommodations), sqlx::Error> {
+        let player_id = player.id;
+        
+        // Bekleyen cevaplar önce yazılır, puan ve cevaplar veritabanından eksiksiz okunur
+        game_service::flush_answers(&self.db_pool, game_id).await?;
+        let score = sqlx::query_scalar!(
+            r#"SELECT COALESCE(score, 0) as "score!" FROM players WHERE id = $1"#,
+            player_id
+        )
+        .fetch_one(&*self.db_pool)
+        .await?;
+        let answers = sqlx::query!(
+            r#"
+            SELECT question_id, answer, is_correct, response_time_ms, points_earned
+            FROM player_answers
+            WHERE player_id = $1
+            "#,
+            player_id
+        )
+        .fetch_all(&*self.db_pool)
+        .await?;
+        
+        self.register_player(game_id, game_code, session_id, user_id, player, locale).await;
+        
+        let mut games = self.games.lock().await;
+        let player_state = games
+            .get_mut(game_code)
+            .and_then(|game| game.players.get_mut(session_id))
+            .ok_or(sqlx::Error::RowNotFound)?;
+        player_state.score = score;
+        player_state.answers = answers
+            .into_iter()
+            .map(|a| (a.question_id, PlayerAnswer {
+                question_id: a.question_id,
+                answer: a.answer,
+                is_correct: a.is_correct,
+                response_time_ms: a.response_time_ms.unwrap_or(0),
+                points_earned: a.points_earned.unwrap_or(0),
+            }))
+            .collect();
+        
+        Ok((score, player_state.accommodations))
+    }
+    
+    // Oyundan ayrılan oyuncuyu bellekteki oyun durumundan çıkar
+    pub async fn remove_player(&self, game_code: &str, player_id: i32) {
+        let mut games = self.games.lock().await;
+        if let Some(game) = games.get_mut(game_code) {
+            game.players.retain(|_, player| player.player_id != player_id);
+        }
+    }
+    
+    // Lobideki oyuncu listesini ve aktif oyuncu sayısını oyunculara ve host'a yayınla
+    pub async fn broadcast_lobby(&self, game_id: i32, game_code: &str) {
+        match PgPlayerRepo::new(&self.db_pool).list_active(game_id).await {
+            Ok(players) => {
+                let players = players
+                    .into_iter()
+                    .map(|p| PlayerInfo {
+                        player_id: p.id,
+                        nickname: p.nickname,
+                        is_guest: p.is_guest,
+                        avatar: p.avatar,
+                    })
+                    .collect();
+                
+                self.broadcast_to_game(game_code, WebSocketMessage::LobbyUpdate {
+                    game_code: game_code.to_string(),
+                    players
+                }).await;
+            }
+            Err(e) => error!("Lobi oyuncuları alınırken hata oluştu: {}", e),
+        }
+        
+        self.refresh_player_count(game_code).await;
+    }
+    
+    // Site genelindeki açık bağlantı ve bellekteki oyun sayısı
+    pub async fn live_counts(&self) -> (usize, usize) {
+        let connections = self.active_connections.lock().await.len();
+        let games = self.games.lock().await.len();
+        (connections, games)
+    }
+    
+    // Oyuncunun bağlantı durumunu güncelle, değiştiyse host'a bildir
+    async fn set_presence(&self, session_id: &str, presence: Presence) {
+        let update = {
+            let mut games = self.games.lock().await;
+            games.iter_mut().find_map(|(code, game)| {
+                let player = game.players.get_mut(session_id)?;
+                if player.presence == presence {
+                    return None;
+                }
+                player.presence = presence;
+                
+                Some((code.clone(), WebSocketMessage::PlayerPresence {
+                    game_code: code.clone(),
+                    player_id: player.player_id,
+                    nickname: player.nickname.clone(),
+                    status: presence.as_str().to_string(),
+                }))
+            })
+        };
+        
+        if let Some((game_code, message)) = update {
+            self.send_to_host(&game_code, message).await;
+        }
+    }
+    
+    // Yeniden bağlanma süresi dolan oyuncuları oyundan çıkar ve veritabanında pasif işaretle
+    pub async fn expire_disconnected_players(&self) {
+        let grace = Duration::from_secs(CONFIG.reconnect_grace_seconds);
+        
+        let expired = {
+            let mut games = self.games.lock().await;
+            let mut expired = Vec::new();
+            
+            for game in games.values_mut() {
+                game.players.retain(|_, player| {
+                    // Bağlantısı kopan oyuncunun last_seen değeri kopma anını gösterir
+                    let keep = player.is_active || player.last_seen.elapsed() < grace;
+                    if !keep {
+                        expired.push(player.player_id);
+                    }
+                    keep
+                });
+            }
+            
+            expired
+        };
+        
+        if expired.is_empty() {
+            return;
+        }
+        
+        info!("Yeniden bağlanma süresi dolan {} oyuncu pasif işaretleniyor", expired.len());
+        
+        if let Err(e) = sqlx::query!(
+            "UPDATE players SET is_active = false WHERE id = ANY($1)",
+            &expired
+        )
+        .execute(&*self.db_pool)
+        .await
+        {
+            error!("Oyuncular pasif işaretlenirken hata oluştu: {}", e);
+        }
+    }
+    
+    // Oyundaki oyuncuların ve izleyicilerin bağlantılarını belirtilen nedenle kapat
+    pub async fn close_game_players(&self, game_code: &str, reason: WsCloseReason) {
         let active_connections = self.active_connections.lock().await;
+        let games = self.games.lock().await;
         
-        if let Some(conn) = active_connections.get(session_id) {
-            if let Some(session) = &conn.session {
-                // Here we need a mutable session
-                let mut session_clone = session.clone();
-                if let Err(e) = session_clone.text(message.to_string()).await {
-                    error!("Oyuncuya mesaj gönderme hatası: {}", e);
+        if let Some(game) = games.get(game_code) {
+            for session_id in game.players.keys().chain(&game.viewers) {
+                if let Some(session) = active_connections.get(session_id).and_then(|conn| conn.session.clone()) {
+                    let _ = session.close(Some(reason.into())).await;
                 }
             }
         }
     }
     
+    // Tüm açık bağlantıları belirtilen nedenle kapat (ör. sunucu kapanırken)
+    pub async fn close_all(&self, reason: WsCloseReason) {
+        let active_connections = self.active_connections.lock().await;
+        info!("{} WebSocket bağlantısı kapatılıyor: {}", active_connections.len(), reason.as_str());
+        
+        for conn in active_connections.values() {
+            if let Some(session) = conn.session.clone() {
+                let _ = session.close(Some(reason.into())).await;
+            }
+        }
+    }
+    
+    // Belirli bir oyuncuya mesaj gönderme
+    pub async fn send_to_player(&self, session_id: &str, message: WebSocketMessage) {
+        let active_connections = self.active_connections.lock().await;
+        
+        if let Some(conn) = active_connections.get(session_id) {
+            conn.enqueue(message.coalesce_kind(), &Arc::new(EncodedMessage::new(message)));
+        }
+    }
+    
     // Oyun durumunu kontrol etme ve gerekirse zamanlayıcıyı çalıştırma
     pub async fn check_game_timers(&self) {
         let mut games_to_advance = Vec::new();
@@ -155,9 +737,19 @@ impl AppState {
                 if game.state == ConnectionState::Question && game.question_timer.is_some() && game.question_duration.is_some() {
                     let now = Instant::now();
                     let start_time = game.question_timer.unwrap();
-                    let duration = game.question_duration.unwrap();
+                    // Ek süresi olan aktif oyuncular varsa soru en uzun süre tanınan oyuncuya göre kapanır
+                    let duration = game
+                        .players
+                        .values()
+                        .filter(|p| p.is_active)
+                        .map(|p| p.accommodations.extended(game.question_duration.unwrap()))
+                        .max()
+                        .unwrap_or(game.question_duration.unwrap());
                     
-                    if now.duration_since(start_time) >= duration {
+                    // Ani ölüm turunda tüm finalistler cevapladıysa süre beklenmez
+                    let finalists_done = game.current_question_id.is_some_and(|id| game.finalists_answered(id));
+                    
+                    if now.duration_since(start_time) >= duration || finalists_done {
                         games_to_advance.push(code.clone());
                     }
                 }
@@ -174,126 +766,581 @@ impl AppState {
     
     // Soru sonucunu göster
     pub async fn show_question_result(&self, game_code: &str) -> Result<(), anyhow::Error> {
-        let mut games = self.games.lock().await;
+        // Oyun durumunu "Review" olarak güncelle ve liderlik tablosunu bellekteki puanlardan al;
+        // sorgular kilit bırakıldıktan sonra yapılır
+        let (game_id, position, leaderboard) = {
+            let mut games = self.games.lock().await;
+            match games.get_mut(game_code) {
+                Some(game) => {
+                    game.state = ConnectionState::Review;
+                    (game.id, game.current_question, game.leaderboard())
+                }
+                None => return Ok(()),
+            }
+        };
         
-        if let Some(game) = games.get_mut(game_code) {
-            // Oyun durumunu "Review" olarak güncelle
-            game.state = ConnectionState::Review;
-            
-            // Mevcut sorunun doğru cevabını veritabanından al
-            let question_id = sqlx::query!(
-                r#"
-                SELECT id, correct_option
-                FROM questions
-                WHERE question_set_id = $1 AND position = $2
-                "#,
-                game.question_set_id,
-                game.current_question
-            )
-            .fetch_one(&*self.db_pool)
-            .await?;
-            
-            // Liderlik tablosunu hesapla
-            let leaderboard = self.get_leaderboard(game_code).await?;
-            
-            // Sonuçları tüm oyunculara bildir
-            let result_message = json!({
-                "type": "question_end",
-                "question_id": question_id.id,
-                "correct_option": question_id.correct_option,
-                "leaderboard": leaderboard
-            }).to_string();
+        // Mevcut sorunun doğru cevabını veritabanından al
+        let question = sqlx::query!(
+            r#"
+            SELECT q.id, q.correct_option
+            FROM game_playlist($1) p
+            JOIN questions q ON q.id = p.question_id
+            WHERE p.position = $2
+            "#,
+            game_id,
+            position
+        )
+        .fetch_one(&*self.db_pool)
+        .await?;
+        
+        // Olay kaydına soru sonucunu ve o anki liderlik tablosunu ekle
+        let sequence = game_events::record_event(&self.db_pool, game_id, game_events::EVENT_QUESTION_END, json!({
+            "question_id": question.id,
+            "correct_option": question.correct_option,
+            "leaderboard": leaderboard
+        })).await;
+        
+        // Sonuçları tüm oyunculara bildir
+        self.broadcast_to_game(game_code, WebSocketMessage::QuestionEnd {
+            question_id: question.id,
+            correct_option: question.correct_option,
+            leaderboard,
+            seq: sequence,
+        }).await;
+        
+        // Ani ölüm turundaysa soruyu doğru cevaplayamayan finalistler elenir
+        let elimination = {
+            let mut games = self.games.lock().await;
+            games.get_mut(game_code).and_then(|game| {
+                let elimination = game.eliminate_finalists(question.id)?;
+                let describe = |player_id: i32| PlayerRef {
+                    player_id,
+                    nickname: game.nickname_of(player_id).map(str::to_string),
+                };
+                let winner = match elimination.remaining.as_slice() {
+                    [player_id] => Some(describe(*player_id)),
+                    _ => None,
+                };
+                Some((
+                    elimination.eliminated.into_iter().map(describe).collect::<Vec<_>>(),
+                    elimination.remaining.into_iter().map(describe).collect::<Vec<_>>(),
+                    winner,
+                ))
+            })
+        };
+        
+        if let Some((eliminated, remaining, winner)) = elimination {
+            let sequence = game_events::record_event(&self.db_pool, game_id, game_events::EVENT_SUDDEN_DEATH_ELIMINATION, json!({
+                "question_id": question.id,
+                "eliminated": eliminated,
+                "remaining": remaining,
+                "winner": winner
+            })).await;
             
-            drop(games); // Kilidi bırak, çünkü broadcast_to_game'de yeniden alınacak
-            self.broadcast_to_game(game_code, &result_message).await;
+            let message = match (&winner, eliminated.is_empty()) {
+                (Some(_), _) => "Ani ölüm turu sona erdi, kazanan belli oldu!",
+                (None, true) => "Bu soruda kimse elenmedi",
+                (None, false) => "Yanlış cevap veren finalistler elendi",
+            };
+            self.broadcast_to_game(game_code, WebSocketMessage::SuddenDeathElimination {
+                question_id: question.id,
+                eliminated,
+                remaining,
+                winner,
+                message: message.to_string(),
+                seq: sequence,
+            }).await;
         }
         
         Ok(())
     }
     
-    // Liderlik tablosunu getir
-    pub async fn get_leaderboard(&self, game_code: &str) -> Result<Vec<LeaderboardEntry>, anyhow::Error> {
-        let games = self.games.lock().await;
+    // Ani ölüm turunu başlat: güncel puana göre ilk `top_n` aktif oyuncu finalist olur. Soru gösterilirken,
+    // tur zaten başlamışsa veya en az iki finalist çıkmıyorsa None döner
+    pub async fn begin_sudden_death(&self, game_code: &str, top_n: usize) -> Option<Vec<LeaderboardEntry>> {
+        let mut games = self.games.lock().await;
+        let game = games.get_mut(game_code)?;
+        if game.sudden_death.is_some() || !matches!(game.state, ConnectionState::Game | ConnectionState::Review) {
+            return None;
+        }
         
-        if let Some(game) = games.get(game_code) {
-            // Veritabanından oyuncuları puanlarına göre sıralanmış olarak getir
-            let players = sqlx::query!(
-                r#"
-                SELECT id, nickname, score, user_id IS NULL as is_guest
-                FROM players
-                WHERE game_id = $1 AND is_active = true
-                ORDER BY score DESC
-                LIMIT 100
-                "#,
-                game.id
-            )
-            .fetch_all(&*self.db_pool)
-            .await?;
-            
-            let leaderboard: Vec<LeaderboardEntry> = players
-                .iter()
-                .map(|p| LeaderboardEntry {
-                    player_id: p.id,
-                    nickname: p.nickname.clone(),
-                    score: p.score.unwrap_or(0),
-                    is_guest: p.is_guest.unwrap_or(false),
-                })
-                .collect();
-            
-            Ok(leaderboard)
-        } else {
-            Err(anyhow::anyhow!("Oyun bulunamadı"))
+        let finalists: Vec<LeaderboardEntry> = game
+            .leaderboard()
+            .into_iter()
+            .take(top_n)
+            .collect();
+        if finalists.len() < 2 {
+            return None;
         }
+        
+        game.sudden_death = Some(SuddenDeath {
+            finalists: finalists.iter().map(|entry| entry.player_id).collect(),
+            eliminated: Vec::new(),
+        });
+        Some(finalists)
     }
-}
-
-// WebSocket handlers
-pub async fn ws_handler(
-    req: HttpRequest,
-    stream: web::Payload,
-    app_state: web::Data<AppState>,
-) -> Result<HttpResponse, Error> {
-    let user_id = app_state.next_user_id.fetch_add(1, Ordering::Relaxed);
-    let active_connections = app_state.active_connections.clone();
-    let games = app_state.games.clone();
-    let db_pool = app_state.db_pool.clone();
-    let session_id = Uuid::new_v4().to_string();
-
-    let (response, session, msg_stream) = actix_ws::handle(&req, stream)?;
-
-    info!(
-        "Yeni WebSocket bağlantısı: user_id={}, session_id={}",
-        user_id, session_id
-    );
-
-    // Veritabanına aktif bağlantıyı ekle
-    match sqlx::query!(
-        r#"
-        INSERT INTO active_connections (session_id, connection_type, last_seen)
-        VALUES ($1, $2, $3)
-        "#,
-        session_id,
-        ConnectionType::Viewer.to_string().to_lowercase(),
-        Utc::now()
-    )
-    .execute(&*db_pool)
-    .await
-    {
-        Ok(_) => {
-            info!("Aktif bağlantı veritabanına eklendi: {}", session_id);
+    
+    // Oyuncu cevap verebilir mi: ani ölüm turunda yalnızca elenmemiş finalistler
+    pub async fn may_answer(&self, game_code: &str, session_id: &str) -> bool {
+        let games = self.games.lock().await;
+        let Some(game) = games.get(game_code) else {
+            return true;
+        };
+        match (&game.sudden_death, game.players.get(session_id)) {
+            (Some(sudden_death), Some(player)) => sudden_death.finalists.contains(&player.player_id),
+            (Some(_), None) => false,
+            (None, _) => true,
         }
-        Err(e) => {
-            error!(
-                "Aktif bağlantı veritabanına eklenirken hata oluştu: {}",
-                e
-            );
+    }
+    
+    // Ani ölüm turunda tek finalist kaldıysa kazananın player_id'si
+    pub async fn sudden_death_winner(&self, game_code: &str) -> Option<i32> {
+        let games = self.games.lock().await;
+        let sudden_death = games.get(game_code)?.sudden_death.as_ref()?;
+        match sudden_death.finalists.len() {
+            1 => sudden_death.finalists.iter().next().copied(),
+            _ => None,
         }
     }
-
-    // Aktif kullanıcılar listesine ekle
-    {
-        let mut connections = active_connections.lock().await;
-        connections.insert(session_id.clone(), WebSocketConnection {
+    
+    // Ani ölüm turu yapıldıysa finalistlerin son sırası
+    pub async fn sudden_death_standings(&self, game_code: &str) -> Option<SuddenDeathStandings> {
+        let games = self.games.lock().await;
+        games.get(game_code)?.sudden_death_standings()
+    }
+    
+    // Oyuncunun bellekteki takma adı
+    pub async fn player_nickname(&self, game_code: &str, player_id: i32) -> Option<String> {
+        let games = self.games.lock().await;
+        games.get(game_code)?.nickname_of(player_id).map(str::to_string)
+    }
+    
+    // Oyun başladı: lobiden oyun ekranına geç
+    pub async fn mark_game_started(&self, game_code: &str) {
+        let mut games = self.games.lock().await;
+        if let Some(game) = games.get_mut(game_code) {
+            game.state = ConnectionState::Game;
+            game.started_at = Some(Instant::now());
+        }
+    }
+    
+    // Yeni soru gösterilmeye başladı, soru zamanlayıcısını kur
+    pub async fn begin_question(&self, game_code: &str, position: i32, question_id: i32, duration: Duration) {
+        let mut games = self.games.lock().await;
+        if let Some(game) = games.get_mut(game_code) {
+            game.current_question = position;
+            game.current_question_id = Some(question_id);
+            game.state = ConnectionState::Question;
+            game.question_timer = Some(Instant::now());
+            game.question_duration = Some(duration);
+        }
+    }
+    
+    // Oyun tamamlandı
+    pub async fn mark_game_ended(&self, game_code: &str) {
+        let mut games = self.games.lock().await;
+        if let Some(game) = games.get_mut(game_code) {
+            game.state = ConnectionState::Ended;
+            game.ended_at = Some(Instant::now());
+        }
+    }
+    
+    // Oyuncunun bu oyundaki erişilebilirlik düzenlemeleri
+    pub async fn player_accommodations(&self, game_code: &str, session_id: &str) -> Accommodations {
+        let games = self.games.lock().await;
+        games
+            .get(game_code)
+            .and_then(|g| g.players.get(session_id))
+            .map(|p| p.accommodations)
+            .unwrap_or_default()
+    }
+    
+    // Kaydedilen cevabı oyuncunun bellekteki durumuna işle ve host'a kimin cevapladığını bildir
+    // (cevabın kendisi ve doğruluğu gönderilmez, host ekranı sınıfa yansıtılıyor olabilir)
+    pub async fn record_player_answer(&self, game_code: &str, session_id: &str, result: &AnswerResult) {
+        let update = {
+            let mut games = self.games.lock().await;
+            games.get_mut(game_code).and_then(|game| {
+                let player = game.players.get_mut(session_id)?;
+                player.score += result.points_earned;
+                player.last_answer_time = Some(Instant::now());
+                player.answers.insert(result.question_id, PlayerAnswer {
+                    question_id: result.question_id,
+                    answer: Some(result.answer.clone()),
+                    is_correct: result.is_correct,
+                    response_time_ms: result.response_time_ms,
+                    points_earned: result.points_earned,
+                });
+                
+                let (player_id, nickname) = (player.player_id, player.nickname.clone());
+                Some(WebSocketMessage::PlayerAnswered {
+                    player_id,
+                    nickname,
+                    progress: game.answer_progress(result.question_id),
+                })
+            })
+        };
+        
+        if let Some(message) = update {
+            self.send_to_host(game_code, message).await;
+        }
+    }
+    
+    // Oyuncuya mevcut soru için tanınan süre: soru süresi (host uzattıysa uzatılmış hali) ve oyuncunun ek süresi
+    pub async fn answer_window(&self, game_code: &str, session_id: &str) -> Option<Duration> {
+        let games = self.games.lock().await;
+        let game = games.get(game_code)?;
+        let accommodations = game.players.get(session_id).map(|p| p.accommodations).unwrap_or_default();
+        game.question_duration.map(|duration| accommodations.extended(duration))
+    }
+    
+    // Oyuncu için soru hâlâ açık mı (ek süresi dahil)
+    pub async fn answer_window_open(&self, game_code: &str, session_id: &str) -> bool {
+        let games = self.games.lock().await;
+        let Some(game) = games.get(game_code) else {
+            return false;
+        };
+        let (Some(started), Some(duration)) = (game.question_timer, game.question_duration) else {
+            return false;
+        };
+        
+        let accommodations = game.players.get(session_id).map(|p| p.accommodations).unwrap_or_default();
+        game.state == ConnectionState::Question && started.elapsed() < accommodations.extended(duration)
+    }
+    
+    // Geri alınan cevabı ve puanını oyuncunun bellekteki durumundan çıkar; oyuncu host'un bekleme listesine döner
+    pub async fn clear_player_answer(&self, game_code: &str, session_id: &str, question_id: i32) {
+        let update = {
+            let mut games = self.games.lock().await;
+            games.get_mut(game_code).and_then(|game| {
+                let player = game.players.get_mut(session_id)?;
+                let answer = player.answers.remove(&question_id)?;
+                player.score -= answer.points_earned;
+                
+                let (player_id, nickname) = (player.player_id, player.nickname.clone());
+                Some(WebSocketMessage::PlayerAnswerRetracted {
+                    player_id,
+                    nickname,
+                    progress: game.answer_progress(question_id),
+                })
+            })
+        };
+        
+        if let Some(message) = update {
+            self.send_to_host(game_code, message).await;
+        }
+    }
+    
+    // Host'un medya oynatma komutunu sunucu zamanıyla birlikte oyunculara ilet.
+    // show_media için oynatmanın başlayacağı an (start_at) biraz ileri alınır, böylece mesaj
+    // tüm cihazlara ulaşmış olur; istemciler saat farkını server_time ile düzeltir.
+    pub async fn relay_media(
+        &self,
+        game_code: &str,
+        user_id: Option<i32>,
+        action: &str,
+        position_ms: i64,
+    ) -> Result<(), WsErrorCode> {
+        {
+            let games = self.games.lock().await;
+            let game = games.get(game_code).ok_or(WsErrorCode::GameNotFound)?;
+            if user_id != Some(game.host_id) {
+                return Err(WsErrorCode::NotHost);
+            }
+            if matches!(game.state, ConnectionState::Lobby | ConnectionState::Ended) {
+                return Err(WsErrorCode::GameNotActive);
+            }
+        }
+        
+        let server_time = Utc::now().timestamp_millis();
+        let game_code_owned = game_code.to_string();
+        let message = match action {
+            "show_media" => WebSocketMessage::ShowMedia {
+                game_code: game_code_owned,
+                position_ms,
+                server_time,
+                start_at: server_time + MEDIA_START_DELAY.as_millis() as i64,
+            },
+            _ => WebSocketMessage::PauseMedia {
+                game_code: game_code_owned,
+                position_ms,
+                server_time,
+            },
+        };
+        
+        self.broadcast_to_game(game_code, message).await;
+        Ok(())
+    }
+    
+    // Gösterilen sorunun süresini uzat ve yeni bitiş zamanını bildir. Ek süresi olan oyuncuların bitiş zamanı
+    // uzatılmış süreye göre kendilerine özel hesaplanır; olay kaydına host'un gördüğü bitiş zamanı eklenir.
+    pub async fn extend_question_timer(
+        &self,
+        game_code: &str,
+        user_id: Option<i32>,
+        extra: Duration,
+    ) -> Result<(), WsErrorCode> {
+        let (game_id, event) = {
+            let active_connections = self.active_connections.lock().await;
+            let mut games = self.games.lock().await;
+            let game = games.get_mut(game_code).ok_or(WsErrorCode::GameNotFound)?;
+            if user_id != Some(game.host_id) {
+                return Err(WsErrorCode::NotHost);
+            }
+            let (ConnectionState::Question, Some(started), Some(duration)) =
+                (game.state, game.question_timer, game.question_duration)
+            else {
+                return Err(WsErrorCode::QuestionNotActive);
+            };
+            
+            let duration = duration + extra;
+            game.question_duration = Some(duration);
+            
+            let server_time = Utc::now().timestamp_millis();
+            let started_at = server_time - started.elapsed().as_millis() as i64;
+            let message = |allowed: Duration| WebSocketMessage::TimerExtended {
+                question_id: game.current_question_id,
+                added_seconds: extra.as_secs(),
+                time_limit: allowed.as_secs(),
+                deadline: started_at + allowed.as_millis() as i64,
+                server_time,
+            };
+            
+            for (session_id, player) in &game.players {
+                if let Some(conn) = active_connections.get(session_id) {
+                    conn.enqueue(None, &Arc::new(EncodedMessage::new(message(player.accommodations.extended(duration)))));
+                }
+            }
+            
+            if let Some(conn) = active_connections.get(&game.host_session_id) {
+                conn.enqueue(None, &Arc::new(EncodedMessage::new(message(duration))));
+            }
+            (game.id, json!({
+                "question_id": game.current_question_id,
+                "added_seconds": extra.as_secs(),
+                "time_limit": duration.as_secs(),
+                "deadline": started_at + duration.as_millis() as i64
+            }))
+        };
+        
+        game_events::record_event(&self.db_pool, game_id, game_events::EVENT_TIMER_EXTENDED, event).await;
+        
+        Ok(())
+    }
+    
+    // Host'un sahne değişikliğini oyun ayarlarındaki işaretle birlikte yayınla ve olay kaydına ekle.
+    // Müzik ve animasyonlar medya komutlarındaki gibi biraz ileri alınmış start_at anında başlar.
+    pub async fn relay_scene(&self, game_code: &str, user_id: Option<i32>, scene: &str) -> Result<(), WsErrorCode> {
+        let game_id = {
+            let games = self.games.lock().await;
+            let game = games.get(game_code).ok_or(WsErrorCode::GameNotFound)?;
+            if user_id != Some(game.host_id) {
+                return Err(WsErrorCode::NotHost);
+            }
+            if game.state == ConnectionState::Ended {
+                return Err(WsErrorCode::GameNotActive);
+            }
+            game.id
+        };
+        
+        let cues = sqlx::query_scalar!("SELECT scene_cues FROM games WHERE id = $1", game_id)
+            .fetch_one(&*self.db_pool)
+            .await
+            .map_err(|e| {
+                error!("Veritabanı sorgu hatası: {}", e);
+                WsErrorCode::InternalError
+            })?;
+        
+        let server_time = Utc::now().timestamp_millis();
+        let start_at = server_time + MEDIA_START_DELAY.as_millis() as i64;
+        let cue = scenes::cue_for(&cues, scene);
+        let sequence = game_events::record_event(&self.db_pool, game_id, game_events::EVENT_SCENE, json!({
+            "scene": scene,
+            "cue": cue,
+            "start_at": start_at
+        }))
+        .await;
+        
+        self.broadcast_to_game(game_code, WebSocketMessage::Scene {
+            game_code: game_code.to_string(),
+            scene: scene.to_string(),
+            cue,
+            start_at,
+            server_time,
+            seq: sequence,
+        })
+        .await;
+        Ok(())
+    }
+    
+    // Bağlantıyı oyunun izleyicisi olarak ekle; izleyici host'a giden yayınları alır ama cevap veremez.
+    // Oyun bellekte değilse (henüz kimse katılmadıysa) veya izleyici sınırı dolduysa hata döner.
+    pub async fn add_viewer(&self, game_code: &str, session_id: &str) -> Result<Value, WsErrorCode> {
+        let mut active_connections = self.active_connections.lock().await;
+        let mut games = self.games.lock().await;
+        
+        let conn = active_connections.get_mut(session_id).ok_or(WsErrorCode::SessionNotFound)?;
+        if conn.connection_type != ConnectionType::Viewer || conn.game_code.is_some() {
+            return Err(WsErrorCode::SessionAlreadyActive);
+        }
+        let game = games.get_mut(game_code).ok_or(WsErrorCode::GameNotFound)?;
+        if game.state == ConnectionState::Ended {
+            return Err(WsErrorCode::GameNotActive);
+        }
+        if game.viewers.len() >= CONFIG.max_viewers_per_game {
+            return Err(WsErrorCode::ViewerLimitReached);
+        }
+        
+        game.viewers.insert(session_id.to_string());
+        conn.game_id = Some(game.id);
+        conn.game_code = Some(game_code.to_string());
+        
+        Ok(json!({
+            "type": "watching",
+            "game_id": game.id,
+            "game_code": game_code,
+            "current_question": game.current_question,
+            "total_questions": game.total_questions,
+            "player_count": game.players.values().filter(|p| p.is_active).count(),
+            "viewer_count": game.viewers.len()
+        }))
+    }
+    
+    // İzleyiciyi oyundan çıkar; bağlantı bir oyunu izlemiyorsa false döner
+    async fn remove_viewer(&self, session_id: &str) -> bool {
+        let mut games = self.games.lock().await;
+        games.values_mut().any(|game| game.viewers.remove(session_id))
+    }
+    
+    // Oyunu dışarıdan (ör. admin) sonlandır, bağlı istemcilere bildir ve bellekten kaldır
+    pub async fn end_game(&self, game_code: &str, reason: &str, message: &str) {
+        self.broadcast_to_game(game_code, WebSocketMessage::GameEnd {
+            reason: Some(reason.to_string()),
+            results: None,
+            message: message.to_string(),
+            seq: None,
+        }).await;
+        
+        self.remove_game(game_code).await;
+    }
+    
+    // Oyunu bellekten tamamen kaldır
+    pub async fn remove_game(&self, game_code: &str) {
+        let mut games = self.games.lock().await;
+        games.remove(game_code);
+    }
+    
+    // Biten veya kimsenin kalmadığı oyunları bellekten temizle
+    pub async fn evict_stale_games(&self) {
+        const ENDED_GAME_TTL: Duration = Duration::from_secs(5 * 60);
+        
+        let active_connections = self.active_connections.lock().await;
+        let mut games = self.games.lock().await;
+        let before = games.len();
+        
+        games.retain(|_, game| {
+            let ended = game.state == ConnectionState::Ended
+                && game.ended_at.map(|t| t.elapsed() > ENDED_GAME_TTL).unwrap_or(true);
+            // Tüm oyuncuları ayrılmış (yeniden bağlanma süresi dolmuş) ve host'u bağlı olmayan oyun
+            let abandoned = game.players.is_empty()
+                && !active_connections.contains_key(&game.host_session_id);
+            
+            !ended && !abandoned
+        });
+        
+        let evicted = before - games.len();
+        if evicted > 0 {
+            info!("{} oyun bellekten temizlendi", evicted);
+        }
+    }
+}
+
+// WebSocket handlers
+pub async fn ws_handler(
+    req: HttpRequest,
+    stream: web::Payload,
+    app_state: web::Data<AppState>,
+) -> Result<HttpResponse, Error> {
+    // Başka sitelerdeki sayfaların kullanıcının tarayıcısı üzerinden bağlanmasını engelle
+    if !origin_allowed(req.headers()) {
+        warn!("İzin verilmeyen kaynaktan WebSocket bağlantısı reddedildi: {:?}", req.headers().get("origin"));
+        return Ok(HttpResponse::Forbidden().json(json!({
+            "error": "Bu kaynaktan WebSocket bağlantısına izin verilmiyor"
+        })));
+    }
+    
+    let subprotocol = negotiate_subprotocol(req.headers());
+    let (mut response, session, msg_stream) = actix_ws::handle(&req, stream)?;
+    
+    match &subprotocol {
+        Subprotocol::Supported(protocol) => {
+            response
+                .headers_mut()
+                .insert(SEC_WEBSOCKET_PROTOCOL, HeaderValue::from_static(protocol));
+        }
+        Subprotocol::Legacy => {}
+        Subprotocol::Unsupported(offered) => {
+            // İstemcinin önerisiyle el sıkışıp nedeni kapatma koduyla bildir; bağlantı kaydedilmez
+            warn!("Desteklenmeyen WebSocket alt protokolü: {}", offered);
+            if let Ok(value) = HeaderValue::from_str(offered) {
+                response.headers_mut().insert(SEC_WEBSOCKET_PROTOCOL, value);
+            }
+            actix_web::rt::spawn(async move {
+                let _ = session.close(Some(WsCloseReason::UnsupportedProtocol.into())).await;
+                drop(msg_stream);
+            });
+            return Ok(response);
+        }
+    }
+    
+    let user_id = app_state.next_user_id.fetch_add(1, Ordering::Relaxed);
+    let active_connections = app_state.active_connections.clone();
+    let games = app_state.games.clone();
+    let db_pool = app_state.db_pool.clone();
+    let session_id = Uuid::new_v4().to_string();
+
+    info!(
+        "Yeni WebSocket bağlantısı: user_id={}, session_id={}",
+        user_id, session_id
+    );
+
+    // Veritabanına aktif bağlantıyı ekle
+    match sqlx::query!(
+        r#"
+        INSERT INTO active_connections (session_id, connection_type, last_seen)
+        VALUES ($1, $2, $3)
+        "#,
+        session_id,
+        ConnectionType::Viewer.to_string().to_lowercase(),
+        Utc::now()
+    )
+    .execute(&*db_pool)
+    .await
+    {
+        Ok(_) => {
+            info!("Aktif bağlantı veritabanına eklendi: {}", session_id);
+        }
+        Err(e) => {
+            error!(
+                "Aktif bağlantı veritabanına eklenirken hata oluştu: {}",
+                e
+            );
+        }
+    }
+
+    // Yayın mesajları bağlantıya ait yazıcı task üzerinden anlaşılan kodlamayla gönderilir
+    let encoding = subprotocol.encoding();
+    let outbox = Arc::new(Outbox::default());
+    let writer_outbox = outbox.clone();
+    let writer_session = session.clone();
+    actix_web::rt::spawn(async move {
+        writer_outbox.run(writer_session, encoding).await;
+    });
+
+    // Aktif kullanıcılar listesine ekle
+    {
+        let mut connections = active_connections.lock().await;
+        connections.insert(session_id.clone(), WebSocketConnection {
             user_id: None,
             player_id: None,
             game_id: None,
@@ -301,34 +1348,48 @@ pub async fn ws_handler(
             connection_type: ConnectionType::Viewer,
             session: Some(session.clone()),
             last_seen: Instant::now(),
+            ip_hash: client_ip(&req).map(|ip| hash_client_ip(&ip)),
+            outbox,
+            api_client: req.extensions().get::<ApiClient>().cloned(),
         });
     }
 
     // WebSocket bağlantısını ayrı bir task'ta işle
-    actix_web::rt::spawn(websocket_task(
-        session,
-        msg_stream,
+    actix_web::rt::spawn(websocket_task(session, msg_stream, WsTaskContext {
         session_id,
         user_id,
+        encoding,
         active_connections,
         games,
         db_pool,
-        app_state.clone(),
-    ));
+        app_state: app_state.clone(),
+    }));
 
     Ok(response)
 }
 
-async fn websocket_task(
-    mut session: Session,
-    mut msg_stream: MessageStream,
+// Bağlantının bilgileri ve mesaj işleme task'ının kullandığı paylaşılan durum
+struct WsTaskContext {
     session_id: String,
     user_id: usize,
+    encoding: Encoding,
     active_connections: Arc<Mutex<HashMap<String, WebSocketConnection>>>,
     games: Arc<Mutex<HashMap<String, GameState>>>,
     db_pool: Arc<Pool<Postgres>>,
     app_state: web::Data<AppState>,
-) {
+}
+
+async fn websocket_task(mut session: Session, mut msg_stream: MessageStream, context: WsTaskContext) {
+    let WsTaskContext {
+        session_id,
+        user_id,
+        encoding,
+        active_connections,
+        games,
+        db_pool,
+        app_state,
+    } = context;
+
     // Kullanıcı için hoş geldin mesajı gönder
     if let Err(e) = session
         .text(
@@ -344,30 +1405,16 @@ async fn websocket_task(
         error!("Hoş geldin mesajı gönderme hatası: {}", e);
     }
 
-    // İlk bağlantı bilgilerini gönder
-    let active_count = {
-        let connections = active_connections.lock().await;
-        connections.len()
-    };
-
-    if let Err(e) = session
-        .text(
-            json!({
-                "type": "counter",
-                "count": active_count
-            })
-            .to_string(),
-        )
-        .await
-    {
-        error!("Aktif kullanıcı sayısı mesajı gönderme hatası: {}", e);
-    }
+    // Sunucu tarafında kapatılırsa istemciye bildirilecek neden
+    let mut close_reason: Option<WsCloseReason> = None;
 
     // Heartbeat için değişkenler
     let mut last_heartbeat = Instant::now();
     let mut interval = time::interval(Duration::from_secs(1));  // 1 saniye aralıklarla kontrol et
     const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
     const CLIENT_TIMEOUT: Duration = Duration::from_secs(30);
+    const AWAY_AFTER: Duration = Duration::from_secs(20);  // Bu süre yanıt yoksa host'a "away" bildirilir
+    let mut is_away = false;
 
     // Ana mesaj işleme döngüsü
     loop {
@@ -376,9 +1423,16 @@ async fn websocket_task(
                 // Heartbeat kontrolü
                 if Instant::now().duration_since(last_heartbeat) > CLIENT_TIMEOUT {
                     warn!("İstemci zaman aşımı: user_id={}, session_id={}", user_id, session_id);
+                    close_reason = Some(WsCloseReason::Timeout);
                     break;
                 }
 
+                // Heartbeat gecikiyorsa host'a oyuncunun uzaklaştığını bildir
+                if !is_away && Instant::now().duration_since(last_heartbeat) > AWAY_AFTER {
+                    is_away = true;
+                    app_state.set_presence(&session_id, Presence::Away).await;
+                }
+
                 // Her 10 saniyede bir ping gönder
                 if Instant::now().duration_since(last_heartbeat) > HEARTBEAT_INTERVAL {
                     if let Err(e) = session.ping(b"").await {
@@ -399,25 +1453,6 @@ async fn websocket_task(
                     error!("Last seen güncellenirken hata oluştu: {}", e);
                 }
 
-                // Aktif kullanıcı sayısını gönder
-                let active_count = {
-                    let connections = active_connections.lock().await;
-                    connections.len()
-                };
-
-                if let Err(e) = session
-                    .text(
-                        json!({
-                            "type": "counter",
-                            "count": active_count
-                        })
-                        .to_string(),
-                    )
-                    .await
-                {
-                    error!("Aktif kullanıcı sayısı mesajı gönderme hatası: {}", e);
-                }
-                
                 // Oyun zamanlayıcılarını kontrol et
                 app_state.check_game_timers().await;
             }
@@ -425,14 +1460,36 @@ async fn websocket_task(
                 Some(Ok(msg)) => {
                     last_heartbeat = Instant::now();
                     
-                    // Bağlantı bilgisini güncelle
+                    // Uzaklaşmış oyuncudan yeniden mesaj geldiyse host'a bildir
+                    if is_away {
+                        is_away = false;
+                        app_state.set_presence(&session_id, Presence::Connected).await;
+                    }
+                    
+                    // Bağlantı bilgisini güncelle; bağlantının oyunu sorgu metriklerinde kullanılır
+                    let mut connection_game_code = None;
                     {
                         let mut connections = active_connections.lock().await;
                         if let Some(conn) = connections.get_mut(&session_id) {
                             conn.last_seen = Instant::now();
+                            connection_game_code = conn.game_code.clone();
                         }
                     }
 
+                    // MessagePack istemcilerinin ikili istekleri JSON mesajlarla aynı şekilde işlenir
+                    let msg = match msg {
+                        Message::Binary(bytes) => match encoding.decode(&bytes) {
+                            Some(text) => Message::Text(text.into()),
+                            None => {
+                                let _ = session
+                                    .text(ws_error(WsErrorCode::InvalidMessage, "İkili mesaj çözümlenemedi"))
+                                    .await;
+                                Message::Nop
+                            }
+                        },
+                        msg => msg,
+                    };
+
                     match msg {
                         Message::Text(text) => {
                             debug!("Metin mesajı alındı: {}", text);
@@ -446,27 +1503,59 @@ async fn websocket_task(
                                     if let Some(msg_type) = msg_value.get("type").and_then(|t| t.as_str()) {
                                         match msg_type {
                                             "ping" => {
-                                                // Pong yanıtı gönder
+                                                // Pong yanıtı gönder; server_time (ms) istemcilerin medya senkronu için saat farkını hesaplamasına yarar
+                                                let now = Utc::now();
                                                 if let Err(e) = session
-                                                    .text(json!({"type": "pong", "timestamp": Utc::now().timestamp()}).to_string())
+                                                    .text(json!({"type": "pong", "timestamp": now.timestamp(), "server_time": now.timestamp_millis()}).to_string())
                                                     .await
                                                 {
                                                     error!("Pong yanıtı gönderme hatası: {}", e);
                                                 }
                                             }
+                                            "time_sync" => {
+                                                // Saat farkı ölçümü: istemci kendi gönderim zamanını yollar, sunucu zamanıyla birlikte geri alır.
+                                                // İstemci farkı server_time - (client_time + alış zamanı) / 2 olarak hesaplar.
+                                                let client_time = msg_value.get("client_time").and_then(|t| t.as_i64());
+                                                let _ = session
+                                                    .text(json!({
+                                                        "type": "time_sync",
+                                                        "client_time": client_time,
+                                                        "server_time": Utc::now().timestamp_millis()
+                                                    }).to_string())
+                                                    .await;
+                                            }
                                             "join_lobby" => {
                                                 // Oyun lobisine katılım isteği
                                                 if let (Some(game_code), Some(nickname)) = (
                                                     msg_value.get("game_code").and_then(|g| g.as_str()),
                                                     msg_value.get("nickname").and_then(|n| n.as_str())
                                                 ) {
-                                                    handle_join_lobby(&mut session, &db_pool, game_code, nickname, &session_id, &app_state).await;
+                                                    let options = JoinOptions {
+                                                        avatar: msg_value.get("avatar").and_then(|a| a.as_str()),
+                                                        locale: msg_value.get("locale").and_then(|l| l.as_str()),
+                                                        recaptcha_token: msg_value.get("recaptcha_token").and_then(|t| t.as_str()),
+                                                    };
+                                                    metrics::with_context(
+                                                        RequestContext::websocket(Some(game_code)),
+                                                        handle_join_lobby(&mut session, &db_pool, game_code, nickname, options, &session_id, &app_state),
+                                                    )
+                                                    .await;
+                                                }
+                                            }
+                                            "watch_game" => {
+                                                // Oyuncu olmadan oyunu izleme isteği (ör. sınıf projeksiyonu)
+                                                if let Some(game_code) = msg_value.get("game_code").and_then(|g| g.as_str()) {
+                                                    handle_watch_game(&mut session, &db_pool, game_code, &session_id, &app_state).await;
                                                 }
                                             }
                                             "start_game" => {
                                                 // Oyun başlatma isteği
                                                 if let Some(game_code) = msg_value.get("game_code").and_then(|g| g.as_str()) {
-                                                    handle_start_game(&mut session, &db_pool, game_code, &session_id, &app_state).await;
+                                                    metrics::with_context(
+                                                        RequestContext::websocket(Some(game_code)),
+                                                        handle_start_game(&mut session, &db_pool, game_code, &session_id, &app_state),
+                                                    )
+                                                    .await;
                                                 }
                                             }
                                             "submit_answer" => {
@@ -476,35 +1565,112 @@ async fn websocket_task(
                                                     msg_value.get("answer").and_then(|a| a.as_str()),
                                                     msg_value.get("response_time_ms").and_then(|r| r.as_i64()),
                                                 ) {
-                                                    handle_submit_answer(&mut session, &db_pool, question_id as i32, answer, response_time as i32, &session_id, &app_state).await;
+                                                    metrics::with_context(
+                                                        RequestContext::websocket(connection_game_code.as_deref()),
+                                                        handle_submit_answer(&mut session, &db_pool, question_id as i32, answer, response_time as i32, &session_id, &app_state),
+                                                    )
+                                                    .await;
+                                                }
+                                            }
+                                            "retract_answer" => {
+                                                // Oyuncu, soru süresi dolmadan verdiği cevabı geri alır
+                                                if let Some(question_id) = msg_value.get("question_id").and_then(|q| q.as_i64()) {
+                                                    metrics::with_context(
+                                                        RequestContext::websocket(connection_game_code.as_deref()),
+                                                        handle_retract_answer(&mut session, &db_pool, question_id as i32, &session_id, &app_state),
+                                                    )
+                                                    .await;
                                                 }
                                             }
                                             "next_question" => {
                                                 // Bir sonraki soru isteği
                                                 if let Some(game_code) = msg_value.get("game_code").and_then(|g| g.as_str()) {
-                                                    handle_next_question(&mut session, &db_pool, game_code, &session_id, &app_state).await;
+                                                    metrics::with_context(
+                                                        RequestContext::websocket(Some(game_code)),
+                                                        handle_next_question(&mut session, &db_pool, game_code, &session_id, &app_state),
+                                                    )
+                                                    .await;
+                                                }
+                                            }
+                                            "start_sudden_death" => {
+                                                // Host'un ani ölüm turunu başlatma isteği
+                                                if let (Some(game_code), Some(top_n)) = (
+                                                    msg_value.get("game_code").and_then(|g| g.as_str()),
+                                                    msg_value.get("top_n").and_then(|n| n.as_i64()),
+                                                ) {
+                                                    metrics::with_context(
+                                                        RequestContext::websocket(Some(game_code)),
+                                                        handle_start_sudden_death(&mut session, &db_pool, game_code, top_n.clamp(0, i32::MAX as i64) as i32, &session_id, &app_state),
+                                                    )
+                                                    .await;
+                                                }
+                                            }
+                                            "extend_timer" => {
+                                                // Host gösterilen sorunun süresini uzatır (ör. yangın tatbikatı, uzun soru)
+                                                if let Some(game_code) = msg_value.get("game_code").and_then(|g| g.as_str()) {
+                                                    let seconds = msg_value
+                                                        .get("seconds")
+                                                        .and_then(|s| s.as_u64())
+                                                        .unwrap_or(DEFAULT_TIMER_EXTENSION_SECS);
+                                                    handle_extend_timer(&mut session, &db_pool, game_code, seconds, &session_id, &app_state).await;
+                                                }
+                                            }
+                                            "show_media" | "pause_media" => {
+                                                // Host'un medya oynatma/duraklatma komutu
+                                                if let Some(game_code) = msg_value.get("game_code").and_then(|g| g.as_str()) {
+                                                    let position_ms = msg_value.get("position_ms").and_then(|p| p.as_i64()).unwrap_or(0);
+                                                    handle_media_control(&mut session, &db_pool, game_code, msg_type, position_ms, &session_id, &app_state).await;
+                                                }
+                                            }
+                                            "set_scene" => {
+                                                // Host'un sahne değişikliği (müzik ve animasyon eşlemesi için)
+                                                if let (Some(game_code), Some(scene)) = (
+                                                    msg_value.get("game_code").and_then(|g| g.as_str()),
+                                                    msg_value.get("scene").and_then(|s| s.as_str()),
+                                                ) {
+                                                    handle_scene_change(&mut session, &db_pool, game_code, scene, &session_id, &app_state).await;
+                                                }
+                                            }
+                                            "focus_change" => {
+                                                // İstemci sekme/uygulama değiştirdiğini bildirir (ör. visibilitychange)
+                                                if let Some(state) = msg_value.get("state").and_then(|s| s.as_str()) {
+                                                    if state == "hidden" || state == "blur" {
+                                                        anti_cheat::record_focus_change(&db_pool, &session_id, state).await;
+                                                    }
                                                 }
                                             }
                                             "reconnect" => {
                                                 // Yeniden bağlanma isteği
                                                 if let Some(old_session_id) = msg_value.get("old_session_id").and_then(|s| s.as_str()) {
-                                                    handle_reconnect(&mut session, &db_pool, old_session_id, &session_id, &app_state).await;
+                                                    // İstemcinin en son aldığı olay numarası; gönderilmezse tüm olaylar tekrar gönderilir
+                                                    let last_seq = msg_value.get("last_seq").and_then(|s| s.as_i64()).unwrap_or(0) as i32;
+                                                    handle_reconnect(&mut session, &db_pool, old_session_id, last_seq, &session_id, &app_state).await;
                                                 }
                                             }
                                             // Diğer mesaj tipleri burada işlenebilir
                                             _ => {
                                                 warn!("Bilinmeyen mesaj tipi: {}", msg_type);
+                                                let _ = session
+                                                    .text(ws_error(WsErrorCode::UnknownMessageType, "Bilinmeyen mesaj tipi"))
+                                                    .await;
                                             }
                                         }
+                                    } else {
+                                        let _ = session
+                                            .text(ws_error(WsErrorCode::InvalidMessage, "Mesaj tipi belirtilmedi"))
+                                            .await;
                                     }
                                 }
                                 Err(e) => {
                                     error!("JSON ayrıştırma hatası: {}", e);
+                                    let _ = session
+                                        .text(ws_error(WsErrorCode::InvalidMessage, "Mesaj JSON formatında olmalıdır"))
+                                        .await;
                                 }
                             }
                         }
                         Message::Binary(_) => {
-                            debug!("İkili mesaj alındı");
+                            // İkili mesajlar yukarıda çözümlenir
                         }
                         Message::Ping(bytes) => {
                             if let Err(e) = session.pong(&bytes).await {
@@ -544,7 +1710,9 @@ async fn websocket_task(
     // Aktif bağlantıları temizle
     {
         let mut connections = active_connections.lock().await;
-        connections.remove(&session_id);
+        if let Some(conn) = connections.remove(&session_id) {
+            conn.outbox.close();
+        }
     }
 
     // Veritabanından aktif bağlantıyı kaldır
@@ -561,6 +1729,14 @@ async fn websocket_task(
         );
     }
 
+    // Host'a oyuncunun bağlantısının koptuğunu bildir
+    app_state.set_presence(&session_id, Presence::Disconnected).await;
+
+    // İzleyiciyse izleme süresini kapat
+    if app_state.remove_viewer(&session_id).await {
+        viewers::record_leave(&db_pool, &session_id).await;
+    }
+
     // Oyun lobisinden oyuncuyu kaldır
     {
         let mut games_lock = games.lock().await;
@@ -569,9 +1745,10 @@ async fn websocket_task(
         
         for (code, game) in games_lock.iter_mut() {
             if game.players.contains_key(&session_id) {
-                // Oyuncuyu pasif olarak işaretle
+                // Oyuncuyu pasif olarak işaretle; durumu yeniden bağlanma süresi boyunca korunur
                 if let Some(player) = game.players.get_mut(&session_id) {
                     player.is_active = false;
+                    player.last_seen = Instant::now();
                     game_to_update = Some(code.clone());
                 }
                 break;
@@ -579,8 +1756,8 @@ async fn websocket_task(
         }
         
         // Eğer bu oyuncu bir oyunun host'u ise, oyunu sonlandır
-        if let Some(game_code) = game_to_update {
-            if let Some(game) = games_lock.get(&game_code) {
+        if let Some(game_code) = &game_to_update {
+            if let Some(game) = games_lock.get(game_code) {
                 if game.host_session_id == session_id {
                     // Oyunu sonlandır ve tüm oyunculara bildir
                     info!("Host ayrıldı, oyun sonlandırılıyor: {}", game_code);
@@ -594,21 +1771,34 @@ async fn websocket_task(
                     .execute(&*db_pool)
                     .await;
                     
-                    // Tüm oyunculara bildir
+                    let sequence = game_events::record_event(&db_pool, game.id, game_events::EVENT_GAME_END, json!({
+                        "reason": "host_left"
+                    })).await;
+                    
+                    // Tüm oyunculara bildir ve bağlantılarını kapat
                     drop(games_lock); // Kilidi bırak
-                    let _ = app_state.broadcast_to_game(&game_code, &json!({
-                        "type": "game_end",
-                        "reason": "host_left",
-                        "message": "Sunucu bağlantısı kesildi, oyun sonlandırıldı"
-                    }).to_string()).await;
+                    app_state.broadcast_to_game(game_code, WebSocketMessage::GameEnd {
+                        reason: Some("host_left".to_string()),
+                        results: None,
+                        message: "Sunucu bağlantısı kesildi, oyun sonlandırıldı".to_string(),
+                        seq: sequence,
+                    }).await;
+                    app_state.close_game_players(game_code, WsCloseReason::GameEnded).await;
+                    app_state.remove_game(game_code).await;
                     return;
                 }
             }
         }
+        
+        // Aktif oyuncu sayısı değiştiyse oyunculara bildir
+        drop(games_lock);
+        if let Some(game_code) = game_to_update {
+            app_state.refresh_player_count(&game_code).await;
+        }
     }
 
     // WebSocket oturumunu kapat
-    let _ = session.close(None).await;
+    let _ = session.close(close_reason.map(Into::into)).await;
 
     info!(
         "WebSocket bağlantısı kapatıldı: user_id={}, session_id={}",
@@ -617,19 +1807,109 @@ async fn websocket_task(
 }
 
 // Oyun mesajları için handler fonksiyonları
+// Oyuncunun lobiye katılırken seçtiği avatar, soruların gösterileceği dil ve misafirler için reCAPTCHA tokenı
+// (frontend'de "guest_join" işlemiyle üretilir, REST katılımındaki X-Recaptcha-Token ile aynı)
+struct JoinOptions<'a> {
+    avatar: Option<&'a str>,
+    locale: Option<&'a str>,
+    recaptcha_token: Option<&'a str>,
+}
+
+// Misafir katılımı için "guest_join" işlemiyle üretilmiş geçerli bir reCAPTCHA tokenı veya bu işlem için
+// yetkili bir API anahtarı gerekir (bkz. RecaptchaValidator); REST'teki /api/game/join ile aynı kurallar
+async fn verify_guest_join(
+    db_pool: &Pool<Postgres>,
+    session_id: &str,
+    app_state: &web::Data<AppState>,
+    recaptcha_token: Option<&str>,
+) -> Result<(), String> {
+    const ACTION: &str = "guest_join";
+    
+    let api_client = app_state
+        .active_connections
+        .lock()
+        .await
+        .get(session_id)
+        .and_then(|conn| conn.api_client.clone());
+    
+    if let Some(client) = api_client {
+        if !client.allows(ACTION) {
+            warn!("API anahtarı bu işlem için yetkili değil: ws (anahtar: {}, işlem: {})", client.id, ACTION);
+            return Err("API anahtarının bu işlem için yetkisi yok".to_string());
+        }
+        info!("reCAPTCHA API anahtarıyla atlandı: ws (anahtar: {}, işlem: {})", client.id, ACTION);
+        audit::record(db_pool, None, audit::API_KEY_USED, json!({
+            "api_key_id": client.id,
+            "api_key_name": client.name,
+            "action": ACTION,
+            "path": "/ws"
+        })).await;
+        return Ok(());
+    }
+    
+    let Some(min_score) = recaptcha::required_score(ACTION) else {
+        return Ok(());
+    };
+    match recaptcha_token {
+        Some(token) => recaptcha::verify_token(token, ACTION, min_score).await,
+        None => Err("reCAPTCHA doğrulaması gerekli".to_string()),
+    }
+}
+
 async fn handle_join_lobby(
     session: &mut Session,
     db_pool: &Pool<Postgres>,
     game_code: &str,
     nickname: &str,
+    options: JoinOptions<'_>,
     session_id: &str,
     app_state: &web::Data<AppState>,
 ) {
     info!("Oyun lobisine katılma isteği: game_code={}, nickname={}", game_code, nickname);
     
+    // Kullanıcı ID'sini al (varsa)
+    let user_id = sqlx::query!(
+        "SELECT user_id FROM active_connections WHERE session_id = $1",
+        session_id
+    )
+    .fetch_optional(db_pool)
+    .await
+    .ok()
+    .flatten()
+    .and_then(|r| r.user_id);
+    
+    // Misafirler REST katılımındaki gibi bot korumasından geçer
+    if user_id.is_none() {
+        if let Err(message) = verify_guest_join(db_pool, session_id, app_state, options.recaptcha_token).await {
+            let _ = session.text(ws_error(WsErrorCode::RecaptchaFailed, &message)).await;
+            return;
+        }
+    }
+    
+    // Oyun kodu yerine takma ad girilmiş olabilir
+    let game_code = match game_alias::resolve(db_pool, game_code, user_id).await {
+        Ok(Resolution::Code(code)) => code,
+        Ok(Resolution::NotFound) => {
+            let _ = session.text(ws_error(WsErrorCode::GameNotFound, "Oyun bulunamadı")).await;
+            return;
+        }
+        Ok(Resolution::Ambiguous) => {
+            let _ = session
+                .text(ws_error(WsErrorCode::AmbiguousAlias, "Bu takma ad birden fazla oyunda kullanılıyor, lütfen oyun kodunu girin"))
+                .await;
+            return;
+        }
+        Err(e) => {
+            error!("Oyun kodu çözümlenirken hata: {}", e);
+            let _ = session.text(ws_error(WsErrorCode::InternalError, "Oyuna katılınamadı")).await;
+            return;
+        }
+    };
+    let game_code = game_code.as_str();
+    
     // Oyunun varlığını kontrol et
     let game = sqlx::query!(
-        "SELECT id, status FROM games WHERE code = $1",
+        "SELECT id, status, roster_class_id FROM games WHERE code = $1",
         game_code
     )
     .fetch_optional(db_pool)
@@ -639,43 +1919,74 @@ async fn handle_join_lobby(
         Ok(Some(game)) => {
             // Oyun durumunu kontrol et
             if game.status != "lobby" {
-                let _ = session.text(
-                    json!({
-                        "type": "error",
-                        "message": "Bu oyun artık katılıma açık değil"
-                    })
-                    .to_string(),
-                )
-                .await;
+                let _ = session.text(ws_error(WsErrorCode::GameNotJoinable, "Bu oyun artık katılıma açık değil")).await;
                 return;
             }
             
-            // Kullanıcı ID'sini al (varsa)
-            let user_id = sqlx::query!(
-                "SELECT user_id FROM active_connections WHERE session_id = $1",
-                session_id
-            )
-            .fetch_optional(db_pool)
-            .await
-            .ok()
-            .flatten()
-            .and_then(|r| r.user_id);
+            // Oyun bir sınıf listesiyle sınırlandırılmışsa yalnızca listedeki öğrenciler katılabilir
+            let roster_nickname = match (game.roster_class_id, user_id) {
+                (Some(class_id), Some(user_id)) => match roster::roster_nickname(db_pool, class_id, user_id).await {
+                    Ok(nickname) => nickname,
+                    Err(e) => {
+                        error!("Sınıf listesi kontrol edilirken hata: {}", e);
+                        let _ = session.text(ws_error(WsErrorCode::InternalError, "Oyuna katılınamadı")).await;
+                        return;
+                    }
+                },
+                _ => None,
+            };
+            
+            if game.roster_class_id.is_some() && roster_nickname.is_none() {
+                let _ = session
+                    .text(ws_error(WsErrorCode::NotOnRoster, "Bu oyuna yalnızca sınıf listesindeki öğrenciler katılabilir"))
+                    .await;
+                return;
+            }
             
             // Misafir oyuncu kontrolü ve nickname oluşturma
             let is_guest = user_id.is_none(); // Oturum açmış kullanıcı yoksa misafir
-            let display_name = if is_guest {
-                if !nickname.starts_with("**") {
-                    format!("**{}", nickname)
-                } else {
-                    nickname.to_string()
+            let display_name = if let Some(nickname) = roster_nickname {
+                nickname // Sınıf listesindeki ad kullanılır
+            } else if is_guest {
+                let Some(base) = guest_nickname_base(nickname) else {
+                    let _ = session
+                        .text(ws_error(WsErrorCode::InvalidNickname, CONFIG.username_charset.nickname_rule_message()))
+                        .await;
+                    return;
+                };
+                
+                // Kayıtlı bir öğrencinin adıyla çakışıyorsa sayı eklenir
+                match resolve_guest_nickname(db_pool, game.id, &base).await {
+                    Ok(name) => name,
+                    Err(e) => {
+                        error!("Takma ad kontrol edilirken hata: {}", e);
+                        let _ = session.text(ws_error(WsErrorCode::InternalError, "Oyuna katılınamadı")).await;
+                        return;
+                    }
                 }
             } else {
-                nickname.to_string() // Oturum açmış kullanıcıların isimlerine dokunma
+                clean_nickname(nickname) // Oturum açmış kullanıcıların isimleri yalnızca kırpılır
             };
             
-            // Nickname benzersizliğini kontrol et
+            // Turnuvanın sonraki turlarına yalnızca elenmemiş katılımcılar katılabilir
+            match tournament::may_join(db_pool, game.id, user_id, &display_name).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    let _ = session
+                        .text(ws_error(WsErrorCode::NotQualified, "Bu tura yalnızca önceki turlardan yükselen katılımcılar katılabilir"))
+                        .await;
+                    return;
+                }
+                Err(e) => {
+                    error!("Turnuva katılımı kontrol edilirken hata: {}", e);
+                    let _ = session.text(ws_error(WsErrorCode::InternalError, "Oyuna katılınamadı")).await;
+                    return;
+                }
+            }
+            
+            // Nickname benzersizliğini kontrol et (büyük/küçük harf ve Türkçe karakter farkı gözetilmez)
             let existing_player = sqlx::query!(
-                "SELECT id FROM players WHERE game_id = $1 AND nickname = $2",
+                "SELECT id FROM players WHERE game_id = $1 AND normalize_nickname(nickname) = normalize_nickname($2)",
                 game.id,
                 display_name
             )
@@ -683,29 +1994,59 @@ async fn handle_join_lobby(
             .await;
             
             if let Ok(Some(_)) = existing_player {
-                let _ = session.text(
-                    json!({
-                        "type": "error",
-                        "message": "Bu takma ad zaten kullanılıyor"
-                    })
-                    .to_string(),
-                )
-                .await;
+                let _ = session.text(ws_error(WsErrorCode::NicknameTaken, "Bu takma ad zaten kullanılıyor")).await;
                 return;
             }
             
+            // Misafirler için IP adresi (hashlenmiş) kaydedilir ve oyunun IP sınırı uygulanır
+            let ip_hash = if is_guest {
+                app_state
+                    .active_connections
+                    .lock()
+                    .await
+                    .get(session_id)
+                    .and_then(|conn| conn.ip_hash.clone())
+            } else {
+                None
+            };
+            
+            if let Some(ip_hash) = &ip_hash {
+                let rejection = match guest_ip::guest_limit_reached(db_pool, game.id, ip_hash).await {
+                    Ok(Some(limit)) => Some((
+                        WsErrorCode::GuestLimitReached,
+                        format!("Bu ağdan en fazla {} misafir oyuncu katılabilir", limit),
+                    )),
+                    Ok(None) => None,
+                    Err(e) => {
+                        error!("IP sınırı kontrol edilirken hata: {}", e);
+                        Some((WsErrorCode::InternalError, "Oyuna katılınamadı".to_string()))
+                    }
+                };
+                
+                if let Some((code, message)) = rejection {
+                    let _ = session.text(ws_error(code, &message)).await;
+                    return;
+                }
+            }
+            
+            let avatar = cosmetics::resolve_player_avatar(db_pool, user_id, options.avatar).await;
+            let locale = options.locale.and_then(localization::normalize_locale);
+            
             // Oyuncuyu ekle
             let player_result = sqlx::query!(
                 r#"
-                INSERT INTO players (game_id, user_id, nickname, session_id, joined_at)
-                VALUES ($1, $2, $3, $4, $5)
+                INSERT INTO players (game_id, user_id, nickname, session_id, joined_at, avatar, ip_hash, locale)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
                 RETURNING id
                 "#,
                 game.id,
                 user_id,
                 display_name,
                 session_id,
-                Utc::now()
+                Utc::now(),
+                avatar,
+                ip_hash,
+                locale
             )
             .fetch_one(db_pool)
             .await;
@@ -727,6 +2068,13 @@ async fn handle_join_lobby(
                     .execute(db_pool)
                     .await;
                     
+                    // Misafirin oturumu bu oyunla sınırlı ve süreli
+                    if is_guest {
+                        if let Err(e) = guest_sessions::open(db_pool, player.id, game.id, session_id).await {
+                            error!("Misafir oturumu açılamadı (player_id={}): {}", player.id, e);
+                        }
+                    }
+                    
                     // AppState'deki active_connections'ı güncelle
                     {
                         let mut connections = app_state.active_connections.lock().await;
@@ -740,442 +2088,216 @@ async fn handle_join_lobby(
                     }
                     
                     // Oyun durumuna oyuncuyu ekle
-                    {
-                        let mut games = app_state.games.lock().await;
-                        if !games.contains_key(game_code) {
-                            // Oyun state'ini oluştur
-                            let total_questions = sqlx::query!(
-                                "SELECT COUNT(*) as count FROM questions WHERE question_set_id = (SELECT question_set_id FROM games WHERE id = $1)",
-                                game.id
-                            )
-                            .fetch_one(db_pool)
-                            .await
-                            .map(|r| r.count.unwrap_or(0) as i32)
-                            .unwrap_or(0);
-                            
-                            let host_info = sqlx::query!(
-                                "SELECT host_id, question_set_id FROM games WHERE id = $1",
-                                game.id
-                            )
-                            .fetch_one(db_pool)
-                            .await;
-                            
-                            if let Ok(host) = host_info {
-                                // Oyun host'unun session ID'sini bul
-                                let host_session = sqlx::query!(
-                                    "SELECT session_id FROM active_connections WHERE user_id = $1 AND game_id = $2",
-                                    host.host_id,
-                                    game.id
-                                )
-                                .fetch_optional(db_pool)
-                                .await
-                                .ok()
-                                .flatten()
-                                .map(|r| r.session_id)
-                                .unwrap_or_else(|| "unknown".to_string());
-                                
-                                games.insert(game_code.to_string(), GameState {
-                                    id: game.id,
-                                    code: game_code.to_string(),
-                                    host_session_id: host_session,
-                                    host_id: host.host_id,
-                                    question_set_id: host.question_set_id,
-                                    players: HashMap::new(),
-                                    current_question: -1, // Henüz başlamamış
-                                    state: ConnectionState::Lobby,
-                                    started_at: None,
-                                    ended_at: None,
-                                    question_timer: None,
-                                    question_duration: None,
-                                    total_questions,
-                                });
-                            }
-                        }
-                        
-                        // Oyuna oyuncuyu ekle
-                        if let Some(game_state) = games.get_mut(game_code) {
-                            game_state.players.insert(session_id.to_string(), PlayerState {
-                                player_id: player.id,
-                                user_id,
-                                session_id: session_id.to_string(),
-                                nickname: display_name.clone(),
-                                score: 0,
-                                answers: HashMap::new(),
-                                is_active: true,
-                                joined_at: Instant::now(),
-                                last_seen: Instant::now(),
-                                last_answer_time: None,
-                            });
-                        }
-                    }
+                    app_state.register_player(game.id, game_code, session_id, user_id, LobbyPlayer {
+                        id: player.id,
+                        nickname: display_name.clone(),
+                        is_guest,
+                        avatar: avatar.clone(),
+                    }, locale.clone()).await;
                     
-                    // Oyuncuya katılım onayı gönder
+                    game_events::record_event(db_pool, game.id, game_events::EVENT_PLAYER_JOINED, json!({
+                        "player_id": player.id,
+                        "nickname": display_name,
+                        "is_guest": is_guest
+                    })).await;
+                    
+                    // Oyuncuya katılım onayı gönder; token REST cevap uç noktası içindir
+                    let (player_token, player_token_expires_at) = generate_player_token(player.id, game.id, session_id);
                     let _ = session.text(
                         json!({
                             "type": "join_success",
                             "player_id": player.id,
                             "game_code": game_code,
+                            "player_token": player_token,
+                            "player_token_expires_at": player_token_expires_at,
                             "nickname": display_name,
-                            "is_guest": is_guest
+                            "is_guest": is_guest,
+                            "avatar": avatar,
+                            "locale": locale,
+                            "accommodations": app_state.player_accommodations(game_code, session_id).await
                         })
                         .to_string(),
                     )
                     .await;
                     
-                    // Lobideki oyuncuları getir
-                    let players = sqlx::query!(
-                        r#"
-                        SELECT p.id, p.nickname, p.user_id IS NULL as is_guest
-                        FROM players p
-                        WHERE p.game_id = $1 AND p.is_active = true
-                        "#,
-                        game.id
-                    )
-                    .fetch_all(db_pool)
-                    .await;
+                    // Tüm lobiye güncel oyuncu listesini gönder
+                    app_state.broadcast_lobby(game.id, game_code).await;
                     
-                    if let Ok(players) = players {
-                        let player_list: Vec<serde_json::Value> = players
-                            .iter()
-                            .map(|p| {
-                                json!({
-                                    "player_id": p.id,
-                                    "nickname": p.nickname,
-                                    "is_guest": p.is_guest.unwrap_or(false)
-                                })
-                            })
-                            .collect();
-                        
-                        // Tüm lobiye yeni oyuncu bildirimini gönder
-                        let lobby_update = json!({
-                            "type": "lobby_update",
-                            "game_code": game_code,
-                            "players": player_list
-                        })
-                        .to_string();
-                        
-                        let _ = app_state.broadcast_to_game(game_code, &lobby_update).await;
+                    // Rekabetçi oyunda aynı IP'yi paylaşan misafirler için öğretmeni uyar
+                    if ip_hash.is_some() {
+                        if let Ok(groups) = guest_ip::shared_ip_warnings(db_pool, game.id).await {
+                            if !groups.is_empty() {
+                                let warning = WebSocketMessage::GuestIpWarning {
+                                    game_code: game_code.to_string(),
+                                    groups,
+                                };
+                                app_state.send_to_host(game_code, warning).await;
+                            }
+                        }
                     }
                 }
+                // Aynı takma ad kontrolden sonra başka bir oyuncu tarafından alındı
+                Err(e) if is_nickname_conflict(&e) => {
+                    let _ = session.text(ws_error(WsErrorCode::NicknameTaken, "Bu takma ad zaten kullanılıyor")).await;
+                }
                 Err(e) => {
                     error!("Oyuncu kaydedilirken hata: {}", e);
-                    let _ = session.text(
-                        json!({
-                            "type": "error",
-                            "message": "Oyuna katılırken bir hata oluştu"
-                        })
-                        .to_string(),
-                    )
-                    .await;
+                    let _ = session.text(ws_error(WsErrorCode::InternalError, "Oyuna katılırken bir hata oluştu")).await;
                 }
             }
         }
         Ok(None) => {
-            let _ = session.text(
-                json!({
-                    "type": "error",
-                    "message": "Oyun bulunamadı"
-                })
-                .to_string(),
-            )
-            .await;
+            let _ = session.text(ws_error(WsErrorCode::GameNotFound, "Oyun bulunamadı")).await;
         }
         Err(e) => {
             error!("Veritabanı sorgu hatası: {}", e);
-            let _ = session.text(
-                json!({
-                    "type": "error",
-                    "message": "Oyuna katılırken bir hata oluştu"
-                })
-                .to_string(),
-            )
-            .await;
+            let _ = session.text(ws_error(WsErrorCode::InternalError, "Oyuna katılırken bir hata oluştu")).await;
         }
     }
 }
 
-async fn handle_start_game(
-    session: &mut Session,
-    db_pool: &Pool<Postgres>,
-    game_code: &str,
-    session_id: &str,
-    app_state: &web::Data<AppState>,
-) {
-    // Oyun ve host kontrolü
-    let game = sqlx::query!(
-        r#"
-        SELECT g.id, g.host_id, g.status, g.question_set_id,
-               ac.user_id
-        FROM games g
-        JOIN active_connections ac ON ac.session_id = $1
-        WHERE g.code = $2
-        "#,
-        session_id,
-        game_code
+// Oturumun bağlı olduğu kullanıcı (host kontrolü için)
+async fn session_user_id(db_pool: &Pool<Postgres>, session_id: &str) -> Result<Option<i32>, sqlx::Error> {
+    Ok(sqlx::query!(
+        "SELECT user_id FROM active_connections WHERE session_id = $1",
+        session_id
     )
     .fetch_optional(db_pool)
-    .await;
-
-    match game {
-        Ok(Some(g)) => {
-            // Sadece host oyunu başlatabilir
-            if g.user_id != Some(g.host_id) {
-                let _ = session.text(
-                    json!({
-                        "type": "error",
-                        "message": "Sadece oyun sahibi oyunu başlatabilir"
-                    })
-                    .to_string(),
-                )
-                .await;
-                return;
-            }
-
-            if g.status != "lobby" {
-                let _ = session.text(
-                    json!({
-                        "type": "error",
-                        "message": "Bu oyun zaten başlatılmış veya sonlanmış"
-                    })
-                    .to_string(),
-                )
-                .await;
-                return;
-            }
-
-            // Oyun durumunu güncelle
-            let update_result = sqlx::query!(
-                r#"
-                UPDATE games SET status = $1, started_at = $2
-                WHERE id = $3
-                "#,
-                GameStatus::Active.to_string().to_lowercase(),
-                Utc::now(),
-                g.id
-            )
-            .execute(db_pool)
-            .await;
-
-            if let Err(e) = update_result {
-                error!("Oyun başlatılırken hata: {}", e);
-                let _ = session.text(
-                    json!({
-                        "type": "error",
-                        "message": "Oyun başlatılırken bir hata oluştu"
-                    })
-                    .to_string(),
-                )
-                .await;
-                return;
-            }
-
-            // Oyun durumunu bellekte güncelle
-            {
-                let mut games = app_state.games.lock().await;
-                if let Some(game_state) = games.get_mut(game_code) {
-                    game_state.state = ConnectionState::Game;
-                    game_state.started_at = Some(Instant::now());
-                }
-            }
-
-            // Tüm oyunculara oyunun başladığını bildir
-            let start_message = json!({
-                "type": "game_started",
-                "game_code": game_code,
-                "message": "Oyun başlatıldı, ilk soru için hazırlanın!"
-            })
-            .to_string();
-
-            let _ = app_state.broadcast_to_game(game_code, &start_message).await;
+    .await?
+    .and_then(|r| r.user_id))
+}
 
-            // İlk soruyu yükle
-            handle_next_question(session, db_pool, game_code, session_id, app_state).await;
-        }
-        Ok(None) => {
-            let _ = session.text(
-                json!({
-                    "type": "error",
-                    "message": "Oyun bulunamadı"
-                })
-                .to_string(),
-            )
-            .await;
-        }
-        Err(e) => {
-            error!("Veritabanı sorgu hatası: {}", e);
-            let _ = session.text(
-                json!({
-                    "type": "error",
-                    "message": "Oyun başlatılırken bir hata oluştu"
-                })
-                .to_string(),
-            )
-            .await;
-        }
+// Oyun servisinin hatalarını istemciye gönderilecek koda çevir
+fn game_error_code(e: &GameError) -> WsErrorCode {
+    match e {
+        GameError::GameNotFound => WsErrorCode::GameNotFound,
+        GameError::NotHost => WsErrorCode::NotHost,
+        GameError::AlreadyStarted => WsErrorCode::GameAlreadyStarted,
+        GameError::NotActive => WsErrorCode::GameNotActive,
+        GameError::PlayerNotFound => WsErrorCode::PlayerNotFound,
+        GameError::QuestionNotFound => WsErrorCode::QuestionNotFound,
+        GameError::QuestionNotActive => WsErrorCode::QuestionNotActive,
+        GameError::InvalidAnswer => WsErrorCode::InvalidMessage,
+        GameError::AlreadyAnswered => WsErrorCode::AlreadyAnswered,
+        GameError::NotAnswered => WsErrorCode::AnswerNotFound,
+        GameError::NotFinalist => WsErrorCode::NotFinalist,
+        GameError::SuddenDeathUnavailable => WsErrorCode::SuddenDeathUnavailable,
+        GameError::InvalidFinalistCount => WsErrorCode::InvalidMessage,
+        GameError::Database => WsErrorCode::InternalError,
     }
 }
 
-async fn handle_submit_answer(
+async fn handle_watch_game(
     session: &mut Session,
     db_pool: &Pool<Postgres>,
-    question_id: i32,
-    answer: &str,
-    response_time_ms: i32,
+    game_code: &str,
     session_id: &str,
     app_state: &web::Data<AppState>,
 ) {
-    // Oyuncu bilgilerini al
-    let player = sqlx::query!(
-        r#"
-        SELECT p.id, p.game_id, p.nickname, g.code as game_code
-        FROM players p 
-        JOIN games g ON p.game_id = g.id
-        JOIN active_connections ac ON p.session_id = ac.session_id
-        WHERE ac.session_id = $1
-        "#,
-        session_id
-    )
-    .fetch_optional(db_pool)
-    .await;
-
-    match player {
-        Ok(Some(p)) => {
-            // Sorunun doğru cevabını kontrol et
-            let question = sqlx::query!(
-                "SELECT correct_option FROM questions WHERE id = $1",
-                question_id
-            )
-            .fetch_optional(db_pool)
-            .await;
-
-            match question {
-                Ok(Some(q)) => {
-                    let is_correct = answer.to_uppercase() == q.correct_option;
-                    
-                    // Puanı hesapla
-                    let points = if is_correct {
-                        // Hızlı cevaplar daha çok puan alır
-                        let max_points = 1000;
-                        let min_points = 100;
-                        let max_time_ms = 10000; // 10 saniye
-                        
-                        let time_factor = (max_time_ms - response_time_ms).max(0) as f64 / max_time_ms as f64;
-                        (min_points as f64 + (max_points - min_points) as f64 * time_factor) as i32
-                    } else {
-                        0
-                    };
-
-                    // Cevabı kaydet
-                    let answer_result = sqlx::query!(
-                        r#"
-                        INSERT INTO player_answers 
-                        (player_id, question_id, answer, is_correct, response_time_ms, points_earned)
-                        VALUES ($1, $2, $3, $4, $5, $6)
-                        "#,
-                        p.id,
-                        question_id,
-                        answer.to_uppercase(),
-                        is_correct,
-                        response_time_ms,
-                        points
-                    )
-                    .execute(db_pool)
-                    .await;
-
-                    if let Ok(_) = answer_result {
-                        // Oyuncu puanını güncelle
-                        let _ = sqlx::query!(
-                            "UPDATE players SET score = score + $1 WHERE id = $2",
-                            points,
-                            p.id
-                        )
-                        .execute(db_pool)
-                        .await;
-
-                        // Oyun durumunu güncelle (bellekte)
-                        {
-                            let mut games = app_state.games.lock().await;
-                            if let Some(game) = games.get_mut(&p.game_code) {
-                                if let Some(player_state) = game.players.get_mut(session_id) {
-                                    player_state.score += points;
-                                    player_state.last_answer_time = Some(Instant::now());
-                                    
-                                    let answer_obj = PlayerAnswer {
-                                        question_id,
-                                        answer: Some(answer.to_uppercase()),
-                                        is_correct,
-                                        response_time_ms,
-                                        points_earned: points,
-                                    };
-                                    
-                                    player_state.answers.insert(question_id, answer_obj);
-                                }
-                            }
-                        }
-
-                        // Oyuncuya sonucu bildir
-                        let _ = session.text(
-                            json!({
-                                "type": "answer_received",
-                                "question_id": question_id,
-                                "your_answer": answer.to_uppercase(),
-                                "is_correct": is_correct,
-                                "points_earned": points,
-                                "message": if is_correct {
-                                    format!("Doğru! {} puan kazandınız", points)
-                                } else {
-                                    "Yanlış cevap".to_string()
-                                }
-                            })
-                            .to_string(),
-                        )
-                        .await;
-                    }
-                }
-                Ok(None) => {
-                    let _ = session.text(
-                        json!({
-                            "type": "error",
-                            "message": "Soru bulunamadı"
-                        })
-                        .to_string(),
-                    )
-                    .await;
-                }
-                Err(e) => {
-                    error!("Veritabanı sorgu hatası: {}", e);
-                    let _ = session.text(
-                        json!({
-                            "type": "error",
-                            "message": "Cevabınız kaydedilirken bir hata oluştu"
-                        })
-                        .to_string(),
-                    )
-                    .await;
-                }
+    // Oyun kodu yerine takma ad girilmiş olabilir
+    let game_code = match game_alias::resolve(db_pool, game_code, None).await {
+        Ok(Resolution::Code(code)) => code,
+        Ok(Resolution::NotFound) => {
+            let _ = session.text(ws_error(WsErrorCode::GameNotFound, "Oyun bulunamadı")).await;
+            return;
+        }
+        Ok(Resolution::Ambiguous) => {
+            let _ = session
+                .text(ws_error(WsErrorCode::AmbiguousAlias, "Bu takma ad birden fazla oyunda kullanılıyor, lütfen oyun kodunu girin"))
+                .await;
+            return;
+        }
+        Err(e) => {
+            error!("Oyun kodu çözümlenirken hata: {}", e);
+            let _ = session.text(ws_error(WsErrorCode::InternalError, "Oyun izlenemiyor")).await;
+            return;
+        }
+    };
+    
+    match app_state.add_viewer(&game_code, session_id).await {
+        Ok(watching) => {
+            if let Some(game_id) = watching["game_id"].as_i64() {
+                viewers::record_join(db_pool, game_id as i32, session_id).await;
             }
+            info!("İzleyici oyuna bağlandı: game_code={}, session_id={}", game_code, session_id);
+            let _ = session.text(watching.to_string()).await;
+        }
+        Err(code) => {
+            let message = match code {
+                WsErrorCode::GameNotFound => "Oyun bulunamadı veya henüz başlamadı",
+                WsErrorCode::GameNotActive => "Oyun sona erdi",
+                WsErrorCode::ViewerLimitReached => "Bu oyun için izleyici sınırına ulaşıldı",
+                WsErrorCode::SessionAlreadyActive => "Bu bağlantı zaten bir oyunda",
+                _ => "Oyun izlenemiyor",
+            };
+            let _ = session.text(ws_error(code, message)).await;
+        }
+    }
+}
+
+async fn handle_start_game(
+    session: &mut Session,
+    db_pool: &Pool<Postgres>,
+    game_code: &str,
+    session_id: &str,
+    app_state: &web::Data<AppState>,
+) {
+    let result = match session_user_id(db_pool, session_id).await {
+        Ok(user_id) => GameService::new(db_pool).start_game(app_state, game_code, user_id).await,
+        Err(e) => Err(GameError::from(e)),
+    };
+
+    match result {
+        // İlk soruyu yükle
+        Ok(_) => handle_next_question(session, db_pool, game_code, session_id, app_state).await,
+        Err(e) => {
+            let _ = session.text(ws_error(game_error_code(&e), e.message())).await;
         }
-        Ok(None) => {
-            let _ = session.text(
-                json!({
-                    "type": "error",
-                    "message": "Aktif oyuncu bulunamadı"
-                })
-                .to_string(),
-            )
-            .await;
+    }
+}
+
+async fn handle_submit_answer(
+    session: &mut Session,
+    db_pool: &Pool<Postgres>,
+    question_id: i32,
+    answer: &str,
+    response_time_ms: i32,
+    session_id: &str,
+    app_state: &web::Data<AppState>,
+) {
+    match GameService::new(db_pool).submit_answer(app_state, session_id, question_id, answer, response_time_ms).await {
+        Ok(result) => {
+            // Oyuncuya cevabın alındığını bildir; sonuç soru bitince gösterilir
+            let _ = session.text(json!(result.receipt()).to_string()).await;
         }
         Err(e) => {
-            error!("Veritabanı sorgu hatası: {}", e);
+            let _ = session.text(ws_error(game_error_code(&e), e.message())).await;
+        }
+    }
+}
+
+async fn handle_retract_answer(
+    session: &mut Session,
+    db_pool: &Pool<Postgres>,
+    question_id: i32,
+    session_id: &str,
+    app_state: &web::Data<AppState>,
+) {
+    match GameService::new(db_pool).retract_answer(app_state, session_id, question_id).await {
+        Ok(question_id) => {
             let _ = session.text(
                 json!({
-                    "type": "error",
-                    "message": "Cevabınız kaydedilirken bir hata oluştu"
+                    "type": "answer_retracted",
+                    "question_id": question_id,
+                    "message": "Cevabınız geri alındı, süre dolmadan yeniden cevap verebilirsiniz"
                 })
                 .to_string(),
             )
             .await;
         }
+        Err(e) => {
+            let _ = session.text(ws_error(game_error_code(&e), e.message())).await;
+        }
     }
 }
 
@@ -1186,248 +2308,135 @@ async fn handle_next_question(
     session_id: &str,
     app_state: &web::Data<AppState>,
 ) {
-    // Oyun ve host kontrolü
-    let game = sqlx::query!(
-        r#"
-        SELECT g.id, g.host_id, g.status, g.current_question, g.question_set_id,
-               ac.user_id
-        FROM games g
-        JOIN active_connections ac ON ac.session_id = $1
-        WHERE g.code = $2
-        "#,
-        session_id,
-        game_code
-    )
-    .fetch_optional(db_pool)
-    .await;
-
-    match game {
-        Ok(Some(g)) => {
-            // Sadece host soruyu ilerletebilir
-            if g.user_id != Some(g.host_id) {
-                let _ = session.text(
-                    json!({
-                        "type": "error",
-                        "message": "Sadece oyun sahibi soruları ilerletebilir"
-                    })
-                    .to_string(),
-                )
-                .await;
-                return;
-            }
-
-            // Bir sonraki soruyu getir
-            let next_question = g.current_question.unwrap_or(-1) + 1;
-            
-            // Soru bilgilerini getir
-            let question = sqlx::query!(
-                r#"
-                SELECT id, question_text, option_a, option_b, option_c, option_d, 
-                       correct_option, time_limit, position
-                FROM questions
-                WHERE question_set_id = $1 AND position = $2
-                "#,
-                g.question_set_id,
-                next_question
-            )
-            .fetch_optional(db_pool)
-            .await;
-
-            // Toplam soru sayısını al
-            let total_questions = sqlx::query!(
-                "SELECT COUNT(*) as count FROM questions WHERE question_set_id = $1",
-                g.question_set_id
-            )
-            .fetch_one(db_pool)
-            .await
-            .map(|r| r.count.unwrap_or(0) as i64)
-            .unwrap_or(0);
-
-            match question {
-                Ok(Some(q)) => {
-                    // Oyun durumunu güncelle
-                    let _ = sqlx::query!(
-                        "UPDATE games SET current_question = $1 WHERE id = $2",
-                        next_question,
-                        g.id
-                    )
-                    .execute(db_pool)
-                    .await;
-
-                    // Oyun durumunu bellekte güncelle
-                    {
-                        let mut games = app_state.games.lock().await;
-                        if let Some(game_state) = games.get_mut(game_code) {
-                            game_state.current_question = next_question;
-                            game_state.state = ConnectionState::Question;
-                            game_state.question_timer = Some(Instant::now());
-                            game_state.question_duration = Some(Duration::from_secs(q.time_limit.unwrap_or(30) as u64));
-                        }
-                    }
-
-                    // Tüm oyunculara soruyu gönder
-                    let question_data = json!({
-                        "type": "question_start",
-                        "question_id": q.id,
-                        "question_text": q.question_text,
-                        "options": {
-                            "A": q.option_a,
-                            "B": q.option_b,
-                            "C": q.option_c, 
-                            "D": q.option_d
-                        },
-                        "time_limit": q.time_limit,
-                        "question_number": next_question + 1,
-                        "total_questions": total_questions
-                    });
-
-                    // Sorudan doğru cevabı çıkar (oyunculara gönderilmemeli)
-                    let mut question_without_answer = question_data.clone();
-                    if let Some(obj) = question_without_answer.as_object_mut() {
-                        obj.remove("correct_option");
-                    }
-
-                    // Tüm oyunculara soruyu gönder
-                    let _ = app_state.broadcast_to_game(game_code, &question_without_answer.to_string()).await;
-
-                    // Host'a doğru cevapla birlikte gönder
-                    let _ = session.text(
-                        json!({
-                            "type": "question_start",
-                            "question_id": q.id,
-                            "question_text": q.question_text,
-                            "options": {
-                                "A": q.option_a,
-                                "B": q.option_b,
-                                "C": q.option_c, 
-                                "D": q.option_d
-                            },
-                            "correct_option": q.correct_option,
-                            "time_limit": q.time_limit,
-                            "question_number": next_question + 1,
-                            "total_questions": total_questions
-                        })
-                        .to_string(),
-                    )
-                    .await;
-                }
-                Ok(None) => {
-                    // Soru kalmadı, oyunu bitir
-                    let _ = sqlx::query!(
-                        r#"
-                        UPDATE games SET status = 'completed', ended_at = NOW()
-                        WHERE id = $1
-                        "#,
-                        g.id
-                    )
-                    .execute(db_pool)
-                    .await;
+    let result = match session_user_id(db_pool, session_id).await {
+        Ok(user_id) => GameService::new(db_pool).next_question(app_state, game_code, user_id).await,
+        Err(e) => Err(GameError::from(e)),
+    };
 
-                    // Oyun durumunu bellekte güncelle
-                    {
-                        let mut games = app_state.games.lock().await;
-                        if let Some(game_state) = games.get_mut(game_code) {
-                            game_state.state = ConnectionState::Ended;
-                            game_state.ended_at = Some(Instant::now());
-                        }
-                    }
+    match result {
+        // Oyunculara soru yayınlandı; host'a doğru cevapla birlikte gönder
+        Ok(NextStep::Question(question)) => {
+            let _ = session.text(json!(question.host_message()).to_string()).await;
+        }
+        // Sonuçlar oyun servisi tarafından tüm bağlantılara yayınlandı
+        Ok(NextStep::Completed(_)) => {}
+        Err(e) => {
+            let _ = session.text(ws_error(game_error_code(&e), e.message())).await;
+        }
+    }
+}
 
-                    // Final skor tablosunu hesapla
-                    let leaderboard = app_state.get_leaderboard(game_code).await;
-
-                    if let Ok(leaderboard) = leaderboard {
-                        // Oyun sonu performans istatistiklerini hesapla
-                        let player_stats = sqlx::query!(
-                            r#"
-                            SELECT 
-                                p.id as player_id,
-                                p.nickname,
-                                p.score,
-                                COUNT(pa.id) as answer_count,
-                                COUNT(pa.id) FILTER (WHERE pa.is_correct) as correct_count,
-                                ROUND(AVG(pa.response_time_ms)) as avg_response_time
-                            FROM players p
-                            LEFT JOIN player_answers pa ON p.id = pa.player_id
-                            WHERE p.game_id = $1 AND p.is_active = true
-                            GROUP BY p.id, p.nickname, p.score
-                            ORDER BY p.score DESC
-                            "#,
-                            g.id
-                        )
-                        .fetch_all(db_pool)
-                        .await;
+async fn handle_start_sudden_death(
+    session: &mut Session,
+    db_pool: &Pool<Postgres>,
+    game_code: &str,
+    top_n: i32,
+    session_id: &str,
+    app_state: &web::Data<AppState>,
+) {
+    let result = match session_user_id(db_pool, session_id).await {
+        Ok(user_id) => GameService::new(db_pool).start_sudden_death(app_state, game_code, user_id, top_n).await,
+        Err(e) => Err(GameError::from(e)),
+    };
 
-                        let stats_json = if let Ok(stats) = player_stats {
-                            stats.iter().map(|s| {
-                                let accuracy = if s.answer_count.unwrap_or(0) > 0 {
-                                    (s.correct_count.unwrap_or(0) as f64 / s.answer_count.unwrap_or(0) as f64 * 100.0).round()
-                                } else {
-                                    0.0
-                                };
-                                
-                                // BigDecimal'ı doğrudan kullanmak yerine bir string ya da sayıya çevir
-                                let avg_time_value = match &s.avg_response_time {
-                                    Some(bd) => bd.to_string().parse::<f64>().unwrap_or(0.0),
-                                    None => 0.0
-                                };
-                                
-                                json!({
-                                    "player_id": s.player_id,
-                                    "nickname": s.nickname,
-                                    "score": s.score,
-                                    "answers": s.answer_count,
-                                    "correct": s.correct_count,
-                                    "accuracy": accuracy,
-                                    "avg_response_time_ms": avg_time_value
-                                })
-                            }).collect::<Vec<_>>()
-                        } else {
-                            Vec::new()
-                        };
+    // Finalistler oyun servisi tarafından tüm bağlantılara yayınlandı
+    if let Err(e) = result {
+        let _ = session.text(ws_error(game_error_code(&e), e.message())).await;
+    }
+}
 
-                        // Tüm oyunculara sonuçları gönder
-                        let _ = app_state.broadcast_to_game(game_code, &json!({
-                            "type": "game_end",
-                            "final_leaderboard": leaderboard,
-                            "player_stats": stats_json,
-                            "message": "Oyun tamamlandı, sonuçlar gösteriliyor"
-                        }).to_string()).await;
-                    }
-                }
-                Err(e) => {
-                    error!("Veritabanı sorgu hatası: {}", e);
-                    let _ = session.text(
-                        json!({
-                            "type": "error",
-                            "message": "Bir sonraki soru alınırken bir hata oluştu"
-                        })
-                        .to_string(),
-                    )
-                    .await;
-                }
-            }
+async fn handle_media_control(
+    session: &mut Session,
+    db_pool: &Pool<Postgres>,
+    game_code: &str,
+    action: &str,
+    position_ms: i64,
+    session_id: &str,
+    app_state: &web::Data<AppState>,
+) {
+    let user_id = match session_user_id(db_pool, session_id).await {
+        Ok(user_id) => user_id,
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            let _ = session.text(ws_error(WsErrorCode::InternalError, "Medya komutu işlenirken bir hata oluştu")).await;
+            return;
         }
-        Ok(None) => {
-            let _ = session.text(
-                json!({
-                    "type": "error",
-                    "message": "Oyun bulunamadı"
-                })
-                .to_string(),
-            )
+    };
+    
+    if let Err(code) = app_state.relay_media(game_code, user_id, action, position_ms.max(0)).await {
+        let message = match code {
+            WsErrorCode::GameNotFound => "Oyun bulunamadı",
+            WsErrorCode::NotHost => "Sadece oyun sahibi medyayı kontrol edebilir",
+            _ => "Oyun aktif değil",
+        };
+        let _ = session.text(ws_error(code, message)).await;
+    }
+}
+
+async fn handle_extend_timer(
+    session: &mut Session,
+    db_pool: &Pool<Postgres>,
+    game_code: &str,
+    seconds: u64,
+    session_id: &str,
+    app_state: &web::Data<AppState>,
+) {
+    if !(1..=MAX_TIMER_EXTENSION_SECS).contains(&seconds) {
+        let _ = session
+            .text(ws_error(WsErrorCode::InvalidMessage, "Uzatılacak süre 1-300 saniye arasında olmalıdır"))
             .await;
+        return;
+    }
+    
+    let user_id = match session_user_id(db_pool, session_id).await {
+        Ok(user_id) => user_id,
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            let _ = session.text(ws_error(WsErrorCode::InternalError, "Süre uzatılırken bir hata oluştu")).await;
+            return;
         }
+    };
+    
+    if let Err(code) = app_state.extend_question_timer(game_code, user_id, Duration::from_secs(seconds)).await {
+        let message = match code {
+            WsErrorCode::GameNotFound => "Oyun bulunamadı",
+            WsErrorCode::NotHost => "Sadece oyun sahibi süreyi uzatabilir",
+            _ => "Şu anda gösterilen bir soru yok",
+        };
+        let _ = session.text(ws_error(code, message)).await;
+    }
+}
+
+async fn handle_scene_change(
+    session: &mut Session,
+    db_pool: &Pool<Postgres>,
+    game_code: &str,
+    scene: &str,
+    session_id: &str,
+    app_state: &web::Data<AppState>,
+) {
+    if !scenes::is_valid_scene(scene) {
+        let _ = session.text(ws_error(WsErrorCode::InvalidMessage, "Geçersiz sahne")).await;
+        return;
+    }
+    
+    let user_id = match session_user_id(db_pool, session_id).await {
+        Ok(user_id) => user_id,
         Err(e) => {
             error!("Veritabanı sorgu hatası: {}", e);
-            let _ = session.text(
-                json!({
-                    "type": "error",
-                    "message": "Bir sonraki soruya geçilirken bir hata oluştu"
-                })
-                .to_string(),
-            )
-            .await;
+            let _ = session.text(ws_error(WsErrorCode::InternalError, "Sahne değiştirilirken bir hata oluştu")).await;
+            return;
         }
+    };
+    
+    if let Err(code) = app_state.relay_scene(game_code, user_id, scene).await {
+        let message = match code {
+            WsErrorCode::GameNotFound => "Oyun bulunamadı",
+            WsErrorCode::NotHost => "Sadece oyun sahibi sahneyi değiştirebilir",
+            WsErrorCode::InternalError => "Sahne değiştirilirken bir hata oluştu",
+            _ => "Oyun sona erdi",
+        };
+        let _ = session.text(ws_error(code, message)).await;
     }
 }
 
@@ -1436,6 +2445,7 @@ async fn handle_reconnect(
     session: &mut Session,
     db_pool: &Pool<Postgres>,
     old_session_id: &str,
+    last_seq: i32,
     new_session_id: &str,
     app_state: &web::Data<AppState>,
 ) {
@@ -1444,7 +2454,7 @@ async fn handle_reconnect(
     // Eski oturumun oyuncu bilgilerini kontrol et
     let player = sqlx::query!(
         r#"
-        SELECT p.id, p.game_id, p.user_id, p.nickname, p.score, p.is_active,
+        SELECT p.id, p.game_id, p.user_id, p.nickname, p.avatar, p.score, p.is_active, p.locale,
                g.code as game_code, g.status, g.current_question
         FROM players p
         JOIN games g ON p.game_id = g.id
@@ -1457,7 +2467,35 @@ async fn handle_reconnect(
     
     match player {
         Ok(Some(p)) => {
-            if !p.is_active.unwrap_or(false) {
+            // Misafirler yalnızca süresi dolmamış misafir oturumlarıyla geri dönebilir
+            if p.user_id.is_none() {
+                match guest_sessions::is_active(db_pool, p.id, old_session_id).await {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        let _ = session
+                            .text(ws_error(WsErrorCode::SessionNotFound, "Misafir oturumunun süresi doldu, lütfen oyuna yeniden katılın"))
+                            .await;
+                        return;
+                    }
+                    Err(e) => {
+                        error!("Misafir oturumu kontrol edilirken hata: {}", e);
+                        let _ = session.text(ws_error(WsErrorCode::InternalError, "Yeniden bağlanırken bir hata oluştu")).await;
+                        return;
+                    }
+                }
+            }
+            
+            // Bağlantısı kopup yeniden bağlanma süresi içinde dönen oyuncunun durumu bellekte korunur
+            let in_grace_period = {
+                let games = app_state.games.lock().await;
+                games
+                    .get(&p.game_code)
+                    .and_then(|game| game.players.get(old_session_id))
+                    .map(|player| !player.is_active)
+                    .unwrap_or(false)
+            };
+            
+            if !p.is_active.unwrap_or(false) || in_grace_period {
                 // Oyuncu aktif değilse aktifleştir
                 let _ = sqlx::query!(
                     "UPDATE players SET is_active = true, session_id = $1 WHERE id = $2",
@@ -1467,6 +2505,12 @@ async fn handle_reconnect(
                 .execute(db_pool)
                 .await;
                 
+                if p.user_id.is_none() {
+                    if let Err(e) = guest_sessions::rotate(db_pool, p.id, old_session_id, new_session_id).await {
+                        error!("Misafir oturumu yenilenemedi (player_id={}): {}", p.id, e);
+                    }
+                }
+                
                 // Aktif bağlantıları güncelle
                 let _ = sqlx::query!(
                     r#"
@@ -1494,40 +2538,84 @@ async fn handle_reconnect(
                     }
                     
                     // Eski bağlantıyı kaldır
-                    connections.remove(old_session_id);
+                    if let Some(old_conn) = connections.remove(old_session_id) {
+                        old_conn.outbox.close();
+                    }
                 }
                 
                 // Oyunu güncelle
+                let mut score = p.score.unwrap_or(0);
+                let mut player_accommodations = Accommodations::default();
+                let mut expired = false;
                 {
                     let mut games = app_state.games.lock().await;
                     if let Some(game) = games.get_mut(&p.game_code) {
-                        // Eski oyuncuyu kaldır
+                        // Eski oyuncuyu kaldır; yeniden bağlanma süresi dolduysa bellekte durumu kalmamıştır
+                        expired = !game.players.contains_key(old_session_id);
                         if let Some(player_state) = game.players.remove(old_session_id) {
-                            // Yeni session ID ile ekle
+                            score = player_state.score;
+                            player_accommodations = player_state.accommodations;
+                            // Yeni session ID ile ekle, cevaplar ve zamanlama bilgisi korunur
                             game.players.insert(new_session_id.to_string(), PlayerState {
                                 player_id: p.id,
                                 user_id: p.user_id,
                                 session_id: new_session_id.to_string(),
                                 nickname: p.nickname.clone(),
-                                score: p.score.unwrap_or(0),
+                                avatar: player_state.avatar,
+                                locale: player_state.locale,
+                                accommodations: player_state.accommodations,
+                                // Bellekteki puan, henüz yazılmamış cevapları da içerir
+                                score,
                                 answers: player_state.answers,
                                 is_active: true,
                                 joined_at: player_state.joined_at,
                                 last_seen: Instant::now(),
                                 last_answer_time: player_state.last_answer_time,
+                                presence: player_state.presence,
                             });
                         }
                     }
                 }
                 
-                // Oyuncuya mevcut oyun durumunu gönder
+                if expired {
+                    let restored = app_state
+                        .restore_player(
+                            p.game_id,
+                            &p.game_code,
+                            new_session_id,
+                            p.user_id,
+                            LobbyPlayer {
+                                id: p.id,
+                                nickname: p.nickname.clone(),
+                                is_guest: p.user_id.is_none(),
+                                avatar: p.avatar.clone(),
+                            },
+                            p.locale.clone(),
+                        )
+                        .await;
+                    match restored {
+                        Ok((restored_score, restored_accommodations)) => {
+                            score = restored_score;
+                            player_accommodations = restored_accommodations;
+                        }
+                        Err(e) => error!("Oyuncu durumu geri yüklenemedi (player_id={}): {}", p.id, e),
+                    }
+                }
+                
+                app_state.set_presence(new_session_id, Presence::Connected).await;
+                app_state.refresh_player_count(&p.game_code).await;
+                
+                // Oyuncuya mevcut oyun durumunu ve yeni oturuma bağlı oyuncu tokenını gönder; eski token artık geçersiz
+                let (player_token, player_token_expires_at) = generate_player_token(p.id, p.game_id, new_session_id);
                 let _ = session.text(
                     json!({
                         "type": "reconnect_success",
                         "player_id": p.id,
                         "game_code": p.game_code,
+                        "player_token": player_token,
+                        "player_token_expires_at": player_token_expires_at,
                         "nickname": p.nickname,
-                        "score": p.score,
+                        "score": score,
                         "game_status": p.status,
                         "current_question": p.current_question
                     })
@@ -1535,117 +2623,130 @@ async fn handle_reconnect(
                 )
                 .await;
                 
-                // Oyunun mevcut durumuna göre ek bilgi gönder
-                if p.status == "active" {
-                    // Mevcut soruyu gönder
-                    if let Some(current_q) = p.current_question {
-                        let question = sqlx::query!(
-                            r#"
-                            SELECT id, question_text, option_a, option_b, option_c, option_d, time_limit, position
-                            FROM questions
-                            WHERE question_set_id = (SELECT question_set_id FROM games WHERE id = $1)
-                            AND position = $2
-                            "#,
-                            p.game_id,
-                            current_q
-                        )
-                        .fetch_optional(db_pool)
-                        .await;
-                        
-                        if let Ok(Some(q)) = question {
-                            let _ = session.text(
-                                json!({
-                                    "type": "current_question",
-                                    "question_id": q.id,
-                                    "question_text": q.question_text,
-                                    "options": {
-                                        "A": q.option_a,
-                                        "B": q.option_b,
-                                        "C": q.option_c, 
-                                        "D": q.option_d
-                                    },
-                                    "time_limit": q.time_limit,
-                                    "question_number": q.position + 1
-                                })
-                                .to_string(),
-                            )
-                            .await;
-                            
-                            // Oyuncunun bu soruya cevap verip vermediğini kontrol et
-                            let answer = sqlx::query!(
-                                "SELECT answer, is_correct, points_earned FROM player_answers WHERE player_id = $1 AND question_id = $2",
-                                p.id,
-                                q.id
-                            )
-                            .fetch_optional(db_pool)
-                            .await;
+                // Kaçırılan olayları sırasıyla gönder; istemci son olaya göre doğru aşamaya (soru, sonuç, oyun sonu) geçer
+                match game_events::get_events_after(db_pool, p.game_id, last_seq).await {
+                    Ok(events) => {
+                        let mut open_question = None;
+                        for event in &events {
+                            match event.event_type.as_str() {
+                                game_events::EVENT_QUESTION_START => {
+                                    open_question = event.payload.get("question_id").and_then(|q| q.as_i64());
+                                }
+                                game_events::EVENT_QUESTION_END | game_events::EVENT_GAME_END => open_question = None,
+                                _ => {}
+                            }
                             
-                            if let Ok(Some(a)) = answer {
-                                // Oyuncu zaten cevap vermiş
-                                let _ = session.text(
-                                    json!({
-                                        "type": "answer_received",
-                                        "question_id": q.id,
-                                        "your_answer": a.answer,
-                                        "is_correct": a.is_correct,
-                                        "points_earned": a.points_earned,
-                                        "message": if a.is_correct {
-                                            format!("Doğru! {} puan kazandınız", a.points_earned.unwrap_or(0))
-                                        } else {
-                                            "Yanlış cevap".to_string()
+                            let mut message = event.to_message();
+                            let question = match event.event_type.as_str() {
+                                game_events::EVENT_QUESTION_START => serde_json::from_value::<QuestionMessage>(message.clone()).ok(),
+                                _ => None,
+                            };
+                            if let Some(mut question) = question {
+                                question.server_time = Utc::now().timestamp_millis();
+                                
+                                // Soru, oyuncunun katılırken seçtiği dilde yeniden gönderilir
+                                if let (Some(locale), Some(question_id)) = (&p.locale, open_question) {
+                                    match localization::question_translations(db_pool, question_id as i32).await {
+                                        Ok(translations) => {
+                                            if let Some(translation) = localization::pick_translation(&translations, locale) {
+                                                localization::apply_translation(&mut question, translation);
+                                            }
                                         }
-                                    })
-                                    .to_string(),
-                                )
-                                .await;
+                                        Err(e) => error!("Soru çevirileri alınamadı: {}", e),
+                                    }
+                                }
+                                player_accommodations.apply_to_question(&mut question);
+                                
+                                message = json!(WebSocketMessage::QuestionStart(question));
+                                message["replayed"] = json!(true);
                             }
+                            let _ = session.text(message.to_string()).await;
+                        }
+                        
+                        // Hâlâ açık olan soruya verilmiş cevabı bellekteki oyuncu durumundan bildir
+                        let answered = match open_question {
+                            Some(question_id) => {
+                                let games = app_state.games.lock().await;
+                                games
+                                    .get(&p.game_code)
+                                    .and_then(|game| game.players.get(new_session_id))
+                                    .and_then(|player| player.answers.get(&(question_id as i32)))
+                                    .map(|a| json!(game_service::answer_receipt(
+                                        a.question_id,
+                                        a.answer.as_deref().unwrap_or_default()
+                                    )))
+                            }
+                            None => None,
+                        };
+                        if let Some(answered) = answered {
+                            let _ = session.text(answered.to_string()).await;
                         }
                     }
-                    
-                    // Liderlik tablosunu gönder
-                    if let Ok(leaderboard) = app_state.get_leaderboard(&p.game_code).await {
-                        let _ = session.text(
-                            json!({
-                                "type": "leaderboard_update",
-                                "leaderboard": leaderboard
-                            })
-                            .to_string(),
-                        )
-                        .await;
+                    Err(e) => {
+                        error!("Veritabanı sorgu hatası: {}", e);
                     }
                 }
             } else {
                 // Oyuncu zaten aktif
-                let _ = session.text(
-                    json!({
-                        "type": "error",
-                        "message": "Bu oturum zaten aktif"
-                    })
-                    .to_string(),
-                )
-                .await;
+                let _ = session.text(ws_error(WsErrorCode::SessionAlreadyActive, "Bu oturum zaten aktif")).await;
             }
         }
         Ok(None) => {
-            let _ = session.text(
-                json!({
-                    "type": "error",
-                    "message": "Önceki oturum bulunamadı"
-                })
-                .to_string(),
-            )
-            .await;
+            let _ = session.text(ws_error(WsErrorCode::SessionNotFound, "Önceki oturum bulunamadı")).await;
         }
         Err(e) => {
             error!("Veritabanı sorgu hatası: {}", e);
-            let _ = session.text(
-                json!({
-                    "type": "error",
-                    "message": "Yeniden bağlanırken bir hata oluştu"
-                })
-                .to_string(),
-            )
-            .await;
+            let _ = session.text(ws_error(WsErrorCode::InternalError, "Yeniden bağlanırken bir hata oluştu")).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::ws_outbox::OUTBOX_CAPACITY;
+    use actix_web::http::header;
+    use actix_web::test::TestRequest;
+    use actix_web::FromRequest;
+
+    #[actix_web::test]
+    async fn test_enqueue_closes_slow_client_with_4005() {
+        let (req, mut payload) = TestRequest::get()
+            .insert_header((header::UPGRADE, "websocket"))
+            .insert_header((header::CONNECTION, "upgrade"))
+            .insert_header((header::SEC_WEBSOCKET_VERSION, "13"))
+            .insert_header((header::SEC_WEBSOCKET_KEY, "dGhlIHNhbXBsZSBub25jZQ=="))
+            .to_http_parts();
+        let payload = web::Payload::from_request(&req, &mut payload).await.unwrap();
+        let (response, session, _stream) = actix_ws::handle(&req, payload).unwrap();
+
+        let outbox = Arc::new(Outbox::default());
+        let connection = WebSocketConnection {
+            user_id: None,
+            player_id: Some(1),
+            game_id: Some(1),
+            game_code: Some("AB12CD".to_string()),
+            connection_type: ConnectionType::Player,
+            session: Some(session),
+            last_seen: Instant::now(),
+            ip_hash: None,
+            outbox: outbox.clone(),
+            api_client: None,
+        };
+
+        // Yazıcı task çalışmadığı için kuyruk boşalmaz; sınırı aşan mesaj bağlantıyı kapatır
+        let message = Arc::new(EncodedMessage::new(WebSocketMessage::Pong { timestamp: 0 }));
+        for _ in 0..=OUTBOX_CAPACITY {
+            connection.enqueue(None, &message);
         }
+        drop(connection);
+
+        // İstemciye giden son çerçeve 4005 (slow_client) kodlu kapatma çerçevesidir
+        let body = actix_web::body::to_bytes(response.into_body()).await.unwrap();
+        let reason = WsCloseReason::SlowClient.as_str().as_bytes();
+        let mut close_frame = vec![0x88, (2 + reason.len()) as u8];
+        close_frame.extend_from_slice(&4005u16.to_be_bytes());
+        close_frame.extend_from_slice(reason);
+        assert_eq!(body.as_ref(), close_frame.as_slice());
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,207 @@
+use actix_web::{http::header, web, HttpResponse, Responder};
+use futures_util::stream;
+use log::error;
+use sqlx::{Pool, Postgres};
+use std::time::Instant;
+
+use crate::db::models::{Claims, WidgetLeaderboardQuery};
+use crate::services::leaderboard_widget::{self, WidgetSnapshot, DEFAULT_LIMIT, STREAM_KEEPALIVE_INTERVAL, STREAM_POLL_INTERVAL};
+use crate::utils::security::{generate_widget_token, hash_widget_token};
+
+// Herkese açık widget uçlarının yolu; bu yollar her kaynaktan (CORS) çağrılabilir
+pub const PUBLIC_PATH_PREFIX: &str = "/api/widget/";
+
+// Oyunu bul ve kullanıcının oyun sahibi (veya admin) olduğunu doğrula
+async fn find_hosted_game(pool: &Pool<Postgres>, code: &str, claims: &Claims) -> Result<i32, HttpResponse> {
+    let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+    
+    let game = sqlx::query!("SELECT id, host_id FROM games WHERE code = $1", code)
+        .fetch_optional(pool)
+        .await;
+    
+    match game {
+        Ok(Some(game)) if game.host_id == user_id || claims.role == "admin" => Ok(game.id),
+        Ok(Some(_)) => Err(HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Bu işlemi yalnızca oyun sahibi yapabilir"
+        }))),
+        Ok(None) => Err(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Oyun bulunamadı"
+        }))),
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            Err(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Oyun bilgileri alınamadı"
+            })))
+        }
+    }
+}
+
+// Oyun için yeni widget tokenı oluştur; önceki token geçersiz olur
+pub async fn create_widget_token(
+    pool: web::Data<Pool<Postgres>>,
+    game_code: web::Path<String>,
+    claims: Claims,
+) -> impl Responder {
+    let game_id = match find_hosted_game(&pool, &game_code.into_inner(), &claims).await {
+        Ok(game_id) => game_id,
+        Err(response) => return response,
+    };
+    
+    let token = generate_widget_token();
+    if let Err(e) = leaderboard_widget::set_token(&pool, game_id, Some(&token)).await {
+        error!("Veritabanı sorgu hatası: {}", e);
+        return HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Widget tokenı oluşturulamadı"
+        }));
+    }
+    
+    // Token yalnızca bu yanıtta gösterilir
+    HttpResponse::Created().json(serde_json::json!({
+        "token": token,
+        "leaderboard_url": format!("{}{}/leaderboard", PUBLIC_PATH_PREFIX, token),
+        "stream_url": format!("{}{}/leaderboard/stream", PUBLIC_PATH_PREFIX, token)
+    }))
+}
+
+// Oyunun widget tokenını iptal et
+pub async fn revoke_widget_token(
+    pool: web::Data<Pool<Postgres>>,
+    game_code: web::Path<String>,
+    claims: Claims,
+) -> impl Responder {
+    let game_id = match find_hosted_game(&pool, &game_code.into_inner(), &claims).await {
+        Ok(game_id) => game_id,
+        Err(response) => return response,
+    };
+    
+    match leaderboard_widget::set_token(&pool, game_id, None).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({
+            "message": "Widget tokenı iptal edildi"
+        })),
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Widget tokenı iptal edilemedi"
+            }))
+        }
+    }
+}
+
+// Tokenın bağlı olduğu oyunu bul (geçersiz tokenda 404)
+async fn widget_game(pool: &Pool<Postgres>, token: &str) -> Result<i32, HttpResponse> {
+    match leaderboard_widget::find_game(pool, token).await {
+        Ok(Some(game_id)) => Ok(game_id),
+        Ok(None) => Err(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Geçersiz veya iptal edilmiş widget tokenı"
+        }))),
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            Err(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Liderlik tablosu alınamadı"
+            })))
+        }
+    }
+}
+
+// Oyunun güncel liderlik tablosu (herkese açık, token ile)
+pub async fn get_widget_leaderboard(
+    pool: web::Data<Pool<Postgres>>,
+    token: web::Path<String>,
+    query: web::Query<WidgetLeaderboardQuery>,
+) -> impl Responder {
+    let game_id = match widget_game(&pool, &token.into_inner()).await {
+        Ok(game_id) => game_id,
+        Err(response) => return response,
+    };
+    
+    match leaderboard_widget::snapshot(&pool, game_id, query.limit.unwrap_or(DEFAULT_LIMIT)).await {
+        Ok(Some(snapshot)) => HttpResponse::Ok()
+            .insert_header((header::CACHE_CONTROL, "no-store"))
+            .json(snapshot),
+        Ok(None) => HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Oyun bulunamadı"
+        })),
+        Err(e) => {
+            error!("Veritabanı sorgu hatası: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Liderlik tablosu alınamadı"
+            }))
+        }
+    }
+}
+
+// Akışın durumu: son gönderilen tablo ve son gönderim zamanı
+struct StreamState {
+    pool: Pool<Postgres>,
+    game_id: i32,
+    token_hash: String,
+    limit: i64,
+    last: Option<WidgetSnapshot>,
+    last_sent: Instant,
+    finished: bool,
+}
+
+// Bir sonraki gönderilecek SSE mesajını bekle; oyun bittiğinde, silindiğinde veya token iptal edildiğinde akış sona erer
+async fn next_event(mut state: StreamState) -> Option<(Result<web::Bytes, actix_web::Error>, StreamState)> {
+    if state.finished {
+        return None;
+    }
+    
+    loop {
+        if state.last.is_some() {
+            tokio::time::sleep(STREAM_POLL_INTERVAL).await;
+        }
+        
+        let snapshot = match leaderboard_widget::shared_snapshot(&state.pool, state.game_id, &state.token_hash, state.limit).await {
+            Ok(Some(snapshot)) => snapshot,
+            Ok(None) => return None,
+            Err(e) => {
+                error!("Veritabanı sorgu hatası: {}", e);
+                return None;
+            }
+        };
+        
+        if state.last.as_ref() != Some(&snapshot) {
+            let data = serde_json::to_string(&snapshot).unwrap_or_default();
+            state.finished = snapshot.is_finished();
+            state.last = Some(snapshot);
+            state.last_sent = Instant::now();
+            return Some((Ok(web::Bytes::from(format!("event: leaderboard\ndata: {}\n\n", data))), state));
+        }
+        
+        if state.last_sent.elapsed() >= STREAM_KEEPALIVE_INTERVAL {
+            state.last_sent = Instant::now();
+            return Some((Ok(web::Bytes::from_static(b": keepalive\n\n")), state));
+        }
+    }
+}
+
+// Liderlik tablosu akışı (Server-Sent Events); tablo her değiştiğinde "leaderboard" olayı gönderilir
+pub async fn stream_widget_leaderboard(
+    pool: web::Data<Pool<Postgres>>,
+    token: web::Path<String>,
+    query: web::Query<WidgetLeaderboardQuery>,
+) -> impl Responder {
+    let token = token.into_inner();
+    let game_id = match widget_game(&pool, &token).await {
+        Ok(game_id) => game_id,
+        Err(response) => return response,
+    };
+    
+    let state = StreamState {
+        pool: pool.get_ref().clone(),
+        game_id,
+        token_hash: hash_widget_token(&token),
+        limit: query.limit.unwrap_or(DEFAULT_LIMIT),
+        last: None,
+        last_sent: Instant::now(),
+        finished: false,
+    };
+    
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        // Nginx gibi proxy'lerin akışı tamponlamasını engelle
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(stream::unfold(state, next_event))
+}
\ No newline at end of file
@@ -0,0 +1,115 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::OnceLock;
+
+// WebSocket mesaj kodlaması
+// Mesajlar varsayılan olarak JSON metin çerçeveleriyle gönderilir. "sorukayisi.v1+msgpack" alt protokolüyle
+// bağlanan istemcilere kuyruktan giden yayınlar (soru, liderlik tablosu, lobi vb.) MessagePack ikili
+// çerçeveleriyle gönderilir; kalabalık sınıflarda yayınlar bant genişliğinin çoğunu oluşturur. Her iki kodlama
+// da tipli mesajın (WebSocketMessage) serde serileştirmesinden üretilir, bu yüzden alanlar ve "type" etiketi
+// aynıdır. Kodlamalar tembeldir: her biri yalnızca ona ihtiyaç duyan ilk bağlantıda ve yayın başına bir kez
+// yapılır, tüm alıcılar MessagePack kullanıyorsa JSON metni hiç üretilmez.
+// Kural basittir: metin çerçeveleri her zaman JSON, ikili çerçeveler her zaman MessagePack'tir. Bu istemciler
+// isteklerini MessagePack ikili çerçeveleriyle de gönderebilir.
+
+// Bağlantının yayınlar için kullandığı kodlama
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Encoding {
+    #[default]
+    Json,
+    MessagePack,
+}
+
+// İstemciye gönderilecek çerçeve
+#[derive(Debug, PartialEq)]
+pub enum Frame {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+// Her iki kodlamaya da serileştirilebilen mesaj
+trait Outgoing: Send + Sync {
+    fn to_json(&self) -> String;
+    fn to_msgpack(&self) -> Option<Vec<u8>>;
+}
+
+impl<T: Serialize + Send + Sync> Outgoing for T {
+    // Mesaj tiplerinin alanları her zaman JSON'a çevrilebilir
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    fn to_msgpack(&self) -> Option<Vec<u8>> {
+        rmp_serde::to_vec_named(self).ok()
+    }
+}
+
+// Bağlantılar arasında paylaşılan, kodlanmış giden mesaj
+pub struct EncodedMessage {
+    message: Box<dyn Outgoing>,
+    text: OnceLock<String>,
+    msgpack: OnceLock<Option<Vec<u8>>>,
+}
+
+impl EncodedMessage {
+    pub fn new(message: impl Serialize + Send + Sync + 'static) -> Self {
+        EncodedMessage {
+            message: Box::new(message),
+            text: OnceLock::new(),
+            msgpack: OnceLock::new(),
+        }
+    }
+
+    // Mesajı bağlantının kodlamasıyla çerçeveye çevir; kodlanamayan mesaj JSON olarak gönderilir
+    pub fn frame(&self, encoding: Encoding) -> Frame {
+        let msgpack = match encoding {
+            Encoding::Json => None,
+            Encoding::MessagePack => self.msgpack.get_or_init(|| self.message.to_msgpack()).as_ref(),
+        };
+
+        match msgpack {
+            Some(bytes) => Frame::Binary(bytes.clone()),
+            None => Frame::Text(self.text.get_or_init(|| self.message.to_json()).clone()),
+        }
+    }
+}
+
+impl Encoding {
+    // İstemciden gelen ikili çerçeveyi JSON mesaja çevir; JSON bağlantılarında ikili çerçeve kabul edilmez
+    pub fn decode(&self, bytes: &[u8]) -> Option<String> {
+        match self {
+            Encoding::Json => None,
+            Encoding::MessagePack => rmp_serde::from_slice::<Value>(bytes)
+                .ok()
+                .map(|value| value.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    #[serde(tag = "type", rename_all = "snake_case")]
+    enum Message {
+        PlayerCount { game_code: String, count: usize },
+    }
+
+    #[test]
+    fn test_both_encodings_come_from_typed_message() {
+        let message = EncodedMessage::new(Message::PlayerCount { game_code: "AB12CD".to_string(), count: 3 });
+        let expected = serde_json::json!({"type": "player_count", "game_code": "AB12CD", "count": 3});
+
+        let Frame::Binary(bytes) = message.frame(Encoding::MessagePack) else {
+            panic!("MessagePack bağlantısı ikili çerçeve almalı");
+        };
+        assert!(message.text.get().is_none(), "JSON yalnızca bir JSON bağlantısı için üretilmeli");
+        assert_eq!(rmp_serde::from_slice::<Value>(&bytes).unwrap(), expected);
+
+        let Frame::Text(text) = message.frame(Encoding::Json) else {
+            panic!("JSON bağlantısı metin çerçeve almalı");
+        };
+        assert_eq!(serde_json::from_str::<Value>(&text).unwrap(), expected);
+    }
+}
@@ -0,0 +1,182 @@
+use actix_ws::Session;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+use super::ws_codec::{EncodedMessage, Encoding, Frame};
+
+// Bağlantı başına giden mesaj kuyruğu
+// Yayın döngüleri mesajı yalnızca kuyruğa ekler, gönderimi bağlantıya ait yazıcı task yapar;
+// böylece yavaş bir istemci diğer oyunculara yapılan yayını bekletmez.
+
+// Kuyrukta bekleyebilecek en fazla mesaj sayısı
+pub const OUTBOX_CAPACITY: usize = 64;
+
+// Kuyruk doldu, istemci mesajlara yetişemiyor
+#[derive(Debug)]
+pub struct OutboxFull;
+
+struct QueuedMessage {
+    kind: Option<&'static str>,
+    message: Arc<EncodedMessage>,
+}
+
+#[derive(Default)]
+struct OutboxState {
+    messages: VecDeque<QueuedMessage>,
+    closed: bool,
+}
+
+#[derive(Default)]
+pub struct Outbox {
+    state: Mutex<OutboxState>,
+    notify: Notify,
+}
+
+impl Outbox {
+    // Mesajı kuyruğa ekle; birleştirilebilir tipteyse (bkz. WebSocketMessage::coalesce_kind) aynı tipte
+    // gönderilmemiş anlık durum mesajının yerini alır
+    pub fn push(&self, kind: Option<&'static str>, message: &Arc<EncodedMessage>) -> Result<(), OutboxFull> {
+        {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            if state.closed {
+                return Ok(());
+            }
+
+            if let Some(kind) = kind {
+                state.messages.retain(|m| m.kind != Some(kind));
+            }
+
+            if state.messages.len() >= OUTBOX_CAPACITY {
+                return Err(OutboxFull);
+            }
+
+            state.messages.push_back(QueuedMessage {
+                kind,
+                message: message.clone(),
+            });
+        }
+
+        self.notify.notify_one();
+        Ok(())
+    }
+
+    // Kuyruğu kapat, bekleyen mesajlar atılır ve yazıcı task sonlanır
+    pub fn close(&self) {
+        {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.closed = true;
+            state.messages.clear();
+        }
+
+        self.notify.notify_one();
+    }
+
+    // Kuyruktaki mesajları sırayla bağlantının kodlamasıyla istemciye gönder
+    pub async fn run(&self, mut session: Session, encoding: Encoding) {
+        loop {
+            let next = {
+                let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+                if state.closed {
+                    return;
+                }
+                state.messages.pop_front()
+            };
+
+            match next {
+                Some(queued) => {
+                    let sent = match queued.message.frame(encoding) {
+                        Frame::Text(text) => session.text(text).await,
+                        Frame::Binary(bytes) => session.binary(bytes).await,
+                    };
+                    if sent.is_err() {
+                        self.close();
+                        return;
+                    }
+                }
+                None => self.notify.notified().await,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // Aynı tipteki mesajlar sürümleriyle ayırt edilir
+    fn message(message_type: &str, version: u32) -> Arc<EncodedMessage> {
+        Arc::new(EncodedMessage::new(json!({"type": message_type, "version": version})))
+    }
+
+    // Kuyruktaki mesajların tipleri ve içerikleri, gönderilecekleri sırayla
+    fn queued(outbox: &Outbox) -> Vec<(Option<&'static str>, Arc<EncodedMessage>)> {
+        let state = outbox.state.lock().unwrap();
+        state.messages.iter().map(|m| (m.kind, m.message.clone())).collect()
+    }
+
+    #[test]
+    fn test_push_fails_when_full() {
+        let outbox = Outbox::default();
+        let answer = message("player_answered", 1);
+        for _ in 0..OUTBOX_CAPACITY {
+            outbox.push(None, &answer).unwrap();
+        }
+
+        assert!(outbox.push(None, &answer).is_err());
+        assert_eq!(queued(&outbox).len(), OUTBOX_CAPACITY);
+    }
+
+    #[test]
+    fn test_coalesced_message_replaces_pending_one() {
+        let outbox = Outbox::default();
+        let old_lobby = message("lobby_update", 1);
+        let question = message("question_start", 1);
+        let count = message("player_count", 1);
+        let new_lobby = message("lobby_update", 2);
+
+        outbox.push(Some("lobby_update"), &old_lobby).unwrap();
+        outbox.push(None, &question).unwrap();
+        outbox.push(Some("player_count"), &count).unwrap();
+        outbox.push(Some("lobby_update"), &new_lobby).unwrap();
+
+        // Eski lobi durumu atılır, yenisi sıranın sonuna eklenir; diğer mesajların sırası korunur
+        let queued = queued(&outbox);
+        let kinds: Vec<_> = queued.iter().map(|(kind, _)| *kind).collect();
+        assert_eq!(kinds, vec![None, Some("player_count"), Some("lobby_update")]);
+        assert!(Arc::ptr_eq(&queued[0].1, &question));
+        assert!(Arc::ptr_eq(&queued[2].1, &new_lobby));
+    }
+
+    #[test]
+    fn test_coalescing_makes_room_in_full_outbox() {
+        // Dolu kuyrukta bekleyen anlık durum mesajı yenisiyle değiştirilebilir, kuyruk büyümez
+        let outbox = Outbox::default();
+        let answer = message("player_answered", 1);
+        outbox.push(Some("player_count"), &message("player_count", 1)).unwrap();
+        for _ in 1..OUTBOX_CAPACITY {
+            outbox.push(None, &answer).unwrap();
+        }
+
+        let count = message("player_count", 2);
+        outbox.push(Some("player_count"), &count).unwrap();
+        assert_eq!(queued(&outbox).len(), OUTBOX_CAPACITY);
+        assert!(Arc::ptr_eq(&queued(&outbox)[OUTBOX_CAPACITY - 1].1, &count));
+        assert!(outbox.push(None, &answer).is_err());
+    }
+
+    #[test]
+    fn test_closed_outbox_drops_messages() {
+        let outbox = Outbox::default();
+        let answer = message("player_answered", 1);
+        outbox.push(None, &answer).unwrap();
+
+        outbox.close();
+        assert!(queued(&outbox).is_empty());
+
+        // Kapatılmış kuyruğa eklenen mesajlar sessizce atılır, yeniden taşma hatası üretilmez
+        outbox.push(None, &answer).unwrap();
+        assert!(queued(&outbox).is_empty());
+    }
+}
@@ -0,0 +1,245 @@
+use actix_web::http::header::{HeaderMap, ORIGIN, SEC_WEBSOCKET_PROTOCOL};
+use actix_ws::{CloseCode, CloseReason};
+use serde_json::json;
+
+use crate::config::CONFIG;
+use crate::handlers::ws_codec::Encoding;
+
+// WebSocket protokolü: kapatma kodları ve hata kodları
+// İstemciler Türkçe mesajları ayrıştırmak yerine bu kodlara göre davranabilir.
+
+// Yayınları MessagePack ile alan v1 istemcileri (bkz. ws_codec)
+pub const MSGPACK_SUBPROTOCOL: &str = "sorukayisi.v1+msgpack";
+
+// Sunucunun konuştuğu mesaj protokolü sürümleri (Sec-WebSocket-Protocol), tercih sırasıyla; iki kodlamayı da
+// öneren istemciye daha küçük olan MessagePack seçilir
+pub const SUPPORTED_SUBPROTOCOLS: &[&str] = &[MSGPACK_SUBPROTOCOL, "sorukayisi.v1"];
+
+// İstemcinin önerdiği alt protokollerle anlaşma sonucu
+#[derive(Debug, Clone, PartialEq)]
+pub enum Subprotocol {
+    Supported(&'static str),
+    // İstemci alt protokol önermedi; sürüm anlaşmasından önceki istemciler v1 konuşur
+    Legacy,
+    // Önerilenlerin hiçbiri desteklenmiyor; el sıkışma istemcinin ilk önerisiyle tamamlanıp bağlantı
+    // UnsupportedProtocol koduyla kapatılır, böylece istemci nedenini görebilir
+    Unsupported(String),
+}
+
+impl Subprotocol {
+    // Anlaşılan alt protokolün yayın kodlaması
+    pub fn encoding(&self) -> Encoding {
+        match self {
+            Subprotocol::Supported(MSGPACK_SUBPROTOCOL) => Encoding::MessagePack,
+            _ => Encoding::Json,
+        }
+    }
+}
+
+// Sec-WebSocket-Protocol başlıklarındaki önerilerden desteklenen ilk sürümü seç
+pub fn negotiate_subprotocol(headers: &HeaderMap) -> Subprotocol {
+    let offered: Vec<&str> = headers
+        .get_all(SEC_WEBSOCKET_PROTOCOL)
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|protocol| !protocol.is_empty())
+        .collect();
+
+    if offered.is_empty() {
+        return Subprotocol::Legacy;
+    }
+
+    SUPPORTED_SUBPROTOCOLS
+        .iter()
+        .find(|supported| offered.contains(supported))
+        .map(|supported| Subprotocol::Supported(supported))
+        .unwrap_or_else(|| Subprotocol::Unsupported(offered[0].to_string()))
+}
+
+// Bağlantı isteğinin kaynağı (Origin) izin verilen ön yüz adreslerinden biri mi. Origin göndermeyen
+// tarayıcı dışı istemciler (mobil uygulama, testler) kabul edilir; başlık tarayıcıdan gelen siteler arası
+// bağlantıları engellemek içindir.
+pub fn origin_allowed(headers: &HeaderMap) -> bool {
+    origin_in(headers, &CONFIG.ws_allowed_origins)
+}
+
+fn origin_in(headers: &HeaderMap, allowed_origins: &[String]) -> bool {
+    let Some(origin) = headers.get(ORIGIN) else {
+        return true;
+    };
+
+    origin.to_str().is_ok_and(|origin| {
+        let origin = origin.trim().trim_end_matches('/').to_ascii_lowercase();
+        allowed_origins.contains(&origin)
+    })
+}
+
+// Bağlantı kapatma nedenleri (4000-4999 aralığı uygulamaya ayrılmıştır)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WsCloseReason {
+    Kicked,
+    GameEnded,
+    Timeout,
+    AuthFailed,
+    ServerShutdown,
+    SlowClient,
+    UnsupportedProtocol,
+}
+
+impl WsCloseReason {
+    pub fn code(&self) -> u16 {
+        match self {
+            WsCloseReason::Kicked => 4000,
+            WsCloseReason::GameEnded => 4001,
+            WsCloseReason::Timeout => 4002,
+            WsCloseReason::AuthFailed => 4003,
+            WsCloseReason::ServerShutdown => 4004,
+            WsCloseReason::SlowClient => 4005,
+            WsCloseReason::UnsupportedProtocol => 4006,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WsCloseReason::Kicked => "kicked",
+            WsCloseReason::GameEnded => "game_ended",
+            WsCloseReason::Timeout => "timeout",
+            WsCloseReason::AuthFailed => "auth_failed",
+            WsCloseReason::ServerShutdown => "server_shutdown",
+            WsCloseReason::SlowClient => "slow_client",
+            WsCloseReason::UnsupportedProtocol => "unsupported_protocol",
+        }
+    }
+}
+
+impl From<WsCloseReason> for CloseReason {
+    fn from(reason: WsCloseReason) -> Self {
+        CloseReason {
+            code: CloseCode::Other(reason.code()),
+            description: Some(reason.as_str().to_string()),
+        }
+    }
+}
+
+// İstemciye gönderilen hata kodları
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WsErrorCode {
+    InvalidMessage,
+    UnknownMessageType,
+    GameNotFound,
+    AmbiguousAlias,
+    GameNotJoinable,
+    GameAlreadyStarted,
+    GameNotActive,
+    NicknameTaken,
+    InvalidNickname,
+    GuestLimitReached,
+    RecaptchaFailed,
+    ViewerLimitReached,
+    NotOnRoster,
+    NotQualified,
+    NotHost,
+    NotFinalist,
+    SuddenDeathUnavailable,
+    PlayerNotFound,
+    QuestionNotFound,
+    QuestionNotActive,
+    AlreadyAnswered,
+    AnswerNotFound,
+    SessionAlreadyActive,
+    SessionNotFound,
+    InternalError,
+}
+
+impl WsErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WsErrorCode::InvalidMessage => "invalid_message",
+            WsErrorCode::UnknownMessageType => "unknown_message_type",
+            WsErrorCode::GameNotFound => "game_not_found",
+            WsErrorCode::AmbiguousAlias => "ambiguous_alias",
+            WsErrorCode::GameNotJoinable => "game_not_joinable",
+            WsErrorCode::GameAlreadyStarted => "game_already_started",
+            WsErrorCode::GameNotActive => "game_not_active",
+            WsErrorCode::NicknameTaken => "nickname_taken",
+            WsErrorCode::InvalidNickname => "invalid_nickname",
+            WsErrorCode::GuestLimitReached => "guest_limit_reached",
+            WsErrorCode::RecaptchaFailed => "recaptcha_failed",
+            WsErrorCode::ViewerLimitReached => "viewer_limit_reached",
+            WsErrorCode::NotOnRoster => "not_on_roster",
+            WsErrorCode::NotQualified => "not_qualified",
+            WsErrorCode::NotHost => "not_host",
+            WsErrorCode::NotFinalist => "not_finalist",
+            WsErrorCode::SuddenDeathUnavailable => "sudden_death_unavailable",
+            WsErrorCode::PlayerNotFound => "player_not_found",
+            WsErrorCode::QuestionNotFound => "question_not_found",
+            WsErrorCode::QuestionNotActive => "question_not_active",
+            WsErrorCode::AlreadyAnswered => "already_answered",
+            WsErrorCode::AnswerNotFound => "answer_not_found",
+            WsErrorCode::SessionAlreadyActive => "session_already_active",
+            WsErrorCode::SessionNotFound => "session_not_found",
+            WsErrorCode::InternalError => "internal_error",
+        }
+    }
+}
+
+// "error" tipinde istemci mesajı oluştur
+pub fn ws_error(code: WsErrorCode, message: &str) -> String {
+    json!({
+        "type": "error",
+        "code": code.as_str(),
+        "message": message
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::{HeaderName, HeaderValue};
+
+    fn headers(pairs: &[(&HeaderName, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append((*name).clone(), HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn test_negotiate_subprotocol() {
+        // Sunucunun tercih sırası istemcinin sırasından önce gelir
+        let offered = headers(&[(&SEC_WEBSOCKET_PROTOCOL, "sorukayisi.v1, sorukayisi.v1+msgpack")]);
+        assert_eq!(negotiate_subprotocol(&offered), Subprotocol::Supported(MSGPACK_SUBPROTOCOL));
+        assert_eq!(negotiate_subprotocol(&offered).encoding(), Encoding::MessagePack);
+
+        let offered = headers(&[
+            (&SEC_WEBSOCKET_PROTOCOL, "sorukayisi.v2"),
+            (&SEC_WEBSOCKET_PROTOCOL, "sorukayisi.v1"),
+        ]);
+        assert_eq!(negotiate_subprotocol(&offered), Subprotocol::Supported("sorukayisi.v1"));
+        assert_eq!(negotiate_subprotocol(&offered).encoding(), Encoding::Json);
+
+        assert_eq!(negotiate_subprotocol(&HeaderMap::new()), Subprotocol::Legacy);
+        assert_eq!(negotiate_subprotocol(&headers(&[(&SEC_WEBSOCKET_PROTOCOL, " , ")])), Subprotocol::Legacy);
+
+        let offered = headers(&[(&SEC_WEBSOCKET_PROTOCOL, "sorukayisi.v2, sorukayisi.v3")]);
+        assert_eq!(negotiate_subprotocol(&offered), Subprotocol::Unsupported("sorukayisi.v2".to_string()));
+    }
+
+    #[test]
+    fn test_origin_in() {
+        let allowed = vec!["https://sorukayisi.com".to_string(), "http://localhost:3000".to_string()];
+
+        assert!(origin_in(&headers(&[(&ORIGIN, "https://sorukayisi.com")]), &allowed));
+        assert!(origin_in(&headers(&[(&ORIGIN, "HTTPS://SoruKayisi.com/")]), &allowed));
+        assert!(origin_in(&headers(&[(&ORIGIN, "http://localhost:3000")]), &allowed));
+        assert!(!origin_in(&headers(&[(&ORIGIN, "https://kotu-site.com")]), &allowed));
+        assert!(!origin_in(&headers(&[(&ORIGIN, "https://sorukayisi.com.kotu-site.com")]), &allowed));
+        assert!(!origin_in(&headers(&[(&ORIGIN, "null")]), &allowed));
+
+        // Origin göndermeyen tarayıcı dışı istemciler kabul edilir
+        assert!(origin_in(&HeaderMap::new(), &allowed));
+    }
+}
@@ -1,11 +1,11 @@
 use actix_cors::Cors;
 use actix_web::{middleware::Logger, web, App, HttpServer};
 use log::info;
-use sqlx::postgres::PgPoolOptions;
 
 mod config;
 mod db;
 mod errors;
+mod grpc;
 mod handlers;
 mod middleware;
 mod services;
@@ -17,47 +17,212 @@ async fn main() -> std::io::Result<()> {
     config::load_config();
     
     // Veritabanı bağlantısı kur
-    let pool = PgPoolOptions::new()
-        .max_connections(10)
-        .connect(&config::CONFIG.database_url)
-        .await
-        .expect("Veritabanına bağlanılamadı");
+    let pool = db::pool::create_pool().await;
+    if let Some(read_pool) = db::pool::create_read_pool().await {
+        db::repositories::set_read_pool(read_pool);
+    }
     
     // Aktif kullanıcıları temizle (sunucu yeniden başlatıldığında)
     sqlx::query!("DELETE FROM active_connections")
         .execute(&pool)
         .await
         .expect("Aktif bağlantılar temizlenemedi");
+
+    // Hiç admin yoksa ADMIN_EMAIL/ADMIN_PASSWORD ile ilk admini oluştur
+    db::schema::ensure_admin_user(&pool).await;
+    db::schema::backfill_username_skeletons(&pool).await;
+
+    // Demo verisi komutu: `sorukayisi-backend seed-demo` veriyi oluşturur ve sunucuyu başlatmadan çıkar
+    if std::env::args().nth(1).as_deref() == Some("seed-demo") {
+        match services::demo_seed::seed_demo(&pool).await {
+            Ok(Some(summary)) => println!(
+                "Demo verisi oluşturuldu: öğretmen {} (şifre: {}), {} öğrenci, {} soru seti, tamamlanmış oyun {}",
+                services::demo_seed::DEMO_TEACHER_USERNAME,
+                services::demo_seed::DEMO_PASSWORD,
+                summary.student_ids.len(),
+                summary.question_set_ids.len(),
+                summary.game_code
+            ),
+            Ok(None) => println!("Demo verisi zaten mevcut, değişiklik yapılmadı"),
+            Err(e) => {
+                log::error!("Demo verisi oluşturulamadı: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    // Bağlantı havuzunun dolup dolmadığını düzenli olarak kontrol et
+    let monitor_pool = pool.clone();
+    actix_web::rt::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(10));
+        loop {
+            interval.tick().await;
+            db::pool::warn_if_saturated(&monitor_pool);
+        }
+    });
+
+    // Sezon geçişlerini saatlik olarak kontrol et (yeni ay sezonu açılır, biten sezon arşivlenir)
+    let season_pool = pool.clone();
+    actix_web::rt::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+        loop {
+            interval.tick().await;
+            if let Err(e) = services::seasons::ensure_current_season(&season_pool).await {
+                log::error!("Sezon geçişi kontrol edilemedi: {}", e);
+            }
+        }
+    });
+    
+    // Saklama süresi dolan çöp kutusu içeriğini günlük olarak temizle
+    let trash_pool = pool.clone();
+    actix_web::rt::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(24 * 3600));
+        loop {
+            interval.tick().await;
+            if let Err(e) = services::trash::purge_expired(&trash_pool).await {
+                log::error!("Çöp kutusu temizlenemedi: {}", e);
+            }
+        }
+    });
+    
+    // Süresi dolan ve bitmiş oyunlara ait misafir oturumlarını saatlik olarak temizle
+    let guest_session_pool = pool.clone();
+    actix_web::rt::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+        loop {
+            interval.tick().await;
+            if let Err(e) = services::guest_sessions::purge_expired(&guest_session_pool).await {
+                log::error!("Misafir oturumları temizlenemedi: {}", e);
+            }
+        }
+    });
+    
+    // Tamamlanan günlerin platform istatistiklerini topla (gece yarısından sonraki ilk saatlik kontrolde)
+    let stats_pool = pool.clone();
+    actix_web::rt::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+        loop {
+            interval.tick().await;
+            if let Err(e) = services::platform_stats::aggregate_missing_days(&stats_pool).await {
+                log::error!("Platform istatistikleri toplanamadı: {}", e);
+            }
+        }
+    });
+    
+    // Veri ambarı aktarımının zamanı geldiyse tamamlanan oyunları aktar
+    let export_pool = pool.clone();
+    actix_web::rt::spawn(async move {
+        let mut interval = tokio::time::interval(services::warehouse_export::SCHEDULER_INTERVAL);
+        loop {
+            interval.tick().await;
+            services::warehouse_export::run_if_due(&export_pool).await;
+        }
+    });
     
-    info!("Veritabanı bağlantısı başarıyla kuruldu");
+    // Başlangıcı yaklaşan planlı oyunlar için hatırlatma gönder
+    let reminder_pool = pool.clone();
+    actix_web::rt::spawn(async move {
+        let mut interval = tokio::time::interval(services::game_notifications::REMINDER_INTERVAL);
+        loop {
+            interval.tick().await;
+            services::game_notifications::send_due_reminders(&reminder_pool).await;
+        }
+    });
+    
+    // İç servisler için dahili gRPC API'yi başlat (INTERNAL_GRPC_ADDR ayarlandıysa)
+    actix_web::rt::spawn(grpc::serve(pool.clone()));
+    
+    // Prometheus metriklerini dahili adreste sun (METRICS_ADDR ayarlandıysa)
+    actix_web::rt::spawn(handlers::metrics::serve(pool.clone()));
     
     // WebSocket durumunu başlat
     let ws_state = handlers::websocket::AppState::new(pool.clone());
     let ws_data = web::Data::new(ws_state);
     
+    // Yeniden bağlanma süresi dolan oyuncuları ve biten/terk edilen oyunları düzenli olarak temizle
+    let sweep_ws = ws_data.clone();
+    actix_web::rt::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(5));
+        loop {
+            interval.tick().await;
+            sweep_ws.expire_disconnected_players().await;
+            sweep_ws.evict_stale_games().await;
+        }
+    });
+    
+    // Oyun sırasında biriken cevapları düzenli olarak veritabanına yaz
+    let answers_pool = pool.clone();
+    actix_web::rt::spawn(async move {
+        let mut interval = tokio::time::interval(services::game::ANSWER_FLUSH_INTERVAL);
+        loop {
+            interval.tick().await;
+            services::game::flush_all_answers(&answers_pool).await;
+        }
+    });
+    
+    // Sunucu kapanırken açık WebSocket bağlantılarına kapatma nedeni gönder ve bekleyen cevapları yaz
+    let shutdown_ws = ws_data.clone();
+    let shutdown_pool = pool.clone();
+    actix_web::rt::spawn(async move {
+        shutdown_signal().await;
+        shutdown_ws.close_all(handlers::ws_protocol::WsCloseReason::ServerShutdown).await;
+        services::game::flush_all_answers(&shutdown_pool).await;
+    });
+    
     // Sunucuyu başlat
     info!("Sunucu başlatılıyor: {}", &config::CONFIG.server_addr);
     
     HttpServer::new(move || {
-        // CORS yapılandırması
+        // CORS yapılandırması (gömülebilir widget uçları her kaynaktan çağrılabilir)
         let cors = Cors::default()
             .allowed_origin(&config::CONFIG.frontend_url)
-            .allowed_methods(vec!["GET", "POST", "PUT", "DELETE"])
-            .allowed_headers(vec!["Content-Type", "Authorization", "X-Recaptcha-Token"])
+            .allowed_origin_fn(|_, req| req.uri.path().starts_with(handlers::widget::PUBLIC_PATH_PREFIX))
+            .allowed_methods(vec!["GET", "POST", "PUT", "PATCH", "DELETE"])
+            .allowed_headers(vec![
+                "Content-Type",
+                "Authorization",
+                "X-Recaptcha-Token",
+                "If-Unmodified-Since",
+                "If-None-Match",
+                "Player-Token",
+            ])
+            .expose_headers(vec!["ETag"])
             .max_age(3600);
         
         App::new()
             .wrap(Logger::default())
             .wrap(cors)
             .wrap(middleware::JwtAuth)
-            // reCAPTCHA doğrulayıcısını etkinleştir
-            .wrap(middleware::RecaptchaValidator)
+            .wrap(middleware::ApiKeyAuth)
+            .wrap(middleware::RequestMetrics)
             // WebSocket paylaşılan durumunu ekle
             .app_data(ws_data.clone())
             .app_data(web::Data::new(pool.clone()))
+            // İstek gövdesi boyut sınırları
+            .app_data(handlers::json_config(config::CONFIG.json_payload_limit_kb * 1024))
+            .app_data(web::PayloadConfig::new(config::CONFIG.payload_limit_kb * 1024))
             .configure(handlers::configure_routes)
     })
     .bind(&config::CONFIG.server_addr)?
     .run()
     .await
+}
+
+// SIGINT veya SIGTERM sinyalini bekle
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        
+        if let Ok(mut terminate) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = terminate.recv() => {}
+            }
+            return;
+        }
+    }
+    
+    let _ = tokio::signal::ctrl_c().await;
 }
\ No newline at end of file
@@ -0,0 +1,91 @@
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    error::{ErrorInternalServerError, ErrorUnauthorized},
+    web, Error, HttpMessage,
+};
+use futures_util::future::{ready, Ready};
+use log::{error, warn};
+use sqlx::{Pool, Postgres};
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+
+use crate::services::api_keys::{self, API_KEY_HEADER};
+
+// Güvenilir istemci API anahtarı middleware'i
+// X-Api-Key başlığı varsa anahtar doğrulanır ve istemci (ApiClient) isteğe eklenir; geçersiz veya iptal
+// edilmiş anahtar reddedilir. Anahtarın hangi işlemlerde reCAPTCHA yerine geçeceğine RecaptchaValidator karar verir.
+pub struct ApiKeyAuth;
+
+impl<S, B> Transform<S, ServiceRequest> for ApiKeyAuth
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = ApiKeyAuthMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ApiKeyAuthMiddleware { service: Rc::new(service) }))
+    }
+}
+
+pub struct ApiKeyAuthMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for ApiKeyAuthMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let key = match req.headers().get(API_KEY_HEADER) {
+            Some(value) => match value.to_str() {
+                Ok(key) => key.trim().to_string(),
+                Err(_) => {
+                    return Box::pin(async move {
+                        Err(ErrorUnauthorized("Geçersiz API anahtarı formatı"))
+                    });
+                }
+            },
+            // Anahtar yoksa istek normal (tarayıcı) isteği olarak devam eder
+            None => return Box::pin(self.service.call(req)),
+        };
+
+        let service = self.service.clone();
+        Box::pin(async move {
+            let pool = req
+                .app_data::<web::Data<Pool<Postgres>>>()
+                .cloned()
+                .ok_or_else(|| ErrorInternalServerError("Veritabanı bağlantısı bulunamadı"))?;
+
+            match api_keys::authenticate(&pool, &key).await {
+                Ok(Some(client)) => {
+                    req.extensions_mut().insert(client);
+                }
+                Ok(None) => {
+                    warn!("Geçersiz veya iptal edilmiş API anahtarı: {}", req.path());
+                    return Err(ErrorUnauthorized("Geçersiz veya iptal edilmiş API anahtarı"));
+                }
+                Err(e) => {
+                    error!("API anahtarı kontrol edilemedi: {}", e);
+                    return Err(ErrorInternalServerError("Yetkilendirme kontrol edilemedi"));
+                }
+            }
+
+            service.call(req).await
+        })
+    }
+}
\ No newline at end of file
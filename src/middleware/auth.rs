@@ -1,17 +1,23 @@
 use actix_web::{
-    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
-    error::ErrorUnauthorized,
+    dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    error::{ErrorForbidden, ErrorInternalServerError, ErrorUnauthorized},
     http::header,
-    Error, HttpMessage,
+    web, Error, FromRequest, HttpMessage, HttpRequest,
 };
 use futures_util::future::{ready, Ready};
-use log::{debug, error};
+use log::{debug, error, warn};
+use sqlx::{Pool, Postgres};
 use std::future::{Future};
 use std::pin::Pin;
+use std::rc::Rc;
 
+use crate::db::models::Claims;
+use crate::services::{blocklist, token_version};
 use crate::utils::security::decode_jwt;
 
 // JWT Kimlik Doğrulama Middleware
+// Geçerli bir token varsa Claims isteğe eklenir, geçersiz token reddedilir.
+// Tokenın zorunlu olup olmadığına rota bazında karar verilir (bkz. RequireAuth ve Claims çıkarıcısı).
 pub struct JwtAuth;
 
 impl<S, B> Transform<S, ServiceRequest> for JwtAuth
@@ -27,12 +33,12 @@ where
     type Future = Ready<Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
-        ready(Ok(JwtAuthMiddleware { service }))
+        ready(Ok(JwtAuthMiddleware { service: Rc::new(service) }))
     }
 }
 
 pub struct JwtAuthMiddleware<S> {
-    service: S,
+    service: Rc<S>,
 }
 
 impl<S, B> Service<ServiceRequest> for JwtAuthMiddleware<S>
@@ -72,24 +78,8 @@ where
                 header_str[7..].to_string() // "Bearer " önekini kaldır
             }
             None => {
-                // Bazı yollar için token gerektirmeyen (public routes) yolları kontrol et
-                let path = req.path();
-                
-                if path.starts_with("/api/auth/login") 
-                   || path.starts_with("/api/auth/register")
-                   || path.starts_with("/api/auth/verify")
-                   || path.starts_with("/api/health")
-                   || path.starts_with("/ws")
-                   || path.starts_with("/health")
-                   || path == "/api/game/join" // Misafir oyuncular için
-                {
-                    // Bu yollar için token gerekmiyor, normal akışa devam et
-                    return Box::pin(self.service.call(req));
-                }
-                
-                return Box::pin(async move {
-                    Err(ErrorUnauthorized("Yetkilendirme başlığı eksik"))
-                });
+                // Token yoksa isteğe kimliksiz devam edilir; token gerektiren rotalar RequireAuth ile korunur
+                return Box::pin(self.service.call(req));
             }
         };
         
@@ -108,14 +98,105 @@ where
         // Bu kısımda rol bazlı erişim kontrolleri yapılabilir
         debug!("JWT doğrulandı: user_id={}, role={}", claims.sub, claims.role);
         
-        // Claims'i request uzantısına ekle
-        req.extensions_mut().insert(claims);
-        
-        // Servisi çağır
-        let fut = self.service.call(req);
+        let service = self.service.clone();
         Box::pin(async move {
-            let res = fut.await?;
-            Ok(res)
+            // Token sürümü kullanıcının güncel sürümüyle eşleşmeli (şifre/rol değişikliği, tüm oturumlardan çıkış)
+            let pool = req
+                .app_data::<web::Data<Pool<Postgres>>>()
+                .cloned()
+                .ok_or_else(|| ErrorInternalServerError("Veritabanı bağlantısı bulunamadı"))?;
+            let user_id = claims.sub.parse::<i32>().unwrap_or_default();
+            
+            match token_version::current(&pool, user_id).await {
+                Ok(Some(version)) if version == claims.ver => {}
+                Ok(_) => return Err(ErrorUnauthorized("Oturum sonlandırılmış, lütfen tekrar giriş yapın")),
+                Err(e) => {
+                    error!("Token sürümü kontrol edilemedi: {}", e);
+                    return Err(ErrorInternalServerError("Yetkilendirme kontrol edilemedi"));
+                }
+            }
+            
+            // Engellenen kullanıcının tokenı hiçbir rotada (WebSocket dahil) kullanılamaz; liste okunamazsa
+            // istek engellenmez
+            match blocklist::find_block(&pool, None, None, Some(user_id)).await {
+                Ok(Some(block)) => {
+                    warn!("Engellenen kullanıcının isteği reddedildi: user_id={} (kayıt: {})", user_id, block.entry_id);
+                    return Err(ErrorForbidden(block.message()));
+                }
+                Ok(None) => {}
+                Err(e) => error!("Engel listesi kontrol edilemedi: {}", e),
+            }
+            
+            // Claims'i request uzantısına ekle
+            req.extensions_mut().insert(claims);
+            
+            // Servisi çağır
+            service.call(req).await
         })
     }
+}
+
+// Handler'lar giriş yapmış kullanıcıyı Claims parametresiyle alır. JwtAuth Claims eklemediyse (token yoksa)
+// istek 401 ile reddedilir; böylece RequireAuth unutulan bir rota da tokensız isteğe açılmaz. Misafirlere de
+// açık rotalar Option<Claims> kullanır.
+impl FromRequest for Claims {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(
+            req.extensions()
+                .get::<Claims>()
+                .cloned()
+                .ok_or_else(|| ErrorUnauthorized("Yetkilendirme başlığı eksik")),
+        )
+    }
+}
+
+// Kimlik doğrulaması zorunlu rotalar için middleware (configure_routes içinde kapsam veya rota bazında eklenir)
+pub struct RequireAuth;
+
+impl<S, B> Transform<S, ServiceRequest> for RequireAuth
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequireAuthMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequireAuthMiddleware { service }))
+    }
+}
+
+pub struct RequireAuthMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RequireAuthMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        // JwtAuth token doğrulandıysa Claims eklemiştir
+        if req.extensions().get::<Claims>().is_none() {
+            return Box::pin(async move {
+                Err(ErrorUnauthorized("Yetkilendirme başlığı eksik"))
+            });
+        }
+        
+        Box::pin(self.service.call(req))
+    }
 }
\ No newline at end of file
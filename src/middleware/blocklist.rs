@@ -0,0 +1,91 @@
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    error::ErrorForbidden,
+    web, Error, HttpMessage,
+};
+use futures_util::future::{ready, Ready};
+use log::{error, warn};
+use sqlx::{Pool, Postgres};
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+
+use crate::services::blocklist;
+use crate::utils::security::client_ip;
+
+// Engel listesi middleware'i
+// Giriş, kayıt ve oyuna katılma rotalarında istemcinin IP adresi ve JSON gövdesindeki "email" alanı engel
+// listesine karşı kontrol edilir (bkz. services::blocklist). Engellenen kullanıcıların tokenları ise tüm
+// rotalarda JwtAuth tarafından reddedilir. Gövde okunduktan sonra handler için isteğe geri konur. Liste
+// okunamazsa istek engellenmez; engel listesi girişleri kilitlememeli.
+pub struct BlocklistGuard;
+
+impl<S, B> Transform<S, ServiceRequest> for BlocklistGuard
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = BlocklistGuardMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(BlocklistGuardMiddleware { service: Rc::new(service) }))
+    }
+}
+
+pub struct BlocklistGuardMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for BlocklistGuardMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        Box::pin(async move {
+            let pool = match req.app_data::<web::Data<Pool<Postgres>>>().cloned() {
+                Some(pool) => pool,
+                None => return service.call(req).await,
+            };
+
+            let ip = client_ip(req.request());
+
+            // Gövdedeki e-posta adresi (kayıt, giriş, şifre sıfırlama)
+            let mut email = None;
+            if req.content_type() == "application/json" {
+                let body = req.extract::<web::Bytes>().await?;
+                email = serde_json::from_slice::<serde_json::Value>(&body)
+                    .ok()
+                    .and_then(|value| value.get("email")?.as_str().map(str::to_string));
+                req.set_payload(body.into());
+            }
+
+            match blocklist::find_block(&pool, ip.as_deref(), email.as_deref(), None).await {
+                Ok(Some(block)) => {
+                    warn!(
+                        "Engel listesindeki istemcinin isteği reddedildi: {} (kayıt: {}, tür: {})",
+                        req.path(), block.entry_id, block.kind
+                    );
+                    return Err(ErrorForbidden(block.message()));
+                }
+                Ok(None) => {}
+                Err(e) => error!("Engel listesi kontrol edilemedi: {}", e),
+            }
+
+            service.call(req).await
+        })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,82 @@
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    Error,
+};
+use futures_util::future::{ready, Ready};
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::time::Instant;
+
+use crate::services::metrics::{self, RequestContext};
+
+// Rota başına istek metrikleri middleware'i
+// İsteğin süresi ve durum kodu eşleşen yol şablonuyla (ör. /api/game/{code}) kaydedilir; şablon kullanıldığı
+// için oyun kodları gibi değişken parçalar ayrı seriler oluşturmaz. İstek süresince çalışan sorgular rota ve
+// oyun koduyla ilişkilendirilir (bkz. services::metrics).
+pub struct RequestMetrics;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestMetrics
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestMetricsMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestMetricsMiddleware { service: Rc::new(service) }))
+    }
+}
+
+pub struct RequestMetricsMiddleware<S> {
+    service: Rc<S>,
+}
+
+// Şablondaki {code} parçasına karşılık gelen oyun kodu
+fn game_code(pattern: &str, path: &str) -> Option<String> {
+    pattern
+        .split('/')
+        .zip(path.split('/'))
+        .find(|(segment, _)| *segment == "{code}")
+        .map(|(_, code)| code.to_uppercase())
+}
+
+impl<S, B> Service<ServiceRequest> for RequestMetricsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let started = Instant::now();
+        let method = req.method().to_string();
+        // Eşleşmeyen yollar tek seride toplanır
+        let route = req.match_pattern().unwrap_or_else(|| "unmatched".to_string());
+        let context = RequestContext {
+            game_code: game_code(&route, req.path()),
+            route: route.clone(),
+        };
+
+        let service = self.service.clone();
+        Box::pin(async move {
+            let result = metrics::with_context(context, service.call(req)).await;
+            let status = match &result {
+                Ok(response) => response.status().as_u16(),
+                Err(e) => e.as_response_error().status_code().as_u16(),
+            };
+            metrics::record_request(&method, &route, status, started.elapsed());
+            result
+        })
+    }
+}
\ No newline at end of file
@@ -1,6 +1,12 @@
+pub mod api_key;
 pub mod auth;
+pub mod blocklist;
+pub mod metrics;
 pub mod recaptcha;
 
 // Ara yazılımlar
-pub use auth::JwtAuth;
+pub use api_key::ApiKeyAuth;
+pub use auth::{JwtAuth, RequireAuth};
+pub use blocklist::BlocklistGuard;
+pub use metrics::RequestMetrics;
 pub use recaptcha::RecaptchaValidator;
\ No newline at end of file
@@ -1,19 +1,23 @@
 use actix_web::{
     dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
-    error::ErrorUnauthorized,
-    Error,
+    error::{ErrorForbidden, ErrorUnauthorized},
+    web, Error, HttpMessage,
 };
 use futures_util::{
     future::{ready, Ready},
     FutureExt,
 };
-use log::{debug, error, warn};
+use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 
 use crate::config::CONFIG;
+use crate::db::models::Claims;
+use crate::services::api_keys::ApiClient;
+use crate::services::audit;
 
 // reCAPTCHA v3 doğrulama yanıtı
 #[derive(Debug, Serialize, Deserialize)]
@@ -26,7 +30,27 @@ struct RecaptchaResponse {
 }
 
 // reCAPTCHA middleware yapısı
-pub struct RecaptchaValidator;
+// configure_routes içinde korunacak rotalara işlem adıyla eklenir. İşlem RECAPTCHA_ACTIONS listesinde
+// yoksa doğrulama yapılmaz; varsa tokenın bu işlem için üretildiği ve eşiği geçtiği kontrol edilir.
+// Geçerli bir API anahtarıyla (bkz. ApiKeyAuth) gelen istemciler, anahtar bu işlem için yetkiliyse
+// reCAPTCHA yerine anahtarla kabul edilir ve kullanım denetim kaydına yazılır.
+#[derive(Clone, Copy)]
+pub struct RecaptchaValidator {
+    action: &'static str,
+    guests_only: bool,
+}
+
+impl RecaptchaValidator {
+    // Frontend'in grecaptcha.execute çağrısında kullandığı işlem adı
+    pub fn action(action: &'static str) -> Self {
+        RecaptchaValidator { action, guests_only: false }
+    }
+
+    // Geçerli bir JWT ile gelen kullanıcılar için doğrulamayı atla (misafir oyuncu girişi gibi)
+    pub fn guests_only(self) -> Self {
+        RecaptchaValidator { guests_only: true, ..self }
+    }
+}
 
 impl<S, B> Transform<S, ServiceRequest> for RecaptchaValidator
 where
@@ -43,12 +67,16 @@ where
     fn new_transform(&self, service: S) -> Self::Future {
         ready(Ok(RecaptchaMiddleware {
             service: Arc::new(service),
+            action: self.action,
+            guests_only: self.guests_only,
         }))
     }
 }
 
 pub struct RecaptchaMiddleware<S> {
     service: Arc<S>,
+    action: &'static str,
+    guests_only: bool,
 }
 
 impl<S, B> Service<ServiceRequest> for RecaptchaMiddleware<S>
@@ -64,17 +92,50 @@ where
     forward_ready!(service);
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        // Sadece belirli rotaları doğrula (kayıt, giriş gibi)
         let path = req.path().to_string();
+        let action = self.action;
         
-        if !path.starts_with("/api/auth/register") && !path.starts_with("/api/auth/login") {
-            // Diğer rotaları atla
+        // Güvenilir istemci anahtarı reCAPTCHA yerine geçer, ancak yalnızca yetkili olduğu işlemlerde
+        let api_client = req.extensions().get::<ApiClient>().cloned();
+        if let Some(client) = api_client {
+            if !client.allows(action) {
+                warn!("API anahtarı bu işlem için yetkili değil: {} (anahtar: {}, işlem: {})", path, client.id, action);
+                return Box::pin(async move {
+                    Err(ErrorForbidden("API anahtarının bu işlem için yetkisi yok"))
+                });
+            }
+            
+            let pool = req.app_data::<web::Data<Pool<Postgres>>>().cloned();
             let service = Arc::clone(&self.service);
             return Box::pin(async move {
+                info!("reCAPTCHA API anahtarıyla atlandı: {} (anahtar: {}, işlem: {})", path, client.id, action);
+                if let Some(pool) = pool {
+                    audit::record(&pool, None, audit::API_KEY_USED, serde_json::json!({
+                        "api_key_id": client.id,
+                        "api_key_name": client.name,
+                        "action": action,
+                        "path": path
+                    })).await;
+                }
                 service.call(req).await
             });
         }
         
+        // JwtAuth tokenı doğruladıysa istek giriş yapmış bir kullanıcıdan gelir
+        let is_guest = req.extensions().get::<Claims>().is_none();
+        
+        // Geliştirme/test ortamında, işlem doğrulama listesinde değilse veya yalnızca misafirler için
+        // istenen doğrulamada kullanıcı giriş yapmışsa atla
+        let min_score = match required_score(action) {
+            Some(score) if is_guest || !self.guests_only => score,
+            _ => {
+                let service = Arc::clone(&self.service);
+                return Box::pin(async move {
+                    service.call(req).await
+                });
+            }
+        };
+        
         // Token'ı header'dan al
         let recaptcha_token = match req.headers().get("X-Recaptcha-Token") {
             Some(token) => match token.to_str() {
@@ -93,57 +154,83 @@ where
             }
         };
         
-        let secret_key = CONFIG.recaptcha_secret_key.clone();
         let service = Arc::clone(&self.service);
         
         Box::pin(async move {
-            // Google API'si ile doğrula
-            let client = reqwest::Client::new();
-            let response = match client
-                .post("https://www.google.com/recaptcha/api/siteverify")
-                .form(&[
-                    ("secret", &secret_key),
-                    ("response", &recaptcha_token),
-                ])
-                .send()
-                .await {
-                    Ok(resp) => resp,
-                    Err(e) => {
-                        error!("reCAPTCHA tokenı doğrulanamadı: {}", e);
-                        return Err(ErrorUnauthorized("reCAPTCHA tokenı doğrulanamadı"));
-                    }
-                };
-            
-            // JSON yanıtını ayrıştır
-            let recaptcha_result: Result<RecaptchaResponse, _> = response.json().await;
-            
-            match recaptcha_result {
-                Ok(result) => {
-                    if result.success {
-                        if let Some(score) = result.score {
-                            if score > 0.5 {
-                                debug!("reCAPTCHA doğrulaması başarılı, score: {}", score);
-                                service.call(req).await
-                            } else {
-                                warn!("reCAPTCHA score çok düşük: {}", score);
-                                Err(ErrorUnauthorized("reCAPTCHA score çok düşük"))
-                            }
-                        } else {
-                            warn!("reCAPTCHA yanıtında score yok");
-                            Err(ErrorUnauthorized("Geçersiz reCAPTCHA yanıtı"))
-                        }
+            match verify_token(&recaptcha_token, action, min_score).await {
+                Ok(()) => service.call(req).await,
+                Err(message) => Err(ErrorUnauthorized(message)),
+            }
+        })
+    }
+}
+
+// İşlem için gereken en düşük reCAPTCHA skoru; doğrulama kapalıysa veya işlem RECAPTCHA_ACTIONS listesinde
+// yoksa None
+pub fn required_score(action: &str) -> Option<f64> {
+    if CONFIG.recaptcha_bypass {
+        return None;
+    }
+    CONFIG
+        .recaptcha_actions
+        .get(action)
+        .map(|score| score.unwrap_or(CONFIG.recaptcha_min_score))
+}
+
+// Tokenı Google API'si ile doğrula: token bu işlem için üretilmiş ve skoru eşiği geçmiş olmalı.
+// Middleware dışında, tokenın mesajla geldiği WebSocket katılımında da kullanılır. Hata durumunda
+// istemciye gösterilecek mesaj döner.
+pub async fn verify_token(token: &str, action: &str, min_score: f64) -> Result<(), String> {
+    let client = reqwest::Client::new();
+    let response = match client
+        .post(&CONFIG.recaptcha_verify_url)
+        .form(&[
+            ("secret", CONFIG.recaptcha_secret_key.as_str()),
+            ("response", token),
+        ])
+        .send()
+        .await {
+            Ok(resp) => resp,
+            Err(e) => {
+                error!("reCAPTCHA tokenı doğrulanamadı: {}", e);
+                return Err("reCAPTCHA tokenı doğrulanamadı".to_string());
+            }
+        };
+    
+    // JSON yanıtını ayrıştır
+    let recaptcha_result: Result<RecaptchaResponse, _> = response.json().await;
+    
+    match recaptcha_result {
+        Ok(result) => {
+            if result.success {
+                // Başka bir işlem için alınmış token bu işlemde kullanılamaz
+                if result.action.as_deref() != Some(action) {
+                    warn!("reCAPTCHA işlemi eşleşmiyor: beklenen {}, gelen {:?}", action, result.action);
+                    return Err("reCAPTCHA işlemi eşleşmiyor".to_string());
+                }
+                
+                if let Some(score) = result.score {
+                    if score >= min_score {
+                        debug!("reCAPTCHA doğrulaması başarılı, işlem: {}, score: {}", action, score);
+                        Ok(())
                     } else {
-                        let error_codes = result.error_codes.unwrap_or_default().join(", ");
-                        warn!("reCAPTCHA doğrulaması başarısız: {}", error_codes);
-                        Err(ErrorUnauthorized(format!("reCAPTCHA doğrulaması başarısız: {}", error_codes)))
+                        warn!("reCAPTCHA score çok düşük: {} (işlem: {}, eşik: {})", score, action, min_score);
+                        Err("reCAPTCHA score çok düşük".to_string())
                     }
-                },
-                Err(e) => {
-                    error!("reCAPTCHA yanıtı ayrıştırılamadı: {}", e);
-                    Err(ErrorUnauthorized("Geçersiz reCAPTCHA yanıtı"))
+                } else {
+                    warn!("reCAPTCHA yanıtında score yok");
+                    Err("Geçersiz reCAPTCHA yanıtı".to_string())
                 }
+            } else {
+                let error_codes = result.error_codes.unwrap_or_default().join(", ");
+                warn!("reCAPTCHA doğrulaması başarısız: {}", error_codes);
+                Err(format!("reCAPTCHA doğrulaması başarısız: {}", error_codes))
             }
-        })
+        },
+        Err(e) => {
+            error!("reCAPTCHA yanıtı ayrıştırılamadı: {}", e);
+            Err("Geçersiz reCAPTCHA yanıtı".to_string())
+        }
     }
 }
 
@@ -151,6 +238,8 @@ impl<S> Clone for RecaptchaMiddleware<S> {
     fn clone(&self) -> Self {
         Self {
             service: Arc::clone(&self.service),
+            action: self.action,
+            guests_only: self.guests_only,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+use std::time::Duration;
+
+use crate::db::models::QuestionMessage;
+
+// Erişilebilirlik düzenlemeleri
+// Öğretmen, sınıf listesindeki öğrencilere ek süre çarpanı ve ekran okuyucu desteği tanımlar. Bu öğrenciler
+// öğretmenin yönettiği bir oyuna katıldığında soru süresi kendileri için uzatılır ve cevap süresi puanlamada
+// çarpana bölünür; böylece ek süre kullanan öğrenci hız puanından kaybetmez.
+
+pub const MIN_TIME_MULTIPLIER: f64 = 1.0;
+pub const MAX_TIME_MULTIPLIER: f64 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Accommodations {
+    pub time_multiplier: f64,
+    pub screen_reader: bool,
+}
+
+impl Default for Accommodations {
+    fn default() -> Self {
+        Accommodations { time_multiplier: MIN_TIME_MULTIPLIER, screen_reader: false }
+    }
+}
+
+impl Accommodations {
+    pub fn is_default(&self) -> bool {
+        *self == Accommodations::default()
+    }
+
+    // Öğrenciye tanınan soru süresi
+    pub fn extended(&self, duration: Duration) -> Duration {
+        duration.mul_f64(self.time_multiplier)
+    }
+
+    // Puanlamada kullanılacak cevap süresi
+    pub fn scored_response_time(&self, response_time_ms: i32) -> i32 {
+        (response_time_ms as f64 / self.time_multiplier).round() as i32
+    }
+
+    // Oyuncuya gönderilen soru mesajındaki süreyi ve bitiş zamanını öğrenciye göre uzat
+    pub fn apply_to_question(&self, message: &mut QuestionMessage) {
+        if self.is_default() {
+            return;
+        }
+
+        let extended = ((message.deadline - message.started_at) as f64 * self.time_multiplier).round() as i64;
+        message.deadline = message.started_at + extended;
+        if let Some(time_limit) = message.time_limit {
+            message.time_limit = Some((time_limit as f64 * self.time_multiplier).ceil() as i32);
+        }
+        message.accommodations = Some(*self);
+    }
+}
+
+// Geçerli bir ek süre çarpanı mı
+pub fn is_valid_multiplier(multiplier: f64) -> bool {
+    (MIN_TIME_MULTIPLIER..=MAX_TIME_MULTIPLIER).contains(&multiplier)
+}
+
+// Oyunun host'unun sınıflarında öğrenciye tanımlanmış düzenlemeler (birden fazla sınıfta ise en geniş olanı)
+pub async fn for_player(pool: &Pool<Postgres>, game_id: i32, user_id: i32) -> Result<Accommodations, sqlx::Error> {
+    let record = sqlx::query!(
+        r#"
+        SELECT MAX(cm.time_multiplier) as time_multiplier, BOOL_OR(cm.screen_reader) as screen_reader
+        FROM class_members cm
+        JOIN classes c ON cm.class_id = c.id
+        JOIN games g ON g.host_id = c.teacher_id
+        WHERE g.id = $1 AND cm.user_id = $2
+        "#,
+        game_id,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(Accommodations {
+        time_multiplier: record.time_multiplier.unwrap_or(MIN_TIME_MULTIPLIER),
+        screen_reader: record.screen_reader.unwrap_or(false),
+    })
+}
\ No newline at end of file
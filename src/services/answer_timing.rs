@@ -0,0 +1,129 @@
+use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
+
+use crate::db::repositories::read_pool;
+use crate::db::models::ResponseTimeBucket;
+use crate::services::game_events;
+
+// Cevap süresi analizi
+// Her soru için cevap sürelerinin dağılımı (süre sınırı eşit aralıklara bölünerek) ve ilk cevabın soru
+// gösterildikten ne kadar sonra geldiği hesaplanır. Sürelerin tek bir tepe etrafında toplandığı ama ilk
+// cevabın geç geldiği soru büyük olasılıkla uzun okunuyordur; süreleri dağınık ve doğruluğu düşük soru ise
+// kafa karıştırıcı olabilir. Süre dolduğu için boş geçilen cevaplar (X) dağılıma katılmaz, ayrıca sayılır.
+
+// Dağılımın aralık sayısı
+const BUCKET_COUNT: i64 = 10;
+
+#[derive(Debug, Clone, Default)]
+pub struct QuestionTiming {
+    pub histogram: Vec<ResponseTimeBucket>,
+    pub median_response_time_ms: Option<i64>,
+    // Soru gösterildikten ilk cevabın sunucuya ulaşmasına kadar geçen süre
+    pub time_to_first_answer_ms: Option<i64>,
+    pub timed_out: i64,
+}
+
+// Süreleri süre sınırı boyunca eşit aralıklara dağıt; sınırı aşan (ek süreli) cevaplar son aralığa düşer
+fn histogram(times: &[i32], time_limit_ms: i64) -> Vec<ResponseTimeBucket> {
+    let width = (time_limit_ms.max(BUCKET_COUNT) + BUCKET_COUNT - 1) / BUCKET_COUNT;
+    let mut buckets: Vec<ResponseTimeBucket> = (0..BUCKET_COUNT)
+        .map(|i| ResponseTimeBucket {
+            from_ms: i * width,
+            to_ms: (i + 1) * width,
+            count: 0,
+        })
+        .collect();
+
+    for &time in times {
+        let index = (time.max(0) as i64 / width).min(BUCKET_COUNT - 1) as usize;
+        buckets[index].count += 1;
+    }
+    buckets
+}
+
+fn median(times: &mut [i32]) -> Option<i64> {
+    if times.is_empty() {
+        return None;
+    }
+    times.sort_unstable();
+    let mid = times.len() / 2;
+    Some(if times.len().is_multiple_of(2) {
+        (times[mid - 1] as i64 + times[mid] as i64) / 2
+    } else {
+        times[mid] as i64
+    })
+}
+
+// Oyundaki soruların süre analizleri (question_id -> analiz); tüm cevapları taradığı için replikada çalışır
+pub async fn game_question_timings(
+    pool: &Pool<Postgres>,
+    game_id: i32,
+) -> Result<HashMap<i32, QuestionTiming>, sqlx::Error> {
+    let pool = read_pool(pool);
+    let answers = sqlx::query!(
+        r#"
+        SELECT pa.question_id, pa.answer, pa.response_time_ms, q.time_limit
+        FROM player_answers pa
+        JOIN players p ON pa.player_id = p.id
+        JOIN questions q ON pa.question_id = q.id
+        WHERE p.game_id = $1
+        "#,
+        game_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    // İlk cevap, sorunun başlama olayına göre sunucu saatiyle ölçülür
+    let first_answers = sqlx::query!(
+        r#"
+        SELECT s.question_id as "question_id!",
+               (EXTRACT(EPOCH FROM MIN(pa.answered_at) - s.started_at) * 1000)::float8 as "first_answer_ms!"
+        FROM (
+            SELECT (payload->>'question_id')::int as question_id, MIN(created_at) as started_at
+            FROM game_events
+            WHERE game_id = $1 AND event_type = $2
+            GROUP BY 1
+        ) s
+        JOIN player_answers pa ON pa.question_id = s.question_id
+        JOIN players p ON pa.player_id = p.id AND p.game_id = $1
+        WHERE pa.answer <> 'X'
+        GROUP BY s.question_id, s.started_at
+        "#,
+        game_id,
+        game_events::EVENT_QUESTION_START
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut times: HashMap<i32, (Vec<i32>, i64, i64)> = HashMap::new();
+    for answer in answers {
+        let time_limit_ms = answer.time_limit.unwrap_or(30).max(1) as i64 * 1000;
+        let entry = times.entry(answer.question_id).or_insert_with(|| (Vec::new(), 0, time_limit_ms));
+        if answer.answer.as_deref() == Some("X") {
+            entry.1 += 1;
+        } else if let Some(time) = answer.response_time_ms {
+            entry.0.push(time);
+        }
+    }
+
+    let mut timings: HashMap<i32, QuestionTiming> = times
+        .into_iter()
+        .map(|(question_id, (mut times, timed_out, time_limit_ms))| {
+            let timing = QuestionTiming {
+                histogram: histogram(&times, time_limit_ms),
+                median_response_time_ms: median(&mut times),
+                time_to_first_answer_ms: None,
+                timed_out,
+            };
+            (question_id, timing)
+        })
+        .collect();
+
+    for first in first_answers {
+        if let Some(timing) = timings.get_mut(&first.question_id) {
+            timing.time_to_first_answer_ms = Some(first.first_answer_ms.max(0.0).round() as i64);
+        }
+    }
+
+    Ok(timings)
+}
\ No newline at end of file
@@ -0,0 +1,218 @@
+use chrono::{DateTime, Utc};
+use log::error;
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use std::time::Duration;
+
+use crate::db::repositories::read_pool;
+
+// Hile tespiti
+// Cevap süreleri ve istemciden gelen odak değişikliği bildirimleri incelenir, şüpheli durumlar
+// işaretlenerek oyunu yöneten öğretmenin istatistik ekranında gösterilir.
+
+// İşaret türleri
+pub const FLAG_IMPOSSIBLE_RESPONSE_TIME: &str = "impossible_response_time";
+pub const FLAG_FOCUS_CHANGE: &str = "focus_change";
+pub const FLAG_IDENTICAL_TIMING: &str = "identical_timing";
+
+// Soruyu okuyup cevaplamak için insanın ihtiyaç duyduğu en kısa süre
+const MIN_HUMAN_RESPONSE_MS: i32 = 250;
+// Süre sınırı kontrolünde ağ gecikmesi için tanınan pay
+const TIME_LIMIT_GRACE_MS: i32 = 2000;
+// İki oyuncunun cevap sürelerinin aynı sayıldığı en büyük fark
+const IDENTICAL_TIMING_TOLERANCE_MS: i32 = 50;
+// Aynı zamanlamanın şüpheli sayılması için gereken en az ortak soru sayısı
+const IDENTICAL_TIMING_MIN_MATCHES: i64 = 3;
+
+// Kaydedilmiş hile işareti
+#[derive(Debug, Serialize, Clone)]
+pub struct CheatFlag {
+    pub player_id: i32,
+    pub nickname: String,
+    pub question_id: Option<i32>,
+    pub kind: String,
+    pub details: serde_json::Value,
+    pub occurrences: i32,
+    pub last_seen_at: DateTime<Utc>,
+}
+
+// Birbirine çok yakın sürelerle aynı cevapları veren oyuncu çifti
+#[derive(Debug, Serialize, Clone)]
+pub struct IdenticalTimingPair {
+    pub kind: &'static str,
+    pub player_ids: [i32; 2],
+    pub nicknames: [String; 2],
+    pub matching_questions: i64,
+}
+
+// Oyunun hile raporu
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct CheatReport {
+    pub flags: Vec<CheatFlag>,
+    pub identical_timing: Vec<IdenticalTimingPair>,
+}
+
+// Bildirilen cevap süresi insan için mümkün mü; answer_window oyuncuya tanınan süredir (ek süre ve host'un
+// uzattığı süre dahil)
+pub fn is_impossible_response_time(response_time_ms: i32, answer_window: Duration) -> bool {
+    let window_ms = i32::try_from(answer_window.as_millis()).unwrap_or(i32::MAX);
+    response_time_ms < MIN_HUMAN_RESPONSE_MS || response_time_ms > window_ms.saturating_add(TIME_LIMIT_GRACE_MS)
+}
+
+// İşaret kaydet; aynı oyuncu, tür ve soru için tekrarlarda sayaç artırılır
+pub async fn record_flag(
+    pool: &Pool<Postgres>,
+    game_id: i32,
+    player_id: i32,
+    question_id: Option<i32>,
+    kind: &str,
+    details: serde_json::Value,
+) {
+    let now = Utc::now();
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO cheat_flags (game_id, player_id, question_id, kind, details, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $6)
+        ON CONFLICT (player_id, kind, (COALESCE(question_id, 0)))
+        DO UPDATE SET occurrences = cheat_flags.occurrences + 1, details = EXCLUDED.details, updated_at = EXCLUDED.updated_at
+        "#,
+        game_id,
+        player_id,
+        question_id,
+        kind,
+        details,
+        now
+    )
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result {
+        error!("Hile işareti kaydedilemedi (player_id={}, kind={}): {}", player_id, kind, e);
+    }
+}
+
+// Kaydedilen cevabın süresini oyuncuya tanınan süreyle karşılaştır
+pub async fn check_answer(
+    pool: &Pool<Postgres>,
+    game_id: i32,
+    player_id: i32,
+    question_id: i32,
+    response_time_ms: i32,
+    answer_window: Duration,
+) {
+    if is_impossible_response_time(response_time_ms, answer_window) {
+        record_flag(
+            pool,
+            game_id,
+            player_id,
+            Some(question_id),
+            FLAG_IMPOSSIBLE_RESPONSE_TIME,
+            serde_json::json!({
+                "response_time_ms": response_time_ms,
+                "answer_window_ms": answer_window.as_millis() as u64
+            }),
+        )
+        .await;
+    }
+}
+
+// Oyuncunun sekme/uygulama değiştirme bildirimini kaydet (yalnızca aktif oyunlarda)
+pub async fn record_focus_change(pool: &Pool<Postgres>, session_id: &str, state: &str) {
+    let player = sqlx::query!(
+        r#"
+        SELECT p.id, p.game_id, q.id as "question_id?"
+        FROM players p
+        JOIN games g ON p.game_id = g.id
+        LEFT JOIN LATERAL (
+            SELECT gp.question_id as id FROM game_playlist(g.id) gp WHERE gp.position = g.current_question
+        ) q ON true
+        WHERE p.session_id = $1 AND p.is_active = true AND g.status = 'active'
+        "#,
+        session_id
+    )
+    .fetch_optional(pool)
+    .await;
+
+    match player {
+        Ok(Some(p)) => {
+            record_flag(
+                pool,
+                p.game_id,
+                p.id,
+                p.question_id,
+                FLAG_FOCUS_CHANGE,
+                serde_json::json!({ "state": state }),
+            )
+            .await;
+        }
+        Ok(None) => {}
+        Err(e) => error!("Odak değişikliği kaydedilemedi: {}", e),
+    }
+}
+
+// Oyunun hile raporunu oluştur (kayıtlı işaretler ve aynı zamanlama analizi); okuma replikasından okunur
+pub async fn game_report(pool: &Pool<Postgres>, game_id: i32) -> Result<CheatReport, sqlx::Error> {
+    let pool = read_pool(pool);
+    let flags = sqlx::query!(
+        r#"
+        SELECT f.player_id, p.nickname, f.question_id, f.kind, f.details, f.occurrences,
+               f.updated_at as "updated_at!"
+        FROM cheat_flags f
+        JOIN players p ON f.player_id = p.id
+        WHERE f.game_id = $1
+        ORDER BY f.player_id, f.updated_at
+        "#,
+        game_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    // Aynı soruya aynı cevabı neredeyse aynı sürede veren oyuncu çiftleri
+    let pairs = sqlx::query!(
+        r#"
+        SELECT a.player_id as player_a, pa_a.nickname as nickname_a,
+               b.player_id as player_b, pa_b.nickname as nickname_b,
+               COUNT(*) as "matches!"
+        FROM player_answers a
+        JOIN player_answers b ON a.question_id = b.question_id
+            AND a.player_id < b.player_id
+            AND a.answer = b.answer
+            AND ABS(a.response_time_ms - b.response_time_ms) <= $2
+        JOIN players pa_a ON a.player_id = pa_a.id
+        JOIN players pa_b ON b.player_id = pa_b.id
+        WHERE pa_a.game_id = $1 AND pa_b.game_id = $1
+        GROUP BY a.player_id, pa_a.nickname, b.player_id, pa_b.nickname
+        HAVING COUNT(*) >= $3
+        ORDER BY COUNT(*) DESC
+        "#,
+        game_id,
+        IDENTICAL_TIMING_TOLERANCE_MS,
+        IDENTICAL_TIMING_MIN_MATCHES
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(CheatReport {
+        flags: flags
+            .into_iter()
+            .map(|f| CheatFlag {
+                player_id: f.player_id,
+                nickname: f.nickname,
+                question_id: f.question_id,
+                kind: f.kind,
+                details: f.details,
+                occurrences: f.occurrences,
+                last_seen_at: f.updated_at,
+            })
+            .collect(),
+        identical_timing: pairs
+            .into_iter()
+            .map(|p| IdenticalTimingPair {
+                kind: FLAG_IDENTICAL_TIMING,
+                player_ids: [p.player_a, p.player_b],
+                nicknames: [p.nickname_a, p.nickname_b],
+                matching_questions: p.matches,
+            })
+            .collect(),
+    })
+}
\ No newline at end of file
@@ -0,0 +1,131 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+use crate::utils::security::hash_api_key;
+
+// Güvenilir istemci API anahtarları
+// Okul bilgi sistemleri gibi sunucudan sunucuya entegrasyonlar kayıt, giriş ve oyuna katılma uçlarını
+// reCAPTCHA tokenı üretemeden çağırır. Admin bu istemcilere yalnızca belirli işlemler (kapsamlar) için
+// geçerli bir anahtar verir; anahtar "X-Api-Key" başlığıyla gönderilir ve kapsamdaki işlemlerde reCAPTCHA
+// doğrulaması atlanır. Veritabanında yalnızca anahtarın özeti ve tanınması için ilk karakterleri saklanır.
+
+// Anahtarın gönderildiği HTTP başlığı
+pub const API_KEY_HEADER: &str = "X-Api-Key";
+
+// Listede gösterilen anahtar başlangıcının uzunluğu (önek dahil)
+const DISPLAY_PREFIX_LEN: usize = 12;
+
+// Verilebilecek kapsamlar; reCAPTCHA ile korunan rotaların işlem adlarıyla aynıdır
+pub const SCOPES: [&str; 3] = ["register", "login", "guest_join"];
+
+// İsteği gönderen, anahtarı doğrulanmış istemci (ApiKeyAuth tarafından isteğe eklenir)
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    pub id: i32,
+    pub name: String,
+    pub scopes: Vec<String>,
+}
+
+impl ApiClient {
+    pub fn allows(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+}
+
+// Admin listesinde gösterilen anahtar bilgisi
+#[derive(Debug, Serialize, Clone)]
+pub struct ApiKeySummary {
+    pub id: i32,
+    pub name: String,
+    pub key_prefix: String,
+    pub scopes: Vec<String>,
+    pub created_by: Option<i32>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+// Yeni anahtarı kaydet
+pub async fn create(
+    pool: &Pool<Postgres>,
+    name: &str,
+    scopes: &[String],
+    created_by: i32,
+    key: &str,
+) -> Result<ApiKeySummary, sqlx::Error> {
+    let key_prefix: String = key.chars().take(DISPLAY_PREFIX_LEN).collect();
+
+    let record = sqlx::query!(
+        r#"
+        INSERT INTO api_keys (name, key_hash, key_prefix, scopes, created_by, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, created_at
+        "#,
+        name,
+        hash_api_key(key),
+        key_prefix,
+        scopes,
+        created_by,
+        Utc::now()
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(ApiKeySummary {
+        id: record.id,
+        name: name.to_string(),
+        key_prefix,
+        scopes: scopes.to_vec(),
+        created_by: Some(created_by),
+        created_at: record.created_at,
+        last_used_at: None,
+        revoked_at: None,
+    })
+}
+
+// Tüm anahtarlar (iptal edilenler dahil), yeniden eskiye
+pub async fn list(pool: &Pool<Postgres>) -> Result<Vec<ApiKeySummary>, sqlx::Error> {
+    sqlx::query_as!(
+        ApiKeySummary,
+        r#"
+        SELECT id, name, key_prefix, scopes, created_by, created_at, last_used_at, revoked_at
+        FROM api_keys
+        ORDER BY id DESC
+        "#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+// Anahtarı iptal et; anahtar yoksa veya zaten iptal edilmişse None
+pub async fn revoke(pool: &Pool<Postgres>, id: i32) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "UPDATE api_keys SET revoked_at = $1 WHERE id = $2 AND revoked_at IS NULL RETURNING name",
+        Utc::now(),
+        id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+// Gönderilen anahtara ait etkin istemci; son kullanım zamanı güncellenir
+pub async fn authenticate(pool: &Pool<Postgres>, key: &str) -> Result<Option<ApiClient>, sqlx::Error> {
+    let client = sqlx::query!(
+        r#"
+        UPDATE api_keys SET last_used_at = $1
+        WHERE key_hash = $2 AND revoked_at IS NULL
+        RETURNING id, name, scopes
+        "#,
+        Utc::now(),
+        hash_api_key(key)
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(client.map(|c| ApiClient {
+        id: c.id,
+        name: c.name,
+        scopes: c.scopes,
+    }))
+}
\ No newline at end of file
@@ -0,0 +1,160 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use std::collections::HashSet;
+
+use crate::utils::{csv, date_range};
+
+// Yoklama
+// Sınıf listesiyle sınırlandırılarak oynatılan oyunlar (games.roster_class_id) sınıfın dersleri sayılır.
+// Tarih aralığında başlatılan her oyun için sınıf üyelerinin ve henüz kayıt olmamış liste öğrencilerinin
+// oyuna katılıp katılmadığı raporlanır; rapor JSON veya CSV olarak alınabilir.
+
+// Tarih aralığı verilmezse son 30 gün raporlanır
+const DEFAULT_RANGE_DAYS: i64 = 30;
+
+// En uzun rapor aralığı
+const MAX_RANGE_DAYS: i64 = 366;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AttendanceGame {
+    pub game_id: i32,
+    pub code: String,
+    pub title: String,
+    pub started_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AttendanceStudent {
+    pub user_id: Option<i32>,
+    pub name: String,
+    pub email: String,
+    // Oyun listesiyle aynı sırada katılım durumu
+    pub present: Vec<bool>,
+    pub attended: usize,
+    pub missed: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AttendanceReport {
+    pub class_id: i32,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub games: Vec<AttendanceGame>,
+    pub students: Vec<AttendanceStudent>,
+}
+
+// Sorgudaki tarih aralığını çöz (bitiş günü dahil); geçersizse hata mesajı döner
+pub fn resolve_range(from: Option<NaiveDate>, to: Option<NaiveDate>) -> Result<(NaiveDate, NaiveDate), String> {
+    date_range::resolve(from, to, DEFAULT_RANGE_DAYS, MAX_RANGE_DAYS)
+}
+
+pub async fn report(
+    pool: &Pool<Postgres>,
+    class_id: i32,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<AttendanceReport, sqlx::Error> {
+    let games = sqlx::query!(
+        r#"
+        SELECT g.id, g.code, g.started_at, qs.title
+        FROM games g
+        JOIN question_sets qs ON g.question_set_id = qs.id
+        WHERE g.roster_class_id = $1
+          AND g.started_at IS NOT NULL
+          AND g.started_at >= $2 AND g.started_at < $3
+        ORDER BY g.started_at
+        "#,
+        class_id,
+        date_range::day_start(from),
+        date_range::day_start(to + Duration::days(1))
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|g| AttendanceGame {
+        game_id: g.id,
+        code: g.code,
+        title: g.title,
+        started_at: g.started_at,
+    })
+    .collect::<Vec<_>>();
+
+    // Sınıf üyeleri (listedeki adlarıyla) ve henüz kayıt olmamış liste öğrencileri
+    let members = sqlx::query!(
+        r#"
+        SELECT u.id as "user_id?", COALESCE(r.display_name, u.username) as "name!", COALESCE(r.email, u.email) as "email!"
+        FROM class_members cm
+        JOIN users u ON cm.user_id = u.id
+        LEFT JOIN class_roster r ON r.class_id = cm.class_id AND r.user_id = cm.user_id
+        WHERE cm.class_id = $1
+        UNION ALL
+        SELECT NULL, r.display_name, r.email
+        FROM class_roster r
+        WHERE r.class_id = $1 AND r.user_id IS NULL
+        ORDER BY 2
+        "#,
+        class_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let game_ids: Vec<i32> = games.iter().map(|g| g.game_id).collect();
+    let participations: HashSet<(i32, i32)> = sqlx::query!(
+        "SELECT DISTINCT game_id, user_id as \"user_id!\" FROM players WHERE game_id = ANY($1) AND user_id IS NOT NULL",
+        &game_ids
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|p| (p.game_id, p.user_id))
+    .collect();
+
+    let students = members
+        .into_iter()
+        .map(|m| {
+            let present: Vec<bool> = game_ids
+                .iter()
+                .map(|game_id| m.user_id.is_some_and(|user_id| participations.contains(&(*game_id, user_id))))
+                .collect();
+            let attended = present.iter().filter(|p| **p).count();
+            AttendanceStudent {
+                user_id: m.user_id,
+                name: m.name,
+                email: m.email,
+                missed: present.len() - attended,
+                attended,
+                present,
+            }
+        })
+        .collect();
+
+    Ok(AttendanceReport {
+        class_id,
+        from,
+        to,
+        games,
+        students,
+    })
+}
+
+// Raporu öğrenci başına bir satır, oyun başına bir sütun olacak şekilde CSV'ye dönüştür
+pub fn to_csv(report: &AttendanceReport) -> String {
+    let mut header = vec!["Öğrenci".to_string(), "E-posta".to_string()];
+    header.extend(report.games.iter().map(|g| {
+        let date = g.started_at.map(|d| d.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default();
+        format!("{} ({}, {})", g.title, g.code, date)
+    }));
+    header.extend(["Katıldı".to_string(), "Katılmadı".to_string()]);
+
+    let mut lines = vec![header.iter().map(|h| csv::field(h)).collect::<Vec<_>>().join(",")];
+    for student in &report.students {
+        let mut fields = vec![csv::field(&student.name), csv::field(&student.email)];
+        fields.extend(student.present.iter().map(|p| if *p { "Var" } else { "Yok" }.to_string()));
+        fields.push(student.attended.to_string());
+        fields.push(student.missed.to_string());
+        lines.push(fields.join(","));
+    }
+
+    csv::join_lines(&lines)
+}
\ No newline at end of file
@@ -0,0 +1,82 @@
+use chrono::{DateTime, Utc};
+use log::error;
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+// Denetim kaydı (audit log)
+// Hesap güvenliğiyle ilgili olaylar kim, ne zaman ve hangi ayrıntıyla gerçekleştiği bilinecek şekilde saklanır.
+
+// Kaydedilen olay tipleri
+pub const EMAIL_VERIFICATION_SENT: &str = "email_verification_sent";
+pub const EMAIL_VERIFIED: &str = "email_verified";
+pub const EMAIL_VERIFICATION_EXPIRED: &str = "email_verification_expired";
+pub const API_KEY_CREATED: &str = "api_key_created";
+pub const API_KEY_REVOKED: &str = "api_key_revoked";
+pub const API_KEY_USED: &str = "api_key_used";
+pub const BLOCKLIST_ENTRY_ADDED: &str = "blocklist_entry_added";
+pub const BLOCKLIST_ENTRY_REMOVED: &str = "blocklist_entry_removed";
+
+// Veritabanındaki denetim kaydı
+#[derive(Debug, Serialize, Clone)]
+pub struct AuditEntry {
+    pub id: i32,
+    pub user_id: Option<i32>,
+    pub username: Option<String>,
+    pub action: String,
+    pub details: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+// Yeni bir olay kaydet
+pub async fn record(pool: &Pool<Postgres>, user_id: Option<i32>, action: &str, details: serde_json::Value) {
+    let result = sqlx::query!(
+        "INSERT INTO audit_logs (user_id, action, details, created_at) VALUES ($1, $2, $3, $4)",
+        user_id,
+        action,
+        details,
+        Utc::now()
+    )
+    .execute(pool)
+    .await;
+
+    // Denetim kaydı asıl işlemi engellememeli
+    if let Err(e) = result {
+        error!("Denetim kaydı yazılamadı (user_id={:?}, action={}): {}", user_id, action, e);
+    }
+}
+
+// Son olayları yeniden eskiye doğru getir
+pub async fn list(
+    pool: &Pool<Postgres>,
+    user_id: Option<i32>,
+    action: Option<&str>,
+    limit: i64,
+) -> Result<Vec<AuditEntry>, sqlx::Error> {
+    let entries = sqlx::query!(
+        r#"
+        SELECT a.id, a.user_id, u.username as "username?", a.action, a.details, a.created_at
+        FROM audit_logs a
+        LEFT JOIN users u ON a.user_id = u.id
+        WHERE ($1::int IS NULL OR a.user_id = $1) AND ($2::text IS NULL OR a.action = $2)
+        ORDER BY a.created_at DESC, a.id DESC
+        LIMIT $3
+        "#,
+        user_id,
+        action,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(entries
+        .into_iter()
+        .map(|e| AuditEntry {
+            id: e.id,
+            user_id: e.user_id,
+            username: e.username,
+            action: e.action,
+            details: e.details,
+            created_at: e.created_at,
+        })
+        .collect())
+}
\ No newline at end of file
@@ -0,0 +1,279 @@
+use chrono::{DateTime, Utc};
+use ipnet::IpNet;
+use lazy_static::lazy_static;
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use std::net::IpAddr;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use crate::utils::validation;
+
+// Kötüye kullanım engel listesi
+// Admin, kötüye kullanım yapan IP adreslerini (CIDR blokları), e-posta alan adlarını ve kullanıcıları süreli
+// veya süresiz olarak engeller. Kayıtlar veritabanında saklanır, kimin eklediği denetim kaydına yazılır; giriş,
+// kayıt ve oyuna katılma isteklerinde her seferinde veritabanına gitmemek için liste bellekte önbelleklenir
+// (bkz. middleware::BlocklistGuard). Engellenen kullanıcıların tokenlı tüm istekleri JwtAuth'ta reddedilir.
+// Süresi dolan kayıtlar listede kalır ancak artık engellemez.
+
+// Engel türleri
+pub const KIND_IP: &str = "ip";
+pub const KIND_EMAIL_DOMAIN: &str = "email_domain";
+pub const KIND_USER: &str = "user";
+
+pub const KINDS: [&str; 3] = [KIND_IP, KIND_EMAIL_DOMAIN, KIND_USER];
+
+// Önbelleğin geçerlilik süresi; değişikliklerde önbellek hemen temizlenir
+const CACHE_TTL: Duration = Duration::from_secs(60);
+
+// Admin listesinde gösterilen kayıt
+#[derive(Debug, Serialize, Clone)]
+pub struct BlocklistEntry {
+    pub id: i32,
+    pub kind: String,
+    pub value: String,
+    pub reason: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i32>,
+    pub created_by_username: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub active: bool,
+}
+
+// Bir isteği engelleyen kayıt
+#[derive(Debug, Clone)]
+pub struct BlockMatch {
+    pub entry_id: i32,
+    pub kind: &'static str,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl BlockMatch {
+    // Engellenen istemciye gösterilecek mesaj
+    pub fn message(&self) -> String {
+        match self.expires_at {
+            Some(expires_at) => format!(
+                "Erişiminiz kötüye kullanım nedeniyle {} tarihine kadar engellendi",
+                expires_at.format("%d.%m.%Y %H:%M UTC")
+            ),
+            None => "Erişiminiz kötüye kullanım nedeniyle engellendi".to_string(),
+        }
+    }
+}
+
+// Önbellekteki etkin kayıtlar
+#[derive(Clone, Default)]
+struct ActiveBlocks {
+    networks: Vec<(IpNet, i32, Option<DateTime<Utc>>)>,
+    email_domains: Vec<(String, i32, Option<DateTime<Utc>>)>,
+    // Engellenen kullanıcı -> e-posta adresi (giriş isteklerinde kullanıcı henüz bilinmez)
+    users: Vec<(i32, String, i32, Option<DateTime<Utc>>)>,
+}
+
+struct CachedBlocks {
+    loaded_at: Instant,
+    blocks: ActiveBlocks,
+}
+
+lazy_static! {
+    static ref BLOCK_CACHE: RwLock<Option<CachedBlocks>> = RwLock::new(None);
+}
+
+// Liste değiştiğinde önbelleği temizle
+pub fn invalidate_cache() {
+    if let Ok(mut cache) = BLOCK_CACHE.write() {
+        *cache = None;
+    }
+}
+
+// Önbellekteki listeyi döndür, süresi dolmuşsa veritabanından yeniden yükle
+async fn active_blocks(pool: &Pool<Postgres>) -> Result<ActiveBlocks, sqlx::Error> {
+    if let Ok(cache) = BLOCK_CACHE.read() {
+        if let Some(cached) = cache.as_ref() {
+            if cached.loaded_at.elapsed() < CACHE_TTL {
+                return Ok(cached.blocks.clone());
+            }
+        }
+    }
+
+    let records = sqlx::query!(
+        r#"
+        SELECT b.id as "id!", b.kind as "kind!", b.value as "value!", b.expires_at, u.email as "email?"
+        FROM blocklist_entries b
+        LEFT JOIN users u ON b.kind = 'user' AND u.id::text = b.value
+        WHERE b.expires_at IS NULL OR b.expires_at > $1
+        "#,
+        Utc::now()
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut blocks = ActiveBlocks::default();
+    for r in records {
+        match r.kind.as_str() {
+            KIND_IP => {
+                if let Ok(network) = r.value.parse::<IpNet>() {
+                    blocks.networks.push((network, r.id, r.expires_at));
+                }
+            }
+            KIND_EMAIL_DOMAIN => blocks.email_domains.push((r.value, r.id, r.expires_at)),
+            KIND_USER => {
+                if let Ok(user_id) = r.value.parse::<i32>() {
+                    blocks.users.push((user_id, r.email.unwrap_or_default().to_lowercase(), r.id, r.expires_at));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Ok(mut cache) = BLOCK_CACHE.write() {
+        *cache = Some(CachedBlocks {
+            loaded_at: Instant::now(),
+            blocks: blocks.clone(),
+        });
+    }
+
+    Ok(blocks)
+}
+
+// İsteği engelleyen ilk etkin kaydı bul (IP adresi, e-posta adresi veya giriş yapmış kullanıcı)
+pub async fn find_block(
+    pool: &Pool<Postgres>,
+    ip: Option<&str>,
+    email: Option<&str>,
+    user_id: Option<i32>,
+) -> Result<Option<BlockMatch>, sqlx::Error> {
+    let blocks = active_blocks(pool).await?;
+    let now = Utc::now();
+    let active = |expires_at: &Option<DateTime<Utc>>| expires_at.is_none_or(|at| at > now);
+
+    if let Some(ip) = ip.and_then(|ip| ip.parse::<IpAddr>().ok()) {
+        if let Some((_, id, expires_at)) = blocks
+            .networks
+            .iter()
+            .find(|(network, _, expires_at)| network.contains(&ip) && active(expires_at))
+        {
+            return Ok(Some(BlockMatch { entry_id: *id, kind: KIND_IP, expires_at: *expires_at }));
+        }
+    }
+
+    if let Some(email) = email.map(|e| e.trim().to_lowercase()) {
+        let domain = validation::email_domain(&email).to_lowercase();
+        if let Some((_, id, expires_at)) = blocks
+            .email_domains
+            .iter()
+            .find(|(blocked, _, expires_at)| validation::domain_matches(&domain, blocked) && active(expires_at))
+        {
+            return Ok(Some(BlockMatch { entry_id: *id, kind: KIND_EMAIL_DOMAIN, expires_at: *expires_at }));
+        }
+
+        if let Some((_, _, id, expires_at)) = blocks
+            .users
+            .iter()
+            .find(|(_, blocked_email, _, expires_at)| *blocked_email == email && active(expires_at))
+        {
+            return Ok(Some(BlockMatch { entry_id: *id, kind: KIND_USER, expires_at: *expires_at }));
+        }
+    }
+
+    if let Some(user_id) = user_id {
+        if let Some((_, _, id, expires_at)) = blocks
+            .users
+            .iter()
+            .find(|(blocked, _, _, expires_at)| *blocked == user_id && active(expires_at))
+        {
+            return Ok(Some(BlockMatch { entry_id: *id, kind: KIND_USER, expires_at: *expires_at }));
+        }
+    }
+
+    Ok(None)
+}
+
+// Girilen değeri türüne göre doğrula ve saklanacak biçime getir
+pub fn normalize_value(kind: &str, value: &str) -> Result<String, &'static str> {
+    let value = value.trim();
+    match kind {
+        // Tek adres /32 veya /128 blok olarak saklanır
+        KIND_IP => value
+            .parse::<IpNet>()
+            .map(|network| network.trunc())
+            .or_else(|_| value.parse::<IpAddr>().map(IpNet::from))
+            .map(|network| network.to_string())
+            .map_err(|_| "Geçerli bir IP adresi veya CIDR bloğu girin (örn. 203.0.113.0/24)"),
+        KIND_EMAIL_DOMAIN => {
+            let domain = value.trim_start_matches('@').to_lowercase();
+            if validation::validate_domain(&domain) {
+                Ok(domain)
+            } else {
+                Err("Geçerli bir e-posta alan adı girin (örn. ornek.com)")
+            }
+        }
+        KIND_USER => value
+            .parse::<i32>()
+            .ok()
+            .filter(|id| *id > 0)
+            .map(|id| id.to_string())
+            .ok_or("Geçerli bir kullanıcı kimliği girin"),
+        _ => Err("Engel türü 'ip', 'email_domain' veya 'user' olmalıdır"),
+    }
+}
+
+// Tüm kayıtlar (süresi dolanlar dahil), yeniden eskiye
+pub async fn list(pool: &Pool<Postgres>) -> Result<Vec<BlocklistEntry>, sqlx::Error> {
+    sqlx::query_as!(
+        BlocklistEntry,
+        r#"
+        SELECT b.id, b.kind, b.value, b.reason, b.expires_at, b.created_by,
+               u.username as "created_by_username?", b.created_at,
+               (b.expires_at IS NULL OR b.expires_at > $1) as "active!"
+        FROM blocklist_entries b
+        LEFT JOIN users u ON b.created_by = u.id
+        ORDER BY b.id DESC
+        "#,
+        Utc::now()
+    )
+    .fetch_all(pool)
+    .await
+}
+
+// Yeni kayıt ekle; aynı tür ve değerde kayıt varsa gerekçe ve süre güncellenir
+pub async fn add(
+    pool: &Pool<Postgres>,
+    kind: &str,
+    value: &str,
+    reason: Option<&str>,
+    expires_at: Option<DateTime<Utc>>,
+    created_by: i32,
+) -> Result<i32, sqlx::Error> {
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO blocklist_entries (kind, value, reason, expires_at, created_by, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (kind, value) DO UPDATE
+        SET reason = EXCLUDED.reason, expires_at = EXCLUDED.expires_at,
+            created_by = EXCLUDED.created_by, created_at = EXCLUDED.created_at
+        RETURNING id
+        "#,
+        kind,
+        value,
+        reason,
+        expires_at,
+        created_by,
+        Utc::now()
+    )
+    .fetch_one(pool)
+    .await?;
+
+    invalidate_cache();
+    Ok(id)
+}
+
+// Kaydı kaldır; kayıt yoksa None, varsa tür ve değeri
+pub async fn remove(pool: &Pool<Postgres>, id: i32) -> Result<Option<(String, String)>, sqlx::Error> {
+    let removed = sqlx::query!("DELETE FROM blocklist_entries WHERE id = $1 RETURNING kind, value", id)
+        .fetch_optional(pool)
+        .await?;
+
+    invalidate_cache();
+    Ok(removed.map(|r| (r.kind, r.value)))
+}
\ No newline at end of file
@@ -0,0 +1,89 @@
+use chrono::Utc;
+use log::error;
+use sqlx::{Pool, Postgres};
+
+use crate::services::organization;
+
+// Soru seti ortak düzenleme yetkileri ve düzenleme geçmişi
+
+// Kullanıcının bir soru seti üzerindeki erişim seviyesi (sıralı: None < Read < Write < Owner)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SetAccess {
+    None,
+    Read,
+    Write,
+    Owner,
+}
+
+// Düzenleme geçmişindeki işlem türleri
+pub const EDIT_CREATE: &str = "create";
+pub const EDIT_UPDATE: &str = "update";
+pub const EDIT_DELETE: &str = "delete";
+pub const EDIT_RESTORE: &str = "restore";
+
+// Geçerli editör izinleri
+pub fn is_valid_permission(permission: &str) -> bool {
+    permission == "read" || permission == "write"
+}
+
+// Kullanıcının soru setine erişim seviyesini belirle
+// Sahiplik, ortak editör kaydı ve kurum paylaşımı sırasıyla kontrol edilir
+pub async fn access_level(
+    pool: &Pool<Postgres>,
+    user_id: i32,
+    set_id: i32,
+    creator_id: i32,
+    visibility: &str,
+) -> SetAccess {
+    if user_id == creator_id {
+        return SetAccess::Owner;
+    }
+
+    let editor = sqlx::query!(
+        "SELECT permission FROM question_set_editors WHERE question_set_id = $1 AND user_id = $2",
+        set_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await;
+
+    match editor {
+        Ok(Some(e)) if e.permission == "write" => return SetAccess::Write,
+        Ok(Some(_)) => return SetAccess::Read,
+        Ok(None) => {}
+        Err(e) => error!("Editör yetkisi sorgulanamadı: {}", e),
+    }
+
+    if organization::can_access_question_set(pool, user_id, creator_id, visibility).await {
+        SetAccess::Read
+    } else {
+        SetAccess::None
+    }
+}
+
+// Soru düzenleme işlemini geçmişe kaydet; hata durumunda işlemi engellemez
+pub async fn record_edit(
+    pool: &Pool<Postgres>,
+    set_id: i32,
+    question_id: i32,
+    user_id: i32,
+    action: &str,
+) {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO question_edit_log (question_set_id, question_id, user_id, action, created_at)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        set_id,
+        question_id,
+        user_id,
+        action,
+        Utc::now()
+    )
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result {
+        error!("Düzenleme geçmişi kaydedilemedi (set_id={}, question_id={}): {}", set_id, question_id, e);
+    }
+}
\ No newline at end of file
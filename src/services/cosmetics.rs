@@ -0,0 +1,180 @@
+use chrono::Utc;
+use log::info;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+
+// Avatarlar ve başarımlarla kazanılan kozmetik rozetler
+
+// Avatar tanımı; bazı avatarlar belirli bir rozet kazanılınca açılır
+#[derive(Debug, Serialize)]
+pub struct AvatarDef {
+    pub id: &'static str,
+    pub required_badge: Option<&'static str>,
+}
+
+// Rozet tanımı
+#[derive(Debug, Serialize)]
+pub struct BadgeDef {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+pub const BADGE_FIRST_GAME: &str = "first_game";
+pub const BADGE_FIRST_WIN: &str = "first_win";
+pub const BADGE_PERFECT_GAME: &str = "perfect_game";
+pub const BADGE_TEN_GAMES: &str = "ten_games";
+
+pub const BADGES: &[BadgeDef] = &[
+    BadgeDef { id: BADGE_FIRST_GAME, name: "İlk Adım", description: "İlk oyununu tamamla" },
+    BadgeDef { id: BADGE_FIRST_WIN, name: "Şampiyon", description: "Bir oyunu birinci bitir" },
+    BadgeDef { id: BADGE_PERFECT_GAME, name: "Kusursuz", description: "Bir oyundaki tüm soruları doğru cevapla" },
+    BadgeDef { id: BADGE_TEN_GAMES, name: "Müdavim", description: "10 oyun tamamla" },
+];
+
+pub const AVATARS: &[AvatarDef] = &[
+    AvatarDef { id: "apricot", required_badge: None },
+    AvatarDef { id: "fox", required_badge: None },
+    AvatarDef { id: "owl", required_badge: None },
+    AvatarDef { id: "cat", required_badge: None },
+    AvatarDef { id: "panda", required_badge: None },
+    AvatarDef { id: "rocket", required_badge: Some(BADGE_FIRST_GAME) },
+    AvatarDef { id: "crown", required_badge: Some(BADGE_FIRST_WIN) },
+    AvatarDef { id: "star", required_badge: Some(BADGE_PERFECT_GAME) },
+    AvatarDef { id: "dragon", required_badge: Some(BADGE_TEN_GAMES) },
+];
+
+// Oyun sonunda yeni kazanılan rozet
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EarnedBadge {
+    pub user_id: i32,
+    pub player_id: i32,
+    pub badge: String,
+}
+
+// Avatar, kazanılan rozetlere göre kullanılabilir mi
+pub fn is_avatar_unlocked(avatar_id: &str, earned_badges: &[String]) -> bool {
+    match AVATARS.iter().find(|a| a.id == avatar_id) {
+        Some(AvatarDef { required_badge: None, .. }) => true,
+        Some(AvatarDef { required_badge: Some(badge), .. }) => earned_badges.iter().any(|b| b == badge),
+        None => false,
+    }
+}
+
+// Kullanıcının kazandığı rozetler
+pub async fn earned_badges(pool: &Pool<Postgres>, user_id: i32) -> Result<Vec<String>, sqlx::Error> {
+    let records = sqlx::query!(
+        "SELECT badge FROM user_badges WHERE user_id = $1 ORDER BY earned_at",
+        user_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(records.into_iter().map(|r| r.badge).collect())
+}
+
+// Tamamlanan oyundaki kayıtlı oyunculara hak ettikleri rozetleri ver
+pub async fn award_game_badges(
+    pool: &Pool<Postgres>,
+    game_id: i32,
+) -> Result<Vec<EarnedBadge>, sqlx::Error> {
+    // Kazanan, ani ölüm turu yapıldıysa turun kazananı, yapılmadıysa en yüksek puanı alan oyuncudur
+    let players = sqlx::query!(
+        r#"
+        SELECT p.id as player_id, p.user_id as "user_id!", COALESCE(p.score, 0) as "score!",
+               (SELECT COUNT(*) FROM player_answers pa WHERE pa.player_id = p.id AND pa.is_correct) as "correct_count!",
+               (SELECT COUNT(*) FROM game_question_ids(g.id)) as "question_count!",
+               CASE WHEN EXISTS (SELECT 1 FROM players WHERE game_id = g.id AND sudden_death_place IS NOT NULL)
+                    THEN COALESCE(p.sudden_death_place = 1, false)
+                    ELSE COALESCE(p.score, 0) > 0
+                         AND p.score = (SELECT MAX(score) FROM players WHERE game_id = g.id AND is_active = true)
+               END as "is_winner!",
+               (SELECT COUNT(*) FROM players p2 JOIN games g2 ON p2.game_id = g2.id
+                WHERE p2.user_id = p.user_id AND g2.status = 'completed') as "games_played!"
+        FROM players p
+        JOIN games g ON p.game_id = g.id
+        WHERE p.game_id = $1 AND p.user_id IS NOT NULL AND p.is_active = true
+        "#,
+        game_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut earned = Vec::new();
+
+    for p in players {
+        let mut badges = Vec::new();
+        if p.games_played >= 1 {
+            badges.push(BADGE_FIRST_GAME);
+        }
+        if p.is_winner {
+            badges.push(BADGE_FIRST_WIN);
+        }
+        if p.question_count > 0 && p.correct_count == p.question_count {
+            badges.push(BADGE_PERFECT_GAME);
+        }
+        if p.games_played >= 10 {
+            badges.push(BADGE_TEN_GAMES);
+        }
+
+        for badge in badges {
+            // Daha önce kazanılmış rozetler tekrar verilmez
+            let inserted = sqlx::query!(
+                r#"
+                INSERT INTO user_badges (user_id, badge, game_id, earned_at)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (user_id, badge) DO NOTHING
+                "#,
+                p.user_id,
+                badge,
+                game_id,
+                Utc::now()
+            )
+            .execute(pool)
+            .await?;
+
+            if inserted.rows_affected() > 0 {
+                info!("Rozet kazanıldı: user_id={}, badge={}", p.user_id, badge);
+                earned.push(EarnedBadge {
+                    user_id: p.user_id,
+                    player_id: p.player_id,
+                    badge: badge.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(earned)
+}
+
+// Oyuna katılırken kullanılacak avatarı belirle
+// Kayıtlı kullanıcılar açtıkları avatarları, misafirler yalnızca herkese açık avatarları seçebilir;
+// geçersiz seçimde kayıtlı kullanıcının profil avatarı kullanılır.
+pub async fn resolve_player_avatar(
+    pool: &Pool<Postgres>,
+    user_id: Option<i32>,
+    requested: Option<&str>,
+) -> Option<String> {
+    let user_id = match user_id {
+        Some(user_id) => user_id,
+        None => {
+            return requested
+                .filter(|avatar| is_avatar_unlocked(avatar, &[]))
+                .map(|avatar| avatar.to_string());
+        }
+    };
+
+    if let Some(avatar) = requested {
+        let badges = earned_badges(pool, user_id).await.unwrap_or_default();
+        if is_avatar_unlocked(avatar, &badges) {
+            return Some(avatar.to_string());
+        }
+    }
+
+    sqlx::query!("SELECT avatar FROM users WHERE id = $1", user_id)
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|r| r.avatar)
+}
\ No newline at end of file
@@ -0,0 +1,151 @@
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
+
+// Müfredat konuları ve kapsam raporları
+// Taksonomi iki düzeylidir: üniteler (parent_id boş) ve ünitelere bağlı konular. Bir konu, soru setinde o
+// konuyla etiketlenmiş en az bir soru varsa kapsanmış sayılır; sınıf raporunda ise sınıf üyelerinin oyunlarda
+// karşılaştığı sorular esas alınır. Alt konusu olmayan ünite kendi başına tek konu sayılır.
+
+// Bir soru en fazla bu kadar konuyla etiketlenebilir
+pub const MAX_TOPICS_PER_QUESTION: usize = 10;
+
+const CODE_UNIQUE_CONSTRAINT: &str = "curriculum_topics_code_key";
+
+// Kısa kod başka bir konuda kullanılıyor mu
+pub fn is_code_conflict(e: &sqlx::Error) -> bool {
+    e.as_database_error()
+        .and_then(|db_error| db_error.constraint())
+        .is_some_and(|constraint| constraint == CODE_UNIQUE_CONSTRAINT)
+}
+
+#[derive(Debug, Serialize)]
+pub struct TopicCoverage {
+    pub id: i32,
+    pub code: Option<String>,
+    pub name: String,
+    pub question_count: i64,
+    pub covered: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnitCoverage {
+    pub id: i32,
+    pub code: Option<String>,
+    pub name: String,
+    pub topic_count: usize,
+    pub covered_count: usize,
+    pub coverage_percentage: f64,
+    pub topics: Vec<TopicCoverage>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CoverageReport {
+    pub topic_count: usize,
+    pub covered_count: usize,
+    pub coverage_percentage: f64,
+    pub units: Vec<UnitCoverage>,
+}
+
+fn percentage(covered: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    (covered as f64 / total as f64 * 1000.0).round() / 10.0
+}
+
+// Konu başına soru sayılarından ünite bazında kapsam raporu oluştur
+async fn build_report(
+    pool: &Pool<Postgres>,
+    question_counts: HashMap<i32, i64>,
+) -> Result<CoverageReport, sqlx::Error> {
+    let topics = sqlx::query!(
+        "SELECT id, parent_id, code, name FROM curriculum_topics ORDER BY position, name, id"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let coverage = |id: i32, code: Option<String>, name: String| {
+        let question_count = question_counts.get(&id).copied().unwrap_or(0);
+        TopicCoverage { id, code, name, question_count, covered: question_count > 0 }
+    };
+
+    let mut children: HashMap<i32, Vec<TopicCoverage>> = HashMap::new();
+    let mut units = Vec::new();
+    for topic in topics {
+        match topic.parent_id {
+            Some(parent_id) => children
+                .entry(parent_id)
+                .or_default()
+                .push(coverage(topic.id, topic.code, topic.name)),
+            None => units.push((topic.id, topic.code, topic.name)),
+        }
+    }
+
+    let units: Vec<UnitCoverage> = units
+        .into_iter()
+        .map(|(id, code, name)| {
+            let topics = children
+                .remove(&id)
+                .unwrap_or_else(|| vec![coverage(id, code.clone(), name.clone())]);
+            let covered_count = topics.iter().filter(|t| t.covered).count();
+            UnitCoverage {
+                id,
+                code,
+                name,
+                topic_count: topics.len(),
+                covered_count,
+                coverage_percentage: percentage(covered_count, topics.len()),
+                topics,
+            }
+        })
+        .collect();
+
+    let topic_count = units.iter().map(|u| u.topic_count).sum();
+    let covered_count = units.iter().map(|u| u.covered_count).sum();
+
+    Ok(CoverageReport {
+        topic_count,
+        covered_count,
+        coverage_percentage: percentage(covered_count, topic_count),
+        units,
+    })
+}
+
+// Soru setinin müfredat kapsamı
+pub async fn question_set_coverage(pool: &Pool<Postgres>, set_id: i32) -> Result<CoverageReport, sqlx::Error> {
+    let counts = sqlx::query!(
+        r#"
+        SELECT qt.topic_id, COUNT(*) as "question_count!"
+        FROM question_topics qt
+        JOIN questions q ON qt.question_id = q.id
+        WHERE q.question_set_id = $1 AND q.deleted_at IS NULL
+        GROUP BY qt.topic_id
+        "#,
+        set_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    build_report(pool, counts.into_iter().map(|c| (c.topic_id, c.question_count)).collect()).await
+}
+
+// Sınıfın müfredat kapsamı: sınıf üyelerinin oyunlarda karşılaştığı farklı soruların konuları
+pub async fn class_coverage(pool: &Pool<Postgres>, class_id: i32) -> Result<CoverageReport, sqlx::Error> {
+    let counts = sqlx::query!(
+        r#"
+        SELECT qt.topic_id, COUNT(DISTINCT qt.question_id) as "question_count!"
+        FROM question_topics qt
+        JOIN player_answers pa ON pa.question_id = qt.question_id
+        JOIN players p ON pa.player_id = p.id
+        JOIN class_members cm ON cm.user_id = p.user_id
+        WHERE cm.class_id = $1
+        GROUP BY qt.topic_id
+        "#,
+        class_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    build_report(pool, counts.into_iter().map(|c| (c.topic_id, c.question_count)).collect()).await
+}
\ No newline at end of file
@@ -0,0 +1,327 @@
+use chrono::{Duration, Utc};
+use log::info;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+use crate::db::models::{CreateQuestionDto, CreateQuestionSetDto, CreateUserDto, UserRole};
+use crate::services::game::answer_points;
+use crate::services::{game_events, progression};
+use crate::utils::security::{generate_game_code, hash_password};
+use crate::utils::validation::name_skeleton;
+
+// Demo verisi: yerel geliştirme ve tanıtım ortamları için örnek öğretmen, öğrenciler,
+// soru setleri ve istatistikleri dolu tamamlanmış bir oyun oluşturur.
+// `cargo run -- seed-demo` ile çalıştırılır, tekrar çalıştırıldığında mevcut veriye dokunmaz.
+
+pub const DEMO_TEACHER_USERNAME: &str = "demo_ogretmen";
+pub const DEMO_PASSWORD: &str = "demo12345";
+
+// Oluşturulan demo verisinin özeti
+#[derive(Debug, Serialize)]
+pub struct DemoSummary {
+    pub teacher_id: i32,
+    pub student_ids: Vec<i32>,
+    pub question_set_ids: Vec<i32>,
+    pub game_code: String,
+}
+
+fn demo_users() -> (CreateUserDto, Vec<CreateUserDto>) {
+    let teacher = CreateUserDto {
+        username: DEMO_TEACHER_USERNAME.to_string(),
+        email: "demo.ogretmen@sorukayisi.edu.tr".to_string(),
+        password: DEMO_PASSWORD.to_string(),
+        role: UserRole::Teacher,
+        invite_code: None,
+    };
+
+    let students = ["ayse", "mehmet", "zeynep", "emre", "elif"]
+        .iter()
+        .map(|name| CreateUserDto {
+            username: format!("demo_{}", name),
+            email: format!("demo.{}@sorukayisi.edu.tr", name),
+            password: DEMO_PASSWORD.to_string(),
+            role: UserRole::Student,
+            invite_code: None,
+        })
+        .collect();
+
+    (teacher, students)
+}
+
+fn question(text: &str, options: [&str; 4], correct: &str, position: i32) -> CreateQuestionDto {
+    CreateQuestionDto {
+        question_set_id: 0, // Soru seti oluşturulduktan sonra atanır
+        question_text: text.to_string(),
+        option_a: options[0].to_string(),
+        option_b: options[1].to_string(),
+        option_c: options[2].to_string(),
+        option_d: options[3].to_string(),
+        correct_option: correct.to_string(),
+        points: Some(100),
+        time_limit: Some(20),
+        position,
+        host_notes: None,
+    }
+}
+
+fn demo_question_sets() -> Vec<(CreateQuestionSetDto, Vec<CreateQuestionDto>)> {
+    vec![
+        (
+            CreateQuestionSetDto {
+                title: "Türkiye Coğrafyası".to_string(),
+                description: Some("Şehirler, dağlar ve göller üzerine temel sorular".to_string()),
+                language: None,
+            },
+            vec![
+                question("Türkiye'nin başkenti neresidir?", ["İstanbul", "Ankara", "İzmir", "Bursa"], "B", 0),
+                question("Türkiye'nin en yüksek dağı hangisidir?", ["Erciyes", "Uludağ", "Ağrı Dağı", "Süphan"], "C", 1),
+                question("Türkiye'nin en büyük gölü hangisidir?", ["Van Gölü", "Tuz Gölü", "Eğirdir Gölü", "Beyşehir Gölü"], "A", 2),
+                question("Karadeniz'e kıyısı olan il hangisidir?", ["Konya", "Malatya", "Trabzon", "Denizli"], "C", 3),
+            ],
+        ),
+        (
+            CreateQuestionSetDto {
+                title: "Temel Matematik".to_string(),
+                description: Some("Dört işlem ve kesirler".to_string()),
+                language: None,
+            },
+            vec![
+                question("12 x 8 kaçtır?", ["86", "96", "104", "112"], "B", 0),
+                question("3/4 + 1/4 kaçtır?", ["1", "1/2", "4/8", "3/16"], "A", 1),
+                question("144'ün karekökü kaçtır?", ["11", "14", "12", "13"], "C", 2),
+                question("250'nin %20'si kaçtır?", ["25", "40", "45", "50"], "D", 3),
+            ],
+        ),
+    ]
+}
+
+async fn insert_user(pool: &Pool<Postgres>, user: &CreateUserDto) -> Result<i32, anyhow::Error> {
+    let password_hash = hash_password(&user.password)?;
+
+    let record = sqlx::query!(
+        r#"
+        INSERT INTO users (username, username_skeleton, email, password_hash, role, is_approved, is_email_verified, created_at)
+        VALUES ($1, $2, $3, $4, $5, true, true, $6)
+        RETURNING id
+        "#,
+        user.username,
+        name_skeleton(&user.username),
+        user.email,
+        password_hash,
+        user.role.to_string().to_lowercase(),
+        Utc::now()
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(record.id)
+}
+
+// Demo verisini oluştur; demo öğretmeni zaten varsa hiçbir şey yapmadan None döner
+pub async fn seed_demo(pool: &Pool<Postgres>) -> Result<Option<DemoSummary>, anyhow::Error> {
+    let existing = sqlx::query!(
+        "SELECT id FROM users WHERE username = $1",
+        DEMO_TEACHER_USERNAME
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if existing.is_some() {
+        info!("Demo verisi zaten mevcut, atlanıyor");
+        return Ok(None);
+    }
+
+    // Kullanıcılar
+    let (teacher, students) = demo_users();
+    let teacher_id = insert_user(pool, &teacher).await?;
+    let mut student_ids = Vec::new();
+    for student in &students {
+        student_ids.push(insert_user(pool, student).await?);
+    }
+
+    // Soru setleri ve sorular
+    let mut question_set_ids = Vec::new();
+    let mut first_set_questions = Vec::new();
+
+    for (set, questions) in demo_question_sets() {
+        let set_record = sqlx::query!(
+            r#"
+            INSERT INTO question_sets (creator_id, title, description, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $4)
+            RETURNING id
+            "#,
+            teacher_id,
+            set.title,
+            set.description,
+            Utc::now()
+        )
+        .fetch_one(pool)
+        .await?;
+
+        for q in questions {
+            let q = CreateQuestionDto { question_set_id: set_record.id, ..q };
+            let record = sqlx::query!(
+                r#"
+                INSERT INTO questions (question_set_id, question_text, option_a, option_b, option_c, option_d,
+                                       correct_option, points, time_limit, position)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                RETURNING id
+                "#,
+                q.question_set_id,
+                q.question_text,
+                q.option_a,
+                q.option_b,
+                q.option_c,
+                q.option_d,
+                q.correct_option,
+                q.points,
+                q.time_limit,
+                q.position
+            )
+            .fetch_one(pool)
+            .await?;
+
+            if question_set_ids.is_empty() {
+                first_set_questions.push((record.id, q.correct_option, q.time_limit.unwrap_or(20)));
+            }
+        }
+
+        question_set_ids.push(set_record.id);
+    }
+
+    // Dün oynanmış, tamamlanmış bir oyun
+    let game_code = generate_game_code();
+    let started_at = Utc::now() - Duration::days(1);
+    let ended_at = started_at + Duration::minutes(6);
+
+    let game = sqlx::query!(
+        r#"
+        INSERT INTO games (code, question_set_id, host_id, status, current_question, started_at, ended_at, created_at)
+        VALUES ($1, $2, $3, 'completed', $4, $5, $6, $5)
+        RETURNING id
+        "#,
+        game_code,
+        question_set_ids[0],
+        teacher_id,
+        first_set_questions.len() as i32 - 1,
+        started_at,
+        ended_at
+    )
+    .fetch_one(pool)
+    .await?;
+
+    game_events::record_event(pool, game.id, game_events::EVENT_GAME_STARTED, serde_json::json!({})).await;
+
+    // Kayıtlı öğrenciler ve iki misafir; her oyuncunun farklı bir başarı oranı vardır
+    let mut participants: Vec<(Option<i32>, String)> = students
+        .iter()
+        .zip(&student_ids)
+        .map(|(s, id)| (Some(*id), s.username.clone()))
+        .collect();
+    participants.push((None, "Misafir Kaplan".to_string()));
+    participants.push((None, "Misafir Yunus".to_string()));
+
+    let mut rng = StdRng::seed_from_u64(2025);
+    let mut player_ids = Vec::new();
+
+    for (user_id, nickname) in &participants {
+        let player = sqlx::query!(
+            r#"
+            INSERT INTO players (game_id, user_id, nickname, score, session_id, is_active, joined_at)
+            VALUES ($1, $2, $3, 0, $4, true, $5)
+            RETURNING id
+            "#,
+            game.id,
+            *user_id,
+            nickname,
+            uuid::Uuid::new_v4().to_string(),
+            started_at - Duration::minutes(2)
+        )
+        .fetch_one(pool)
+        .await?;
+
+        game_events::record_event(pool, game.id, game_events::EVENT_PLAYER_JOINED, serde_json::json!({
+            "player_id": player.id,
+            "nickname": nickname,
+            "is_guest": user_id.is_none()
+        }))
+        .await;
+
+        player_ids.push((player.id, rng.gen_range(0.45..0.95)));
+    }
+
+    for (index, (question_id, correct_option, time_limit)) in first_set_questions.iter().enumerate() {
+        let question_started = started_at + Duration::seconds(index as i64 * 60);
+        game_events::record_event(pool, game.id, game_events::EVENT_QUESTION_START, serde_json::json!({
+            "question_id": question_id,
+            "question_number": index + 1,
+            "total_questions": first_set_questions.len(),
+            "time_limit": time_limit
+        }))
+        .await;
+
+        for (player_id, accuracy) in &player_ids {
+            let is_correct = rng.gen_bool(*accuracy);
+            let answer = if is_correct {
+                correct_option.clone()
+            } else {
+                ["A", "B", "C", "D"]
+                    .iter()
+                    .find(|o| **o != correct_option.as_str())
+                    .map(|o| o.to_string())
+                    .unwrap_or_default()
+            };
+            let response_time_ms = rng.gen_range(1500..(time_limit * 1000).max(2000));
+            let points = answer_points(is_correct, response_time_ms);
+
+            sqlx::query!(
+                r#"
+                INSERT INTO player_answers (player_id, question_id, answer, is_correct, response_time_ms, points_earned, answered_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+                player_id,
+                question_id,
+                answer,
+                is_correct,
+                response_time_ms,
+                points,
+                question_started + Duration::milliseconds(response_time_ms as i64)
+            )
+            .execute(pool)
+            .await?;
+
+            sqlx::query!(
+                "UPDATE players SET score = score + $1 WHERE id = $2",
+                points,
+                player_id
+            )
+            .execute(pool)
+            .await?;
+        }
+
+        game_events::record_event(pool, game.id, game_events::EVENT_QUESTION_END, serde_json::json!({
+            "question_id": question_id,
+            "correct_option": correct_option
+        }))
+        .await;
+    }
+
+    game_events::record_event(pool, game.id, game_events::EVENT_GAME_END, serde_json::json!({
+        "reason": "completed"
+    }))
+    .await;
+
+    // Kayıtlı öğrencilere XP, rozet ve sezon puanı ver
+    progression::award_game_rewards(pool, game.id).await;
+
+    info!("Demo verisi oluşturuldu: öğretmen={}, oyun={}", DEMO_TEACHER_USERNAME, game_code);
+
+    Ok(Some(DemoSummary {
+        teacher_id,
+        student_ids,
+        question_set_ids,
+        game_code,
+    }))
+}
\ No newline at end of file
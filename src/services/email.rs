@@ -297,4 +297,124 @@ impl EmailService {
             }
         }
     }
+
+    // Sınıf listesi davet e-postası gönderme (hesabı olmayan öğrenciler için)
+    pub async fn send_class_invitation(
+        &self,
+        to_email: &str,
+        name: &str,
+        class_name: &str,
+        teacher_username: &str,
+        invite_link: &str,
+    ) -> Result<(), anyhow::Error> {
+        let to_address = Mailbox::from_str(to_email)?;
+
+        let email = Message::builder()
+            .from(self.from_address.clone())
+            .to(to_address)
+            .subject(format!("Soru Kayısı - Sınıf Daveti: {}", class_name))
+            .header(ContentType::TEXT_HTML)
+            .body(format!(
+                r#"
+                <html>
+                <body style="font-family: Arial, sans-serif; color: #333; max-width: 600px; margin: 0 auto;">
+                    <div style="background-color: #f9d5a7; padding: 20px; text-align: center; border-radius: 5px 5px 0 0;">
+                        <h1 style="color: #8b4513;">Soru Kayısı</h1>
+                    </div>
+                    <div style="padding: 20px; border: 1px solid #ddd; border-top: none; border-radius: 0 0 5px 5px;">
+                        <p>Merhaba <strong>{}</strong>,</p>
+                        <p><strong>{}</strong> sizi <strong>{}</strong> sınıfına davet etti.</p>
+                        <p>Hesabınızı oluşturmak ve sınıfa katılmak için aşağıdaki bağlantıya tıklayın:</p>
+                        <p style="text-align: center; margin: 30px 0;">
+                            <a href="{}" style="background-color: #ff9933; color: white; padding: 10px 20px; text-decoration: none; border-radius: 5px; font-weight: bold;">Kayıt Ol</a>
+                        </p>
+                        <p>Bu bağlantı yalnızca bir kez kullanılabilir ve 30 gün boyunca geçerlidir.</p>
+                        <p>Teşekkürler,<br>Soru Kayısı Ekibi</p>
+                    </div>
+                </body>
+                </html>
+                "#,
+                escape_html(name), teacher_username, escape_html(class_name), invite_link
+            ))?;
+
+        // E-postayı gönder - send_async yerine send kullanılması gerekir
+        match self.mailer.send(email).await {
+            Ok(_) => {
+                info!("Sınıf davet e-postası gönderildi: {}", to_email);
+                Ok(())
+            }
+            Err(e) => {
+                error!("E-posta gönderme hatası: {}", e);
+                Err(anyhow::anyhow!("E-posta gönderme hatası: {}", e))
+            }
+        }
+    }
+
+    // İçerik moderasyon bildirimi gönderme
+    pub async fn send_moderation_email(
+        &self,
+        to_email: &str,
+        username: &str,
+        content_label: &str,
+        action: &str,
+        note: Option<&str>,
+    ) -> Result<(), anyhow::Error> {
+        let to_address = Mailbox::from_str(to_email)?;
+
+        let action_text = match action {
+            "hide" => "gizlenmiştir",
+            "delete" => "kaldırılmıştır",
+            _ => "incelenmiştir",
+        };
+
+        let note_html = match note {
+            Some(n) if !n.is_empty() => format!("<p>Yönetici notu: <em>{}</em></p>", n),
+            _ => String::new(),
+        };
+
+        let email = Message::builder()
+            .from(self.from_address.clone())
+            .to(to_address)
+            .subject("Soru Kayısı - İçerik Moderasyonu")
+            .header(ContentType::TEXT_HTML)
+            .body(format!(
+                r#"
+                <html>
+                <body style="font-family: Arial, sans-serif; color: #333; max-width: 600px; margin: 0 auto;">
+                    <div style="background-color: #f9d5a7; padding: 20px; text-align: center; border-radius: 5px 5px 0 0;">
+                        <h1 style="color: #8b4513;">Soru Kayısı</h1>
+                    </div>
+                    <div style="padding: 20px; border: 1px solid #ddd; border-top: none; border-radius: 0 0 5px 5px;">
+                        <p>Merhaba <strong>{}</strong>,</p>
+                        <p>Hakkında yapılan bir şikayet sonucunda <strong>{}</strong> içeriğiniz {}.</p>
+                        {}
+                        <p>Bunun bir hata olduğunu düşünüyorsanız, lütfen bizimle iletişime geçin.</p>
+                        <p>Teşekkürler,<br>Soru Kayısı Ekibi</p>
+                    </div>
+                </body>
+                </html>
+                "#,
+                username, content_label, action_text, note_html
+            ))?;
+
+        // E-postayı gönder - send_async yerine send kullanılması gerekir
+        match self.mailer.send(email).await {
+            Ok(_) => {
+                info!("Moderasyon e-postası gönderildi: {}", to_email);
+                Ok(())
+            }
+            Err(e) => {
+                error!("E-posta gönderme hatası: {}", e);
+                Err(anyhow::anyhow!("E-posta gönderme hatası: {}", e))
+            }
+        }
+    }
+}
+
+// Öğretmenin girdiği metinleri (öğrenci ve sınıf adları) HTML içinde güvenli hale getir
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
 }
\ No newline at end of file
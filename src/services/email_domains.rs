@@ -0,0 +1,92 @@
+use lazy_static::lazy_static;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use log::error;
+use sqlx::{Pool, Postgres};
+
+use crate::config::CONFIG;
+use crate::services::settings;
+use crate::utils::validation::{self, EmailPolicy};
+
+// Kurumların izin verdiği e-posta alan adları
+// Kayıt sırasında her istekte veritabanına gitmemek için liste bellekte önbelleklenir.
+
+// Önbelleğin geçerlilik süresi
+const CACHE_TTL: Duration = Duration::from_secs(300);
+
+struct CachedDomains {
+    loaded_at: Instant,
+    domains: Vec<(String, i32)>, // alan adı -> kurum id
+}
+
+lazy_static! {
+    static ref DOMAIN_CACHE: RwLock<Option<CachedDomains>> = RwLock::new(None);
+}
+
+// İzin listesi değiştiğinde önbelleği temizle
+pub fn invalidate_cache() {
+    if let Ok(mut cache) = DOMAIN_CACHE.write() {
+        *cache = None;
+    }
+}
+
+// Önbellekteki listeyi döndür, süresi dolmuşsa veritabanından yeniden yükle
+async fn cached_domains(pool: &Pool<Postgres>) -> Result<Vec<(String, i32)>, sqlx::Error> {
+    if let Ok(cache) = DOMAIN_CACHE.read() {
+        if let Some(cached) = cache.as_ref() {
+            if cached.loaded_at.elapsed() < CACHE_TTL {
+                return Ok(cached.domains.clone());
+            }
+        }
+    }
+
+    let records = sqlx::query!("SELECT domain, organization_id FROM organization_email_domains")
+        .fetch_all(pool)
+        .await?;
+
+    let domains: Vec<(String, i32)> = records
+        .into_iter()
+        .map(|r| (r.domain, r.organization_id))
+        .collect();
+
+    if let Ok(mut cache) = DOMAIN_CACHE.write() {
+        *cache = Some(CachedDomains {
+            loaded_at: Instant::now(),
+            domains: domains.clone(),
+        });
+    }
+
+    Ok(domains)
+}
+
+// E-posta adresinin alan adı bir kurumun izin listesindeyse kurum id'sini döndür
+pub async fn find_organization_for_email(
+    pool: &Pool<Postgres>,
+    email: &str,
+) -> Result<Option<i32>, sqlx::Error> {
+    let domain = validation::email_domain(email).to_lowercase();
+    let domains = cached_domains(pool).await?;
+
+    // En spesifik (en uzun) eşleşme kazanır
+    Ok(domains
+        .iter()
+        .filter(|(allowed, _)| validation::domain_matches(&domain, allowed))
+        .max_by_key(|(allowed, _)| allowed.len())
+        .map(|(_, org_id)| *org_id))
+}
+
+// Geçerli kayıt politikası: admin ayarı varsa o, yoksa konfigürasyon
+pub async fn current_policy(pool: &Pool<Postgres>) -> EmailPolicy {
+    match settings::get_setting(pool, settings::SETTING_EMAIL_POLICY).await {
+        Ok(Some(value)) => value.parse::<EmailPolicy>().unwrap_or_else(|e| {
+            error!("{}", e);
+            CONFIG.email_policy
+        }),
+        Ok(None) => CONFIG.email_policy,
+        Err(e) => {
+            error!("E-posta politikası okunamadı: {}", e);
+            CONFIG.email_policy
+        }
+    }
+}
\ No newline at end of file
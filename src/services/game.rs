@@ -0,0 +1,1187 @@
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use log::{error, warn};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::db::models::{GameResults, LeaderboardEntry, PlayerRef, QuestionMessage, WebSocketMessage};
+use crate::db::repositories::{
+    GameRepo, NewAnswer, PgGameRepo, PgPlayerRepo, PgQuestionRepo, PlayerRepo, QuestionRepo, SessionPlayer,
+};
+use crate::handlers::websocket::AppState;
+use crate::services::option_labels::OptionLabelStyle;
+use crate::services::progression::{self, GameRewards};
+use crate::services::{anti_cheat, game_events, game_playlist, localization, notifications, tournament, tts};
+
+// Oyun akışı: başlatma, soru ilerletme, cevap kaydetme ve oyunu bitirme.
+// REST ve WebSocket işleyicileri aynı fonksiyonları kullanır; her durum değişikliği veritabanına yazılır,
+// bellekteki oyun durumuna işlenir ve bağlı istemcilere yayınlanır.
+//
+// Cevaplar yoğun anlarda tek tek yazılmaz: önce bellekte biriktirilir, soru bittiğinde ve düzenli
+// aralıklarla (bkz. ANSWER_FLUSH_INTERVAL) toplu olarak veritabanına yazılır.
+
+// Biriken cevapların en geç yazılma aralığı
+pub const ANSWER_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+// Doğru cevabın alabileceği en yüksek puan
+pub const MAX_ANSWER_POINTS: i32 = 1000;
+
+// Kabul edilen cevap şıkları (X: süre doldu / boş)
+pub const VALID_ANSWERS: [&str; 5] = ["A", "B", "C", "D", "X"];
+
+// Ani ölüm turundaki en az finalist sayısı
+pub const MIN_SUDDEN_DEATH_FINALISTS: i32 = 2;
+
+// Yazılamayan cevaplar atılmaz, her yazmada yeniden denenir; bu kadar başarısız denemeden sonra hata loglanır
+const FLUSH_ERROR_ATTEMPTS: u32 = 5;
+
+// Bir oyunun henüz veritabanına yazılmamış cevapları. Yazılmakta olan cevaplar kayıt tamamlanana kadar
+// in_flight'ta kalır; böylece aynı soruya ikinci cevap o arada da reddedilir.
+#[derive(Default)]
+struct PendingAnswers {
+    queued: Vec<NewAnswer>,
+    in_flight: Vec<NewAnswer>,
+    // Yazılırken geri alınan cevaplar (player_id, question_id); kayıt tamamlanınca silinir
+    retracted: Vec<(i32, i32)>,
+    failed_attempts: u32,
+}
+
+impl PendingAnswers {
+    fn contains(&self, player_id: i32, question_id: i32) -> bool {
+        let key = (player_id, question_id);
+        self.queued.iter().any(|a| (a.player_id, a.question_id) == key)
+            || (self.in_flight.iter().any(|a| (a.player_id, a.question_id) == key) && !self.retracted.contains(&key))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.queued.is_empty() && self.in_flight.is_empty()
+    }
+}
+
+lazy_static! {
+    // Henüz veritabanına yazılmamış cevaplar (game_id -> cevaplar)
+    static ref PENDING_ANSWERS: Mutex<HashMap<i32, PendingAnswers>> = Mutex::new(HashMap::new());
+    // Oyun başına yazma kilidi; aynı oyunun cevapları sırayla yazılır ve yazma isteyen, o anda süren yazmayı bekler
+    static ref FLUSH_LOCKS: Mutex<HashMap<i32, Arc<tokio::sync::Mutex<()>>>> = Mutex::new(HashMap::new());
+}
+
+// Oyun işlemlerinin reddedilme nedenleri
+#[derive(Debug)]
+pub enum GameError {
+    GameNotFound,
+    NotHost,
+    AlreadyStarted,
+    NotActive,
+    PlayerNotFound,
+    QuestionNotFound,
+    QuestionNotActive,
+    InvalidAnswer,
+    AlreadyAnswered,
+    NotAnswered,
+    NotFinalist,
+    SuddenDeathUnavailable,
+    InvalidFinalistCount,
+    Database,
+}
+
+impl GameError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            GameError::GameNotFound => "Oyun bulunamadı",
+            GameError::NotHost => "Bu işlemi yalnızca oyun sahibi yapabilir",
+            GameError::AlreadyStarted => "Bu oyun zaten başlatılmış veya tamamlanmış",
+            GameError::NotActive => "Oyun aktif değil",
+            GameError::PlayerNotFound => "Aktif oyuncu bulunamadı",
+            GameError::QuestionNotFound => "Soru bulunamadı",
+            GameError::QuestionNotActive => "Bu soru şu anda aktif değil",
+            GameError::InvalidAnswer => "Geçersiz cevap",
+            GameError::AlreadyAnswered => "Bu soruya zaten cevap verdiniz",
+            GameError::NotAnswered => "Bu soruya verilmiş bir cevabınız yok",
+            GameError::NotFinalist => "Ani ölüm turunda yalnızca elenmemiş finalistler cevap verebilir",
+            GameError::SuddenDeathUnavailable => "Ani ölüm turu yalnızca sorular arasında, en az iki aktif oyuncuyla ve bir kez başlatılabilir",
+            GameError::InvalidFinalistCount => "Ani ölüm turu için en az 2 finalist seçilmelidir",
+            GameError::Database => "İşlem sırasında bir hata oluştu",
+        }
+    }
+}
+
+impl From<sqlx::Error> for GameError {
+    fn from(e: sqlx::Error) -> Self {
+        error!("Veritabanı sorgu hatası: {}", e);
+        GameError::Database
+    }
+}
+
+// Oyunculara gösterilen soru; doğru cevap yalnızca host mesajında bulunur
+#[derive(Debug, Clone)]
+pub struct QuestionStart {
+    pub question_id: i32,
+    pub question_text: String,
+    pub options: [String; 4],
+    pub correct_option: String,
+    pub time_limit: Option<i32>,
+    pub question_number: i64,
+    pub total_questions: i64,
+    pub started_at: DateTime<Utc>,
+    pub deadline: DateTime<Utc>,
+    pub sequence: Option<i32>,
+    // Sesli okuma etkinse ve ses hazırsa sorunun seslendirilmiş hali
+    pub audio_url: Option<String>,
+    pub option_labels: OptionLabelStyle,
+    // Öğretmenin soruya eklediği notlar; oyunculara gönderilmez
+    pub host_notes: Option<String>,
+}
+
+impl QuestionStart {
+    // Oyunculara giden soru; çeviri ve ek süreler oyuncuya göre bunun üzerine uygulanır
+    pub fn player_payload(&self) -> QuestionMessage {
+        QuestionMessage {
+            question_id: self.question_id,
+            question_text: self.question_text.clone(),
+            options: QuestionMessage::option_map(&self.options),
+            time_limit: self.time_limit,
+            question_number: self.question_number,
+            total_questions: self.total_questions,
+            started_at: self.started_at.timestamp_millis(),
+            deadline: self.deadline.timestamp_millis(),
+            server_time: Utc::now().timestamp_millis(),
+            seq: self.sequence,
+            audio_url: self.audio_url.clone(),
+            option_labels: self.option_labels.payload(),
+            locale: None,
+            accommodations: None,
+            correct_option: None,
+            host_notes: None,
+        }
+    }
+
+    pub fn player_message(&self) -> WebSocketMessage {
+        WebSocketMessage::QuestionStart(self.player_payload())
+    }
+
+    // Host'a gönderilen soru (doğru cevap ve host notlarıyla birlikte)
+    pub fn host_message(&self) -> WebSocketMessage {
+        let mut payload = self.player_payload();
+        payload.correct_option = Some(self.correct_option.clone());
+        payload.host_notes = self.host_notes.clone();
+        WebSocketMessage::QuestionStart(payload)
+    }
+}
+
+// Tamamlanan oyunun sonuçları
+#[derive(Debug)]
+pub struct GameSummary {
+    pub game_id: i32,
+    pub final_leaderboard: Vec<LeaderboardEntry>,
+    pub player_stats: Vec<serde_json::Value>,
+    pub rewards: GameRewards,
+    // Ani ölüm turu tek finaliste indiyse kazanan (player_id, nickname)
+    pub sudden_death_winner: Option<PlayerRef>,
+}
+
+// Soru ilerletmenin sonucu: yeni soru veya sorular bittiyse oyun sonu
+#[derive(Debug)]
+pub enum NextStep {
+    Question(QuestionStart),
+    Completed(GameSummary),
+}
+
+// Kaydedilen cevap
+#[derive(Debug)]
+pub struct AnswerResult {
+    pub question_id: i32,
+    pub answer: String,
+    pub is_correct: bool,
+    pub response_time_ms: i32,
+    pub points_earned: i32,
+    pub correct_option: String,
+}
+
+impl AnswerResult {
+    // Oyuncuya gönderilen alındı bildirimi
+    pub fn receipt(&self) -> WebSocketMessage {
+        answer_receipt(self.question_id, &self.answer)
+    }
+}
+
+// Cevap süre dolana kadar geri alınabildiği için bildirimde doğruluk, puan ve doğru şık yer almaz; aksi halde oyuncu
+// yanlış çıkan cevabı geri alıp kalan şıkları deneyebilirdi. Oyuncu sonucu question_end ile öğrenir.
+pub fn answer_receipt(question_id: i32, answer: &str) -> WebSocketMessage {
+    WebSocketMessage::AnswerReceived {
+        question_id,
+        your_answer: answer.to_string(),
+        message: "Cevabınız alındı, sonuç soru süresi dolunca gösterilecek".to_string(),
+    }
+}
+
+// Doğru cevaplarda hıza göre 100-1000 puan (10 saniye ve üzeri en az puanı alır).
+// İstemciden gelen negatif süreler sıfır sayılır; puan hiçbir durumda üst sınırı aşmaz.
+pub fn answer_points(is_correct: bool, response_time_ms: i32) -> i32 {
+    if !is_correct {
+        return 0;
+    }
+    let max_points = MAX_ANSWER_POINTS;
+    let min_points = 100;
+    let max_time_ms = 10000;
+
+    let response_time_ms = response_time_ms.max(0);
+    let time_factor = ((max_time_ms - response_time_ms) as f64 / max_time_ms as f64).clamp(0.0, 1.0);
+    (min_points as f64 + (max_points - min_points) as f64 * time_factor) as i32
+}
+
+// Oyunda alınabilecek en yüksek puan: her soru doğru ve en hızlı cevaplandığında (taban puan + hız bonusu).
+// Oyuncu puanı yalnızca answer_points'ten gelir; sorulardaki points alanı canlı oyunda kasıtlı olarak kullanılmaz
+// (soru setlerinde düzenlenip kopyalanır ama puanlamaya girmez), bu yüzden her soru MAX_ANSWER_POINTS sayılır.
+// İstemciler "olası puanın %80'i" gibi yorumları bu değere göre yapar.
+pub fn max_possible_score(question_count: i64) -> i64 {
+    question_count * MAX_ANSWER_POINTS as i64
+}
+
+// Yüzde olarak puan / en yüksek olası puan (bir ondalık)
+pub fn percent_of_max(score: i64, max_possible_score: i64) -> f64 {
+    if max_possible_score > 0 {
+        ((score.max(0) as f64 / max_possible_score as f64) * 1000.0).round() / 10.0
+    } else {
+        0.0
+    }
+}
+
+// Oyunun güncel liderlik tablosu (aktif oyuncular, puana göre)
+pub async fn leaderboard(pool: &Pool<Postgres>, game_id: i32) -> Result<Vec<LeaderboardEntry>, sqlx::Error> {
+    PgPlayerRepo::new(pool).leaderboard(game_id).await
+}
+
+// Cevabı yazılmak üzere kuyruğa ekle; aynı oyuncunun aynı soruya bekleyen cevabı varsa false döner
+fn buffer_answer(game_id: i32, answer: NewAnswer) -> bool {
+    let mut pending = PENDING_ANSWERS.lock().unwrap_or_else(|e| e.into_inner());
+    let answers = pending.entry(game_id).or_default();
+    if answers.contains(answer.player_id, answer.question_id) {
+        return false;
+    }
+    answers.queued.push(answer);
+    true
+}
+
+// Henüz yazılmamış cevabı geri al; cevap o anda yazılıyorsa kayıt tamamlanınca silinir
+fn retract_pending_answer(game_id: i32, player_id: i32, question_id: i32) -> bool {
+    let mut pending = PENDING_ANSWERS.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(answers) = pending.get_mut(&game_id) else {
+        return false;
+    };
+
+    if let Some(index) = answers
+        .queued
+        .iter()
+        .position(|a| a.player_id == player_id && a.question_id == question_id)
+    {
+        answers.queued.remove(index);
+        return true;
+    }
+
+    if answers.contains(player_id, question_id) {
+        answers.retracted.push((player_id, question_id));
+        return true;
+    }
+
+    false
+}
+
+fn has_pending_answer(game_id: i32, player_id: i32, question_id: i32) -> bool {
+    let pending = PENDING_ANSWERS.lock().unwrap_or_else(|e| e.into_inner());
+    pending
+        .get(&game_id)
+        .is_some_and(|answers| answers.contains(player_id, question_id))
+}
+
+// Oyunun biriken cevaplarını veritabanına yaz (liderlik tablosu ve sonuçlar okunmadan önce çağrılır).
+// O anda süren bir yazma varsa önce onun bitmesi beklenir, böylece dönüşte çağrıdan önce verilen tüm cevaplar
+// yazılmış olur. Yazılamayan cevaplar kuyruğun başına geri konur ve hata döndürülür.
+pub async fn flush_answers(pool: &Pool<Postgres>, game_id: i32) -> Result<(), sqlx::Error> {
+    flush_answers_with(&PgPlayerRepo::new(pool), game_id).await
+}
+
+async fn flush_answers_with(players: &impl PlayerRepo, game_id: i32) -> Result<(), sqlx::Error> {
+    let lock = {
+        let mut locks = FLUSH_LOCKS.lock().unwrap_or_else(|e| e.into_inner());
+        locks.entry(game_id).or_default().clone()
+    };
+
+    let result = {
+        let _guard = lock.lock().await;
+        write_pending_answers(players, game_id).await
+    };
+
+    // Bekleyen başka yazma yoksa kilit bırakılır; kopyalar yalnızca bu harita kilitliyken alındığından
+    // sayım güvenilirdir
+    let mut locks = FLUSH_LOCKS.lock().unwrap_or_else(|e| e.into_inner());
+    if Arc::strong_count(&lock) == 2 {
+        locks.remove(&game_id);
+    }
+
+    result
+}
+
+// Kuyruktaki cevapları tek işlemde yaz; yazma kilidi tutulurken çağrılır
+async fn write_pending_answers(players: &impl PlayerRepo, game_id: i32) -> Result<(), sqlx::Error> {
+    let batch = {
+        let mut pending = PENDING_ANSWERS.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(answers) = pending.get_mut(&game_id) else {
+            return Ok(());
+        };
+        if answers.queued.is_empty() {
+            return Ok(());
+        }
+        answers.in_flight = std::mem::take(&mut answers.queued);
+        answers.in_flight.clone()
+    };
+
+    let result = players.record_answers(&batch).await;
+
+    let retracted = {
+        let mut pending = PENDING_ANSWERS.lock().unwrap_or_else(|e| e.into_inner());
+        let answers = pending.entry(game_id).or_default();
+        answers.in_flight.clear();
+        let retracted = std::mem::take(&mut answers.retracted);
+
+        match &result {
+            Ok(()) => answers.failed_attempts = 0,
+            Err(e) => {
+                // Bellekteki puanlar bu cevapları içerdiğinden cevaplar atılmaz; canlı ve son liderlik tabloları
+                // ancak hepsi yazıldığında aynı olur
+                answers.failed_attempts += 1;
+                if answers.failed_attempts >= FLUSH_ERROR_ATTEMPTS {
+                    error!(
+                        "{} cevap {} denemedir kaydedilemiyor (game_id={}): {}",
+                        batch.len(), answers.failed_attempts, game_id, e
+                    );
+                } else {
+                    warn!(
+                        "{} cevap kaydedilemedi, tekrar denenecek (game_id={}, deneme {}): {}",
+                        batch.len(), game_id, answers.failed_attempts, e
+                    );
+                }
+                let requeued = batch
+                    .iter()
+                    .filter(|a| !retracted.contains(&(a.player_id, a.question_id)))
+                    .cloned();
+                answers.queued.splice(0..0, requeued);
+            }
+        }
+
+        if answers.is_empty() {
+            pending.remove(&game_id);
+        }
+        retracted
+    };
+
+    result?;
+
+    // Yazılırken geri alınan cevaplar kayıt tamamlandıktan sonra silinir
+    for (player_id, question_id) in retracted {
+        if let Err(e) = players.retract_answer(player_id, question_id).await {
+            error!("Geri alınan cevap silinemedi (player_id={}, question_id={}): {}", player_id, question_id, e);
+        }
+    }
+
+    Ok(())
+}
+
+// Tüm oyunların biriken cevaplarını yaz
+pub async fn flush_all_answers(pool: &Pool<Postgres>) {
+    let game_ids: Vec<i32> = {
+        let pending = PENDING_ANSWERS.lock().unwrap_or_else(|e| e.into_inner());
+        pending.keys().copied().collect()
+    };
+
+    // Hatalar flush_answers içinde loglanır; cevaplar bir sonraki turda yeniden denenir
+    for game_id in game_ids {
+        let _ = flush_answers(pool, game_id).await;
+    }
+}
+
+// Oyun akışı işlemleri. Oyun, oyuncu ve soru tabloları depo trait'leri üzerinden kullanılır; testlerde sahte
+// depolarla çalıştırılabilir
+pub struct GameService<'a, G, P, Q> {
+    pool: &'a Pool<Postgres>,
+    games: G,
+    players: P,
+    questions: Q,
+}
+
+impl<'a> GameService<'a, PgGameRepo<'a>, PgPlayerRepo<'a>, PgQuestionRepo<'a>> {
+    pub fn new(pool: &'a Pool<Postgres>) -> Self {
+        GameService {
+            pool,
+            games: PgGameRepo::new(pool),
+            players: PgPlayerRepo::new(pool),
+            questions: PgQuestionRepo::new(pool),
+        }
+    }
+}
+
+impl<G: GameRepo, P: PlayerRepo, Q: QuestionRepo> GameService<'_, G, P, Q> {
+    // Oyunu başlat; ilk soru next_question ile gösterilir
+    pub async fn start_game(
+        &self,
+        state: &AppState,
+        game_code: &str,
+        user_id: Option<i32>,
+    ) -> Result<i32, GameError> {
+        let pool = self.pool;
+        let game = self.games.find_by_code(game_code).await?.ok_or(GameError::GameNotFound)?;
+
+        if user_id != Some(game.host_id) {
+            return Err(GameError::NotHost);
+        }
+
+        if !self.games.start(game.id).await? {
+            return Err(GameError::AlreadyStarted);
+        }
+
+        // Oyuna başka setler eklendiyse veya rastgele soru seçimi ayarlıysa soru listesi şimdi oluşturulur
+        game_playlist::resolve(pool, game.id).await?;
+
+        state.mark_game_started(game_code).await;
+
+        // Soruların sesleri ilk soru gösterilmeden hazır olsun diye arka planda üretilir
+        if tts::is_enabled() {
+            let pool = pool.clone();
+            let mut question_set_ids = vec![game.question_set_id];
+            question_set_ids.extend(game_playlist::pooled_set_ids(&pool, game.id).await?);
+            let host_id = game.host_id;
+            actix_web::rt::spawn(async move {
+                for question_set_id in question_set_ids {
+                    match tts::prepare_question_set(&pool, question_set_id, Some(host_id)).await {
+                        Ok(summary) if summary.quota_exceeded.is_some() => {
+                            warn!("Soru sesleri kota dolduğu için yarıda kaldı: host_id={}", host_id);
+                            break;
+                        }
+                        Ok(_) => {}
+                        Err(e) => error!("Soru sesleri hazırlanamadı: {}", e),
+                    }
+                }
+            });
+        }
+        let sequence = game_events::record_event(pool, game.id, game_events::EVENT_GAME_STARTED, json!({})).await;
+
+        state
+            .broadcast_to_game(game_code, WebSocketMessage::GameStarted {
+                game_code: game_code.to_string(),
+                message: "Oyun başlatıldı, ilk soru için hazırlanın!".to_string(),
+                seq: sequence,
+            })
+            .await;
+
+        Ok(game.id)
+    }
+
+    // Bir sonraki soruya geç; soru kalmadıysa oyunu tamamla
+    pub async fn next_question(
+        &self,
+        state: &AppState,
+        game_code: &str,
+        user_id: Option<i32>,
+    ) -> Result<NextStep, GameError> {
+        let pool = self.pool;
+        let game = self.games.find_by_code(game_code).await?.ok_or(GameError::GameNotFound)?;
+
+        if user_id != Some(game.host_id) {
+            return Err(GameError::NotHost);
+        }
+
+        if game.status != "active" {
+            return Err(GameError::NotActive);
+        }
+
+        // Önceki sorunun cevapları yazılmadan ilerlenmez; oyun sonu sonuçları bunlara dayanır
+        flush_answers_with(&self.players, game.id).await?;
+
+        // Ani ölüm turunda tek finalist kaldıysa kalan sorular gösterilmeden oyun biter
+        if state.sudden_death_winner(game_code).await.is_some() {
+            return Ok(NextStep::Completed(self.complete_game(state, game.id, game_code).await?));
+        }
+
+        // Oyunda sorulacak sorulardan mevcut sorudan sonraki; oyun yeni başladıysa (current_question boş) ilk soru
+        let question = self.questions.next_in_game(game.id, game.current_question).await?;
+
+        let q = match question {
+            Some(q) => q,
+            None => return Ok(NextStep::Completed(self.complete_game(state, game.id, game_code).await?)),
+        };
+
+        self.games.set_current_question(game.id, q.position).await?;
+
+        let duration = Duration::from_secs(q.time_limit.unwrap_or(30).max(0) as u64);
+        let started_at = Utc::now();
+        let deadline = started_at + chrono::Duration::from_std(duration).unwrap_or_default();
+        state.begin_question(game_code, q.position, q.id, duration).await;
+
+        let option_labels = game.option_labels.parse::<OptionLabelStyle>().unwrap_or_default();
+        let audio_url = match tts::is_enabled() {
+            true => {
+                let language = sqlx::query_scalar!("SELECT language FROM question_sets WHERE id = $1", game.question_set_id)
+                    .fetch_one(pool)
+                    .await?;
+                tts::cached_audio_url(&language, &q.question_text, &q.options).await
+            }
+            false => None,
+        };
+
+        // Olay, yeniden bağlanan oyunculara aynen gönderilebilmesi için soru metnini de içerir (doğru cevap hariç)
+        let sequence = game_events::record_event(pool, game.id, game_events::EVENT_QUESTION_START, json!({
+            "question_id": q.id,
+            "question_text": q.question_text,
+            "options": {
+                "A": q.options[0],
+                "B": q.options[1],
+                "C": q.options[2],
+                "D": q.options[3]
+            },
+            "question_number": q.question_number,
+            "total_questions": q.total_questions,
+            "time_limit": q.time_limit,
+            "started_at": started_at.timestamp_millis(),
+            "deadline": deadline.timestamp_millis(),
+            "audio_url": audio_url,
+            "option_labels": option_labels.payload()
+        }))
+        .await;
+
+        let question = QuestionStart {
+            question_id: q.id,
+            question_text: q.question_text,
+            options: q.options,
+            correct_option: q.correct_option,
+            time_limit: q.time_limit,
+            question_number: q.question_number,
+            total_questions: q.total_questions,
+            started_at,
+            deadline,
+            sequence,
+            audio_url,
+            option_labels,
+            host_notes: q.host_notes,
+        };
+
+        // Oyunculara doğru cevap olmadan ve tercih ettikleri dilde gönderilir; host mesajını çağıran taraf iletir
+        let translations = localization::question_translations(pool, question.question_id)
+            .await
+            .unwrap_or_else(|e| {
+                error!("Soru çevirileri alınamadı: {}", e);
+                Vec::new()
+            });
+        state.broadcast_question(game_code, &question, &translations).await;
+
+        Ok(NextStep::Question(question))
+    }
+
+    // Ani ölüm turunu başlat: güncel puanlara göre ilk `top_n` aktif oyuncu finalist olur, diğerleri izler.
+    // Tur sorular arasında başlatılır; sonraki sorularda yanlış cevap veren veya cevap vermeyen finalist elenir.
+    pub async fn start_sudden_death(
+        &self,
+        state: &AppState,
+        game_code: &str,
+        user_id: Option<i32>,
+        top_n: i32,
+    ) -> Result<Vec<LeaderboardEntry>, GameError> {
+        let game = self.games.find_by_code(game_code).await?.ok_or(GameError::GameNotFound)?;
+
+        if user_id != Some(game.host_id) {
+            return Err(GameError::NotHost);
+        }
+
+        if game.status != "active" {
+            return Err(GameError::NotActive);
+        }
+
+        if top_n < MIN_SUDDEN_DEATH_FINALISTS {
+            return Err(GameError::InvalidFinalistCount);
+        }
+
+        let finalists = state
+            .begin_sudden_death(game_code, top_n as usize)
+            .await
+            .ok_or(GameError::SuddenDeathUnavailable)?;
+
+        let sequence = game_events::record_event(self.pool, game.id, game_events::EVENT_SUDDEN_DEATH_START, json!({
+            "finalists": finalists
+        }))
+        .await;
+
+        state
+            .broadcast_to_game(game_code, WebSocketMessage::SuddenDeathStart {
+                finalists: finalists.clone(),
+                message: "Ani ölüm turu başladı! Yanlış cevap veren finalist elenir".to_string(),
+                seq: sequence,
+            })
+            .await;
+
+        Ok(finalists)
+    }
+
+    // Oyunu tamamla: sonuçları hesapla, ödülleri ver, oyunculara bildir ve bellekten kaldır
+    async fn complete_game(
+        &self,
+        state: &AppState,
+        game_id: i32,
+        game_code: &str,
+    ) -> Result<GameSummary, GameError> {
+        let pool = self.pool;
+
+        // Sonuçlar, XP ve turnuva puanları veritabanındaki cevaplardan hesaplanır; tüm cevaplar yazılmadan oyun bitmez
+        flush_answers_with(&self.players, game_id).await?;
+
+        // Ani ölüm turu yapıldıysa finalistler son sıralamada ve ödüllerde elenme sırasına göre öne alınır
+        let sudden_death = state.sudden_death_standings(game_code).await;
+        if let Some(standings) = &sudden_death {
+            self.players.set_sudden_death_places(&standings.places).await?;
+        }
+        let sudden_death_winner = match sudden_death.and_then(|standings| standings.winner) {
+            Some(player_id) => Some(PlayerRef {
+                player_id,
+                nickname: state.player_nickname(game_code, player_id).await,
+            }),
+            None => None,
+        };
+
+        self.games.complete(game_id).await?;
+
+        state.mark_game_ended(game_code).await;
+
+        let final_leaderboard = self.players.leaderboard(game_id).await?;
+
+        // Oyun sonu performans istatistikleri
+        let stats = sqlx::query!(
+            r#"
+            SELECT
+                p.id as player_id,
+                p.nickname,
+                p.score,
+                COUNT(pa.id) as "answer_count!",
+                COUNT(pa.id) FILTER (WHERE pa.is_correct) as "correct_count!",
+                ROUND(AVG(pa.response_time_ms))::float8 as avg_response_time
+            FROM players p
+            LEFT JOIN player_answers pa ON p.id = pa.player_id
+            WHERE p.game_id = $1 AND p.is_active = true
+            GROUP BY p.id, p.nickname, p.score
+            ORDER BY p.sudden_death_place ASC NULLS LAST, p.score DESC
+            "#,
+            game_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let player_stats = stats
+            .iter()
+            .map(|s| {
+                let accuracy = if s.answer_count > 0 {
+                    (s.correct_count as f64 / s.answer_count as f64 * 100.0).round()
+                } else {
+                    0.0
+                };
+
+                json!({
+                    "player_id": s.player_id,
+                    "nickname": s.nickname,
+                    "score": s.score,
+                    "answers": s.answer_count,
+                    "correct": s.correct_count,
+                    "accuracy": accuracy,
+                    "avg_response_time_ms": s.avg_response_time.unwrap_or(0.0)
+                })
+            })
+            .collect();
+
+        let sequence = game_events::record_event(pool, game_id, game_events::EVENT_GAME_END, json!({
+            "reason": "completed",
+            "final_leaderboard": final_leaderboard,
+            "sudden_death_winner": &sudden_death_winner
+        }))
+        .await;
+
+        // Kayıtlı oyunculara XP, rozet ve sezon puanı ver
+        let rewards = progression::award_game_rewards(pool, game_id).await;
+        notifications::notify_game_report_ready(pool, game_id).await;
+
+        let summary = GameSummary {
+            game_id,
+            final_leaderboard,
+            player_stats,
+            rewards,
+            sudden_death_winner,
+        };
+
+        state
+            .broadcast_to_game(game_code, WebSocketMessage::GameEnd {
+                reason: None,
+                results: Some(GameResults {
+                    final_leaderboard: summary.final_leaderboard.clone(),
+                    player_stats: summary.player_stats.clone(),
+                    xp_awards: summary.rewards.xp_awards.clone(),
+                    badges_earned: summary.rewards.badges_earned.clone(),
+                    rating_changes: summary.rewards.rating_changes.clone(),
+                    sudden_death_winner: summary.sudden_death_winner.clone(),
+                }),
+                message: "Oyun tamamlandı, sonuçlar gösteriliyor".to_string(),
+                seq: sequence,
+            })
+            .await;
+
+        // Seviye atlayan oyuncular için ayrı bildirim gönder
+        for award in summary.rewards.xp_awards.iter().filter(|a| a.leveled_up) {
+            state
+                .broadcast_to_game(game_code, WebSocketMessage::LevelUp {
+                    player_id: award.player_id,
+                    level: award.level,
+                    total_xp: award.total_xp,
+                })
+                .await;
+        }
+
+        // Oyun bir turnuva turuysa puanlar turnuvaya eklenir ve turnuva sıralaması yayınlanır
+        tournament::record_round(pool, state, game_id, game_code).await;
+
+        // Sonuçlar gönderildi, oyun durumu artık bellekte tutulmaz
+        state.remove_game(game_code).await;
+
+        Ok(summary)
+    }
+
+    // Oyuncunun cevabını kaydet ve puanla
+    pub async fn submit_answer(
+        &self,
+        state: &AppState,
+        session_id: &str,
+        question_id: i32,
+        answer: &str,
+        response_time_ms: i32,
+    ) -> Result<AnswerResult, GameError> {
+        let player = self
+            .players
+            .find_active_by_session(session_id)
+            .await?
+            .ok_or(GameError::PlayerNotFound)?;
+
+        if player.status != "active" {
+            return Err(GameError::NotActive);
+        }
+
+        if !state.may_answer(&player.game_code, session_id).await {
+            return Err(GameError::NotFinalist);
+        }
+
+        // Soru bu oyunun soru listesinde olmalı ve şu anda gösteriliyor olmalı
+        let question = self
+            .questions
+            .answer_key(question_id, player.game_id)
+            .await?
+            .ok_or(GameError::QuestionNotFound)?;
+
+        let answer = answer.to_uppercase();
+        if !VALID_ANSWERS.contains(&answer.as_str()) {
+            return Err(GameError::InvalidAnswer);
+        }
+
+        // Süre dolduğunu bildiren "X" süre bitiminde gönderildiği için pencere kontrolünden muaftır
+        ensure_question_open(state, &player, question.position, session_id, answer == "X").await?;
+
+        // Aynı soruya ikinci cevap kabul edilmez (henüz yazılmamış ya da kaydedilmiş)
+        if has_pending_answer(player.game_id, player.id, question_id)
+            || self.players.has_answered(player.id, question_id).await?
+        {
+            return Err(GameError::AlreadyAnswered);
+        }
+
+        let is_correct = answer == question.correct_option;
+        // Ek süresi olan öğrencinin hız puanı, tanınan süreye göre hesaplanır
+        let accommodations = state.player_accommodations(&player.game_code, session_id).await;
+        let points = answer_points(is_correct, accommodations.scored_response_time(response_time_ms));
+
+        let queued = buffer_answer(player.game_id, NewAnswer {
+            player_id: player.id,
+            question_id,
+            answer: answer.clone(),
+            is_correct,
+            response_time_ms,
+            points,
+            answered_at: Utc::now(),
+        });
+        if !queued {
+            return Err(GameError::AlreadyAnswered);
+        }
+
+        if let Some(answer_window) = state.answer_window(&player.game_code, session_id).await {
+            anti_cheat::check_answer(self.pool, player.game_id, player.id, question_id, response_time_ms, answer_window).await;
+        }
+
+        let result = AnswerResult {
+            question_id,
+            answer,
+            is_correct,
+            response_time_ms,
+            points_earned: points,
+            correct_option: question.correct_option,
+        };
+
+        state.record_player_answer(&player.game_code, session_id, &result).await;
+
+        Ok(result)
+    }
+
+    // Oyuncunun açık sorudaki cevabını geri al (yanlışlıkla dokunma); soru süresi dolana kadar yeniden cevap verilebilir
+    pub async fn retract_answer(
+        &self,
+        state: &AppState,
+        session_id: &str,
+        question_id: i32,
+    ) -> Result<i32, GameError> {
+        let player = self
+            .players
+            .find_active_by_session(session_id)
+            .await?
+            .ok_or(GameError::PlayerNotFound)?;
+
+        if player.status != "active" {
+            return Err(GameError::NotActive);
+        }
+
+        let question = self
+            .questions
+            .answer_key(question_id, player.game_id)
+            .await?
+            .ok_or(GameError::QuestionNotFound)?;
+
+        // Süre dolduktan sonra (sonuçlar gösterilirken) cevap değiştirilemez
+        ensure_question_open(state, &player, question.position, session_id, false).await?;
+
+        // Cevap henüz yazılmadıysa bellekten, yazıldıysa veritabanından silinir
+        let removed = retract_pending_answer(player.game_id, player.id, question_id)
+            || self.players.retract_answer(player.id, question_id).await?;
+        if !removed {
+            return Err(GameError::NotAnswered);
+        }
+
+        state.clear_player_answer(&player.game_code, session_id, question_id).await;
+
+        Ok(question_id)
+    }
+}
+
+// Cevap verme ve geri alma için soru açık mı: soru oyuncuya şu anda gösterilen soru olmalı ve süresi (ek süresi
+// dahil) dolmamış olmalı. timed_out ise yalnızca sorunun gösteriliyor olması yeterlidir.
+async fn ensure_question_open(
+    state: &AppState,
+    player: &SessionPlayer,
+    question_position: i32,
+    session_id: &str,
+    timed_out: bool,
+) -> Result<(), GameError> {
+    if player.current_question != Some(question_position) {
+        return Err(GameError::QuestionNotActive);
+    }
+
+    if !timed_out && !state.answer_window_open(&player.game_code, session_id).await {
+        return Err(GameError::QuestionNotActive);
+    }
+
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::repositories::game::GameRecord;
+    use crate::db::repositories::mock::{MockGameRepo, MockPlayerRepo, MockQuestionRepo};
+    use crate::db::repositories::QuestionRecord;
+    use sqlx::postgres::PgPoolOptions;
+
+    // Bekleyen cevaplar süreç genelinde tutulduğundan her test kendi oyun kimliğini kullanır
+    fn answer(player_id: i32, question_id: i32) -> NewAnswer {
+        NewAnswer {
+            player_id,
+            question_id,
+            answer: "A".to_string(),
+            is_correct: true,
+            response_time_ms: 1500,
+            points: answer_points(true, 1500),
+            answered_at: Utc::now(),
+        }
+    }
+
+    fn pending_count(game_id: i32) -> usize {
+        let pending = PENDING_ANSWERS.lock().unwrap();
+        pending.get(&game_id).map_or(0, |a| a.queued.len() + a.in_flight.len())
+    }
+
+    #[tokio::test]
+    async fn test_flush_dedup() {
+        let game_id = 9001;
+        let players = MockPlayerRepo::default();
+
+        assert!(buffer_answer(game_id, answer(1, 10)));
+        assert!(!buffer_answer(game_id, answer(1, 10)), "aynı soruya ikinci cevap kuyruğa girmemeli");
+        assert!(buffer_answer(game_id, answer(2, 10)));
+
+        // Yazılmakta olan cevap için de ikinci cevap reddedilir
+        players.while_recording(move || {
+            assert!(has_pending_answer(game_id, 1, 10));
+            assert!(!buffer_answer(game_id, answer(1, 10)));
+        });
+        flush_answers_with(&players, game_id).await.unwrap();
+
+        assert_eq!(players.recorded(), vec![(1, 10), (2, 10)]);
+        assert_eq!(pending_count(game_id), 0);
+        assert!(!has_pending_answer(game_id, 1, 10));
+    }
+
+    #[tokio::test]
+    async fn test_flush_retries_failed_batch() {
+        let game_id = 9002;
+        let players = MockPlayerRepo::default();
+        players.fail_next_writes(FLUSH_ERROR_ATTEMPTS + 1);
+
+        buffer_answer(game_id, answer(1, 10));
+        buffer_answer(game_id, answer(2, 10));
+
+        // Yazılamayan cevaplar deneme sınırı aşılsa da atılmaz
+        for _ in 0..=FLUSH_ERROR_ATTEMPTS {
+            assert!(flush_answers_with(&players, game_id).await.is_err());
+            assert_eq!(pending_count(game_id), 2);
+        }
+
+        // Arada gelen cevap, geri konan cevapların arkasına yazılır
+        buffer_answer(game_id, answer(3, 10));
+        flush_answers_with(&players, game_id).await.unwrap();
+
+        assert_eq!(players.recorded(), vec![(1, 10), (2, 10), (3, 10)]);
+        assert_eq!(pending_count(game_id), 0);
+    }
+
+    #[tokio::test]
+    async fn test_retract_during_flush() {
+        let game_id = 9003;
+        let players = MockPlayerRepo::default();
+
+        buffer_answer(game_id, answer(1, 10));
+        buffer_answer(game_id, answer(2, 10));
+
+        // Kuyruktaki cevap doğrudan, yazılmakta olan cevap kayıt tamamlanınca silinir; geri alınan cevabın yerine
+        // yeni cevap verilebilir
+        players.while_recording(move || {
+            assert!(retract_pending_answer(game_id, 1, 10));
+            assert!(!has_pending_answer(game_id, 1, 10));
+            assert!(buffer_answer(game_id, answer(1, 10)));
+        });
+        flush_answers_with(&players, game_id).await.unwrap();
+        assert_eq!(players.recorded(), vec![(2, 10)]);
+
+        flush_answers_with(&players, game_id).await.unwrap();
+        assert_eq!(players.recorded(), vec![(2, 10), (1, 10)]);
+        assert_eq!(pending_count(game_id), 0);
+    }
+
+    #[tokio::test]
+    async fn test_retract_during_failed_flush() {
+        let game_id = 9004;
+        let players = MockPlayerRepo::default();
+        players.fail_next_writes(1);
+
+        buffer_answer(game_id, answer(1, 10));
+        buffer_answer(game_id, answer(2, 10));
+
+        // Yazma başarısız olursa geri alınan cevap kuyruğa geri konmaz
+        players.while_recording(move || {
+            assert!(retract_pending_answer(game_id, 1, 10));
+        });
+        assert!(flush_answers_with(&players, game_id).await.is_err());
+        flush_answers_with(&players, game_id).await.unwrap();
+
+        assert_eq!(players.recorded(), vec![(2, 10)]);
+    }
+
+    #[tokio::test]
+    async fn test_flush_waits_for_write_in_progress() {
+        let game_id = 9005;
+        let players = Arc::new(MockPlayerRepo::default());
+        let (started_tx, started_rx) = std::sync::mpsc::channel();
+        let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+
+        buffer_answer(game_id, answer(1, 10));
+        players.while_recording(move || {
+            started_tx.send(()).unwrap();
+            release_rx.recv().unwrap();
+        });
+
+        // İlk yazma ayrı bir iş parçacığında sürerken ikinci çağrı onu beklemeli, boş dönmemeli
+        let writer = {
+            let players = players.clone();
+            std::thread::spawn(move || {
+                tokio::runtime::Runtime::new()
+                    .unwrap()
+                    .block_on(flush_answers_with(&*players, game_id))
+            })
+        };
+        started_rx.recv().unwrap();
+
+        let waiting = {
+            let players = players.clone();
+            tokio::spawn(async move { flush_answers_with(&*players, game_id).await })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiting.is_finished(), "süren yazma bitmeden dönülmemeli");
+
+        release_tx.send(()).unwrap();
+        writer.join().unwrap().unwrap();
+        waiting.await.unwrap().unwrap();
+
+        assert_eq!(players.recorded(), vec![(1, 10)]);
+        assert!(!FLUSH_LOCKS.lock().unwrap().contains_key(&game_id));
+    }
+
+    // Sahte depolarla yalnızca veritabanına inmeyen yollar çalıştırılır; havuz hiç bağlanmaz
+    fn mock_service(pool: &Pool<Postgres>) -> GameService<'_, MockGameRepo, MockPlayerRepo, MockQuestionRepo> {
+        GameService {
+            pool,
+            games: MockGameRepo::default(),
+            players: MockPlayerRepo::default(),
+            questions: MockQuestionRepo::default(),
+        }
+    }
+
+    fn lazy_pool() -> Pool<Postgres> {
+        PgPoolOptions::new().connect_lazy("postgres://localhost/unused").unwrap()
+    }
+
+    fn game(id: i32, status: &str) -> GameRecord {
+        GameRecord {
+            id,
+            host_id: 1,
+            status: status.to_string(),
+            current_question: None,
+            question_set_id: 1,
+            option_labels: "letters".to_string(),
+        }
+    }
+
+    fn question(id: i32, position: i32) -> QuestionRecord {
+        QuestionRecord {
+            id,
+            question_text: format!("Soru {}", id),
+            options: ["a", "b", "c", "d"].map(str::to_string),
+            correct_option: "B".to_string(),
+            time_limit: Some(20),
+            position,
+            question_number: position as i64,
+            total_questions: 3,
+            host_notes: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_host_actions_check_host_and_status() {
+        let pool = lazy_pool();
+        let state = AppState::new(pool.clone());
+        let service = mock_service(&pool);
+        service.games.insert("LOBBY1", game(9006, "lobby"));
+        service.games.insert("ACTIVE", game(9007, "active"));
+
+        assert!(matches!(service.start_game(&state, "NOGAME", Some(1)).await, Err(GameError::GameNotFound)));
+        assert!(matches!(service.start_game(&state, "LOBBY1", Some(2)).await, Err(GameError::NotHost)));
+        assert!(matches!(service.start_game(&state, "LOBBY1", None).await, Err(GameError::NotHost)));
+        assert!(matches!(service.start_game(&state, "ACTIVE", Some(1)).await, Err(GameError::AlreadyStarted)));
+        assert!(matches!(service.next_question(&state, "LOBBY1", Some(1)).await, Err(GameError::NotActive)));
+        assert!(matches!(service.next_question(&state, "ACTIVE", Some(2)).await, Err(GameError::NotHost)));
+        assert!(matches!(
+            service.start_sudden_death(&state, "ACTIVE", Some(1), 1).await,
+            Err(GameError::InvalidFinalistCount)
+        ));
+
+        // Reddedilen istekler oyunun durumunu değiştirmez
+        assert_eq!(service.games.status(9006).as_deref(), Some("lobby"));
+    }
+
+    #[tokio::test]
+    async fn test_submit_answer_checks_playlist_and_duplicates() {
+        let game_id = 9008;
+        let pool = lazy_pool();
+        let state = AppState::new(pool.clone());
+        let service = mock_service(&pool);
+        service.players.players.lock().unwrap().push(SessionPlayer {
+            id: 5,
+            game_id,
+            game_code: "PLAY01".to_string(),
+            status: "active".to_string(),
+            current_question: Some(2),
+        });
+        service.questions.add(game_id, question(20, 2));
+        service.questions.add(game_id, question(30, 3));
+
+        assert!(matches!(service.submit_answer(&state, "6", 20, "B", 1000).await, Err(GameError::PlayerNotFound)));
+        // Oyunun soru listesinde olmayan soru
+        assert!(matches!(service.submit_answer(&state, "5", 99, "B", 1000).await, Err(GameError::QuestionNotFound)));
+        assert!(matches!(service.submit_answer(&state, "5", 20, "E", 1000).await, Err(GameError::InvalidAnswer)));
+        // Listede olan ama şu anda gösterilmeyen soru
+        assert!(matches!(service.submit_answer(&state, "5", 30, "X", 1000).await, Err(GameError::QuestionNotActive)));
+        // Soru zamanlayıcısı yoksa cevap penceresi kapalıdır; yalnızca süre doldu bildirimi kabul edilir
+        assert!(matches!(service.submit_answer(&state, "5", 20, "B", 1000).await, Err(GameError::QuestionNotActive)));
+        assert!(matches!(service.retract_answer(&state, "5", 20).await, Err(GameError::QuestionNotActive)));
+
+        let result = service.submit_answer(&state, "5", 20, "x", 20000).await.unwrap();
+        assert_eq!(result.answer, "X");
+        assert!(!result.is_correct);
+        assert_eq!(result.points_earned, 0);
+        assert_eq!(result.correct_option, "B");
+
+        // İkinci cevap hem yazılmayı beklerken hem de yazıldıktan sonra reddedilir
+        assert!(matches!(service.submit_answer(&state, "5", 20, "X", 20000).await, Err(GameError::AlreadyAnswered)));
+        flush_answers_with(&service.players, game_id).await.unwrap();
+        assert_eq!(service.players.recorded(), vec![(5, 20)]);
+        assert!(matches!(service.submit_answer(&state, "5", 20, "X", 20000).await, Err(GameError::AlreadyAnswered)));
+    }
+
+    fn result(answer: &str, correct_option: &str) -> AnswerResult {
+        let is_correct = answer == correct_option;
+        AnswerResult {
+            question_id: 7,
+            answer: answer.to_string(),
+            is_correct,
+            response_time_ms: 1500,
+            points_earned: answer_points(is_correct, 1500),
+            correct_option: correct_option.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_answer_receipt_hides_correctness() {
+        // Doğru ve yanlış cevabın alındı bildirimi yalnızca verilen şıkta farklıdır; geri alıp yeniden
+        // cevaplayan oyuncu hangi şıkkın doğru olduğunu bildirimden çıkaramaz
+        let mut wrong = json!(result("A", "B").receipt());
+        let mut right = json!(result("B", "B").receipt());
+        assert_eq!(wrong["type"], "answer_received");
+        assert_eq!(wrong["your_answer"], "A");
+        assert_eq!(right["your_answer"], "B");
+
+        for receipt in [&wrong, &right] {
+            for hidden in ["is_correct", "points_earned", "correct_option"] {
+                assert!(receipt.get(hidden).is_none(), "{} gönderilmemeli", hidden);
+            }
+        }
+
+        wrong["your_answer"] = json!(null);
+        right["your_answer"] = json!(null);
+        assert_eq!(wrong, right);
+    }
+
+    #[test]
+    fn test_answer_points_stays_in_range() {
+        // Negatif (saat kayması veya sahte) süreler en hızlı cevap sayılır, üst sınırı aşmaz
+        assert_eq!(answer_points(true, 0), MAX_ANSWER_POINTS);
+        assert_eq!(answer_points(true, -5000), MAX_ANSWER_POINTS);
+        assert_eq!(answer_points(true, i32::MIN), MAX_ANSWER_POINTS);
+        assert_eq!(answer_points(true, 10000), 100);
+        assert_eq!(answer_points(true, i32::MAX), 100);
+        assert_eq!(answer_points(false, -5000), 0);
+    }
+}
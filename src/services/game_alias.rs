@@ -0,0 +1,106 @@
+use sqlx::{Pool, Postgres};
+
+// Oyun kodu takma adları
+// Öğretmen oyuna sesli söylemesi kolay bir takma ad (ör. "BIO101-QUIZ3") verebilir; takma ad oyun koduyla
+// aynı katılım akışına çözülür. Takma adlar büyük/küçük harf duyarsızdır ve bitmemiş oyunlar arasında
+// kurum bazında benzersizdir, oyun bitince aynı ad tekrar kullanılabilir.
+
+pub const MIN_ALIAS_LEN: usize = 3;
+pub const MAX_ALIAS_LEN: usize = 32;
+
+// Kurum başına benzersizliği sağlayan indeks
+const ALIAS_UNIQUE_INDEX: &str = "idx_games_alias_active";
+
+// Takma adın çözümlenme sonucu
+#[derive(Debug, Clone, PartialEq)]
+pub enum Resolution {
+    Code(String),
+    // Aynı takma ad farklı kurumlarda kullanılıyor ve katılanın kurumu bunlardan biri değil
+    Ambiguous,
+    NotFound,
+}
+
+// Takma adı normalize et ve doğrula: büyük harfe çevrilir, yalnızca İngilizce harf, rakam, '-' ve '_' içerebilir.
+// Oyun kodlarıyla karışmaması için 6 karakterli harf/rakam dizileri kabul edilmez.
+pub fn normalize_alias(raw: &str) -> Result<String, String> {
+    let alias = raw.trim().to_ascii_uppercase();
+    let len = alias.chars().count();
+
+    if !(MIN_ALIAS_LEN..=MAX_ALIAS_LEN).contains(&len) {
+        return Err(format!("Takma ad {}-{} karakter olmalıdır", MIN_ALIAS_LEN, MAX_ALIAS_LEN));
+    }
+    if !alias.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err("Takma ad yalnızca harf, rakam, '-' ve '_' içerebilir".to_string());
+    }
+    if !alias.chars().any(|c| c.is_ascii_alphanumeric()) {
+        return Err("Takma ad en az bir harf veya rakam içermelidir".to_string());
+    }
+    if len == 6 && alias.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err("6 karakterli harf/rakam dizileri oyun koduyla karışır, '-' veya '_' ekleyin".to_string());
+    }
+
+    Ok(alias)
+}
+
+// Güncelleme hatası, takma adın kurumda başka bir oyunda kullanılmasından mı kaynaklanıyor
+pub fn is_alias_conflict(e: &sqlx::Error) -> bool {
+    e.as_database_error()
+        .and_then(|db_error| db_error.constraint())
+        .is_some_and(|constraint| constraint == ALIAS_UNIQUE_INDEX)
+}
+
+// Oyunun takma adını ayarla veya kaldır (None). Takma ad, ayarlayan hostun kurumuna bağlanır;
+// `alias` normalize_alias ile doğrulanmış olmalı
+pub async fn set_alias(
+    pool: &Pool<Postgres>,
+    game_id: i32,
+    organization_id: Option<i32>,
+    alias: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE games SET alias = $1, alias_organization_id = $2 WHERE id = $3",
+        alias,
+        alias.and(organization_id),
+        game_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Girilen oyun kodunu veya takma adı oyun koduna çevir. Takma ad birden fazla kurumda kullanılıyorsa
+// katılan kullanıcının kurumundaki oyun seçilir; misafirler için tek eşleşme olmalıdır.
+pub async fn resolve(pool: &Pool<Postgres>, input: &str, user_id: Option<i32>) -> Result<Resolution, sqlx::Error> {
+    let input = input.trim().to_ascii_uppercase();
+
+    let by_code = sqlx::query_scalar!("SELECT code FROM games WHERE code = $1", input)
+        .fetch_optional(pool)
+        .await?;
+    if let Some(code) = by_code {
+        return Ok(Resolution::Code(code));
+    }
+
+    let candidates = sqlx::query!(
+        r#"
+        SELECT g.code,
+               g.alias_organization_id IS NOT DISTINCT FROM
+                   (SELECT organization_id FROM users WHERE id = $2) AND $2::int IS NOT NULL as "same_organization!"
+        FROM games g
+        WHERE g.alias = $1 AND g.status <> 'completed'
+        ORDER BY 2 DESC
+        LIMIT 2
+        "#,
+        input,
+        user_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(match candidates.as_slice() {
+        [] => Resolution::NotFound,
+        [only] => Resolution::Code(only.code.clone()),
+        [first, ..] if first.same_organization => Resolution::Code(first.code.clone()),
+        _ => Resolution::Ambiguous,
+    })
+}
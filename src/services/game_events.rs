@@ -0,0 +1,119 @@
+use chrono::{DateTime, Utc};
+use log::error;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+
+// Oyun olay kaydı (event log)
+// Oyun boyunca gerçekleşen önemli olaylar sıra numarasıyla birlikte saklanır,
+// böylece oyunlar sonradan adım adım yeniden oynatılabilir.
+
+// Kaydedilen olay tipleri
+pub const EVENT_PLAYER_JOINED: &str = "player_joined";
+pub const EVENT_GAME_STARTED: &str = "game_started";
+pub const EVENT_QUESTION_START: &str = "question_start";
+pub const EVENT_QUESTION_END: &str = "question_end";
+pub const EVENT_GAME_END: &str = "game_end";
+pub const EVENT_SCENE: &str = "scene";
+pub const EVENT_SUDDEN_DEATH_START: &str = "sudden_death_start";
+pub const EVENT_SUDDEN_DEATH_ELIMINATION: &str = "sudden_death_elimination";
+pub const EVENT_TIMER_EXTENDED: &str = "timer_extended";
+
+// Veritabanındaki olay kaydı
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GameEvent {
+    pub sequence: i32,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+impl GameEvent {
+    // Olayı canlı yayındaki mesaj biçiminde istemciye gönderilecek hale getir
+    pub fn to_message(&self) -> serde_json::Value {
+        let mut message = match &self.payload {
+            serde_json::Value::Object(fields) => serde_json::Value::Object(fields.clone()),
+            _ => serde_json::json!({}),
+        };
+        message["type"] = serde_json::json!(self.event_type);
+        message["seq"] = serde_json::json!(self.sequence);
+        message["replayed"] = serde_json::json!(true);
+        message
+    }
+}
+
+// Oyun için yeni bir olay kaydet, atanan sıra numarasını döndür
+pub async fn record_event(
+    pool: &Pool<Postgres>,
+    game_id: i32,
+    event_type: &str,
+    payload: serde_json::Value,
+) -> Option<i32> {
+    // Sıra numarası oyunun sayacı artırılarak atanır; satır kilidi sayesinde eşzamanlı
+    // kayıtlar aynı numarayı alamaz
+    let result = sqlx::query!(
+        r#"
+        WITH next AS (
+            UPDATE games SET event_seq = event_seq + 1
+            WHERE id = $1
+            RETURNING event_seq
+        )
+        INSERT INTO game_events (game_id, sequence, event_type, payload, created_at)
+        SELECT $1, event_seq, $2, $3, $4
+        FROM next
+        RETURNING sequence
+        "#,
+        game_id,
+        event_type,
+        payload,
+        Utc::now()
+    )
+    .fetch_one(pool)
+    .await;
+
+    match result {
+        Ok(record) => Some(record.sequence),
+        Err(e) => {
+            // Olay kaydı oyunun akışını engellememeli
+            error!("Oyun olayı kaydedilemedi (game_id={}, type={}): {}", game_id, event_type, e);
+            None
+        }
+    }
+}
+
+// Oyunun tüm olaylarını sırasıyla getir
+pub async fn get_events(
+    pool: &Pool<Postgres>,
+    game_id: i32,
+) -> Result<Vec<GameEvent>, sqlx::Error> {
+    get_events_after(pool, game_id, 0).await
+}
+
+// Verilen sıra numarasından sonraki olayları getir (yeniden bağlanan istemcinin kaçırdıkları)
+pub async fn get_events_after(
+    pool: &Pool<Postgres>,
+    game_id: i32,
+    after_sequence: i32,
+) -> Result<Vec<GameEvent>, sqlx::Error> {
+    let events = sqlx::query!(
+        r#"
+        SELECT sequence, event_type, payload, created_at as "created_at!"
+        FROM game_events
+        WHERE game_id = $1 AND sequence > $2
+        ORDER BY sequence
+        "#,
+        game_id,
+        after_sequence
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(events
+        .into_iter()
+        .map(|e| GameEvent {
+            sequence: e.sequence,
+            event_type: e.event_type,
+            payload: e.payload,
+            created_at: e.created_at,
+        })
+        .collect())
+}
@@ -0,0 +1,225 @@
+use chrono::{Duration, Utc};
+use log::{error, info};
+use sqlx::{Pool, Postgres};
+
+use crate::services::notifications::{self, KIND_CHALLENGE, KIND_GAME_REMINDER};
+use crate::services::push::PushService;
+
+// Oyun bildirimleri: planlanan oyunların hatırlatmaları ve meydan okuma davetleri
+// Her bildirim uygulama içi bildirim kutusuna yazılır ve kullanıcının kayıtlı cihazlarına push olarak gönderilir.
+
+// Hatırlatma zamanı gelen oyunları kontrol etme aralığı
+pub const REMINDER_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+// Planlanan başlangıçtan bu kadar önce hatırlatma gönderilir
+const REMINDER_LEAD_MINUTES: i64 = 15;
+
+#[derive(Debug)]
+pub enum ChallengeError {
+    GameNotFound,
+    NotParticipant,
+    NotInLobby,
+    UserNotFound,
+    SelfChallenge,
+    AlreadyChallenged,
+    Database,
+}
+
+impl ChallengeError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            ChallengeError::GameNotFound => "Oyun bulunamadı",
+            ChallengeError::NotParticipant => "Yalnızca oyunun sahibi veya oyuncuları davet gönderebilir",
+            ChallengeError::NotInLobby => "Yalnızca başlamamış oyunlara davet gönderilebilir",
+            ChallengeError::UserNotFound => "Kullanıcı bulunamadı",
+            ChallengeError::SelfChallenge => "Kendinize davet gönderemezsiniz",
+            ChallengeError::AlreadyChallenged => "Bu kullanıcıyı bu oyuna zaten davet ettiniz",
+            ChallengeError::Database => "Davet gönderilemedi",
+        }
+    }
+}
+
+impl From<sqlx::Error> for ChallengeError {
+    fn from(e: sqlx::Error) -> Self {
+        error!("Meydan okuma daveti veritabanı hatası: {}", e);
+        ChallengeError::Database
+    }
+}
+
+// Başlangıcı yaklaşan planlı oyunlar için hosta ve bağlı sınıfın öğrencilerine hatırlatma gönder
+pub async fn send_due_reminders(pool: &Pool<Postgres>) {
+    let now = Utc::now();
+
+    // Hatırlatma işaretlenerek alınır; birden fazla sunucu aynı oyunu iki kez hatırlatmaz.
+    // Başlangıç saati geçmiş oyunlar için hatırlatma gönderilmez.
+    let due = sqlx::query!(
+        r#"
+        UPDATE games g SET reminder_sent_at = $1
+        FROM question_sets qs
+        WHERE qs.id = g.question_set_id
+          AND g.status = 'lobby'
+          AND g.reminder_sent_at IS NULL
+          AND g.scheduled_at > $1
+          AND g.scheduled_at <= $2
+        RETURNING g.id, g.code, g.host_id, g.roster_class_id, g.scheduled_at as "scheduled_at!", qs.title
+        "#,
+        now,
+        now + Duration::minutes(REMINDER_LEAD_MINUTES)
+    )
+    .fetch_all(pool)
+    .await;
+
+    let due = match due {
+        Ok(due) => due,
+        Err(e) => {
+            error!("Planlı oyun hatırlatmaları alınamadı: {}", e);
+            return;
+        }
+    };
+
+    let push = PushService::new();
+
+    for game in due {
+        let recipients = match sqlx::query_scalar!(
+            r#"
+            SELECT $1::int as "user_id!"
+            UNION
+            SELECT user_id FROM class_members WHERE class_id = $2
+            "#,
+            game.host_id,
+            game.roster_class_id
+        )
+        .fetch_all(pool)
+        .await
+        {
+            Ok(recipients) => recipients,
+            Err(e) => {
+                error!("Hatırlatma alıcıları alınamadı (game_id={}): {}", game.id, e);
+                continue;
+            }
+        };
+
+        let minutes = (game.scheduled_at - now).num_minutes().max(1);
+        let title = "Oyun hatırlatması";
+        let body = format!(
+            "\"{}\" oyunu {} dakika içinde başlıyor. Oyun kodu: {}",
+            game.title, minutes, game.code
+        );
+        let data = serde_json::json!({
+            "game_id": game.id,
+            "game_code": game.code,
+            "scheduled_at": game.scheduled_at
+        });
+
+        for user_id in &recipients {
+            notifications::notify(pool, *user_id, KIND_GAME_REMINDER, title, &body, data.clone()).await;
+            push.send_to_user(
+                pool,
+                *user_id,
+                title,
+                &body,
+                serde_json::json!({"type": KIND_GAME_REMINDER, "game_code": game.code}),
+            )
+            .await;
+        }
+
+        info!("Planlı oyun hatırlatması gönderildi: code={}, alıcı={}", game.code, recipients.len());
+    }
+}
+
+// Başka bir kullanıcıyı lobideki oyuna davet et; davet eden oyunun sahibi veya aktif oyuncusu olmalı
+pub async fn send_challenge(
+    pool: &Pool<Postgres>,
+    game_code: &str,
+    sender_id: i32,
+    target_username: &str,
+) -> Result<(), ChallengeError> {
+    let game = sqlx::query!(
+        r#"
+        SELECT g.id, g.status, qs.title, u.username as sender_username,
+               (g.host_id = $2 OR EXISTS (
+                   SELECT 1 FROM players p WHERE p.game_id = g.id AND p.user_id = $2 AND p.is_active = true
+               )) as "is_participant!"
+        FROM games g
+        JOIN question_sets qs ON g.question_set_id = qs.id
+        JOIN users u ON u.id = $2
+        WHERE g.code = $1
+        "#,
+        game_code,
+        sender_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(ChallengeError::GameNotFound)?;
+
+    if !game.is_participant {
+        return Err(ChallengeError::NotParticipant);
+    }
+    if game.status != "lobby" {
+        return Err(ChallengeError::NotInLobby);
+    }
+
+    let target_id = sqlx::query_scalar!(
+        "SELECT id FROM users WHERE LOWER(username) = LOWER($1)",
+        target_username
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(ChallengeError::UserNotFound)?;
+
+    if target_id == sender_id {
+        return Err(ChallengeError::SelfChallenge);
+    }
+
+    let title = "Meydan okuma";
+    let body = format!(
+        "{} sizi \"{}\" oyununa davet ediyor. Oyun kodu: {}",
+        game.sender_username, game.title, game_code
+    );
+
+    // Aynı kullanıcı aynı oyuna aynı kişiden yalnızca bir davet alır
+    let inserted = sqlx::query!(
+        r#"
+        INSERT INTO notifications (user_id, kind, title, body, data, created_at)
+        SELECT $1, $2::varchar, $3, $4, $5, $6
+        WHERE NOT EXISTS (
+            SELECT 1 FROM notifications
+            WHERE user_id = $1 AND kind = $2::varchar
+              AND (data->>'game_id')::int = $7
+              AND (data->>'from_user_id')::int = $8
+        )
+        "#,
+        target_id,
+        KIND_CHALLENGE,
+        title,
+        body,
+        serde_json::json!({
+            "game_id": game.id,
+            "game_code": game_code,
+            "from_user_id": sender_id,
+            "from_username": game.sender_username
+        }),
+        Utc::now(),
+        game.id,
+        sender_id
+    )
+    .execute(pool)
+    .await?;
+
+    if inserted.rows_affected() == 0 {
+        return Err(ChallengeError::AlreadyChallenged);
+    }
+
+    PushService::new()
+        .send_to_user(
+            pool,
+            target_id,
+            title,
+            &body,
+            serde_json::json!({"type": KIND_CHALLENGE, "game_code": game_code}),
+        )
+        .await;
+
+    info!("Meydan okuma daveti gönderildi: code={}, from={}, to={}", game_code, sender_id, target_id);
+    Ok(())
+}
@@ -0,0 +1,167 @@
+use sqlx::{Pool, Postgres};
+
+use crate::services::question_sample::QuestionSample;
+
+// Oyunun soru listesi
+// Oyunda varsayılan olarak ana setin (games.question_set_id) tüm soruları setteki sırasıyla sorulur. Öğretmen
+// oyuna başka setler de eklediyse (ör. bu haftanın konusuna bir tekrar seti) veya rastgele soru seçimi
+// ayarladıysa liste oyun başlarken oluşturulur: setlerin soruları setlerin eklenme sırasıyla birleştirilir, aynı
+// soru birden fazla sette varsa ilk geçtiği yer korunur, ardından seçim ayarı uygulanır. Liste game_questions
+// tablosuna oyundaki sırasıyla kaydedilir; oyun akışı ve istatistikler bu listeye göre çalışır (bkz. game_playlist).
+// Liste her oyun için kaydedilir, böylece oyun başladıktan sonra silinen sorular oyunun sonuçlarından düşmez.
+
+// Ana setle birlikte bir oyunda birleştirilebilecek en fazla soru seti
+pub const MAX_POOLED_SETS: usize = 10;
+
+// Ana setten sonra oyuna eklenen setleri sırasıyla kaydet
+pub async fn add_pooled_sets(pool: &Pool<Postgres>, game_id: i32, set_ids: &[i32]) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO game_question_sets (game_id, question_set_id, position)
+        SELECT $1, set_id, ord::int FROM UNNEST($2::int[]) WITH ORDINALITY AS s(set_id, ord)
+        ON CONFLICT DO NOTHING
+        "#,
+        game_id,
+        set_ids
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Oyuna ana setten sonra eklenen setler, eklenme sırasıyla
+pub async fn pooled_set_ids(pool: &Pool<Postgres>, game_id: i32) -> Result<Vec<i32>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT question_set_id FROM game_question_sets WHERE game_id = $1 ORDER BY position",
+        game_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+// Oyunda sorulacak soru sayısı. Liste henüz oluşturulmadıysa (lobi) tüm setlerin soruları sayılır ve seçim
+// ayarıyla sınırlanır; setler arasında tekrar eden sorular liste oluşturulunca düşer.
+pub async fn question_count(pool: &Pool<Postgres>, game_id: i32) -> Result<i64, sqlx::Error> {
+    let counts = sqlx::query!(
+        r#"
+        SELECT g.question_sample,
+               EXISTS(SELECT 1 FROM game_questions WHERE game_id = g.id) as "resolved!",
+               (SELECT COUNT(*) FROM game_question_ids(g.id)) as "listed!",
+               (SELECT COUNT(*) FROM questions q
+                WHERE q.deleted_at IS NULL
+                  AND (q.question_set_id = g.question_set_id
+                       OR q.question_set_id IN (SELECT question_set_id FROM game_question_sets WHERE game_id = g.id))
+               ) as "pooled!"
+        FROM games g WHERE g.id = $1
+        "#,
+        game_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    if counts.resolved {
+        return Ok(counts.listed);
+    }
+
+    let sample = counts
+        .question_sample
+        .and_then(|value| serde_json::from_value::<QuestionSample>(value).ok());
+    Ok(match sample {
+        Some(sample) => counts.pooled.min(sample.count as i64),
+        None => counts.pooled,
+    })
+}
+
+// Oyunun soru listesini oluştur ve kaydet; oyuna başka set eklenmediyse ve seçim ayarı yoksa ana setin tüm
+// soruları setteki sıralarıyla kaydedilir. Seçim tabakalandırılıyorsa her gruptaki sorular rastgele sıralanır ve grubun
+// büyüklüğüne oranlanan sıraya göre birleştirilir, böylece ilk N soru grupların paylarını korur. Seçilen sorular
+// yine setlerin ve setlerdeki soruların sırasıyla sorulur.
+pub async fn resolve(pool: &Pool<Postgres>, game_id: i32) -> Result<(), sqlx::Error> {
+    let game = sqlx::query!(
+        r#"
+        SELECT question_sample,
+               EXISTS(SELECT 1 FROM game_question_sets WHERE game_id = $1) as "pooled!"
+        FROM games WHERE id = $1
+        "#,
+        game_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let sample = game
+        .question_sample
+        .and_then(|value| serde_json::from_value::<QuestionSample>(value).ok());
+    if sample.is_none() && !game.pooled {
+        sqlx::query!(
+            r#"
+            INSERT INTO game_questions (game_id, question_id, position)
+            SELECT g.id, q.id, q.position
+            FROM games g
+            JOIN questions q ON q.question_set_id = g.question_set_id AND q.deleted_at IS NULL
+            WHERE g.id = $1
+            ON CONFLICT DO NOTHING
+            "#,
+            game_id
+        )
+        .execute(pool)
+        .await?;
+
+        return Ok(());
+    }
+
+    sqlx::query!(
+        r#"
+        WITH sets AS (
+            SELECT g.question_set_id as set_id, 0 as set_order FROM games g WHERE g.id = $1
+            UNION ALL
+            SELECT gs.question_set_id, gs.position FROM game_question_sets gs WHERE gs.game_id = $1
+        ),
+        pooled AS (
+            SELECT DISTINCT ON (LOWER(BTRIM(q.question_text)), LOWER(BTRIM(q.option_a)), LOWER(BTRIM(q.option_b)),
+                                LOWER(BTRIM(q.option_c)), LOWER(BTRIM(q.option_d)), q.correct_option)
+                   q.id, s.set_order, q.position
+            FROM sets s
+            JOIN questions q ON q.question_set_id = s.set_id AND q.deleted_at IS NULL
+            ORDER BY LOWER(BTRIM(q.question_text)), LOWER(BTRIM(q.option_a)), LOWER(BTRIM(q.option_b)),
+                     LOWER(BTRIM(q.option_c)), LOWER(BTRIM(q.option_d)), q.correct_option, s.set_order, q.position
+        ),
+        candidates AS (
+            SELECT p.id, p.set_order, p.position,
+                   CASE $3
+                       WHEN 'topic' THEN COALESCE(
+                           (SELECT MIN(qt.topic_id) FROM question_topics qt WHERE qt.question_id = p.id), 0)
+                       WHEN 'difficulty' THEN (
+                           SELECT CASE WHEN COUNT(pa.id) = 0 THEN 1 ELSE LEAST(FLOOR((
+                               (1 - COUNT(pa.id) FILTER (WHERE pa.is_correct)::float8 / COUNT(pa.id)) * 0.7
+                               + LEAST(COALESCE(AVG(pa.response_time_ms)::float8, 5000) / 10000, 1) * 0.3
+                           ) * 3), 2)::int END
+                           FROM player_answers pa WHERE pa.question_id = p.id)
+                       ELSE 0
+                   END as stratum
+            FROM pooled p
+        ),
+        ranked AS (
+            SELECT id, set_order, position,
+                   (ROW_NUMBER() OVER (PARTITION BY stratum ORDER BY random()) - 0.5)
+                       / COUNT(*) OVER (PARTITION BY stratum) as share
+            FROM candidates
+        ),
+        selected AS (
+            SELECT id, set_order, position FROM ranked
+            ORDER BY share, random()
+            LIMIT $2
+        )
+        INSERT INTO game_questions (game_id, question_id, position)
+        SELECT $1, id, (ROW_NUMBER() OVER (ORDER BY set_order, position))::int FROM selected
+        ON CONFLICT DO NOTHING
+        "#,
+        game_id,
+        sample.as_ref().map(|s| s.count as i64),
+        sample.map(|s| s.stratify_by).unwrap_or_default().as_str()
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
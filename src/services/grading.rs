@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+
+use crate::db::repositories::read_pool;
+use crate::services::game::{max_possible_score, percent_of_max};
+use crate::utils::csv;
+
+// Notlandırma
+// Öğretmen oyun için bir not ölçeği seçer: puanın en yüksek olası puana oranı (yüzde) ya da puan veya
+// yüzde eşiklerine göre harf notları (ör. 85+ AA). Oyun bittiğinde kayıtlı her öğrencinin notu bu ölçeğe
+// göre hesaplanır. Sonuçlar LMS not aktarımının (LTI AGS) beklediği alanlarla (verilen puan, en yüksek puan,
+// kullanıcı e-postası) döndürülür ve CSV olarak da alınabilir. Misafir oyuncular notlandırılmaz.
+
+// Bir ölçekteki en fazla not bandı
+const MAX_BANDS: usize = 20;
+
+// Not adının en fazla uzunluğu
+const MAX_GRADE_LEN: usize = 20;
+
+// Bant eşiklerinin neye göre karşılaştırılacağı
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BandBasis {
+    #[default]
+    Percent,
+    Score,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GradeBand {
+    pub min: f64,
+    pub grade: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(tag = "scale", rename_all = "snake_case")]
+pub enum GradingScale {
+    // Not, en yüksek olası puanın yüzdesidir
+    #[default]
+    Percent,
+    // Not, eşiği aşılan en yüksek banttır
+    Bands {
+        #[serde(default)]
+        basis: BandBasis,
+        bands: Vec<GradeBand>,
+    },
+}
+
+impl GradingScale {
+    // Ayarlardan gelen ölçeği doğrula; bantlar eşiğe göre büyükten küçüğe sıralanır
+    pub fn from_settings(value: &serde_json::Value) -> Result<Self, &'static str> {
+        let scale: GradingScale = serde_json::from_value(value.clone())
+            .map_err(|_| "Not ölçeği {\"scale\": \"percent\"} veya {\"scale\": \"bands\", \"bands\": [...]} biçiminde olmalıdır")?;
+
+        match scale {
+            GradingScale::Percent => Ok(GradingScale::Percent),
+            GradingScale::Bands { basis, mut bands } => {
+                if bands.is_empty() || bands.len() > MAX_BANDS {
+                    return Err("Not ölçeği 1-20 bant içermelidir");
+                }
+                for band in &mut bands {
+                    band.grade = band.grade.trim().to_string();
+                    if band.grade.is_empty() || band.grade.chars().count() > MAX_GRADE_LEN {
+                        return Err("Not adı 1-20 karakter arasında olmalıdır");
+                    }
+                    if !band.min.is_finite() || band.min < 0.0 || (basis == BandBasis::Percent && band.min > 100.0) {
+                        return Err("Bant eşiği 0 veya daha büyük olmalı, yüzde eşikleri 100'ü geçmemelidir");
+                    }
+                }
+
+                bands.sort_by(|a, b| b.min.total_cmp(&a.min));
+                if bands.windows(2).any(|pair| pair[0].min == pair[1].min) {
+                    return Err("Aynı eşiğe sahip birden fazla bant olamaz");
+                }
+                // Her puanın bir nota karşılık gelmesi için en düşük bant 0'dan başlamalı
+                if bands.last().is_some_and(|band| band.min != 0.0) {
+                    return Err("En düşük bandın eşiği 0 olmalıdır");
+                }
+
+                Ok(GradingScale::Bands { basis, bands })
+            }
+        }
+    }
+
+    // Veritabanındaki ayarı oku (bozuk ayar varsayılan ölçeğe düşer)
+    pub fn from_stored(value: &serde_json::Value) -> Self {
+        serde_json::from_value(value.clone()).unwrap_or_default()
+    }
+
+    pub fn to_value(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    pub fn grade(&self, score: i32, percent: f64) -> String {
+        match self {
+            GradingScale::Percent => format!("{:.1}", percent),
+            GradingScale::Bands { basis, bands } => {
+                let value = match basis {
+                    BandBasis::Percent => percent,
+                    BandBasis::Score => score as f64,
+                };
+                bands
+                    .iter()
+                    .find(|band| value >= band.min)
+                    .or(bands.last())
+                    .map(|band| band.grade.clone())
+                    .unwrap_or_default()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StudentGrade {
+    pub player_id: i32,
+    pub user_id: i32,
+    pub nickname: String,
+    pub email: String,
+    pub score_given: i32,
+    pub score_maximum: i64,
+    pub percent: f64,
+    pub grade: String,
+    pub correct_count: i64,
+}
+
+// Oyunun kayıtlı oyuncularının notları (puana göre); notlar biten oyunlar için hesaplandığından replikadan okunur
+pub async fn game_grades(
+    pool: &Pool<Postgres>,
+    game_id: i32,
+    question_count: i64,
+    scale: &GradingScale,
+) -> Result<Vec<StudentGrade>, sqlx::Error> {
+    let pool = read_pool(pool);
+    let score_maximum = max_possible_score(question_count);
+
+    let players = sqlx::query!(
+        r#"
+        SELECT p.id, p.user_id as "user_id!", p.nickname, p.score, u.email,
+               COUNT(pa.id) FILTER (WHERE pa.is_correct) as "correct_count!"
+        FROM players p
+        JOIN users u ON p.user_id = u.id
+        LEFT JOIN player_answers pa ON pa.player_id = p.id
+        WHERE p.game_id = $1
+        GROUP BY p.id, u.email
+        ORDER BY p.score DESC NULLS LAST, p.nickname
+        "#,
+        game_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(players
+        .into_iter()
+        .map(|p| {
+            let score = p.score.unwrap_or(0).max(0);
+            let percent = percent_of_max(score as i64, score_maximum);
+            StudentGrade {
+                player_id: p.id,
+                user_id: p.user_id,
+                nickname: p.nickname,
+                email: p.email,
+                score_given: score,
+                score_maximum,
+                percent,
+                grade: scale.grade(score, percent),
+                correct_count: p.correct_count,
+            }
+        })
+        .collect())
+}
+
+// Notları LMS'lerin not içe aktarma ekranlarına uygun, öğrenci başına bir satırlık CSV'ye dönüştür
+pub fn to_csv(grades: &[StudentGrade]) -> String {
+    let mut lines = vec!["Öğrenci,E-posta,Puan,En Yüksek Puan,Yüzde,Not,Doğru Sayısı".to_string()];
+    for g in grades {
+        lines.push(format!(
+            "{},{},{},{},{:.1},{},{}",
+            csv::field(&g.nickname),
+            csv::field(&g.email),
+            g.score_given,
+            g.score_maximum,
+            g.percent,
+            csv::field(&g.grade),
+            g.correct_count
+        ));
+    }
+
+    csv::join_lines(&lines)
+}
\ No newline at end of file
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+
+// Aynı IP adresinden katılan misafir oyuncuların tespiti
+// IP adresleri hashlenmiş olarak saklanır; öğretmene yalnızca takma ad grupları gösterilir.
+
+// Aynı IP adresini paylaşan misafir grubu
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SharedIpGroup {
+    pub guest_count: i64,
+    pub nicknames: Vec<String>,
+}
+
+// Oyunun IP sınırı doluysa sınırı döndür (yalnızca rekabetçi oyunlarda ve sınır tanımlıysa)
+pub async fn guest_limit_reached(
+    pool: &Pool<Postgres>,
+    game_id: i32,
+    ip_hash: &str,
+) -> Result<Option<i32>, sqlx::Error> {
+    let record = sqlx::query!(
+        r#"
+        SELECT g.max_guests_per_ip as "limit!",
+               (SELECT COUNT(*) FROM players p
+                WHERE p.game_id = g.id AND p.user_id IS NULL AND p.is_active = true AND p.ip_hash = $2) as "guests!"
+        FROM games g
+        WHERE g.id = $1 AND g.is_competitive = true AND g.max_guests_per_ip IS NOT NULL
+        "#,
+        game_id,
+        ip_hash
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(record.filter(|r| r.guests >= r.limit as i64).map(|r| r.limit))
+}
+
+// Rekabetçi oyunda aynı IP adresini paylaşan misafir grupları
+pub async fn shared_ip_warnings(
+    pool: &Pool<Postgres>,
+    game_id: i32,
+) -> Result<Vec<SharedIpGroup>, sqlx::Error> {
+    let groups = sqlx::query!(
+        r#"
+        SELECT COUNT(*) as "guest_count!", array_agg(p.nickname ORDER BY p.joined_at) as "nicknames!"
+        FROM players p
+        JOIN games g ON p.game_id = g.id
+        WHERE p.game_id = $1 AND g.is_competitive = true
+          AND p.user_id IS NULL AND p.is_active = true AND p.ip_hash IS NOT NULL
+        GROUP BY p.ip_hash
+        HAVING COUNT(*) > 1
+        ORDER BY COUNT(*) DESC
+        "#,
+        game_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(groups
+        .into_iter()
+        .map(|g| SharedIpGroup {
+            guest_count: g.guest_count,
+            nicknames: g.nicknames,
+        })
+        .collect())
+}
\ No newline at end of file
@@ -0,0 +1,123 @@
+use chrono::{Duration, Utc};
+use log::info;
+use sqlx::{Pool, Postgres};
+
+use crate::config::CONFIG;
+use crate::utils::security::PlayerToken;
+
+// Misafir oturumları
+// Misafir oyuncular yalnızca oturum kimlikleriyle tanınır. Her misafir oturumu tek bir oyuncu ve oyunla sınırlıdır
+// ve GUEST_SESSION_TTL_HOURS sonra geçerliliğini yitirir; yeniden bağlanan misafirin eski oturumu iptal edilip yeni
+// oturum açılır. Geçerli bir misafir oturumu olmayan oyuncu yeniden bağlanamaz ve oyuncu tokenıyla cevap
+// gönderemez. Süresi dolan, iptal edilen ve bitmiş oyunlara ait oturumlar düzenli olarak silinir.
+
+// Misafir oyuncu için oturum aç
+pub async fn open(pool: &Pool<Postgres>, player_id: i32, game_id: i32, session_id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO guest_sessions (player_id, game_id, session_id, expires_at)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (session_id) DO NOTHING
+        "#,
+        player_id,
+        game_id,
+        session_id,
+        Utc::now() + Duration::hours(CONFIG.guest_session_ttl_hours)
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Oturum geçerli bir misafir oturumu mu (iptal edilmemiş, süresi dolmamış)
+pub async fn is_active(pool: &Pool<Postgres>, player_id: i32, session_id: &str) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM guest_sessions
+            WHERE player_id = $1 AND session_id = $2 AND revoked_at IS NULL AND expires_at > NOW()
+        ) as "active!"
+        "#,
+        player_id,
+        session_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
+// İmzalı oyuncu tokenının sahibinin güncel oturum kimliği. Token oyuncunun güncel oturumuyla imzalanmış olmalı;
+// yeniden bağlanmadan önce alınan tokenlar reddedilir. Misafirlerin oturumu ayrıca geçerli bir misafir oturumu
+// olmalıdır. Geçersiz tokenda None döner.
+pub async fn player_session(pool: &Pool<Postgres>, token: &PlayerToken) -> Result<Option<String>, sqlx::Error> {
+    let player = sqlx::query!(
+        r#"
+        SELECT p.session_id,
+               p.user_id IS NOT NULL OR EXISTS (
+                   SELECT 1 FROM guest_sessions gs
+                   WHERE gs.player_id = p.id AND gs.session_id = p.session_id
+                     AND gs.revoked_at IS NULL AND gs.expires_at > NOW()
+               ) as "session_active!"
+        FROM players p
+        WHERE p.id = $1 AND p.game_id = $2
+        "#,
+        token.player_id,
+        token.game_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(player
+        .filter(|player| player.session_active && token.verify(&player.session_id))
+        .map(|player| player.session_id))
+}
+
+// Yeniden bağlanan misafirin eski oturumunu iptal et ve yeni oturumu aynı son kullanma zamanıyla aç; yeniden
+// bağlanmak oturumun süresini uzatmaz
+pub async fn rotate(
+    pool: &Pool<Postgres>,
+    player_id: i32,
+    old_session_id: &str,
+    new_session_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        WITH revoked AS (
+            UPDATE guest_sessions SET revoked_at = NOW()
+            WHERE player_id = $1 AND session_id = $2 AND revoked_at IS NULL
+            RETURNING game_id, expires_at
+        )
+        INSERT INTO guest_sessions (player_id, game_id, session_id, expires_at)
+        SELECT $1, game_id, $3, expires_at FROM revoked
+        ON CONFLICT (session_id) DO NOTHING
+        "#,
+        player_id,
+        old_session_id,
+        new_session_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Süresi dolan, iptal edilen ve bitmiş oyunlara ait misafir oturumlarını sil, silinen oturum sayısını döndür
+pub async fn purge_expired(pool: &Pool<Postgres>) -> Result<u64, sqlx::Error> {
+    let purged = sqlx::query!(
+        r#"
+        DELETE FROM guest_sessions gs
+        WHERE gs.expires_at <= NOW()
+           OR gs.revoked_at IS NOT NULL
+           OR EXISTS (SELECT 1 FROM games g WHERE g.id = gs.game_id AND g.status = 'completed')
+        "#
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if purged > 0 {
+        info!("{} misafir oturumu silindi", purged);
+    }
+
+    Ok(purged)
+}
@@ -0,0 +1,67 @@
+use sqlx::{Pool, Postgres, Transaction};
+
+// Öğrenci davet kodları
+
+// Kullanılabilir durumdaki davet
+#[derive(Debug, Clone)]
+pub struct ValidInvitation {
+    pub id: i32,
+    pub teacher_id: i32,
+    pub teacher_username: String,
+    pub class_id: Option<i32>,
+    pub class_name: Option<String>,
+    pub organization_id: Option<i32>,
+}
+
+// Kodu geçerli (iptal edilmemiş, süresi dolmamış, kullanım hakkı kalan) daveti getir
+pub async fn find_valid(
+    pool: &Pool<Postgres>,
+    code: &str,
+) -> Result<Option<ValidInvitation>, sqlx::Error> {
+    let record = sqlx::query!(
+        r#"
+        SELECT i.id, i.teacher_id, u.username as teacher_username, i.class_id,
+               c.name as "class_name?", u.organization_id
+        FROM invitations i
+        JOIN users u ON i.teacher_id = u.id
+        LEFT JOIN classes c ON i.class_id = c.id
+        WHERE i.code = $1
+          AND i.revoked_at IS NULL
+          AND (i.expires_at IS NULL OR i.expires_at > NOW())
+          AND (i.max_uses IS NULL OR i.use_count < i.max_uses)
+        "#,
+        code.trim().to_uppercase()
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(record.map(|r| ValidInvitation {
+        id: r.id,
+        teacher_id: r.teacher_id,
+        teacher_username: r.teacher_username,
+        class_id: r.class_id,
+        class_name: r.class_name,
+        organization_id: r.organization_id,
+    }))
+}
+
+// Davetin bir kullanım hakkını düş; davet bu arada geçersizleştiyse false döner
+pub async fn consume(
+    tx: &mut Transaction<'_, Postgres>,
+    invitation_id: i32,
+) -> Result<bool, sqlx::Error> {
+    let updated = sqlx::query!(
+        r#"
+        UPDATE invitations SET use_count = use_count + 1
+        WHERE id = $1
+          AND revoked_at IS NULL
+          AND (expires_at IS NULL OR expires_at > NOW())
+          AND (max_uses IS NULL OR use_count < max_uses)
+        "#,
+        invitation_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(updated.rows_affected() > 0)
+}
\ No newline at end of file
@@ -0,0 +1,188 @@
+use lazy_static::lazy_static;
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::services::game as game_service;
+use crate::utils::security::hash_widget_token;
+
+// Gömülebilir canlı liderlik tablosu
+// Okullar oyunun liderlik tablosunu dijital panolarda göstermek için izleyici soketine katılmadan, oyuna
+// özel bir token ile herkese açık JSON uçundan veya SSE akışından alabilir. Token yalnızca o oyunun
+// sıralamasını okumaya yetki verir; veritabanında yalnızca özeti saklanır ve öğretmen yeni token
+// oluşturduğunda eskisi geçersiz olur. Akış, sıralama veya soru değiştikçe güncel tabloyu gönderir.
+// Aynı oyunun akışları tabloyu paylaşır: izleyici sayısından bağımsız olarak oyun başına her yoklama
+// aralığında bir kez okunur. Token her yoklamada bu paylaşılan kayıtla yeniden kontrol edilir; iptal
+// edilen veya yenilenen tokenla açılmış akışlar kapanır.
+
+// Tabloda varsayılan ve en fazla gösterilecek oyuncu sayısı
+pub const DEFAULT_LIMIT: i64 = 10;
+pub const MAX_LIMIT: i64 = 50;
+
+// Akışta değişikliklerin yoklanma aralığı
+pub const STREAM_POLL_INTERVAL: Duration = Duration::from_secs(2);
+// Değişiklik olmadığında bağlantının proxy'lerce kapatılmaması için yorum satırı gönderme aralığı
+pub const STREAM_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct WidgetEntry {
+    // Eşit puanlı oyuncular aynı sırayı paylaşır
+    pub rank: usize,
+    pub nickname: String,
+    pub score: i32,
+    pub avatar: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct WidgetSnapshot {
+    pub game_code: String,
+    pub title: String,
+    pub status: String,
+    // Gösterilen sorunun sırası (1'den başlar, lobide 0)
+    pub question_number: i64,
+    pub total_questions: i64,
+    pub leaderboard: Vec<WidgetEntry>,
+}
+
+// Akışların paylaştığı son okuma; oyun silindiyse snapshot None olur
+struct SharedSnapshot {
+    fetched_at: Instant,
+    token_hash: Option<String>,
+    snapshot: Option<WidgetSnapshot>,
+}
+
+type SharedSlot = Arc<tokio::sync::Mutex<Option<SharedSnapshot>>>;
+
+lazy_static! {
+    // Oyun başına paylaşılan tablo (game_id -> son okuma); okuma sürerken gelen akışlar aynı sonucu bekler
+    static ref SHARED_SNAPSHOTS: Mutex<HashMap<i32, SharedSlot>> = Mutex::new(HashMap::new());
+}
+
+impl WidgetSnapshot {
+    pub fn is_finished(&self) -> bool {
+        self.status == "completed"
+    }
+}
+
+// Widget tokenının bağlı olduğu oyun
+pub async fn find_game(pool: &Pool<Postgres>, token: &str) -> Result<Option<i32>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT id FROM games WHERE widget_token_hash = $1",
+        hash_widget_token(token)
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+// Oyunun tokenını yenile (önceki token geçersiz olur) veya None ile kaldır
+pub async fn set_token(pool: &Pool<Postgres>, game_id: i32, token: Option<&str>) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE games SET widget_token_hash = $1 WHERE id = $2",
+        token.map(hash_widget_token),
+        game_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// Oyunun güncel durumu ve ilk `limit` oyuncusu (oyun silindiyse None)
+pub async fn snapshot(pool: &Pool<Postgres>, game_id: i32, limit: i64) -> Result<Option<WidgetSnapshot>, sqlx::Error> {
+    let game = sqlx::query!(
+        r#"
+        SELECT g.code, g.status, qs.title,
+               CASE WHEN g.status = 'lobby' THEN 0 ELSE
+                   (SELECT COUNT(*) FROM game_playlist(g.id) gp WHERE gp.position <= g.current_question)
+               END as "question_number!",
+               (SELECT COUNT(*) FROM game_question_ids(g.id)) as "total_questions!"
+        FROM games g
+        JOIN question_sets qs ON g.question_set_id = qs.id
+        WHERE g.id = $1
+        "#,
+        game_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    let Some(game) = game else {
+        return Ok(None);
+    };
+
+    let players = game_service::leaderboard(pool, game_id).await?;
+
+    let mut leaderboard: Vec<WidgetEntry> = Vec::new();
+    for (index, player) in players.into_iter().take(limit.clamp(1, MAX_LIMIT) as usize).enumerate() {
+        let rank = match leaderboard.last() {
+            Some(previous) if previous.score == player.score => previous.rank,
+            _ => index + 1,
+        };
+        leaderboard.push(WidgetEntry {
+            rank,
+            nickname: player.nickname,
+            score: player.score,
+            avatar: player.avatar,
+        });
+    }
+
+    Ok(Some(WidgetSnapshot {
+        game_code: game.code,
+        title: game.title,
+        status: game.status,
+        question_number: game.question_number,
+        total_questions: game.total_questions,
+        leaderboard,
+    }))
+}
+
+// Akış için oyunun güncel tablosu; token artık oyuna bağlı değilse veya oyun silindiyse None döner.
+// Tablo oyunun tüm akışları için en fazla MAX_LIMIT oyuncuyla bir kez okunur ve her akışın limitine kırpılır.
+pub async fn shared_snapshot(
+    pool: &Pool<Postgres>,
+    game_id: i32,
+    token_hash: &str,
+    limit: i64,
+) -> Result<Option<WidgetSnapshot>, sqlx::Error> {
+    let slot = {
+        let mut snapshots = SHARED_SNAPSHOTS.lock().unwrap_or_else(|e| e.into_inner());
+        snapshots.entry(game_id).or_default().clone()
+    };
+
+    let mut shared = slot.lock().await;
+    let is_fresh = shared.as_ref().is_some_and(|s| s.fetched_at.elapsed() < STREAM_POLL_INTERVAL);
+    if !is_fresh {
+        let current_hash = sqlx::query_scalar!("SELECT widget_token_hash FROM games WHERE id = $1", game_id)
+            .fetch_optional(pool)
+            .await?
+            .flatten();
+        let snapshot = snapshot(pool, game_id, MAX_LIMIT).await?;
+        *shared = Some(SharedSnapshot {
+            fetched_at: Instant::now(),
+            token_hash: current_hash,
+            snapshot,
+        });
+        prune_shared_snapshots();
+    }
+
+    let Some(current) = shared.as_ref() else {
+        return Ok(None);
+    };
+    if current.token_hash.as_deref() != Some(token_hash) {
+        return Ok(None);
+    }
+
+    Ok(current.snapshot.clone().map(|mut snapshot| {
+        snapshot.leaderboard.truncate(limit.clamp(1, MAX_LIMIT) as usize);
+        snapshot
+    }))
+}
+
+// Akışı kalmamış oyunların kayıtlarını bırak (okuması süren kayıtlara dokunulmaz)
+fn prune_shared_snapshots() {
+    let mut snapshots = SHARED_SNAPSHOTS.lock().unwrap_or_else(|e| e.into_inner());
+    snapshots.retain(|_, slot| match slot.try_lock() {
+        Ok(shared) => shared.as_ref().is_some_and(|s| s.fetched_at.elapsed() < STREAM_KEEPALIVE_INTERVAL),
+        Err(_) => true,
+    });
+}
@@ -0,0 +1,93 @@
+use sqlx::{Pool, Postgres};
+
+use crate::db::models::QuestionMessage;
+use crate::services::tts;
+
+// Çok dilli soru setleri
+// Soru setinin asıl dili question_sets.language alanındadır; sorular başka dillere çevrilebilir. Oyuncu
+// katılırken tercih ettiği dili (ör. "en", "en-US") bildirir ve soru, varsa o dildeki çevirisiyle gösterilir.
+// Önce tam eşleşme, yoksa aynı ana dil ("en-us" için "en") aranır; çeviri yoksa asıl metin kullanılır.
+
+// Dili belirtilmeyen soru setleri Türkçe kabul edilir
+pub const DEFAULT_LANGUAGE: &str = "tr";
+
+// Dil kodları BCP 47 biçiminde, küçük harfle saklanır
+const MAX_LOCALE_LEN: usize = 10;
+
+#[derive(Debug, Clone)]
+pub struct QuestionTranslation {
+    pub locale: String,
+    pub question_text: String,
+    pub options: [String; 4],
+    pub audio_url: Option<String>,
+}
+
+// Dil kodunu sadeleştir ("en_US" -> "en-us"); geçersizse None
+pub fn normalize_locale(locale: &str) -> Option<String> {
+    let locale = locale.trim().replace('_', "-").to_lowercase();
+    if locale.is_empty() || locale.len() > MAX_LOCALE_LEN {
+        return None;
+    }
+
+    let mut parts = locale.split('-');
+    let language = parts.next()?;
+    let valid = (2..=3).contains(&language.len())
+        && language.chars().all(|c| c.is_ascii_lowercase())
+        && parts.all(|p| (2..=8).contains(&p.len()) && p.chars().all(|c| c.is_ascii_alphanumeric()));
+
+    valid.then_some(locale)
+}
+
+fn primary_language(locale: &str) -> &str {
+    locale.split('-').next().unwrap_or(locale)
+}
+
+// Oyuncunun diline en uygun çeviri: önce tam eşleşme, sonra aynı ana dil
+pub fn pick_translation<'a>(translations: &'a [QuestionTranslation], locale: &str) -> Option<&'a QuestionTranslation> {
+    translations.iter().find(|t| t.locale == locale).or_else(|| {
+        translations
+            .iter()
+            .find(|t| primary_language(&t.locale) == primary_language(locale))
+    })
+}
+
+// Oyunculara gönderilen soru mesajındaki metni çeviriyle değiştir
+pub fn apply_translation(message: &mut QuestionMessage, translation: &QuestionTranslation) {
+    message.question_text = translation.question_text.clone();
+    message.options = QuestionMessage::option_map(&translation.options);
+    message.locale = Some(translation.locale.clone());
+    // Asıl dildeki ses çeviriyle uyuşmaz; çevirinin sesi yoksa ses gönderilmez
+    message.audio_url = translation.audio_url.clone();
+}
+
+// Sorunun tüm çevirileri
+pub async fn question_translations(
+    pool: &Pool<Postgres>,
+    question_id: i32,
+) -> Result<Vec<QuestionTranslation>, sqlx::Error> {
+    let translations = sqlx::query!(
+        r#"
+        SELECT locale, question_text, option_a, option_b, option_c, option_d
+        FROM question_translations
+        WHERE question_id = $1
+        ORDER BY locale
+        "#,
+        question_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut result = Vec::with_capacity(translations.len());
+    for t in translations {
+        let options = [t.option_a, t.option_b, t.option_c, t.option_d];
+        let audio_url = tts::cached_audio_url(&t.locale, &t.question_text, &options).await;
+        result.push(QuestionTranslation {
+            locale: t.locale,
+            question_text: t.question_text,
+            options,
+            audio_url,
+        });
+    }
+
+    Ok(result)
+}
\ No newline at end of file
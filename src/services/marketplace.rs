@@ -0,0 +1,504 @@
+use chrono::{DateTime, Utc};
+use log::error;
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+use crate::services::set_validation::{self, QuestionInput};
+use crate::services::similarity;
+
+// Soru seti pazaryeri
+// Herkese açık (visibility = 'public') setlerin sahipleri setlerini bir kategoriyle pazaryerine gönderir; admin
+// inceleyip onaylar veya gerekçesiyle reddeder. Onaylanan setler kurumlar arasında listelenir, öne çıkarılabilir
+// ve öğretmenler kendi kütüphanelerine kopyalayabilir (indirme). Set herkese açık olmaktan çıkarılır, gizlenir
+// veya silinirse listeden de düşer; reddedilen veya geri çekilen setler yeniden gönderilebilir.
+// Her gönderimde set yayındaki setlerle benzerlik taramasından geçer (bkz. similarity); başka bir öğretmenin
+// setiyle yoğun örtüşen gönderimler inceleme kuyruğunda işaretlenir.
+
+// İnceleme durumları
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_APPROVED: &str = "approved";
+pub const STATUS_REJECTED: &str = "rejected";
+pub const STATUS_WITHDRAWN: &str = "withdrawn";
+
+// Kopyalanan setin başlığına eklenen ek (kaynak setle karışmaması için)
+const COPY_TITLE_SUFFIX: &str = " (Pazaryeri)";
+
+// Pazaryeri işlemlerinin reddedilme nedenleri
+#[derive(Debug)]
+pub enum MarketplaceError {
+    SetNotFound,
+    NotPublic,
+    InvalidSet,
+    CategoryNotFound,
+    AlreadySubmitted,
+    ListingNotFound,
+    NotPending,
+    NotApproved,
+    Database,
+}
+
+impl MarketplaceError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            MarketplaceError::SetNotFound => "Soru seti bulunamadı veya size ait değil",
+            MarketplaceError::NotPublic => "Pazaryerine yalnızca herkese açık soru setleri gönderilebilir",
+            MarketplaceError::InvalidSet => "Soru setinde hatalar var; önce doğrulama raporundaki hataları düzeltin",
+            MarketplaceError::CategoryNotFound => "Kategori bulunamadı",
+            MarketplaceError::AlreadySubmitted => "Bu soru seti zaten pazaryerinde veya incelemede",
+            MarketplaceError::ListingNotFound => "Pazaryeri kaydı bulunamadı",
+            MarketplaceError::NotPending => "Bu kayıt inceleme beklemiyor",
+            MarketplaceError::NotApproved => "Bu kayıt pazaryerinde yayında değil",
+            MarketplaceError::Database => "İşlem sırasında bir hata oluştu",
+        }
+    }
+}
+
+impl From<sqlx::Error> for MarketplaceError {
+    fn from(e: sqlx::Error) -> Self {
+        error!("Veritabanı sorgu hatası: {}", e);
+        MarketplaceError::Database
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Category {
+    pub id: i32,
+    pub slug: String,
+    pub name: String,
+}
+
+// Pazaryeri kaydı ve setin özeti
+#[derive(Debug, Serialize, Clone)]
+pub struct Listing {
+    pub id: i32,
+    pub question_set_id: i32,
+    pub title: String,
+    pub description: Option<String>,
+    pub language: String,
+    pub creator: String,
+    pub category_slug: String,
+    pub category_name: String,
+    pub status: String,
+    pub is_featured: bool,
+    pub download_count: i32,
+    pub question_count: i64,
+    pub average_rating: Option<f64>,
+    pub rating_count: i64,
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub review_note: Option<String>,
+    // Benzerlik taraması sonucu; yalnızca inceleme kuyruğunda doldurulur
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similarity_flagged: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similarity_score: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similarity_matches: Option<serde_json::Value>,
+}
+
+// Listeleme filtresi
+#[derive(Debug, Default)]
+pub struct ListingFilter {
+    pub status: Option<String>,
+    // Yalnızca bu kullanıcının setleri
+    pub creator_id: Option<i32>,
+    pub category: Option<String>,
+    pub featured_only: bool,
+    // ILIKE deseni
+    pub search: Option<String>,
+    // Yalnızca benzerlik taramasında işaretlenenler
+    pub flagged_only: bool,
+    // Benzerlik taraması sonuçlarını da döndür (inceleme kuyruğu için)
+    pub include_similarity: bool,
+    // "popular" (varsayılan), "recent" veya "top_rated"
+    pub sort: String,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+// Gönderim sonucu
+#[derive(Debug)]
+pub struct Submission {
+    pub listing_id: i32,
+    // Yayındaki bir setle yoğun örtüşme bulundu, inceleme sırasında admin uyarılır
+    pub similarity_flagged: bool,
+}
+
+// İnceleme sonucu bildirilecek kayıt
+#[derive(Debug)]
+pub struct ReviewedListing {
+    pub question_set_id: i32,
+    pub title: String,
+    pub creator_id: i32,
+}
+
+pub async fn categories(pool: &Pool<Postgres>) -> Result<Vec<Category>, sqlx::Error> {
+    sqlx::query_as!(Category, "SELECT id, slug, name FROM marketplace_categories ORDER BY name")
+        .fetch_all(pool)
+        .await
+}
+
+// Yeni kategori ekle; aynı kısa adla kategori varsa None
+pub async fn create_category(pool: &Pool<Postgres>, slug: &str, name: &str) -> Result<Option<Category>, sqlx::Error> {
+    sqlx::query_as!(
+        Category,
+        r#"
+        INSERT INTO marketplace_categories (slug, name, created_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (slug) DO NOTHING
+        RETURNING id, slug, name
+        "#,
+        slug,
+        name,
+        Utc::now()
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+// Kayıtları filtreye göre listele. Onaylı kayıtlar yalnızca set hâlâ herkese açık ve erişilebilir olduğunda döner.
+pub async fn listings(pool: &Pool<Postgres>, filter: &ListingFilter) -> Result<Vec<Listing>, sqlx::Error> {
+    sqlx::query_as!(
+        Listing,
+        r#"
+        SELECT ml.id, ml.question_set_id, qs.title, qs.description, qs.language, u.username as creator,
+               mc.slug as category_slug, mc.name as category_name, ml.status, ml.is_featured, ml.download_count,
+               (SELECT COUNT(*) FROM questions q WHERE q.question_set_id = qs.id AND q.deleted_at IS NULL) as "question_count!",
+               r.average_rating::float8 as "average_rating?",
+               COALESCE(r.rating_count, 0) as "rating_count!",
+               ml.submitted_at, ml.reviewed_at, ml.review_note,
+               CASE WHEN $9 THEN ml.similarity_flagged END as "similarity_flagged?",
+               CASE WHEN $9 THEN ml.similarity_score END as "similarity_score?",
+               CASE WHEN $9 THEN ml.similarity_matches END as "similarity_matches?"
+        FROM marketplace_listings ml
+        JOIN question_sets qs ON ml.question_set_id = qs.id
+        JOIN users u ON qs.creator_id = u.id
+        JOIN marketplace_categories mc ON ml.category_id = mc.id
+        LEFT JOIN (
+            SELECT question_set_id, AVG(rating) as average_rating, COUNT(*) as rating_count
+            FROM question_set_ratings
+            GROUP BY question_set_id
+        ) r ON r.question_set_id = qs.id
+        WHERE qs.deleted_at IS NULL
+          AND ($1::text IS NULL OR ml.status = $1)
+          AND ($1::text IS DISTINCT FROM 'approved'
+               OR (qs.visibility = 'public' AND qs.is_hidden = false AND qs.archived_at IS NULL))
+          AND ($2::int IS NULL OR qs.creator_id = $2)
+          AND ($3::text IS NULL OR mc.slug = $3)
+          AND (NOT $4 OR ml.is_featured)
+          AND ($5::text IS NULL OR qs.title ILIKE $5 OR qs.description ILIKE $5)
+          AND (NOT $10 OR ml.similarity_flagged)
+        ORDER BY
+            CASE WHEN $6 = 'popular' THEN ml.download_count END DESC,
+            CASE WHEN $6 = 'top_rated' THEN r.average_rating END DESC NULLS LAST,
+            CASE WHEN $6 = 'top_rated' THEN r.rating_count END DESC NULLS LAST,
+            COALESCE(ml.reviewed_at, ml.submitted_at) DESC,
+            ml.id DESC
+        LIMIT $7 OFFSET $8
+        "#,
+        filter.status,
+        filter.creator_id,
+        filter.category,
+        filter.featured_only,
+        filter.search,
+        filter.sort,
+        filter.limit,
+        filter.offset,
+        filter.include_similarity,
+        filter.flagged_only
+    )
+    .fetch_all(pool)
+    .await
+}
+
+// Setin sahibi olarak seti pazaryerine gönder (reddedilmiş veya geri çekilmiş set yeniden incelemeye girer)
+pub async fn submit(
+    pool: &Pool<Postgres>,
+    user_id: i32,
+    question_set_id: i32,
+    category_id: i32,
+) -> Result<Submission, MarketplaceError> {
+    let set = sqlx::query!(
+        r#"
+        SELECT visibility, is_hidden FROM question_sets
+        WHERE id = $1 AND creator_id = $2 AND deleted_at IS NULL AND archived_at IS NULL
+        "#,
+        question_set_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(MarketplaceError::SetNotFound)?;
+
+    if set.visibility != "public" || set.is_hidden {
+        return Err(MarketplaceError::NotPublic);
+    }
+
+    let category_exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM marketplace_categories WHERE id = $1) as "exists!""#,
+        category_id
+    )
+    .fetch_one(pool)
+    .await?;
+    if !category_exists {
+        return Err(MarketplaceError::CategoryNotFound);
+    }
+
+    // Boş veya oyunu bozacak hatalar içeren setler incelemeye gönderilemez
+    let questions: Vec<QuestionInput> = sqlx::query!(
+        r#"
+        SELECT id, position, question_text, option_a, option_b, option_c, option_d, time_limit
+        FROM questions
+        WHERE question_set_id = $1 AND deleted_at IS NULL
+        ORDER BY position, id
+        "#,
+        question_set_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|q| QuestionInput {
+        id: q.id,
+        position: q.position,
+        question_text: q.question_text,
+        options: [q.option_a, q.option_b, q.option_c, q.option_d],
+        time_limit: q.time_limit,
+    })
+    .collect();
+
+    let has_errors = set_validation::validate_questions(&questions)
+        .iter()
+        .any(|issue| issue.severity == set_validation::SEVERITY_ERROR);
+    if questions.is_empty() || has_errors {
+        return Err(MarketplaceError::InvalidSet);
+    }
+
+    let listing_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO marketplace_listings (question_set_id, category_id, status, submitted_by, submitted_at)
+        VALUES ($1, $2, 'pending', $3, $4)
+        ON CONFLICT (question_set_id) DO UPDATE
+        SET category_id = EXCLUDED.category_id, status = 'pending', submitted_by = EXCLUDED.submitted_by,
+            submitted_at = EXCLUDED.submitted_at, reviewed_by = NULL, reviewed_at = NULL, review_note = NULL,
+            is_featured = false
+        WHERE marketplace_listings.status IN ('rejected', 'withdrawn')
+        RETURNING id
+        "#,
+        question_set_id,
+        category_id,
+        user_id,
+        Utc::now()
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(MarketplaceError::AlreadySubmitted)?;
+
+    let report = similarity::scan(pool, question_set_id, user_id).await?;
+    sqlx::query!(
+        r#"
+        UPDATE marketplace_listings
+        SET similarity_score = $1, similarity_matches = $2, similarity_flagged = $3
+        WHERE id = $4
+        "#,
+        report.score as f32,
+        serde_json::to_value(&report.matches).unwrap_or_default(),
+        report.flagged,
+        listing_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(Submission {
+        listing_id,
+        similarity_flagged: report.flagged,
+    })
+}
+
+// Setin sahibi olarak kaydı incelemeden veya pazaryerinden geri çek
+pub async fn withdraw(pool: &Pool<Postgres>, user_id: i32, listing_id: i32) -> Result<(), MarketplaceError> {
+    sqlx::query_scalar!(
+        r#"
+        UPDATE marketplace_listings ml SET status = 'withdrawn', is_featured = false
+        FROM question_sets qs
+        WHERE ml.question_set_id = qs.id AND ml.id = $1 AND qs.creator_id = $2
+          AND ml.status IN ('pending', 'approved')
+        RETURNING ml.id
+        "#,
+        listing_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .map(|_| ())
+    .ok_or(MarketplaceError::ListingNotFound)
+}
+
+// Bekleyen kaydı onayla veya reddet
+pub async fn review(
+    pool: &Pool<Postgres>,
+    admin_id: i32,
+    listing_id: i32,
+    approve: bool,
+    note: Option<&str>,
+) -> Result<ReviewedListing, MarketplaceError> {
+    let status = if approve { STATUS_APPROVED } else { STATUS_REJECTED };
+
+    let reviewed = sqlx::query!(
+        r#"
+        UPDATE marketplace_listings ml
+        SET status = $1, reviewed_by = $2, reviewed_at = $3, review_note = $4
+        FROM question_sets qs
+        WHERE ml.question_set_id = qs.id AND ml.id = $5 AND ml.status = 'pending'
+        RETURNING ml.question_set_id, qs.title, qs.creator_id
+        "#,
+        status,
+        admin_id,
+        Utc::now(),
+        note,
+        listing_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    match reviewed {
+        Some(r) => Ok(ReviewedListing {
+            question_set_id: r.question_set_id,
+            title: r.title,
+            creator_id: r.creator_id,
+        }),
+        None => Err(not_found_or(pool, listing_id, MarketplaceError::NotPending).await),
+    }
+}
+
+// Onaylı kaydı öne çıkar veya öne çıkanlardan kaldır
+pub async fn set_featured(pool: &Pool<Postgres>, listing_id: i32, featured: bool) -> Result<(), MarketplaceError> {
+    let updated = sqlx::query!(
+        "UPDATE marketplace_listings SET is_featured = $1 WHERE id = $2 AND status = 'approved'",
+        featured,
+        listing_id
+    )
+    .execute(pool)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        return Err(not_found_or(pool, listing_id, MarketplaceError::NotApproved).await);
+    }
+    Ok(())
+}
+
+// Kayıt varsa verilen hatayı, yoksa ListingNotFound döndür
+async fn not_found_or(pool: &Pool<Postgres>, listing_id: i32, otherwise: MarketplaceError) -> MarketplaceError {
+    match sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM marketplace_listings WHERE id = $1) as "exists!""#,
+        listing_id
+    )
+    .fetch_one(pool)
+    .await
+    {
+        Ok(true) => otherwise,
+        Ok(false) => MarketplaceError::ListingNotFound,
+        Err(e) => e.into(),
+    }
+}
+
+// Onaylı seti kullanıcının kütüphanesine kopyala (sorular, konu etiketleri ve çevirileriyle) ve indirme sayısını artır.
+// Yeni setin kimliğini döndürür.
+pub async fn download(pool: &Pool<Postgres>, user_id: i32, listing_id: i32) -> Result<i32, MarketplaceError> {
+    let mut tx = pool.begin().await?;
+
+    let source = sqlx::query!(
+        r#"
+        UPDATE marketplace_listings ml SET download_count = ml.download_count + 1
+        FROM question_sets qs
+        WHERE ml.question_set_id = qs.id AND ml.id = $1 AND ml.status = 'approved'
+          AND qs.visibility = 'public' AND qs.is_hidden = false
+          AND qs.archived_at IS NULL AND qs.deleted_at IS NULL
+        RETURNING qs.id, qs.title, qs.description, qs.language
+        "#,
+        listing_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(MarketplaceError::ListingNotFound)?;
+
+    let now = Utc::now();
+    let new_set_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO question_sets (creator_id, title, description, language, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $5)
+        RETURNING id
+        "#,
+        user_id,
+        format!("{}{}", source.title, COPY_TITLE_SUFFIX),
+        source.description,
+        source.language,
+        now
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let questions = sqlx::query!(
+        r#"
+        SELECT id, question_text, option_a, option_b, option_c, option_d, correct_option,
+               points, time_limit, position, host_notes
+        FROM questions
+        WHERE question_set_id = $1 AND deleted_at IS NULL
+        ORDER BY position, id
+        "#,
+        source.id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    for question in questions {
+        let new_question_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO questions (question_set_id, question_text, option_a, option_b, option_c, option_d,
+                                   correct_option, points, time_limit, position, host_notes)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            RETURNING id
+            "#,
+            new_set_id,
+            question.question_text,
+            question.option_a,
+            question.option_b,
+            question.option_c,
+            question.option_d,
+            question.correct_option,
+            question.points,
+            question.time_limit,
+            question.position,
+            question.host_notes
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO question_topics (question_id, topic_id)
+            SELECT $1, topic_id FROM question_topics WHERE question_id = $2
+            "#,
+            new_question_id,
+            question.id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO question_translations (question_id, locale, question_text, option_a, option_b, option_c,
+                                               option_d, updated_at)
+            SELECT $1, locale, question_text, option_a, option_b, option_c, option_d, $3
+            FROM question_translations WHERE question_id = $2
+            "#,
+            new_question_id,
+            question.id,
+            now
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(new_set_id)
+}
\ No newline at end of file
@@ -0,0 +1,89 @@
+use log::error;
+use sqlx::{Pool, Postgres};
+use std::io;
+use std::path::PathBuf;
+
+use crate::config::CONFIG;
+use crate::services::quota::{self, QuotaMetric};
+
+// Medya deposu
+// Sunucunun ürettiği dosyalar (ör. sesli okuma kayıtları) MEDIA_DIR altında düz bir dizinde saklanır.
+// Dosya adları içerikten türetilir ve değişmez; bu yüzden aynı ad her zaman aynı içeriği gösterir ve
+// istemciler ile önbellekler dosyayı süresiz saklayabilir.
+
+// Dosya adı yalnızca küçük harf, rakam, '-' ve tek bir uzantıdan oluşabilir (dizin dışına çıkılamaz)
+pub fn is_valid_name(name: &str) -> bool {
+    match name.split_once('.') {
+        Some((stem, extension)) => {
+            !stem.is_empty()
+                && stem.len() <= 128
+                && stem.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+                && content_type(extension).is_some()
+        }
+        None => false,
+    }
+}
+
+// Desteklenen uzantıların içerik türü
+pub fn content_type(extension: &str) -> Option<&'static str> {
+    match extension {
+        "mp3" => Some("audio/mpeg"),
+        "ogg" => Some("audio/ogg"),
+        "wav" => Some("audio/wav"),
+        _ => None,
+    }
+}
+
+fn path(name: &str) -> PathBuf {
+    PathBuf::from(&CONFIG.media_dir).join(name)
+}
+
+// Dosyanın istemcilere verilecek adresi
+pub fn url(name: &str) -> String {
+    format!("{}/{}", CONFIG.media_base_url.trim_end_matches('/'), name)
+}
+
+pub async fn exists(name: &str) -> bool {
+    tokio::fs::try_exists(path(name)).await.unwrap_or(false)
+}
+
+// Dosyayı kaydet. Dosyayı üreten kullanıcı verilmişse boyutu kullanıcının medya depolama kotasından düşülür
+// (kota yetmezse dosya yazılmaz); yazma başarısız olursa kota geri verilir.
+pub async fn store(
+    pool: &Pool<Postgres>,
+    owner_id: Option<i32>,
+    name: &str,
+    data: &[u8],
+) -> Result<(), anyhow::Error> {
+    let size = data.len() as i64;
+    if let Some(user_id) = owner_id {
+        quota::consume(pool, user_id, QuotaMetric::MediaStorageBytes, size).await?;
+    }
+
+    if let Err(e) = write(name, data).await {
+        if let Some(user_id) = owner_id {
+            if let Err(e) = quota::refund(pool, user_id, QuotaMetric::MediaStorageBytes, size).await {
+                error!("Medya kotası geri verilemedi (user_id={}): {}", user_id, e);
+            }
+        }
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+// Yarım yazılmış dosya sunulmasın diye önce geçici dosyaya yazılıp taşınır
+async fn write(name: &str, data: &[u8]) -> io::Result<()> {
+    tokio::fs::create_dir_all(&CONFIG.media_dir).await?;
+
+    let temp = path(&format!("{}.{}.tmp", name, uuid::Uuid::new_v4().simple()));
+    tokio::fs::write(&temp, data).await?;
+    if let Err(e) = tokio::fs::rename(&temp, path(name)).await {
+        let _ = tokio::fs::remove_file(&temp).await;
+        return Err(e);
+    }
+    Ok(())
+}
+
+pub async fn read(name: &str) -> io::Result<Vec<u8>> {
+    tokio::fs::read(path(name)).await
+}
\ No newline at end of file
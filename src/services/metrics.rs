@@ -0,0 +1,186 @@
+use lazy_static::lazy_static;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::db::pool::PoolStats;
+
+// İstek ve sorgu metrikleri (Prometheus)
+// Her rota (eşleşen yol şablonu, ör. /api/game/{code}) ve her depo sorgusu (bkz. db::repositories::observe)
+// için çağrı sayısı, toplam süre ve son çağrılardan hesaplanan gecikme yüzdelikleri bellekte tutulur. İstek
+// veya WebSocket mesajı işlenirken rota ve oyun kodu görev bağlamına konur; yavaş sorgu uyarıları ve sayaçları
+// bu bağlamı kullanır. Metrikler dahili /metrics adresinden Prometheus metin biçiminde okunur (bkz. handlers::metrics).
+
+// Yüzdelik hesabında kullanılan son çağrı sayısı (rota veya sorgu başına)
+const LATENCY_WINDOW: usize = 1024;
+
+// Dışa aktarılan yüzdelikler
+const QUANTILES: [f64; 3] = [0.5, 0.9, 0.99];
+
+// İstek sırasında çalışan sorgulara eklenen bağlam
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+    pub route: String,
+    pub game_code: Option<String>,
+}
+
+impl RequestContext {
+    // WebSocket üzerinden gelen oyun mesajı
+    pub fn websocket(game_code: Option<&str>) -> Self {
+        RequestContext {
+            route: "/ws".to_string(),
+            game_code: game_code.map(str::to_uppercase),
+        }
+    }
+}
+
+tokio::task_local! {
+    static REQUEST_CONTEXT: RequestContext;
+}
+
+// Süre dağılımı
+#[derive(Default)]
+struct Latency {
+    count: u64,
+    sum_secs: f64,
+    recent: VecDeque<f64>,
+}
+
+impl Latency {
+    fn observe(&mut self, secs: f64) {
+        self.count += 1;
+        self.sum_secs += secs;
+        if self.recent.len() == LATENCY_WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(secs);
+    }
+
+    // Summary satırları: yüzdelikler, toplam ve sayı
+    fn write(&self, out: &mut String, name: &str, labels: &str) {
+        let mut sorted: Vec<f64> = self.recent.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        for q in QUANTILES {
+            let _ = writeln!(out, "{}{{{},quantile=\"{}\"}} {}", name, labels, q, quantile(&sorted, q));
+        }
+        let _ = writeln!(out, "{}_sum{{{}}} {}", name, labels, self.sum_secs);
+        let _ = writeln!(out, "{}_count{{{}}} {}", name, labels, self.count);
+    }
+}
+
+#[derive(Default)]
+struct RouteStats {
+    latency: Latency,
+    // Durum kodu -> istek sayısı
+    statuses: BTreeMap<u16, u64>,
+}
+
+#[derive(Default)]
+struct Registry {
+    // (metot, rota) -> istatistikler
+    routes: BTreeMap<(String, String), RouteStats>,
+    // Sorgu adı -> süreler
+    queries: BTreeMap<&'static str, Latency>,
+    // (sorgu adı, rota) -> yavaş sorgu sayısı
+    slow_queries: BTreeMap<(&'static str, String), u64>,
+}
+
+lazy_static! {
+    static ref REGISTRY: Mutex<Registry> = Mutex::new(Registry::default());
+}
+
+// Geleceği verilen istek bağlamıyla çalıştır; içindeki sorgular bu bağlamla loglanır
+pub async fn with_context<F: Future>(context: RequestContext, future: F) -> F::Output {
+    REQUEST_CONTEXT.scope(context, future).await
+}
+
+// Çalışan isteğin bağlamı (istek dışında, ör. WebSocket oturumlarında None)
+pub fn current_context() -> Option<RequestContext> {
+    REQUEST_CONTEXT.try_with(|context| context.clone()).ok()
+}
+
+// Tamamlanan isteği kaydet
+pub fn record_request(method: &str, route: &str, status: u16, elapsed: Duration) {
+    if let Ok(mut registry) = REGISTRY.lock() {
+        let stats = registry
+            .routes
+            .entry((method.to_string(), route.to_string()))
+            .or_default();
+        stats.latency.observe(elapsed.as_secs_f64());
+        *stats.statuses.entry(status).or_default() += 1;
+    }
+}
+
+// Tamamlanan depo sorgusunu kaydet; yavaşsa çalıştığı rotaya göre de say
+pub fn record_query(name: &'static str, elapsed: Duration, slow_route: Option<&str>) {
+    if let Ok(mut registry) = REGISTRY.lock() {
+        registry.queries.entry(name).or_default().observe(elapsed.as_secs_f64());
+        if let Some(route) = slow_route {
+            *registry.slow_queries.entry((name, route.to_string())).or_default() += 1;
+        }
+    }
+}
+
+// Sıralı örneklerden en yakın sıra yöntemiyle yüzdelik
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (q * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+// Etiket değerindeki özel karakterleri kaçır
+fn label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+// Tüm metrikleri Prometheus metin biçiminde yaz
+pub fn render(pool: &PoolStats) -> String {
+    let mut out = String::new();
+    let registry = match REGISTRY.lock() {
+        Ok(registry) => registry,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+
+    out.push_str("# HELP http_request_duration_seconds Rota başına istek süresi (yüzdelikler son isteklerden hesaplanır)\n");
+    out.push_str("# TYPE http_request_duration_seconds summary\n");
+    for ((method, route), stats) in &registry.routes {
+        let labels = format!("method=\"{}\",route=\"{}\"", label(method), label(route));
+        stats.latency.write(&mut out, "http_request_duration_seconds", &labels);
+    }
+
+    out.push_str("# HELP http_requests_total Rota ve durum koduna göre istek sayısı\n");
+    out.push_str("# TYPE http_requests_total counter\n");
+    for ((method, route), stats) in &registry.routes {
+        for (status, count) in &stats.statuses {
+            let _ = writeln!(
+                out,
+                "http_requests_total{{method=\"{}\",route=\"{}\",status=\"{}\"}} {}",
+                label(method), label(route), status, count
+            );
+        }
+    }
+
+    out.push_str("# HELP db_query_duration_seconds Depo sorgusu başına süre (yüzdelikler son sorgulardan hesaplanır)\n");
+    out.push_str("# TYPE db_query_duration_seconds summary\n");
+    for (name, latency) in &registry.queries {
+        latency.write(&mut out, "db_query_duration_seconds", &format!("query=\"{}\"", label(name)));
+    }
+
+    out.push_str("# HELP db_slow_queries_total Eşik süresini aşan sorgu sayısı (sorgu ve isteğin rotasına göre)\n");
+    out.push_str("# TYPE db_slow_queries_total counter\n");
+    for ((name, route), count) in &registry.slow_queries {
+        let _ = writeln!(out, "db_slow_queries_total{{query=\"{}\",route=\"{}\"}} {}", label(name), label(route), count);
+    }
+
+    out.push_str("# HELP db_pool_connections Veritabanı bağlantı havuzu\n");
+    out.push_str("# TYPE db_pool_connections gauge\n");
+    let _ = writeln!(out, "db_pool_connections{{state=\"in_use\"}} {}", pool.in_use);
+    let _ = writeln!(out, "db_pool_connections{{state=\"idle\"}} {}", pool.idle);
+    let _ = writeln!(out, "db_pool_connections{{state=\"max\"}} {}", pool.max);
+
+    out
+}
\ No newline at end of file
@@ -0,0 +1,180 @@
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::db::repositories::read_pool;
+
+// Kavram yanılgısı analizi
+// Yanlış cevaplar şıklara göre gruplanır. Bir soruda oyuncuların önemli bir kısmının aynı yanlış şıkkı
+// (çeldiriciyi) seçmesi rastgele tahminden çok ortak bir kavram yanılgısına işaret eder. Oyuncu bazında da
+// yanlış cevapların tek bir harfte toplanması (şık harfi alışkanlığı) ve sınıfın en çok seçtiği
+// çeldiricilere tekrar tekrar düşülmesi raporlanır.
+
+// Çeldiricinin kavram yanılgısı sayılması için seçen oyuncuların tüm cevaplara oranı
+const MISCONCEPTION_MIN_SHARE: f64 = 0.3;
+// Çeldiricinin kavram yanılgısı sayılması için en az seçen oyuncu sayısı
+const MISCONCEPTION_MIN_PLAYERS: i64 = 2;
+// Oyuncu örüntüsünün raporlanması için gereken en az yanlış cevap sayısı
+const PATTERN_MIN_WRONG_ANSWERS: i64 = 3;
+// Yanlış cevapların bu oranı aynı harfteyse şık harfi alışkanlığı sayılır
+const LETTER_BIAS_MIN_SHARE: f64 = 0.6;
+// Oyuncunun sınıfın kavram yanılgılarını paylaştığının söylenmesi için gereken en az eşleşme
+const PATTERN_MIN_MISCONCEPTION_MATCHES: i64 = 2;
+
+const OPTIONS: [&str; 4] = ["A", "B", "C", "D"];
+
+// Sorunun çeldirici analizi
+#[derive(Debug, Serialize, Clone)]
+pub struct QuestionDistractors {
+    pub question_id: i32,
+    pub correct_option: String,
+    pub answer_count: i64,
+    // Yanlış şık -> seçen oyuncu sayısı
+    pub wrong_option_counts: BTreeMap<String, i64>,
+    pub top_distractor: Option<String>,
+    pub top_distractor_count: i64,
+    // En çok seçilen çeldiriciyi seçenlerin tüm cevaplara oranı (%)
+    pub top_distractor_share: f64,
+    pub is_misconception: bool,
+}
+
+// Oyuncunun yanlış cevap örüntüsü
+#[derive(Debug, Serialize, Clone)]
+pub struct PlayerDistractorPattern {
+    pub player_id: i32,
+    pub nickname: String,
+    pub wrong_answers: i64,
+    // Yanlış cevaplarının çoğunlukla toplandığı harf (yoksa null)
+    pub favorite_wrong_option: Option<String>,
+    pub favorite_wrong_option_count: i64,
+    // Yanlış cevaplarından kaçı, sorunun kavram yanılgısı sayılan çeldiricisiydi
+    pub misconception_matches: i64,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct MisconceptionReport {
+    pub questions: Vec<QuestionDistractors>,
+    pub players: Vec<PlayerDistractorPattern>,
+}
+
+fn share(count: i64, total: i64) -> f64 {
+    if total > 0 {
+        ((count as f64 / total as f64) * 1000.0).round() / 10.0
+    } else {
+        0.0
+    }
+}
+
+// Kavram yanılgısı raporu oyun sonrası analiz olduğundan okuma replikasından hesaplanır
+pub async fn game_report(pool: &Pool<Postgres>, game_id: i32) -> Result<MisconceptionReport, sqlx::Error> {
+    let pool = read_pool(pool);
+    let answers = sqlx::query!(
+        r#"
+        SELECT pa.player_id, p.nickname, pa.question_id, pa.answer, q.correct_option
+        FROM player_answers pa
+        JOIN players p ON pa.player_id = p.id
+        JOIN questions q ON pa.question_id = q.id
+        WHERE p.game_id = $1
+        ORDER BY q.position, pa.player_id
+        "#,
+        game_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    // Soru bazında şık dağılımları (soru sırasıyla)
+    let mut questions: Vec<QuestionDistractors> = Vec::new();
+    for answer in &answers {
+        if questions.last().map(|q| q.question_id) != Some(answer.question_id) {
+            questions.push(QuestionDistractors {
+                question_id: answer.question_id,
+                correct_option: answer.correct_option.clone(),
+                answer_count: 0,
+                wrong_option_counts: OPTIONS
+                    .iter()
+                    .filter(|o| **o != answer.correct_option)
+                    .map(|o| (o.to_string(), 0))
+                    .collect(),
+                top_distractor: None,
+                top_distractor_count: 0,
+                top_distractor_share: 0.0,
+                is_misconception: false,
+            });
+        }
+
+        if let Some(question) = questions.last_mut() {
+            question.answer_count += 1;
+            if let Some(count) = answer.answer.as_deref().and_then(|a| question.wrong_option_counts.get_mut(a)) {
+                *count += 1;
+            }
+        }
+    }
+
+    for question in &mut questions {
+        // Eşitlikte alfabetik olarak ilk şık seçilir
+        let top = question
+            .wrong_option_counts
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)));
+
+        if let Some((option, count)) = top {
+            question.top_distractor = Some(option.clone());
+            question.top_distractor_count = *count;
+            question.top_distractor_share = share(*count, question.answer_count);
+            question.is_misconception = *count >= MISCONCEPTION_MIN_PLAYERS
+                && (*count as f64) >= question.answer_count as f64 * MISCONCEPTION_MIN_SHARE;
+        }
+    }
+
+    let misconceptions: HashMap<i32, &str> = questions
+        .iter()
+        .filter(|q| q.is_misconception)
+        .filter_map(|q| q.top_distractor.as_deref().map(|option| (q.question_id, option)))
+        .collect();
+
+    // Oyuncu bazında yanlış cevap örüntüleri
+    let mut players: BTreeMap<i32, (String, BTreeMap<String, i64>, i64)> = BTreeMap::new();
+    for answer in &answers {
+        let Some(option) = answer.answer.as_deref().filter(|a| OPTIONS.contains(a) && *a != answer.correct_option) else {
+            continue;
+        };
+        let entry = players
+            .entry(answer.player_id)
+            .or_insert_with(|| (answer.nickname.clone(), BTreeMap::new(), 0));
+        *entry.1.entry(option.to_string()).or_default() += 1;
+        if misconceptions.get(&answer.question_id) == Some(&option) {
+            entry.2 += 1;
+        }
+    }
+
+    let players = players
+        .into_iter()
+        .filter_map(|(player_id, (nickname, letters, misconception_matches))| {
+            let wrong_answers: i64 = letters.values().sum();
+            if wrong_answers < PATTERN_MIN_WRONG_ANSWERS {
+                return None;
+            }
+
+            let favorite = letters
+                .iter()
+                .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+                .filter(|(_, count)| **count as f64 >= wrong_answers as f64 * LETTER_BIAS_MIN_SHARE);
+
+            if favorite.is_none() && misconception_matches < PATTERN_MIN_MISCONCEPTION_MATCHES {
+                return None;
+            }
+
+            Some(PlayerDistractorPattern {
+                player_id,
+                nickname,
+                wrong_answers,
+                favorite_wrong_option: favorite.map(|(option, _)| option.clone()),
+                favorite_wrong_option_count: favorite.map(|(_, count)| *count).unwrap_or(0),
+                misconception_matches,
+            })
+        })
+        .collect();
+
+    Ok(MisconceptionReport { questions, players })
+}
\ No newline at end of file
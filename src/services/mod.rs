@@ -1,2 +1,55 @@
+pub mod accommodations;
+pub mod answer_timing;
+pub mod anti_cheat;
+pub mod api_keys;
+pub mod attendance;
+pub mod audit;
+pub mod blocklist;
+pub mod collaboration;
+pub mod cosmetics;
+pub mod curriculum;
+pub mod demo_seed;
 pub mod email;
+pub mod email_domains;
+pub mod game;
+pub mod game_alias;
+pub mod game_events;
+pub mod game_notifications;
+pub mod game_playlist;
+pub mod grading;
+pub mod guest_ip;
+pub mod guest_sessions;
+pub mod invitation;
+pub mod leaderboard_widget;
+pub mod localization;
+pub mod marketplace;
+pub mod media;
+pub mod metrics;
+pub mod misconceptions;
+pub mod nickname;
+pub mod notifications;
+pub mod option_labels;
+pub mod organization;
+pub mod password_policy;
+pub mod password_reset;
+pub mod platform_stats;
+pub mod preview;
+pub mod progression;
+pub mod push;
+pub mod question_edit;
+pub mod question_sample;
+pub mod quota;
+pub mod roster;
+pub mod scenes;
+pub mod seasons;
+pub mod set_validation;
+pub mod settings;
+pub mod similarity;
+pub mod student_report;
+pub mod token_version;
+pub mod tournament;
+pub mod trash;
+pub mod tts;
+pub mod viewers;
+pub mod warehouse_export;
 // pub mod websocket;
\ No newline at end of file
@@ -0,0 +1,108 @@
+use std::collections::HashSet;
+
+use sqlx::{Pool, Postgres};
+
+use crate::config::CONFIG;
+use crate::utils::validation::{name_skeleton, normalize_name, validate_nickname};
+
+// Misafir takma adlarının kayıtlı öğrencileri taklit etmesini engelle
+// Oyunu açan öğretmenin listesinde (sınıfları ve önceki oyunlarının kayıtlı oyuncuları)
+// aynı veya benzer görünen (ör. "Ayşe" ve Kiril "а" ile yazılmış "Аyşe") isimde bir kullanıcı varsa
+// misafirin takma adına sayı eklenir.
+
+// Misafir öneki
+pub const GUEST_PREFIX: &str = "**";
+
+// Oyun içi takma ad benzersizliğini sağlayan veritabanı indeksi
+const NICKNAME_UNIQUE_INDEX: &str = "idx_players_game_nickname";
+
+// Baştaki/sondaki boşlukları kırp, aradaki ardışık boşlukları teke indir
+pub fn clean_nickname(nickname: &str) -> String {
+    nickname.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Takma adların karşılaştırılacağı biçim: Türkçe I/İ/ı harfleri i'ye katlanır, geri kalanı küçük harfe çevrilir.
+// Veritabanındaki normalize_nickname() fonksiyonuyla aynı kuralları izler.
+pub fn normalize_nickname(nickname: &str) -> String {
+    clean_nickname(nickname)
+        .chars()
+        .flat_map(|c| match c {
+            'I' | 'İ' | 'ı' => 'i'.to_lowercase(),
+            _ => c.to_lowercase(),
+        })
+        .collect()
+}
+
+// Misafirin istediği takma adı önekten arındırıp normalize et; izin verilen karakter kümesine uymuyorsa None
+pub fn guest_nickname_base(requested: &str) -> Option<String> {
+    let base = normalize_name(requested.trim_start_matches(GUEST_PREFIX));
+    validate_nickname(&base, CONFIG.username_charset).then_some(base)
+}
+
+// Ekleme hatası, aynı takma adın eşzamanlı olarak alınmasından mı kaynaklanıyor
+pub fn is_nickname_conflict(e: &sqlx::Error) -> bool {
+    e.as_database_error()
+        .and_then(|db_error| db_error.constraint())
+        .is_some_and(|constraint| constraint == NICKNAME_UNIQUE_INDEX)
+}
+
+// Misafir için kullanılacak takma adı belirle (önekli olarak döndürür); `base` guest_nickname_base ile doğrulanmış olmalı
+pub async fn resolve_guest_nickname(
+    pool: &Pool<Postgres>,
+    game_id: i32,
+    base: &str,
+) -> Result<String, sqlx::Error> {
+    // Öğretmenin listesindeki kayıtlı kullanıcı adlarının karşılaştırma anahtarları
+    let reserved: HashSet<String> = sqlx::query!(
+        r#"
+        WITH host AS (SELECT host_id FROM games WHERE id = $1)
+        SELECT DISTINCT u.username
+        FROM users u
+        WHERE u.id IN (
+            SELECT cm.user_id FROM class_members cm
+            JOIN classes c ON cm.class_id = c.id
+            WHERE c.teacher_id = (SELECT host_id FROM host)
+            UNION
+            SELECT p.user_id FROM players p
+            JOIN games g ON p.game_id = g.id
+            WHERE g.host_id = (SELECT host_id FROM host) AND p.user_id IS NOT NULL
+        )
+        "#,
+        game_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| name_skeleton(&r.username))
+    .collect();
+
+    if !reserved.contains(&name_skeleton(base)) {
+        return Ok(format!("{}{}", GUEST_PREFIX, base));
+    }
+
+    // Oyunda zaten kullanılan takma adlar
+    let taken: HashSet<String> = sqlx::query!(
+        "SELECT normalize_nickname(nickname) as \"nickname!\" FROM players WHERE game_id = $1",
+        game_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| r.nickname)
+    .collect();
+
+    Ok(pick_suffixed(base, &reserved, &taken))
+}
+
+// Ne listede ne de oyunda kullanılan ilk sayı ekli takma adı seç
+fn pick_suffixed(base: &str, reserved: &HashSet<String>, taken: &HashSet<String>) -> String {
+    let mut n = 2;
+    loop {
+        let candidate = format!("{}_{}", base, n);
+        let prefixed = format!("{}{}", GUEST_PREFIX, candidate);
+        if !reserved.contains(&name_skeleton(&candidate)) && !taken.contains(&normalize_nickname(&prefixed)) {
+            return prefixed;
+        }
+        n += 1;
+    }
+}
\ No newline at end of file
@@ -0,0 +1,109 @@
+use chrono::Utc;
+use log::error;
+use sqlx::{Pool, Postgres};
+
+// Uygulama içi bildirim kutusu
+// E-posta gönderilen olaylar burada da kayıt altına alınır; e-postayı kaçıran kullanıcılar
+// bildirimleri uygulama içinden görebilir.
+
+// Bildirim türleri
+pub const KIND_TEACHER_APPROVAL: &str = "teacher_approval";
+pub const KIND_MODERATION: &str = "moderation";
+pub const KIND_SET_SHARED: &str = "set_shared";
+pub const KIND_GAME_REPORT: &str = "game_report";
+pub const KIND_MARKETPLACE: &str = "marketplace";
+pub const KIND_GAME_REMINDER: &str = "game_reminder";
+pub const KIND_CHALLENGE: &str = "challenge";
+
+// Kullanıcıya bildirim yaz; hata durumunda işlemi engellemez
+pub async fn notify(
+    pool: &Pool<Postgres>,
+    user_id: i32,
+    kind: &str,
+    title: &str,
+    body: &str,
+    data: serde_json::Value,
+) {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO notifications (user_id, kind, title, body, data, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        user_id,
+        kind,
+        title,
+        body,
+        data,
+        Utc::now()
+    )
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result {
+        error!("Bildirim kaydedilemedi (user_id={}, kind={}): {}", user_id, kind, e);
+    }
+}
+
+// Kurumdaki tüm öğretmenlere (gönderen hariç) bildirim yaz
+pub async fn notify_organization_teachers(
+    pool: &Pool<Postgres>,
+    organization_id: i32,
+    except_user_id: i32,
+    kind: &str,
+    title: &str,
+    body: &str,
+    data: serde_json::Value,
+) {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO notifications (user_id, kind, title, body, data, created_at)
+        SELECT id, $3, $4, $5, $6, $7
+        FROM users
+        WHERE organization_id = $1 AND id <> $2 AND role = 'teacher' AND is_approved = true
+        "#,
+        organization_id,
+        except_user_id,
+        kind,
+        title,
+        body,
+        data,
+        Utc::now()
+    )
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result {
+        error!("Kurum bildirimi kaydedilemedi (org_id={}, kind={}): {}", organization_id, kind, e);
+    }
+}
+
+// Tamamlanan oyunun raporu hazır olduğunda oyunu yöneten öğretmeni bilgilendir
+pub async fn notify_game_report_ready(pool: &Pool<Postgres>, game_id: i32) {
+    let game = sqlx::query!(
+        r#"
+        SELECT g.code, g.host_id, qs.title
+        FROM games g
+        JOIN question_sets qs ON g.question_set_id = qs.id
+        WHERE g.id = $1
+        "#,
+        game_id
+    )
+    .fetch_optional(pool)
+    .await;
+
+    match game {
+        Ok(Some(game)) => {
+            notify(
+                pool,
+                game.host_id,
+                KIND_GAME_REPORT,
+                "Oyun raporu hazır",
+                &format!("\"{}\" oyununun ({}) sonuç raporu hazır", game.title, game.code),
+                serde_json::json!({"game_id": game_id, "game_code": game.code}),
+            )
+            .await;
+        }
+        Ok(None) => {}
+        Err(e) => error!("Oyun raporu bildirimi oluşturulamadı: {}", e),
+    }
+}
\ No newline at end of file
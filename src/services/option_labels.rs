@@ -0,0 +1,64 @@
+use serde_json::json;
+
+// Şık etiketleri
+// Oyunu açan öğretmen şıkların harfle, şekille veya renkle gösterilmesini seçer. Soru mesajında seçilen
+// stille birlikte her şıkkın harfi, şekli ve rengi gönderilir; istemciler düğmeleri bu bilgilere göre çizer.
+// Renkler renk körlüğüne uygun (Okabe-Ito) paletten seçilmiştir ve her şıkkın şekli de farklıdır, böylece
+// renk stili seçilse bile şıklar yalnızca renge bakılarak ayırt edilmek zorunda kalmaz.
+
+// Şık -> (şekil, renk, renk adı)
+const OPTION_MARKERS: [(&str, &str, &str, &str); 4] = [
+    ("A", "triangle", "#E69F00", "orange"),
+    ("B", "diamond", "#56B4E9", "sky_blue"),
+    ("C", "circle", "#009E73", "bluish_green"),
+    ("D", "square", "#CC79A7", "reddish_purple"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OptionLabelStyle {
+    #[default]
+    Letters,
+    Shapes,
+    Colors,
+}
+
+impl OptionLabelStyle {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OptionLabelStyle::Letters => "letters",
+            OptionLabelStyle::Shapes => "shapes",
+            OptionLabelStyle::Colors => "colors",
+        }
+    }
+
+    // Soru mesajına eklenen etiket bilgisi
+    pub fn payload(&self) -> serde_json::Value {
+        let mut options = serde_json::Map::new();
+        for (option, shape, color, color_name) in OPTION_MARKERS {
+            options.insert(option.to_string(), json!({
+                "letter": option,
+                "shape": shape,
+                "color": color,
+                "color_name": color_name
+            }));
+        }
+
+        json!({
+            "style": self.as_str(),
+            "options": options
+        })
+    }
+}
+
+impl std::str::FromStr for OptionLabelStyle {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "letters" => Ok(OptionLabelStyle::Letters),
+            "shapes" => Ok(OptionLabelStyle::Shapes),
+            "colors" => Ok(OptionLabelStyle::Colors),
+            _ => Err(format!("Bilinmeyen şık etiketi stili: {}", value)),
+        }
+    }
+}
\ No newline at end of file
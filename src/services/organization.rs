@@ -0,0 +1,93 @@
+use sqlx::{Pool, Postgres};
+
+use crate::db::models::Claims;
+
+// Kurum (okul) bazlı yetkilendirme yardımcıları
+
+// Admin uç noktalarının hangi kapsamda çalışacağı
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AdminScope {
+    // Sistem admini: tüm kullanıcılar ve içerikler
+    Global,
+    // Kurum admini: yalnızca kendi kurumunun kullanıcıları
+    Organization(i32),
+}
+
+impl AdminScope {
+    // Sorgu filtresi olarak kullanılacak kurum kimliği (global için None)
+    pub fn organization_id(&self) -> Option<i32> {
+        match self {
+            AdminScope::Global => None,
+            AdminScope::Organization(id) => Some(*id),
+        }
+    }
+
+    // Verilen kurumdaki bir kayıt bu kapsamda mı
+    pub fn covers(&self, organization_id: Option<i32>) -> bool {
+        match self {
+            AdminScope::Global => true,
+            AdminScope::Organization(id) => organization_id == Some(*id),
+        }
+    }
+}
+
+// Kullanıcının kurum üyeliği
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Membership {
+    pub organization_id: Option<i32>,
+    pub is_org_admin: bool,
+}
+
+// Token içindeki kurum üyeliği (üyelik değiştiğinde token sürümü artırılır)
+pub fn membership(claims: &Claims) -> Membership {
+    Membership {
+        organization_id: claims.organization_id,
+        is_org_admin: claims.is_org_admin,
+    }
+}
+
+// İsteği yapan kullanıcının admin kapsamını belirle, yetkisi yoksa None
+pub fn admin_scope(claims: &Claims) -> Option<AdminScope> {
+    if claims.role == "admin" {
+        return Some(AdminScope::Global);
+    }
+
+    match membership(claims) {
+        Membership { organization_id: Some(org_id), is_org_admin: true } => {
+            Some(AdminScope::Organization(org_id))
+        }
+        _ => None,
+    }
+}
+
+// Kullanıcı, herkese açık veya kurumla paylaşılmış bir soru setini görebilir mi
+pub async fn can_access_question_set(
+    pool: &Pool<Postgres>,
+    user_id: i32,
+    creator_id: i32,
+    visibility: &str,
+) -> bool {
+    if user_id == creator_id || visibility == "public" {
+        return true;
+    }
+
+    if visibility != "organization" {
+        return false;
+    }
+
+    let record = sqlx::query!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM users viewer
+            JOIN users creator ON creator.organization_id = viewer.organization_id
+            WHERE viewer.id = $1 AND creator.id = $2
+        ) as "same_org!"
+        "#,
+        user_id,
+        creator_id
+    )
+    .fetch_one(pool)
+    .await;
+
+    matches!(record, Ok(r) if r.same_org)
+}
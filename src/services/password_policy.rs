@@ -0,0 +1,293 @@
+use log::warn;
+use serde::Serialize;
+use sha1::{Digest, Sha1};
+use std::time::Duration;
+
+use crate::config::CONFIG;
+
+// Şifre politikası: yapılandırılabilir karmaşıklık kuralları, zxcvbn benzeri güç tahmini
+// ve isteğe bağlı haveibeenpwned k-anonimlik kontrolü.
+
+// Sık kullanılan ve tahmin edilmesi kolay şifre parçaları
+const COMMON_WORDS: &[&str] = &[
+    "password", "parola", "sifre", "şifre", "qwerty", "asdfgh", "zxcvbn", "123456", "111111",
+    "123123", "abc123", "iloveyou", "admin", "welcome", "letmein", "monkey", "dragon", "football",
+    "galatasaray", "fenerbahce", "besiktas", "trabzonspor", "istanbul", "ankara", "izmir",
+    "turkiye", "türkiye", "ogretmen", "öğretmen", "ogrenci", "öğrenci", "okul", "sorukayisi",
+];
+
+// Klavye sıraları (ardışık tuş dizileri için)
+const KEYBOARD_ROWS: &[&str] = &["qwertyuiopğü", "asdfghjklşi", "zxcvbnmöç", "1234567890"];
+
+// Şifre gücü tahmini (0: çok zayıf, 4: çok güçlü)
+#[derive(Debug, Clone, Serialize)]
+pub struct PasswordStrength {
+    pub score: u8,
+    pub label: &'static str,
+    pub guesses_log10: f64,
+    pub feedback: Vec<String>,
+}
+
+// Şifrenin reddedilme nedeni
+#[derive(Debug)]
+pub struct PasswordRejection {
+    pub message: String,
+    pub strength: PasswordStrength,
+}
+
+impl PasswordRejection {
+    // İstemciye dönecek JSON gövdesi
+    pub fn body(&self) -> serde_json::Value {
+        serde_json::json!({
+            "error": self.message,
+            "password_strength": self.strength
+        })
+    }
+}
+
+fn label_for(score: u8) -> &'static str {
+    match score {
+        0 => "very_weak",
+        1 => "weak",
+        2 => "fair",
+        3 => "strong",
+        _ => "very_strong",
+    }
+}
+
+// Tahmin edilmesi kolay kalıplara uyan karakterlerin katkısı (log10 tahmin sayısı)
+const PREDICTABLE_CHAR_WEIGHT: f64 = 0.2;
+
+// Kalıba uymayan bir karakterin tahmin sayısına katkısı (log10).
+// Küçük harfler genellikle kelimelerden geldiği için düşük tutulur, baştaki büyük harf de öyle.
+fn char_weight(c: char, index: usize) -> f64 {
+    if c.is_lowercase() {
+        0.7
+    } else if c.is_uppercase() {
+        if index == 0 { 0.7 } else { 1.2 }
+    } else if c.is_ascii_digit() {
+        1.0
+    } else {
+        1.5
+    }
+}
+
+// Tahmini kolay kısımları (tekrar, ardışık dizi, sözlük kelimesi) işaretle
+fn predictable_mask(chars: &[char], user_inputs: &[String], feedback: &mut Vec<String>) -> Vec<bool> {
+    let mut mask = vec![false; chars.len()];
+    let lowered: String = chars.iter().flat_map(|c| c.to_lowercase()).collect();
+    let lowered_chars: Vec<char> = lowered.chars().collect();
+
+    // Aynı karakterin tekrarı (aaa, 111)
+    let mut repeated = false;
+    for i in 2..chars.len() {
+        if chars[i] == chars[i - 1] && chars[i] == chars[i - 2] {
+            mask[i] = true;
+            mask[i - 1] = true;
+            repeated = true;
+        }
+    }
+    if repeated {
+        feedback.push("Aynı karakteri arka arkaya tekrarlamaktan kaçının".to_string());
+    }
+
+    // Ardışık karakterler (abc, 321) ve klavye dizileri (qwe, asd)
+    let mut sequential = false;
+    if lowered_chars.len() == chars.len() {
+        for i in 2..lowered_chars.len() {
+            let (a, b, c) = (lowered_chars[i - 2], lowered_chars[i - 1], lowered_chars[i]);
+            let step1 = b as i32 - a as i32;
+            let step2 = c as i32 - b as i32;
+            let is_sequence = step1 == step2 && step1.abs() == 1;
+            let is_keyboard = KEYBOARD_ROWS.iter().any(|row| {
+                let forward: String = [a, b, c].iter().collect();
+                let backward: String = [c, b, a].iter().collect();
+                row.contains(&forward) || row.contains(&backward)
+            });
+            if is_sequence || is_keyboard {
+                mask[i - 2] = true;
+                mask[i - 1] = true;
+                mask[i] = true;
+                sequential = true;
+            }
+        }
+    }
+    if sequential {
+        feedback.push("abc, 123 veya qwerty gibi ardışık dizilerden kaçının".to_string());
+    }
+
+    // Sık kullanılan kelimeler ve kullanıcının kendi bilgileri
+    let mark_word = |word: &str, mask: &mut [bool]| -> bool {
+        if word.chars().count() < 3 {
+            return false;
+        }
+        let mut found = false;
+        let mut start = 0;
+        while let Some(pos) = lowered[start..].find(word) {
+            let byte_start = start + pos;
+            let char_start = lowered[..byte_start].chars().count();
+            let char_len = word.chars().count();
+            if char_start + char_len <= mask.len() {
+                mask[char_start..char_start + char_len].iter_mut().for_each(|m| *m = true);
+            }
+            found = true;
+            start = byte_start + word.len();
+        }
+        found
+    };
+
+    let mut common = false;
+    for word in COMMON_WORDS {
+        common |= mark_word(word, &mut mask);
+    }
+    if common {
+        feedback.push("Sık kullanılan kelime ve şifrelerden kaçının".to_string());
+    }
+
+    let mut personal = false;
+    for input in user_inputs {
+        personal |= mark_word(&input.to_lowercase(), &mut mask);
+    }
+    if personal {
+        feedback.push("Şifrenizde kullanıcı adınızı veya e-posta adresinizi kullanmayın".to_string());
+    }
+
+    mask
+}
+
+// Şifrenin tahmin edilme zorluğunu hesapla
+pub fn estimate_strength(password: &str, user_inputs: &[&str]) -> PasswordStrength {
+    let chars: Vec<char> = password.chars().collect();
+    let mut feedback = Vec::new();
+
+    // E-posta adresinin yerel kısmı da kişisel bilgi sayılır
+    let inputs: Vec<String> = user_inputs
+        .iter()
+        .flat_map(|i| [i.to_string(), i.split('@').next().unwrap_or_default().to_string()])
+        .filter(|i| !i.is_empty())
+        .collect();
+
+    let mask = predictable_mask(&chars, &inputs, &mut feedback);
+
+    // Kalıplara uyan kısımlar tahmin sayısına çok az katkı sağlar
+    let guesses_log10: f64 = chars
+        .iter()
+        .zip(&mask)
+        .enumerate()
+        .map(|(i, (c, predictable))| if *predictable { PREDICTABLE_CHAR_WEIGHT } else { char_weight(*c, i) })
+        .sum();
+
+    let score = match guesses_log10 {
+        g if g < 3.0 => 0,
+        g if g < 6.0 => 1,
+        g if g < 8.0 => 2,
+        g if g < 10.0 => 3,
+        _ => 4,
+    };
+
+    if score < 3 && chars.len() < 12 {
+        feedback.push("Daha uzun bir şifre ya da birkaç kelimeden oluşan bir parola kullanın".to_string());
+    }
+
+    PasswordStrength {
+        score,
+        label: label_for(score),
+        guesses_log10: (guesses_log10 * 100.0).round() / 100.0,
+        feedback,
+    }
+}
+
+// Konfigürasyondaki karmaşıklık kurallarına uymayan maddeler
+pub fn policy_violations(password: &str) -> Vec<String> {
+    let mut violations = Vec::new();
+
+    if password.chars().count() < CONFIG.password_min_length {
+        violations.push(format!("Şifre en az {} karakter olmalıdır", CONFIG.password_min_length));
+    }
+    if CONFIG.password_require_uppercase && !password.chars().any(|c| c.is_uppercase()) {
+        violations.push("Şifre en az bir büyük harf içermelidir".to_string());
+    }
+    if CONFIG.password_require_lowercase && !password.chars().any(|c| c.is_lowercase()) {
+        violations.push("Şifre en az bir küçük harf içermelidir".to_string());
+    }
+    if CONFIG.password_require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+        violations.push("Şifre en az bir rakam içermelidir".to_string());
+    }
+    if CONFIG.password_require_symbol && password.chars().all(|c| c.is_alphanumeric()) {
+        violations.push("Şifre en az bir özel karakter içermelidir".to_string());
+    }
+
+    violations
+}
+
+// Şifrenin bilinen veri sızıntılarında kaç kez görüldüğü.
+// Yalnızca SHA-1 özetinin ilk 5 karakteri gönderilir (k-anonimlik); servis erişilemezse None döner.
+pub async fn breach_count(password: &str) -> Option<u64> {
+    let digest = hex::encode_upper(Sha1::digest(password.as_bytes()));
+    let (prefix, suffix) = digest.split_at(5);
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(3))
+        .build()
+        .ok()?;
+
+    let body = match client
+        .get(format!("{}{}", CONFIG.password_breach_api_url, prefix))
+        .header("Add-Padding", "true")
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+    {
+        Ok(response) => response.text().await.ok()?,
+        Err(e) => {
+            warn!("Şifre sızıntı kontrolü yapılamadı: {}", e);
+            return None;
+        }
+    };
+
+    let count = body
+        .lines()
+        .filter_map(|line| line.trim().split_once(':'))
+        .find(|(hash_suffix, _)| hash_suffix.eq_ignore_ascii_case(suffix))
+        .and_then(|(_, count)| count.trim().parse().ok())
+        .unwrap_or(0);
+
+    Some(count)
+}
+
+// Yeni şifreyi kurallara, güç eşiğine ve (etkinse) sızıntı listesine göre kontrol et
+pub async fn check_new_password(
+    password: &str,
+    user_inputs: &[&str],
+) -> Result<PasswordStrength, PasswordRejection> {
+    let strength = estimate_strength(password, user_inputs);
+
+    let violations = policy_violations(password);
+    if !violations.is_empty() {
+        return Err(PasswordRejection {
+            message: violations.join(". "),
+            strength,
+        });
+    }
+
+    if strength.score < CONFIG.password_min_score {
+        return Err(PasswordRejection {
+            message: "Şifre çok zayıf, lütfen daha güçlü bir şifre seçin".to_string(),
+            strength,
+        });
+    }
+
+    if CONFIG.password_breach_check {
+        if let Some(count) = breach_count(password).await {
+            if count > 0 {
+                return Err(PasswordRejection {
+                    message: "Bu şifre bilinen veri sızıntılarında yer alıyor, lütfen farklı bir şifre seçin"
+                        .to_string(),
+                    strength,
+                });
+            }
+        }
+    }
+
+    Ok(strength)
+}
\ No newline at end of file
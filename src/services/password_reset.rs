@@ -0,0 +1,40 @@
+use sqlx::{Pool, Postgres};
+
+use crate::config::CONFIG;
+
+// Şifre sıfırlama isteklerinin e-posta bazında hız sınırı.
+// Sınır, hesabın var olup olmadığından bağımsız uygulanır; böylece yanıt kayıtlı e-postaları ele vermez.
+
+// İsteği kaydet; son bir saatteki istek sayısı sınırı aşıyorsa false döner
+pub async fn record_request(pool: &Pool<Postgres>, email: &str) -> Result<bool, sqlx::Error> {
+    let email = email.trim().to_lowercase();
+
+    // Hız sınırı penceresinden çok eski kayıtlar gereksizdir
+    sqlx::query!("DELETE FROM password_reset_requests WHERE requested_at < NOW() - INTERVAL '1 day'")
+        .execute(pool)
+        .await?;
+
+    // Aynı e-posta için eşzamanlı istekler sırayla sayılır; kilit işlem bitince bırakılır
+    let mut tx = pool.begin().await?;
+    sqlx::query!("SELECT pg_advisory_xact_lock(hashtext($1))", format!("password_reset:{}", email))
+        .execute(&mut *tx)
+        .await?;
+
+    let inserted = sqlx::query!(
+        r#"
+        INSERT INTO password_reset_requests (email)
+        SELECT $1::varchar
+        WHERE (
+            SELECT COUNT(*) FROM password_reset_requests
+            WHERE email = $1::varchar AND requested_at > NOW() - INTERVAL '1 hour'
+        ) < $2
+        "#,
+        email,
+        CONFIG.password_reset_max_per_hour
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(inserted.rows_affected() > 0)
+}
@@ -0,0 +1,252 @@
+use chrono::{Duration, NaiveDate, Utc};
+use log::info;
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use std::collections::{BTreeSet, HashMap};
+
+use crate::db::repositories::read_pool;
+use crate::utils::date_range;
+
+// Platform istatistikleri zaman serisi
+// Toplama işi, tamamlanan her UTC günü için başlatılan oyun, yeni kullanıcı ve gönderilen cevap sayılarını
+// kurum bazında daily_stats tablosuna yazar (oyunlar ve cevaplar oyunu açan öğretmenin kurumuna sayılır).
+// Toplanan günler yeniden hesaplanmaz; böylece sonradan silinen oyun ve kullanıcılar geçmiş grafikleri
+// değiştirmez. Etkinlik olmayan günler için de kurumsuz (organization_id NULL) bir sıfır satırı yazılır.
+
+// Tarih aralığı verilmezse son 30 gün döndürülür
+const DEFAULT_RANGE_DAYS: i64 = 30;
+
+// En uzun sorgu aralığı
+const MAX_RANGE_DAYS: i64 = 366;
+
+// Sunucu kapalı kaldığında geriye dönük toplanacak en fazla gün
+const BACKFILL_DAYS: i64 = 90;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyStats {
+    pub date: NaiveDate,
+    pub games_played: i64,
+    pub new_users: i64,
+    pub answers_submitted: i64,
+}
+
+// Admin panelindeki anlık sayımlar
+#[derive(Debug, Clone)]
+pub struct CurrentCounts {
+    pub students: i64,
+    pub teachers: i64,
+    pub pending_teachers: i64,
+    pub unverified: i64,
+    pub question_sets: i64,
+    pub questions: i64,
+    pub games: i64,
+    pub active_games: i64,
+    pub players: i64,
+    pub active_connections: i64,
+}
+
+// Sorgudaki tarih aralığını çöz (bitiş günü dahil); geçersizse hata mesajı döner
+pub fn resolve_range(from: Option<NaiveDate>, to: Option<NaiveDate>) -> Result<(NaiveDate, NaiveDate), String> {
+    date_range::resolve(from, to, DEFAULT_RANGE_DAYS, MAX_RANGE_DAYS)
+}
+
+// Bir günün istatistiklerini hesaplayıp yaz (varsa önceki satırlar değiştirilir)
+pub async fn aggregate_day(pool: &Pool<Postgres>, day: NaiveDate) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!("DELETE FROM daily_stats WHERE day = $1", day)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO daily_stats (day, organization_id, games_played, new_users, answers_submitted)
+        SELECT $1, organization_id, SUM(games_played), SUM(new_users), SUM(answers_submitted)
+        FROM (
+            SELECT u.organization_id, COUNT(*) as games_played, 0 as new_users, 0 as answers_submitted
+            FROM games g
+            JOIN users u ON g.host_id = u.id
+            WHERE g.started_at >= $2 AND g.started_at < $3
+            GROUP BY u.organization_id
+            UNION ALL
+            SELECT organization_id, 0, COUNT(*), 0
+            FROM users
+            WHERE created_at >= $2 AND created_at < $3
+            GROUP BY organization_id
+            UNION ALL
+            SELECT u.organization_id, 0, 0, COUNT(*)
+            FROM player_answers pa
+            JOIN players p ON pa.player_id = p.id
+            JOIN games g ON p.game_id = g.id
+            JOIN users u ON g.host_id = u.id
+            WHERE pa.answered_at >= $2 AND pa.answered_at < $3
+            GROUP BY u.organization_id
+            UNION ALL
+            SELECT NULL, 0, 0, 0
+        ) s
+        GROUP BY organization_id
+        "#,
+        day,
+        date_range::day_start(day),
+        date_range::day_start(day + Duration::days(1))
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await
+}
+
+// Henüz toplanmamış tamamlanmış günleri topla, toplanan gün sayısını döndür
+pub async fn aggregate_missing_days(pool: &Pool<Postgres>) -> Result<usize, sqlx::Error> {
+    let yesterday = Utc::now().date_naive() - Duration::days(1);
+    let first = yesterday - Duration::days(BACKFILL_DAYS - 1);
+
+    let aggregated: BTreeSet<NaiveDate> = sqlx::query_scalar!("SELECT DISTINCT day FROM daily_stats WHERE day >= $1", first)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .collect();
+
+    let mut count = 0;
+    for day in first.iter_days().take_while(|day| *day <= yesterday) {
+        if !aggregated.contains(&day) {
+            aggregate_day(pool, day).await?;
+            count += 1;
+        }
+    }
+
+    if count > 0 {
+        info!("{} günün platform istatistikleri toplandı", count);
+    }
+
+    Ok(count)
+}
+
+// Toplanmış son gün (henüz hiç toplama yapılmadıysa None); panel için replikadan okunur
+pub async fn last_aggregated_day(pool: &Pool<Postgres>) -> Result<Option<NaiveDate>, sqlx::Error> {
+    sqlx::query_scalar!("SELECT MAX(day) FROM daily_stats").fetch_one(read_pool(pool)).await
+}
+
+// Aralıktaki günlük istatistikler (kurum verilirse yalnızca o kurum); toplanmamış günler sıfır döner
+pub async fn timeseries(
+    pool: &Pool<Postgres>,
+    organization_id: Option<i32>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<DailyStats>, sqlx::Error> {
+    let pool = read_pool(pool);
+    let rows = sqlx::query!(
+        r#"
+        SELECT day,
+               SUM(games_played)::bigint as "games_played!",
+               SUM(new_users)::bigint as "new_users!",
+               SUM(answers_submitted)::bigint as "answers_submitted!"
+        FROM daily_stats
+        WHERE day >= $1 AND day <= $2
+          AND ($3::int IS NULL OR organization_id = $3)
+        GROUP BY day
+        "#,
+        from,
+        to,
+        organization_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut by_day: HashMap<NaiveDate, DailyStats> = rows
+        .into_iter()
+        .map(|r| {
+            let stats = DailyStats {
+                date: r.day,
+                games_played: r.games_played,
+                new_users: r.new_users,
+                answers_submitted: r.answers_submitted,
+            };
+            (r.day, stats)
+        })
+        .collect();
+
+    Ok(from
+        .iter_days()
+        .take_while(|day| *day <= to)
+        .map(|day| {
+            by_day.remove(&day).unwrap_or(DailyStats {
+                date: day,
+                games_played: 0,
+                new_users: 0,
+                answers_submitted: 0,
+            })
+        })
+        .collect())
+}
+
+// Kullanıcı, içerik ve bağlantı sayıları (kurum verilirse yalnızca o kurum); sayımlar okuma replikasından yapılır
+pub async fn current_counts(pool: &Pool<Postgres>, organization_id: Option<i32>) -> Result<CurrentCounts, sqlx::Error> {
+    let pool = read_pool(pool);
+
+    // Kullanıcı sayıları
+    let users = sqlx::query!(
+        r#"
+        SELECT
+            COUNT(*) FILTER (WHERE role = 'student') as "student_count!",
+            COUNT(*) FILTER (WHERE role = 'teacher') as "teacher_count!",
+            COUNT(*) FILTER (WHERE role = 'teacher' AND is_approved = false) as "pending_teacher_count!",
+            COUNT(*) FILTER (WHERE is_email_verified = false) as "unverified_count!"
+        FROM users
+        WHERE $1::int IS NULL OR organization_id = $1
+        "#,
+        organization_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    // Oyun ve soru seti sayıları
+    let content = sqlx::query!(
+        r#"
+        WITH scoped_users AS (
+            SELECT id FROM users WHERE $1::int IS NULL OR organization_id = $1
+        )
+        SELECT
+            (SELECT COUNT(*) FROM question_sets
+             WHERE creator_id IN (SELECT id FROM scoped_users)) as "question_set_count!",
+            (SELECT COUNT(*) FROM questions q JOIN question_sets qs ON q.question_set_id = qs.id
+             WHERE qs.creator_id IN (SELECT id FROM scoped_users) AND q.deleted_at IS NULL) as "question_count!",
+            (SELECT COUNT(*) FROM games
+             WHERE host_id IN (SELECT id FROM scoped_users)) as "game_count!",
+            (SELECT COUNT(*) FROM games
+             WHERE status = 'active' AND host_id IN (SELECT id FROM scoped_users)) as "active_game_count!",
+            (SELECT COUNT(*) FROM players p JOIN games g ON p.game_id = g.id
+             WHERE g.host_id IN (SELECT id FROM scoped_users)) as "player_count!"
+        "#,
+        organization_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    // Son bir dakikada görülen bağlantılar
+    let active_connections = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) as "count!" FROM active_connections ac
+        LEFT JOIN games g ON ac.game_id = g.id
+        LEFT JOIN users host ON g.host_id = host.id
+        WHERE ac.last_seen > CURRENT_TIMESTAMP - INTERVAL '1 minute'
+          AND ($1::int IS NULL OR host.organization_id = $1)
+        "#,
+        organization_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(CurrentCounts {
+        students: users.student_count,
+        teachers: users.teacher_count,
+        pending_teachers: users.pending_teacher_count,
+        unverified: users.unverified_count,
+        question_sets: content.question_set_count,
+        questions: content.question_count,
+        games: content.game_count,
+        active_games: content.active_game_count,
+        players: content.player_count,
+        active_connections,
+    })
+}
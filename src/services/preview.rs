@@ -0,0 +1,213 @@
+use chrono::Utc;
+use lazy_static::lazy_static;
+use log::error;
+use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::db::repositories::{PgQuestionRepo, QuestionRecord, QuestionRepo};
+use crate::services::game::{answer_points, QuestionStart, VALID_ANSWERS};
+use crate::services::option_labels::OptionLabelStyle;
+
+// Soru seti önizlemesi
+// Öğretmen, setini canlıya almadan önce öğrenci gibi tek başına oynar. Sorular ve puanlama canlı oyunla
+// aynı kurallara göre sunulur, ancak önizleme yalnızca bellekte tutulur: oyun, oyuncu, cevap veya
+// istatistik kaydı oluşturulmaz.
+
+// Son işlemden bu kadar süre sonra önizleme silinir
+const PREVIEW_TTL: Duration = Duration::from_secs(30 * 60);
+
+lazy_static! {
+    // preview_id -> önizleme
+    static ref PREVIEWS: Mutex<HashMap<String, PreviewSession>> = Mutex::new(HashMap::new());
+}
+
+struct PreviewSession {
+    user_id: i32,
+    question_set_id: i32,
+    position: i32,
+    question: Option<OpenQuestion>, // Cevap bekleyen soru
+    score: i32,
+    correct_count: i32,
+    answered_count: i32,
+    last_activity: Instant,
+}
+
+struct OpenQuestion {
+    id: i32,
+    correct_option: String,
+    shown_at: Instant,
+}
+
+// Önizleme işlemlerinin reddedilme nedenleri
+#[derive(Debug)]
+pub enum PreviewError {
+    EmptySet,
+    NotFound,
+    QuestionNotActive,
+    InvalidAnswer,
+    Database,
+}
+
+impl PreviewError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            PreviewError::EmptySet => "Soru setinde henüz soru yok",
+            PreviewError::NotFound => "Önizleme bulunamadı veya süresi doldu",
+            PreviewError::QuestionNotActive => "Bu soru şu anda aktif değil",
+            PreviewError::InvalidAnswer => "Geçersiz cevap",
+            PreviewError::Database => "Önizleme sırasında bir hata oluştu",
+        }
+    }
+}
+
+impl From<sqlx::Error> for PreviewError {
+    fn from(e: sqlx::Error) -> Self {
+        error!("Veritabanı sorgu hatası: {}", e);
+        PreviewError::Database
+    }
+}
+
+// Önizlemede verilen cevabın sonucu; sıradaki soru yoksa önizleme tamamlanmıştır
+#[derive(Debug)]
+pub struct PreviewAnswer {
+    pub question_id: i32,
+    pub is_correct: bool,
+    pub correct_option: String,
+    pub points_earned: i32,
+    pub score: i32,
+    pub correct_count: i32,
+    pub answered_count: i32,
+    pub next_question: Option<QuestionStart>,
+}
+
+// Soruyu canlı oyundaki biçimiyle hazırla
+fn question_start(q: QuestionRecord) -> (QuestionStart, OpenQuestion) {
+    let started_at = Utc::now();
+    let duration = chrono::Duration::seconds(q.time_limit.unwrap_or(30).max(0) as i64);
+    let open = OpenQuestion {
+        id: q.id,
+        correct_option: q.correct_option.clone(),
+        shown_at: Instant::now(),
+    };
+
+    let question = QuestionStart {
+        question_id: q.id,
+        question_text: q.question_text,
+        options: q.options,
+        correct_option: q.correct_option,
+        time_limit: q.time_limit,
+        question_number: q.question_number,
+        total_questions: q.total_questions,
+        started_at,
+        deadline: started_at + duration,
+        sequence: None,
+        audio_url: None,
+        option_labels: OptionLabelStyle::default(),
+        host_notes: q.host_notes,
+    };
+
+    (question, open)
+}
+
+// Yeni önizleme başlat ve ilk soruyu döndür (erişim kontrolü çağıran tarafta yapılır)
+pub async fn start_preview(
+    pool: &Pool<Postgres>,
+    user_id: i32,
+    question_set_id: i32,
+) -> Result<(String, QuestionStart), PreviewError> {
+    let first = PgQuestionRepo::new(pool)
+        .next_after(question_set_id, None)
+        .await?
+        .ok_or(PreviewError::EmptySet)?;
+
+    let position = first.position;
+    let (question, open) = question_start(first);
+    let preview_id = Uuid::new_v4().to_string();
+
+    let mut previews = PREVIEWS.lock().unwrap_or_else(|e| e.into_inner());
+    previews.retain(|_, p| p.last_activity.elapsed() < PREVIEW_TTL);
+    previews.insert(preview_id.clone(), PreviewSession {
+        user_id,
+        question_set_id,
+        position,
+        question: Some(open),
+        score: 0,
+        correct_count: 0,
+        answered_count: 0,
+        last_activity: Instant::now(),
+    });
+
+    Ok((preview_id, question))
+}
+
+// Açık soruya cevap ver; cevap süresi sunucuda, sorunun gösterildiği andan itibaren ölçülür
+pub async fn submit_answer(
+    pool: &Pool<Postgres>,
+    user_id: i32,
+    preview_id: &str,
+    question_id: i32,
+    answer: &str,
+) -> Result<PreviewAnswer, PreviewError> {
+    if !VALID_ANSWERS.contains(&answer) {
+        return Err(PreviewError::InvalidAnswer);
+    }
+
+    // Soru kilit altında kapatılır; aynı soruya gelen ikinci cevap reddedilir
+    let (question_set_id, position, mut result) = {
+        let mut previews = PREVIEWS.lock().unwrap_or_else(|e| e.into_inner());
+        let preview = previews
+            .get_mut(preview_id)
+            .filter(|p| p.user_id == user_id && p.last_activity.elapsed() < PREVIEW_TTL)
+            .ok_or(PreviewError::NotFound)?;
+
+        if preview.question.as_ref().map(|q| q.id) != Some(question_id) {
+            return Err(PreviewError::QuestionNotActive);
+        }
+        let open = preview.question.take().ok_or(PreviewError::QuestionNotActive)?;
+
+        let is_correct = answer == open.correct_option;
+        let response_time_ms = open.shown_at.elapsed().as_millis().min(i32::MAX as u128) as i32;
+        let points_earned = answer_points(is_correct, response_time_ms);
+
+        preview.score += points_earned;
+        preview.answered_count += 1;
+        if is_correct {
+            preview.correct_count += 1;
+        }
+        preview.last_activity = Instant::now();
+
+        (preview.question_set_id, preview.position, PreviewAnswer {
+            question_id,
+            is_correct,
+            correct_option: open.correct_option,
+            points_earned,
+            score: preview.score,
+            correct_count: preview.correct_count,
+            answered_count: preview.answered_count,
+            next_question: None,
+        })
+    };
+
+    let next = PgQuestionRepo::new(pool).next_after(question_set_id, Some(position)).await?;
+
+    let mut previews = PREVIEWS.lock().unwrap_or_else(|e| e.into_inner());
+    match next {
+        Some(q) => {
+            if let Some(preview) = previews.get_mut(preview_id) {
+                preview.position = q.position;
+                let (question, open) = question_start(q);
+                preview.question = Some(open);
+                result.next_question = Some(question);
+            }
+        }
+        // Sorular bitti, önizleme kapanır
+        None => {
+            previews.remove(preview_id);
+        }
+    }
+
+    Ok(result)
+}
\ No newline at end of file
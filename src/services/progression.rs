@@ -0,0 +1,144 @@
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+
+use crate::services::cosmetics::{self, EarnedBadge};
+use crate::services::seasons::{self, RatingChange};
+
+// Deneyim puanı (XP) ve seviye sistemi
+// Kayıtlı oyuncular tamamladıkları her oyundan doğruluk oranlarıyla ölçeklenen XP kazanır.
+
+// Oyunu tamamlamanın sabit XP'si
+const XP_COMPLETION: i64 = 20;
+// Tüm soruları doğru cevaplayan oyuncunun ek olarak kazandığı en yüksek XP
+const XP_ACCURACY_MAX: i64 = 80;
+// Seviye eşikleri bu değerin üçgensel katlarıdır (0, 100, 300, 600, 1000, ...)
+const XP_LEVEL_STEP: i64 = 100;
+
+// Toplam XP'ye karşılık gelen seviye (1'den başlar)
+pub fn level_for_xp(xp: i64) -> i32 {
+    let mut level = 1;
+    while xp >= xp_for_level(level + 1) {
+        level += 1;
+    }
+    level
+}
+
+// Seviyeye ulaşmak için gereken toplam XP
+pub fn xp_for_level(level: i32) -> i64 {
+    let n = (level.max(1) - 1) as i64;
+    XP_LEVEL_STEP * n * (n + 1) / 2
+}
+
+// Bir oyundan kazanılan XP
+fn xp_for_game(correct_count: i64, question_count: i64) -> i64 {
+    if question_count <= 0 {
+        return XP_COMPLETION;
+    }
+    let accuracy = correct_count.min(question_count) as f64 / question_count as f64;
+    XP_COMPLETION + (XP_ACCURACY_MAX as f64 * accuracy).round() as i64
+}
+
+// Oyuncuya verilen XP
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct XpAward {
+    pub user_id: i32,
+    pub player_id: i32,
+    pub xp_earned: i64,
+    pub total_xp: i64,
+    pub level: i32,
+    pub leveled_up: bool,
+}
+
+// Oyun sonunda dağıtılan ödüller
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct GameRewards {
+    pub xp_awards: Vec<XpAward>,
+    pub badges_earned: Vec<EarnedBadge>,
+    pub rating_changes: Vec<RatingChange>,
+}
+
+// Tamamlanan oyundaki kayıtlı oyunculara XP ver (her oyuncu kaydı için bir kez)
+pub async fn award_game_xp(pool: &Pool<Postgres>, game_id: i32) -> Result<Vec<XpAward>, sqlx::Error> {
+    let players = sqlx::query!(
+        r#"
+        SELECT p.id as player_id, p.user_id as "user_id!",
+               (SELECT COUNT(*) FROM player_answers pa WHERE pa.player_id = p.id AND pa.is_correct) as "correct_count!",
+               (SELECT COUNT(*) FROM game_question_ids(g.id)) as "question_count!"
+        FROM players p
+        JOIN games g ON p.game_id = g.id
+        WHERE p.game_id = $1 AND p.user_id IS NOT NULL AND p.is_active = true AND p.xp_earned IS NULL
+        "#,
+        game_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut awards = Vec::new();
+
+    for p in players {
+        let xp = xp_for_game(p.correct_count, p.question_count);
+
+        let mut tx = pool.begin().await?;
+
+        // Aynı oyuncu kaydına iki kez XP verilmesini engelle
+        let marked = sqlx::query!(
+            "UPDATE players SET xp_earned = $1 WHERE id = $2 AND xp_earned IS NULL",
+            xp as i32,
+            p.player_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if marked.rows_affected() == 0 {
+            continue;
+        }
+
+        let user = sqlx::query!(
+            "UPDATE users SET xp = xp + $1 WHERE id = $2 RETURNING xp",
+            xp,
+            p.user_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        let level = level_for_xp(user.xp);
+        let leveled_up = level > level_for_xp(user.xp - xp);
+        if leveled_up {
+            info!("Seviye atlandı: user_id={}, level={}", p.user_id, level);
+        }
+
+        awards.push(XpAward {
+            user_id: p.user_id,
+            player_id: p.player_id,
+            xp_earned: xp,
+            total_xp: user.xp,
+            level,
+            leveled_up,
+        });
+    }
+
+    Ok(awards)
+}
+
+// Oyun tamamlandığında XP, rozet ve sezon puanlarını dağıt; hatalar oyunun bitişini engellemez
+pub async fn award_game_rewards(pool: &Pool<Postgres>, game_id: i32) -> GameRewards {
+    let xp_awards = award_game_xp(pool, game_id).await.unwrap_or_else(|e| {
+        error!("XP verilirken hata (game_id={}): {}", game_id, e);
+        Vec::new()
+    });
+
+    let badges_earned = cosmetics::award_game_badges(pool, game_id).await.unwrap_or_else(|e| {
+        error!("Rozetler verilirken hata (game_id={}): {}", game_id, e);
+        Vec::new()
+    });
+
+    let rating_changes = seasons::rate_game(pool, game_id).await.unwrap_or_else(|e| {
+        error!("Sezon puanları güncellenirken hata (game_id={}): {}", game_id, e);
+        Vec::new()
+    });
+
+    GameRewards { xp_awards, badges_earned, rating_changes }
+}
\ No newline at end of file
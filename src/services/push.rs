@@ -0,0 +1,298 @@
+use crate::config::CONFIG;
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use lazy_static::lazy_static;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+
+// Push bildirim servisi (FCM HTTP v1 ve Web Push)
+// FCM istekleri servis hesabıyla alınan OAuth erişim tokenı ile yetkilendirilir.
+// Web Push bildirimleri yük taşımadan (VAPID imzalı boş istek) gönderilir;
+// istemci bildirimi aldığında içeriği API'den çeker.
+
+// Bildirim gönderiminin sonucu
+enum PushOutcome {
+    Delivered,
+    // Abonelik artık geçerli değil, silinmeli
+    Expired,
+}
+
+// VAPID JWT içeriği
+#[derive(Serialize)]
+struct VapidClaims {
+    aud: String,
+    exp: i64,
+    sub: String,
+}
+
+// FCM erişim tokenı için istenen yetki
+const FCM_SCOPE: &str = "https://www.googleapis.com/auth/firebase.messaging";
+
+lazy_static! {
+    // Tüm PushService örnekleri aynı erişim tokenını paylaşır
+    static ref FCM_ACCESS_TOKEN: tokio::sync::Mutex<Option<(String, DateTime<Utc>)>> =
+        tokio::sync::Mutex::new(None);
+}
+
+// Firebase servis hesabı anahtar dosyasının kullanılan alanları
+#[derive(Deserialize)]
+struct ServiceAccount {
+    project_id: String,
+    client_email: String,
+    private_key: String,
+    token_uri: String,
+}
+
+// Servis hesabı OAuth JWT içeriği
+#[derive(Serialize)]
+struct ServiceAccountClaims {
+    iss: String,
+    scope: String,
+    aud: String,
+    iat: i64,
+    exp: i64,
+}
+
+#[derive(Deserialize)]
+struct AccessTokenResponse {
+    access_token: String,
+    expires_in: i64,
+}
+
+pub struct PushService {
+    client: reqwest::Client,
+    fcm_account: Option<ServiceAccount>,
+    vapid_key: Option<EncodingKey>,
+}
+
+impl PushService {
+    pub fn new() -> Self {
+        // VAPID özel anahtarı tanımlıysa Web Push etkinleşir
+        let vapid_key = if CONFIG.vapid_private_key_path.is_empty() {
+            None
+        } else {
+            std::fs::read(&CONFIG.vapid_private_key_path)
+                .map_err(anyhow::Error::from)
+                .and_then(|pem| EncodingKey::from_ec_pem(&pem).map_err(anyhow::Error::from))
+                .map_err(|e| error!("VAPID anahtarı okunamadı: {}", e))
+                .ok()
+        };
+
+        // Servis hesabı anahtarı tanımlıysa FCM etkinleşir
+        let fcm_account = if CONFIG.fcm_service_account_path.is_empty() {
+            None
+        } else {
+            std::fs::read(&CONFIG.fcm_service_account_path)
+                .map_err(anyhow::Error::from)
+                .and_then(|json| serde_json::from_slice(&json).map_err(anyhow::Error::from))
+                .map_err(|e| error!("FCM servis hesabı okunamadı: {}", e))
+                .ok()
+        };
+
+        PushService {
+            client: reqwest::Client::new(),
+            fcm_account,
+            vapid_key,
+        }
+    }
+
+    // Kullanıcının tüm cihazlarına bildirim gönder, ulaşan bildirim sayısını döndür
+    pub async fn send_to_user(
+        &self,
+        pool: &Pool<Postgres>,
+        user_id: i32,
+        title: &str,
+        body: &str,
+        data: serde_json::Value,
+    ) -> usize {
+        let subscriptions = match sqlx::query!(
+            "SELECT id, platform, token FROM push_subscriptions WHERE user_id = $1",
+            user_id
+        )
+        .fetch_all(pool)
+        .await
+        {
+            Ok(subscriptions) => subscriptions,
+            Err(e) => {
+                error!("Push abonelikleri alınamadı: {}", e);
+                return 0;
+            }
+        };
+
+        let mut delivered = 0;
+
+        for subscription in subscriptions {
+            let result = match subscription.platform.as_str() {
+                "fcm" => self.send_fcm(&subscription.token, title, body, &data).await,
+                "webpush" => self.send_webpush(&subscription.token).await,
+                _ => continue,
+            };
+
+            match result {
+                Ok(PushOutcome::Delivered) => {
+                    delivered += 1;
+                    let _ = sqlx::query!(
+                        "UPDATE push_subscriptions SET last_used_at = $1 WHERE id = $2",
+                        Utc::now(),
+                        subscription.id
+                    )
+                    .execute(pool)
+                    .await;
+                }
+                Ok(PushOutcome::Expired) => {
+                    info!("Geçersiz push aboneliği silindi: id={}", subscription.id);
+                    let _ = sqlx::query!("DELETE FROM push_subscriptions WHERE id = $1", subscription.id)
+                        .execute(pool)
+                        .await;
+                }
+                Err(e) => {
+                    warn!("Push bildirimi gönderilemedi (id={}): {}", subscription.id, e);
+                }
+            }
+        }
+
+        delivered
+    }
+
+    // FCM HTTP v1 API üzerinden bildirim gönder
+    async fn send_fcm(
+        &self,
+        token: &str,
+        title: &str,
+        body: &str,
+        data: &serde_json::Value,
+    ) -> Result<PushOutcome, anyhow::Error> {
+        let account = self
+            .fcm_account
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("FCM yapılandırılmamış"))?;
+        let access_token = self.fcm_access_token(account).await?;
+
+        // v1 API veri alanında yalnızca metin değerleri kabul eder
+        let data: serde_json::Map<String, serde_json::Value> = data
+            .as_object()
+            .into_iter()
+            .flatten()
+            .map(|(key, value)| {
+                let value = match value {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                (key.clone(), serde_json::Value::String(value))
+            })
+            .collect();
+
+        let response = self
+            .client
+            .post(format!(
+                "https://fcm.googleapis.com/v1/projects/{}/messages:send",
+                account.project_id
+            ))
+            .bearer_auth(access_token)
+            .json(&serde_json::json!({
+                "message": {
+                    "token": token,
+                    "notification": {
+                        "title": title,
+                        "body": body
+                    },
+                    "data": data
+                }
+            }))
+            .send()
+            .await?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(PushOutcome::Delivered);
+        }
+
+        // Geçersiz tokenlar 404 (UNREGISTERED) veya 400 INVALID_ARGUMENT olarak döner
+        let result: serde_json::Value = response.json().await.unwrap_or_default();
+        let error_status = result["error"]["status"].as_str().unwrap_or("");
+        let unregistered = result["error"]["details"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .any(|detail| detail["errorCode"] == "UNREGISTERED");
+        if status.as_u16() == 404 || unregistered || error_status == "INVALID_ARGUMENT" {
+            return Ok(PushOutcome::Expired);
+        }
+
+        Err(anyhow::anyhow!("FCM yanıtı: {} {}", status, error_status))
+    }
+
+    // Servis hesabıyla OAuth erişim tokenı al; süresi dolana kadar önbellekten kullanılır
+    async fn fcm_access_token(&self, account: &ServiceAccount) -> Result<String, anyhow::Error> {
+        let mut cached = FCM_ACCESS_TOKEN.lock().await;
+        if let Some((token, expires_at)) = cached.as_ref() {
+            if *expires_at > Utc::now() + Duration::minutes(1) {
+                return Ok(token.clone());
+            }
+        }
+
+        let now = Utc::now();
+        let claims = ServiceAccountClaims {
+            iss: account.client_email.clone(),
+            scope: FCM_SCOPE.to_string(),
+            aud: account.token_uri.clone(),
+            iat: now.timestamp(),
+            exp: (now + Duration::hours(1)).timestamp(),
+        };
+        let key = EncodingKey::from_rsa_pem(account.private_key.as_bytes())?;
+        let assertion = encode(&Header::new(Algorithm::RS256), &claims, &key)?;
+
+        let response = self
+            .client
+            .post(&account.token_uri)
+            .form(&[
+                ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                ("assertion", assertion.as_str()),
+            ])
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!("FCM erişim tokenı alınamadı: {}", response.status()));
+        }
+
+        let token: AccessTokenResponse = response.json().await?;
+        *cached = Some((token.access_token.clone(), now + Duration::seconds(token.expires_in)));
+        Ok(token.access_token)
+    }
+
+    // Web Push uç noktasına VAPID imzalı boş bildirim gönder
+    async fn send_webpush(&self, endpoint: &str) -> Result<PushOutcome, anyhow::Error> {
+        let key = self
+            .vapid_key
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Web Push yapılandırılmamış"))?;
+
+        let url = url::Url::parse(endpoint)?;
+        let claims = VapidClaims {
+            aud: url.origin().ascii_serialization(),
+            exp: (Utc::now() + Duration::hours(12)).timestamp(),
+            sub: CONFIG.vapid_subject.clone(),
+        };
+        let jwt = encode(&Header::new(Algorithm::ES256), &claims, key)?;
+
+        let response = self
+            .client
+            .post(endpoint)
+            .header("TTL", "86400")
+            .header("Content-Length", "0")
+            .header(
+                "Authorization",
+                format!("vapid t={}, k={}", jwt, CONFIG.vapid_public_key),
+            )
+            .send()
+            .await?;
+
+        match response.status().as_u16() {
+            200..=299 => Ok(PushOutcome::Delivered),
+            404 | 410 => Ok(PushOutcome::Expired),
+            status => Err(anyhow::anyhow!("Web Push yanıtı: {}", status)),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,299 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+use crate::db::models::{BulkQuestionOperation, PatchQuestionDto};
+use crate::services::collaboration;
+
+// Soru düzenleme
+// Kısmi güncellemenin (PATCH) alan doğrulaması ve sorgusu ile toplu oluşturma/güncelleme/silme burada toplanır.
+// Toplu işlemler tek bir veritabanı işleminde uygulanır: bir öğe bile geçersizse veya sette bulunamazsa hiçbir
+// değişiklik kaydedilmez ve her öğenin sonucu ayrı ayrı döner. Böylece içe aktarma ve zengin editörler yarım
+// uygulanmış bir setle kalmaz, hatalı öğeleri düzeltip aynı isteği tekrar gönderebilir.
+
+// Soru başına host notlarının en fazla uzunluğu (karakter)
+pub const MAX_HOST_NOTES_LEN: usize = 2000;
+
+// Tek istekte en fazla işlem
+pub const MAX_BULK_OPERATIONS: usize = 500;
+
+// Güncellenen sorunun son hali
+#[derive(Debug, Serialize)]
+pub struct EditedQuestion {
+    pub id: i32,
+    pub question_set_id: i32,
+    pub question_text: String,
+    pub option_a: String,
+    pub option_b: String,
+    pub option_c: String,
+    pub option_d: String,
+    pub correct_option: String,
+    pub points: Option<i32>,
+    pub time_limit: Option<i32>,
+    pub position: i32,
+    pub host_notes: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// Toplu işlemdeki bir öğenin sonucu
+#[derive(Debug, Serialize)]
+pub struct BulkItemResult {
+    pub index: usize,
+    pub op: &'static str,
+    pub id: Option<i32>,
+    // created, updated, deleted; hata durumunda invalid veya not_found, işlem geri alındıysa skipped
+    pub status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+#[derive(Debug)]
+pub struct BulkOutcome {
+    pub applied: bool,
+    pub results: Vec<BulkItemResult>,
+}
+
+// Gönderilen alanları doğrula; hatalar alan adına göre toplanır
+pub fn validate_fields(patch: &PatchQuestionDto) -> serde_json::Map<String, serde_json::Value> {
+    let mut errors = serde_json::Map::new();
+
+    let texts = [
+        ("question_text", &patch.question_text),
+        ("option_a", &patch.option_a),
+        ("option_b", &patch.option_b),
+        ("option_c", &patch.option_c),
+        ("option_d", &patch.option_d),
+    ];
+    for (field, value) in texts {
+        if value.as_deref().is_some_and(|v| v.trim().is_empty()) {
+            errors.insert(field.to_string(), "Boş bırakılamaz".into());
+        }
+    }
+
+    if patch
+        .correct_option
+        .as_deref()
+        .is_some_and(|o| !["A", "B", "C", "D"].contains(&o.to_uppercase().as_str()))
+    {
+        errors.insert("correct_option".to_string(), "Doğru cevap A, B, C veya D olmalıdır".into());
+    }
+    if patch.points.is_some_and(|p| p < 0) {
+        errors.insert("points".to_string(), "Puan negatif olamaz".into());
+    }
+    if patch.time_limit.is_some_and(|t| t <= 0) {
+        errors.insert("time_limit".to_string(), "Süre sınırı pozitif olmalıdır".into());
+    }
+    if patch.position.is_some_and(|p| p < 0) {
+        errors.insert("position".to_string(), "Sıra numarası negatif olamaz".into());
+    }
+    if patch
+        .host_notes
+        .as_deref()
+        .is_some_and(|n| n.trim().chars().count() > MAX_HOST_NOTES_LEN)
+    {
+        errors.insert("host_notes".to_string(), "Host notları en fazla 2000 karakter olabilir".into());
+    }
+
+    errors
+}
+
+// Yeni soruda bulunması gereken alanlar
+fn validate_required(fields: &PatchQuestionDto) -> serde_json::Map<String, serde_json::Value> {
+    let mut errors = validate_fields(fields);
+
+    let required = [
+        ("question_text", fields.question_text.is_some()),
+        ("option_a", fields.option_a.is_some()),
+        ("option_b", fields.option_b.is_some()),
+        ("option_c", fields.option_c.is_some()),
+        ("option_d", fields.option_d.is_some()),
+        ("correct_option", fields.correct_option.is_some()),
+        ("position", fields.position.is_some()),
+    ];
+    for (field, present) in required {
+        if !present {
+            errors.insert(field.to_string(), "Zorunlu alan".into());
+        }
+    }
+
+    errors
+}
+
+// Sorunun gönderilen alanlarını güncelle. `unmodified_since` verilirse soru o zamandan sonra değişmemiş olmalı
+// (saniye hassasiyetinde); değişmişse veya soru yoksa None döner.
+pub async fn patch<'e, E>(
+    executor: E,
+    question_id: i32,
+    patch: &PatchQuestionDto,
+    unmodified_since: Option<DateTime<Utc>>,
+) -> Result<Option<EditedQuestion>, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = Postgres>,
+{
+    let correct_option = patch.correct_option.as_deref().map(str::to_uppercase);
+    // Boş not gönderilmesi notları siler
+    let host_notes = patch.host_notes.as_deref().map(str::trim);
+
+    sqlx::query_as!(
+        EditedQuestion,
+        r#"
+        UPDATE questions
+        SET question_text = COALESCE($1, question_text),
+            option_a = COALESCE($2, option_a),
+            option_b = COALESCE($3, option_b),
+            option_c = COALESCE($4, option_c),
+            option_d = COALESCE($5, option_d),
+            correct_option = COALESCE($6, correct_option),
+            points = COALESCE($7, points),
+            time_limit = COALESCE($8, time_limit),
+            position = COALESCE($9, position),
+            host_notes = CASE WHEN $10::text IS NULL THEN host_notes ELSE NULLIF($10, '') END
+        WHERE id = $11 AND ($12::timestamptz IS NULL OR date_trunc('second', updated_at) <= $12)
+        RETURNING id, question_set_id, question_text, option_a, option_b, option_c, option_d,
+                  correct_option, points, time_limit, position, host_notes, updated_at
+        "#,
+        patch.question_text,
+        patch.option_a,
+        patch.option_b,
+        patch.option_c,
+        patch.option_d,
+        correct_option,
+        patch.points,
+        patch.time_limit,
+        patch.position,
+        host_notes,
+        question_id,
+        unmodified_since
+    )
+    .fetch_optional(executor)
+    .await
+}
+
+// Toplu işlemleri tek bir veritabanı işleminde uygula
+pub async fn apply_bulk(
+    pool: &Pool<Postgres>,
+    set_id: i32,
+    user_id: i32,
+    operations: &[BulkQuestionOperation],
+) -> Result<BulkOutcome, sqlx::Error> {
+    // Önce tüm öğeler doğrulanır; geçersiz öğe varsa veritabanına hiç gidilmez
+    let mut results: Vec<BulkItemResult> = operations
+        .iter()
+        .enumerate()
+        .map(|(index, operation)| {
+            let (op, id, errors) = match operation {
+                BulkQuestionOperation::Create(fields) => ("create", None, validate_required(fields)),
+                BulkQuestionOperation::Update { id, fields } => ("update", Some(*id), validate_fields(fields)),
+                BulkQuestionOperation::Delete { id } => ("delete", Some(*id), serde_json::Map::new()),
+            };
+            let valid = errors.is_empty();
+            BulkItemResult {
+                index,
+                op,
+                id,
+                status: if valid { "skipped" } else { "invalid" },
+                fields: (!valid).then_some(errors),
+            }
+        })
+        .collect();
+
+    if results.iter().any(|r| r.status == "invalid") {
+        return Ok(BulkOutcome { applied: false, results });
+    }
+
+    let mut tx = pool.begin().await?;
+    let mut failed = false;
+
+    for (operation, result) in operations.iter().zip(results.iter_mut()) {
+        match operation {
+            BulkQuestionOperation::Create(fields) => {
+                let host_notes = fields.host_notes.as_deref().map(str::trim).filter(|n| !n.is_empty());
+                let id = sqlx::query_scalar!(
+                    r#"
+                    INSERT INTO questions
+                    (question_set_id, question_text, option_a, option_b, option_c, option_d,
+                    correct_option, points, time_limit, position, host_notes)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                    RETURNING id
+                    "#,
+                    set_id,
+                    fields.question_text,
+                    fields.option_a,
+                    fields.option_b,
+                    fields.option_c,
+                    fields.option_d,
+                    fields.correct_option.as_deref().map(str::to_uppercase),
+                    fields.points.unwrap_or(100),
+                    fields.time_limit.unwrap_or(30),
+                    fields.position,
+                    host_notes
+                )
+                .fetch_one(&mut *tx)
+                .await?;
+                result.id = Some(id);
+                result.status = "created";
+            }
+            BulkQuestionOperation::Update { id, fields } => {
+                // Yalnızca bu setteki sorular güncellenebilir
+                let in_set = sqlx::query_scalar!(
+                    "SELECT id FROM questions WHERE id = $1 AND question_set_id = $2 AND deleted_at IS NULL",
+                    id,
+                    set_id
+                )
+                .fetch_optional(&mut *tx)
+                .await?;
+                let updated = match in_set {
+                    Some(_) => patch(&mut *tx, *id, fields, None).await?,
+                    None => None,
+                };
+                result.status = if updated.is_some() { "updated" } else { "not_found" };
+            }
+            BulkQuestionOperation::Delete { id } => {
+                // Tek soru silmede olduğu gibi geri alma süresi boyunca saklanır
+                let deleted = sqlx::query!(
+                    r#"
+                    UPDATE questions SET deleted_at = $1
+                    WHERE id = $2 AND question_set_id = $3 AND deleted_at IS NULL
+                    "#,
+                    Utc::now(),
+                    id,
+                    set_id
+                )
+                .execute(&mut *tx)
+                .await?;
+                result.status = if deleted.rows_affected() > 0 { "deleted" } else { "not_found" };
+            }
+        }
+        failed |= result.status == "not_found";
+    }
+
+    if failed {
+        tx.rollback().await?;
+        for result in results.iter_mut().filter(|r| r.status != "not_found") {
+            result.status = "skipped";
+            if result.op == "create" {
+                result.id = None;
+            }
+        }
+        return Ok(BulkOutcome { applied: false, results });
+    }
+
+    sqlx::query!("UPDATE question_sets SET updated_at = $1 WHERE id = $2", Utc::now(), set_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    // Düzenleme geçmişi işlem dışında kaydedilir
+    for result in &results {
+        let action = match result.status {
+            "created" => collaboration::EDIT_CREATE,
+            "updated" => collaboration::EDIT_UPDATE,
+            _ => collaboration::EDIT_DELETE,
+        };
+        if let Some(id) = result.id {
+            collaboration::record_edit(pool, set_id, id, user_id, action).await;
+        }
+    }
+
+    Ok(BulkOutcome { applied: true, results })
+}
\ No newline at end of file
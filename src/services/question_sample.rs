@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+// Rastgele soru seçimi
+// Öğretmen oyunda setin tüm soruları yerine rastgele N soru sorulmasını seçebilir. Seçim isteğe bağlı olarak
+// müfredat konusuna veya zorluğa göre tabakalandırılır: her gruptan, grubun setteki payı oranında soru alınır.
+// Sorular oyun başlarken seçilir (bkz. game_playlist::resolve); oyun istatistikleri yalnızca bu sorulara göre
+// hesaplanır.
+
+// Seçilebilecek en fazla soru sayısı
+pub const MAX_SAMPLE_COUNT: i32 = 500;
+
+// Soruların hangi gruplara göre dengeli seçileceği
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stratum {
+    #[default]
+    None,
+    // Sorunun bağlı olduğu ilk müfredat konusu; konusu olmayan sorular ayrı bir grup oluşturur
+    Topic,
+    // Oynanmış oyunlardaki cevaplara göre kolay / orta / zor; hiç cevaplanmamış sorular orta sayılır
+    Difficulty,
+}
+
+impl Stratum {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Stratum::None => "none",
+            Stratum::Topic => "topic",
+            Stratum::Difficulty => "difficulty",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuestionSample {
+    pub count: i32,
+    #[serde(default)]
+    pub stratify_by: Stratum,
+}
+
+impl QuestionSample {
+    // Ayarlardan gelen seçimi doğrula; count 0 ise seçim kaldırılır ve tüm sorular sorulur (None)
+    pub fn from_settings(value: &serde_json::Value) -> Result<Option<Self>, &'static str> {
+        let sample: QuestionSample = serde_json::from_value(value.clone()).map_err(|_| {
+            "Soru seçimi {\"count\": 10, \"stratify_by\": \"none\" | \"topic\" | \"difficulty\"} biçiminde olmalıdır"
+        })?;
+
+        match sample.count {
+            0 => Ok(None),
+            1..=MAX_SAMPLE_COUNT => Ok(Some(sample)),
+            _ => Err("Seçilecek soru sayısı 1-500 arasında olmalıdır"),
+        }
+    }
+
+    pub fn to_value(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+}
@@ -0,0 +1,230 @@
+use chrono::{Datelike, NaiveDate, Utc};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+use crate::config::CONFIG;
+
+// Kota uygulanan kullanım türleri
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuotaMetric {
+    GamesCreated,
+    AiGenerations,
+    MediaStorageBytes,
+}
+
+// Kota sayaçlarının sıfırlanma periyodu
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum QuotaPeriod {
+    Daily,
+    Monthly,
+    Lifetime,
+}
+
+impl QuotaMetric {
+    pub const ALL: [QuotaMetric; 3] = [
+        QuotaMetric::GamesCreated,
+        QuotaMetric::AiGenerations,
+        QuotaMetric::MediaStorageBytes,
+    ];
+
+    // Veritabanında kullanılan anahtar
+    pub fn key(&self) -> &'static str {
+        match self {
+            QuotaMetric::GamesCreated => "games_created",
+            QuotaMetric::AiGenerations => "ai_generations",
+            QuotaMetric::MediaStorageBytes => "media_storage_bytes",
+        }
+    }
+
+    fn period(&self) -> QuotaPeriod {
+        match self {
+            QuotaMetric::GamesCreated => QuotaPeriod::Daily,
+            QuotaMetric::AiGenerations => QuotaPeriod::Monthly,
+            QuotaMetric::MediaStorageBytes => QuotaPeriod::Lifetime,
+        }
+    }
+
+    // Konfigürasyondan gelen limit
+    pub fn limit(&self) -> i64 {
+        match self {
+            QuotaMetric::GamesCreated => CONFIG.quota_games_per_day,
+            QuotaMetric::AiGenerations => CONFIG.quota_ai_generations_per_month,
+            QuotaMetric::MediaStorageBytes => CONFIG.quota_media_storage_mb * 1024 * 1024,
+        }
+    }
+
+    // Mevcut periyodun başlangıç tarihi
+    fn period_start(&self) -> NaiveDate {
+        let today = Utc::now().date_naive();
+        match self.period() {
+            QuotaPeriod::Daily => today,
+            QuotaPeriod::Monthly => today.with_day(1).unwrap_or(today),
+            QuotaPeriod::Lifetime => NaiveDate::from_ymd_opt(1970, 1, 1).unwrap_or(today),
+        }
+    }
+
+    fn period_name(&self) -> &'static str {
+        match self.period() {
+            QuotaPeriod::Daily => "daily",
+            QuotaPeriod::Monthly => "monthly",
+            QuotaPeriod::Lifetime => "total",
+        }
+    }
+}
+
+// Kota aşımı veya sayaç hatası
+#[derive(Debug)]
+pub enum QuotaError {
+    Exceeded { metric: QuotaMetric, used: i64, limit: i64 },
+    Database(sqlx::Error),
+}
+
+impl std::fmt::Display for QuotaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuotaError::Exceeded { metric, used, limit } => {
+                write!(f, "{} kotası aşıldı ({}/{})", metric.key(), used, limit)
+            }
+            QuotaError::Database(e) => write!(f, "Kota sayacı güncellenemedi: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for QuotaError {}
+
+// Kullanıcının bir metrik için kullanım özeti
+#[derive(Debug, Serialize)]
+pub struct UsageSummary {
+    pub metric: &'static str,
+    pub period: &'static str,
+    pub period_start: NaiveDate,
+    pub used: i64,
+    pub limit: i64,
+    pub remaining: i64,
+}
+
+// Kota uygunsa kullanımı artır, aşılıyorsa hiçbir şey yazmadan hata döndür
+pub async fn consume(
+    pool: &Pool<Postgres>,
+    user_id: i32,
+    metric: QuotaMetric,
+    amount: i64,
+) -> Result<(), QuotaError> {
+    let limit = metric.limit();
+    let period_start = metric.period_start();
+
+    // Sayaç artışı tek sorguda ve limit koşuluyla yapılır (yarış durumlarına karşı)
+    let updated = sqlx::query!(
+        r#"
+        INSERT INTO usage_counters (user_id, metric, period_start, amount, updated_at)
+        SELECT $1, $2, $3, $4::BIGINT, NOW()
+        WHERE $4::BIGINT <= $5::BIGINT
+        ON CONFLICT (user_id, metric, period_start)
+        DO UPDATE SET amount = usage_counters.amount + EXCLUDED.amount, updated_at = NOW()
+        WHERE usage_counters.amount + EXCLUDED.amount <= $5
+        RETURNING amount
+        "#,
+        user_id,
+        metric.key(),
+        period_start,
+        amount,
+        limit
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(QuotaError::Database)?;
+
+    if updated.is_some() {
+        return Ok(());
+    }
+
+    let used = current_usage(pool, user_id, metric)
+        .await
+        .map_err(QuotaError::Database)?;
+
+    Err(QuotaError::Exceeded { metric, used, limit })
+}
+
+// Tamamlanamayan işlem için tüketilen kotayı geri ver
+pub async fn refund(
+    pool: &Pool<Postgres>,
+    user_id: i32,
+    metric: QuotaMetric,
+    amount: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE usage_counters SET amount = GREATEST(amount - $4, 0), updated_at = NOW()
+        WHERE user_id = $1 AND metric = $2 AND period_start = $3
+        "#,
+        user_id,
+        metric.key(),
+        metric.period_start(),
+        amount
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Mevcut periyottaki kullanım miktarı
+async fn current_usage(
+    pool: &Pool<Postgres>,
+    user_id: i32,
+    metric: QuotaMetric,
+) -> Result<i64, sqlx::Error> {
+    let record = sqlx::query!(
+        "SELECT amount FROM usage_counters WHERE user_id = $1 AND metric = $2 AND period_start = $3",
+        user_id,
+        metric.key(),
+        metric.period_start()
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(record.map(|r| r.amount).unwrap_or(0))
+}
+
+// Kullanıcının tüm kotaları için kullanım özeti
+pub async fn usage_summary(
+    pool: &Pool<Postgres>,
+    user_id: i32,
+) -> Result<Vec<UsageSummary>, sqlx::Error> {
+    let mut summary = Vec::new();
+
+    for metric in QuotaMetric::ALL {
+        let used = current_usage(pool, user_id, metric).await?;
+        let limit = metric.limit();
+
+        summary.push(UsageSummary {
+            metric: metric.key(),
+            period: metric.period_name(),
+            period_start: metric.period_start(),
+            used,
+            limit,
+            remaining: (limit - used).max(0),
+        });
+    }
+
+    Ok(summary)
+}
+
+// Kota aşımı için istemciye dönecek JSON gövdesi
+pub fn exceeded_body(metric: QuotaMetric, used: i64, limit: i64) -> serde_json::Value {
+    let message = match metric {
+        QuotaMetric::GamesCreated => "Günlük oyun oluşturma kotanız doldu",
+        QuotaMetric::AiGenerations => "Aylık yapay zeka soru üretme kotanız doldu",
+        QuotaMetric::MediaStorageBytes => "Medya depolama kotanız doldu",
+    };
+
+    serde_json::json!({
+        "error": message,
+        "quota": {
+            "metric": metric.key(),
+            "period": metric.period_name(),
+            "used": used,
+            "limit": limit
+        }
+    })
+}
@@ -0,0 +1,352 @@
+use chrono::{Duration, Utc};
+use serde::Serialize;
+use sqlx::{Pool, Postgres, Transaction};
+
+use crate::config::CONFIG;
+use crate::services::nickname::clean_nickname;
+use crate::utils::security::generate_invite_code;
+use crate::utils::validation::validate_email_format;
+
+// Sınıf listesi
+// Öğretmen sınıf listesini CSV olarak (ad, e-posta) içe aktarır. Hesabı olan öğrenciler doğrudan sınıfa
+// eklenir; hesabı olmayanlar için tek kullanımlık, sınıfa bağlı bir davet oluşturulur ve öğrenci bu davetle
+// kayıt olunca listedeki satırla eşleştirilir. Oyun bir sınıf listesiyle sınırlandırılırsa yalnızca listedeki
+// (veya sınıfa üye) öğrenciler katılabilir ve takma adları listedeki addan atanır. Öğretmen bu oyun için listedeki
+// her öğrenciye imzalı, kişiye özel bir katılım bağlantısı da oluşturabilir.
+
+// Tek seferde içe aktarılabilecek en fazla satır
+pub const MAX_ROSTER_ROWS: usize = 500;
+
+// Listedeki adın en fazla uzunluğu (oyunda takma ad olarak kullanılır)
+const MAX_DISPLAY_NAME_LEN: usize = 50;
+
+// Liste davetlerinin geçerlilik süresi
+const INVITATION_VALID_DAYS: i64 = 30;
+
+// Kişiye özel oyun katılım bağlantılarının geçerlilik süresi
+pub const JOIN_LINK_VALID_HOURS: i64 = 24;
+
+#[derive(Debug, Clone)]
+pub struct RosterRow {
+    pub line: usize,
+    pub name: String,
+    pub email: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RosterRowError {
+    pub line: usize,
+    pub error: String,
+}
+
+// İçe aktarılan satırın sonucu: invited (davet oluşturuldu), enrolled (mevcut hesap sınıfa eklendi)
+// veya existing (zaten listede)
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportedEntry {
+    pub line: usize,
+    pub name: String,
+    pub email: String,
+    pub status: &'static str,
+    pub invite_code: Option<String>,
+    pub invite_link: Option<String>,
+}
+
+// Sınıf listesindeki öğrenci (kişiye özel katılım bağlantıları için)
+#[derive(Debug, Clone)]
+pub struct RosterEntry {
+    pub id: i32,
+    pub class_id: i32,
+    pub display_name: String,
+    pub user_id: Option<i32>,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportOutcome {
+    pub entries: Vec<ImportedEntry>,
+    pub errors: Vec<RosterRowError>,
+}
+
+// CSV satırını alanlara ayır; tırnak içindeki ayraçlar ve "" kaçışı desteklenir
+fn split_fields(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields.into_iter().map(|f| f.trim().to_string()).collect()
+}
+
+// CSV metnini satırlara ayır. Ayraç virgül veya (Türkçe Excel çıktılarındaki gibi) noktalı virgül olabilir.
+// E-posta içeren alan e-posta, diğeri ad kabul edilir; ilk satırda e-posta yoksa başlık satırı sayılır.
+pub fn parse_csv(text: &str) -> (Vec<RosterRow>, Vec<RosterRowError>) {
+    let text = text.trim_start_matches('\u{feff}');
+    let delimiter = match text.lines().find(|l| !l.trim().is_empty()) {
+        Some(first) if first.contains(';') && !first.contains(',') => ';',
+        _ => ',',
+    };
+
+    let mut rows: Vec<RosterRow> = Vec::new();
+    let mut errors = Vec::new();
+    let mut seen_first = false;
+
+    for (index, raw) in text.lines().enumerate() {
+        let line = index + 1;
+        if raw.trim().is_empty() {
+            continue;
+        }
+
+        let fields: Vec<String> = split_fields(raw, delimiter).into_iter().filter(|f| !f.is_empty()).collect();
+        let email_index = fields.iter().position(|f| f.contains('@'));
+        let is_first = !seen_first;
+        seen_first = true;
+
+        let (Some(email_index), 2) = (email_index, fields.len()) else {
+            if is_first && email_index.is_none() {
+                continue;
+            }
+            errors.push(RosterRowError {
+                line,
+                error: "Satır ad ve e-posta olmak üzere iki alandan oluşmalıdır".to_string(),
+            });
+            continue;
+        };
+
+        let email = fields[email_index].to_lowercase();
+        let name = clean_nickname(&fields[1 - email_index]);
+
+        let error = if !validate_email_format(&email) {
+            Some("Geçersiz e-posta adresi")
+        } else if name.is_empty() || name.chars().count() > MAX_DISPLAY_NAME_LEN {
+            Some("Ad 1-50 karakter arasında olmalıdır")
+        } else if rows.iter().any(|r| r.email == email) {
+            Some("Bu e-posta dosyada birden fazla kez geçiyor")
+        } else {
+            None
+        };
+
+        match error {
+            Some(error) => errors.push(RosterRowError { line, error: error.to_string() }),
+            None => rows.push(RosterRow { line, name, email }),
+        }
+    }
+
+    (rows, errors)
+}
+
+fn invite_link(code: &str) -> String {
+    format!("{}/register?invite={}", CONFIG.frontend_url, code)
+}
+
+// Öğrencinin kod ve takma ad girmeden doğrudan lobiye düştüğü bağlantı
+pub fn join_link(token: &str) -> String {
+    format!("{}/join?token={}", CONFIG.frontend_url, token)
+}
+
+// Satırları sınıf listesine ekle; hesabı olmayan öğrenciler için tek kullanımlık davet oluşturulur
+pub async fn import(
+    pool: &Pool<Postgres>,
+    class_id: i32,
+    teacher_id: i32,
+    rows: Vec<RosterRow>,
+) -> Result<ImportOutcome, sqlx::Error> {
+    let mut outcome = ImportOutcome::default();
+    let mut tx = pool.begin().await?;
+    let expires_at = Utc::now() + Duration::days(INVITATION_VALID_DAYS);
+
+    for row in rows {
+        let existing = sqlx::query_scalar!(
+            "SELECT id FROM class_roster WHERE class_id = $1 AND email = $2",
+            class_id,
+            row.email
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if existing.is_some() {
+            outcome.entries.push(ImportedEntry {
+                line: row.line,
+                name: row.name,
+                email: row.email,
+                status: "existing",
+                invite_code: None,
+                invite_link: None,
+            });
+            continue;
+        }
+
+        let user = sqlx::query!(
+            "SELECT id, role FROM users WHERE LOWER(email) = $1",
+            row.email
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        match user {
+            Some(user) if user.role != "student" => {
+                outcome.errors.push(RosterRowError {
+                    line: row.line,
+                    error: "Bu e-posta öğrenci olmayan bir hesaba ait".to_string(),
+                });
+            }
+            Some(user) => {
+                sqlx::query!(
+                    "INSERT INTO class_roster (class_id, display_name, email, user_id, created_at) VALUES ($1, $2, $3, $4, $5)",
+                    class_id,
+                    row.name,
+                    row.email,
+                    user.id,
+                    Utc::now()
+                )
+                .execute(&mut *tx)
+                .await?;
+
+                sqlx::query!(
+                    "INSERT INTO class_members (class_id, user_id, joined_at) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+                    class_id,
+                    user.id,
+                    Utc::now()
+                )
+                .execute(&mut *tx)
+                .await?;
+
+                outcome.entries.push(ImportedEntry {
+                    line: row.line,
+                    name: row.name,
+                    email: row.email,
+                    status: "enrolled",
+                    invite_code: None,
+                    invite_link: None,
+                });
+            }
+            None => {
+                let code = generate_invite_code();
+                let invitation_id = sqlx::query_scalar!(
+                    r#"
+                    INSERT INTO invitations (code, teacher_id, class_id, max_uses, expires_at, created_at)
+                    VALUES ($1, $2, $3, 1, $4, $5)
+                    RETURNING id
+                    "#,
+                    code,
+                    teacher_id,
+                    class_id,
+                    expires_at,
+                    Utc::now()
+                )
+                .fetch_one(&mut *tx)
+                .await?;
+
+                sqlx::query!(
+                    "INSERT INTO class_roster (class_id, display_name, email, invitation_id, created_at) VALUES ($1, $2, $3, $4, $5)",
+                    class_id,
+                    row.name,
+                    row.email,
+                    invitation_id,
+                    Utc::now()
+                )
+                .execute(&mut *tx)
+                .await?;
+
+                outcome.entries.push(ImportedEntry {
+                    line: row.line,
+                    name: row.name,
+                    email: row.email,
+                    status: "invited",
+                    invite_link: Some(invite_link(&code)),
+                    invite_code: Some(code),
+                });
+            }
+        }
+    }
+
+    tx.commit().await?;
+    Ok(outcome)
+}
+
+// Davetle kayıt olan öğrenciyi listedeki satırla eşleştir (kayıt işlemiyle aynı işlemde)
+pub async fn link_invited_user(
+    tx: &mut Transaction<'_, Postgres>,
+    invitation_id: i32,
+    user_id: i32,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE class_roster SET user_id = $1 WHERE invitation_id = $2 AND user_id IS NULL",
+        user_id,
+        invitation_id
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+// Sınırlandırılmış oyuna katılacak kullanıcının takma adı: listedeki adı, listede olmayan sınıf üyeleri için
+// kullanıcı adı. Kullanıcı ne listede ne de sınıfta ise None döner.
+pub async fn roster_nickname(
+    pool: &Pool<Postgres>,
+    class_id: i32,
+    user_id: i32,
+) -> Result<Option<String>, sqlx::Error> {
+    let nickname = sqlx::query_scalar!(
+        r#"
+        SELECT COALESCE(r.display_name, u.username) as "nickname!"
+        FROM users u
+        LEFT JOIN class_roster r ON r.class_id = $1 AND r.user_id = u.id
+        WHERE u.id = $2
+          AND (r.id IS NOT NULL
+               OR EXISTS (SELECT 1 FROM class_members cm WHERE cm.class_id = $1 AND cm.user_id = u.id))
+        "#,
+        class_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(nickname)
+}
+
+// Sınıf listesindeki tüm öğrenciler (ada göre sıralı)
+pub async fn class_entries(pool: &Pool<Postgres>, class_id: i32) -> Result<Vec<RosterEntry>, sqlx::Error> {
+    sqlx::query_as!(
+        RosterEntry,
+        "SELECT id, class_id, display_name, user_id FROM class_roster WHERE class_id = $1 ORDER BY display_name, id",
+        class_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+// Sınıf listesi satırı
+pub async fn entry(pool: &Pool<Postgres>, roster_id: i32) -> Result<Option<RosterEntry>, sqlx::Error> {
+    sqlx::query_as!(
+        RosterEntry,
+        "SELECT id, class_id, display_name, user_id FROM class_roster WHERE id = $1",
+        roster_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+// Sınıfın sahibi (veya admin) mi
+pub async fn can_manage_class(
+    pool: &Pool<Postgres>,
+    class_id: i32,
+    user_id: i32,
+    is_admin: bool,
+) -> Result<Option<bool>, sqlx::Error> {
+    let teacher_id = sqlx::query_scalar!("SELECT teacher_id FROM classes WHERE id = $1", class_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(teacher_id.map(|teacher_id| teacher_id == user_id || is_admin))
+}
\ No newline at end of file
@@ -0,0 +1,44 @@
+use serde_json::json;
+
+// Sahne işaretleri
+// Host oyunun akışına göre sahne değiştirir (lobi, hazırlan, gerilim, sonuçlar); sunucu sahneyi tüm
+// istemcilere aynı başlama anıyla yayınlar, böylece müzik ve animasyonlar cihazlar arasında eş zamanlı
+// başlar. Öğretmen oyun ayarlarında her sahne için bir işaret (ör. müzik parçası veya tema adı) seçebilir;
+// işaretin ne çalacağı istemciye bırakılır.
+
+pub const SCENES: [&str; 4] = ["lobby", "get_ready", "suspense", "results"];
+
+// Sahne işaretinin en fazla uzunluğu
+const MAX_CUE_LEN: usize = 100;
+
+pub fn is_valid_scene(scene: &str) -> bool {
+    SCENES.contains(&scene)
+}
+
+// Ayarlardaki sahne işaretlerini doğrula: yalnızca bilinen sahneler, değerler kısa metin veya null (varsayılan)
+pub fn validate_cues(cues: &serde_json::Value) -> Result<serde_json::Value, &'static str> {
+    let Some(cues) = cues.as_object() else {
+        return Err("Sahne işaretleri sahne adı -> işaret biçiminde olmalıdır");
+    };
+
+    let mut validated = serde_json::Map::new();
+    for (scene, cue) in cues {
+        if !is_valid_scene(scene) {
+            return Err("Geçersiz sahne: lobby, get_ready, suspense veya results olmalıdır");
+        }
+        match cue {
+            serde_json::Value::Null => {}
+            serde_json::Value::String(cue) if !cue.trim().is_empty() && cue.len() <= MAX_CUE_LEN => {
+                validated.insert(scene.clone(), json!(cue.trim()));
+            }
+            _ => return Err("Sahne işareti en fazla 100 karakterlik bir metin olmalıdır"),
+        }
+    }
+
+    Ok(serde_json::Value::Object(validated))
+}
+
+// Sahne için ayarlanmış işaret (ayarlanmamışsa null; istemci varsayılanını kullanır)
+pub fn cue_for(cues: &serde_json::Value, scene: &str) -> serde_json::Value {
+    cues.get(scene).cloned().unwrap_or(serde_json::Value::Null)
+}
\ No newline at end of file
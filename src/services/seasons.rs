@@ -0,0 +1,279 @@
+use chrono::{Datelike, NaiveDate, Utc};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+
+use crate::db::repositories::read_pool;
+
+// Aylık rekabet sezonları ve ELO benzeri puanlama
+// Her ay yeni bir sezon açılır; biten sezonların sıralamaları arşivlenir.
+
+// Yeni oyuncuların başlangıç puanı
+pub const INITIAL_RATING: i32 = 1000;
+// Bir oyunda kazanılabilecek/kaybedilebilecek en yüksek puan
+const K_FACTOR: f64 = 32.0;
+
+// Oyun sonrası puan değişimi
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RatingChange {
+    pub user_id: i32,
+    pub player_id: i32,
+    pub old_rating: i32,
+    pub new_rating: i32,
+    pub delta: i32,
+}
+
+// Sezon sıralamasındaki bir kullanıcı
+#[derive(Debug, Clone)]
+pub struct SeasonStanding {
+    pub user_id: i32,
+    pub username: String,
+    pub avatar: Option<String>,
+    pub rating: i32,
+    pub games_played: i32,
+    pub wins: i32,
+}
+
+// Verilen tarihin ait olduğu sezonun ilk ve son günü
+fn season_bounds(today: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let start = today.with_day(1).unwrap_or(today);
+    let next_month = if start.month() == 12 {
+        NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+    };
+    let end = next_month.and_then(|d| d.pred_opt()).unwrap_or(start);
+    (start, end)
+}
+
+// Çok oyunculu ELO: her oyuncu diğer her oyuncuyla ikili karşılaşma yapmış sayılır
+// Girdi (puan, oyundaki sıra; 1 birinci, eşitler aynı sırada), çıktı her oyuncu için puan değişimi
+pub fn elo_deltas(players: &[(i32, i64)]) -> Vec<i32> {
+    let n = players.len();
+    if n < 2 {
+        return vec![0; n];
+    }
+
+    players
+        .iter()
+        .enumerate()
+        .map(|(i, &(rating_i, place_i))| {
+            let total: f64 = players
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, &(rating_j, place_j))| {
+                    let expected = 1.0 / (1.0 + 10f64.powf((rating_j - rating_i) as f64 / 400.0));
+                    let actual = match place_j.cmp(&place_i) {
+                        std::cmp::Ordering::Greater => 1.0,
+                        std::cmp::Ordering::Equal => 0.5,
+                        std::cmp::Ordering::Less => 0.0,
+                    };
+                    actual - expected
+                })
+                .sum();
+            (K_FACTOR * total / (n - 1) as f64).round() as i32
+        })
+        .collect()
+}
+
+// Geçerli ayın sezonunu getir (yoksa oluştur) ve biten sezonları arşivle
+pub async fn ensure_current_season(pool: &Pool<Postgres>) -> Result<i32, sqlx::Error> {
+    let today = Utc::now().date_naive();
+    let (starts_on, ends_on) = season_bounds(today);
+    let name = starts_on.format("%Y-%m").to_string();
+
+    let season = sqlx::query!(
+        r#"
+        INSERT INTO seasons (name, starts_on, ends_on)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (name) DO UPDATE SET name = EXCLUDED.name
+        RETURNING id
+        "#,
+        name,
+        starts_on,
+        ends_on
+    )
+    .fetch_one(pool)
+    .await?;
+
+    // Bitmiş ama arşivlenmemiş sezonları arşivle
+    let ended = sqlx::query!(
+        "SELECT id, name FROM seasons WHERE ends_on < $1 AND archived_at IS NULL",
+        today
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for old in ended {
+        if let Err(e) = archive_season(pool, old.id).await {
+            error!("Sezon arşivlenemedi ({}): {}", old.name, e);
+        } else {
+            info!("Sezon arşivlendi: {}", old.name);
+        }
+    }
+
+    Ok(season.id)
+}
+
+// Sezonun son sıralamasını (genel ve kurum içi) arşiv tablosuna yaz
+async fn archive_season(pool: &Pool<Postgres>, season_id: i32) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO season_standings
+            (season_id, user_id, organization_id, rank, organization_rank, rating, games_played, wins)
+        SELECT sr.season_id, sr.user_id, u.organization_id,
+               RANK() OVER (ORDER BY sr.rating DESC)::INTEGER,
+               CASE WHEN u.organization_id IS NULL THEN NULL
+                    ELSE RANK() OVER (PARTITION BY u.organization_id ORDER BY sr.rating DESC)::INTEGER END,
+               sr.rating, sr.games_played, sr.wins
+        FROM season_ratings sr
+        JOIN users u ON sr.user_id = u.id
+        WHERE sr.season_id = $1
+        ON CONFLICT (season_id, user_id) DO NOTHING
+        "#,
+        season_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE seasons SET archived_at = $1 WHERE id = $2",
+        Utc::now(),
+        season_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await
+}
+
+// Tamamlanan oyundaki kayıtlı oyuncuların sezon puanlarını güncelle
+pub async fn rate_game(pool: &Pool<Postgres>, game_id: i32) -> Result<Vec<RatingChange>, sqlx::Error> {
+    let season_id = ensure_current_season(pool).await?;
+
+    let mut tx = pool.begin().await?;
+
+    // Oyun daha önce puanlandıysa tekrar puanlama
+    let marked = sqlx::query!(
+        "INSERT INTO season_rated_games (game_id, season_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        game_id,
+        season_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    if marked.rows_affected() == 0 {
+        return Ok(Vec::new());
+    }
+
+    let players = sqlx::query!(
+        r#"
+        SELECT p.id as player_id, p.user_id as "user_id!", COALESCE(p.score, 0) as "score!",
+               COALESCE(sr.rating, $2) as "rating!",
+               COALESCE(p.sudden_death_place = 1, false) as "sudden_death_winner!",
+               RANK() OVER (ORDER BY p.sudden_death_place ASC NULLS LAST, COALESCE(p.score, 0) DESC) as "place!"
+        FROM players p
+        LEFT JOIN season_ratings sr ON sr.user_id = p.user_id AND sr.season_id = $3
+        WHERE p.game_id = $1 AND p.user_id IS NOT NULL AND p.is_active = true
+        "#,
+        game_id,
+        INITIAL_RATING,
+        season_id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    // Tek kayıtlı oyunculu oyunlar puanlamaya dahil edilmez
+    if players.len() < 2 {
+        tx.commit().await?;
+        return Ok(Vec::new());
+    }
+
+    // Sıra ani ölüm turu yapıldıysa önce finalistlerin elenme sırasına, sonra puana göredir
+    let deltas = elo_deltas(&players.iter().map(|p| (p.rating, p.place)).collect::<Vec<_>>());
+    let mut changes = Vec::new();
+
+    for (p, delta) in players.iter().zip(deltas) {
+        let is_win = p.place == 1 && (p.score > 0 || p.sudden_death_winner);
+        let new_rating = p.rating + delta;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO season_ratings (season_id, user_id, rating, games_played, wins, updated_at)
+            VALUES ($1, $2, $3, 1, $4, $5)
+            ON CONFLICT (season_id, user_id) DO UPDATE SET
+                rating = EXCLUDED.rating,
+                games_played = season_ratings.games_played + 1,
+                wins = season_ratings.wins + EXCLUDED.wins,
+                updated_at = EXCLUDED.updated_at
+            "#,
+            season_id,
+            p.user_id,
+            new_rating,
+            if is_win { 1 } else { 0 },
+            Utc::now()
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        changes.push(RatingChange {
+            user_id: p.user_id,
+            player_id: p.player_id,
+            old_rating: p.rating,
+            new_rating,
+            delta,
+        });
+    }
+
+    tx.commit().await?;
+
+    Ok(changes)
+}
+
+// Sezonun ilk 100 kullanıcısı (kurum verilirse yalnızca o kurum). Arşivlenmiş sezonlar arşiv tablosundan, aktif
+// sezon canlı puanlardan okunur; sıralama sayfası okuma replikasından beslenir
+pub async fn standings(
+    pool: &Pool<Postgres>,
+    season_id: i32,
+    organization_id: Option<i32>,
+    archived: bool,
+) -> Result<Vec<SeasonStanding>, sqlx::Error> {
+    let pool = read_pool(pool);
+
+    if archived {
+        sqlx::query_as!(
+            SeasonStanding,
+            r#"
+            SELECT ss.user_id, u.username, u.avatar, ss.rating, ss.games_played, ss.wins
+            FROM season_standings ss
+            JOIN users u ON ss.user_id = u.id
+            WHERE ss.season_id = $1 AND ($2::int IS NULL OR ss.organization_id = $2)
+            ORDER BY ss.rating DESC
+            LIMIT 100
+            "#,
+            season_id,
+            organization_id
+        )
+        .fetch_all(pool)
+        .await
+    } else {
+        sqlx::query_as!(
+            SeasonStanding,
+            r#"
+            SELECT sr.user_id, u.username, u.avatar, sr.rating, sr.games_played, sr.wins
+            FROM season_ratings sr
+            JOIN users u ON sr.user_id = u.id
+            WHERE sr.season_id = $1 AND ($2::int IS NULL OR u.organization_id = $2)
+            ORDER BY sr.rating DESC
+            LIMIT 100
+            "#,
+            season_id,
+            organization_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
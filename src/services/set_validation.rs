@@ -0,0 +1,147 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+// Soru seti doğrulama raporu
+// Oyun başlamadan önce öğretmene gösterilecek kontrol listesi: sıra numarası çakışmaları, boş şıklar,
+// fazla uzun metinler ve tekrarlanan sorular. Hatalar oyunu bozar, uyarılar yalnızca dikkat çeker.
+
+// Bu uzunluğu aşan metinler küçük ekranlarda okunmakta zorlanır
+const MAX_QUESTION_TEXT_CHARS: usize = 300;
+const MAX_OPTION_TEXT_CHARS: usize = 120;
+
+// Daha kısa süreler öğrencilere soruyu okuma fırsatı bırakmaz
+const MIN_TIME_LIMIT_SECS: i32 = 5;
+
+pub const SEVERITY_ERROR: &str = "error";
+pub const SEVERITY_WARNING: &str = "warning";
+
+// Doğrulanacak soru
+#[derive(Debug)]
+pub struct QuestionInput {
+    pub id: i32,
+    pub position: i32,
+    pub question_text: String,
+    pub options: [String; 4],
+    pub time_limit: Option<i32>,
+}
+
+// Rapordaki tek bir bulgu
+#[derive(Debug, Serialize)]
+pub struct SetIssue {
+    pub code: &'static str,
+    pub severity: &'static str,
+    pub question_ids: Vec<i32>,
+    pub message: String,
+}
+
+impl SetIssue {
+    fn error(code: &'static str, question_ids: Vec<i32>, message: String) -> Self {
+        SetIssue { code, severity: SEVERITY_ERROR, question_ids, message }
+    }
+
+    fn warning(code: &'static str, question_ids: Vec<i32>, message: String) -> Self {
+        SetIssue { code, severity: SEVERITY_WARNING, question_ids, message }
+    }
+}
+
+// Tekrar karşılaştırması için metni sadeleştir (büyük/küçük harf ve boşluk farkı gözetilmez)
+fn normalize_text(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Aynı anahtarı paylaşan soruları grupla (yalnızca birden fazla soru içeren gruplar döner)
+fn duplicate_groups<K: std::hash::Hash + Eq + Ord>(
+    questions: &[QuestionInput],
+    key: impl Fn(&QuestionInput) -> K,
+) -> Vec<(K, Vec<i32>)> {
+    let mut groups: HashMap<K, Vec<i32>> = HashMap::new();
+    for q in questions {
+        groups.entry(key(q)).or_default().push(q.id);
+    }
+
+    let mut duplicates: Vec<(K, Vec<i32>)> = groups.into_iter().filter(|(_, ids)| ids.len() > 1).collect();
+    duplicates.sort_by(|a, b| a.0.cmp(&b.0));
+    duplicates
+}
+
+// Soru setindeki sorunları bul
+pub fn validate_questions(questions: &[QuestionInput]) -> Vec<SetIssue> {
+    let mut issues = Vec::new();
+
+    if questions.is_empty() {
+        issues.push(SetIssue::error("empty_set", vec![], "Soru setinde hiç soru yok".to_string()));
+        return issues;
+    }
+
+    for (position, ids) in duplicate_groups(questions, |q| q.position) {
+        issues.push(SetIssue::error(
+            "duplicate_position",
+            ids,
+            format!("{}. sırada birden fazla soru var", position + 1),
+        ));
+    }
+
+    for (_, ids) in duplicate_groups(questions, |q| normalize_text(&q.question_text)) {
+        issues.push(SetIssue::warning(
+            "duplicate_question",
+            ids,
+            "Aynı soru sette birden fazla kez geçiyor".to_string(),
+        ));
+    }
+
+    for q in questions {
+        if q.question_text.trim().is_empty() {
+            issues.push(SetIssue::error("missing_question_text", vec![q.id], "Soru metni boş".to_string()));
+        } else if q.question_text.chars().count() > MAX_QUESTION_TEXT_CHARS {
+            issues.push(SetIssue::warning(
+                "long_question_text",
+                vec![q.id],
+                format!("Soru metni {} karakterden uzun", MAX_QUESTION_TEXT_CHARS),
+            ));
+        }
+
+        let labels = ["A", "B", "C", "D"];
+        for (label, option) in labels.iter().zip(&q.options) {
+            if option.trim().is_empty() {
+                issues.push(SetIssue::error("missing_option", vec![q.id], format!("{} şıkkı boş", label)));
+            } else if option.chars().count() > MAX_OPTION_TEXT_CHARS {
+                issues.push(SetIssue::warning(
+                    "long_option_text",
+                    vec![q.id],
+                    format!("{} şıkkı {} karakterden uzun", label, MAX_OPTION_TEXT_CHARS),
+                ));
+            }
+        }
+
+        // Aynı metne sahip dolu şıklar öğrenciyi şaşırtır
+        let mut seen: HashMap<String, &str> = HashMap::new();
+        for (label, option) in labels.iter().zip(&q.options) {
+            let normalized = normalize_text(option);
+            if normalized.is_empty() {
+                continue;
+            }
+            if let Some(first) = seen.get(&normalized) {
+                issues.push(SetIssue::warning(
+                    "duplicate_option",
+                    vec![q.id],
+                    format!("{} ve {} şıkları aynı", first, label),
+                ));
+            } else {
+                seen.insert(normalized, label);
+            }
+        }
+
+        if matches!(q.time_limit, Some(limit) if limit < MIN_TIME_LIMIT_SECS) {
+            issues.push(SetIssue::warning(
+                "short_time_limit",
+                vec![q.id],
+                format!("Süre sınırı {} saniyeden kısa", MIN_TIME_LIMIT_SECS),
+            ));
+        }
+    }
+
+    issues
+}
\ No newline at end of file
@@ -0,0 +1,51 @@
+use chrono::Utc;
+use sqlx::{Pool, Postgres};
+
+// Admin panelinden değiştirilebilen sistem ayarları
+// Ayar kaydı yoksa ortam değişkenlerinden gelen konfigürasyon geçerlidir.
+
+// Ayar anahtarları
+pub const SETTING_EMAIL_POLICY: &str = "email_policy";
+pub const SETTING_WAREHOUSE_EXPORT: &str = "warehouse_export";
+
+// Ayar değerini getir
+pub async fn get_setting(pool: &Pool<Postgres>, key: &str) -> Result<Option<String>, sqlx::Error> {
+    let record = sqlx::query!("SELECT value FROM system_settings WHERE key = $1", key)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(record.map(|r| r.value))
+}
+
+// Ayar değerini kaydet
+pub async fn set_setting(
+    pool: &Pool<Postgres>,
+    key: &str,
+    value: &str,
+    updated_by: i32,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO system_settings (key, value, updated_by, updated_at)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value, updated_by = EXCLUDED.updated_by, updated_at = EXCLUDED.updated_at
+        "#,
+        key,
+        value,
+        updated_by,
+        Utc::now()
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Ayar kaydını sil (konfigürasyondaki varsayılana dön)
+pub async fn clear_setting(pool: &Pool<Postgres>, key: &str) -> Result<(), sqlx::Error> {
+    sqlx::query!("DELETE FROM system_settings WHERE key = $1", key)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
\ No newline at end of file
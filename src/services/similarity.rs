@@ -0,0 +1,135 @@
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use std::collections::{HashMap, HashSet};
+
+// Pazaryerine gönderilen setlerde benzerlik taraması
+// Gönderilen setin soruları, başka öğretmenlere ait ve pazaryerinde yayında olan setlerin sorularıyla
+// karşılaştırılır. İki soru, soru metni ve şıklarındaki kelimelerin büyük kısmı ortaksa (Jaccard benzerliği)
+// eşleşmiş sayılır; böylece sıra, noktalama veya büyük/küçük harf değiştirilerek kopyalanan sorular da
+// yakalanır. Sorularının büyük bölümü tek bir yayındaki setle eşleşen gönderimler admin incelemesi için işaretlenir.
+
+// İki sorunun aynı sayılması için gereken kelime kümesi benzerliği
+const QUESTION_MATCH_THRESHOLD: f64 = 0.8;
+
+// Setin işaretlenmesi için tek bir yayındaki setle eşleşen soruların oranı
+const FLAG_THRESHOLD: f64 = 0.5;
+
+// Raporda gösterilen en düşük örtüşme oranı ve en fazla set sayısı
+const MIN_REPORTED_OVERLAP: f64 = 0.2;
+const MAX_REPORTED_SETS: usize = 5;
+
+// Yayındaki bir setle örtüşme
+#[derive(Debug, Serialize, Clone)]
+pub struct SetOverlap {
+    pub listing_id: i32,
+    pub question_set_id: i32,
+    pub title: String,
+    pub creator: String,
+    pub matched_questions: usize,
+    // Gönderilen setin sorularından bu setle eşleşenlerin oranı (0-1)
+    pub overlap: f64,
+}
+
+// Taramanın sonucu; örtüşmeler en yüksekten düşüğe sıralıdır
+#[derive(Debug, Serialize, Clone)]
+pub struct SimilarityReport {
+    pub score: f64,
+    pub flagged: bool,
+    pub matches: Vec<SetOverlap>,
+}
+
+// Soru metni ve şıkların kelime kümesi (Türkçe I/İ/ı katlanır, noktalama yok sayılır)
+fn question_words(question_text: &str, options: [&str; 4]) -> HashSet<String> {
+    std::iter::once(question_text)
+        .chain(options)
+        .flat_map(|text| text.split(|c: char| !c.is_alphanumeric()))
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            word.chars()
+                .flat_map(|c| match c {
+                    'I' | 'İ' | 'ı' => 'i'.to_lowercase(),
+                    _ => c.to_lowercase(),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+// Seti, sahibine ait olmayan ve pazaryerinde yayında olan setlerle karşılaştır
+pub async fn scan(pool: &Pool<Postgres>, question_set_id: i32, creator_id: i32) -> Result<SimilarityReport, sqlx::Error> {
+    let submitted: Vec<HashSet<String>> = sqlx::query!(
+        "SELECT question_text, option_a, option_b, option_c, option_d FROM questions WHERE question_set_id = $1 AND deleted_at IS NULL",
+        question_set_id
+    )
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(|q| question_words(&q.question_text, [&q.option_a, &q.option_b, &q.option_c, &q.option_d]))
+    .collect();
+
+    let published = sqlx::query!(
+        r#"
+        SELECT ml.id as listing_id, qs.id as question_set_id, qs.title, u.username as creator,
+               q.question_text, q.option_a, q.option_b, q.option_c, q.option_d
+        FROM marketplace_listings ml
+        JOIN question_sets qs ON ml.question_set_id = qs.id
+        JOIN users u ON qs.creator_id = u.id
+        JOIN questions q ON q.question_set_id = qs.id AND q.deleted_at IS NULL
+        WHERE ml.status = 'approved' AND qs.deleted_at IS NULL
+          AND qs.creator_id <> $1 AND qs.id <> $2
+        "#,
+        creator_id,
+        question_set_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    // Yayındaki setleri ve soru kelime kümelerini grupla
+    let mut sets: HashMap<i32, (SetOverlap, Vec<HashSet<String>>)> = HashMap::new();
+    for row in published {
+        let words = question_words(&row.question_text, [&row.option_a, &row.option_b, &row.option_c, &row.option_d]);
+        sets.entry(row.listing_id)
+            .or_insert_with(|| {
+                let overlap = SetOverlap {
+                    listing_id: row.listing_id,
+                    question_set_id: row.question_set_id,
+                    title: row.title,
+                    creator: row.creator,
+                    matched_questions: 0,
+                    overlap: 0.0,
+                };
+                (overlap, Vec::new())
+            })
+            .1
+            .push(words);
+    }
+
+    let mut matches: Vec<SetOverlap> = sets
+        .into_values()
+        .filter_map(|(mut overlap, questions)| {
+            overlap.matched_questions = submitted
+                .iter()
+                .filter(|s| questions.iter().any(|q| jaccard(s, q) >= QUESTION_MATCH_THRESHOLD))
+                .count();
+            overlap.overlap = overlap.matched_questions as f64 / submitted.len().max(1) as f64;
+            (overlap.overlap >= MIN_REPORTED_OVERLAP).then_some(overlap)
+        })
+        .collect();
+    matches.sort_by(|a, b| b.overlap.total_cmp(&a.overlap).then(a.listing_id.cmp(&b.listing_id)));
+    matches.truncate(MAX_REPORTED_SETS);
+
+    let score = matches.first().map_or(0.0, |m| m.overlap);
+    Ok(SimilarityReport {
+        score,
+        flagged: score >= FLAG_THRESHOLD,
+        matches,
+    })
+}
\ No newline at end of file
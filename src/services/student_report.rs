@@ -0,0 +1,170 @@
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+use crate::db::repositories::read_pool;
+
+// Öğrenci karşılaştırma raporu
+// Veli görüşmeleri için bir öğrencinin sınıf oyunlarındaki doğruluk ve hızı, aynı oyunlarda sınıfın
+// ortalamasıyla konu konu karşılaştırılır. Sınıf oyunları, sınıf listesiyle sınırlandırılan oyunlar ile
+// sınıf öğretmeninin açıp sınıf üyelerinin katıldığı oyunlardır. Sınıf ortalaması tüm sınıf üyelerinin
+// (öğrenci dahil) cevaplarından hesaplanır; süre dolan (boş) cevaplar hız ortalamasına katılmaz.
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct PerformanceSummary {
+    pub answers: i64,
+    pub correct: i64,
+    pub accuracy: Option<f64>,
+    pub avg_response_time_ms: Option<i64>,
+}
+
+impl PerformanceSummary {
+    fn new(answers: i64, correct: i64, avg_response_time_ms: Option<f64>) -> Self {
+        PerformanceSummary {
+            answers,
+            correct,
+            accuracy: (answers > 0).then(|| (correct as f64 / answers as f64 * 1000.0).round() / 10.0),
+            avg_response_time_ms: avg_response_time_ms.map(|t| t.round() as i64),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TopicComparison {
+    pub topic_id: i32,
+    pub code: Option<String>,
+    pub name: String,
+    pub unit: Option<String>,
+    pub student: PerformanceSummary,
+    pub class: PerformanceSummary,
+    // Öğrencinin doğruluğunun sınıf ortalamasından farkı (yüzde puanı, pozitif: ortalamanın üstünde)
+    pub accuracy_difference: Option<f64>,
+    // Öğrencinin ortalama cevap süresinin sınıf ortalamasından farkı (negatif: daha hızlı)
+    pub response_time_difference_ms: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct StudentReport {
+    pub class_game_count: usize,
+    pub games_played: i64,
+    pub overall_student: PerformanceSummary,
+    pub overall_class: PerformanceSummary,
+    pub topics: Vec<TopicComparison>,
+}
+
+fn differences(student: &PerformanceSummary, class: &PerformanceSummary) -> (Option<f64>, Option<i64>) {
+    let accuracy = student
+        .accuracy
+        .zip(class.accuracy)
+        .map(|(s, c)| ((s - c) * 10.0).round() / 10.0);
+    let time = student
+        .avg_response_time_ms
+        .zip(class.avg_response_time_ms)
+        .map(|(s, c)| s - c);
+    (accuracy, time)
+}
+
+// Öğrencinin sınıftaki tüm oyunlarını tarayan ağır bir sorgu olduğundan okuma replikasında çalışır
+pub async fn student_report(
+    pool: &Pool<Postgres>,
+    class_id: i32,
+    user_id: i32,
+) -> Result<StudentReport, sqlx::Error> {
+    let pool = read_pool(pool);
+    let game_ids: Vec<i32> = sqlx::query_scalar!(
+        r#"
+        SELECT g.id
+        FROM games g
+        JOIN classes c ON c.id = $1
+        WHERE g.roster_class_id = c.id
+           OR (g.host_id = c.teacher_id AND EXISTS (
+                   SELECT 1 FROM players p
+                   JOIN class_members cm ON cm.user_id = p.user_id AND cm.class_id = c.id
+                   WHERE p.game_id = g.id))
+        "#,
+        class_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let games_played = sqlx::query_scalar!(
+        r#"SELECT COUNT(DISTINCT game_id) as "count!" FROM players WHERE game_id = ANY($1) AND user_id = $2"#,
+        &game_ids,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let overall = sqlx::query!(
+        r#"
+        SELECT
+            COUNT(*) FILTER (WHERE p.user_id = $2) as "student_answers!",
+            COUNT(*) FILTER (WHERE p.user_id = $2 AND pa.is_correct) as "student_correct!",
+            AVG(pa.response_time_ms) FILTER (WHERE p.user_id = $2 AND pa.answer <> 'X')::float8 as student_avg_time,
+            COUNT(*) as "class_answers!",
+            COUNT(*) FILTER (WHERE pa.is_correct) as "class_correct!",
+            AVG(pa.response_time_ms) FILTER (WHERE pa.answer <> 'X')::float8 as class_avg_time
+        FROM player_answers pa
+        JOIN players p ON pa.player_id = p.id
+        JOIN class_members cm ON cm.user_id = p.user_id AND cm.class_id = $1
+        WHERE p.game_id = ANY($3)
+        "#,
+        class_id,
+        user_id,
+        &game_ids
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let topics = sqlx::query!(
+        r#"
+        SELECT t.id, t.code, t.name, u.name as "unit?",
+            COUNT(*) FILTER (WHERE p.user_id = $2) as "student_answers!",
+            COUNT(*) FILTER (WHERE p.user_id = $2 AND pa.is_correct) as "student_correct!",
+            AVG(pa.response_time_ms) FILTER (WHERE p.user_id = $2 AND pa.answer <> 'X')::float8 as student_avg_time,
+            COUNT(*) as "class_answers!",
+            COUNT(*) FILTER (WHERE pa.is_correct) as "class_correct!",
+            AVG(pa.response_time_ms) FILTER (WHERE pa.answer <> 'X')::float8 as class_avg_time
+        FROM player_answers pa
+        JOIN players p ON pa.player_id = p.id
+        JOIN class_members cm ON cm.user_id = p.user_id AND cm.class_id = $1
+        JOIN question_topics qt ON qt.question_id = pa.question_id
+        JOIN curriculum_topics t ON t.id = qt.topic_id
+        LEFT JOIN curriculum_topics u ON u.id = t.parent_id
+        WHERE p.game_id = ANY($3)
+        GROUP BY t.id, t.code, t.name, u.name, u.position, t.position
+        ORDER BY u.position NULLS FIRST, t.position, t.name
+        "#,
+        class_id,
+        user_id,
+        &game_ids
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let topics = topics
+        .into_iter()
+        .map(|t| {
+            let student = PerformanceSummary::new(t.student_answers, t.student_correct, t.student_avg_time);
+            let class = PerformanceSummary::new(t.class_answers, t.class_correct, t.class_avg_time);
+            let (accuracy_difference, response_time_difference_ms) = differences(&student, &class);
+            TopicComparison {
+                topic_id: t.id,
+                code: t.code,
+                name: t.name,
+                unit: t.unit,
+                student,
+                class,
+                accuracy_difference,
+                response_time_difference_ms,
+            }
+        })
+        .collect();
+
+    Ok(StudentReport {
+        class_game_count: game_ids.len(),
+        games_played,
+        overall_student: PerformanceSummary::new(overall.student_answers, overall.student_correct, overall.student_avg_time),
+        overall_class: PerformanceSummary::new(overall.class_answers, overall.class_correct, overall.class_avg_time),
+        topics,
+    })
+}
\ No newline at end of file
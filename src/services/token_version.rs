@@ -0,0 +1,67 @@
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use sqlx::{Pool, Postgres};
+
+// JWT sürümleri
+// Her kullanıcının token_version değeri şifre/rol değişikliğinde veya tüm oturumlardan çıkışta artırılır;
+// tokendaki sürüm bununla eşleşmezse token geçersiz sayılır. Her istekte veritabanına gitmemek için
+// sürümler kısa süreliğine bellekte tutulur, bu sunucudaki artırımlar önbelleği hemen günceller.
+
+// Önbelleğin geçerlilik süresi (başka bir sunucu örneğindeki artırımlar en geç bu sürede görülür)
+const CACHE_TTL: Duration = Duration::from_secs(30);
+
+lazy_static! {
+    static ref VERSION_CACHE: RwLock<HashMap<i32, (Option<i32>, Instant)>> = RwLock::new(HashMap::new());
+}
+
+fn store(user_id: i32, version: Option<i32>) {
+    if let Ok(mut cache) = VERSION_CACHE.write() {
+        // Süresi dolan kayıtlar ara sıra temizlenir
+        if cache.len() > 10_000 {
+            cache.retain(|_, (_, loaded_at)| loaded_at.elapsed() < CACHE_TTL);
+        }
+        cache.insert(user_id, (version, Instant::now()));
+    }
+}
+
+// Kullanıcının geçerli token sürümü; kullanıcı silinmişse None
+pub async fn current(pool: &Pool<Postgres>, user_id: i32) -> Result<Option<i32>, sqlx::Error> {
+    if let Ok(cache) = VERSION_CACHE.read() {
+        if let Some((version, loaded_at)) = cache.get(&user_id) {
+            if loaded_at.elapsed() < CACHE_TTL {
+                return Ok(*version);
+            }
+        }
+    }
+
+    let version = sqlx::query!("SELECT token_version FROM users WHERE id = $1", user_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|r| r.token_version);
+
+    store(user_id, version);
+    Ok(version)
+}
+
+// Kullanıcının tüm tokenlarını geçersiz kıl
+pub async fn bump(pool: &Pool<Postgres>, user_id: i32) -> Result<i32, sqlx::Error> {
+    let record = sqlx::query!(
+        "UPDATE users SET token_version = token_version + 1 WHERE id = $1 RETURNING token_version",
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    store(user_id, Some(record.token_version));
+    Ok(record.token_version)
+}
+
+// Sürüm başka bir sorgu içinde artırıldığında önbellekteki değeri düşür
+pub fn invalidate(user_id: i32) {
+    if let Ok(mut cache) = VERSION_CACHE.write() {
+        cache.remove(&user_id);
+    }
+}
\ No newline at end of file
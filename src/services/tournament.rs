@@ -0,0 +1,212 @@
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+
+use crate::db::models::WebSocketMessage;
+use crate::handlers::websocket::AppState;
+
+// Turnuvalar
+// Bir turnuva, sırayla oynanan birden fazla oyundan (tur) oluşur. Her tur bittiğinde oyuncuların puanları
+// turnuva puanına eklenir ve tur için bir yükselme sınırı (advance_count) belirlenmişse birikimli puana göre
+// ilk N katılımcı bir sonraki tura yükselir, diğerleri elenir. İlk tura herkes katılabilir; sonraki turlara
+// yalnızca elenmemiş katılımcılar katılabilir. Katılımcılar turlar arasında kullanıcı hesabıyla, misafirler
+// normalize edilmiş takma adla eşleştirilir.
+
+// Turnuva adının en fazla uzunluğu
+pub const MAX_NAME_LEN: usize = 100;
+
+// Tur sonunda oyunculara yayınlanan sıralamadaki en fazla katılımcı
+const BROADCAST_STANDINGS_LIMIT: i64 = 20;
+
+// Turnuva sıralamasındaki katılımcı
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Standing {
+    pub rank: i64,
+    pub participant_id: i32,
+    pub user_id: Option<i32>,
+    pub nickname: String,
+    pub total_score: i32,
+    pub rounds_played: i32,
+    pub eliminated_after_round: Option<i32>,
+}
+
+// Oyunun ait olduğu turnuva turu
+#[derive(Debug, Clone)]
+pub struct Round {
+    pub tournament_id: i32,
+    pub round_number: i32,
+    pub advance_count: Option<i32>,
+}
+
+// Oyuncu bu oyuna katılabilir mi: turnuva turu değilse veya ilk tursa herkes, sonraki turlarda yalnızca
+// elenmemiş katılımcılar. `nickname` oyuncunun oyunda kullanacağı (misafirler için önekli) takma addır.
+pub async fn may_join(
+    pool: &Pool<Postgres>,
+    game_id: i32,
+    user_id: Option<i32>,
+    nickname: &str,
+) -> Result<bool, sqlx::Error> {
+    let allowed = sqlx::query_scalar!(
+        r#"
+        SELECT r.round_number = 1 OR EXISTS (
+            SELECT 1 FROM tournament_participants tp
+            WHERE tp.tournament_id = r.tournament_id AND tp.eliminated_after_round IS NULL
+              AND tp.participant_key = CASE WHEN $2::int IS NOT NULL THEN 'u:' || $2
+                                            ELSE 'n:' || normalize_nickname($3) END
+        ) as "allowed!"
+        FROM tournament_rounds r
+        WHERE r.game_id = $1
+        "#,
+        game_id,
+        user_id,
+        nickname
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(allowed.unwrap_or(true))
+}
+
+// Turnuva sıralaması (birikimli puana göre); `limit` verilmezse tüm katılımcılar
+pub async fn standings(
+    pool: &Pool<Postgres>,
+    tournament_id: i32,
+    limit: Option<i64>,
+) -> Result<Vec<Standing>, sqlx::Error> {
+    sqlx::query_as!(
+        Standing,
+        r#"
+        SELECT RANK() OVER (ORDER BY total_score DESC) as "rank!", id as participant_id, user_id, nickname,
+               total_score, rounds_played, eliminated_after_round
+        FROM tournament_participants
+        WHERE tournament_id = $1
+        ORDER BY total_score DESC, id
+        LIMIT $2
+        "#,
+        tournament_id,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
+
+// Biten turun puanlarını turnuvaya ekle ve yükselme sınırını uygula; oyun bir tur değilse veya tur zaten
+// işlenmişse None döner
+async fn finish_round(pool: &Pool<Postgres>, game_id: i32) -> Result<Option<Round>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let round = sqlx::query_as!(
+        Round,
+        r#"
+        SELECT tournament_id, round_number, advance_count FROM tournament_rounds
+        WHERE game_id = $1 AND completed_at IS NULL
+        FOR UPDATE
+        "#,
+        game_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(round) = round else {
+        return Ok(None);
+    };
+
+    sqlx::query!(
+        r#"
+        INSERT INTO tournament_participants (tournament_id, participant_key, user_id, nickname, total_score, rounds_played)
+        SELECT DISTINCT ON (participant_key) $2, participant_key, user_id, nickname, score, 1
+        FROM (
+            SELECT CASE WHEN p.user_id IS NOT NULL THEN 'u:' || p.user_id
+                        ELSE 'n:' || normalize_nickname(p.nickname) END as participant_key,
+                   p.user_id, p.nickname, COALESCE(p.score, 0) as score
+            FROM players p
+            WHERE p.game_id = $1 AND p.is_active = true
+        ) played
+        ORDER BY participant_key, score DESC
+        ON CONFLICT (tournament_id, participant_key) DO UPDATE
+        SET total_score = tournament_participants.total_score + EXCLUDED.total_score,
+            rounds_played = tournament_participants.rounds_played + 1,
+            nickname = EXCLUDED.nickname
+        "#,
+        game_id,
+        round.tournament_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // Bu turu oynayan elenmemiş katılımcılardan ilk N kişi yükselir; turda ani ölüm yapıldıysa finalistler elenme
+    // sırasıyla öne alınır, diğerleri birikimli puana göre sıralanır. Turu oynamayanlar ve sınırın altında kalanlar elenir
+    if let Some(advance_count) = round.advance_count {
+        sqlx::query!(
+            r#"
+            WITH played AS (
+                SELECT CASE WHEN p.user_id IS NOT NULL THEN 'u:' || p.user_id
+                            ELSE 'n:' || normalize_nickname(p.nickname) END as participant_key,
+                       MIN(p.sudden_death_place) as sudden_death_place
+                FROM players p
+                WHERE p.game_id = $1 AND p.is_active = true
+                GROUP BY 1
+            ),
+            advancing AS (
+                SELECT tp.id
+                FROM tournament_participants tp
+                JOIN played ON played.participant_key = tp.participant_key
+                WHERE tp.tournament_id = $2 AND tp.eliminated_after_round IS NULL
+                ORDER BY played.sudden_death_place ASC NULLS LAST, tp.total_score DESC, tp.id
+                LIMIT $4
+            )
+            UPDATE tournament_participants SET eliminated_after_round = $3
+            WHERE tournament_id = $2 AND eliminated_after_round IS NULL
+              AND id NOT IN (SELECT id FROM advancing)
+            "#,
+            game_id,
+            round.tournament_id,
+            round.round_number,
+            advance_count as i64
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    sqlx::query!(
+        "UPDATE tournament_rounds SET completed_at = NOW() WHERE game_id = $1",
+        game_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(Some(round))
+}
+
+// Oyun bir turnuva turuysa sonuçları turnuvaya işle ve güncel turnuva sıralamasını oyunculara yayınla.
+// Oyunun bitişini engellemez, hatalar loglanır.
+pub async fn record_round(pool: &Pool<Postgres>, state: &AppState, game_id: i32, game_code: &str) {
+    let round = match finish_round(pool, game_id).await {
+        Ok(Some(round)) => round,
+        Ok(None) => return,
+        Err(e) => {
+            error!("Turnuva turu işlenemedi (game_id={}): {}", game_id, e);
+            return;
+        }
+    };
+
+    info!(
+        "Turnuva turu tamamlandı: tournament_id={}, tur={}",
+        round.tournament_id, round.round_number
+    );
+
+    match standings(pool, round.tournament_id, Some(BROADCAST_STANDINGS_LIMIT)).await {
+        Ok(standings) => {
+            state
+                .broadcast_to_game(game_code, WebSocketMessage::TournamentStandings {
+                    tournament_id: round.tournament_id,
+                    round: round.round_number,
+                    advance_count: round.advance_count,
+                    standings,
+                })
+                .await;
+        }
+        Err(e) => error!("Turnuva sıralaması alınamadı (tournament_id={}): {}", round.tournament_id, e),
+    }
+}
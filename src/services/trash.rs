@@ -0,0 +1,50 @@
+use chrono::{Duration, Utc};
+use log::info;
+use sqlx::{Pool, Postgres};
+
+use crate::config::CONFIG;
+
+// Çöp kutusu temizliği
+// Saklama süresi dolan soru setleri, bu setlerle oynanan oyunlarla birlikte kalıcı olarak silinir.
+// Tek tek silinen sorular ise geri alma süresi dolunca kalıcı olarak silinir.
+
+// Süresi dolan setleri ve soruları sil, silinen set sayısını döndür
+pub async fn purge_expired(pool: &Pool<Postgres>) -> Result<u64, sqlx::Error> {
+    let cutoff = Utc::now() - Duration::days(CONFIG.trash_retention_days);
+
+    let mut tx = pool.begin().await?;
+
+    // Oyunların soru seti bağlantısı cascade değildir, önce oyunlar silinir
+    sqlx::query!(
+        r#"
+        DELETE FROM games
+        WHERE question_set_id IN (SELECT id FROM question_sets WHERE deleted_at < $1)
+        "#,
+        cutoff
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let purged = sqlx::query!("DELETE FROM question_sets WHERE deleted_at < $1", cutoff)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    tx.commit().await?;
+
+    if purged > 0 {
+        info!("Çöp kutusundan {} soru seti kalıcı olarak silindi", purged);
+    }
+
+    let question_cutoff = Utc::now() - Duration::minutes(CONFIG.question_undo_window_minutes);
+    let purged_questions = sqlx::query!("DELETE FROM questions WHERE deleted_at < $1", question_cutoff)
+        .execute(pool)
+        .await?
+        .rows_affected();
+
+    if purged_questions > 0 {
+        info!("Geri alma süresi dolan {} soru kalıcı olarak silindi", purged_questions);
+    }
+
+    Ok(purged)
+}
\ No newline at end of file
@@ -0,0 +1,233 @@
+use base64::Engine;
+use log::{error, warn};
+use serde::Serialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use sqlx::{Pool, Postgres};
+use std::time::Duration;
+
+use crate::config::CONFIG;
+use crate::services::media;
+use crate::services::quota::{self, QuotaError, QuotaMetric};
+
+// Sesli okuma (TTS)
+// Soru metni ve şıklar yapılandırılan sağlayıcıya seslendirilir, ses dosyası medya deposunda saklanır ve
+// oyunculara gönderilen soru mesajına audio_url olarak eklenir. Dosya adı sağlayıcı, ses, dil ve metinden
+// türetildiği için değişmeyen bir soru bir kez seslendirilir; metin değişince yeni dosya üretilir.
+// Seslendirme oyun akışını bekletmez: oyun başlarken setin sesleri arka planda hazırlanır, soru
+// gösterildiğinde sesi hazır olmayan soru sessiz gönderilir.
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TtsProvider {
+    // Sesli okuma kapalı
+    None,
+    // Google Cloud Text-to-Speech
+    Google,
+    // TTS_API_URL'e {text, language, voice} JSON'u gönderilir, yanıt gövdesi MP3 sestir
+    Http,
+}
+
+impl TtsProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TtsProvider::None => "none",
+            TtsProvider::Google => "google",
+            TtsProvider::Http => "http",
+        }
+    }
+}
+
+impl std::str::FromStr for TtsProvider {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "none" | "" => Ok(TtsProvider::None),
+            "google" => Ok(TtsProvider::Google),
+            "http" => Ok(TtsProvider::Http),
+            _ => Err(format!("Bilinmeyen TTS sağlayıcısı: {}", value)),
+        }
+    }
+}
+
+pub fn is_enabled() -> bool {
+    CONFIG.tts_provider != TtsProvider::None
+}
+
+// Seslendirilecek metin: soru ve ardından şıklar
+fn speech_text(question_text: &str, options: &[String; 4]) -> String {
+    format!(
+        "{}\nA) {}\nB) {}\nC) {}\nD) {}",
+        question_text.trim(),
+        options[0].trim(),
+        options[1].trim(),
+        options[2].trim(),
+        options[3].trim()
+    )
+}
+
+fn file_name(language: &str, text: &str) -> String {
+    let key = format!("{}|{}|{}|{}", CONFIG.tts_provider.as_str(), CONFIG.tts_voice, language, text);
+    format!("tts-{}.mp3", hex::encode(Sha256::digest(key.as_bytes())))
+}
+
+// Sorunun hazır sesinin adresi; servis kapalıysa veya ses henüz üretilmediyse None
+pub async fn cached_audio_url(language: &str, question_text: &str, options: &[String; 4]) -> Option<String> {
+    if !is_enabled() {
+        return None;
+    }
+
+    let name = file_name(language, &speech_text(question_text, options));
+    media::exists(&name).await.then(|| media::url(&name))
+}
+
+// Sorunun sesini üret (hazırsa yeniden üretmez); sesin adresini ve yeni üretilip üretilmediğini döndürür.
+// Kullanıcı verilmişse her seslendirme yapay zeka üretim kotasından, dosya boyutu medya depolama kotasından düşülür.
+pub async fn audio_url(
+    pool: &Pool<Postgres>,
+    charged_to: Option<i32>,
+    language: &str,
+    question_text: &str,
+    options: &[String; 4],
+) -> Result<(String, bool), anyhow::Error> {
+    let text = speech_text(question_text, options);
+    let name = file_name(language, &text);
+    if media::exists(&name).await {
+        return Ok((media::url(&name), false));
+    }
+
+    if let Some(user_id) = charged_to {
+        quota::consume(pool, user_id, QuotaMetric::AiGenerations, 1).await?;
+    }
+    let audio = match synthesize(language, &text).await {
+        Ok(audio) => audio,
+        Err(e) => {
+            // Sağlayıcı ses üretemediyse kota geri verilir
+            if let Some(user_id) = charged_to {
+                if let Err(e) = quota::refund(pool, user_id, QuotaMetric::AiGenerations, 1).await {
+                    error!("Yapay zeka kotası geri verilemedi (user_id={}): {}", user_id, e);
+                }
+            }
+            return Err(e);
+        }
+    };
+    media::store(pool, charged_to, &name, &audio).await?;
+    Ok((media::url(&name), true))
+}
+
+// Metni sağlayıcıya seslendir
+async fn synthesize(language: &str, text: &str) -> Result<Vec<u8>, anyhow::Error> {
+    let client = reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+
+    match CONFIG.tts_provider {
+        TtsProvider::None => Err(anyhow::anyhow!("Sesli okuma yapılandırılmamış")),
+        TtsProvider::Google => {
+            let mut voice = json!({ "languageCode": language });
+            if !CONFIG.tts_voice.is_empty() {
+                voice["name"] = json!(CONFIG.tts_voice);
+            }
+
+            let response: serde_json::Value = client
+                .post(&CONFIG.tts_api_url)
+                .query(&[("key", &CONFIG.tts_api_key)])
+                .json(&json!({
+                    "input": { "text": text },
+                    "voice": voice,
+                    "audioConfig": { "audioEncoding": "MP3" }
+                }))
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+            let content = response["audioContent"]
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("TTS yanıtında ses bulunamadı"))?;
+            Ok(base64::engine::general_purpose::STANDARD.decode(content)?)
+        }
+        TtsProvider::Http => {
+            let mut request = client.post(&CONFIG.tts_api_url).json(&json!({
+                "text": text,
+                "language": language,
+                "voice": CONFIG.tts_voice,
+                "format": "mp3"
+            }));
+            if !CONFIG.tts_api_key.is_empty() {
+                request = request.bearer_auth(&CONFIG.tts_api_key);
+            }
+
+            let audio = request.send().await?.error_for_status()?.bytes().await?;
+            if audio.is_empty() {
+                return Err(anyhow::anyhow!("TTS yanıtında ses bulunamadı"));
+            }
+            Ok(audio.to_vec())
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct PreparationSummary {
+    pub generated: usize,
+    pub cached: usize,
+    pub failed: usize,
+    // Hazırlık kota dolduğu için yarıda kaldıysa aşılan kota, kullanım ve limit
+    #[serde(skip)]
+    pub quota_exceeded: Option<(QuotaMetric, i64, i64)>,
+}
+
+// Soru setinin tüm soruları ve çevirileri için sesleri hazırla; üretim charged_to kullanıcısının kotalarından
+// düşülür, kota dolunca kalan sorular denenmez
+pub async fn prepare_question_set(
+    pool: &Pool<Postgres>,
+    set_id: i32,
+    charged_to: Option<i32>,
+) -> Result<PreparationSummary, sqlx::Error> {
+    let questions = sqlx::query!(
+        r#"
+        SELECT qs.language as locale, q.question_text, q.option_a, q.option_b, q.option_c, q.option_d, q.position
+        FROM questions q
+        JOIN question_sets qs ON q.question_set_id = qs.id
+        WHERE q.question_set_id = $1 AND q.deleted_at IS NULL
+        UNION ALL
+        SELECT t.locale, t.question_text, t.option_a, t.option_b, t.option_c, t.option_d, q.position
+        FROM question_translations t
+        JOIN questions q ON t.question_id = q.id
+        WHERE q.question_set_id = $1 AND q.deleted_at IS NULL
+        ORDER BY position, locale
+        "#,
+        set_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut summary = PreparationSummary::default();
+    for q in questions {
+        let (Some(locale), Some(question_text)) = (q.locale, q.question_text) else {
+            continue;
+        };
+        let options = [
+            q.option_a.unwrap_or_default(),
+            q.option_b.unwrap_or_default(),
+            q.option_c.unwrap_or_default(),
+            q.option_d.unwrap_or_default(),
+        ];
+
+        match audio_url(pool, charged_to, &locale, &question_text, &options).await {
+            Ok((_, true)) => summary.generated += 1,
+            Ok((_, false)) => summary.cached += 1,
+            Err(e) => {
+                if let Some(&QuotaError::Exceeded { metric, used, limit }) = e.downcast_ref::<QuotaError>() {
+                    summary.quota_exceeded = Some((metric, used, limit));
+                    break;
+                }
+                warn!("Soru seslendirilemedi (set_id={}, dil={}): {}", set_id, locale, e);
+                summary.failed += 1;
+            }
+        }
+    }
+
+    Ok(summary)
+}
\ No newline at end of file
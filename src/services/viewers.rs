@@ -0,0 +1,87 @@
+use log::error;
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+use crate::db::repositories::read_pool;
+
+// Oyun izleyicileri
+// Oyuncu veya host olmadan oyuna bağlanan istemciler (ör. sınıftaki projeksiyon, veli ekranı) oyunun
+// yayınlarını host ile aynı şekilde alır. Her oyun için izleyici sayısı MAX_VIEWERS_PER_GAME ile sınırlanır;
+// izleyici bağlantılarının başlangıç ve bitiş zamanları oyun istatistikleri için kaydedilir.
+
+// Oyunun izleyici istatistikleri
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ViewerReport {
+    pub total_viewers: i64,
+    pub peak_concurrent_viewers: i64,
+    pub total_watch_seconds: i64,
+    pub avg_watch_seconds: i64,
+}
+
+// İzleyici bağlantısının başladığını kaydet; hata oyunu izlemeyi engellemez
+pub async fn record_join(pool: &Pool<Postgres>, game_id: i32, session_id: &str) {
+    if let Err(e) = sqlx::query!(
+        "INSERT INTO game_viewers (game_id, session_id) VALUES ($1, $2)",
+        game_id,
+        session_id
+    )
+    .execute(pool)
+    .await
+    {
+        error!("İzleyici kaydedilemedi (game_id={}): {}", game_id, e);
+    }
+}
+
+// İzleyici bağlantısının bittiğini kaydet
+pub async fn record_leave(pool: &Pool<Postgres>, session_id: &str) {
+    if let Err(e) = sqlx::query!(
+        "UPDATE game_viewers SET left_at = NOW() WHERE session_id = $1 AND left_at IS NULL",
+        session_id
+    )
+    .execute(pool)
+    .await
+    {
+        error!("İzleyici ayrılışı kaydedilemedi: {}", e);
+    }
+}
+
+// Oyunun izleyici raporu. Oyun bittikten sonra açık kalan bağlantılar oyunun bitişine kadar sayılır; sunucu
+// yeniden başlatıldığında kapanışı kaydedilemeyen bağlantılar da böylece sınırlanır. Rapor replikadan okunur.
+pub async fn game_report(pool: &Pool<Postgres>, game_id: i32) -> Result<ViewerReport, sqlx::Error> {
+    let pool = read_pool(pool);
+    let report = sqlx::query!(
+        r#"
+        WITH watched AS (
+            SELECT v.joined_at as started,
+                   GREATEST(v.joined_at, LEAST(COALESCE(v.left_at, NOW()), COALESCE(g.ended_at, NOW()))) as ended
+            FROM game_viewers v
+            JOIN games g ON g.id = v.game_id
+            WHERE v.game_id = $1
+        ),
+        edges AS (
+            SELECT started as at, 1 as delta FROM watched
+            UNION ALL
+            SELECT ended, -1 FROM watched
+        )
+        SELECT (SELECT COUNT(*) FROM watched) as "total_viewers!",
+               COALESCE((SELECT MAX(running) FROM (
+                   SELECT SUM(delta) OVER (ORDER BY at, delta) as running FROM edges
+               ) r), 0)::bigint as "peak_concurrent_viewers!",
+               COALESCE((SELECT SUM(EXTRACT(EPOCH FROM ended - started)) FROM watched), 0)::bigint as "total_watch_seconds!"
+        "#,
+        game_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(ViewerReport {
+        total_viewers: report.total_viewers,
+        peak_concurrent_viewers: report.peak_concurrent_viewers,
+        total_watch_seconds: report.total_watch_seconds,
+        avg_watch_seconds: if report.total_viewers > 0 {
+            report.total_watch_seconds / report.total_viewers
+        } else {
+            0
+        },
+    })
+}
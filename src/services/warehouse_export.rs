@@ -0,0 +1,469 @@
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use lazy_static::lazy_static;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{Pool, Postgres};
+use std::path::PathBuf;
+use tokio::sync::Mutex;
+
+use crate::config::CONFIG;
+use crate::services::settings;
+use crate::utils::{csv, security};
+
+// Veri ambarı dışa aktarımı
+// Analistlerin canlı veritabanını sorgulaması yerine tamamlanan oyunlar ve cevaplar düzenli aralıklarla
+// anonimleştirilmiş olgu tabloları (games.csv, answers.csv) olarak yerel bir dizine veya S3'e yazılır.
+// Her aktarım yalnızca son başarılı aktarımdan sonra biten oyunları içerir. Kullanıcı adı, oyuncu takma adı
+// ve e-posta aktarılmaz; öğretmenler ve oyuncular anahtarlı takma adlarla temsil edilir, böylece aynı
+// öğrenci farklı oyunlarda eşleştirilebilir ama kimliği çıkarılamaz. Zamanlama ve hedef admin panelinden
+// ayarlanır; S3 kimlik bilgileri yalnızca ortam değişkenlerinde tutulur.
+
+// Zamanlayıcının aktarım zamanının gelip gelmediğini kontrol etme aralığı
+pub const SCHEDULER_INTERVAL: std::time::Duration = std::time::Duration::from_secs(600);
+
+// Aktarım aralığı sınırları (saat)
+const MIN_INTERVAL_HOURS: i64 = 1;
+const MAX_INTERVAL_HOURS: i64 = 24 * 7;
+
+// Tek bir dosyanın S3'e yüklenmesi için en uzun süre
+const UPLOAD_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
+
+// Admin paneline listelenen son aktarım sayısı
+const RECENT_EXPORT_LIMIT: i64 = 20;
+
+lazy_static! {
+    // Zamanlanmış ve elle başlatılan aktarımlar üst üste binmesin
+    static ref EXPORT_LOCK: Mutex<()> = Mutex::new(());
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExportDestination {
+    // EXPORT_DIR altında her aktarım için bir klasör
+    #[default]
+    Local,
+    // S3 veya S3 uyumlu depo; endpoint verilirse yol tarzı adresleme kullanılır (ör. MinIO)
+    S3 {
+        bucket: String,
+        region: String,
+        #[serde(default)]
+        prefix: String,
+        #[serde(default)]
+        endpoint: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSettings {
+    pub enabled: bool,
+    pub interval_hours: i64,
+    pub destination: ExportDestination,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        ExportSettings {
+            enabled: false,
+            interval_hours: 24,
+            destination: ExportDestination::Local,
+        }
+    }
+}
+
+// S3 nesne anahtarlarında imzalama sırasında kodlama gerektirmeyen karakterler
+fn is_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '/' | '-' | '_' | '.')
+}
+
+impl ExportSettings {
+    // Admin panelinden gelen ayarları doğrula ve normalleştir
+    pub fn validate(mut self) -> Result<Self, &'static str> {
+        if !(MIN_INTERVAL_HOURS..=MAX_INTERVAL_HOURS).contains(&self.interval_hours) {
+            return Err("Aktarım aralığı 1-168 saat arasında olmalıdır");
+        }
+
+        if let ExportDestination::S3 { bucket, region, prefix, endpoint } = &mut self.destination {
+            *bucket = bucket.trim().to_string();
+            if bucket.len() < 3
+                || bucket.len() > 63
+                || !bucket.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '-')
+                || !bucket.starts_with(|c: char| c.is_ascii_alphanumeric())
+                || !bucket.ends_with(|c: char| c.is_ascii_alphanumeric())
+            {
+                return Err("Geçersiz S3 bucket adı");
+            }
+
+            *region = region.trim().to_string();
+            if region.is_empty()
+                || region.len() > 32
+                || !region.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            {
+                return Err("Geçersiz S3 bölgesi");
+            }
+
+            *prefix = prefix.trim().trim_matches('/').to_string();
+            if prefix.len() > 200 || !prefix.chars().all(is_key_char) || prefix.contains("..") || prefix.contains("//") {
+                return Err("Ön ek yalnızca harf, rakam, '/', '-', '_' ve '.' içerebilir");
+            }
+
+            *endpoint = endpoint.as_deref().map(str::trim).filter(|e| !e.is_empty()).map(|e| e.trim_end_matches('/').to_string());
+            if let Some(endpoint) = endpoint {
+                match url::Url::parse(endpoint) {
+                    Ok(url) if matches!(url.scheme(), "http" | "https") && url.host_str().is_some() && url.path() == "/" => {}
+                    _ => return Err("S3 adresi http(s)://sunucu[:port] biçiminde olmalıdır"),
+                }
+            }
+
+            if CONFIG.export_s3_access_key_id.is_empty() || CONFIG.export_s3_secret_access_key.is_empty() {
+                return Err("S3 kimlik bilgileri (EXPORT_S3_ACCESS_KEY_ID, EXPORT_S3_SECRET_ACCESS_KEY) yapılandırılmamış");
+            }
+        }
+
+        Ok(self)
+    }
+}
+
+pub fn s3_credentials_configured() -> bool {
+    !CONFIG.export_s3_access_key_id.is_empty() && !CONFIG.export_s3_secret_access_key.is_empty()
+}
+
+// Kayıtlı ayarlar; kayıt yoksa veya okunamazsa aktarım kapalıdır
+pub async fn current_settings(pool: &Pool<Postgres>) -> ExportSettings {
+    match settings::get_setting(pool, settings::SETTING_WAREHOUSE_EXPORT).await {
+        Ok(Some(value)) => serde_json::from_str(&value).unwrap_or_else(|e| {
+            error!("Veri ambarı aktarım ayarları okunamadı: {}", e);
+            ExportSettings::default()
+        }),
+        Ok(None) => ExportSettings::default(),
+        Err(e) => {
+            error!("Veri ambarı aktarım ayarları okunamadı: {}", e);
+            ExportSettings::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportRun {
+    pub id: i32,
+    pub window_start: Option<DateTime<Utc>>,
+    pub window_end: DateTime<Utc>,
+    pub location: String,
+    pub game_count: i32,
+    pub answer_count: i32,
+    pub status: String,
+    pub error: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+pub async fn recent_exports(pool: &Pool<Postgres>) -> Result<Vec<ExportRun>, sqlx::Error> {
+    sqlx::query_as!(
+        ExportRun,
+        r#"
+        SELECT id, window_start, window_end, location, game_count, answer_count, status, error, started_at, finished_at
+        FROM warehouse_exports
+        ORDER BY started_at DESC
+        LIMIT $1
+        "#,
+        RECENT_EXPORT_LIMIT
+    )
+    .fetch_all(pool)
+    .await
+}
+
+// Aktarım etkinse ve son aktarımın üzerinden ayarlanan süre geçtiyse aktar (zamanlayıcı tarafından çağrılır)
+pub async fn run_if_due(pool: &Pool<Postgres>) {
+    let export_settings = current_settings(pool).await;
+    if !export_settings.enabled {
+        return;
+    }
+
+    let last_started = match sqlx::query_scalar!("SELECT MAX(started_at) FROM warehouse_exports").fetch_one(pool).await {
+        Ok(last) => last,
+        Err(e) => {
+            error!("Son veri ambarı aktarımı okunamadı: {}", e);
+            return;
+        }
+    };
+    if last_started.is_some_and(|t| Utc::now() - t < Duration::hours(export_settings.interval_hours)) {
+        return;
+    }
+
+    if let Err(e) = run_export(pool, &export_settings).await {
+        error!("Veri ambarı aktarımı kaydedilemedi: {}", e);
+    }
+}
+
+// Son başarılı aktarımdan bu yana biten oyunları aktar. Başka bir aktarım sürüyorsa None döner; hedefe
+// yazma hataları aktarım kaydına işlenir ve kayıt "failed" durumuyla döner.
+pub async fn run_export(pool: &Pool<Postgres>, export_settings: &ExportSettings) -> Result<Option<ExportRun>, sqlx::Error> {
+    let Ok(_guard) = EXPORT_LOCK.try_lock() else {
+        return Ok(None);
+    };
+
+    let window_start =
+        sqlx::query_scalar!("SELECT MAX(window_end) FROM warehouse_exports WHERE status = 'completed'")
+            .fetch_one(pool)
+            .await?;
+    let window_end = Utc::now();
+
+    let run_id = sqlx::query_scalar!(
+        "INSERT INTO warehouse_exports (window_start, window_end, location) VALUES ($1, $2, '') RETURNING id",
+        window_start,
+        window_end
+    )
+    .fetch_one(pool)
+    .await?;
+
+    // Klasör adı zamana göre sıralanır, aynı saniyedeki aktarımlar kayıt numarasıyla ayrılır
+    let stamp = format!("{}-{}", window_end.format("%Y%m%dT%H%M%SZ"), run_id);
+    let location = location(&export_settings.destination, &stamp);
+    sqlx::query!("UPDATE warehouse_exports SET location = $2 WHERE id = $1", run_id, location)
+        .execute(pool)
+        .await?;
+
+    match export_window(pool, &export_settings.destination, window_start, window_end, &stamp).await {
+        Ok((game_count, answer_count)) => {
+            info!("Veri ambarına {} oyun ve {} cevap aktarıldı: {}", game_count, answer_count, location);
+            sqlx::query!(
+                r#"
+                UPDATE warehouse_exports
+                SET status = 'completed', game_count = $2, answer_count = $3, finished_at = CURRENT_TIMESTAMP
+                WHERE id = $1
+                "#,
+                run_id,
+                game_count as i32,
+                answer_count as i32
+            )
+            .execute(pool)
+            .await?;
+        }
+        Err(e) => {
+            error!("Veri ambarı aktarımı başarısız ({}): {}", location, e);
+            sqlx::query!(
+                "UPDATE warehouse_exports SET status = 'failed', error = $2, finished_at = CURRENT_TIMESTAMP WHERE id = $1",
+                run_id,
+                e.to_string()
+            )
+            .execute(pool)
+            .await?;
+        }
+    }
+
+    sqlx::query_as!(
+        ExportRun,
+        r#"
+        SELECT id, window_start, window_end, location, game_count, answer_count, status, error, started_at, finished_at
+        FROM warehouse_exports
+        WHERE id = $1
+        "#,
+        run_id
+    )
+    .fetch_one(pool)
+    .await
+    .map(Some)
+}
+
+fn location(destination: &ExportDestination, stamp: &str) -> String {
+    match destination {
+        ExportDestination::Local => PathBuf::from(&CONFIG.export_dir).join(stamp).display().to_string(),
+        ExportDestination::S3 { bucket, prefix, .. } => format!("s3://{}/{}", bucket, object_key(prefix, stamp, "")),
+    }
+}
+
+fn object_key(prefix: &str, stamp: &str, file: &str) -> String {
+    if prefix.is_empty() {
+        format!("{}/{}", stamp, file)
+    } else {
+        format!("{}/{}/{}", prefix, stamp, file)
+    }
+}
+
+fn timestamp(value: Option<DateTime<Utc>>) -> String {
+    value.map(|t| t.to_rfc3339()).unwrap_or_default()
+}
+
+// Aralıkta biten oyunların olgu tablolarını üret ve hedefe yaz; aktarılan oyun ve cevap sayısını döndür
+async fn export_window(
+    pool: &Pool<Postgres>,
+    destination: &ExportDestination,
+    window_start: Option<DateTime<Utc>>,
+    window_end: DateTime<Utc>,
+    stamp: &str,
+) -> Result<(usize, usize), anyhow::Error> {
+    let games = sqlx::query!(
+        r#"
+        SELECT g.id, g.question_set_id, g.host_id, u.organization_id, g.is_competitive,
+               g.created_at, g.started_at, g.ended_at,
+               (SELECT COUNT(*) FROM game_question_ids(g.id)) as "question_count!",
+               (SELECT COUNT(*) FROM players p WHERE p.game_id = g.id) as "player_count!",
+               (SELECT COUNT(*) FROM players p WHERE p.game_id = g.id AND p.user_id IS NOT NULL) as "registered_player_count!"
+        FROM games g
+        JOIN users u ON g.host_id = u.id
+        WHERE g.status = 'completed'
+          AND g.ended_at <= $2
+          AND ($1::timestamptz IS NULL OR g.ended_at > $1)
+        ORDER BY g.ended_at
+        "#,
+        window_start,
+        window_end
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut game_lines = vec![
+        "game_id,question_set_id,host_key,organization_id,is_competitive,question_count,player_count,registered_player_count,created_at,started_at,ended_at"
+            .to_string(),
+    ];
+    for g in &games {
+        game_lines.push(format!(
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            g.id,
+            g.question_set_id,
+            security::pseudonym("user", g.host_id),
+            g.organization_id.map(|id| id.to_string()).unwrap_or_default(),
+            g.is_competitive,
+            g.question_count,
+            g.player_count,
+            g.registered_player_count,
+            timestamp(g.created_at),
+            timestamp(g.started_at),
+            timestamp(g.ended_at)
+        ));
+    }
+
+    let game_ids: Vec<i32> = games.iter().map(|g| g.id).collect();
+    let answers = sqlx::query!(
+        r#"
+        SELECT p.game_id, pa.question_id, p.id as player_id, p.user_id, pa.answer, pa.is_correct,
+               pa.response_time_ms, pa.points_earned, pa.answered_at
+        FROM player_answers pa
+        JOIN players p ON pa.player_id = p.id
+        WHERE p.game_id = ANY($1)
+        ORDER BY p.game_id, pa.answered_at
+        "#,
+        &game_ids
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut answer_lines = vec![
+        "game_id,question_id,player_key,is_guest,answer,is_correct,response_time_ms,points_earned,answered_at".to_string(),
+    ];
+    for a in &answers {
+        // Kayıtlı kullanıcılar oyunlar arasında eşleşsin diye kullanıcı kimliğinden, misafirler oyuncu kaydından
+        let player_key = match a.user_id {
+            Some(user_id) => security::pseudonym("user", user_id),
+            None => security::pseudonym("player", a.player_id),
+        };
+        answer_lines.push(format!(
+            "{},{},{},{},{},{},{},{},{}",
+            a.game_id,
+            a.question_id,
+            player_key,
+            a.user_id.is_none(),
+            csv::field(a.answer.as_deref().unwrap_or_default()),
+            a.is_correct,
+            a.response_time_ms.map(|t| t.to_string()).unwrap_or_default(),
+            a.points_earned.unwrap_or(0),
+            timestamp(a.answered_at)
+        ));
+    }
+
+    let files = [
+        ("games.csv", csv::join_lines(&game_lines)),
+        ("answers.csv", csv::join_lines(&answer_lines)),
+    ];
+
+    match destination {
+        ExportDestination::Local => {
+            let dir = PathBuf::from(&CONFIG.export_dir).join(stamp);
+            tokio::fs::create_dir_all(&dir).await?;
+            for (name, content) in files {
+                tokio::fs::write(dir.join(name), content).await?;
+            }
+        }
+        ExportDestination::S3 { bucket, region, prefix, endpoint } => {
+            for (name, content) in files {
+                put_s3_object(bucket, region, endpoint.as_deref(), &object_key(prefix, stamp, name), content.into_bytes()).await?;
+            }
+        }
+    }
+
+    Ok((games.len(), answers.len()))
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC her uzunlukta anahtarı kabul eder");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+// Nesneyi AWS Signature V4 ile imzalanmış bir PUT isteğiyle yükle. Anahtar yalnızca kodlama gerektirmeyen
+// karakterlerden oluştuğu için (ayarlar doğrulanırken kontrol edilir) yol olduğu gibi imzalanır.
+async fn put_s3_object(
+    bucket: &str,
+    region: &str,
+    endpoint: Option<&str>,
+    key: &str,
+    body: Vec<u8>,
+) -> Result<(), anyhow::Error> {
+    let url = match endpoint {
+        Some(endpoint) => url::Url::parse(&format!("{}/{}/{}", endpoint, bucket, key))?,
+        None => url::Url::parse(&format!("https://{}.s3.{}.amazonaws.com/{}", bucket, region, key))?,
+    };
+    let host = match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        (Some(host), None) => host.to_string(),
+        (None, _) => return Err(anyhow::anyhow!("Geçersiz S3 adresi")),
+    };
+
+    let now = Utc::now();
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let payload_hash = hex::encode(Sha256::digest(&body));
+
+    let canonical_request = format!(
+        "PUT\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\nhost;x-amz-content-sha256;x-amz-date\n{}",
+        url.path(),
+        host,
+        payload_hash,
+        amz_date,
+        payload_hash
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let mut signing_key = hmac_sha256(format!("AWS4{}", CONFIG.export_s3_secret_access_key).as_bytes(), &date);
+    for part in [region, "s3", "aws4_request"] {
+        signing_key = hmac_sha256(&signing_key, part);
+    }
+    let signature = hex::encode(hmac_sha256(&signing_key, &string_to_sign));
+
+    let client = reqwest::Client::builder().timeout(UPLOAD_TIMEOUT).build()?;
+    client
+        .put(url)
+        .header("x-amz-date", amz_date)
+        .header("x-amz-content-sha256", payload_hash)
+        .header(
+            "Authorization",
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={}",
+                CONFIG.export_s3_access_key_id, scope, signature
+            ),
+        )
+        .header("Content-Type", "text/csv; charset=utf-8")
+        .body(body)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
\ No newline at end of file
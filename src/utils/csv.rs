@@ -0,0 +1,15 @@
+// CSV dışa aktarımları için yardımcılar
+
+// Alanı gerekiyorsa tırnak içine al (ayraç, tırnak veya satır sonu içeren alanlar)
+pub fn field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Satırları CRLF ile birleştir (Excel ve LMS içe aktarma ekranlarının beklediği biçim)
+pub fn join_lines(lines: &[String]) -> String {
+    lines.join("\r\n") + "\r\n"
+}
\ No newline at end of file
@@ -0,0 +1,27 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+// Rapor tarih aralıkları için yardımcılar (UTC günleri, bitiş günü dahil)
+
+// Sorgudaki tarih aralığını çöz; bitiş verilmezse bugün, başlangıç verilmezse bitişten geriye default_days gün
+pub fn resolve(
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    default_days: i64,
+    max_days: i64,
+) -> Result<(NaiveDate, NaiveDate), String> {
+    let to = to.unwrap_or_else(|| Utc::now().date_naive());
+    let from = from.unwrap_or(to - Duration::days(default_days - 1));
+
+    if from > to {
+        return Err("Başlangıç tarihi bitiş tarihinden sonra olamaz".to_string());
+    }
+    if (to - from).num_days() >= max_days {
+        return Err(format!("Rapor aralığı en fazla {} gün olabilir", max_days));
+    }
+    Ok((from, to))
+}
+
+// Günün başlangıcı (UTC)
+pub fn day_start(date: NaiveDate) -> DateTime<Utc> {
+    date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()
+}
\ No newline at end of file
@@ -0,0 +1,44 @@
+use actix_web::{http::header, HttpMessage, HttpRequest};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::time::SystemTime;
+
+// Koşullu istek desteği
+// Sık yoklanan uç noktalar (lobideki oyun bilgisi, soru seti) yanıtlarını belirleyen değerlerden (son
+// güncelleme zamanı, sayımlar, kullanıcının yetkisi) bir ETag üretir. İstemci If-None-Match ile aynı değeri
+// gönderirse gövde yerine 304 döner. Düzenlemelerde If-Unmodified-Since ön koşulu okunur (iyimser kilitleme).
+
+// Verilen parçalardan zayıf ETag üret (W/"<sha256 özetinin ilk 32 karakteri>")
+pub fn weak_etag(parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part.as_bytes());
+        hasher.update([0u8]);
+    }
+    let digest = hex::encode(hasher.finalize());
+    format!("W/\"{}\"", &digest[..32])
+}
+
+// İsteğin If-None-Match başlığı ETag ile eşleşiyor mu (zayıf karşılaştırma, "*" ve virgülle ayrılmış liste dahil)
+pub fn if_none_match(req: &HttpRequest, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let wanted = opaque(etag);
+
+    req.headers()
+        .get_all(header::IF_NONE_MATCH)
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|tag| tag.trim() == "*" || opaque(tag) == wanted)
+}
+
+// If-Unmodified-Since başlığındaki zaman; başlık yoksa veya geçersizse None (RFC 9110'a göre yok sayılır).
+// HTTP tarihleri saniye hassasiyetindedir; karşılaştırma yapılan zaman da saniyeye yuvarlanmalıdır.
+pub fn if_unmodified_since(req: &HttpRequest) -> Option<DateTime<Utc>> {
+    let header::IfUnmodifiedSince(date) = req.get_header::<header::IfUnmodifiedSince>()?;
+    Some(DateTime::<Utc>::from(SystemTime::from(date)))
+}
+
+// Last-Modified başlığı için HTTP tarihi
+pub fn http_date(time: DateTime<Utc>) -> String {
+    header::HttpDate::from(SystemTime::from(time)).to_string()
+}
\ No newline at end of file
@@ -1,2 +1,5 @@
+pub mod csv;
+pub mod date_range;
+pub mod etag;
 pub mod security;
 pub mod validation;
\ No newline at end of file
@@ -1,10 +1,15 @@
+use actix_web::HttpRequest;
 use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
     Argon2,
 };
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::net::{IpAddr, SocketAddr};
 use uuid::Uuid;
 
 use crate::{config::CONFIG, db::models::Claims};
@@ -26,16 +31,30 @@ pub fn verify_password(password: &str, hash: &str) -> Result<bool, anyhow::Error
     Ok(result.is_ok())
 }
 
+// Token içine yazılacak kullanıcı bilgileri
+pub struct TokenUser<'a> {
+    pub id: i32,
+    pub role: &'a str,
+    pub username: &'a str,
+    pub organization_id: Option<i32>,
+    pub is_org_admin: bool,
+    pub token_version: i32,
+}
+
 // JWT token oluşturma
-pub fn generate_jwt(user_id: i32, role: &str) -> Result<String, anyhow::Error> {
+pub fn generate_jwt(user: &TokenUser) -> Result<String, anyhow::Error> {
     let expiration = Utc::now()
         .checked_add_signed(Duration::seconds(CONFIG.jwt_expiration))
         .expect("Invalid timestamp")
         .timestamp() as usize;
 
     let claims = Claims {
-        sub: user_id.to_string(),
-        role: role.to_string(),
+        sub: user.id.to_string(),
+        role: user.role.to_string(),
+        username: user.username.to_string(),
+        organization_id: user.organization_id,
+        is_org_admin: user.is_org_admin,
+        ver: user.token_version,
         exp: expiration,
     };
 
@@ -59,6 +78,107 @@ pub fn decode_jwt(token: &str) -> Result<Claims, anyhow::Error> {
     Ok(token_data.claims)
 }
 
+// Kişiye özel oyun katılım bağlantısının içeriği; oturum tokenlarıyla karışmaması için ayrı bir audience taşır
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JoinLinkClaims {
+    pub game_id: i32,
+    pub roster_id: i32, // class_roster satırı
+    pub aud: String,
+    pub exp: usize,
+}
+
+const JOIN_LINK_AUDIENCE: &str = "join_link";
+
+// Katılım bağlantısı tokenı oluşturma
+pub fn generate_join_link_token(game_id: i32, roster_id: i32, expires_at: DateTime<Utc>) -> Result<String, anyhow::Error> {
+    let claims = JoinLinkClaims {
+        game_id,
+        roster_id,
+        aud: JOIN_LINK_AUDIENCE.to_string(),
+        exp: expires_at.timestamp() as usize,
+    };
+
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(CONFIG.jwt_secret.as_bytes()),
+    )?;
+
+    Ok(token)
+}
+
+// Katılım bağlantısı tokenı çözme
+pub fn decode_join_link_token(token: &str) -> Result<JoinLinkClaims, anyhow::Error> {
+    let mut validation = Validation::default();
+    validation.set_audience(&[JOIN_LINK_AUDIENCE]);
+
+    let token_data = decode::<JoinLinkClaims>(
+        token,
+        &DecodingKey::from_secret(CONFIG.jwt_secret.as_bytes()),
+        &validation,
+    )?;
+
+    Ok(token_data.claims)
+}
+
+// REST üzerinden cevap gönderen oyuncunun tokenı: "player_id.game_id.exp.imza". İmza, bu alanların oyuncunun
+// güncel oturum kimliğiyle birlikte HMAC'idir; oyuncu yeniden bağlanınca oturumu değiştiği için eski tokenlar
+// geçersiz olur. Oturum kimliği tokenda yer almaz.
+pub struct PlayerToken {
+    pub player_id: i32,
+    pub game_id: i32,
+    exp: i64,
+    signature: Vec<u8>,
+}
+
+fn player_token_mac(key: &[u8], player_id: i32, game_id: i32, exp: i64, session_id: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC her uzunlukta anahtarı kabul eder");
+    mac.update(format!("player:{}:{}:{}:{}", player_id, game_id, exp, session_id).as_bytes());
+    mac
+}
+
+fn sign_player_token(key: &[u8], player_id: i32, game_id: i32, exp: i64, session_id: &str) -> String {
+    let signature = player_token_mac(key, player_id, game_id, exp, session_id).finalize().into_bytes();
+    format!("{}.{}.{}.{}", player_id, game_id, exp, hex::encode(signature))
+}
+
+// Oyuncu tokenı oluşturma; token ve son kullanma zamanı döner
+pub fn generate_player_token(player_id: i32, game_id: i32, session_id: &str) -> (String, DateTime<Utc>) {
+    let expires_at = Utc::now() + Duration::minutes(CONFIG.player_token_ttl_minutes);
+    let token = sign_player_token(CONFIG.jwt_secret.as_bytes(), player_id, game_id, expires_at.timestamp(), session_id);
+
+    (token, expires_at)
+}
+
+// Oyuncu tokenını ayrıştır; biçimi bozuk veya süresi dolmuş tokenlar için None. İmza oyuncunun güncel
+// oturumuyla PlayerToken::verify ile doğrulanmalıdır.
+pub fn parse_player_token(token: &str) -> Option<PlayerToken> {
+    let mut parts = token.trim().splitn(4, '.');
+    let player_id = parts.next()?.parse::<i32>().ok()?;
+    let game_id = parts.next()?.parse::<i32>().ok()?;
+    let exp = parts.next()?.parse::<i64>().ok()?;
+    let signature = hex::decode(parts.next()?).ok()?;
+
+    if exp <= Utc::now().timestamp() {
+        return None;
+    }
+
+    Some(PlayerToken { player_id, game_id, exp, signature })
+}
+
+impl PlayerToken {
+    // İmzayı oyuncunun güncel oturum kimliğiyle sabit sürede doğrula
+    pub fn verify(&self, session_id: &str) -> bool {
+        self.verify_with_key(CONFIG.jwt_secret.as_bytes(), session_id)
+    }
+
+    fn verify_with_key(&self, key: &[u8], session_id: &str) -> bool {
+        player_token_mac(key, self.player_id, self.game_id, self.exp, session_id)
+            .verify_slice(&self.signature)
+            .is_ok()
+    }
+}
+
 // Doğrulama tokeni oluşturma
 pub fn generate_verification_token() -> String {
     Uuid::new_v4().to_string()
@@ -74,6 +194,16 @@ pub fn generate_game_code() -> String {
         .to_uppercase()
 }
 
+// Öğrenci davet kodu oluşturma
+pub fn generate_invite_code() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(10)
+        .map(char::from)
+        .collect::<String>()
+        .to_uppercase()
+}
+
 // Öğretmen onay tokeni oluşturma
 pub fn generate_approval_token() -> String {
     Uuid::new_v4().to_string()
@@ -82,4 +212,198 @@ pub fn generate_approval_token() -> String {
 // Şifre sıfırlama tokeni oluşturma
 pub fn generate_reset_token() -> String {
     Uuid::new_v4().to_string()
-}
\ No newline at end of file
+}
+
+// Sıfırlama tokenının veritabanında saklanan özeti; ham token yalnızca e-postada bulunur
+pub fn hash_reset_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+// Gömülebilir liderlik tablosu tokenı oluşturma
+pub fn generate_widget_token() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+// Widget tokenının veritabanında saklanan özeti; ham token yalnızca oluşturulduğunda öğretmene gösterilir
+pub fn hash_widget_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+// Güvenilir istemci API anahtarı oluşturma (önek, anahtarın loglarda ve gizli bilgi taramalarında tanınmasını sağlar)
+pub fn generate_api_key() -> String {
+    format!("sk_live_{}", Uuid::new_v4().simple())
+}
+
+// API anahtarının veritabanında saklanan özeti; ham anahtar yalnızca oluşturulduğunda admine gösterilir
+pub fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+// İstek yapan istemcinin IP adresi. Forwarded / X-Forwarded-For başlıkları istemci tarafından
+// yazılabildiği için yalnızca bağlantı TRUSTED_PROXIES listesindeki bir proxy'den geliyorsa okunur.
+pub fn client_ip(req: &HttpRequest) -> Option<String> {
+    let forwarded = req.headers().get("forwarded").and_then(|value| value.to_str().ok());
+    let forwarded_for = req.headers().get("x-forwarded-for").and_then(|value| value.to_str().ok());
+
+    resolve_client_ip(
+        req.peer_addr().map(|addr| addr.ip()),
+        forwarded,
+        forwarded_for,
+        &CONFIG.trusted_proxies,
+    )
+    .map(|ip| ip.to_string())
+}
+
+// Proxy zincirini sağdan sola yürü: her güvenilen proxy bir önceki adresi bildirir, güvenilmeyen ilk
+// adres istemcidir. Zincir yalnızca güvenilen proxy'lerden oluşuyorsa en soldaki adres kullanılır.
+fn resolve_client_ip(
+    peer: Option<IpAddr>,
+    forwarded: Option<&str>,
+    forwarded_for: Option<&str>,
+    trusted_proxies: &[IpAddr],
+) -> Option<IpAddr> {
+    let peer = peer?;
+    if !trusted_proxies.contains(&peer) {
+        return Some(peer);
+    }
+
+    // Forwarded (RFC 7239) varsa X-Forwarded-For yerine o kullanılır
+    let hops: Vec<&str> = match (forwarded, forwarded_for) {
+        (Some(forwarded), _) => forwarded
+            .split(',')
+            .filter_map(|element| {
+                element.split(';').find_map(|pair| {
+                    let (key, value) = pair.split_once('=')?;
+                    key.trim().eq_ignore_ascii_case("for").then_some(value)
+                })
+            })
+            .collect(),
+        (None, Some(forwarded_for)) => forwarded_for.split(',').collect(),
+        (None, None) => Vec::new(),
+    };
+
+    let mut client = peer;
+    for hop in hops.iter().rev() {
+        // Ayrıştırılamayan adres (ör. "unknown") zinciri keser; son geçerli adres kullanılır
+        let Some(ip) = parse_hop(hop) else { break };
+        client = ip;
+        if !trusted_proxies.contains(&ip) {
+            break;
+        }
+    }
+
+    Some(client)
+}
+
+// Zincirdeki tek adresi ayrıştır: "1.2.3.4", "1.2.3.4:5678", "[2001:db8::1]:80", "\"[2001:db8::1]\""
+fn parse_hop(hop: &str) -> Option<IpAddr> {
+    let hop = hop.trim().trim_matches('"');
+
+    if let Ok(ip) = hop.parse::<IpAddr>() {
+        return Some(ip);
+    }
+    if let Ok(socket) = hop.parse::<SocketAddr>() {
+        return Some(socket.ip());
+    }
+    hop.strip_prefix('[')
+        .and_then(|rest| rest.split_once(']'))
+        .and_then(|(ip, _)| ip.parse().ok())
+}
+
+// IP adresini anahtarlı olarak hashle; ham IP veritabanında saklanmaz
+pub fn hash_client_ip(ip: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(CONFIG.jwt_secret.as_bytes())
+        .expect("HMAC her uzunlukta anahtarı kabul eder");
+    mac.update(ip.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+// Kimliği anahtarlı olarak takma ada dönüştür; aynı tür ve kimlik her zaman aynı takma adı verir
+pub fn pseudonym(kind: &str, id: i32) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(CONFIG.jwt_secret.as_bytes())
+        .expect("HMAC her uzunlukta anahtarı kabul eder");
+    mac.update(format!("{}:{}", kind, id).as_bytes());
+    hex::encode(&mac.finalize().into_bytes()[..16])
+}
+
+// Dahili API anahtarını sabit sürede karşılaştır (anahtar ayarlanmamışsa her zaman false)
+pub fn internal_token_matches(token: &str) -> bool {
+    if CONFIG.internal_api_token.is_empty() {
+        return false;
+    }
+    let keyed = |value: &str| {
+        let mut mac = Hmac::<Sha256>::new_from_slice(CONFIG.jwt_secret.as_bytes())
+            .expect("HMAC her uzunlukta anahtarı kabul eder");
+        mac.update(value.as_bytes());
+        mac
+    };
+    let expected = keyed(&CONFIG.internal_api_token).finalize().into_bytes();
+    keyed(token).verify_slice(&expected).is_ok()
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(addr: &str) -> IpAddr {
+        addr.parse().unwrap()
+    }
+
+    #[test]
+    fn test_player_token() {
+        let key = b"test-secret";
+        let exp = (Utc::now() + Duration::minutes(5)).timestamp();
+        let token = sign_player_token(key, 7, 42, exp, "oturum-1");
+
+        let parsed = parse_player_token(&token).expect("geçerli token ayrıştırılmalı");
+        assert_eq!((parsed.player_id, parsed.game_id), (7, 42));
+        assert!(parsed.verify_with_key(key, "oturum-1"));
+
+        // Başka oturum veya anahtar
+        assert!(!parsed.verify_with_key(key, "oturum-2"));
+        assert!(!parsed.verify_with_key(b"baska-secret", "oturum-1"));
+
+        // Değiştirilmiş imza, oyuncu veya son kullanma zamanı
+        let mut tampered = token.clone();
+        let last = if tampered.ends_with('0') { "1" } else { "0" };
+        tampered.replace_range(tampered.len() - 1.., last);
+        assert!(!parse_player_token(&tampered).unwrap().verify_with_key(key, "oturum-1"));
+        let other_player = token.replacen("7.", "8.", 1);
+        assert!(!parse_player_token(&other_player).unwrap().verify_with_key(key, "oturum-1"));
+        let extended = token.replacen(&exp.to_string(), &(exp + 3600).to_string(), 1);
+        assert!(!parse_player_token(&extended).unwrap().verify_with_key(key, "oturum-1"));
+
+        // Süresi dolmuş token
+        let expired = sign_player_token(key, 7, 42, Utc::now().timestamp() - 1, "oturum-1");
+        assert!(parse_player_token(&expired).is_none());
+
+        // Bozuk biçim
+        assert!(parse_player_token("").is_none());
+        assert!(parse_player_token("7.42").is_none());
+        assert!(parse_player_token(&format!("7.42.{}", exp)).is_none());
+        assert!(parse_player_token(&format!("a.42.{}.00", exp)).is_none());
+        assert!(parse_player_token(&format!("7.42.{}.zz", exp)).is_none());
+        assert!(parse_player_token(&format!("7.42.{}.abc", exp)).is_none());
+    }
+
+    #[test]
+    fn test_resolve_client_ip() {
+        let proxies = [ip("10.0.0.2"), ip("10.0.0.3")];
+
+        // Güvenilmeyen bağlantının başlıkları yok sayılır
+        assert_eq!(resolve_client_ip(Some(ip("203.0.113.9")), None, Some("1.1.1.1"), &proxies), Some(ip("203.0.113.9")));
+        assert_eq!(resolve_client_ip(Some(ip("203.0.113.9")), None, Some("1.1.1.1"), &[]), Some(ip("203.0.113.9")));
+        assert_eq!(resolve_client_ip(None, None, Some("1.1.1.1"), &proxies), None);
+
+        // Güvenilen proxy arkasında istemcinin eklediği sahte adres atlanır
+        assert_eq!(
+            resolve_client_ip(Some(ip("10.0.0.2")), None, Some("1.1.1.1, 198.51.100.7, 10.0.0.3"), &proxies),
+            Some(ip("198.51.100.7"))
+        );
+        assert_eq!(
+            resolve_client_ip(Some(ip("10.0.0.2")), Some("for=1.1.1.1, for=\"[2001:db8::1]:4711\";proto=https"), Some("9.9.9.9"), &proxies),
+            Some(ip("2001:db8::1"))
+        );
+        assert_eq!(resolve_client_ip(Some(ip("10.0.0.2")), None, Some("unknown"), &proxies), Some(ip("10.0.0.2")));
+        assert_eq!(resolve_client_ip(Some(ip("10.0.0.2")), None, None, &proxies), Some(ip("10.0.0.2")));
+    }
+}
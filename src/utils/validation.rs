@@ -1,19 +1,21 @@
 use regex::Regex;
 use lazy_static::lazy_static;
+use unicode_normalization::UnicodeNormalization;
+use unicode_security::{GeneralSecurityProfile, MixedScript};
 
 lazy_static! {
     static ref EMAIL_REGEX: Regex = Regex::new(
         r"^([a-z0-9_+]([a-z0-9_+.]*[a-z0-9_+])?)@([a-z0-9]+([\-\.]{1}[a-z0-9]+)*\.[a-z]{2,6})"
     ).unwrap();
     
-    static ref USERNAME_REGEX: Regex = Regex::new(
-        r"^[a-zA-Z0-9_]{3,30}$"
-    ).unwrap();
-    
     static ref PASSWORD_REGEX: Regex = Regex::new(
         r"^.{8,100}$"
     ).unwrap();
     
+    static ref DOMAIN_REGEX: Regex = Regex::new(
+        r"^([a-z0-9]+(-[a-z0-9]+)*\.)+[a-z]{2,}$"
+    ).unwrap();
+    
     static ref GAME_CODE_REGEX: Regex = Regex::new(
         r"^[A-Z0-9]{6}$"
     ).unwrap();
@@ -21,23 +23,202 @@ lazy_static! {
 
 // Email formatı kontrolü
 pub fn validate_email(email: &str) -> bool {
-    if !EMAIL_REGEX.is_match(email) {
+    if !validate_email_format(email) {
         return false;
     }
     
     // Edu domain kontrolü
-    let domain = email.split('@').nth(1).unwrap_or("");
+    is_edu_domain(email_domain(email))
+}
+
+// Alan adı kısıtlaması olmadan yalnızca email formatı kontrolü
+pub fn validate_email_format(email: &str) -> bool {
+    EMAIL_REGEX.is_match(email)
+}
+
+// Email adresinin alan adı kısmı
+pub fn email_domain(email: &str) -> &str {
+    email.split('@').nth(1).unwrap_or("")
+}
+
+// Edu alan adı kontrolü
+pub fn is_edu_domain(domain: &str) -> bool {
     domain.ends_with(".edu.tr") || domain.ends_with(".edu")
 }
 
-// Kullanıcı adı kontrolü
-pub fn validate_username(username: &str) -> bool {
+// Alan adı, izin verilen alan adının kendisi veya bir alt alan adı mı
+pub fn domain_matches(domain: &str, allowed: &str) -> bool {
+    domain == allowed
+        || (domain.len() > allowed.len()
+            && domain.ends_with(allowed)
+            && domain.as_bytes()[domain.len() - allowed.len() - 1] == b'.')
+}
+
+// İzin listesine eklenecek alan adı kontrolü
+pub fn validate_domain(domain: &str) -> bool {
+    DOMAIN_REGEX.is_match(domain)
+}
+
+// Kayıtta kabul edilecek e-posta alan adı politikası
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EmailPolicy {
+    // Yalnızca .edu / .edu.tr adresleri
+    EduOnly,
+    // .edu adresleri ve kurumların izin verdiği alan adları
+    EduOrAllowlist,
+    // Yalnızca kurumların izin verdiği alan adları
+    Allowlist,
+    // Her geçerli e-posta adresi
+    Open,
+}
+
+impl EmailPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmailPolicy::EduOnly => "edu_only",
+            EmailPolicy::EduOrAllowlist => "edu_or_allowlist",
+            EmailPolicy::Allowlist => "allowlist",
+            EmailPolicy::Open => "open",
+        }
+    }
+
+    // E-posta adresi bu politikaya göre kayıt olabilir mi
+    pub fn allows(&self, email: &str, in_allowlist: bool) -> bool {
+        let is_edu = validate_email(email);
+        match self {
+            EmailPolicy::EduOnly => is_edu,
+            EmailPolicy::EduOrAllowlist => is_edu || in_allowlist,
+            EmailPolicy::Allowlist => in_allowlist,
+            EmailPolicy::Open => true,
+        }
+    }
+
+    // Politika reddettiğinde kullanıcıya gösterilecek mesaj
+    pub fn rejection_message(&self) -> &'static str {
+        match self {
+            EmailPolicy::EduOnly => "E-posta adresi .edu.tr veya .edu ile bitmelidir",
+            EmailPolicy::EduOrAllowlist => "E-posta adresi .edu.tr veya .edu ile bitmeli ya da kayıtlı bir kurumun alan adına ait olmalıdır",
+            EmailPolicy::Allowlist => "E-posta adresi kayıtlı bir kurumun alan adına ait olmalıdır",
+            EmailPolicy::Open => "Geçersiz e-posta adresi",
+        }
+    }
+}
+
+impl std::str::FromStr for EmailPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "edu_only" => Ok(EmailPolicy::EduOnly),
+            "edu_or_allowlist" => Ok(EmailPolicy::EduOrAllowlist),
+            "allowlist" => Ok(EmailPolicy::Allowlist),
+            "open" => Ok(EmailPolicy::Open),
+            _ => Err(format!("Bilinmeyen e-posta politikası: {}", value)),
+        }
+    }
+}
+
+// Kullanıcı adı ve takma ad uzunluk sınırları (karakter sayısı)
+const USERNAME_MIN_CHARS: usize = 3;
+const USERNAME_MAX_CHARS: usize = 30;
+const NICKNAME_MAX_CHARS: usize = 30;
+
+// ASCII dışında kabul edilen Türkçe harfler
+const TURKISH_LETTERS: &str = "çğıöşüâîûÇĞİÖŞÜÂÎÛ";
+
+// Kullanıcı adı ve misafir takma adlarında kabul edilen karakterler
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NameCharset {
+    // Yalnızca İngilizce harfler, rakamlar ve alt çizgi
+    Ascii,
+    // ASCII ve Türkçe harfler (ç, ğ, ı, İ, ö, ş, ü ve şapkalı ünlüler)
+    Turkish,
+    // Tüm dillerin harfleri; ad tek bir alfabeden oluşmalı (ör. Latin ve Kiril karışamaz)
+    Unicode,
+}
+
+impl NameCharset {
+    fn allows_letter(&self, c: char) -> bool {
+        match self {
+            NameCharset::Ascii => c.is_ascii_alphanumeric(),
+            NameCharset::Turkish => c.is_ascii_alphanumeric() || TURKISH_LETTERS.contains(c),
+            // Görünmez ve kısıtlı karakterler (UTS #39 tanımlayıcı profili dışındakiler) reddedilir
+            NameCharset::Unicode => c.is_alphanumeric() && c.identifier_allowed(),
+        }
+    }
+
+    // Geçersiz kullanıcı adında gösterilecek mesaj
+    pub fn username_rule_message(&self) -> &'static str {
+        match self {
+            NameCharset::Ascii => "Kullanıcı adı geçersiz. 3-30 karakter arasında olmalı ve sadece harf, rakam ve alt çizgi içermelidir.",
+            NameCharset::Turkish => "Kullanıcı adı geçersiz. 3-30 karakter arasında olmalı ve sadece harf (Türkçe karakterler dahil), rakam ve alt çizgi içermelidir.",
+            NameCharset::Unicode => "Kullanıcı adı geçersiz. 3-30 karakter arasında olmalı, sadece harf, rakam ve alt çizgi içermeli ve harfler tek bir alfabeden olmalıdır.",
+        }
+    }
+
+    // Geçersiz takma adda gösterilecek mesaj
+    pub fn nickname_rule_message(&self) -> &'static str {
+        match self {
+            NameCharset::Ascii => "Takma ad geçersiz. En fazla 30 karakter olmalı ve sadece harf, rakam, boşluk, tire ve alt çizgi içermelidir.",
+            NameCharset::Turkish => "Takma ad geçersiz. En fazla 30 karakter olmalı ve sadece harf (Türkçe karakterler dahil), rakam, boşluk, tire ve alt çizgi içermelidir.",
+            NameCharset::Unicode => "Takma ad geçersiz. En fazla 30 karakter olmalı, sadece harf, rakam, boşluk, tire ve alt çizgi içermeli ve harfler tek bir alfabeden olmalıdır.",
+        }
+    }
+
+    // Ad yalnızca izin verilen harflerden ve verilen noktalama işaretlerinden mi oluşuyor
+    fn allows(&self, name: &str, punctuation: &[char]) -> bool {
+        name.chars().all(|c| punctuation.contains(&c) || self.allows_letter(c))
+            && (*self != NameCharset::Unicode || name.is_single_script())
+    }
+}
+
+impl std::str::FromStr for NameCharset {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "ascii" => Ok(NameCharset::Ascii),
+            "turkish" => Ok(NameCharset::Turkish),
+            "unicode" => Ok(NameCharset::Unicode),
+            _ => Err(format!("Bilinmeyen karakter kümesi: {}", value)),
+        }
+    }
+}
+
+// Adı NFC biçimine getir ve boşlukları düzenle; böylece aynı görünen "Ö" (tek karakter veya O + iki nokta)
+// aynı şekilde saklanır ve karşılaştırılır
+pub fn normalize_name(name: &str) -> String {
+    name.nfc().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Birbirine benzeyen adların (ör. Latin "a" ile Kiril "а", "I" ile "l") aynı sayıldığı karşılaştırma anahtarı.
+// Büyük/küçük harf ve Türkçe I/İ/ı farkı gözetilmez, ardından UTS #39 iskeleti alınır.
+pub fn name_skeleton(name: &str) -> String {
+    let folded: String = normalize_name(name)
+        .chars()
+        .flat_map(|c| match c {
+            'I' | 'İ' | 'ı' => 'i'.to_lowercase(),
+            _ => c.to_lowercase(),
+        })
+        .collect();
+    unicode_security::skeleton(&folded).flat_map(char::to_lowercase).collect()
+}
+
+// Kullanıcı adı kontrolü (ad normalize_name ile düzenlenmiş olmalı)
+pub fn validate_username(username: &str, charset: NameCharset) -> bool {
     // Misafir öneki kontrolü
     if username.starts_with("**") {
         return false;
     }
     
-    USERNAME_REGEX.is_match(username)
+    let length = username.chars().count();
+    (USERNAME_MIN_CHARS..=USERNAME_MAX_CHARS).contains(&length) && charset.allows(username, &['_'])
+}
+
+// Misafir takma adı kontrolü (ad normalize_name ile düzenlenmiş olmalı)
+pub fn validate_nickname(nickname: &str, charset: NameCharset) -> bool {
+    let length = nickname.chars().count();
+    (1..=NICKNAME_MAX_CHARS).contains(&length) && charset.allows(nickname, &['_', '-', ' '])
 }
 
 // Şifre kontrolü
@@ -68,13 +249,52 @@ mod tests {
         assert!(!validate_email("invalid-email"));
     }
     
+    #[test]
+    fn test_domain_matches() {
+        assert!(domain_matches("okul.k12.tr", "okul.k12.tr"));
+        assert!(domain_matches("ogrenci.okul.k12.tr", "okul.k12.tr"));
+        assert!(!domain_matches("kotuokul.k12.tr", "okul.k12.tr"));
+        assert!(!domain_matches("k12.tr", "okul.k12.tr"));
+        assert!(validate_domain("dershane.com.tr"));
+        assert!(!validate_domain(".com"));
+        assert!(!validate_domain("localhost"));
+    }
+    
+    #[test]
+    fn test_email_policy() {
+        assert!(EmailPolicy::EduOnly.allows("a@uni.edu.tr", false));
+        assert!(!EmailPolicy::EduOnly.allows("a@dershane.com", true));
+        assert!(EmailPolicy::EduOrAllowlist.allows("a@dershane.com", true));
+        assert!(!EmailPolicy::Allowlist.allows("a@uni.edu.tr", false));
+        assert!(EmailPolicy::Open.allows("a@gmail.com", false));
+        assert_eq!("open".parse::<EmailPolicy>(), Ok(EmailPolicy::Open));
+        assert!("strict".parse::<EmailPolicy>().is_err());
+    }
+    
     #[test]
     fn test_validate_username() {
-        assert!(validate_username("validuser"));
-        assert!(validate_username("valid_user_123"));
-        assert!(!validate_username("**guest"));
-        assert!(!validate_username("ab")); // too short
-        assert!(!validate_username("invalid username")); // contains space
+        assert!(validate_username("validuser", NameCharset::Ascii));
+        assert!(validate_username("valid_user_123", NameCharset::Ascii));
+        assert!(!validate_username("**guest", NameCharset::Ascii));
+        assert!(!validate_username("ab", NameCharset::Ascii)); // too short
+        assert!(!validate_username("invalid username", NameCharset::Ascii)); // contains space
+        assert!(!validate_username("Öğrenci_Ayşe", NameCharset::Ascii));
+        assert!(validate_username("Öğrenci_Ayşe", NameCharset::Turkish));
+        assert!(validate_username(&normalize_name("O\u{308}ğrenci"), NameCharset::Turkish)); // decomposed Ö
+        assert!(validate_username("Иван_Петров", NameCharset::Unicode));
+        assert!(!validate_username("Иван_Петров", NameCharset::Turkish));
+        assert!(!validate_username("demo_\u{430}yse", NameCharset::Unicode)); // Cyrillic а mixed with Latin
+        assert!(!validate_username("gizli\u{200b}ad", NameCharset::Unicode)); // zero-width space
+    }
+    
+    #[test]
+    fn test_name_skeleton() {
+        assert_eq!(name_skeleton("Ayşe"), name_skeleton("AYŞE"));
+        assert_eq!(name_skeleton("ALİ"), name_skeleton("alı"));
+        assert_eq!(name_skeleton("demo_ayse"), name_skeleton("demo_\u{430}yse"));
+        assert_ne!(name_skeleton("ayse"), name_skeleton("ayşe"));
+        assert!(validate_nickname("Misafir Kaplan", NameCharset::Turkish));
+        assert!(!validate_nickname("", NameCharset::Turkish));
     }
     
     #[test]